@@ -0,0 +1,555 @@
+//! ACP request handling.
+//!
+//! An ACP session is an app-server thread: it lives in the workspace's thread
+//! store, so sessions started from an editor can be continued from the app
+//! server or `cokra mcp serve` and vice versa. Each prompt spawns a
+//! [`cokra_core::Cokra`] runtime on the saved history, like the MCP server
+//! does, so prompts to sessions of the same workspace run one at a time (see
+//! [`cokra_app_server::thread_runner`]).
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use cokra_app_server::thread_runner::ThreadRunner;
+use cokra_app_server::thread_runner::canonical_cwd;
+use cokra_app_server::thread_runner::internal;
+use cokra_app_server::thread_runner::invalid_params;
+use cokra_app_server::thread_runner::model_overrides;
+use cokra_app_server::thread_store::ThreadStore;
+use cokra_app_server::thread_store::transcript;
+use cokra_app_server_protocol::INVALID_PARAMS_ERROR_CODE;
+use cokra_app_server_protocol::INVALID_REQUEST_ERROR_CODE;
+use cokra_app_server_protocol::JSONRPC_VERSION;
+use cokra_app_server_protocol::JsonRpcError;
+use cokra_app_server_protocol::JsonRpcMessage;
+use cokra_app_server_protocol::JsonRpcNotification;
+use cokra_app_server_protocol::JsonRpcRequest;
+use cokra_app_server_protocol::METHOD_NOT_FOUND_ERROR_CODE;
+use cokra_app_server_protocol::PARSE_ERROR_CODE;
+use cokra_app_server_protocol::ThreadSummary;
+use cokra_core::model::ModelClient;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tokio::sync::Mutex;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::connection::ClientConnection;
+use crate::editor_fs::AcpFileSystem;
+use crate::schema::AUTHENTICATE_METHOD;
+use crate::schema::AgentCapabilities;
+use crate::schema::CancelParams;
+use crate::schema::ClientCapabilities;
+use crate::schema::ContentBlock;
+use crate::schema::INITIALIZE_METHOD;
+use crate::schema::InitializeParams;
+use crate::schema::InitializeResponse;
+use crate::schema::LoadSessionParams;
+use crate::schema::NewSessionParams;
+use crate::schema::NewSessionResponse;
+use crate::schema::PROTOCOL_VERSION;
+use crate::schema::PromptCapabilities;
+use crate::schema::PromptParams;
+use crate::schema::PromptResponse;
+use crate::schema::SESSION_CANCEL_METHOD;
+use crate::schema::SESSION_LOAD_METHOD;
+use crate::schema::SESSION_NEW_METHOD;
+use crate::schema::SESSION_PROMPT_METHOD;
+use crate::schema::SessionUpdate;
+use crate::turn::prompt_text;
+use crate::turn::run_prompt;
+use crate::turn::send_update;
+
+struct AcpSession {
+  thread_id: String,
+  cwd: PathBuf,
+  store: Arc<ThreadStore>,
+  /// Set while a prompt is running; `session/cancel` fires it.
+  cancel: std::sync::Mutex<Option<CancellationToken>>,
+}
+
+impl AcpSession {
+  fn set_cancel(&self, token: Option<CancellationToken>) {
+    *self
+      .cancel
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner) = token;
+  }
+
+  fn cancel(&self) {
+    if let Some(token) = self
+      .cancel
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .as_ref()
+    {
+      token.cancel();
+    }
+  }
+}
+
+pub struct AcpAgent {
+  connection: Arc<ClientConnection>,
+  runner: ThreadRunner,
+  client_capabilities: std::sync::Mutex<ClientCapabilities>,
+  sessions: Mutex<HashMap<String, Arc<AcpSession>>>,
+}
+
+impl AcpAgent {
+  pub fn new(outgoing: mpsc::Sender<JsonRpcMessage>, overrides: Vec<(String, String)>) -> Self {
+    Self {
+      connection: Arc::new(ClientConnection::new(outgoing)),
+      runner: ThreadRunner::new(overrides),
+      client_capabilities: std::sync::Mutex::new(ClientCapabilities::default()),
+      sessions: Mutex::new(HashMap::new()),
+    }
+  }
+
+  pub fn with_model_client(mut self, model_client: Arc<ModelClient>) -> Self {
+    self.runner = self.runner.with_model_client(model_client);
+    self
+  }
+
+  /// Handle one line from the client.
+  ///
+  /// Requests run on their own task: a `session/prompt` stays open for the
+  /// whole turn while the client answers permission and file requests.
+  pub async fn process_line(self: &Arc<Self>, line: &str) {
+    let value = match serde_json::from_str::<Value>(line) {
+      Ok(value) => value,
+      Err(err) => {
+        self
+          .connection
+          .send_error(None, JsonRpcError::new(PARSE_ERROR_CODE, err.to_string()))
+          .await;
+        return;
+      }
+    };
+    match serde_json::from_value::<JsonRpcMessage>(value) {
+      Ok(JsonRpcMessage::Request(request)) => {
+        let agent = Arc::clone(self);
+        tokio::spawn(async move { agent.handle_request(request).await });
+      }
+      Ok(JsonRpcMessage::Notification(notification)) => {
+        self.handle_notification(notification).await;
+      }
+      Ok(JsonRpcMessage::Response(response)) => {
+        self.connection.resolve(&response.id, Ok(response.result));
+      }
+      Ok(JsonRpcMessage::Error(error)) => match error.id {
+        Some(id) => self.connection.resolve(&id, Err(error.error)),
+        None => tracing::warn!("client reported an error: {}", error.error.message),
+      },
+      Err(_) => {
+        self
+          .connection
+          .send_error(
+            None,
+            JsonRpcError::new(INVALID_REQUEST_ERROR_CODE, "not a JSON-RPC 2.0 message"),
+          )
+          .await;
+      }
+    }
+  }
+
+  /// Cancel running prompts and fail requests still waiting on the client.
+  pub async fn shutdown(&self) {
+    for session in self.sessions.lock().await.values() {
+      session.cancel();
+    }
+    self.connection.close();
+  }
+
+  async fn handle_request(&self, request: JsonRpcRequest) {
+    let id = request.id;
+    if request.jsonrpc != JSONRPC_VERSION {
+      self
+        .connection
+        .send_error(
+          Some(id),
+          JsonRpcError::new(INVALID_REQUEST_ERROR_CODE, "jsonrpc must be \"2.0\""),
+        )
+        .await;
+      return;
+    }
+    let params = request.params.unwrap_or(Value::Null);
+    let result = match request.method.as_str() {
+      INITIALIZE_METHOD => respond_with(parse_params(params).map(|p| self.initialize(p))),
+      AUTHENTICATE_METHOD => Ok(serde_json::json!({})),
+      SESSION_NEW_METHOD => match parse_params(params) {
+        Ok(params) => respond_with(self.new_session(params).await),
+        Err(err) => Err(err),
+      },
+      SESSION_LOAD_METHOD => match parse_params(params) {
+        Ok(params) => respond_with(self.load_session(params).await),
+        Err(err) => Err(err),
+      },
+      SESSION_PROMPT_METHOD => match parse_params(params) {
+        Ok(params) => respond_with(self.prompt(params).await),
+        Err(err) => Err(err),
+      },
+      other => Err(JsonRpcError::new(
+        METHOD_NOT_FOUND_ERROR_CODE,
+        format!("unknown method: {other}"),
+      )),
+    };
+    self.connection.respond(id, result).await;
+  }
+
+  async fn handle_notification(&self, notification: JsonRpcNotification) {
+    if notification.method != SESSION_CANCEL_METHOD {
+      return;
+    }
+    let Ok(params) = parse_params::<CancelParams>(notification.params.unwrap_or(Value::Null))
+    else {
+      return;
+    };
+    if let Some(session) = self.sessions.lock().await.get(&params.session_id) {
+      session.cancel();
+    }
+  }
+
+  fn initialize(&self, params: InitializeParams) -> InitializeResponse {
+    *self
+      .client_capabilities
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner) = params.client_capabilities;
+    InitializeResponse {
+      protocol_version: params.protocol_version.min(PROTOCOL_VERSION),
+      agent_capabilities: AgentCapabilities {
+        load_session: true,
+        prompt_capabilities: PromptCapabilities {
+          image: false,
+          audio: false,
+          embedded_context: true,
+        },
+      },
+      auth_methods: Vec::new(),
+    }
+  }
+
+  async fn new_session(
+    &self,
+    params: NewSessionParams,
+  ) -> Result<NewSessionResponse, JsonRpcError> {
+    let cwd = resolve_cwd(&params.cwd)?;
+    let store = ThreadStore::open(&cwd).await.map_err(internal)?;
+    let config = self.runner.load_config(&cwd, Vec::new())?;
+    let now = chrono::Utc::now().timestamp();
+    let thread = ThreadSummary {
+      thread_id: uuid::Uuid::new_v4().to_string(),
+      cwd: cwd.clone(),
+      model: config.models.model.clone(),
+      preview: None,
+      created_at: now,
+      updated_at: now,
+      archived: false,
+    };
+    store.insert(&thread).await.map_err(internal)?;
+    self
+      .register_session(thread.thread_id.clone(), cwd, store)
+      .await;
+    Ok(NewSessionResponse {
+      session_id: thread.thread_id,
+    })
+  }
+
+  /// Reopen a saved thread and replay its conversation to the client.
+  async fn load_session(&self, params: LoadSessionParams) -> Result<Value, JsonRpcError> {
+    let cwd = resolve_cwd(&params.cwd)?;
+    let store = ThreadStore::open(&cwd).await.map_err(internal)?;
+    if store
+      .get(&params.session_id)
+      .await
+      .map_err(internal)?
+      .is_none()
+    {
+      return Err(JsonRpcError::new(
+        INVALID_PARAMS_ERROR_CODE,
+        format!("unknown session: {}", params.session_id),
+      ));
+    }
+    let history = store
+      .load_history(&params.session_id)
+      .await
+      .map_err(internal)?;
+    for message in transcript(&history) {
+      let content = ContentBlock::text(message.text);
+      let update = if message.role == "user" {
+        SessionUpdate::UserMessageChunk { content }
+      } else {
+        SessionUpdate::AgentMessageChunk { content }
+      };
+      send_update(&self.connection, &params.session_id, update).await;
+    }
+    self.register_session(params.session_id, cwd, store).await;
+    Ok(Value::Null)
+  }
+
+  async fn prompt(&self, params: PromptParams) -> Result<PromptResponse, JsonRpcError> {
+    let session = self
+      .sessions
+      .lock()
+      .await
+      .get(&params.session_id)
+      .cloned()
+      .ok_or_else(|| {
+        JsonRpcError::new(
+          INVALID_PARAMS_ERROR_CODE,
+          format!("unknown session: {}", params.session_id),
+        )
+      })?;
+    let prompt = prompt_text(&params.prompt);
+    if prompt.trim().is_empty() {
+      return Err(JsonRpcError::new(
+        INVALID_PARAMS_ERROR_CODE,
+        "prompt has no text content",
+      ));
+    }
+    let thread = session
+      .store
+      .get(&session.thread_id)
+      .await
+      .map_err(internal)?
+      .ok_or_else(|| internal(format!("thread {} was removed", session.thread_id)))?;
+
+    let _workspace = self.runner.lock_workspace(&session.cwd).await;
+    let cancel = CancellationToken::new();
+    session.set_cancel(Some(cancel.clone()));
+    let result = self.run_on_thread(&session, &thread, prompt, &cancel).await;
+    session.set_cancel(None);
+    Ok(PromptResponse {
+      stop_reason: result?,
+    })
+  }
+
+  async fn run_on_thread(
+    &self,
+    session: &AcpSession,
+    thread: &ThreadSummary,
+    prompt: String,
+    cancel: &CancellationToken,
+  ) -> Result<crate::schema::StopReason, JsonRpcError> {
+    let config = self
+      .runner
+      .load_config(&session.cwd, model_overrides(&thread.model))?;
+    let capability = self
+      .client_capabilities
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .fs;
+    let editor_fs = AcpFileSystem::new(
+      Arc::clone(&self.connection),
+      thread.thread_id.clone(),
+      capability,
+    );
+    self
+      .runner
+      .run_on_thread(&session.store, thread, config, &prompt, async |cokra| {
+        if let Some(editor_fs) = editor_fs {
+          cokra
+            .set_editor_file_system(Some(Arc::new(editor_fs)))
+            .await;
+        }
+        run_prompt(
+          cokra,
+          &self.connection,
+          &thread.thread_id,
+          prompt.clone(),
+          cancel,
+        )
+        .await
+      })
+      .await
+  }
+
+  async fn register_session(&self, thread_id: String, cwd: PathBuf, store: ThreadStore) {
+    let session = Arc::new(AcpSession {
+      thread_id: thread_id.clone(),
+      cwd,
+      store: Arc::new(store),
+      cancel: std::sync::Mutex::new(None),
+    });
+    self.sessions.lock().await.insert(thread_id, session);
+  }
+}
+
+/// ACP requires absolute session directories.
+fn resolve_cwd(cwd: &Path) -> Result<PathBuf, JsonRpcError> {
+  if !cwd.is_absolute() {
+    return Err(JsonRpcError::new(
+      INVALID_PARAMS_ERROR_CODE,
+      format!("cwd must be absolute: {}", cwd.display()),
+    ));
+  }
+  canonical_cwd(cwd)
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, JsonRpcError> {
+  serde_json::from_value(params).map_err(invalid_params)
+}
+
+fn respond_with<T: Serialize>(result: Result<T, JsonRpcError>) -> Result<Value, JsonRpcError> {
+  result.and_then(|value| serde_json::to_value(value).map_err(internal))
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use cokra_core::test_support::ScriptedProvider;
+  use pretty_assertions::assert_eq;
+  use serde_json::json;
+
+  use super::*;
+
+  struct Harness {
+    agent: Arc<AcpAgent>,
+    outgoing: mpsc::Receiver<JsonRpcMessage>,
+    next_id: i64,
+  }
+
+  impl Harness {
+    async fn new() -> Self {
+      let model_client = ScriptedProvider::replying("mock reply")
+        .into_model_client()
+        .await;
+      let (tx, outgoing) = mpsc::channel(256);
+      let agent =
+        AcpAgent::new(tx, ScriptedProvider::config_overrides()).with_model_client(model_client);
+      Self {
+        agent: Arc::new(agent),
+        outgoing,
+        next_id: 0,
+      }
+    }
+
+    async fn next_message(&mut self) -> JsonRpcMessage {
+      tokio::time::timeout(Duration::from_secs(20), self.outgoing.recv())
+        .await
+        .expect("timed out waiting for agent output")
+        .expect("agent output closed")
+    }
+
+    /// Send a request and return its result plus the notifications that
+    /// arrived before it.
+    async fn request(&mut self, method: &str, params: Value) -> (Value, Vec<JsonRpcNotification>) {
+      self.next_id += 1;
+      let line = json!({
+        "jsonrpc": "2.0",
+        "id": self.next_id,
+        "method": method,
+        "params": params,
+      })
+      .to_string();
+      self.agent.process_line(&line).await;
+      let mut notifications = Vec::new();
+      loop {
+        match self.next_message().await {
+          JsonRpcMessage::Response(response)
+            if response.id == cokra_app_server_protocol::RequestId::Integer(self.next_id) =>
+          {
+            return (response.result, notifications);
+          }
+          JsonRpcMessage::Error(error) => panic!("{method} failed: {}", error.error.message),
+          JsonRpcMessage::Notification(notification) => notifications.push(notification),
+          other => panic!("unexpected message: {other:?}"),
+        }
+      }
+    }
+  }
+
+  fn agent_text(notifications: &[JsonRpcNotification], kind: &str) -> String {
+    notifications
+      .iter()
+      .filter_map(|notification| notification.params.as_ref())
+      .filter(|params| params["update"]["sessionUpdate"] == kind)
+      .filter_map(|params| params["update"]["content"]["text"].as_str())
+      .collect()
+  }
+
+  #[tokio::test]
+  async fn prompt_streams_updates_and_load_replays_history() {
+    let workspace = tempfile::tempdir().expect("tempdir");
+    let cwd = std::fs::canonicalize(workspace.path()).expect("canonical cwd");
+    let mut harness = Harness::new().await;
+
+    let (init, _) = harness
+      .request(
+        "initialize",
+        json!({ "protocolVersion": 1, "clientCapabilities": {} }),
+      )
+      .await;
+    assert_eq!(init["protocolVersion"], 1);
+    assert_eq!(init["agentCapabilities"]["loadSession"], true);
+
+    let (session, _) = harness
+      .request("session/new", json!({ "cwd": cwd, "mcpServers": [] }))
+      .await;
+    let session_id = session["sessionId"]
+      .as_str()
+      .expect("session id")
+      .to_string();
+
+    let (prompted, updates) = harness
+      .request(
+        "session/prompt",
+        json!({ "sessionId": session_id, "prompt": [{ "type": "text", "text": "say hi" }] }),
+      )
+      .await;
+    assert_eq!(prompted, json!({ "stopReason": "end_turn" }));
+    assert_eq!(agent_text(&updates, "agent_message_chunk"), "mock reply");
+
+    let (_, replayed) = harness
+      .request(
+        "session/load",
+        json!({ "sessionId": session_id, "cwd": cwd, "mcpServers": [] }),
+      )
+      .await;
+    assert_eq!(agent_text(&replayed, "user_message_chunk"), "say hi");
+    assert_eq!(agent_text(&replayed, "agent_message_chunk"), "mock reply");
+
+    let store = ThreadStore::open(&cwd).await.expect("store");
+    let thread = store.get(&session_id).await.expect("get").expect("thread");
+    assert_eq!(thread.preview.as_deref(), Some("say hi"));
+  }
+
+  #[tokio::test]
+  async fn editor_reads_go_through_the_client() {
+    let mut harness = Harness::new().await;
+    let editor_fs = AcpFileSystem::new(
+      Arc::clone(&harness.agent.connection),
+      "session".to_string(),
+      crate::schema::FileSystemCapability {
+        read_text_file: true,
+        write_text_file: false,
+      },
+    )
+    .expect("editor fs");
+    let read = tokio::spawn(async move {
+      cokra_core::EditorFileSystem::read_text_file(&editor_fs, Path::new("/repo/main.rs")).await
+    });
+
+    let JsonRpcMessage::Request(request) = harness.next_message().await else {
+      panic!("expected a request to the client");
+    };
+    assert_eq!(request.method, "fs/read_text_file");
+    assert_eq!(
+      request.params,
+      Some(json!({ "sessionId": "session", "path": "/repo/main.rs" }))
+    );
+    let response = json!({
+      "jsonrpc": "2.0",
+      "id": request.id,
+      "result": { "content": "unsaved buffer" },
+    });
+    harness.agent.process_line(&response.to_string()).await;
+    assert_eq!(
+      read.await.expect("join").expect("read"),
+      "unsaved buffer".to_string()
+    );
+  }
+}
@@ -0,0 +1,137 @@
+//! Outgoing half of the ACP connection: responses, notifications, and
+//! requests the agent makes of the editor (permissions, file buffers).
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicI64;
+use std::sync::atomic::Ordering;
+
+use cokra_app_server_protocol::JSONRPC_VERSION;
+use cokra_app_server_protocol::JsonRpcError;
+use cokra_app_server_protocol::JsonRpcErrorResponse;
+use cokra_app_server_protocol::JsonRpcMessage;
+use cokra_app_server_protocol::JsonRpcNotification;
+use cokra_app_server_protocol::JsonRpcRequest;
+use cokra_app_server_protocol::JsonRpcResponse;
+use cokra_app_server_protocol::RequestId;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+
+type PendingResponse = oneshot::Sender<Result<Value, JsonRpcError>>;
+
+pub(crate) struct ClientConnection {
+  outgoing: mpsc::Sender<JsonRpcMessage>,
+  next_request_id: AtomicI64,
+  pending: Mutex<HashMap<RequestId, PendingResponse>>,
+}
+
+impl ClientConnection {
+  pub(crate) fn new(outgoing: mpsc::Sender<JsonRpcMessage>) -> Self {
+    Self {
+      outgoing,
+      next_request_id: AtomicI64::new(0),
+      pending: Mutex::new(HashMap::new()),
+    }
+  }
+
+  /// Call `method` on the client and wait for its answer.
+  pub(crate) async fn request<T: DeserializeOwned>(
+    &self,
+    method: &str,
+    params: impl Serialize,
+  ) -> anyhow::Result<T> {
+    let id = RequestId::Integer(self.next_request_id.fetch_add(1, Ordering::Relaxed));
+    let (tx, rx) = oneshot::channel();
+    self.pending_map().insert(id.clone(), tx);
+    let request = JsonRpcRequest {
+      jsonrpc: JSONRPC_VERSION.to_string(),
+      id: id.clone(),
+      method: method.to_string(),
+      params: Some(serde_json::to_value(params)?),
+    };
+    if self
+      .outgoing
+      .send(JsonRpcMessage::Request(request))
+      .await
+      .is_err()
+    {
+      self.pending_map().remove(&id);
+      anyhow::bail!("connection closed");
+    }
+    match rx.await {
+      Ok(Ok(result)) => Ok(serde_json::from_value(result)?),
+      Ok(Err(error)) => anyhow::bail!("{method} failed: {}", error.message),
+      Err(_) => anyhow::bail!("connection closed before {method} was answered"),
+    }
+  }
+
+  /// Route a client response to the [`Self::request`] waiting on it.
+  pub(crate) fn resolve(&self, id: &RequestId, result: Result<Value, JsonRpcError>) {
+    match self.pending_map().remove(id) {
+      Some(tx) => {
+        let _ = tx.send(result);
+      }
+      None => tracing::debug!("dropping response to unknown request {id:?}"),
+    }
+  }
+
+  /// Fail every outstanding request, e.g. when the client disconnects.
+  pub(crate) fn close(&self) {
+    self.pending_map().clear();
+  }
+
+  pub(crate) async fn notify(&self, method: &str, params: impl Serialize) {
+    let params = match serde_json::to_value(params) {
+      Ok(params) => params,
+      Err(err) => {
+        tracing::warn!("failed to serialize {method} notification: {err}");
+        return;
+      }
+    };
+    let _ = self
+      .outgoing
+      .send(JsonRpcMessage::Notification(JsonRpcNotification {
+        jsonrpc: JSONRPC_VERSION.to_string(),
+        method: method.to_string(),
+        params: Some(params),
+      }))
+      .await;
+  }
+
+  pub(crate) async fn respond(&self, id: RequestId, result: Result<Value, JsonRpcError>) {
+    let message = match result {
+      Ok(result) => JsonRpcMessage::Response(JsonRpcResponse {
+        jsonrpc: JSONRPC_VERSION.to_string(),
+        id,
+        result,
+      }),
+      Err(error) => JsonRpcMessage::Error(JsonRpcErrorResponse {
+        jsonrpc: JSONRPC_VERSION.to_string(),
+        id: Some(id),
+        error,
+      }),
+    };
+    let _ = self.outgoing.send(message).await;
+  }
+
+  pub(crate) async fn send_error(&self, id: Option<RequestId>, error: JsonRpcError) {
+    let _ = self
+      .outgoing
+      .send(JsonRpcMessage::Error(JsonRpcErrorResponse {
+        jsonrpc: JSONRPC_VERSION.to_string(),
+        id,
+        error,
+      }))
+      .await;
+  }
+
+  fn pending_map(&self) -> std::sync::MutexGuard<'_, HashMap<RequestId, PendingResponse>> {
+    self
+      .pending
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+  }
+}
@@ -0,0 +1,76 @@
+//! `read_file`/`write_file` backed by the editor's buffers.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use cokra_core::EditorFileSystem;
+
+use crate::connection::ClientConnection;
+use crate::schema::FileSystemCapability;
+use crate::schema::READ_TEXT_FILE_METHOD;
+use crate::schema::ReadTextFileParams;
+use crate::schema::ReadTextFileResponse;
+use crate::schema::WRITE_TEXT_FILE_METHOD;
+use crate::schema::WriteTextFileParams;
+
+/// Forwards file access to the client for the capabilities it advertised and
+/// falls back to disk for the rest.
+pub(crate) struct AcpFileSystem {
+  connection: Arc<ClientConnection>,
+  session_id: String,
+  capability: FileSystemCapability,
+}
+
+impl AcpFileSystem {
+  /// `None` when the client serves neither reads nor writes.
+  pub(crate) fn new(
+    connection: Arc<ClientConnection>,
+    session_id: String,
+    capability: FileSystemCapability,
+  ) -> Option<Self> {
+    (capability.read_text_file || capability.write_text_file).then_some(Self {
+      connection,
+      session_id,
+      capability,
+    })
+  }
+}
+
+#[async_trait]
+impl EditorFileSystem for AcpFileSystem {
+  async fn read_text_file(&self, path: &Path) -> anyhow::Result<String> {
+    if !self.capability.read_text_file {
+      return Ok(tokio::fs::read_to_string(path).await?);
+    }
+    let response: ReadTextFileResponse = self
+      .connection
+      .request(
+        READ_TEXT_FILE_METHOD,
+        ReadTextFileParams {
+          session_id: self.session_id.clone(),
+          path: path.to_path_buf(),
+        },
+      )
+      .await?;
+    Ok(response.content)
+  }
+
+  async fn write_text_file(&self, path: &Path, content: &str) -> anyhow::Result<()> {
+    if !self.capability.write_text_file {
+      return Ok(tokio::fs::write(path, content).await?);
+    }
+    self
+      .connection
+      .request::<serde_json::Value>(
+        WRITE_TEXT_FILE_METHOD,
+        WriteTextFileParams {
+          session_id: self.session_id.clone(),
+          path: path.to_path_buf(),
+          content: content.to_string(),
+        },
+      )
+      .await?;
+    Ok(())
+  }
+}
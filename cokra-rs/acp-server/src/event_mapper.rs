@@ -0,0 +1,273 @@
+//! Translate Cokra events into ACP `session/update` payloads.
+
+use cokra_core::tools::CONTAINER_EXEC_TOOL_ALIAS;
+use cokra_core::tools::LOCAL_SHELL_TOOL_ALIAS;
+use cokra_core::tools::SHELL_TOOL_NAME;
+use cokra_core::tools::UNIFIED_EXEC_TOOL_NAME;
+use cokra_protocol::EventMsg;
+use cokra_protocol::McpToolCallResult;
+use cokra_protocol::StepStatus;
+
+use crate::schema::ContentBlock;
+use crate::schema::PlanEntry;
+use crate::schema::SessionUpdate;
+use crate::schema::ToolCall;
+use crate::schema::ToolCallContent;
+use crate::schema::ToolCallLocation;
+use crate::schema::ToolCallStatus;
+use crate::schema::ToolCallUpdate;
+use crate::schema::ToolKind;
+
+/// Per-turn mapping state.
+///
+/// Streaming providers send deltas followed by the full message; the full
+/// message is only forwarded when nothing was streamed, so editors do not
+/// render the answer twice.
+#[derive(Debug, Default)]
+pub(crate) struct EventMapper {
+  streamed_message: bool,
+  streamed_thought: bool,
+}
+
+impl EventMapper {
+  pub(crate) fn map(&mut self, msg: &EventMsg) -> Option<SessionUpdate> {
+    match msg {
+      EventMsg::AgentMessageDelta(delta) | EventMsg::AgentMessageContentDelta(delta) => {
+        self.streamed_message = true;
+        Some(SessionUpdate::AgentMessageChunk {
+          content: ContentBlock::text(delta.delta.clone()),
+        })
+      }
+      EventMsg::AgentMessage(message) => {
+        if std::mem::take(&mut self.streamed_message) {
+          return None;
+        }
+        let text = message
+          .content
+          .iter()
+          .map(|part| match part {
+            cokra_protocol::AgentMessageContent::Text { text } => text.as_str(),
+          })
+          .collect::<String>();
+        (!text.is_empty()).then(|| SessionUpdate::AgentMessageChunk {
+          content: ContentBlock::text(text),
+        })
+      }
+      EventMsg::AgentReasoningDelta(delta) => {
+        self.streamed_thought = true;
+        Some(SessionUpdate::AgentThoughtChunk {
+          content: ContentBlock::text(delta.delta.clone()),
+        })
+      }
+      EventMsg::AgentReasoning(reasoning) => {
+        if std::mem::take(&mut self.streamed_thought) || reasoning.text.is_empty() {
+          return None;
+        }
+        Some(SessionUpdate::AgentThoughtChunk {
+          content: ContentBlock::text(reasoning.text.clone()),
+        })
+      }
+      EventMsg::ExecCommandBegin(begin) => Some(SessionUpdate::ToolCall(ToolCall {
+        tool_call_id: begin.command_id.clone(),
+        title: begin.command.clone(),
+        kind: tool_kind(&begin.tool_name),
+        status: ToolCallStatus::InProgress,
+        locations: Vec::new(),
+        raw_input: None,
+      })),
+      EventMsg::ExecCommandEnd(end) => Some(SessionUpdate::ToolCallUpdate(ToolCallUpdate {
+        tool_call_id: end.command_id.clone(),
+        status: Some(if end.exit_code == 0 {
+          ToolCallStatus::Completed
+        } else {
+          ToolCallStatus::Failed
+        }),
+        content: text_content(&end.output),
+      })),
+      EventMsg::PatchApplyBegin(begin) => {
+        let mut paths = begin.changes.keys().cloned().collect::<Vec<_>>();
+        paths.sort();
+        let locations = paths
+          .into_iter()
+          .map(|path| ToolCallLocation { path })
+          .collect();
+        Some(SessionUpdate::ToolCall(ToolCall {
+          tool_call_id: begin.call_id.clone(),
+          title: format!("Edit {} file(s)", begin.changes.len()),
+          kind: ToolKind::Edit,
+          status: ToolCallStatus::InProgress,
+          locations,
+          raw_input: None,
+        }))
+      }
+      EventMsg::PatchApplyEnd(end) => Some(SessionUpdate::ToolCallUpdate(ToolCallUpdate {
+        tool_call_id: end.call_id.clone(),
+        status: Some(if end.success {
+          ToolCallStatus::Completed
+        } else {
+          ToolCallStatus::Failed
+        }),
+        content: text_content(if end.success {
+          &end.stdout
+        } else {
+          &end.stderr
+        }),
+      })),
+      EventMsg::McpToolCallBegin(begin) => Some(SessionUpdate::ToolCall(ToolCall {
+        tool_call_id: begin.call_id.clone(),
+        title: format!("{}.{}", begin.invocation.server, begin.invocation.tool),
+        kind: ToolKind::Other,
+        status: ToolCallStatus::InProgress,
+        locations: Vec::new(),
+        raw_input: begin.invocation.arguments.clone(),
+      })),
+      EventMsg::McpToolCallEnd(end) => {
+        let (status, text) = match &end.result {
+          McpToolCallResult::Ok { content } => (
+            ToolCallStatus::Completed,
+            content
+              .iter()
+              .filter_map(|block| match block {
+                cokra_protocol::McpContentBlock::Text { text } => Some(text.as_str()),
+                _ => None,
+              })
+              .collect::<Vec<_>>()
+              .join("\n"),
+          ),
+          McpToolCallResult::Err(error) => (ToolCallStatus::Failed, error.clone()),
+        };
+        Some(SessionUpdate::ToolCallUpdate(ToolCallUpdate {
+          tool_call_id: end.call_id.clone(),
+          status: Some(status),
+          content: text_content(&text),
+        }))
+      }
+      EventMsg::PlanUpdate(plan) => Some(SessionUpdate::Plan {
+        entries: plan
+          .plan
+          .iter()
+          .map(|item| PlanEntry {
+            content: item.step.clone(),
+            priority: "medium",
+            status: match item.status {
+              StepStatus::Pending => "pending",
+              StepStatus::InProgress => "in_progress",
+              StepStatus::Completed => "completed",
+            },
+          })
+          .collect(),
+      }),
+      _ => None,
+    }
+  }
+}
+
+/// ACP kind for a Cokra tool, which editors use to pick an icon.
+pub(crate) fn tool_kind(tool_name: &str) -> ToolKind {
+  match tool_name {
+    "read_file" | "read_many_files" | "list_dir" => ToolKind::Read,
+    "grep_files" | "glob" | "code_search" => ToolKind::Search,
+    "write_file" | "edit_file" | "apply_patch" => ToolKind::Edit,
+    SHELL_TOOL_NAME
+    | UNIFIED_EXEC_TOOL_NAME
+    | LOCAL_SHELL_TOOL_ALIAS
+    | CONTAINER_EXEC_TOOL_ALIAS => ToolKind::Execute,
+    _ => ToolKind::Other,
+  }
+}
+
+fn text_content(text: &str) -> Vec<ToolCallContent> {
+  if text.is_empty() {
+    return Vec::new();
+  }
+  vec![ToolCallContent::Content {
+    content: ContentBlock::text(text),
+  }]
+}
+
+#[cfg(test)]
+mod tests {
+  use cokra_protocol::AgentMessageContent;
+  use cokra_protocol::AgentMessageDeltaEvent;
+  use cokra_protocol::AgentMessageEvent;
+  use cokra_protocol::ExecCommandBeginEvent;
+  use cokra_protocol::ExecCommandEndEvent;
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  #[test]
+  fn streamed_message_is_not_repeated() {
+    let mut mapper = EventMapper::default();
+    let delta = mapper.map(&EventMsg::AgentMessageContentDelta(
+      AgentMessageDeltaEvent {
+        thread_id: "t".to_string(),
+        turn_id: "turn".to_string(),
+        item_id: "item".to_string(),
+        delta: "hel".to_string(),
+      },
+    ));
+    assert_eq!(
+      delta,
+      Some(SessionUpdate::AgentMessageChunk {
+        content: ContentBlock::text("hel")
+      })
+    );
+    let full = EventMsg::AgentMessage(AgentMessageEvent {
+      thread_id: "t".to_string(),
+      turn_id: "turn".to_string(),
+      item_id: "item".to_string(),
+      content: vec![AgentMessageContent::Text {
+        text: "hello".to_string(),
+      }],
+    });
+    assert_eq!(mapper.map(&full), None);
+    assert_eq!(
+      mapper.map(&full),
+      Some(SessionUpdate::AgentMessageChunk {
+        content: ContentBlock::text("hello")
+      })
+    );
+  }
+
+  #[test]
+  fn exec_events_become_tool_calls() {
+    let mut mapper = EventMapper::default();
+    let begin = mapper.map(&EventMsg::ExecCommandBegin(ExecCommandBeginEvent {
+      thread_id: "t".to_string(),
+      turn_id: "turn".to_string(),
+      command_id: "call-1".to_string(),
+      tool_name: "shell".to_string(),
+      command: "cargo test".to_string(),
+      cwd: "/repo".into(),
+    }));
+    assert_eq!(
+      begin,
+      Some(SessionUpdate::ToolCall(ToolCall {
+        tool_call_id: "call-1".to_string(),
+        title: "cargo test".to_string(),
+        kind: ToolKind::Execute,
+        status: ToolCallStatus::InProgress,
+        locations: Vec::new(),
+        raw_input: None,
+      }))
+    );
+    let end = mapper.map(&EventMsg::ExecCommandEnd(ExecCommandEndEvent {
+      thread_id: "t".to_string(),
+      turn_id: "turn".to_string(),
+      command_id: "call-1".to_string(),
+      exit_code: 1,
+      output: "1 failed".to_string(),
+    }));
+    assert_eq!(
+      end,
+      Some(SessionUpdate::ToolCallUpdate(ToolCallUpdate {
+        tool_call_id: "call-1".to_string(),
+        status: Some(ToolCallStatus::Failed),
+        content: vec![ToolCallContent::Content {
+          content: ContentBlock::text("1 failed")
+        }],
+      }))
+    );
+  }
+}
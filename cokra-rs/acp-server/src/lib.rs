@@ -0,0 +1,52 @@
+// Cokra ACP Server
+// Agent Client Protocol over stdio for editors that host external agents
+
+mod agent;
+mod connection;
+mod editor_fs;
+mod event_mapper;
+mod schema;
+mod turn;
+
+pub use agent::AcpAgent;
+
+use std::sync::Arc;
+
+use cokra_app_server_protocol::JsonRpcMessage;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::io::BufReader;
+use tokio::sync::mpsc;
+
+const OUTGOING_CHANNEL_CAPACITY: usize = 1024;
+
+/// Serve ACP on stdin/stdout until stdin closes.
+///
+/// `overrides` are the `-c key=value` pairs applied on top of every loaded
+/// config; session directories come from the client.
+pub async fn run_main(overrides: Vec<(String, String)>) -> anyhow::Result<()> {
+  let (outgoing, mut outgoing_rx) = mpsc::channel::<JsonRpcMessage>(OUTGOING_CHANNEL_CAPACITY);
+  let writer = tokio::spawn(async move {
+    let mut stdout = tokio::io::stdout();
+    while let Some(message) = outgoing_rx.recv().await {
+      let mut line = serde_json::to_vec(&message)?;
+      line.push(b'\n');
+      stdout.write_all(&line).await?;
+      stdout.flush().await?;
+    }
+    anyhow::Ok(())
+  });
+
+  let agent = Arc::new(AcpAgent::new(outgoing, overrides));
+  let mut lines = BufReader::new(tokio::io::stdin()).lines();
+  while let Some(line) = lines.next_line().await? {
+    if line.trim().is_empty() {
+      continue;
+    }
+    agent.process_line(&line).await;
+  }
+
+  agent.shutdown().await;
+  drop(agent);
+  writer.await?
+}
@@ -0,0 +1,281 @@
+//! The subset of the Agent Client Protocol Cokra speaks.
+//!
+//! Field names follow the ACP JSON schema (camelCase); unknown fields sent by
+//! newer clients are ignored.
+
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+
+pub(crate) const PROTOCOL_VERSION: u16 = 1;
+
+// Client → agent.
+pub(crate) const INITIALIZE_METHOD: &str = "initialize";
+pub(crate) const AUTHENTICATE_METHOD: &str = "authenticate";
+pub(crate) const SESSION_NEW_METHOD: &str = "session/new";
+pub(crate) const SESSION_LOAD_METHOD: &str = "session/load";
+pub(crate) const SESSION_PROMPT_METHOD: &str = "session/prompt";
+pub(crate) const SESSION_CANCEL_METHOD: &str = "session/cancel";
+
+// Agent → client.
+pub(crate) const SESSION_UPDATE_METHOD: &str = "session/update";
+pub(crate) const REQUEST_PERMISSION_METHOD: &str = "session/request_permission";
+pub(crate) const READ_TEXT_FILE_METHOD: &str = "fs/read_text_file";
+pub(crate) const WRITE_TEXT_FILE_METHOD: &str = "fs/write_text_file";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct InitializeParams {
+  #[serde(default)]
+  pub(crate) protocol_version: u16,
+  #[serde(default)]
+  pub(crate) client_capabilities: ClientCapabilities,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ClientCapabilities {
+  #[serde(default)]
+  pub(crate) fs: FileSystemCapability,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FileSystemCapability {
+  #[serde(default)]
+  pub(crate) read_text_file: bool,
+  #[serde(default)]
+  pub(crate) write_text_file: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct InitializeResponse {
+  pub(crate) protocol_version: u16,
+  pub(crate) agent_capabilities: AgentCapabilities,
+  pub(crate) auth_methods: Vec<Value>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AgentCapabilities {
+  pub(crate) load_session: bool,
+  pub(crate) prompt_capabilities: PromptCapabilities,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PromptCapabilities {
+  pub(crate) image: bool,
+  pub(crate) audio: bool,
+  pub(crate) embedded_context: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct NewSessionParams {
+  pub(crate) cwd: PathBuf,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct NewSessionResponse {
+  pub(crate) session_id: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LoadSessionParams {
+  pub(crate) session_id: String,
+  pub(crate) cwd: PathBuf,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PromptParams {
+  pub(crate) session_id: String,
+  pub(crate) prompt: Vec<ContentBlock>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PromptResponse {
+  pub(crate) stop_reason: StopReason,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum StopReason {
+  EndTurn,
+  Cancelled,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CancelParams {
+  pub(crate) session_id: String,
+}
+
+/// Prompt and message content; kinds Cokra cannot use are kept as `Other`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum ContentBlock {
+  Text {
+    text: String,
+  },
+  ResourceLink {
+    uri: String,
+    #[serde(default)]
+    name: String,
+  },
+  Resource {
+    resource: EmbeddedResource,
+  },
+  #[serde(other)]
+  Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EmbeddedResource {
+  pub(crate) uri: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub(crate) text: Option<String>,
+}
+
+impl ContentBlock {
+  pub(crate) fn text(text: impl Into<String>) -> Self {
+    Self::Text { text: text.into() }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SessionNotification {
+  pub(crate) session_id: String,
+  pub(crate) update: SessionUpdate,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "sessionUpdate", rename_all = "snake_case")]
+pub(crate) enum SessionUpdate {
+  UserMessageChunk { content: ContentBlock },
+  AgentMessageChunk { content: ContentBlock },
+  AgentThoughtChunk { content: ContentBlock },
+  ToolCall(ToolCall),
+  ToolCallUpdate(ToolCallUpdate),
+  Plan { entries: Vec<PlanEntry> },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ToolCall {
+  pub(crate) tool_call_id: String,
+  pub(crate) title: String,
+  pub(crate) kind: ToolKind,
+  pub(crate) status: ToolCallStatus,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub(crate) locations: Vec<ToolCallLocation>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub(crate) raw_input: Option<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ToolCallUpdate {
+  pub(crate) tool_call_id: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub(crate) status: Option<ToolCallStatus>,
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  pub(crate) content: Vec<ToolCallContent>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ToolKind {
+  Read,
+  Edit,
+  Search,
+  Execute,
+  Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ToolCallStatus {
+  Pending,
+  InProgress,
+  Completed,
+  Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum ToolCallContent {
+  Content { content: ContentBlock },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct ToolCallLocation {
+  pub(crate) path: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct PlanEntry {
+  pub(crate) content: String,
+  pub(crate) priority: &'static str,
+  pub(crate) status: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RequestPermissionParams {
+  pub(crate) session_id: String,
+  pub(crate) tool_call: ToolCall,
+  pub(crate) options: Vec<PermissionOption>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PermissionOption {
+  pub(crate) option_id: &'static str,
+  pub(crate) name: &'static str,
+  pub(crate) kind: &'static str,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct RequestPermissionResponse {
+  pub(crate) outcome: PermissionOutcome,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub(crate) enum PermissionOutcome {
+  Cancelled,
+  #[serde(rename_all = "camelCase")]
+  Selected {
+    option_id: String,
+  },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReadTextFileParams {
+  pub(crate) session_id: String,
+  pub(crate) path: PathBuf,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct ReadTextFileResponse {
+  pub(crate) content: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WriteTextFileParams {
+  pub(crate) session_id: String,
+  pub(crate) path: PathBuf,
+  pub(crate) content: String,
+}
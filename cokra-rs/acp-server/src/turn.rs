@@ -0,0 +1,280 @@
+//! Drive one Cokra turn for a `session/prompt` request.
+
+use cokra_core::Cokra;
+use cokra_protocol::CompletionStatus;
+use cokra_protocol::ElicitationAction;
+use cokra_protocol::EventMsg;
+use cokra_protocol::ExecApprovalRequestEvent;
+use cokra_protocol::Op;
+use cokra_protocol::RequestUserInputResponse;
+use cokra_protocol::ReviewDecision;
+use cokra_protocol::UserInput;
+use tokio_util::sync::CancellationToken;
+
+use crate::connection::ClientConnection;
+use crate::event_mapper::EventMapper;
+use crate::event_mapper::tool_kind;
+use crate::schema::ContentBlock;
+use crate::schema::PermissionOption;
+use crate::schema::PermissionOutcome;
+use crate::schema::REQUEST_PERMISSION_METHOD;
+use crate::schema::RequestPermissionParams;
+use crate::schema::RequestPermissionResponse;
+use crate::schema::SESSION_UPDATE_METHOD;
+use crate::schema::SessionNotification;
+use crate::schema::SessionUpdate;
+use crate::schema::StopReason;
+use crate::schema::ToolCall;
+use crate::schema::ToolCallStatus;
+
+const ALLOW_ONCE_OPTION: &str = "allow_once";
+const ALLOW_ALWAYS_OPTION: &str = "allow_always";
+const REJECT_ONCE_OPTION: &str = "reject_once";
+
+/// Flatten ACP prompt blocks into the text Cokra submits.
+///
+/// Tradeoff: Cokra takes text prompts only, so images and audio the client
+/// attaches are dropped; the agent advertises neither capability.
+pub(crate) fn prompt_text(blocks: &[ContentBlock]) -> String {
+  blocks
+    .iter()
+    .filter_map(|block| match block {
+      ContentBlock::Text { text } => Some(text.clone()),
+      ContentBlock::ResourceLink { uri, name } if name.is_empty() => Some(uri.clone()),
+      ContentBlock::ResourceLink { uri, name } => Some(format!("[{name}]({uri})")),
+      ContentBlock::Resource { resource } => Some(match &resource.text {
+        Some(text) => format!("Context from {}:\n```\n{text}\n```", resource.uri),
+        None => resource.uri.clone(),
+      }),
+      ContentBlock::Other => None,
+    })
+    .collect::<Vec<_>>()
+    .join("\n\n")
+}
+
+pub(crate) async fn send_update(
+  connection: &ClientConnection,
+  session_id: &str,
+  update: SessionUpdate,
+) {
+  connection
+    .notify(
+      SESSION_UPDATE_METHOD,
+      SessionNotification {
+        session_id: session_id.to_string(),
+        update,
+      },
+    )
+    .await;
+}
+
+/// Submit `prompt` and stream the turn to the client until it settles.
+pub(crate) async fn run_prompt(
+  cokra: &Cokra,
+  connection: &ClientConnection,
+  session_id: &str,
+  prompt: String,
+  cancel: &CancellationToken,
+) -> anyhow::Result<StopReason> {
+  cokra
+    .submit(Op::UserInput {
+      items: vec![UserInput::Text {
+        text: prompt,
+        text_elements: Vec::new(),
+      }],
+      final_output_json_schema: None,
+    })
+    .await?;
+
+  let mut mapper = EventMapper::default();
+  let mut last_error = None;
+  let mut interrupted = false;
+  loop {
+    let event = tokio::select! {
+      event = cokra.next_event() => event?,
+      _ = cancel.cancelled(), if !interrupted => {
+        interrupted = true;
+        cokra.submit(Op::Interrupt).await?;
+        continue;
+      }
+    };
+    if let Some(update) = mapper.map(&event.msg) {
+      send_update(connection, session_id, update).await;
+    }
+    match event.msg {
+      EventMsg::Error(error) => last_error = Some(error.user_facing_message),
+      EventMsg::ExecApprovalRequest(request) => {
+        let decision = if interrupted {
+          ReviewDecision::Denied
+        } else {
+          request_permission(connection, session_id, &request).await
+        };
+        cokra
+          .submit(Op::ExecApproval {
+            id: request.id,
+            turn_id: Some(request.turn_id),
+            decision,
+          })
+          .await?;
+      }
+      // Tradeoff: ACP has no free-form question flow, so the model gets an
+      // empty answer and proceeds with its own judgement.
+      EventMsg::RequestUserInput(request) => {
+        cokra
+          .submit(Op::UserInputAnswer {
+            id: request.call_id,
+            response: RequestUserInputResponse::default(),
+          })
+          .await?;
+      }
+      // ACP has no form prompt either, so MCP elicitations are declined.
+      EventMsg::ElicitationRequest(request) => {
+        cokra
+          .submit(Op::ResolveElicitation {
+            server_name: request.server_name,
+            request_id: request.id,
+            decision: ElicitationAction::Decline,
+            content: None,
+          })
+          .await?;
+      }
+      EventMsg::TurnComplete(complete) => {
+        if interrupted {
+          return Ok(StopReason::Cancelled);
+        }
+        return match complete.status {
+          CompletionStatus::Errored {
+            user_facing_message,
+            ..
+          } => Err(anyhow::anyhow!(user_facing_message)),
+          CompletionStatus::Success => Ok(StopReason::EndTurn),
+        };
+      }
+      EventMsg::TurnAborted(aborted) => {
+        if interrupted {
+          return Ok(StopReason::Cancelled);
+        }
+        return Err(anyhow::anyhow!(
+          last_error.unwrap_or_else(|| format!("turn aborted: {}", aborted.reason))
+        ));
+      }
+      EventMsg::ShutdownComplete => {
+        anyhow::bail!("Cokra shut down before the turn finished");
+      }
+      _ => {}
+    }
+  }
+}
+
+async fn request_permission(
+  connection: &ClientConnection,
+  session_id: &str,
+  request: &ExecApprovalRequestEvent,
+) -> ReviewDecision {
+  let params = RequestPermissionParams {
+    session_id: session_id.to_string(),
+    tool_call: ToolCall {
+      tool_call_id: request.id.clone(),
+      title: request.command.clone(),
+      kind: tool_kind(&request.tool_name),
+      status: ToolCallStatus::Pending,
+      locations: Vec::new(),
+      raw_input: Some(serde_json::json!({
+        "command": request.command,
+        "cwd": request.cwd,
+      })),
+    },
+    options: vec![
+      PermissionOption {
+        option_id: ALLOW_ONCE_OPTION,
+        name: "Allow",
+        kind: "allow_once",
+      },
+      PermissionOption {
+        option_id: ALLOW_ALWAYS_OPTION,
+        name: "Always allow",
+        kind: "allow_always",
+      },
+      PermissionOption {
+        option_id: REJECT_ONCE_OPTION,
+        name: "Reject",
+        kind: "reject_once",
+      },
+    ],
+  };
+  match connection
+    .request::<RequestPermissionResponse>(REQUEST_PERMISSION_METHOD, params)
+    .await
+  {
+    Ok(response) => decision_from_outcome(&response.outcome),
+    Err(err) => {
+      tracing::warn!("permission request failed: {err:#}");
+      ReviewDecision::Denied
+    }
+  }
+}
+
+pub(crate) fn decision_from_outcome(outcome: &PermissionOutcome) -> ReviewDecision {
+  match outcome {
+    PermissionOutcome::Selected { option_id } if option_id == ALLOW_ONCE_OPTION => {
+      ReviewDecision::Approved
+    }
+    PermissionOutcome::Selected { option_id } if option_id == ALLOW_ALWAYS_OPTION => {
+      ReviewDecision::Always
+    }
+    PermissionOutcome::Selected { .. } | PermissionOutcome::Cancelled => ReviewDecision::Denied,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+  use crate::schema::EmbeddedResource;
+
+  #[test]
+  fn prompt_blocks_flatten_to_text() {
+    let text = prompt_text(&[
+      ContentBlock::text("explain"),
+      ContentBlock::ResourceLink {
+        uri: "file:///repo/src/lib.rs".to_string(),
+        name: "lib.rs".to_string(),
+      },
+      ContentBlock::Resource {
+        resource: EmbeddedResource {
+          uri: "file:///repo/notes.md".to_string(),
+          text: Some("todo".to_string()),
+        },
+      },
+      ContentBlock::Other,
+    ]);
+    assert_eq!(
+      text,
+      "explain\n\n[lib.rs](file:///repo/src/lib.rs)\n\nContext from file:///repo/notes.md:\n```\ntodo\n```"
+    );
+  }
+
+  #[test]
+  fn permission_outcome_maps_to_review_decision() {
+    let selected = |option_id: &str| PermissionOutcome::Selected {
+      option_id: option_id.to_string(),
+    };
+    assert!(matches!(
+      decision_from_outcome(&selected(ALLOW_ONCE_OPTION)),
+      ReviewDecision::Approved
+    ));
+    assert!(matches!(
+      decision_from_outcome(&selected(ALLOW_ALWAYS_OPTION)),
+      ReviewDecision::Always
+    ));
+    assert!(matches!(
+      decision_from_outcome(&selected(REJECT_ONCE_OPTION)),
+      ReviewDecision::Denied
+    ));
+    assert!(matches!(
+      decision_from_outcome(&PermissionOutcome::Cancelled),
+      ReviewDecision::Denied
+    ));
+  }
+}
@@ -0,0 +1,85 @@
+// Cokra App Server Protocol
+// JSON-RPC 2.0 envelopes
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+
+pub const JSONRPC_VERSION: &str = "2.0";
+
+pub const PARSE_ERROR_CODE: i64 = -32700;
+pub const INVALID_REQUEST_ERROR_CODE: i64 = -32600;
+pub const METHOD_NOT_FOUND_ERROR_CODE: i64 = -32601;
+pub const INVALID_PARAMS_ERROR_CODE: i64 = -32602;
+pub const INTERNAL_ERROR_CODE: i64 = -32603;
+
+/// Request id; JSON-RPC allows either a number or a string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RequestId {
+  Integer(i64),
+  String(String),
+}
+
+/// Client → server call that expects a response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcRequest {
+  pub jsonrpc: String,
+  pub id: RequestId,
+  pub method: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub params: Option<Value>,
+}
+
+/// One-way message in either direction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcNotification {
+  pub jsonrpc: String,
+  pub method: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub params: Option<Value>,
+}
+
+/// Successful response to a [`JsonRpcRequest`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcResponse {
+  pub jsonrpc: String,
+  pub id: RequestId,
+  pub result: Value,
+}
+
+/// Failed response; `id` is null when the request could not be parsed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcErrorResponse {
+  pub jsonrpc: String,
+  pub id: Option<RequestId>,
+  pub error: JsonRpcError,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonRpcError {
+  pub code: i64,
+  pub message: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub data: Option<Value>,
+}
+
+impl JsonRpcError {
+  pub fn new(code: i64, message: impl Into<String>) -> Self {
+    Self {
+      code,
+      message: message.into(),
+      data: None,
+    }
+  }
+}
+
+/// Anything the server writes to its output stream.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum JsonRpcMessage {
+  Request(JsonRpcRequest),
+  Response(JsonRpcResponse),
+  Error(JsonRpcErrorResponse),
+  Notification(JsonRpcNotification),
+}
@@ -1,6 +1,8 @@
 // Cokra App Server Protocol
 // JSON-RPC protocol definitions
 
+pub mod jsonrpc;
 pub mod v2;
 
+pub use jsonrpc::*;
 pub use v2::*;
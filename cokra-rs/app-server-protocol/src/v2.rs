@@ -1,17 +1,350 @@
 // Cokra App Server Protocol V2
 // Current API version definitions
 
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use cokra_protocol::CustomPrompt;
+use cokra_protocol::ElicitationAction;
+use cokra_protocol::Event;
+use cokra_protocol::RequestUserInputResponse;
+use cokra_protocol::ReviewDecision;
+use cokra_protocol::UserInput;
 use serde::Deserialize;
 use serde::Serialize;
+use serde_json::Value;
 
-/// Thread start request
+use crate::jsonrpc::JSONRPC_VERSION;
+use crate::jsonrpc::JsonRpcNotification;
+
+/// Every method a client may call, keyed by its JSON-RPC method name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params")]
+pub enum ClientRequest {
+  #[serde(rename = "thread/start")]
+  ThreadStart(ThreadStartParams),
+  #[serde(rename = "thread/resume")]
+  ThreadResume(ThreadResumeParams),
+  #[serde(rename = "thread/list")]
+  ThreadList(ThreadListParams),
+  #[serde(rename = "thread/archive")]
+  ThreadArchive(ThreadArchiveParams),
+  #[serde(rename = "turn/submit")]
+  TurnSubmit(TurnSubmitParams),
+  #[serde(rename = "turn/interrupt")]
+  TurnInterrupt(TurnInterruptParams),
+  #[serde(rename = "approval/respond")]
+  ApprovalRespond(ApprovalRespondParams),
+  #[serde(rename = "user_input/respond")]
+  UserInputRespond(UserInputRespondParams),
+  #[serde(rename = "elicitation/respond")]
+  ElicitationRespond(ElicitationRespondParams),
+  #[serde(rename = "prompt/list")]
+  PromptList(PromptListParams),
+  #[serde(rename = "prompt/get")]
+  PromptGet(PromptGetParams),
+  #[serde(rename = "model/list")]
+  ModelList(ModelListParams),
+  #[serde(rename = "config/read")]
+  ConfigRead(ConfigReadParams),
+}
+
+impl ClientRequest {
+  pub const METHODS: &'static [&'static str] = &[
+    "thread/start",
+    "thread/resume",
+    "thread/list",
+    "thread/archive",
+    "turn/submit",
+    "turn/interrupt",
+    "approval/respond",
+    "user_input/respond",
+    "elicitation/respond",
+    "prompt/list",
+    "prompt/get",
+    "model/list",
+    "config/read",
+  ];
+
+  /// Decode `method` + `params`; a missing `params` is read as `{}`.
+  pub fn from_parts(method: &str, params: Option<Value>) -> serde_json::Result<Self> {
+    serde_json::from_value(serde_json::json!({
+      "method": method,
+      "params": params.unwrap_or_else(|| Value::Object(Default::default())),
+    }))
+  }
+}
+
+/// Notifications the server pushes without being asked.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params")]
+pub enum ServerNotification {
+  /// One core event from a loaded thread.
+  #[serde(rename = "event")]
+  Event(Box<EventNotification>),
+  /// The thread's runtime stopped; it can be resumed with `thread/resume`.
+  #[serde(rename = "thread/closed")]
+  ThreadClosed(ThreadClosedNotification),
+}
+
+impl ServerNotification {
+  pub fn into_jsonrpc(self) -> serde_json::Result<JsonRpcNotification> {
+    let mut value = serde_json::to_value(self)?;
+    let params = value.get_mut("params").map(Value::take);
+    let method = value
+      .get("method")
+      .and_then(Value::as_str)
+      .unwrap_or_default()
+      .to_string();
+    Ok(JsonRpcNotification {
+      jsonrpc: JSONRPC_VERSION.to_string(),
+      method,
+      params,
+    })
+  }
+}
+
+/// Persisted metadata for one app-server thread.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThreadSummary {
+  pub thread_id: String,
+  pub cwd: PathBuf,
+  pub model: String,
+  /// First user message, for thread pickers.
+  #[serde(default)]
+  pub preview: Option<String>,
+  /// Unix seconds.
+  pub created_at: i64,
+  pub updated_at: i64,
+  #[serde(default)]
+  pub archived: bool,
+}
+
+/// One replayable message of a resumed thread.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThreadMessage {
+  pub role: String,
+  pub text: String,
+}
+
+/// Thread start request
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ThreadStartParams {
-  pub query: String,
+  /// Workspace root; defaults to the server's working directory.
+  #[serde(default)]
+  pub cwd: Option<PathBuf>,
+  /// `provider/model` override for this thread.
+  #[serde(default)]
+  pub model: Option<String>,
 }
 
 /// Thread start response
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ThreadStartResponse {
+  pub thread: ThreadSummary,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThreadResumeParams {
+  pub thread_id: String,
+  #[serde(default)]
+  pub cwd: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadResumeResponse {
+  pub thread: ThreadSummary,
+  pub messages: Vec<ThreadMessage>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThreadListParams {
+  #[serde(default)]
+  pub cwd: Option<PathBuf>,
+  #[serde(default)]
+  pub include_archived: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadListResponse {
+  /// Most recently updated first.
+  pub threads: Vec<ThreadSummary>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThreadArchiveParams {
+  pub thread_id: String,
+  #[serde(default)]
+  pub cwd: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThreadArchiveResponse {}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TurnSubmitParams {
   pub thread_id: String,
+  pub text: String,
+  /// Extra inputs (images, mentions, skills) sent after `text`.
+  #[serde(default)]
+  pub items: Vec<UserInput>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TurnSubmitResponse {
+  /// Id of the submitted `Op::UserInput`.
+  pub turn_id: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TurnInterruptParams {
+  pub thread_id: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TurnInterruptResponse {}
+
+/// Answer to an `ExecApprovalRequest` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRespondParams {
+  pub thread_id: String,
+  pub id: String,
+  #[serde(default)]
+  pub turn_id: Option<String>,
+  pub decision: ReviewDecision,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApprovalRespondResponse {}
+
+/// Answer to a `RequestUserInput` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserInputRespondParams {
+  pub thread_id: String,
+  pub id: String,
+  pub response: RequestUserInputResponse,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UserInputRespondResponse {}
+
+/// Answer to an `ElicitationRequest` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElicitationRespondParams {
+  pub thread_id: String,
+  pub server_name: String,
+  pub id: String,
+  pub decision: ElicitationAction,
+  #[serde(default)]
+  pub content: Option<Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ElicitationRespondResponse {}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PromptListParams {
+  pub thread_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptListResponse {
+  /// MCP prompts of the thread's servers, sorted by slash command name.
+  pub prompts: Vec<CustomPrompt>,
+}
+
+/// Render one prompt from `prompt/list`; submit the text with `turn/submit`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PromptGetParams {
+  pub thread_id: String,
+  /// The prompt's `id`.
+  pub id: String,
+  #[serde(default)]
+  pub arguments: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptGetResponse {
+  pub text: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelListParams {
+  #[serde(default)]
+  pub cwd: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelSummary {
+  /// `provider/model`, as accepted by `thread/start`.
+  pub id: String,
+  pub provider: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelListResponse {
+  pub models: Vec<ModelSummary>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfigReadParams {
+  #[serde(default)]
+  pub cwd: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigReadResponse {
+  /// The effective config after all layers and server overrides.
+  pub config: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventNotification {
+  pub thread_id: String,
+  pub event: Event,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadClosedNotification {
+  pub thread_id: String,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn client_request_reads_method_and_params() {
+    let request = ClientRequest::from_parts(
+      "turn/submit",
+      Some(serde_json::json!({ "thread_id": "t1", "text": "hi" })),
+    )
+    .expect("decode");
+    let ClientRequest::TurnSubmit(params) = request else {
+      panic!("unexpected request: {request:?}");
+    };
+    assert_eq!(params.thread_id, "t1");
+    assert_eq!(params.text, "hi");
+    assert!(params.items.is_empty());
+
+    assert!(matches!(
+      ClientRequest::from_parts("model/list", None).expect("decode"),
+      ClientRequest::ModelList(ModelListParams { cwd: None })
+    ));
+  }
+
+  #[test]
+  fn server_notification_uses_method_name() {
+    let notification = ServerNotification::ThreadClosed(ThreadClosedNotification {
+      thread_id: "t1".to_string(),
+    })
+    .into_jsonrpc()
+    .expect("encode");
+    assert_eq!(notification.method, "thread/closed");
+    assert_eq!(
+      notification.params,
+      Some(serde_json::json!({ "thread_id": "t1" }))
+    );
+  }
 }
@@ -1,6 +1,50 @@
-// app-server - Cokra module
-// Auto-generated placeholder
+// Cokra App Server
+// JSON-RPC 2.0 over stdio for editor and IDE integrations
 
-pub fn placeholder() -> bool {
-  true
+mod message_processor;
+pub mod thread_runner;
+pub mod thread_store;
+
+pub use message_processor::MessageProcessor;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use cokra_app_server_protocol::JsonRpcMessage;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::io::BufReader;
+use tokio::sync::mpsc;
+
+const OUTGOING_CHANNEL_CAPACITY: usize = 1024;
+
+/// Serve newline-delimited JSON-RPC on stdin/stdout until stdin closes.
+///
+/// `cwd` is the workspace used when a request omits one; `overrides` are the
+/// `-c key=value` pairs applied on top of every loaded config.
+pub async fn run_main(cwd: PathBuf, overrides: Vec<(String, String)>) -> anyhow::Result<()> {
+  let (outgoing, mut outgoing_rx) = mpsc::channel::<JsonRpcMessage>(OUTGOING_CHANNEL_CAPACITY);
+  let writer = tokio::spawn(async move {
+    let mut stdout = tokio::io::stdout();
+    while let Some(message) = outgoing_rx.recv().await {
+      let mut line = serde_json::to_vec(&message)?;
+      line.push(b'\n');
+      stdout.write_all(&line).await?;
+      stdout.flush().await?;
+    }
+    anyhow::Ok(())
+  });
+
+  let processor = Arc::new(MessageProcessor::new(outgoing, cwd, overrides));
+  let mut lines = BufReader::new(tokio::io::stdin()).lines();
+  while let Some(line) = lines.next_line().await? {
+    if line.trim().is_empty() {
+      continue;
+    }
+    processor.process_line(&line).await;
+  }
+
+  processor.shutdown().await;
+  drop(processor);
+  writer.await?
 }
@@ -0,0 +1,1070 @@
+//! Dispatch of JSON-RPC requests onto [`Cokra`] runtimes.
+//!
+//! Every loaded thread owns one runtime plus a pump task that turns
+//! `next_event` into `event` notifications and saves the history whenever a
+//! turn settles. Only one thread per workspace is loaded at a time (see
+//! [`crate::thread_runner`]); starting or resuming another one saves and
+//! unloads the current one first, and is refused while it is running a turn.
+//!
+//! Requests run on their own tasks, so a slow one (e.g. `thread/start`
+//! spawning a runtime) does not hold up `turn/interrupt` or approvals.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use cokra_app_server_protocol::ApprovalRespondParams;
+use cokra_app_server_protocol::ApprovalRespondResponse;
+use cokra_app_server_protocol::ClientRequest;
+use cokra_app_server_protocol::ConfigReadParams;
+use cokra_app_server_protocol::ConfigReadResponse;
+use cokra_app_server_protocol::ElicitationRespondParams;
+use cokra_app_server_protocol::ElicitationRespondResponse;
+use cokra_app_server_protocol::EventNotification;
+use cokra_app_server_protocol::INVALID_PARAMS_ERROR_CODE;
+use cokra_app_server_protocol::INVALID_REQUEST_ERROR_CODE;
+use cokra_app_server_protocol::JSONRPC_VERSION;
+use cokra_app_server_protocol::JsonRpcError;
+use cokra_app_server_protocol::JsonRpcErrorResponse;
+use cokra_app_server_protocol::JsonRpcMessage;
+use cokra_app_server_protocol::JsonRpcRequest;
+use cokra_app_server_protocol::JsonRpcResponse;
+use cokra_app_server_protocol::METHOD_NOT_FOUND_ERROR_CODE;
+use cokra_app_server_protocol::ModelListParams;
+use cokra_app_server_protocol::ModelListResponse;
+use cokra_app_server_protocol::ModelSummary;
+use cokra_app_server_protocol::PARSE_ERROR_CODE;
+use cokra_app_server_protocol::PromptGetParams;
+use cokra_app_server_protocol::PromptGetResponse;
+use cokra_app_server_protocol::PromptListParams;
+use cokra_app_server_protocol::PromptListResponse;
+use cokra_app_server_protocol::RequestId;
+use cokra_app_server_protocol::ServerNotification;
+use cokra_app_server_protocol::ThreadArchiveParams;
+use cokra_app_server_protocol::ThreadArchiveResponse;
+use cokra_app_server_protocol::ThreadClosedNotification;
+use cokra_app_server_protocol::ThreadListParams;
+use cokra_app_server_protocol::ThreadListResponse;
+use cokra_app_server_protocol::ThreadResumeParams;
+use cokra_app_server_protocol::ThreadResumeResponse;
+use cokra_app_server_protocol::ThreadStartParams;
+use cokra_app_server_protocol::ThreadStartResponse;
+use cokra_app_server_protocol::ThreadSummary;
+use cokra_app_server_protocol::TurnInterruptParams;
+use cokra_app_server_protocol::TurnInterruptResponse;
+use cokra_app_server_protocol::TurnSubmitParams;
+use cokra_app_server_protocol::TurnSubmitResponse;
+use cokra_app_server_protocol::UserInputRespondParams;
+use cokra_app_server_protocol::UserInputRespondResponse;
+use cokra_config::Config;
+use cokra_config::McpServerTransportConfig;
+use cokra_core::Cokra;
+use cokra_core::model::ModelClient;
+use cokra_core::model::init_model_layer;
+use cokra_protocol::EventMsg;
+use cokra_protocol::Op;
+use cokra_protocol::UserInput;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::Mutex;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::thread_runner::ThreadRunner;
+use crate::thread_runner::internal;
+use crate::thread_runner::model_overrides;
+use crate::thread_runner::resolve_cwd;
+use crate::thread_store::ThreadStore;
+use crate::thread_store::preview_of;
+use crate::thread_store::transcript;
+
+const UNLOAD_TIMEOUT: Duration = Duration::from_secs(5);
+const REDACTED: &str = "[REDACTED]";
+
+type HandlerResult<T> = Result<T, JsonRpcError>;
+
+struct LoadedThread {
+  cwd: PathBuf,
+  cokra: Arc<Cokra>,
+  store: Arc<ThreadStore>,
+  pump: JoinHandle<()>,
+  /// Set from `turn/submit` until the pump sees the turn settle.
+  turn_active: Arc<AtomicBool>,
+}
+
+type LoadedThreads = Arc<Mutex<HashMap<String, LoadedThread>>>;
+
+pub struct MessageProcessor {
+  outgoing: mpsc::Sender<JsonRpcMessage>,
+  default_cwd: PathBuf,
+  runner: ThreadRunner,
+  threads: LoadedThreads,
+  stores: Mutex<HashMap<PathBuf, Arc<ThreadStore>>>,
+}
+
+impl MessageProcessor {
+  pub fn new(
+    outgoing: mpsc::Sender<JsonRpcMessage>,
+    default_cwd: PathBuf,
+    overrides: Vec<(String, String)>,
+  ) -> Self {
+    Self {
+      outgoing,
+      default_cwd,
+      runner: ThreadRunner::new(overrides),
+      threads: Arc::new(Mutex::new(HashMap::new())),
+      stores: Mutex::new(HashMap::new()),
+    }
+  }
+
+  pub fn with_model_client(mut self, model_client: Arc<ModelClient>) -> Self {
+    self.runner = self.runner.with_model_client(model_client);
+    self
+  }
+
+  /// Handle one line of input; replies go to the outgoing channel.
+  ///
+  /// Requests are answered from their own task, possibly out of order.
+  pub async fn process_line(self: &Arc<Self>, line: &str) {
+    let value = match serde_json::from_str::<Value>(line) {
+      Ok(value) => value,
+      Err(err) => {
+        self
+          .send_error(None, JsonRpcError::new(PARSE_ERROR_CODE, err.to_string()))
+          .await;
+        return;
+      }
+    };
+    match serde_json::from_value::<JsonRpcMessage>(value) {
+      Ok(JsonRpcMessage::Request(request)) => {
+        let processor = Arc::clone(self);
+        tokio::spawn(async move { processor.handle_request(request).await });
+      }
+      // Nothing is sent to clients that expects an answer, and no client
+      // notification carries meaning yet (e.g. `initialized`).
+      Ok(JsonRpcMessage::Notification(_))
+      | Ok(JsonRpcMessage::Response(_))
+      | Ok(JsonRpcMessage::Error(_)) => {}
+      Err(_) => {
+        self
+          .send_error(
+            None,
+            JsonRpcError::new(INVALID_REQUEST_ERROR_CODE, "not a JSON-RPC 2.0 message"),
+          )
+          .await;
+      }
+    }
+  }
+
+  /// Save and stop every loaded thread.
+  pub async fn shutdown(&self) {
+    let loaded = std::mem::take(&mut *self.threads.lock().await);
+    for (thread_id, thread) in loaded {
+      self.unload(&thread_id, thread).await;
+    }
+  }
+
+  async fn handle_request(&self, request: JsonRpcRequest) {
+    let id = request.id;
+    if request.jsonrpc != JSONRPC_VERSION {
+      self
+        .send_error(
+          Some(id),
+          JsonRpcError::new(INVALID_REQUEST_ERROR_CODE, "jsonrpc must be \"2.0\""),
+        )
+        .await;
+      return;
+    }
+    if !ClientRequest::METHODS.contains(&request.method.as_str()) {
+      self
+        .send_error(
+          Some(id),
+          JsonRpcError::new(
+            METHOD_NOT_FOUND_ERROR_CODE,
+            format!("unknown method: {}", request.method),
+          ),
+        )
+        .await;
+      return;
+    }
+    let result = match ClientRequest::from_parts(&request.method, request.params) {
+      Ok(call) => self.dispatch(call).await,
+      Err(err) => Err(JsonRpcError::new(
+        INVALID_PARAMS_ERROR_CODE,
+        err.to_string(),
+      )),
+    };
+    let message = match result {
+      Ok(result) => JsonRpcMessage::Response(JsonRpcResponse {
+        jsonrpc: JSONRPC_VERSION.to_string(),
+        id,
+        result,
+      }),
+      Err(error) => JsonRpcMessage::Error(JsonRpcErrorResponse {
+        jsonrpc: JSONRPC_VERSION.to_string(),
+        id: Some(id),
+        error,
+      }),
+    };
+    let _ = self.outgoing.send(message).await;
+  }
+
+  async fn dispatch(&self, call: ClientRequest) -> HandlerResult<Value> {
+    match call {
+      ClientRequest::ThreadStart(params) => to_result(self.thread_start(params).await),
+      ClientRequest::ThreadResume(params) => to_result(self.thread_resume(params).await),
+      ClientRequest::ThreadList(params) => to_result(self.thread_list(params).await),
+      ClientRequest::ThreadArchive(params) => to_result(self.thread_archive(params).await),
+      ClientRequest::TurnSubmit(params) => to_result(self.turn_submit(params).await),
+      ClientRequest::TurnInterrupt(params) => to_result(self.turn_interrupt(params).await),
+      ClientRequest::ApprovalRespond(params) => to_result(self.approval_respond(params).await),
+      ClientRequest::UserInputRespond(params) => to_result(self.user_input_respond(params).await),
+      ClientRequest::ElicitationRespond(params) => {
+        to_result(self.elicitation_respond(params).await)
+      }
+      ClientRequest::PromptList(params) => to_result(self.prompt_list(params).await),
+      ClientRequest::PromptGet(params) => to_result(self.prompt_get(params).await),
+      ClientRequest::ModelList(params) => to_result(self.model_list(params).await),
+      ClientRequest::ConfigRead(params) => to_result(self.config_read(params).await),
+    }
+  }
+
+  async fn thread_start(&self, params: ThreadStartParams) -> HandlerResult<ThreadStartResponse> {
+    let cwd = self.resolve_cwd(params.cwd)?;
+    let store = self.store(&cwd).await?;
+    let config = self.load_config(&cwd, params.model.as_deref())?;
+    let now = chrono::Utc::now().timestamp();
+    let thread = ThreadSummary {
+      thread_id: uuid::Uuid::new_v4().to_string(),
+      cwd: cwd.clone(),
+      model: config.models.model.clone(),
+      preview: None,
+      created_at: now,
+      updated_at: now,
+      archived: false,
+    };
+    let _workspace = self.runner.lock_workspace(&cwd).await;
+    self.unload_cwd(&cwd).await?;
+    let cokra = self.spawn_runtime(config).await?;
+    store.insert(&thread).await.map_err(internal)?;
+    self.load(&thread.thread_id, cwd, cokra, store).await;
+    Ok(ThreadStartResponse { thread })
+  }
+
+  async fn thread_resume(&self, params: ThreadResumeParams) -> HandlerResult<ThreadResumeResponse> {
+    let cwd = self.resolve_cwd(params.cwd)?;
+    let store = self.store(&cwd).await?;
+    let thread = store
+      .get(&params.thread_id)
+      .await
+      .map_err(internal)?
+      .ok_or_else(|| unknown_thread(&params.thread_id))?;
+
+    if let Some(cokra) = self.loaded(&params.thread_id).await {
+      let messages = transcript(&cokra.history().await);
+      return Ok(ThreadResumeResponse { thread, messages });
+    }
+
+    let config = self.load_config(&cwd, Some(&thread.model))?;
+    let history = store
+      .load_history(&thread.thread_id)
+      .await
+      .map_err(internal)?;
+    let _workspace = self.runner.lock_workspace(&cwd).await;
+    self.unload_cwd(&cwd).await?;
+    let cokra = self.spawn_runtime(config).await?;
+    cokra.restore_history(history.clone()).await;
+    let thread = store
+      .update(&thread.thread_id, |thread| thread.archived = false)
+      .await
+      .map_err(internal)?
+      .unwrap_or(thread);
+    self.load(&thread.thread_id, cwd, cokra, store).await;
+    Ok(ThreadResumeResponse {
+      thread,
+      messages: transcript(&history),
+    })
+  }
+
+  async fn thread_list(&self, params: ThreadListParams) -> HandlerResult<ThreadListResponse> {
+    let cwd = self.resolve_cwd(params.cwd)?;
+    let store = self.store(&cwd).await?;
+    let threads = store
+      .list()
+      .await
+      .map_err(internal)?
+      .into_iter()
+      .filter(|thread| params.include_archived || !thread.archived)
+      .collect();
+    Ok(ThreadListResponse { threads })
+  }
+
+  async fn thread_archive(
+    &self,
+    params: ThreadArchiveParams,
+  ) -> HandlerResult<ThreadArchiveResponse> {
+    let cwd = self.resolve_cwd(params.cwd)?;
+    let store = self.store(&cwd).await?;
+    let loaded = self.threads.lock().await.remove(&params.thread_id);
+    if let Some(thread) = loaded {
+      self.unload(&params.thread_id, thread).await;
+    }
+    store
+      .update(&params.thread_id, |thread| thread.archived = true)
+      .await
+      .map_err(internal)?
+      .ok_or_else(|| unknown_thread(&params.thread_id))?;
+    Ok(ThreadArchiveResponse {})
+  }
+
+  async fn turn_submit(&self, params: TurnSubmitParams) -> HandlerResult<TurnSubmitResponse> {
+    if params.text.trim().is_empty() && params.items.is_empty() {
+      return Err(JsonRpcError::new(
+        INVALID_PARAMS_ERROR_CODE,
+        "turn/submit needs text or items",
+      ));
+    }
+    let (cokra, store, turn_active) = {
+      let threads = self.threads.lock().await;
+      let thread = threads
+        .get(&params.thread_id)
+        .ok_or_else(|| not_loaded(&params.thread_id))?;
+      (
+        thread.cokra.clone(),
+        thread.store.clone(),
+        thread.turn_active.clone(),
+      )
+    };
+    let mut items = Vec::with_capacity(params.items.len() + 1);
+    if !params.text.trim().is_empty() {
+      items.push(UserInput::Text {
+        text: params.text.clone(),
+        text_elements: Vec::new(),
+      });
+    }
+    items.extend(params.items);
+    turn_active.store(true, Ordering::SeqCst);
+    let turn_id = cokra
+      .submit(Op::UserInput {
+        items,
+        final_output_json_schema: None,
+      })
+      .await
+      .map_err(|err| {
+        turn_active.store(false, Ordering::SeqCst);
+        internal(err)
+      })?;
+
+    let preview = preview_of(&params.text);
+    store
+      .update(&params.thread_id, |thread| {
+        thread.updated_at = chrono::Utc::now().timestamp();
+        if thread.preview.is_none() {
+          thread.preview = preview;
+        }
+      })
+      .await
+      .map_err(internal)?;
+    Ok(TurnSubmitResponse { turn_id })
+  }
+
+  async fn turn_interrupt(
+    &self,
+    params: TurnInterruptParams,
+  ) -> HandlerResult<TurnInterruptResponse> {
+    self.submit(&params.thread_id, Op::Interrupt).await?;
+    Ok(TurnInterruptResponse {})
+  }
+
+  async fn approval_respond(
+    &self,
+    params: ApprovalRespondParams,
+  ) -> HandlerResult<ApprovalRespondResponse> {
+    self
+      .submit(
+        &params.thread_id,
+        Op::ExecApproval {
+          id: params.id,
+          turn_id: params.turn_id,
+          decision: params.decision,
+        },
+      )
+      .await?;
+    Ok(ApprovalRespondResponse {})
+  }
+
+  async fn user_input_respond(
+    &self,
+    params: UserInputRespondParams,
+  ) -> HandlerResult<UserInputRespondResponse> {
+    self
+      .submit(
+        &params.thread_id,
+        Op::UserInputAnswer {
+          id: params.id,
+          response: params.response,
+        },
+      )
+      .await?;
+    Ok(UserInputRespondResponse {})
+  }
+
+  async fn elicitation_respond(
+    &self,
+    params: ElicitationRespondParams,
+  ) -> HandlerResult<ElicitationRespondResponse> {
+    self
+      .submit(
+        &params.thread_id,
+        Op::ResolveElicitation {
+          server_name: params.server_name,
+          request_id: params.id,
+          decision: params.decision,
+          content: params.content,
+        },
+      )
+      .await?;
+    Ok(ElicitationRespondResponse {})
+  }
+
+  async fn prompt_list(&self, params: PromptListParams) -> HandlerResult<PromptListResponse> {
+    let (cokra, _) = self.loaded_with_store(&params.thread_id).await?;
+    Ok(PromptListResponse {
+      prompts: cokra.mcp_prompts(),
+    })
+  }
+
+  async fn prompt_get(&self, params: PromptGetParams) -> HandlerResult<PromptGetResponse> {
+    let (cokra, _) = self.loaded_with_store(&params.thread_id).await?;
+    let text = cokra
+      .render_mcp_prompt(&params.id, params.arguments)
+      .await
+      .map_err(|err| JsonRpcError::new(INVALID_PARAMS_ERROR_CODE, format!("{err:#}")))?;
+    Ok(PromptGetResponse { text })
+  }
+
+  async fn model_list(&self, params: ModelListParams) -> HandlerResult<ModelListResponse> {
+    let model_client = match self.runner.model_client() {
+      Some(model_client) => model_client.clone(),
+      None => {
+        let cwd = self.resolve_cwd(params.cwd)?;
+        let config = self.load_config(&cwd, None)?;
+        init_model_layer(&config).await.map_err(internal)?
+      }
+    };
+    let mut models = Vec::new();
+    for provider in model_client.registry().list_providers().await {
+      let prefix = format!("{}/", provider.id);
+      for model in provider.models {
+        let id = if model.starts_with(&prefix) {
+          model
+        } else {
+          format!("{prefix}{model}")
+        };
+        models.push(ModelSummary {
+          id,
+          provider: provider.id.clone(),
+        });
+      }
+    }
+    models.sort_by(|a, b| a.id.cmp(&b.id));
+    models.dedup();
+    Ok(ModelListResponse { models })
+  }
+
+  async fn config_read(&self, params: ConfigReadParams) -> HandlerResult<ConfigReadResponse> {
+    let cwd = self.resolve_cwd(params.cwd)?;
+    let config = self.load_config(&cwd, None)?;
+    Ok(ConfigReadResponse {
+      config: serde_json::to_value(redact_config(config)).map_err(internal)?,
+    })
+  }
+
+  fn resolve_cwd(&self, cwd: Option<PathBuf>) -> HandlerResult<PathBuf> {
+    resolve_cwd(&self.default_cwd, cwd)
+  }
+
+  fn load_config(&self, cwd: &Path, model: Option<&str>) -> HandlerResult<Config> {
+    self
+      .runner
+      .load_config(cwd, model.map(model_overrides).unwrap_or_default())
+  }
+
+  async fn store(&self, cwd: &Path) -> HandlerResult<Arc<ThreadStore>> {
+    let mut stores = self.stores.lock().await;
+    if let Some(store) = stores.get(cwd) {
+      return Ok(store.clone());
+    }
+    let store = Arc::new(ThreadStore::open(cwd).await.map_err(internal)?);
+    stores.insert(cwd.to_path_buf(), store.clone());
+    Ok(store)
+  }
+
+  async fn spawn_runtime(&self, config: Config) -> HandlerResult<Arc<Cokra>> {
+    Ok(Arc::new(self.runner.spawn(config).await?))
+  }
+
+  async fn load(&self, thread_id: &str, cwd: PathBuf, cokra: Arc<Cokra>, store: Arc<ThreadStore>) {
+    let turn_active = Arc::new(AtomicBool::new(false));
+    let pump = tokio::spawn(pump_events(
+      thread_id.to_string(),
+      cokra.clone(),
+      store.clone(),
+      self.outgoing.clone(),
+      self.threads.clone(),
+      turn_active.clone(),
+    ));
+    self.threads.lock().await.insert(
+      thread_id.to_string(),
+      LoadedThread {
+        cwd,
+        cokra,
+        store,
+        pump,
+        turn_active,
+      },
+    );
+  }
+
+  /// Unload the thread loaded for `cwd`, unless it is running a turn.
+  async fn unload_cwd(&self, cwd: &Path) -> HandlerResult<()> {
+    let unloaded = {
+      let mut threads = self.threads.lock().await;
+      if let Some((thread_id, _)) = threads
+        .iter()
+        .find(|(_, thread)| thread.cwd == cwd && thread.turn_active.load(Ordering::SeqCst))
+      {
+        return Err(JsonRpcError::new(
+          INVALID_REQUEST_ERROR_CODE,
+          format!(
+            "thread {thread_id} is running a turn in this workspace; interrupt it or wait for it to finish"
+          ),
+        ));
+      }
+      let ids = threads
+        .iter()
+        .filter(|(_, thread)| thread.cwd == cwd)
+        .map(|(thread_id, _)| thread_id.clone())
+        .collect::<Vec<_>>();
+      ids
+        .into_iter()
+        .filter_map(|thread_id| threads.remove(&thread_id).map(|thread| (thread_id, thread)))
+        .collect::<Vec<_>>()
+    };
+    for (thread_id, thread) in unloaded {
+      self.unload(&thread_id, thread).await;
+    }
+    Ok(())
+  }
+
+  async fn unload(&self, thread_id: &str, thread: LoadedThread) {
+    save_history(thread_id, &thread.cokra, &thread.store).await;
+    thread.pump.abort();
+    let _ = thread.pump.await;
+    let shutdown = match Arc::try_unwrap(thread.cokra) {
+      Ok(cokra) => tokio::time::timeout(UNLOAD_TIMEOUT, cokra.shutdown()).await,
+      // A request still holds the runtime; stop its loop through the shared
+      // handle so the thread does not outlive its unload.
+      Err(cokra) => {
+        tokio::time::timeout(UNLOAD_TIMEOUT, async {
+          cokra.submit(Op::Shutdown).await.map(drop)
+        })
+        .await
+      }
+    };
+    match shutdown {
+      Ok(Err(err)) => tracing::warn!("failed to shut down thread {thread_id}: {err:#}"),
+      Err(_) => tracing::warn!("timed out shutting down thread {thread_id}"),
+      Ok(Ok(())) => {}
+    }
+    send_notification(
+      &self.outgoing,
+      ServerNotification::ThreadClosed(ThreadClosedNotification {
+        thread_id: thread_id.to_string(),
+      }),
+    )
+    .await;
+  }
+
+  async fn loaded(&self, thread_id: &str) -> Option<Arc<Cokra>> {
+    self
+      .threads
+      .lock()
+      .await
+      .get(thread_id)
+      .map(|thread| thread.cokra.clone())
+  }
+
+  async fn loaded_with_store(
+    &self,
+    thread_id: &str,
+  ) -> HandlerResult<(Arc<Cokra>, Arc<ThreadStore>)> {
+    self
+      .threads
+      .lock()
+      .await
+      .get(thread_id)
+      .map(|thread| (thread.cokra.clone(), thread.store.clone()))
+      .ok_or_else(|| not_loaded(thread_id))
+  }
+
+  async fn submit(&self, thread_id: &str, op: Op) -> HandlerResult<String> {
+    let (cokra, _) = self.loaded_with_store(thread_id).await?;
+    cokra.submit(op).await.map_err(internal)
+  }
+
+  async fn send_error(&self, id: Option<RequestId>, error: JsonRpcError) {
+    let _ = self
+      .outgoing
+      .send(JsonRpcMessage::Error(JsonRpcErrorResponse {
+        jsonrpc: JSONRPC_VERSION.to_string(),
+        id,
+        error,
+      }))
+      .await;
+  }
+}
+
+/// Forward a thread's events until its runtime stops.
+async fn pump_events(
+  thread_id: String,
+  cokra: Arc<Cokra>,
+  store: Arc<ThreadStore>,
+  outgoing: mpsc::Sender<JsonRpcMessage>,
+  threads: LoadedThreads,
+  turn_active: Arc<AtomicBool>,
+) {
+  while let Ok(event) = cokra.next_event().await {
+    let settled = matches!(
+      event.msg,
+      EventMsg::TurnComplete(_) | EventMsg::TurnAborted(_)
+    );
+    if settled {
+      turn_active.store(false, Ordering::SeqCst);
+    } else if matches!(event.msg, EventMsg::TurnStarted(_)) {
+      turn_active.store(true, Ordering::SeqCst);
+    }
+    let stopped = matches!(event.msg, EventMsg::ShutdownComplete);
+    send_notification(
+      &outgoing,
+      ServerNotification::Event(Box::new(EventNotification {
+        thread_id: thread_id.clone(),
+        event,
+      })),
+    )
+    .await;
+    if settled {
+      save_history(&thread_id, &cokra, &store).await;
+    }
+    if stopped {
+      break;
+    }
+  }
+  // Reached only when the runtime stopped on its own; `unload` aborts us first.
+  save_history(&thread_id, &cokra, &store).await;
+  drop(threads.lock().await.remove(&thread_id));
+  send_notification(
+    &outgoing,
+    ServerNotification::ThreadClosed(ThreadClosedNotification { thread_id }),
+  )
+  .await;
+}
+
+async fn save_history(thread_id: &str, cokra: &Cokra, store: &ThreadStore) {
+  let history = cokra.history().await;
+  if let Err(err) = store.save_history(thread_id, &history).await {
+    tracing::warn!("failed to save history of thread {thread_id}: {err:#}");
+    return;
+  }
+  let now = chrono::Utc::now().timestamp();
+  if let Err(err) = store
+    .update(thread_id, |thread| thread.updated_at = now)
+    .await
+  {
+    tracing::warn!("failed to update thread {thread_id}: {err:#}");
+  }
+}
+
+async fn send_notification(
+  outgoing: &mpsc::Sender<JsonRpcMessage>,
+  notification: ServerNotification,
+) {
+  match notification.into_jsonrpc() {
+    Ok(notification) => {
+      let _ = outgoing
+        .send(JsonRpcMessage::Notification(notification))
+        .await;
+    }
+    Err(err) => tracing::warn!("failed to encode notification: {err}"),
+  }
+}
+
+fn to_result<T: Serialize>(result: HandlerResult<T>) -> HandlerResult<Value> {
+  result.and_then(|value| serde_json::to_value(value).map_err(internal))
+}
+
+/// `config` as `config/read` shows it: API keys, MCP bearer tokens and the
+/// values of MCP headers and environment variables are replaced.
+fn redact_config(mut config: Config) -> Config {
+  if config.models.api_key.is_some() {
+    config.models.api_key = Some(REDACTED.to_string());
+  }
+  for server in config.mcp.servers.values_mut() {
+    match &mut server.transport {
+      McpServerTransportConfig::Stdio { env, .. } => {
+        for value in env.iter_mut().flat_map(|env| env.values_mut()) {
+          *value = REDACTED.to_string();
+        }
+      }
+      McpServerTransportConfig::Http {
+        bearer_token,
+        headers,
+        ..
+      } => {
+        if bearer_token.is_some() {
+          *bearer_token = Some(REDACTED.to_string());
+        }
+        for value in headers.iter_mut().flat_map(|headers| headers.values_mut()) {
+          *value = REDACTED.to_string();
+        }
+      }
+    }
+  }
+  config
+}
+
+fn not_loaded(thread_id: &str) -> JsonRpcError {
+  JsonRpcError::new(
+    INVALID_PARAMS_ERROR_CODE,
+    format!("thread {thread_id} is not loaded; call thread/resume first"),
+  )
+}
+
+fn unknown_thread(thread_id: &str) -> JsonRpcError {
+  JsonRpcError::new(
+    INVALID_PARAMS_ERROR_CODE,
+    format!("unknown thread: {thread_id}"),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use cokra_core::test_support::ScriptedProvider;
+  use pretty_assertions::assert_eq;
+  use serde_json::json;
+
+  use super::*;
+
+  struct Harness {
+    processor: Arc<MessageProcessor>,
+    outgoing: mpsc::Receiver<JsonRpcMessage>,
+    buffered: std::collections::VecDeque<JsonRpcMessage>,
+    next_id: i64,
+    _workspace: tempfile::TempDir,
+  }
+
+  impl Harness {
+    async fn new() -> Self {
+      Self::with_provider(ScriptedProvider::replying("mock reply")).await
+    }
+
+    async fn with_provider(provider: ScriptedProvider) -> Self {
+      let model_client = provider.into_model_client().await;
+
+      let workspace = tempfile::tempdir().expect("tempdir");
+      let cwd = std::fs::canonicalize(workspace.path()).expect("canonical cwd");
+      let (tx, outgoing) = mpsc::channel(1024);
+      let processor = Arc::new(
+        MessageProcessor::new(tx, cwd, ScriptedProvider::config_overrides())
+          .with_model_client(model_client),
+      );
+      Self {
+        processor,
+        outgoing,
+        buffered: Default::default(),
+        next_id: 0,
+        _workspace: workspace,
+      }
+    }
+
+    /// Send a request and return its result or error; notifications that
+    /// arrive first are kept for [`Self::recv`].
+    async fn call(&mut self, method: &str, params: Value) -> Result<Value, JsonRpcError> {
+      self.next_id += 1;
+      let id = RequestId::Integer(self.next_id);
+      let line = json!({
+        "jsonrpc": "2.0",
+        "id": self.next_id,
+        "method": method,
+        "params": params,
+      });
+      self.processor.process_line(&line.to_string()).await;
+      loop {
+        let message = tokio::time::timeout(Duration::from_secs(10), self.outgoing.recv())
+          .await
+          .expect("timed out waiting for response")
+          .expect("outgoing channel closed");
+        match message {
+          JsonRpcMessage::Response(response) if response.id == id => return Ok(response.result),
+          JsonRpcMessage::Error(error) if error.id.as_ref() == Some(&id) => {
+            return Err(error.error);
+          }
+          other => self.buffered.push_back(other),
+        }
+      }
+    }
+
+    async fn recv(&mut self) -> JsonRpcMessage {
+      if let Some(message) = self.buffered.pop_front() {
+        return message;
+      }
+      tokio::time::timeout(Duration::from_secs(10), self.outgoing.recv())
+        .await
+        .expect("timed out waiting for output")
+        .expect("outgoing channel closed")
+    }
+
+    async fn wait_for_turn_complete(&mut self, thread_id: &str) {
+      loop {
+        if let ServerNotification::Event(event) = self.next_notification().await
+          && event.thread_id == thread_id
+          && matches!(event.event.msg, EventMsg::TurnComplete(_))
+        {
+          return;
+        }
+      }
+    }
+
+    async fn wait_for_thread_closed(&mut self, thread_id: &str) {
+      loop {
+        if let ServerNotification::ThreadClosed(closed) = self.next_notification().await
+          && closed.thread_id == thread_id
+        {
+          return;
+        }
+      }
+    }
+
+    async fn next_notification(&mut self) -> ServerNotification {
+      loop {
+        let JsonRpcMessage::Notification(notification) = self.recv().await else {
+          continue;
+        };
+        if let Ok(notification) = serde_json::from_value(json!({
+          "method": notification.method,
+          "params": notification.params,
+        })) {
+          return notification;
+        }
+      }
+    }
+
+    async fn start_thread(&mut self) -> Result<String, JsonRpcError> {
+      let started: ThreadStartResponse =
+        serde_json::from_value(self.call("thread/start", json!({})).await?)
+          .expect("start response");
+      Ok(started.thread.thread_id)
+    }
+  }
+
+  #[tokio::test]
+  async fn thread_lifecycle_streams_events_and_resumes_history() {
+    let mut harness = Harness::new().await;
+
+    let started: ThreadStartResponse = serde_json::from_value(
+      harness
+        .call("thread/start", json!({}))
+        .await
+        .expect("start"),
+    )
+    .expect("start response");
+    let thread_id = started.thread.thread_id.clone();
+    assert_eq!(started.thread.model, "mock/default");
+
+    let submitted: TurnSubmitResponse = serde_json::from_value(
+      harness
+        .call(
+          "turn/submit",
+          json!({ "thread_id": thread_id, "text": "hello there" }),
+        )
+        .await
+        .expect("submit"),
+    )
+    .expect("submit response");
+    assert!(!submitted.turn_id.is_empty());
+    harness.wait_for_turn_complete(&thread_id).await;
+
+    harness
+      .call("thread/archive", json!({ "thread_id": thread_id }))
+      .await
+      .expect("archive");
+    let listed: ThreadListResponse =
+      serde_json::from_value(harness.call("thread/list", json!({})).await.expect("list"))
+        .expect("list response");
+    assert!(listed.threads.is_empty());
+    let listed: ThreadListResponse = serde_json::from_value(
+      harness
+        .call("thread/list", json!({ "include_archived": true }))
+        .await
+        .expect("list archived"),
+    )
+    .expect("list response");
+    assert_eq!(listed.threads.len(), 1);
+    assert!(listed.threads[0].archived);
+    assert_eq!(listed.threads[0].preview.as_deref(), Some("hello there"));
+
+    let resumed: ThreadResumeResponse = serde_json::from_value(
+      harness
+        .call("thread/resume", json!({ "thread_id": thread_id }))
+        .await
+        .expect("resume"),
+    )
+    .expect("resume response");
+    assert!(!resumed.thread.archived);
+    assert_eq!(
+      resumed.messages,
+      vec![
+        cokra_app_server_protocol::ThreadMessage {
+          role: "user".to_string(),
+          text: "hello there".to_string(),
+        },
+        cokra_app_server_protocol::ThreadMessage {
+          role: "assistant".to_string(),
+          text: "mock reply".to_string(),
+        },
+      ]
+    );
+
+    harness.processor.shutdown().await;
+  }
+
+  #[tokio::test]
+  async fn malformed_requests_get_jsonrpc_errors() {
+    let mut harness = Harness::new().await;
+
+    harness.processor.process_line("{not json").await;
+    let JsonRpcMessage::Error(parse_error) = harness.recv().await else {
+      panic!("expected a parse error");
+    };
+    assert_eq!(parse_error.id, None);
+    assert_eq!(parse_error.error.code, PARSE_ERROR_CODE);
+
+    let unknown = harness
+      .call("thread/fork", json!({}))
+      .await
+      .expect_err("unknown method");
+    assert_eq!(unknown.code, METHOD_NOT_FOUND_ERROR_CODE);
+
+    let missing_field = harness
+      .call("turn/submit", json!({ "text": "hi" }))
+      .await
+      .expect_err("missing thread id");
+    assert_eq!(missing_field.code, INVALID_PARAMS_ERROR_CODE);
+
+    let not_loaded = harness
+      .call("turn/interrupt", json!({ "thread_id": "nope" }))
+      .await
+      .expect_err("thread not loaded");
+    assert_eq!(not_loaded.code, INVALID_PARAMS_ERROR_CODE);
+
+    let models: ModelListResponse =
+      serde_json::from_value(harness.call("model/list", json!({})).await.expect("models"))
+        .expect("models response");
+    assert!(
+      models
+        .models
+        .iter()
+        .any(|model| model.id == "mock/default" && model.provider == "mock")
+    );
+  }
+
+  #[tokio::test]
+  async fn busy_workspace_thread_is_not_unloaded() {
+    let gate = Arc::new(tokio::sync::Semaphore::new(0));
+    let mut harness =
+      Harness::with_provider(ScriptedProvider::replying("mock reply").gated(gate.clone())).await;
+    let first = harness.start_thread().await.expect("start");
+    harness
+      .call(
+        "turn/submit",
+        json!({ "thread_id": first, "text": "take your time" }),
+      )
+      .await
+      .expect("submit");
+
+    let refused = harness
+      .start_thread()
+      .await
+      .expect_err("the first thread is mid-turn");
+    assert_eq!(refused.code, INVALID_REQUEST_ERROR_CODE);
+    assert!(refused.message.contains(&first), "{}", refused.message);
+    harness
+      .call("prompt/list", json!({ "thread_id": first }))
+      .await
+      .expect("the first thread is still loaded");
+
+    gate.add_permits(64);
+    harness.wait_for_turn_complete(&first).await;
+    let second = harness.start_thread().await.expect("start after the turn");
+    assert_ne!(second, first);
+    harness.wait_for_thread_closed(&first).await;
+
+    harness.processor.shutdown().await;
+  }
+
+  #[test]
+  fn config_read_hides_credentials() {
+    let server = |transport| cokra_config::McpServerConfig {
+      transport,
+      enabled: true,
+      required: false,
+      startup_timeout_sec: None,
+      tool_timeout_sec: None,
+      enabled_tools: None,
+      disabled_tools: None,
+      allow_sampling: false,
+    };
+    let mut config = Config::default();
+    config.models.api_key = Some("sk-live".to_string());
+    config.mcp.servers.insert(
+      "local".to_string(),
+      server(McpServerTransportConfig::Stdio {
+        command: "mcp-local".to_string(),
+        args: Vec::new(),
+        env: Some(HashMap::from([(
+          "GITHUB_TOKEN".to_string(),
+          "env-secret".to_string(),
+        )])),
+        cwd: None,
+      }),
+    );
+    config.mcp.servers.insert(
+      "remote".to_string(),
+      server(McpServerTransportConfig::Http {
+        url: "https://mcp.example.com/mcp".to_string(),
+        bearer_token: Some("bearer-secret".to_string()),
+        headers: Some(HashMap::from([(
+          "X-Api-Key".to_string(),
+          "header-secret".to_string(),
+        )])),
+      }),
+    );
+
+    let shown = serde_json::to_string(&redact_config(config)).expect("serialize config");
+
+    for secret in ["sk-live", "env-secret", "bearer-secret", "header-secret"] {
+      assert!(
+        !shown.contains(secret),
+        "config/read leaks {secret}: {shown}"
+      );
+    }
+    // Names stay visible so clients can tell what is configured.
+    assert!(shown.contains("GITHUB_TOKEN"));
+    assert!(shown.contains("X-Api-Key"));
+    assert!(shown.contains("https://mcp.example.com/mcp"));
+  }
+}
@@ -0,0 +1,173 @@
+//! Running [`Cokra`] runtimes on saved threads.
+//!
+//! The app server, the MCP server and the ACP server all keep conversations
+//! in the workspace's [`ThreadStore`], so a thread started from one front end
+//! can be continued from another. This module holds what they share: config
+//! loading, runtime spawning, the per-workspace lock and the
+//! load/run/save cycle of a single prompt.
+//!
+//! Tradeoff: core keys its root thread by workspace, so only one runtime per
+//! workspace may run at a time. The MCP and ACP servers take
+//! [`ThreadRunner::lock_workspace`] around every prompt, so prompts for the
+//! same workspace run one after another; the app server keeps one thread per
+//! workspace loaded and unloads it before loading another.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use cokra_app_server_protocol::INTERNAL_ERROR_CODE;
+use cokra_app_server_protocol::INVALID_PARAMS_ERROR_CODE;
+use cokra_app_server_protocol::JsonRpcError;
+use cokra_app_server_protocol::ThreadSummary;
+use cokra_config::Config;
+use cokra_config::ConfigLoader;
+use cokra_core::Cokra;
+use cokra_core::model::ModelClient;
+use tokio::sync::Mutex;
+use tokio::sync::OwnedMutexGuard;
+
+use crate::thread_store::ThreadStore;
+use crate::thread_store::preview_of;
+
+pub struct ThreadRunner {
+  overrides: Vec<(String, String)>,
+  /// Used instead of the configured providers when set (tests, embedders).
+  model_client: Option<Arc<ModelClient>>,
+  workspace_locks: Mutex<HashMap<PathBuf, Arc<Mutex<()>>>>,
+}
+
+impl ThreadRunner {
+  /// `overrides` are the `-c key=value` pairs applied on top of every loaded
+  /// config.
+  pub fn new(overrides: Vec<(String, String)>) -> Self {
+    Self {
+      overrides,
+      model_client: None,
+      workspace_locks: Mutex::new(HashMap::new()),
+    }
+  }
+
+  pub fn with_model_client(mut self, model_client: Arc<ModelClient>) -> Self {
+    self.model_client = Some(model_client);
+    self
+  }
+
+  pub fn model_client(&self) -> Option<&Arc<ModelClient>> {
+    self.model_client.as_ref()
+  }
+
+  /// Load the config for `cwd` with `extra` applied after the runner's own
+  /// overrides.
+  pub fn load_config(
+    &self,
+    cwd: &Path,
+    extra: Vec<(String, String)>,
+  ) -> Result<Config, JsonRpcError> {
+    let mut overrides = self.overrides.clone();
+    overrides.extend(extra);
+    ConfigLoader::default()
+      .with_cwd(cwd.to_path_buf())
+      .load_with_cli_overrides(overrides)
+      .map_err(internal)
+  }
+
+  pub async fn spawn(&self, config: Config) -> Result<Cokra, JsonRpcError> {
+    let spawned = match &self.model_client {
+      Some(model_client) => Cokra::spawn_with_model_client(config, model_client.clone()).await,
+      None => Cokra::spawn(config).await,
+    };
+    Ok(spawned.map_err(internal)?.cokra)
+  }
+
+  /// Wait until no other prompt runs in `cwd`.
+  pub async fn lock_workspace(&self, cwd: &Path) -> OwnedMutexGuard<()> {
+    let lock = self
+      .workspace_locks
+      .lock()
+      .await
+      .entry(cwd.to_path_buf())
+      .or_default()
+      .clone();
+    lock.lock_owned().await
+  }
+
+  /// Spawn a runtime on `thread`'s saved history, hand it to `run` and save
+  /// the conversation afterwards, whatever `run` returned.
+  ///
+  /// The caller holds the workspace lock. The thread's preview is set from
+  /// `prompt` when it has none yet.
+  pub async fn run_on_thread<T>(
+    &self,
+    store: &ThreadStore,
+    thread: &ThreadSummary,
+    config: Config,
+    prompt: &str,
+    run: impl AsyncFnOnce(&Cokra) -> anyhow::Result<T>,
+  ) -> Result<T, JsonRpcError> {
+    let history = store
+      .load_history(&thread.thread_id)
+      .await
+      .map_err(internal)?;
+    let cokra = self.spawn(config).await?;
+    if !history.is_empty() {
+      cokra.restore_history(history).await;
+    }
+
+    let outcome = run(&cokra).await;
+
+    let history = cokra.history().await;
+    if let Err(err) = store.save_history(&thread.thread_id, &history).await {
+      tracing::warn!("failed to save thread {}: {err:#}", thread.thread_id);
+    }
+    let now = chrono::Utc::now().timestamp();
+    let preview = preview_of(prompt);
+    let _ = store
+      .update(&thread.thread_id, |summary| {
+        summary.updated_at = now;
+        summary.archived = false;
+        if summary.preview.is_none() {
+          summary.preview = preview;
+        }
+      })
+      .await;
+    if let Err(err) = cokra.shutdown().await {
+      tracing::warn!("failed to shut down thread {}: {err:#}", thread.thread_id);
+    }
+    outcome.map_err(internal)
+  }
+}
+
+/// The overrides that pin a config to `model`, a `provider/model` id.
+pub fn model_overrides(model: &str) -> Vec<(String, String)> {
+  let mut overrides = Vec::new();
+  if let Some((provider, _)) = model.split_once('/') {
+    overrides.push(("models.provider".to_string(), provider.to_string()));
+  }
+  overrides.push(("models.model".to_string(), model.to_string()));
+  overrides
+}
+
+/// Resolve a requested workspace against `default_cwd`; relative paths are
+/// joined onto it.
+pub fn resolve_cwd(default_cwd: &Path, cwd: Option<PathBuf>) -> Result<PathBuf, JsonRpcError> {
+  match cwd {
+    Some(cwd) if cwd.is_relative() => canonical_cwd(&default_cwd.join(cwd)),
+    Some(cwd) => canonical_cwd(&cwd),
+    None => Ok(default_cwd.to_path_buf()),
+  }
+}
+
+pub fn canonical_cwd(cwd: &Path) -> Result<PathBuf, JsonRpcError> {
+  std::fs::canonicalize(cwd)
+    .map_err(|err| invalid_params(format!("invalid cwd {}: {err}", cwd.display())))
+}
+
+pub fn invalid_params(err: impl std::fmt::Display) -> JsonRpcError {
+  JsonRpcError::new(INVALID_PARAMS_ERROR_CODE, err.to_string())
+}
+
+pub fn internal(err: impl std::fmt::Display) -> JsonRpcError {
+  JsonRpcError::new(INTERNAL_ERROR_CODE, format!("{err:#}"))
+}
@@ -0,0 +1,147 @@
+//! Persisted app-server threads for one workspace.
+//!
+//! The index and every thread's history live in the workspace's
+//! `.cokra/state.db`, next to the team state, so a plugin reopening the same
+//! folder sees the same threads.
+
+use std::path::Path;
+
+use cokra_app_server_protocol::ThreadMessage;
+use cokra_app_server_protocol::ThreadSummary;
+use cokra_core::model::Message;
+use cokra_state::StateDb;
+use tokio::sync::Mutex;
+
+const PREVIEW_MAX_CHARS: usize = 80;
+const THREAD_INDEX_STATE_KEY_SUFFIX: &str = "::app_server_threads";
+const THREAD_HISTORY_STATE_KEY_SUFFIX: &str = "::app_server_thread::";
+
+/// Thread index and histories of one workspace.
+///
+/// Tradeoff: the whole index is one JSON row, rewritten on every update; fine
+/// for the handful of threads a workspace accumulates.
+pub struct ThreadStore {
+  db: StateDb,
+  scope: String,
+  /// Serializes read-modify-write of the index between requests and pumps.
+  index_lock: Mutex<()>,
+}
+
+impl ThreadStore {
+  pub async fn open(cwd: &Path) -> anyhow::Result<Self> {
+    Ok(Self {
+      db: StateDb::new(StateDb::default_path_for(cwd)).await?,
+      scope: cwd.display().to_string(),
+      index_lock: Mutex::new(()),
+    })
+  }
+
+  fn index_key(&self) -> String {
+    format!("{}{THREAD_INDEX_STATE_KEY_SUFFIX}", self.scope)
+  }
+
+  fn history_key(&self, thread_id: &str) -> String {
+    format!("{}{THREAD_HISTORY_STATE_KEY_SUFFIX}{thread_id}", self.scope)
+  }
+
+  async fn load_index(&self) -> anyhow::Result<Vec<ThreadSummary>> {
+    Ok(
+      self
+        .db
+        .load_json::<Vec<ThreadSummary>>(&self.index_key())
+        .await?
+        .unwrap_or_default(),
+    )
+  }
+
+  /// All threads, most recently updated first.
+  pub async fn list(&self) -> anyhow::Result<Vec<ThreadSummary>> {
+    let _guard = self.index_lock.lock().await;
+    let mut threads = self.load_index().await?;
+    threads.sort_by_key(|thread| std::cmp::Reverse(thread.updated_at));
+    Ok(threads)
+  }
+
+  pub async fn get(&self, thread_id: &str) -> anyhow::Result<Option<ThreadSummary>> {
+    let _guard = self.index_lock.lock().await;
+    Ok(
+      self
+        .load_index()
+        .await?
+        .into_iter()
+        .find(|thread| thread.thread_id == thread_id),
+    )
+  }
+
+  /// Apply `update` to the stored summary and return the result.
+  pub async fn update(
+    &self,
+    thread_id: &str,
+    update: impl FnOnce(&mut ThreadSummary),
+  ) -> anyhow::Result<Option<ThreadSummary>> {
+    let _guard = self.index_lock.lock().await;
+    let mut threads = self.load_index().await?;
+    let Some(thread) = threads
+      .iter_mut()
+      .find(|thread| thread.thread_id == thread_id)
+    else {
+      return Ok(None);
+    };
+    update(thread);
+    let updated = thread.clone();
+    self.db.save_json(&self.index_key(), &threads).await?;
+    Ok(Some(updated))
+  }
+
+  pub async fn insert(&self, summary: &ThreadSummary) -> anyhow::Result<()> {
+    let _guard = self.index_lock.lock().await;
+    let mut threads = self.load_index().await?;
+    threads.retain(|thread| thread.thread_id != summary.thread_id);
+    threads.push(summary.clone());
+    self.db.save_json(&self.index_key(), &threads).await
+  }
+
+  pub async fn load_history(&self, thread_id: &str) -> anyhow::Result<Vec<Message>> {
+    Ok(
+      self
+        .db
+        .load_json::<Vec<Message>>(&self.history_key(thread_id))
+        .await?
+        .unwrap_or_default(),
+    )
+  }
+
+  pub async fn save_history(&self, thread_id: &str, messages: &[Message]) -> anyhow::Result<()> {
+    self
+      .db
+      .save_json(&self.history_key(thread_id), &messages)
+      .await
+  }
+}
+
+/// The user/assistant text of `messages`, for clients redrawing a resumed thread.
+pub fn transcript(messages: &[Message]) -> Vec<ThreadMessage> {
+  messages
+    .iter()
+    .filter_map(|message| match message {
+      Message::User(text) => Some(ThreadMessage {
+        role: "user".to_string(),
+        text: text.clone(),
+      }),
+      Message::Assistant {
+        content: Some(text),
+        ..
+      } if !text.is_empty() => Some(ThreadMessage {
+        role: "assistant".to_string(),
+        text: text.clone(),
+      }),
+      _ => None,
+    })
+    .collect()
+}
+
+/// First non-empty line of a prompt, shortened for thread pickers.
+pub fn preview_of(text: &str) -> Option<String> {
+  let line = text.lines().map(str::trim).find(|line| !line.is_empty())?;
+  Some(line.chars().take(PREVIEW_MAX_CHARS).collect())
+}
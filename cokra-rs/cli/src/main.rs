@@ -3,7 +3,9 @@ use anyhow::Result;
 use clap::Parser;
 use clap::Subcommand;
 use cokra_config::ConfigLoader;
+use cokra_config::McpServerTransportConfig;
 use cokra_core::Cokra;
+use cokra_core::mcp::McpTokenStore;
 use cokra_core::model::auth::AuthManager;
 use cokra_core::model::auth::AuthRequest;
 use cokra_core::model::auth::AuthType;
@@ -128,6 +130,20 @@ enum Commands {
     auth_command: AuthCommands,
   },
   Models,
+  /// Serve JSON-RPC 2.0 on stdio for editor and IDE integrations.
+  AppServer {
+    /// Workspace used when a request does not name one.
+    #[arg(long = "cd", short = 'C', value_name = "DIR", alias = "cwd")]
+    cwd: Option<PathBuf>,
+  },
+  /// Serve the Agent Client Protocol on stdio for editors such as Zed.
+  Acp,
+  /// Host one out-of-process teammate for the leader listening on `socket`.
+  #[command(hide = true)]
+  TeammateHost {
+    #[arg(long = "socket", value_name = "PATH")]
+    socket: PathBuf,
+  },
 }
 
 #[derive(Debug, Subcommand)]
@@ -144,6 +160,23 @@ enum McpCommands {
   Remove {
     server: String,
   },
+  /// Authorize Cokra with a streamable-HTTP server that requires OAuth.
+  Login {
+    server: String,
+  },
+  /// Forget the OAuth tokens stored for a server.
+  Logout {
+    server: String,
+  },
+  /// Serve Cokra itself as an MCP server on stdio.
+  Serve {
+    /// Workspace used when a tool call does not name one.
+    #[arg(long = "cd", short = 'C', value_name = "DIR", alias = "cwd")]
+    cwd: Option<PathBuf>,
+    /// Let `run_task` loosen the sandbox or approval policy below the config.
+    #[arg(long)]
+    allow_unsafe_overrides: bool,
+  },
 }
 
 #[derive(Debug, Subcommand)]
@@ -225,6 +258,21 @@ async fn main() -> Result<()> {
       )
       .await
     }
+    Some(Commands::Mcp {
+      mcp_command: McpCommands::Serve {
+        cwd,
+        allow_unsafe_overrides,
+      },
+    }) => {
+      let resolved_cwd = resolve_cwd(cwd, None, cli.cwd, cli.dir_compat)?;
+      cokra_mcp_server::run_main(resolved_cwd, overrides.clone(), allow_unsafe_overrides).await
+    }
+    Some(Commands::Mcp {
+      mcp_command: McpCommands::Login { server },
+    }) => {
+      let resolved_cwd = resolve_cwd(None, None, cli.cwd, cli.dir_compat)?;
+      mcp_login(&server, resolved_cwd, overrides.clone()).await
+    }
     Some(Commands::Mcp { mcp_command }) => handle_mcp_command(mcp_command).await,
     Some(Commands::Config { config_command }) => handle_config_command(config_command).await,
     Some(Commands::Auth { auth_command }) => handle_auth_command(auth_command).await,
@@ -232,6 +280,12 @@ async fn main() -> Result<()> {
       let resolved_cwd = resolve_cwd(None, None, cli.cwd, cli.dir_compat)?;
       list_models(resolved_cwd, overrides.clone()).await
     }
+    Some(Commands::AppServer { cwd }) => {
+      let resolved_cwd = resolve_cwd(cwd, None, cli.cwd, cli.dir_compat)?;
+      cokra_app_server::run_main(resolved_cwd, overrides.clone()).await
+    }
+    Some(Commands::Acp) => cokra_acp_server::run_main(overrides.clone()).await,
+    Some(Commands::TeammateHost { socket }) => cokra_core::serve_teammate_process(&socket).await,
     None => {
       if let Some(prompt) = cli.prompt {
         let resolved_cwd = resolve_cwd(None, None, cli.cwd, cli.dir_compat)?;
@@ -443,6 +497,25 @@ fn print_human_event(event: &EventMsg) {
         println!("\n[item:done] {summary}");
       }
     }
+    EventMsg::TokenCount(e) => {
+      let cost = e
+        .cost_usd
+        .map(|cost_usd| {
+          format!(
+            " cost=${cost_usd:.4} session_cost=${:.4}",
+            e.session_cost_usd
+          )
+        })
+        .unwrap_or_default();
+      println!(
+        "[tokens] input={} cached={} output={} reasoning={} total={}{cost}",
+        e.input_tokens,
+        e.cached_input_tokens,
+        e.output_tokens,
+        e.reasoning_output_tokens,
+        e.total_tokens
+      );
+    }
     EventMsg::TurnComplete(e) => {
       println!("[turn:done] status={:?}", e.status);
     }
@@ -476,10 +549,44 @@ async fn handle_mcp_command(cmd: McpCommands) -> anyhow::Result<()> {
     McpCommands::Remove { server } => {
       println!("Removing MCP server: {}", server);
     }
+    McpCommands::Logout { server } => {
+      if McpTokenStore::default_store()?.remove(&server).await? {
+        println!("Logged out from MCP server `{server}`.");
+      } else {
+        println!("No OAuth tokens stored for MCP server `{server}`.");
+      }
+    }
+    // Handled in `main`, which has the working directory and overrides.
+    McpCommands::Serve { .. } | McpCommands::Login { .. } => {}
   }
   Ok(())
 }
 
+async fn mcp_login(
+  server: &str,
+  resolved_cwd: PathBuf,
+  overrides: Vec<(String, String)>,
+) -> anyhow::Result<()> {
+  let config = load_config(&resolved_cwd, overrides)?;
+  let server_config = config
+    .mcp
+    .servers
+    .get(server)
+    .with_context(|| format!("no MCP server named `{server}` is configured"))?;
+  let McpServerTransportConfig::Http { url, .. } = &server_config.transport else {
+    anyhow::bail!("`{server}` is a stdio server; only streamable-HTTP servers use OAuth");
+  };
+
+  cokra_core::mcp::oauth_login(server, url, |authorize_url| {
+    println!("Open this URL to authorize Cokra with `{server}`:");
+    println!("  {authorize_url}");
+    println!("Waiting for authorization...");
+  })
+  .await?;
+  println!("Logged in to MCP server `{server}`.");
+  Ok(())
+}
+
 async fn handle_config_command(cmd: ConfigCommands) -> anyhow::Result<()> {
   match cmd {
     ConfigCommands::Show => {
@@ -59,6 +59,9 @@ pub struct Config {
   /// Tool configuration
   #[serde(default)]
   pub tools: ToolsConfig,
+  /// Spend limits
+  #[serde(default)]
+  pub budget: BudgetConfig,
 
   /// Projects trust map keyed by canonical path string.
   ///
@@ -98,6 +101,7 @@ impl Default for Config {
       shell_environment: ShellEnvironmentPolicy::default(),
       agents: AgentConfig::default(),
       tools: ToolsConfig::default(),
+      budget: BudgetConfig::default(),
       projects: HashMap::new(),
       project_root_markers: None,
       cwd: default_cwd(),
@@ -384,6 +388,8 @@ struct RawMcpServerConfig {
   enabled_tools: Option<Vec<String>>,
   #[serde(default)]
   disabled_tools: Option<Vec<String>>,
+  #[serde(default)]
+  allow_sampling: bool,
 }
 
 /// MCP server configuration.
@@ -401,6 +407,8 @@ struct RawMcpServerConfig {
 /// [mcp.servers.my-http-server]
 /// url = "https://mcp.example.com/mcp/"
 /// bearer_token = "..."
+/// # Let the server ask Cokra's model for completions (each one is approved)
+/// allow_sampling = true
 /// ```
 #[derive(Debug, Clone, Serialize, JsonSchema)]
 pub struct McpServerConfig {
@@ -415,6 +423,9 @@ pub struct McpServerConfig {
   pub tool_timeout_sec: Option<u64>,
   pub enabled_tools: Option<Vec<String>>,
   pub disabled_tools: Option<Vec<String>>,
+  /// Whether the server may request completions from the session's model.
+  #[serde(default)]
+  pub allow_sampling: bool,
 }
 
 impl<'de> serde::Deserialize<'de> for McpServerConfig {
@@ -451,6 +462,7 @@ impl<'de> serde::Deserialize<'de> for McpServerConfig {
       tool_timeout_sec: raw.tool_timeout_sec,
       enabled_tools: raw.enabled_tools,
       disabled_tools: raw.disabled_tools,
+      allow_sampling: raw.allow_sampling,
     })
   }
 }
@@ -520,6 +532,9 @@ pub struct ModelsConfig {
   pub base_url: Option<String>,
   /// API key (optional — falls back to environment variable)
   pub api_key: Option<String>,
+  /// Record or replay provider HTTP traffic (overridden by `COKRA_CASSETTE`)
+  #[serde(default)]
+  pub cassette: Option<CassetteConfig>,
 }
 
 impl Default for ModelsConfig {
@@ -529,10 +544,47 @@ impl Default for ModelsConfig {
       model: "gpt-5.2-codex".to_string(),
       base_url: None,
       api_key: None,
+      cassette: None,
     }
   }
 }
 
+/// Whether a cassette captures live traffic or serves it back offline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CassetteMode {
+  /// Forward requests to the provider and write each exchange to disk.
+  Record,
+  /// Serve previously recorded exchanges without touching the network.
+  Replay,
+}
+
+/// Provider HTTP cassette used for deterministic offline tests.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CassetteConfig {
+  /// Record or replay
+  pub mode: CassetteMode,
+  /// Directory holding the numbered interaction files
+  pub dir: PathBuf,
+}
+
+// ============================================================================
+// BUDGET CONFIGURATION
+// ============================================================================
+
+/// Spend limits for a session, priced from the models.dev catalog.
+///
+/// Both limits cover the root thread plus every spawned teammate.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct BudgetConfig {
+  /// Pause and ask for confirmation once session spend reaches this amount (USD).
+  #[serde(default)]
+  pub soft_limit_usd: Option<f64>,
+  /// Refuse further model requests once session spend reaches this amount (USD).
+  #[serde(default)]
+  pub hard_limit_usd: Option<f64>,
+}
+
 // ============================================================================
 // HISTORY CONFIGURATION
 // ============================================================================
@@ -648,6 +700,28 @@ pub struct AgentConfig {
   pub max_threads: usize,
   /// Agent roles
   pub roles: HashMap<String, AgentRoleConfig>,
+  /// Where spawned teammates write their changes
+  #[serde(default)]
+  pub isolation: AgentIsolation,
+  /// Pathspecs of untracked files a teammate worktree leaves uncommitted when
+  /// merging back; every other file not ignored by git is committed
+  #[serde(default)]
+  pub worktree_exclude_untracked: Vec<String>,
+  /// Limits applied to every spawned teammate (roles may override fields)
+  #[serde(default)]
+  pub budget: AgentBudgetConfig,
+  /// Limits applied to all spawned teammates combined
+  #[serde(default)]
+  pub team_budget: AgentBudgetConfig,
+  /// Stall detection for busy teammates
+  #[serde(default)]
+  pub liveness: AgentLivenessConfig,
+  /// Whether spawned teammates run inside this process or as child processes
+  #[serde(default)]
+  pub execution: AgentExecution,
+  /// Child process settings used when `execution = "process"`
+  #[serde(default)]
+  pub process: AgentProcessConfig,
 }
 
 impl Default for AgentConfig {
@@ -655,10 +729,123 @@ impl Default for AgentConfig {
     Self {
       max_threads: 10,
       roles: HashMap::new(),
+      isolation: AgentIsolation::default(),
+      worktree_exclude_untracked: Vec::new(),
+      budget: AgentBudgetConfig::default(),
+      team_budget: AgentBudgetConfig::default(),
+      liveness: AgentLivenessConfig::default(),
+      execution: AgentExecution::default(),
+      process: AgentProcessConfig::default(),
     }
   }
 }
 
+/// Consumption limits for spawned teammates; unset fields are unlimited
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AgentBudgetConfig {
+  /// Total model tokens (input + output)
+  pub max_tokens: Option<u64>,
+  /// Model spend in USD, from catalog pricing
+  pub max_cost_usd: Option<f64>,
+  /// Seconds since the teammate (re)started
+  pub max_wall_clock_secs: Option<u64>,
+  /// Completed turns
+  pub max_turns: Option<u64>,
+  /// Tool calls dispatched
+  pub max_tool_calls: Option<u64>,
+}
+
+/// Stall thresholds for busy teammates, in seconds without any event;
+/// `0` skips that step
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct AgentLivenessConfig {
+  /// Quiet time before the teammate is nudged
+  #[serde(default = "default_liveness_nudge_after_secs")]
+  pub nudge_after_secs: u64,
+  /// Quiet time before its turn is interrupted and restarted; off by default,
+  /// since a long build or test run can be quiet without being stuck
+  #[serde(default)]
+  pub restart_after_secs: u64,
+  /// Quiet time before the leader is asked to step in
+  #[serde(default = "default_liveness_escalate_after_secs")]
+  pub escalate_after_secs: u64,
+  /// How often busy teammates are checked
+  #[serde(default = "default_liveness_check_interval_secs")]
+  pub check_interval_secs: u64,
+}
+
+fn default_liveness_nudge_after_secs() -> u64 {
+  120
+}
+
+fn default_liveness_escalate_after_secs() -> u64 {
+  600
+}
+
+fn default_liveness_check_interval_secs() -> u64 {
+  15
+}
+
+impl Default for AgentLivenessConfig {
+  fn default() -> Self {
+    Self {
+      nudge_after_secs: default_liveness_nudge_after_secs(),
+      restart_after_secs: 0,
+      escalate_after_secs: default_liveness_escalate_after_secs(),
+      check_interval_secs: default_liveness_check_interval_secs(),
+    }
+  }
+}
+
+/// How spawned teammates are run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum AgentExecution {
+  /// Every teammate is a task inside this process
+  #[default]
+  InProcess,
+  /// Every teammate is a child `cokra` process talking to this one over a
+  /// Unix domain socket, so a crash or runaway tool only takes down that
+  /// teammate (Unix only)
+  Process,
+}
+
+/// Child process settings for out-of-process teammates
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct AgentProcessConfig {
+  /// Executable started for each teammate; defaults to the running `cokra`
+  #[serde(default)]
+  pub program: Option<PathBuf>,
+  /// Consecutive crashes of one teammate before it is marked failed
+  #[serde(default = "default_agent_process_max_restarts")]
+  pub max_restarts: u32,
+}
+
+fn default_agent_process_max_restarts() -> u32 {
+  3
+}
+
+impl Default for AgentProcessConfig {
+  fn default() -> Self {
+    Self {
+      program: None,
+      max_restarts: default_agent_process_max_restarts(),
+    }
+  }
+}
+
+/// Workspace isolation for spawned teammates
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AgentIsolation {
+  /// Every teammate works in the session cwd; ownership leases keep writers apart
+  #[default]
+  Shared,
+  /// Every teammate gets its own `git worktree` on a `cokra/<nickname>` branch
+  /// that is merged back when its task completes
+  Worktree,
+}
+
 /// Agent role configuration
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AgentRoleConfig {
@@ -666,6 +853,26 @@ pub struct AgentRoleConfig {
   pub description: Option<String>,
   /// Config file path
   pub config_file: Option<String>,
+  /// Model for teammates spawned with this role (`provider/model` or a bare id)
+  pub model: Option<String>,
+  /// Provider for teammates spawned with this role
+  pub provider: Option<String>,
+  /// Reasoning effort for teammates spawned with this role
+  pub reasoning_effort: Option<AgentReasoningEffort>,
+  /// Sampling temperature for teammates spawned with this role
+  pub temperature: Option<f32>,
+  /// Per-teammate limits for this role; set fields override `[agents.budget]`
+  pub budget: Option<AgentBudgetConfig>,
+}
+
+/// Reasoning effort a role can request for its teammates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AgentReasoningEffort {
+  Minimal,
+  Low,
+  Medium,
+  High,
 }
 
 // ============================================================================
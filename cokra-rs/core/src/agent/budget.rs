@@ -0,0 +1,155 @@
+//! Consumption budgets for spawned teammates.
+//!
+//! `[agents.budget]` applies to each teammate, `[agents.roles.<name>.budget]`
+//! overrides it field by field, and `[agents.team_budget]` caps all teammates
+//! combined. Limits are checked before each turn and after every model request
+//! or tool call; a teammate that crosses one has its turn interrupted and is
+//! blocked until the leader closes or replaces it. Usage and the blocked state
+//! are kept in the team roster, so a resumed teammate picks up where it was.
+
+use cokra_config::AgentBudgetConfig;
+use cokra_config::AgentConfig;
+use cokra_protocol::TeamBudget;
+use cokra_protocol::TeamMemberUsage;
+
+/// Budget for a teammate spawned with `role`.
+pub(crate) fn agent_budget(agents: &AgentConfig, role: &str) -> TeamBudget {
+  let base = &agents.budget;
+  let Some(role_budget) = agents.roles.get(role).and_then(|role| role.budget.as_ref()) else {
+    return to_team_budget(base);
+  };
+  TeamBudget {
+    max_tokens: role_budget.max_tokens.or(base.max_tokens),
+    max_cost_usd: role_budget.max_cost_usd.or(base.max_cost_usd),
+    max_wall_clock_secs: role_budget.max_wall_clock_secs.or(base.max_wall_clock_secs),
+    max_turns: role_budget.max_turns.or(base.max_turns),
+    max_tool_calls: role_budget.max_tool_calls.or(base.max_tool_calls),
+  }
+}
+
+/// Budget shared by every teammate.
+pub(crate) fn team_budget(agents: &AgentConfig) -> TeamBudget {
+  to_team_budget(&agents.team_budget)
+}
+
+fn to_team_budget(config: &AgentBudgetConfig) -> TeamBudget {
+  TeamBudget {
+    max_tokens: config.max_tokens,
+    max_cost_usd: config.max_cost_usd,
+    max_wall_clock_secs: config.max_wall_clock_secs,
+    max_turns: config.max_turns,
+    max_tool_calls: config.max_tool_calls,
+  }
+}
+
+/// Adds `usage` into `total`. Elapsed time is the longest, not the sum: the
+/// team budget caps how long the team has been running.
+pub(crate) fn accumulate(total: &mut TeamMemberUsage, usage: &TeamMemberUsage) {
+  total.total_tokens += usage.total_tokens;
+  total.cost_usd += usage.cost_usd;
+  total.turns += usage.turns;
+  total.tool_calls += usage.tool_calls;
+  total.elapsed_secs = total.elapsed_secs.max(usage.elapsed_secs);
+}
+
+/// The first limit `usage` has reached, described for the leader.
+pub(crate) fn exhausted(usage: &TeamMemberUsage, budget: &TeamBudget) -> Option<String> {
+  let tokens = u64::try_from(usage.total_tokens).unwrap_or_default();
+  if let Some(max) = budget.max_tokens
+    && tokens >= max
+  {
+    return Some(format!("token budget of {max} reached ({tokens} used)"));
+  }
+  if let Some(max) = budget.max_cost_usd
+    && usage.cost_usd >= max
+  {
+    return Some(format!(
+      "cost budget of ${max:.2} reached (${:.2} spent)",
+      usage.cost_usd
+    ));
+  }
+  if let Some(max) = budget.max_wall_clock_secs
+    && usage.elapsed_secs >= max
+  {
+    return Some(format!(
+      "time budget of {max}s reached ({}s elapsed)",
+      usage.elapsed_secs
+    ));
+  }
+  if let Some(max) = budget.max_turns
+    && usage.turns >= max
+  {
+    return Some(format!("turn budget of {max} reached"));
+  }
+  if let Some(max) = budget.max_tool_calls
+    && usage.tool_calls >= max
+  {
+    return Some(format!(
+      "tool call budget of {max} reached ({} calls)",
+      usage.tool_calls
+    ));
+  }
+  None
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn role_budget_overrides_agent_budget_field_by_field() {
+    let mut agents = AgentConfig::default();
+    agents.budget.max_tokens = Some(10_000);
+    agents.budget.max_turns = Some(20);
+    agents.roles.insert(
+      "scout".to_string(),
+      cokra_config::AgentRoleConfig {
+        description: None,
+        config_file: None,
+        model: None,
+        provider: None,
+        reasoning_effort: None,
+        temperature: None,
+        budget: Some(AgentBudgetConfig {
+          max_turns: Some(3),
+          ..Default::default()
+        }),
+      },
+    );
+
+    let scout = agent_budget(&agents, "scout");
+    assert_eq!(scout.max_tokens, Some(10_000));
+    assert_eq!(scout.max_turns, Some(3));
+    assert_eq!(agent_budget(&agents, "coder").max_turns, Some(20));
+    assert!(team_budget(&agents).is_unlimited());
+  }
+
+  #[test]
+  fn reports_the_first_exhausted_limit() {
+    let budget = TeamBudget {
+      max_cost_usd: Some(1.0),
+      max_tool_calls: Some(5),
+      ..Default::default()
+    };
+    let mut usage = TeamMemberUsage {
+      cost_usd: 0.5,
+      tool_calls: 4,
+      ..Default::default()
+    };
+    assert_eq!(exhausted(&usage, &budget), None);
+
+    usage.tool_calls = 5;
+    assert_eq!(
+      exhausted(&usage, &budget).as_deref(),
+      Some("tool call budget of 5 reached (5 calls)")
+    );
+
+    let mut total = TeamMemberUsage::default();
+    accumulate(&mut total, &usage);
+    accumulate(&mut total, &usage);
+    assert_eq!(
+      exhausted(&total, &budget).as_deref(),
+      Some("cost budget of $1.00 reached ($1.00 spent)")
+    );
+  }
+}
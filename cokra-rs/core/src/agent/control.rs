@@ -13,6 +13,7 @@ use cokra_protocol::ThreadId;
 
 use crate::model::ModelClient;
 use crate::session::Session;
+use crate::thread_manager::ThreadInfo;
 use crate::thread_manager::ThreadManagerState;
 use crate::tool_runtime::UnifiedToolRuntime;
 use crate::tools::registry::ToolRegistry;
@@ -136,6 +137,11 @@ impl AgentControl {
     }
   }
 
+  /// Settle the status of a turn that was dropped mid-flight.
+  pub(crate) async fn abort_turn(&self) {
+    self.transition(AgentStatus::Ready).await;
+  }
+
   pub async fn stop(&self) -> anyhow::Result<()> {
     self.transition(AgentStatus::Shutdown).await;
     Ok(())
@@ -149,6 +155,15 @@ impl AgentControl {
     self.turn_config.read().await.clone()
   }
 
+  /// Current model without waiting; `None` while the turn config is being replaced.
+  pub(crate) fn current_model(&self) -> Option<String> {
+    self
+      .turn_config
+      .try_read()
+      .ok()
+      .map(|config| config.model.clone())
+  }
+
   pub async fn status(&self) -> AgentStatus {
     self.status.read().await.clone()
   }
@@ -157,6 +172,10 @@ impl AgentControl {
     self.root_thread_id.clone()
   }
 
+  pub fn session(&self) -> Arc<Session> {
+    Arc::clone(&self.session)
+  }
+
   pub fn guards(&self) -> Arc<Guards> {
     Arc::clone(&self.guards)
   }
@@ -237,6 +256,24 @@ impl AgentControl {
     Ok(thread_id)
   }
 
+  /// Re-register a teammate thread from a previous process under its original id.
+  pub(crate) fn restore_spawned_agent(
+    &self,
+    info: ThreadInfo,
+    max_threads: Option<usize>,
+  ) -> anyhow::Result<()> {
+    let manager = self.upgrade_manager()?;
+    let reservation = self
+      .guards
+      .reserve_spawn_slot(max_threads)
+      .map_err(anyhow::Error::from)?;
+    let thread_id = info.thread_id.clone();
+    manager.restore_thread(info);
+    reservation.commit(thread_id.clone());
+    manager.notify_thread_created(thread_id);
+    Ok(())
+  }
+
   pub fn shutdown_spawned_agent(&self, thread_id: ThreadId) -> anyhow::Result<()> {
     let manager = self.upgrade_manager()?;
     if manager.remove_thread(&thread_id) {
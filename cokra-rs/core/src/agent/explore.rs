@@ -0,0 +1,70 @@
+//! One-shot, read-only exploration subagents.
+//!
+//! `explore` runs a single isolated turn restricted to read-only tools and
+//! hands back only its final answer. Unlike `spawn_agent` it creates no
+//! teammate, mailbox, task or lease, and none of the subagent's tool traffic
+//! reaches the caller's history. `[agents.roles.explorer]` picks its model,
+//! so exploration can run on a cheaper one than the leader.
+
+/// Role whose `[agents.roles.<name>]` entry configures explorers.
+pub(crate) const EXPLORER_ROLE: &str = "explorer";
+
+/// Tools an explorer may call.
+pub(crate) const EXPLORER_TOOLS: &[&str] = &[
+  "read_file",
+  "read_many_files",
+  "list_dir",
+  "grep_files",
+  "glob",
+  "lsp",
+  "code_search",
+];
+
+/// Longest answer handed back to the caller, in characters.
+const MAX_ANSWER_CHARS: usize = 8_000;
+
+/// The caller's prompt without the orchestrator rules, plus the explorer contract.
+pub(crate) fn build_explorer_system_prompt(base: Option<&str>) -> String {
+  match base {
+    Some(base) => format!(
+      "{}{}",
+      base
+        .strip_suffix(crate::prompts::AGENT_LEADER_SUFFIX)
+        .unwrap_or(base),
+      crate::prompts::AGENT_EXPLORER_SUFFIX
+    ),
+    None => crate::prompts::AGENT_EXPLORER_SUFFIX
+      .trim_start()
+      .to_string(),
+  }
+}
+
+/// Trim the explorer's final message to what the caller's context can afford.
+pub(crate) fn condense_answer(answer: &str) -> String {
+  let answer = answer.trim();
+  match answer.char_indices().nth(MAX_ANSWER_CHARS) {
+    Some((cut, _)) => format!(
+      "{}\n\n[explorer answer truncated after {MAX_ANSWER_CHARS} characters]",
+      answer[..cut].trim_end()
+    ),
+    None => answer.to_string(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn long_answers_are_truncated_on_a_char_boundary() {
+    assert_eq!(
+      condense_answer("  auth lives in auth.rs\n"),
+      "auth lives in auth.rs"
+    );
+
+    let long = "é".repeat(MAX_ANSWER_CHARS + 10);
+    let condensed = condense_answer(&long);
+    assert!(condensed.starts_with(&"é".repeat(MAX_ANSWER_CHARS)));
+    assert!(condensed.ends_with("characters]"));
+  }
+}
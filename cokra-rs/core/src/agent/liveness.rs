@@ -0,0 +1,258 @@
+//! Stall detection for busy teammates.
+//!
+//! Every event a teammate's turn emits refreshes its liveness, and tool events
+//! also count as tool progress. A busy teammate that stays quiet past the
+//! `[agents.liveness]` thresholds is first nudged, then (when enabled) has its
+//! turn interrupted and restarted, and finally is escalated to the leader. A
+//! teammate waiting on an approval or a user answer is not stalled.
+
+use chrono::Utc;
+use cokra_config::AgentLivenessConfig;
+use cokra_protocol::EventMsg;
+use cokra_protocol::TeamMemberLiveness;
+use tokio::time::Instant;
+
+/// Unix time in seconds, advanced by the tokio clock so a paused test clock
+/// drives stall detection.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LivenessClock {
+  origin: Instant,
+  origin_unix: i64,
+}
+
+impl LivenessClock {
+  pub(crate) fn new() -> Self {
+    Self {
+      origin: Instant::now(),
+      origin_unix: Utc::now().timestamp(),
+    }
+  }
+
+  pub(crate) fn now(&self) -> i64 {
+    self.origin_unix + i64::try_from(self.origin.elapsed().as_secs()).unwrap_or(i64::MAX)
+  }
+}
+
+/// How far stall recovery has gone for the current piece of work.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum StallStage {
+  #[default]
+  Healthy,
+  Nudged,
+  Restarted,
+  Escalated,
+}
+
+/// Next recovery step for a stalled teammate, with its quiet time in seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StallAction {
+  Nudge { quiet_secs: u64 },
+  Restart { quiet_secs: u64 },
+  Escalate { quiet_secs: u64 },
+}
+
+impl StallAction {
+  /// Attention reason recorded on the teammate when the step is taken.
+  pub(crate) fn attention_reason(&self) -> String {
+    match self {
+      Self::Nudge { quiet_secs } => format!("no progress for {quiet_secs}s: nudged"),
+      Self::Restart { quiet_secs } => {
+        format!("no progress for {quiet_secs}s: turn interrupted and restarted")
+      }
+      Self::Escalate { quiet_secs } => {
+        format!("no progress for {quiet_secs}s: escalated to the leader")
+      }
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct AgentLiveness {
+  last_event_at: i64,
+  last_tool_progress_at: Option<i64>,
+  awaiting_input: bool,
+  stage: StallStage,
+  restart_pending: bool,
+}
+
+impl AgentLiveness {
+  pub(crate) fn new(now: i64) -> Self {
+    Self {
+      last_event_at: now,
+      last_tool_progress_at: None,
+      awaiting_input: false,
+      stage: StallStage::Healthy,
+      restart_pending: false,
+    }
+  }
+
+  pub(crate) fn record_event(&mut self, event: &EventMsg, now: i64) {
+    self.last_event_at = now;
+    if is_tool_progress(event) {
+      self.last_tool_progress_at = Some(now);
+    }
+    self.awaiting_input = awaits_input(event);
+    // A nudged teammate that speaks up again gets a fresh nudge next time; one
+    // that was already restarted goes straight to escalation if it stalls again.
+    if self.stage == StallStage::Nudged {
+      self.stage = StallStage::Healthy;
+    }
+  }
+
+  /// Reset the quiet clock for a new turn. Returns true when the turn is the
+  /// restart of a stalled one, which keeps its recovery stage.
+  pub(crate) fn begin_turn(&mut self, now: i64) -> bool {
+    self.last_event_at = now;
+    self.awaiting_input = false;
+    if std::mem::take(&mut self.restart_pending) {
+      return true;
+    }
+    self.stage = StallStage::Healthy;
+    false
+  }
+
+  /// Advance to the next enabled recovery step once its threshold has passed.
+  pub(crate) fn next_action(
+    &mut self,
+    config: &AgentLivenessConfig,
+    now: i64,
+  ) -> Option<StallAction> {
+    if self.awaiting_input || self.restart_pending {
+      return None;
+    }
+    let (stage, threshold) = [
+      (StallStage::Nudged, config.nudge_after_secs),
+      (StallStage::Restarted, config.restart_after_secs),
+      (StallStage::Escalated, config.escalate_after_secs),
+    ]
+    .into_iter()
+    .find(|(stage, threshold)| *stage > self.stage && *threshold > 0)?;
+    let quiet_secs = u64::try_from(now - self.last_event_at).unwrap_or_default();
+    if quiet_secs < threshold {
+      return None;
+    }
+    self.stage = stage;
+    Some(match stage {
+      StallStage::Nudged => StallAction::Nudge { quiet_secs },
+      StallStage::Restarted => {
+        self.restart_pending = true;
+        StallAction::Restart { quiet_secs }
+      }
+      StallStage::Healthy | StallStage::Escalated => StallAction::Escalate { quiet_secs },
+    })
+  }
+
+  pub(crate) fn snapshot(&self) -> TeamMemberLiveness {
+    TeamMemberLiveness {
+      last_event_at: self.last_event_at,
+      last_tool_progress_at: self.last_tool_progress_at,
+    }
+  }
+}
+
+fn is_tool_progress(event: &EventMsg) -> bool {
+  matches!(
+    event,
+    EventMsg::ExecCommandBegin(_)
+      | EventMsg::ExecCommandOutputDelta(_)
+      | EventMsg::TerminalInteraction(_)
+      | EventMsg::ExecCommandEnd(_)
+      | EventMsg::McpToolCallBegin(_)
+      | EventMsg::McpToolCallProgress(_)
+      | EventMsg::McpToolCallEnd(_)
+      | EventMsg::WebSearchBegin(_)
+      | EventMsg::WebSearchEnd(_)
+      | EventMsg::PatchApplyBegin(_)
+      | EventMsg::PatchApplyEnd(_)
+      | EventMsg::ViewImageToolCall(_)
+  )
+}
+
+fn awaits_input(event: &EventMsg) -> bool {
+  matches!(
+    event,
+    EventMsg::ExecApprovalRequest(_)
+      | EventMsg::ApplyPatchApprovalRequest(_)
+      | EventMsg::RequestUserInput(_)
+      | EventMsg::DynamicToolCallRequest(_)
+      | EventMsg::ElicitationRequest(_)
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cokra_protocol::WarningEvent;
+
+  fn config() -> AgentLivenessConfig {
+    AgentLivenessConfig {
+      nudge_after_secs: 10,
+      restart_after_secs: 20,
+      escalate_after_secs: 30,
+      check_interval_secs: 1,
+    }
+  }
+
+  #[test]
+  fn quiet_teammate_is_nudged_restarted_then_escalated() {
+    let config = config();
+    let mut liveness = AgentLiveness::new(0);
+    assert!(!liveness.begin_turn(0));
+    assert_eq!(liveness.next_action(&config, 9), None);
+    assert_eq!(
+      liveness.next_action(&config, 12),
+      Some(StallAction::Nudge { quiet_secs: 12 })
+    );
+    assert_eq!(liveness.next_action(&config, 15), None);
+    assert_eq!(
+      liveness.next_action(&config, 21),
+      Some(StallAction::Restart { quiet_secs: 21 })
+    );
+    // Nothing more until the restarted turn begins.
+    assert_eq!(liveness.next_action(&config, 100), None);
+    assert!(liveness.begin_turn(100));
+    assert_eq!(liveness.next_action(&config, 120), None);
+    assert_eq!(
+      liveness.next_action(&config, 130),
+      Some(StallAction::Escalate { quiet_secs: 30 })
+    );
+    assert_eq!(liveness.next_action(&config, 500), None);
+  }
+
+  #[test]
+  fn activity_and_pending_approvals_hold_off_recovery() {
+    let config = AgentLivenessConfig {
+      restart_after_secs: 0,
+      ..config()
+    };
+    let mut liveness = AgentLiveness::new(0);
+    liveness.begin_turn(0);
+    assert_eq!(
+      liveness.next_action(&config, 10),
+      Some(StallAction::Nudge { quiet_secs: 10 })
+    );
+    liveness.record_event(
+      &EventMsg::Warning(WarningEvent {
+        thread_id: "alpha".to_string(),
+        turn_id: "turn-1".to_string(),
+        message: "still here".to_string(),
+      }),
+      11,
+    );
+    assert_eq!(
+      liveness.next_action(&config, 21),
+      Some(StallAction::Nudge { quiet_secs: 10 })
+    );
+    // The restart step is disabled, so escalation is next.
+    assert_eq!(
+      liveness.next_action(&config, 41),
+      Some(StallAction::Escalate { quiet_secs: 30 })
+    );
+
+    let mut waiting = AgentLiveness::new(0);
+    waiting.begin_turn(0);
+    waiting.awaiting_input = true;
+    assert_eq!(waiting.next_action(&config, 1_000), None);
+    assert_eq!(waiting.snapshot().last_tool_progress_at, None);
+  }
+}
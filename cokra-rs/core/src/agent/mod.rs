@@ -3,12 +3,20 @@
 //! The only collaboration state center is [`team_runtime`]. Other modules here
 //! support turn execution and guardrails, but are not part of the public kernel.
 
+pub(crate) mod budget;
 pub(crate) mod control;
+pub(crate) mod explore;
 pub(crate) mod guards;
+pub(crate) mod liveness;
 pub(crate) mod role;
 pub(crate) mod status;
 pub(crate) mod team_runtime;
 pub(crate) mod team_state;
+pub(crate) mod teammate_host;
+pub(crate) mod teammate_process;
+pub(crate) mod teammate_wire;
+pub(crate) mod workflow;
+pub(crate) mod worktree;
 
 pub use control::AgentControl;
 pub use control::Turn;
@@ -1,13 +1,51 @@
+//! Agent roles: named instructions, sampling settings and tool access for
+//! spawned teammates.
+//!
+//! Roles are loaded from `.cokra/agents/*.md` (the user directory first, then
+//! project directories from the outermost ancestor down, later files winning)
+//! and from `[agents.roles.<name>]` entries whose `config_file` points at a file
+//! in the same format:
+//!
+//! ```markdown
+//! ---
+//! name: reviewer
+//! description: Reads changes and reports problems
+//! allowed_tools: [read_file, grep_files, list_dir, send_team_message]
+//! denied_tools: apply_patch, edit_file, write_file
+//! temperature: 0.1
+//! ---
+//! You review changes. Never modify files.
+//! ```
+//!
+//! Tool lists are single-line (inline `[a, b]` or comma separated); a trailing
+//! `*` matches a prefix such as `mcp__github__*`.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use cokra_config::Config;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::skills::loader::collect_markdown_files;
+use crate::skills::loader::extract_frontmatter_field;
+use crate::skills::loader::ordered_cokra_roots;
+use crate::skills::loader::split_frontmatter;
+use crate::tools::registry::ToolAccessPolicy;
+
+pub const AGENTS_DIR: &str = "agents";
+
 /// Agent role definition.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentRole {
   pub name: String,
   pub description: String,
   pub system_prompt: String,
+  /// Tools the role may call; empty means every tool.
   pub allowed_tools: Vec<String>,
+  /// Tools the role may never call, even if also allowed.
+  #[serde(default)]
+  pub denied_tools: Vec<String>,
   pub temperature: Option<f32>,
   pub max_tokens: Option<u32>,
 }
@@ -23,8 +61,259 @@ impl AgentRole {
       description: "General coding role with tool access".to_string(),
       system_prompt: "You are a pragmatic coding agent.".to_string(),
       allowed_tools: Vec::new(),
+      denied_tools: Vec::new(),
       temperature: Some(0.2),
       max_tokens: Some(4096),
     }
   }
+
+  pub(crate) fn tool_policy(&self) -> ToolAccessPolicy {
+    ToolAccessPolicy {
+      allowed: (!self.allowed_tools.is_empty()).then(|| self.allowed_tools.clone()),
+      denied: self.denied_tools.clone(),
+    }
+  }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct AgentRoleCatalog {
+  pub roles: Vec<AgentRole>,
+  pub warnings: Vec<String>,
+}
+
+pub async fn discover_agent_roles(config: &Config, cwd: &Path) -> AgentRoleCatalog {
+  let mut by_name: HashMap<String, AgentRole> = HashMap::new();
+  let mut warnings = Vec::new();
+
+  for root in ordered_cokra_roots(cwd) {
+    for path in collect_markdown_files(&root.config_dir.join(AGENTS_DIR)).await {
+      match read_role_file(&path).await {
+        Ok(role) => {
+          by_name.insert(role.name.clone(), role);
+        }
+        Err(err) => warnings.push(format!("failed to load role {}: {err}", path.display())),
+      }
+    }
+  }
+
+  // Config entries win over discovered files: they are the user's explicit
+  // choice for that role name.
+  for (name, role_config) in &config.agents.roles {
+    let mut role = match role_config.config_file.as_deref() {
+      Some(config_file) => {
+        let path = cwd.join(config_file);
+        match read_role_file(&path).await {
+          Ok(role) => role,
+          Err(err) => {
+            warnings.push(format!("failed to load role {}: {err}", path.display()));
+            continue;
+          }
+        }
+      }
+      None => by_name.remove(name).unwrap_or_else(|| AgentRole {
+        name: name.clone(),
+        description: String::new(),
+        system_prompt: String::new(),
+        allowed_tools: Vec::new(),
+        denied_tools: Vec::new(),
+        temperature: None,
+        max_tokens: None,
+      }),
+    };
+    role.name = name.clone();
+    if let Some(description) = role_config.description.clone() {
+      role.description = description;
+    }
+    by_name.insert(name.clone(), role);
+  }
+
+  for warning in &warnings {
+    tracing::warn!("{warning}");
+  }
+  let mut roles = by_name.into_values().collect::<Vec<_>>();
+  roles.sort_by(|left, right| left.name.cmp(&right.name));
+  AgentRoleCatalog { roles, warnings }
+}
+
+pub async fn load_agent_role(config: &Config, cwd: &Path, name: &str) -> Option<AgentRole> {
+  discover_agent_roles(config, cwd)
+    .await
+    .roles
+    .into_iter()
+    .find(|role| role.name == name)
+}
+
+pub(crate) fn build_spawn_agent_description(roles: &[AgentRole]) -> String {
+  let base = "Spawn a sub-agent and immediately start it on an initial task.";
+  if roles.is_empty() {
+    return base.to_string();
+  }
+
+  let role_lines = roles
+    .iter()
+    .map(|role| {
+      let mut line = format!("- {}", role.name);
+      if !role.description.is_empty() {
+        line.push_str(&format!(": {}", role.description));
+      }
+      if !role.allowed_tools.is_empty() {
+        line.push_str(&format!(" (tools: {})", role.allowed_tools.join(", ")));
+      }
+      if !role.denied_tools.is_empty() {
+        line.push_str(&format!(" (cannot use: {})", role.denied_tools.join(", ")));
+      }
+      line
+    })
+    .collect::<Vec<_>>()
+    .join("\n");
+  format!("{base}\n\nAvailable roles (pass one as `role`):\n{role_lines}")
+}
+
+async fn read_role_file(path: &Path) -> Result<AgentRole, String> {
+  let raw = tokio::fs::read_to_string(path)
+    .await
+    .map_err(|err| format!("read error: {err}"))?;
+  let fallback_name = path
+    .file_stem()
+    .and_then(|stem| stem.to_str())
+    .unwrap_or("unnamed");
+  parse_role(&raw, fallback_name)
+}
+
+fn parse_role(raw: &str, fallback_name: &str) -> Result<AgentRole, String> {
+  let Some((frontmatter, body)) = split_frontmatter(raw) else {
+    return Ok(AgentRole {
+      name: fallback_name.to_string(),
+      description: String::new(),
+      system_prompt: raw.trim().to_string(),
+      allowed_tools: Vec::new(),
+      denied_tools: Vec::new(),
+      temperature: None,
+      max_tokens: None,
+    });
+  };
+  let field = |keys: &[&str]| {
+    keys
+      .iter()
+      .find_map(|key| extract_frontmatter_field(frontmatter, key))
+  };
+  let temperature = field(&["temperature"])
+    .map(|value| {
+      value
+        .parse::<f32>()
+        .map_err(|_| format!("invalid temperature `{value}`"))
+    })
+    .transpose()?;
+  let max_tokens = field(&["max_tokens"])
+    .map(|value| {
+      value
+        .parse::<u32>()
+        .map_err(|_| format!("invalid max_tokens `{value}`"))
+    })
+    .transpose()?;
+
+  Ok(AgentRole {
+    name: field(&["name"]).unwrap_or_else(|| fallback_name.to_string()),
+    description: field(&["description"]).unwrap_or_default(),
+    system_prompt: body.trim().to_string(),
+    allowed_tools: field(&["allowed_tools", "tools"])
+      .map(|value| parse_tool_list(&value))
+      .unwrap_or_default(),
+    denied_tools: field(&["denied_tools", "disallowed_tools"])
+      .map(|value| parse_tool_list(&value))
+      .unwrap_or_default(),
+    temperature,
+    max_tokens,
+  })
+}
+
+fn parse_tool_list(value: &str) -> Vec<String> {
+  value
+    .trim()
+    .trim_start_matches('[')
+    .trim_end_matches(']')
+    .split(',')
+    .map(|item| item.trim().trim_matches('"').trim_matches('\'').to_string())
+    .filter(|item| !item.is_empty())
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parses_frontmatter_tool_lists_and_prompt_body() {
+    let role = parse_role(
+      "---\nname: reviewer\ndescription: Reviews diffs\nallowed_tools: [read_file, \"grep_files\", mcp__github__*]\ndisallowed_tools: apply_patch, write_file\ntemperature: 0.1\n---\nNever modify files.\n",
+      "fallback",
+    )
+    .expect("role parses");
+
+    assert_eq!(role.name, "reviewer");
+    assert_eq!(role.description, "Reviews diffs");
+    assert_eq!(role.system_prompt, "Never modify files.");
+    assert_eq!(
+      role.allowed_tools,
+      vec!["read_file", "grep_files", "mcp__github__*"]
+    );
+    assert_eq!(role.denied_tools, vec!["apply_patch", "write_file"]);
+    assert_eq!(role.temperature, Some(0.1));
+
+    let policy = role.tool_policy();
+    assert!(policy.permits("mcp__github__search"));
+    assert!(!policy.permits("apply_patch"));
+    assert!(!policy.permits("shell"));
+  }
+
+  #[tokio::test]
+  async fn config_file_roles_override_discovered_roles() {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let agents_dir = temp.path().join(".cokra").join(AGENTS_DIR);
+    std::fs::create_dir_all(&agents_dir).expect("agents dir");
+    std::fs::write(
+      agents_dir.join("scout.md"),
+      "---\ndescription: Explores the codebase\ntools: read_file\n---\nExplore.\n",
+    )
+    .expect("write scout");
+    std::fs::write(
+      temp.path().join("tester.md"),
+      "---\nname: ignored\ndenied_tools: apply_patch\n---\nWrite tests.\n",
+    )
+    .expect("write tester");
+
+    let mut config = Config::default();
+    config.agents.roles.insert(
+      "tester".to_string(),
+      cokra_config::AgentRoleConfig {
+        description: Some("Writes tests".to_string()),
+        config_file: Some("tester.md".to_string()),
+        model: None,
+        provider: None,
+        reasoning_effort: None,
+        temperature: None,
+        budget: None,
+      },
+    );
+
+    let catalog = discover_agent_roles(&config, temp.path()).await;
+    let names = catalog
+      .roles
+      .iter()
+      .map(|role| role.name.as_str())
+      .collect::<Vec<_>>();
+    assert!(names.contains(&"scout"));
+    assert!(names.contains(&"tester"));
+
+    let tester = load_agent_role(&config, temp.path(), "tester")
+      .await
+      .expect("tester role");
+    assert_eq!(tester.description, "Writes tests");
+    assert_eq!(tester.system_prompt, "Write tests.");
+    assert_eq!(tester.denied_tools, vec!["apply_patch"]);
+
+    let description = build_spawn_agent_description(&catalog.roles);
+    assert!(description.contains("- scout: Explores the codebase (tools: read_file)"));
+    assert!(description.contains("- tester: Writes tests (cannot use: apply_patch)"));
+  }
 }
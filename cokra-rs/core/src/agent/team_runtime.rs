@@ -1,30 +1,50 @@
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::VecDeque;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::path::Path;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::sync::MutexGuard;
 use std::sync::OnceLock;
+use std::sync::Weak;
 use std::time::Duration;
+use std::time::Instant;
 
 use anyhow::Context;
 use chrono::Utc;
 use cokra_protocol::EventMsg;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::sync::Notify;
 use tokio::sync::mpsc;
 use tokio::sync::watch;
 use tokio::time::timeout;
 use uuid::Uuid;
 
+use cokra_config::AgentExecution;
+use cokra_config::AgentIsolation;
+use cokra_config::AgentReasoningEffort;
 use cokra_config::Config;
+use cokra_protocol::AskForApproval;
 use cokra_protocol::CollabAgentLifecycle;
 use cokra_protocol::CollabAgentRef;
 use cokra_protocol::CollabAgentStateChangedEvent;
 use cokra_protocol::CollabAgentStatusEntry;
 use cokra_protocol::CollabAgentWaitState;
+use cokra_protocol::CollabResumeBeginEvent;
+use cokra_protocol::CollabResumeEndEvent;
 use cokra_protocol::CollabTurnOutcome;
 use cokra_protocol::OwnershipAccessMode;
 use cokra_protocol::OwnershipLease;
 use cokra_protocol::OwnershipScopeKind;
 use cokra_protocol::ScopeRequest;
+use cokra_protocol::TeamBudget;
+use cokra_protocol::TeamDoc;
+use cokra_protocol::TeamDocSummary;
+use cokra_protocol::TeamMemberUsage;
 use cokra_protocol::TeamMessage;
 use cokra_protocol::TeamMessageDeliveryMode;
 use cokra_protocol::TeamMessageKind;
@@ -35,23 +55,54 @@ use cokra_protocol::TeamTask;
 use cokra_protocol::TeamTaskReviewState;
 use cokra_protocol::TeamTaskStatus;
 use cokra_protocol::ThreadId;
+use cokra_protocol::UserInput;
+use cokra_protocol::WarningEvent;
 use cokra_protocol::WorkflowRun;
+use cokra_protocol::WorkflowRunStatus;
 use cokra_protocol::WorkflowRuntimeSnapshot;
 use cokra_state::StateDb;
 
 use crate::agent::AgentControl;
 use crate::agent::Turn;
+use crate::agent::role::load_agent_role;
+use crate::model::Message;
 use crate::model::ModelClient;
+use crate::model::ReasoningEffort;
+use crate::model::ReasoningRequest;
+use crate::model::ReasoningSummary;
+use crate::model::client::get_model_name;
 use crate::session::Session;
+use crate::session::ThreadCost;
 use crate::thread_manager::ThreadInfo;
 use crate::thread_manager::ThreadManagerState;
+use crate::tools::build_collaboration_tooling;
 use crate::tools::build_default_tooling_with_cwd;
+use crate::tools::build_restricted_tooling;
+use crate::tools::registry::ToolRegistry;
+use crate::turn::TurnConfig;
+use crate::turn::TurnExecutor;
 
 use self::team_runs::TeamRunState;
 use super::Guards;
+use super::budget;
+use super::explore;
+use super::liveness::AgentLiveness;
+use super::liveness::LivenessClock;
+use super::liveness::StallAction;
+use super::team_state::DocWriteFailure;
 use super::team_state::TeamState;
+use super::team_state::render_doc_diff;
+use super::teammate_process::RouterProxy;
+use super::teammate_process::TeammateProcess;
+use super::teammate_process::process_launcher;
+use super::teammate_wire::TeammateInit;
+use super::workflow;
+use super::workflow::WorkflowTemplate;
+use super::worktree::MergeBackOutcome;
+use super::worktree::TeammateWorktree;
 
 const CHILD_COMMAND_CHANNEL_CAPACITY: usize = 32;
+const STALLED_TURN_RESTART_MESSAGE: &str = "Your previous turn stopped making progress and was interrupted. Re-check the current state of your task, files and mailbox, then continue where you left off.";
 const CHILD_EVENT_CHANNEL_CAPACITY: usize = 512;
 const LEASE_STALE_AFTER_SECS: i64 = 300;
 
@@ -68,9 +119,14 @@ pub(crate) struct ManagedAgentState {
   pub(crate) last_turn_summary: Option<String>,
   pub(crate) attention_reason: Option<String>,
   pub(crate) pending_wake_count: usize,
+  /// Turns finished since the agent (re)started.
+  pub(crate) turns: u64,
   pub(crate) scheduled_generation: u64,
   pub(crate) inflight_generation: u64,
   pub(crate) settled_generation: u64,
+  /// The current turn stalled and was escalated to the leader, which releases
+  /// `wait` until the next turn starts.
+  pub(crate) escalated: bool,
 }
 
 impl ManagedAgentState {
@@ -85,7 +141,7 @@ impl ManagedAgentState {
   }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 enum WakeReason {
   UserInput,
   TaskAssigned { task_id: String },
@@ -94,23 +150,359 @@ enum WakeReason {
   MailboxUnread,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct WakeRequest {
   message: String,
   reason: WakeReason,
 }
 
+/// Everything needed to rebuild a teammate after the process restarts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PersistedTeammate {
+  thread_id: String,
+  parent_thread_id: Option<String>,
+  depth: usize,
+  nickname: Option<String>,
+  role: String,
+  task: String,
+  created_at: i64,
+  /// Pinned `provider/model` the teammate was launched with.
+  model: String,
+  #[serde(default)]
+  reasoning_effort: Option<ReasoningEffort>,
+  #[serde(default)]
+  temperature: Option<f32>,
+  #[serde(default)]
+  worktree: Option<TeammateWorktree>,
+  /// Stored under its own key (see [`TeamRoster`]).
+  #[serde(skip)]
+  history: Vec<Message>,
+  #[serde(default)]
+  last_turn_summary: Option<String>,
+  /// Message of the turn that was running at the last checkpoint; replayed on resume.
+  #[serde(default)]
+  inflight_message: Option<String>,
+  #[serde(default)]
+  pending_wakes: Vec<WakeRequest>,
+  /// Consumption up to the last checkpoint, so budgets keep counting across
+  /// restarts.
+  #[serde(default)]
+  usage: TeamMemberUsage,
+  /// Why the teammate was blocked; a blocked teammate stays blocked on resume.
+  #[serde(default)]
+  blocked_reason: Option<String>,
+}
+
+/// Persisted teammates keyed by thread id.
+///
+/// The roster document holds every teammate without its history; each
+/// history lives under its own key. A checkpoint (turn start, turn end,
+/// queued wake) only writes the parts whose content changed since the last
+/// write, so a wake does not rewrite any history and a turn rewrites only its
+/// own teammate's.
+struct TeamRoster {
+  state_db: Arc<StateDb>,
+  store_key: String,
+  teammates: Mutex<HashMap<String, PersistedTeammate>>,
+  /// Fingerprints of what is on disk. Held across the writes so concurrent
+  /// checkpoints cannot store an older snapshot after a newer one.
+  written: tokio::sync::Mutex<WrittenRoster>,
+}
+
+#[derive(Default)]
+struct WrittenRoster {
+  roster: Option<u64>,
+  histories: HashMap<String, u64>,
+}
+
+impl TeamRoster {
+  async fn load(state_db: Arc<StateDb>, store_key: String) -> anyhow::Result<Self> {
+    let mut teammates =
+      load_persisted_state::<HashMap<String, PersistedTeammate>>(&state_db, &store_key, None)
+        .await?
+        .unwrap_or_default();
+    let mut written = WrittenRoster::default();
+    for (thread_id, teammate) in &mut teammates {
+      let key = history_store_key(&store_key, thread_id);
+      if let Some(history) = state_db.load_json::<Vec<Message>>(&key).await? {
+        teammate.history = history;
+      }
+      if let Some(fingerprint) = fingerprint(&teammate.history) {
+        written.histories.insert(thread_id.clone(), fingerprint);
+      }
+    }
+    Ok(Self {
+      state_db,
+      store_key,
+      teammates: Mutex::new(teammates),
+      written: tokio::sync::Mutex::new(written),
+    })
+  }
+
+  fn thread_ids(&self) -> Vec<String> {
+    self
+      .teammates
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .keys()
+      .cloned()
+      .collect()
+  }
+
+  fn get(&self, thread_id: &str) -> Option<PersistedTeammate> {
+    self
+      .teammates
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .get(thread_id)
+      .cloned()
+  }
+
+  fn worktrees(&self) -> Vec<TeammateWorktree> {
+    self
+      .teammates
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .values()
+      .filter_map(|teammate| teammate.worktree.clone())
+      .collect()
+  }
+
+  async fn upsert(&self, teammate: PersistedTeammate) {
+    let thread_id = teammate.thread_id.clone();
+    self
+      .teammates
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .insert(thread_id.clone(), teammate);
+    self.save(Some(&thread_id)).await;
+  }
+
+  async fn remove(&self, thread_id: &str) {
+    let removed = self
+      .teammates
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .remove(thread_id)
+      .is_some();
+    if removed {
+      self.save(None).await;
+      let mut written = self.written.lock().await;
+      written.histories.remove(thread_id);
+      let _ = self
+        .state_db
+        .delete(&history_store_key(&self.store_key, thread_id))
+        .await;
+    }
+  }
+
+  async fn clear(&self) {
+    let thread_ids = std::mem::take(
+      &mut *self
+        .teammates
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner),
+    )
+    .into_keys()
+    .collect::<Vec<_>>();
+    let mut written = self.written.lock().await;
+    *written = WrittenRoster::default();
+    for thread_id in thread_ids {
+      let _ = self
+        .state_db
+        .delete(&history_store_key(&self.store_key, &thread_id))
+        .await;
+    }
+    let _ = self.state_db.delete(&self.store_key).await;
+  }
+
+  async fn begin_turn(&self, handle: &ManagedAgentHandle, message: &str) {
+    self.update(handle, Some(Some(message.to_string()))).await;
+  }
+
+  async fn end_turn(&self, handle: &ManagedAgentHandle) {
+    self.update(handle, Some(None)).await;
+  }
+
+  /// Record queued wakes between turns. History is only captured at turn
+  /// boundaries, so this never rewrites it.
+  async fn checkpoint(&self, handle: &ManagedAgentHandle) {
+    self.update(handle, None).await;
+  }
+
+  /// `inflight_message` is `Some` at a turn boundary, which also captures
+  /// the history.
+  async fn update(&self, handle: &ManagedAgentHandle, inflight_message: Option<Option<String>>) {
+    let history = match inflight_message {
+      Some(_) => Some(handle.session.clone_history().await),
+      None => None,
+    };
+    let thread_id = handle.thread_id.to_string();
+    let captures_history = history.is_some();
+    let state = handle.state();
+    let usage = TeamMemberUsage {
+      budget: None,
+      ..handle.usage(&handle.session.cost_ledger().thread_costs())
+    };
+    let pending_wakes = handle
+      .wake_queue
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .iter()
+      .cloned()
+      .collect::<Vec<_>>();
+    {
+      let mut teammates = self
+        .teammates
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+      let Some(teammate) = teammates.get_mut(&thread_id) else {
+        return;
+      };
+      if let Some(history) = history {
+        teammate.history = history;
+      }
+      teammate.last_turn_summary = state.last_turn_summary;
+      teammate.pending_wakes = pending_wakes;
+      teammate.usage = usage;
+      teammate.blocked_reason = (state.lifecycle == CollabAgentLifecycle::Blocked)
+        .then_some(state.attention_reason)
+        .flatten();
+      if let Some(inflight_message) = inflight_message {
+        teammate.inflight_message = inflight_message;
+      }
+    }
+    self
+      .save(captures_history.then_some(thread_id.as_str()))
+      .await;
+  }
+
+  /// Write the histories and the roster document that changed since the
+  /// last save. Only `updated`'s history and histories never written are
+  /// compared; they go first so the roster never names a teammate whose
+  /// history is missing.
+  async fn save(&self, updated: Option<&str>) {
+    let mut written = self.written.lock().await;
+    let (histories, roster) = {
+      let teammates = self
+        .teammates
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+      let histories = teammates
+        .iter()
+        .filter(|(thread_id, _)| {
+          updated == Some(thread_id.as_str()) || !written.histories.contains_key(*thread_id)
+        })
+        .filter_map(|(thread_id, teammate)| {
+          let fingerprint = fingerprint(&teammate.history)?;
+          (written.histories.get(thread_id) != Some(&fingerprint))
+            .then(|| (thread_id.clone(), teammate.history.clone(), fingerprint))
+        })
+        .collect::<Vec<_>>();
+      // Sorted so an unchanged roster always has the same fingerprint.
+      let roster = serde_json::to_value(teammates.iter().collect::<BTreeMap<_, _>>()).ok();
+      (histories, roster)
+    };
+
+    for (thread_id, history, fingerprint) in histories {
+      let key = history_store_key(&self.store_key, &thread_id);
+      match self.state_db.save_json(&key, &history).await {
+        Ok(()) => {
+          written.histories.insert(thread_id, fingerprint);
+        }
+        Err(err) => tracing::warn!("failed to persist history of teammate {thread_id}: {err}"),
+      }
+    }
+
+    let Some(fingerprint) = roster.as_ref().and_then(fingerprint) else {
+      return;
+    };
+    if written.roster == Some(fingerprint) {
+      return;
+    }
+    match self.state_db.save_json(&self.store_key, &roster).await {
+      Ok(()) => written.roster = Some(fingerprint),
+      Err(err) => tracing::warn!("failed to persist team roster: {err}"),
+    }
+  }
+}
+
+fn history_store_key(roster_store_key: &str, thread_id: &str) -> String {
+  format!("{roster_store_key}::{thread_id}::history")
+}
+
+/// Hash of a value's JSON form, to tell whether it changed since last written.
+fn fingerprint(value: &impl Serialize) -> Option<u64> {
+  let json = serde_json::to_vec(value).ok()?;
+  let mut hasher = std::collections::hash_map::DefaultHasher::new();
+  json.hash(&mut hasher);
+  Some(hasher.finish())
+}
+
+/// What a one-shot explorer hands back to its caller.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct ExploreReport {
+  pub(crate) model: String,
+  pub(crate) answer: String,
+  pub(crate) tool_calls: u64,
+}
+
+/// Model settings requested for one teammate. Unset fields fall back to the
+/// role's `[agents.roles.<role>]` entry, then to the leader's turn config.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct AgentModelOverrides {
+  pub(crate) model: Option<String>,
+  pub(crate) provider: Option<String>,
+  pub(crate) reasoning_effort: Option<ReasoningEffort>,
+  pub(crate) temperature: Option<f32>,
+}
+
 #[derive(Clone)]
 pub(crate) struct ManagedAgentHandle {
   thread_id: ThreadId,
+  model: String,
   session: Arc<Session>,
   tx_cmd: mpsc::Sender<ChildCommand>,
   state_tx: watch::Sender<ManagedAgentState>,
   state_rx: watch::Receiver<ManagedAgentState>,
   wake_queue: Arc<Mutex<VecDeque<WakeRequest>>>,
+  tool_registry: Arc<ToolRegistry>,
+  started_at: Instant,
+  budget: TeamBudget,
+  liveness: Arc<Mutex<AgentLiveness>>,
+  /// Drops the in-flight turn of a stalled teammate so it can be restarted.
+  interrupt: Arc<Notify>,
+  /// Set when the teammate runs in a child process instead of this one.
+  process: Option<Arc<TeammateProcess>>,
+  /// What the teammate consumed before the session restarted.
+  prior_usage: TeamMemberUsage,
 }
 
 impl ManagedAgentHandle {
+  fn usage(&self, thread_costs: &HashMap<String, ThreadCost>) -> TeamMemberUsage {
+    let cost = thread_costs.get(&self.thread_id.to_string());
+    let prior = &self.prior_usage;
+    TeamMemberUsage {
+      total_tokens: prior.total_tokens + cost.map_or(0, |cost| cost.total_tokens as i64),
+      cost_usd: prior.cost_usd + cost.map_or(0.0, |cost| cost.cost_usd),
+      turns: prior.turns + self.state().turns,
+      tool_calls: prior.tool_calls
+        + match &self.process {
+          Some(process) => process.tool_calls(),
+          None => self.tool_registry.dispatched_calls(),
+        },
+      elapsed_secs: prior.elapsed_secs + self.started_at.elapsed().as_secs(),
+      budget: (!self.budget.is_unlimited()).then(|| self.budget.clone()),
+    }
+  }
+
+  fn liveness(&self) -> MutexGuard<'_, AgentLiveness> {
+    self
+      .liveness
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+  }
+
   pub(crate) async fn send_turn_now(&self, message: String) -> anyhow::Result<()> {
     self
       .tx_cmd
@@ -200,6 +592,9 @@ impl ManagedAgentHandle {
     approval_id: &str,
     decision: cokra_protocol::ReviewDecision,
   ) -> bool {
+    if let Some(process) = &self.process {
+      return process.notify_exec_approval(approval_id, decision).await;
+    }
     self
       .session
       .notify_exec_approval(approval_id, decision)
@@ -211,8 +606,21 @@ impl ManagedAgentHandle {
     request_id: &str,
     response: cokra_protocol::user_input::RequestUserInputResponse,
   ) -> bool {
+    if let Some(process) = &self.process {
+      return process.notify_user_input(request_id, response).await;
+    }
     self.session.notify_user_input(request_id, response).await
   }
+
+  /// Inject `items` into the teammate's running turn.
+  async fn steer(&self, items: Vec<UserInput>) {
+    match &self.process {
+      Some(process) => process.steer(items).await,
+      None => {
+        let _ = self.session.steer_input(None, items).await;
+      }
+    }
+  }
 }
 
 pub(crate) struct TeamRuntime {
@@ -231,6 +639,12 @@ pub(crate) struct TeamRuntime {
   run_state: Arc<Mutex<TeamRunState>>,
   mailbox_version_tx: watch::Sender<u64>,
   state_db: Arc<StateDb>,
+  /// Teammate checkouts keyed by thread id; empty unless `agents.isolation = "worktree"`.
+  worktrees: Mutex<HashMap<String, TeammateWorktree>>,
+  roster: Arc<TeamRoster>,
+  /// Usage of closed teammates, so the team budget keeps counting it.
+  closed_usage: Mutex<TeamMemberUsage>,
+  liveness_clock: LivenessClock,
 }
 
 static TEAM_RUNTIMES: OnceLock<Mutex<Vec<Arc<TeamRuntime>>>> = OnceLock::new();
@@ -273,6 +687,12 @@ pub(crate) async fn register_team_runtime(
   let persisted =
     load_persisted_state::<TeamState>(&state_db, &team_store_key, Some(&legacy_store_key)).await?;
   let run_state = load_persisted_state::<TeamRunState>(&state_db, &run_store_key, None).await?;
+  let roster = TeamRoster::load(
+    state_db.clone(),
+    scoped_store_key("teammates", &legacy_store_key),
+  )
+  .await?;
+  let check_interval_secs = config.agents.liveness.check_interval_secs;
   let mailbox_version = persisted
     .as_ref()
     .map(TeamState::mailbox_version)
@@ -293,17 +713,40 @@ pub(crate) async fn register_team_runtime(
     team_state: Arc::new(Mutex::new(persisted.unwrap_or_default())),
     run_state: Arc::new(Mutex::new(run_state.unwrap_or_default())),
     mailbox_version_tx,
+    roster: Arc::new(roster),
     state_db,
+    worktrees: Mutex::new(HashMap::new()),
+    closed_usage: Mutex::new(TeamMemberUsage::default()),
+    liveness_clock: LivenessClock::new(),
   });
 
   let mut runtimes = runtime_registry()
     .lock()
     .unwrap_or_else(std::sync::PoisonError::into_inner);
   runtimes.retain(|item| item.root_thread_id != root_thread_id);
+  spawn_liveness_monitor(Arc::downgrade(&runtime), check_interval_secs);
   runtimes.push(runtime);
   Ok(())
 }
 
+/// Check busy teammates for stalls until the runtime is dropped.
+fn spawn_liveness_monitor(runtime: Weak<TeamRuntime>, check_interval_secs: u64) {
+  if check_interval_secs == 0 {
+    return;
+  }
+  tokio::spawn(async move {
+    let mut ticker = tokio::time::interval(Duration::from_secs(check_interval_secs));
+    ticker.tick().await;
+    loop {
+      ticker.tick().await;
+      let Some(runtime) = runtime.upgrade() else {
+        break;
+      };
+      runtime.check_liveness().await;
+    }
+  });
+}
+
 fn scoped_store_key(scope: &str, base_key: &str) -> String {
   format!("{scope}::{base_key}")
 }
@@ -383,16 +826,38 @@ impl TeamRuntime {
         (thread.thread_id.to_string(), status)
       })
       .collect();
-    let mut team_state = self
+    let mut snapshot = self
       .team_state
       .lock()
-      .unwrap_or_else(std::sync::PoisonError::into_inner);
-    team_state.snapshot(
-      self.root_thread_id.to_string(),
-      threads,
-      statuses,
-      Some(self.run_snapshot()),
-    )
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .snapshot(
+        self.root_thread_id.to_string(),
+        threads,
+        statuses,
+        Some(self.run_snapshot()),
+      );
+    let thread_costs = self.agent_control.session().cost_ledger().thread_costs();
+    for member in &mut snapshot.members {
+      member.model = if member.thread_id == snapshot.root_thread_id {
+        self.agent_control.current_model()
+      } else {
+        self.agent_model(&member.thread_id)
+      };
+      if let Some(handle) = self.handle_for(&member.thread_id) {
+        member.usage = handle.usage(&thread_costs);
+        member.liveness = Some(handle.liveness().snapshot());
+      } else if let Some(cost) = thread_costs.get(&member.thread_id) {
+        member.usage = TeamMemberUsage {
+          total_tokens: cost.total_tokens as i64,
+          cost_usd: cost.cost_usd,
+          ..Default::default()
+        };
+      }
+    }
+    snapshot.team_usage = self.team_usage(&thread_costs);
+    let team_budget = budget::team_budget(&self.config.agents);
+    snapshot.team_budget = (!team_budget.is_unlimited()).then_some(team_budget);
+    snapshot
   }
 
   pub(crate) fn run_snapshot(&self) -> WorkflowRuntimeSnapshot {
@@ -503,6 +968,194 @@ impl TeamRuntime {
     plan
   }
 
+  /// Instantiate a workflow template as one run owned by `leader_thread_id`:
+  /// spawn its roles, then create its tasks with their dependencies, scopes,
+  /// review gates and acceptance step. A run that fails part way is marked
+  /// failed; teammates already spawned stay up for the leader to reuse or close.
+  pub(crate) async fn run_workflow(
+    &self,
+    leader_thread_id: &str,
+    template: &WorkflowTemplate,
+    goal: Option<&str>,
+  ) -> anyhow::Result<WorkflowRun> {
+    let mut steps = template
+      .tasks
+      .iter()
+      .map(|task| (task.id.clone(), workflow::render(&task.title, goal)))
+      .collect::<Vec<_>>();
+    if let Some(acceptance) = &template.acceptance {
+      steps.push((
+        workflow::ACCEPTANCE_STEP_ID.to_string(),
+        workflow::render(&acceptance.title, goal),
+      ));
+    }
+    let run = self
+      .run_state
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .create_run_from_template(
+        leader_thread_id.to_string(),
+        template.name.clone(),
+        template.run_title(goal),
+        steps,
+      );
+    self.persist_run_state().await;
+
+    if let Err(err) = self
+      .instantiate_workflow(&run.id, leader_thread_id, template, goal)
+      .await
+    {
+      self
+        .run_state
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .set_run_status(&run.id, WorkflowRunStatus::Failed, None, None);
+      self.persist_run_state().await;
+      return Err(err.context(format!("workflow `{}` failed to start", template.name)));
+    }
+    let run = self
+      .run_state
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .run(&run.id)
+      .unwrap_or(run);
+    Ok(run)
+  }
+
+  async fn instantiate_workflow(
+    &self,
+    run_id: &str,
+    leader_thread_id: &str,
+    template: &WorkflowTemplate,
+    goal: Option<&str>,
+  ) -> anyhow::Result<()> {
+    let mut members = HashMap::from([(workflow::LEADER.to_string(), leader_thread_id.to_string())]);
+    for role in &template.roles {
+      let thread_id = self
+        .spawn_agent(
+          leader_thread_id,
+          template.teammate_briefing(role, goal),
+          Some(role.name.clone()),
+          role.role.clone().unwrap_or_else(|| "default".to_string()),
+          AgentModelOverrides {
+            model: role.model.clone(),
+            provider: role.provider.clone(),
+            reasoning_effort: role.reasoning_effort,
+            temperature: None,
+          },
+        )
+        .await?;
+      members.insert(role.name.clone(), thread_id.to_string());
+    }
+    let member = |name: Option<&String>| name.and_then(|name| members.get(name)).cloned();
+
+    // Tasks are created with their owner but no assignee. Assignees (and
+    // reviewers) are only set once every dependency edge exists, so nobody is
+    // woken for a task that still has to wait.
+    let mut task_ids = HashMap::new();
+    for task in &template.tasks {
+      let details = template.task_details(task, goal);
+      let created = self
+        .create_task(
+          workflow::render(&task.title, goal),
+          (!details.is_empty()).then_some(details),
+          member(task.assignee.as_ref()),
+          None,
+          Some(run_id.to_string()),
+          task.scopes.clone(),
+          None,
+          false,
+        )
+        .await?;
+      self
+        .run_state
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .link_task(&created.id, run_id, &task.id, task.review_by.is_some());
+      task_ids.insert(task.id.clone(), created.id);
+    }
+    let task_id = |id: &str| {
+      task_ids
+        .get(id)
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("workflow task `{id}` was not created"))
+    };
+    for task in &template.tasks {
+      for dependency in &task.depends_on {
+        self
+          .add_task_dependency(
+            &task_id(&task.id)?,
+            &task_id(dependency)?,
+            Some(format!("waits for `{dependency}`")),
+          )
+          .await;
+      }
+    }
+
+    let mut assignments = template
+      .tasks
+      .iter()
+      .map(|task| {
+        Ok((
+          task_id(&task.id)?,
+          member(task.assignee.as_ref()),
+          member(task.review_by.as_ref()),
+        ))
+      })
+      .collect::<anyhow::Result<Vec<_>>>()?;
+    if let Some(acceptance) = &template.acceptance {
+      let created = self
+        .create_task(
+          workflow::render(&acceptance.title, goal),
+          acceptance
+            .details
+            .as_deref()
+            .map(|details| workflow::render(details, goal)),
+          Some(leader_thread_id.to_string()),
+          None,
+          Some(run_id.to_string()),
+          Vec::new(),
+          None,
+          false,
+        )
+        .await?;
+      self
+        .run_state
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .link_task(&created.id, run_id, workflow::ACCEPTANCE_STEP_ID, false);
+      for sink in template.sink_task_ids() {
+        self
+          .add_task_dependency(
+            &created.id,
+            &task_id(sink)?,
+            Some(format!("waits for `{sink}`")),
+          )
+          .await;
+      }
+      assignments.push((created.id, Some(leader_thread_id.to_string()), None));
+    }
+
+    for (task_id, assignee, reviewer) in assignments {
+      self
+        .update_task(
+          leader_thread_id,
+          &task_id,
+          None,
+          assignee.map(Some),
+          None,
+          reviewer.map(Some),
+          None,
+          None,
+          None,
+          None,
+          None,
+        )
+        .await?;
+    }
+    Ok(())
+  }
+
   pub(crate) async fn decide_plan(
     &self,
     plan_id: &str,
@@ -544,8 +1197,11 @@ impl TeamRuntime {
       .requires_approval(thread_id)
   }
 
+  /// Update a task on behalf of `actor_thread_id`. Only the task's reviewer,
+  /// or the leader when it has none, may set its review state to `Approved`.
   pub(crate) async fn update_task(
     &self,
+    actor_thread_id: &str,
     task_id: &str,
     status: Option<TeamTaskStatus>,
     assignee_thread_id: Option<Option<String>>,
@@ -577,7 +1233,30 @@ impl TeamRuntime {
         effective_scopes,
         effective_override,
       )?;
+      if review_state == Some(TeamTaskReviewState::Approved)
+        && current.review_state != TeamTaskReviewState::Approved
+      {
+        let root_thread_id = self.root_thread_id.to_string();
+        let approver = current
+          .reviewer_thread_id
+          .as_deref()
+          .unwrap_or(&root_thread_id);
+        if actor_thread_id != approver {
+          anyhow::bail!("only {approver} can approve task {task_id}");
+        }
+      }
+      if matches!(status, Some(TeamTaskStatus::Completed))
+        && current.review_state != TeamTaskReviewState::Approved
+        && review_state != Some(TeamTaskReviewState::Approved)
+        && self.is_review_gated(task_id)
+      {
+        anyhow::bail!(
+          "task {task_id} has a review gate: set its status to Review and have its reviewer \
+           complete it with review_state Approved"
+        );
+      }
     }
+    let completing = matches!(status, Some(TeamTaskStatus::Completed));
     let task = self
       .team_state
       .lock()
@@ -600,9 +1279,16 @@ impl TeamRuntime {
     {
       self.note_task_claim(task.clone(), assignee_thread_id.to_string());
     }
+    if let Some(task) = &task {
+      self.sync_workflow_step(task);
+    }
     self.persist_states().await;
     if let Some(task) = &task {
       self.nudge_task_participants(task).await;
+      if completing && matches!(task.status, TeamTaskStatus::Completed) {
+        self.merge_back_task(task).await;
+        self.nudge_unblocked_dependents(task).await;
+      }
     }
     Ok(task)
   }
@@ -849,6 +1535,11 @@ impl TeamRuntime {
     thread_id: &str,
     paths: &[String],
   ) -> anyhow::Result<()> {
+    if self.worktree_for(thread_id).is_some() {
+      // Writes land in the teammate's own checkout; overlaps with other
+      // teammates surface as conflicts when its branch merges back.
+      return Ok(());
+    }
     self.maintain_leases().await;
     let paths = paths
       .iter()
@@ -1005,6 +1696,135 @@ impl TeamRuntime {
     message
   }
 
+  /// Save a new version of a team document and mail its other subscribers,
+  /// which wakes idle watchers through the mailbox.
+  pub(crate) async fn write_doc(
+    &self,
+    author_thread_id: &str,
+    name: &str,
+    content: String,
+    base_version: u64,
+    summary: Option<String>,
+  ) -> anyhow::Result<TeamDoc> {
+    let doc = self
+      .team_state
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .write_doc(
+        name,
+        author_thread_id.to_string(),
+        content,
+        base_version,
+        summary,
+      )
+      .map_err(|failure| match failure {
+        DocWriteFailure::InvalidName => anyhow::anyhow!(
+          "team doc names must be 1-100 characters without control characters"
+        ),
+        DocWriteFailure::VersionConflict { latest_version } => anyhow::anyhow!(
+          "team doc '{}' is at v{latest_version}, not v{base_version}; read it again, merge your change and write against v{latest_version}",
+          name.trim()
+        ),
+      })?;
+    self.persist_team_state().await;
+
+    let change = doc
+      .summary
+      .as_deref()
+      .map(|summary| format!(": {summary}"))
+      .unwrap_or_default();
+    for subscriber in doc
+      .subscribers
+      .iter()
+      .filter(|subscriber| subscriber.as_str() != author_thread_id)
+    {
+      self
+        .post_message(
+          author_thread_id.to_string(),
+          Some(subscriber.clone()),
+          TeamMessageKind::Direct,
+          None,
+          TeamMessageDeliveryMode::DurableMail,
+          TeamMessagePriority::Normal,
+          None,
+          None,
+          format!(
+            "Team doc '{}' is now v{}{change}. Read it with read_team_doc or see the change with diff_team_doc.",
+            doc.name, doc.version
+          ),
+          None,
+        )
+        .await;
+    }
+    Ok(doc)
+  }
+
+  pub(crate) fn read_doc(&self, name: &str, version: Option<u64>) -> Option<TeamDoc> {
+    self
+      .team_state
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .doc(name, version)
+  }
+
+  pub(crate) fn list_docs(&self) -> Vec<TeamDocSummary> {
+    self
+      .team_state
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .sorted_docs()
+  }
+
+  /// Unified diff of `name` from `from_version` to `to_version`, defaulting to
+  /// the version before `to_version` and the latest version.
+  pub(crate) fn diff_doc(
+    &self,
+    name: &str,
+    from_version: Option<u64>,
+    to_version: Option<u64>,
+  ) -> anyhow::Result<String> {
+    let state = self
+      .team_state
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let missing = |version: u64| anyhow::anyhow!("team doc '{}' has no v{version}", name.trim());
+    let to = state
+      .doc(name, to_version)
+      .ok_or_else(|| match to_version {
+        Some(version) => missing(version),
+        None => anyhow::anyhow!("team doc '{}' does not exist", name.trim()),
+      })?;
+    let from_version = from_version.unwrap_or(to.version.saturating_sub(1));
+    // v0 is the empty document, so the first version diffs as all additions.
+    let from = match from_version {
+      0 => TeamDoc {
+        version: 0,
+        content: String::new(),
+        ..to.clone()
+      },
+      version => state
+        .doc(name, Some(version))
+        .ok_or_else(|| missing(version))?,
+    };
+    Ok(render_doc_diff(&from, &to))
+  }
+
+  pub(crate) async fn subscribe_doc(
+    &self,
+    thread_id: &str,
+    name: &str,
+    subscribed: bool,
+  ) -> anyhow::Result<TeamDoc> {
+    let doc = self
+      .team_state
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .set_doc_subscription(name, thread_id, subscribed)
+      .ok_or_else(|| anyhow::anyhow!("team doc '{}' does not exist", name.trim()))?;
+    self.persist_team_state().await;
+    Ok(doc)
+  }
+
   pub(crate) async fn watch_inbox(
     &self,
     reader_thread_id: &str,
@@ -1061,6 +1881,8 @@ impl TeamRuntime {
     let _ = self.state_db.delete(&self.team_store_key).await;
     let _ = self.state_db.delete(&self.run_store_key).await;
     let _ = self.state_db.delete(&self.legacy_store_key).await;
+    self.remove_all_worktrees().await;
+    self.roster.clear().await;
   }
 
   pub(crate) fn thread_depth(&self, thread_id: &str) -> Option<usize> {
@@ -1121,6 +1943,7 @@ impl TeamRuntime {
     message: String,
     nickname: Option<String>,
     role: String,
+    overrides: AgentModelOverrides,
   ) -> anyhow::Result<ThreadId> {
     let parent = self
       .resolve_thread_id(parent_thread_id)
@@ -1138,8 +1961,12 @@ impl TeamRuntime {
       )
       .await?;
 
-    if let Err(err) = self.launch_spawned_agent(thread_id.clone(), message).await {
+    if let Err(err) = self
+      .launch_spawned_agent(thread_id.clone(), message, overrides)
+      .await
+    {
       let _ = self.agent_control.shutdown_spawned_agent(thread_id.clone());
+      self.remove_worktree(&thread_id.to_string()).await;
       return Err(err);
     }
 
@@ -1172,6 +1999,14 @@ impl TeamRuntime {
     });
     handle.clear_wakes();
     let _ = handle.shutdown().await;
+    let thread_costs = self.agent_control.session().cost_ledger().thread_costs();
+    budget::accumulate(
+      &mut self
+        .closed_usage
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner),
+      &handle.usage(&thread_costs),
+    );
     self
       .agent_control
       .shutdown_spawned_agent(handle.thread_id().clone())?;
@@ -1184,10 +2019,152 @@ impl TeamRuntime {
     {
       self.persist_states().await;
     }
+    self.roster.remove(agent_id).await;
     self.emit_agent_state_changed(agent_id).await;
     Ok(CollabAgentLifecycle::Shutdown)
   }
 
+  /// Teammates persisted by an earlier process that have not been resumed yet.
+  pub(crate) fn resumable_teammates(&self) -> Vec<CollabAgentRef> {
+    let mut teammates = self
+      .roster
+      .thread_ids()
+      .into_iter()
+      .filter(|thread_id| self.handle_for(thread_id).is_none())
+      .filter_map(|thread_id| self.roster.get(&thread_id))
+      .map(|teammate| CollabAgentRef {
+        thread_id: teammate.thread_id,
+        nickname: teammate.nickname,
+        role: Some(teammate.role),
+      })
+      .collect::<Vec<_>>();
+    teammates.sort_by(|left, right| left.thread_id.cmp(&right.thread_id));
+    teammates
+  }
+
+  /// Re-create every persisted teammate under its original thread id: restore
+  /// its conversation and checkout, reattach its leases, and replay the turn it
+  /// was running plus any queued wakes.
+  pub(crate) async fn resume_team(&self, sender_thread_id: &str) -> Vec<CollabAgentStatusEntry> {
+    let mut resumed = HashMap::new();
+    for agent in self.resumable_teammates() {
+      let call_id = Uuid::new_v4().to_string();
+      let _ = self
+        .root_tx_event
+        .send(EventMsg::CollabResumeBegin(CollabResumeBeginEvent {
+          call_id: call_id.clone(),
+          sender_thread_id: sender_thread_id.to_string(),
+          receiver_thread_id: agent.thread_id.clone(),
+        }))
+        .await;
+      let result = self.resume_teammate(&agent.thread_id).await;
+      if let Err(err) = &result {
+        tracing::warn!("failed to resume teammate {}: {err}", agent.thread_id);
+      } else if let Some(state) = self.wait_state(&agent.thread_id) {
+        resumed.insert(agent.thread_id.clone(), state);
+      }
+      let _ = self
+        .root_tx_event
+        .send(EventMsg::CollabResumeEnd(CollabResumeEndEvent {
+          call_id,
+          sender_thread_id: sender_thread_id.to_string(),
+          receiver_thread_id: agent.thread_id.clone(),
+          receiver_nickname: agent.nickname,
+          receiver_role: agent.role,
+          error: result.err().map(|err| err.to_string()),
+          lifecycle: self
+            .wait_state(&agent.thread_id)
+            .map(|state| state.lifecycle)
+            .unwrap_or(CollabAgentLifecycle::NotFound),
+        }))
+        .await;
+    }
+    self.collab_agent_status_entries(&resumed)
+  }
+
+  async fn resume_teammate(&self, agent_id: &str) -> anyhow::Result<()> {
+    let teammate = self
+      .roster
+      .get(agent_id)
+      .ok_or_else(|| anyhow::anyhow!("no persisted teammate {agent_id}"))?;
+    let thread_id = ThreadId::parse(&teammate.thread_id)
+      .ok_or_else(|| anyhow::anyhow!("invalid persisted thread id {}", teammate.thread_id))?;
+    let parent_thread_id = teammate
+      .parent_thread_id
+      .as_deref()
+      .and_then(ThreadId::parse)
+      .unwrap_or_else(|| self.root_thread_id.clone());
+    self.agent_control.restore_spawned_agent(
+      ThreadInfo {
+        thread_id: thread_id.clone(),
+        parent_thread_id: Some(parent_thread_id),
+        depth: teammate.depth,
+        nickname: teammate.nickname.clone(),
+        role: teammate.role.clone(),
+        task: teammate.task.clone(),
+        created_at: teammate.created_at,
+      },
+      Some(self.config.agents.max_threads),
+    )?;
+    let overrides = AgentModelOverrides {
+      model: Some(teammate.model.clone()),
+      provider: None,
+      reasoning_effort: teammate.reasoning_effort,
+      temperature: teammate.temperature,
+    };
+    let handle = match self
+      .start_agent(thread_id.clone(), overrides, Some(&teammate))
+      .await
+    {
+      Ok(handle) => handle,
+      Err(err) => {
+        let _ = self.agent_control.shutdown_spawned_agent(thread_id);
+        return Err(err);
+      }
+    };
+    handle.update_state(|state| {
+      state.last_turn_summary = teammate.last_turn_summary.clone();
+      if let Some(reason) = &teammate.blocked_reason {
+        state.lifecycle = CollabAgentLifecycle::Blocked;
+        state.attention_reason = Some(reason.clone());
+      }
+    });
+
+    if self
+      .team_state
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .touch_thread_leases(agent_id)
+      > 0
+    {
+      self.persist_team_state().await;
+    }
+
+    // A blocked teammate takes no more work, so nothing is replayed.
+    if teammate.blocked_reason.is_some() {
+      self.emit_agent_state_changed(agent_id).await;
+      return Ok(());
+    }
+    if let Some(message) = teammate.inflight_message {
+      self
+        .schedule_turn(
+          agent_id,
+          format!(
+            "The session restarted while you were working on the request below. Check the current state of the workspace, then continue where you left off.\n\n{message}"
+          ),
+          WakeReason::UserInput,
+        )
+        .await?;
+    }
+    for wake in teammate.pending_wakes {
+      self
+        .schedule_turn(agent_id, wake.message, wake.reason)
+        .await?;
+    }
+    self.emit_agent_state_changed(agent_id).await;
+    Ok(())
+  }
+
   pub(crate) fn subscribe_state(
     &self,
     agent_id: &str,
@@ -1245,6 +2222,161 @@ impl TeamRuntime {
     entries
   }
 
+  pub(crate) fn agent_model(&self, agent_id: &str) -> Option<String> {
+    self.handle_for(agent_id).map(|handle| handle.model.clone())
+  }
+
+  fn team_usage(&self, thread_costs: &HashMap<String, ThreadCost>) -> TeamMemberUsage {
+    let handles = self
+      .handles
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .values()
+      .cloned()
+      .collect::<Vec<_>>();
+    let mut total = self
+      .closed_usage
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .clone();
+    for handle in handles {
+      budget::accumulate(&mut total, &handle.usage(thread_costs));
+    }
+    total
+  }
+
+  /// Why `agent_id` may not start another turn, if its own or the team's
+  /// budget is spent.
+  pub(crate) fn budget_exhaustion(&self, agent_id: &str) -> Option<String> {
+    let handle = self.handle_for(agent_id)?;
+    let thread_costs = self.agent_control.session().cost_ledger().thread_costs();
+    if let Some(reason) = budget::exhausted(&handle.usage(&thread_costs), &handle.budget) {
+      return Some(reason);
+    }
+    let team_budget = budget::team_budget(&self.config.agents);
+    if team_budget.is_unlimited() {
+      return None;
+    }
+    budget::exhausted(&self.team_usage(&thread_costs), &team_budget)
+      .map(|reason| format!("team {reason}"))
+  }
+
+  /// Nudge, restart or escalate busy teammates that have gone quiet.
+  async fn check_liveness(&self) {
+    let config = &self.config.agents.liveness;
+    let now = self.liveness_clock.now();
+    let handles = self
+      .handles
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .values()
+      .cloned()
+      .collect::<Vec<_>>();
+    for handle in handles {
+      if handle.state().lifecycle != CollabAgentLifecycle::Busy {
+        continue;
+      }
+      let Some(action) = handle.liveness().next_action(config, now) else {
+        continue;
+      };
+      let agent_id = handle.thread_id().to_string();
+      let reason = action.attention_reason();
+      match action {
+        StallAction::Nudge { quiet_secs } => {
+          let message = format!(
+            "No progress from you for {quiet_secs}s. If something is blocking you, say what it is; otherwise wrap up the current step."
+          );
+          // Tradeoff: the mailbox copy is what `send_team_nudge` would leave,
+          // but it is only read after the turn ends, so the nudge is also
+          // steered into the running turn for its next model request.
+          handle
+            .steer(vec![UserInput::Text {
+              text: message.clone(),
+              text_elements: Vec::new(),
+            }])
+            .await;
+          self
+            .post_message(
+              self.root_thread_id.to_string(),
+              Some(agent_id.clone()),
+              TeamMessageKind::Direct,
+              None,
+              TeamMessageDeliveryMode::EphemeralNudge,
+              TeamMessagePriority::High,
+              None,
+              None,
+              message,
+              Some(now + 300),
+            )
+            .await;
+        }
+        StallAction::Restart { .. } => handle.interrupt.notify_waiters(),
+        StallAction::Escalate { quiet_secs } => {
+          handle.update_state(|state| {
+            state.escalated = true;
+            state.attention_reason = Some(reason.clone());
+          });
+          let name = self
+            .find_thread_info(&agent_id)
+            .and_then(|info| info.nickname)
+            .unwrap_or_else(|| agent_id.clone());
+          self
+            .post_message(
+              agent_id.clone(),
+              Some(self.root_thread_id.to_string()),
+              TeamMessageKind::Direct,
+              None,
+              TeamMessageDeliveryMode::DurableMail,
+              TeamMessagePriority::High,
+              None,
+              None,
+              format!(
+                "{name} has made no progress for {quiet_secs}s and automatic recovery did not help. Check on it with send_input, or close it with close_agent and reassign its task."
+              ),
+              None,
+            )
+            .await;
+        }
+      }
+      self.note_attention(&agent_id, reason);
+    }
+  }
+
+  /// Park `agent_id` in `Blocked` and tell the leader why.
+  pub(crate) async fn block_for_budget(&self, agent_id: &str, reason: &str) {
+    let Some(handle) = self.handle_for(agent_id) else {
+      return;
+    };
+    handle.clear_wakes();
+    handle.update_state(|state| {
+      state.lifecycle = CollabAgentLifecycle::Blocked;
+      state.attention_reason = Some(format!("budget exhausted: {reason}"));
+      state.settled_generation = state.scheduled_generation;
+    });
+    let name = self
+      .find_thread_info(agent_id)
+      .and_then(|info| info.nickname)
+      .unwrap_or_else(|| agent_id.to_string());
+    self
+      .post_message(
+        agent_id.to_string(),
+        Some(self.root_thread_id.to_string()),
+        TeamMessageKind::Direct,
+        None,
+        TeamMessageDeliveryMode::DurableMail,
+        TeamMessagePriority::High,
+        None,
+        None,
+        format!(
+          "{name} stopped: {reason}. It will not take more work; close it with close_agent or spawn a replacement with a narrower task."
+        ),
+        None,
+      )
+      .await;
+    self.emit_agent_state_changed(agent_id).await;
+    self.roster.checkpoint(&handle).await;
+  }
+
   fn handles_thread(&self, thread_id: &str) -> bool {
     self.find_thread_info(thread_id).is_some()
   }
@@ -1290,6 +2422,22 @@ impl TeamRuntime {
     }
   }
 
+  fn is_review_gated(&self, task_id: &str) -> bool {
+    self
+      .run_state
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .is_review_gated(task_id)
+  }
+
+  fn sync_workflow_step(&self, task: &TeamTask) {
+    self
+      .run_state
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .sync_task(task);
+  }
+
   fn note_task_claim(&self, task: TeamTask, claimer_thread_id: String) {
     self
       .run_state
@@ -1348,7 +2496,9 @@ impl TeamRuntime {
     let handle = self
       .handle_for(agent_id)
       .ok_or_else(|| anyhow::anyhow!("agent not found: {agent_id}"))?;
-    self.clear_attention(agent_id);
+    if handle.state().lifecycle != CollabAgentLifecycle::Blocked {
+      self.clear_attention(agent_id);
+    }
     match handle.state().lifecycle {
       CollabAgentLifecycle::Ready => {
         handle.update_state(|state| {
@@ -1377,10 +2527,14 @@ impl TeamRuntime {
           state.scheduled_generation = state.scheduled_generation.saturating_add(1);
           state.pending_wake_count = queue_len;
         });
+        self.roster.checkpoint(&handle).await;
       }
       CollabAgentLifecycle::Error => {
         anyhow::bail!("agent {agent_id} is in an error state and cannot accept new work")
       }
+      CollabAgentLifecycle::Blocked => {
+        anyhow::bail!("agent {agent_id} has exhausted its budget and cannot accept new work")
+      }
       CollabAgentLifecycle::Shutdown | CollabAgentLifecycle::NotFound => {
         anyhow::bail!("agent {agent_id} is not available")
       }
@@ -1423,6 +2577,48 @@ impl TeamRuntime {
     }
   }
 
+  /// Wake the assignees of tasks that `completed` was the last blocker of.
+  /// The leader is not woken by the runtime, so it gets a mailbox message.
+  async fn nudge_unblocked_dependents(&self, completed: &TeamTask) {
+    for dependent_id in &completed.blocks_task_ids {
+      let Some(dependent) = self.task(dependent_id) else {
+        continue;
+      };
+      if dependent.ready_state != cokra_protocol::TeamTaskReadyState::Ready {
+        continue;
+      }
+      self.sync_workflow_step(&dependent);
+      match dependent.assignee_thread_id.as_deref() {
+        Some(assignee) if self.is_root_thread(assignee) => {
+          let sender = completed
+            .assignee_thread_id
+            .clone()
+            .unwrap_or_else(|| assignee.to_string());
+          self
+            .post_message(
+              sender,
+              Some(assignee.to_string()),
+              TeamMessageKind::Direct,
+              None,
+              TeamMessageDeliveryMode::DurableMail,
+              TeamMessagePriority::Normal,
+              None,
+              Some(dependent.id.clone()),
+              format!(
+                "Task '{}' is ready for you: '{}' was completed.",
+                dependent.title, completed.title
+              ),
+              None,
+            )
+            .await;
+        }
+        Some(_) => self.nudge_task_participants(&dependent).await,
+        None => {}
+      }
+    }
+    self.persist_run_state().await;
+  }
+
   async fn nudge_message_recipients(&self, message: &TeamMessage) {
     if let Some(recipient_thread_id) = message.recipient_thread_id.as_deref() {
       let reason = WakeReason::MailboxUnread;
@@ -1566,6 +2762,9 @@ impl TeamRuntime {
 
   fn active_lease_owner_ids(&self) -> HashSet<String> {
     let mut active_thread_ids = HashSet::from([self.root_thread_id.to_string()]);
+    // Teammates waiting to be resumed keep their leases until `resume_team`
+    // reattaches them (or the team is cleaned up).
+    active_thread_ids.extend(self.roster.thread_ids());
     let handles = self
       .handles
       .lock()
@@ -1698,14 +2897,347 @@ impl TeamRuntime {
     false
   }
 
+  fn worktree_for(&self, thread_id: &str) -> Option<TeammateWorktree> {
+    self
+      .worktrees
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .get(thread_id)
+      .cloned()
+  }
+
+  async fn create_worktree(
+    &self,
+    thread_id: &ThreadId,
+    thread_info: Option<&ThreadInfo>,
+  ) -> anyhow::Result<std::path::PathBuf> {
+    let thread_id = thread_id.to_string();
+    let short_id = thread_id.chars().take(8).collect::<String>();
+    let label = thread_info
+      .and_then(|info| info.nickname.clone())
+      .unwrap_or_else(|| short_id.clone());
+    let worktree =
+      super::worktree::create_teammate_worktree(&self.config.cwd, &label, &short_id).await?;
+    let cwd = worktree.cwd.clone();
+    self
+      .worktrees
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .insert(thread_id, worktree);
+    Ok(cwd)
+  }
+
+  async fn remove_worktree(&self, thread_id: &str) {
+    let worktree = self
+      .worktrees
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .remove(thread_id);
+    if let Some(worktree) = worktree {
+      self.remove_teammate_worktree(&worktree).await;
+    }
+  }
+
+  /// Remove one checkout, telling the leader when it had to be kept.
+  async fn remove_teammate_worktree(&self, worktree: &TeammateWorktree) {
+    if let Err(err) = super::worktree::remove_teammate_worktree(
+      worktree,
+      &self.config.agents.worktree_exclude_untracked,
+    )
+    .await
+    {
+      tracing::warn!(
+        "failed to remove worktree {}: {err}",
+        worktree.path.display()
+      );
+      let _ = self
+        .root_tx_event
+        .send(EventMsg::Warning(WarningEvent {
+          thread_id: self.root_thread_id.to_string(),
+          turn_id: String::new(),
+          message: format!("Teammate worktree cleanup: {err}"),
+        }))
+        .await;
+    }
+  }
+
+  async fn remove_all_worktrees(&self) {
+    let thread_ids = self
+      .worktrees
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .keys()
+      .cloned()
+      .collect::<Vec<_>>();
+    for thread_id in thread_ids {
+      self.remove_worktree(&thread_id).await;
+    }
+    // Teammates restored from this session's store but never respawned still
+    // own checkouts; only those whose creating process is gone are removed.
+    for worktree in super::worktree::removable_teammate_worktrees(self.roster.worktrees()) {
+      self.remove_teammate_worktree(&worktree).await;
+    }
+  }
+
+  /// Bring a completed task's worktree branch into the leader's checkout and
+  /// report the result to the leader as a team message.
+  async fn merge_back_task(&self, task: &TeamTask) {
+    let Some((thread_id, worktree)) = [
+      task.assignee_thread_id.as_deref(),
+      task.owner_thread_id.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .find_map(|thread_id| {
+      self
+        .worktree_for(thread_id)
+        .map(|worktree| (thread_id.to_string(), worktree))
+    }) else {
+      return;
+    };
+    let branch = worktree.branch.as_str();
+    let commit_message = format!("{} ({})", task.title, task.id);
+    let (priority, report, teammate_instruction) = match super::worktree::merge_back(
+      &worktree,
+      &commit_message,
+      &self.config.agents.worktree_exclude_untracked,
+    )
+    .await
+    {
+      Ok(MergeBackOutcome::UpToDate) => return,
+      Ok(MergeBackOutcome::Merged { commits }) => (
+        TeamMessagePriority::Normal,
+        format!(
+          "Merged {commits} commit(s) from {branch} for task {}.",
+          task.id
+        ),
+        None,
+      ),
+      Ok(MergeBackOutcome::Conflict { onto, files }) => {
+        let files = files.join(", ");
+        (
+          TeamMessagePriority::High,
+          format!(
+            "{branch} conflicts with the leader checkout for task {} ({files}); it was left unmerged and the teammate was asked to rebase.",
+            task.id
+          ),
+          Some(format!(
+            "Your branch {branch} conflicts with the leader checkout ({files}). Run `git rebase {onto}` in your worktree, resolve the conflicts, then mark task {} completed again.",
+            task.id
+          )),
+        )
+      }
+      Ok(MergeBackOutcome::Rejected { reason }) => (
+        TeamMessagePriority::High,
+        format!(
+          "Could not fast-forward the leader checkout to {branch} for task {}: {reason}",
+          task.id
+        ),
+        None,
+      ),
+      Err(err) => (
+        TeamMessagePriority::High,
+        format!("Merge-back of {branch} for task {} failed: {err}", task.id),
+        None,
+      ),
+    };
+    let root_thread_id = self.root_thread_id.to_string();
+    self
+      .post_message(
+        thread_id.clone(),
+        Some(root_thread_id.clone()),
+        TeamMessageKind::Direct,
+        None,
+        TeamMessageDeliveryMode::DurableMail,
+        priority.clone(),
+        None,
+        Some(task.id.clone()),
+        report,
+        None,
+      )
+      .await;
+    if let Some(instruction) = teammate_instruction {
+      self
+        .post_message(
+          root_thread_id,
+          Some(thread_id),
+          TeamMessageKind::Direct,
+          None,
+          TeamMessageDeliveryMode::DurableMail,
+          priority,
+          None,
+          Some(task.id.clone()),
+          instruction,
+          None,
+        )
+        .await;
+    }
+  }
+
+  /// Pin a teammate's model settings at spawn time.
+  ///
+  /// The model is stored as `provider/model`, so switching the leader's model
+  /// or default provider later does not move teammates that are already running.
+  async fn apply_model_overrides(
+    &self,
+    turn_config: &mut TurnConfig,
+    role: &str,
+    overrides: AgentModelOverrides,
+  ) {
+    let role_config = self.config.agents.roles.get(role);
+    let model = overrides
+      .model
+      .or_else(|| role_config.and_then(|config| config.model.clone()))
+      .unwrap_or_else(|| turn_config.model.clone());
+    let provider = match overrides
+      .provider
+      .or_else(|| role_config.and_then(|config| config.provider.clone()))
+    {
+      Some(provider) => Some(provider),
+      None => self.model_client.resolve_provider_id(&model).await.ok(),
+    };
+    turn_config.model = match provider {
+      Some(provider) => format!("{provider}/{}", get_model_name(&model)),
+      None => model,
+    };
+
+    if let Some(temperature) = overrides
+      .temperature
+      .or_else(|| role_config.and_then(|config| config.temperature))
+    {
+      turn_config.temperature = Some(temperature);
+    }
+
+    let effort = overrides.reasoning_effort.or_else(|| {
+      role_config
+        .and_then(|config| config.reasoning_effort)
+        .map(|effort| match effort {
+          AgentReasoningEffort::Minimal => ReasoningEffort::Minimal,
+          AgentReasoningEffort::Low => ReasoningEffort::Low,
+          AgentReasoningEffort::Medium => ReasoningEffort::Medium,
+          AgentReasoningEffort::High => ReasoningEffort::High,
+        })
+    });
+    if let Some(effort) = effort {
+      turn_config.reasoning = Some(ReasoningRequest {
+        effort,
+        summary: match turn_config.reasoning {
+          Some(reasoning) => reasoning.summary,
+          None => Some(ReasoningSummary::Auto),
+        },
+      });
+    }
+
+    let entry = self
+      .model_client
+      .resolve_model_catalog(&turn_config.model)
+      .await;
+    if let Some(entry) = &entry {
+      turn_config.context_window_limit = entry
+        .context_window
+        .and_then(|limit| usize::try_from(limit).ok());
+    }
+    if !entry.is_some_and(|entry| entry.reasoning) {
+      turn_config.reasoning = None;
+    }
+  }
+
   async fn launch_spawned_agent(
     &self,
     thread_id: ThreadId,
     initial_message: String,
+    overrides: AgentModelOverrides,
   ) -> anyhow::Result<()> {
-    let session = Arc::new(Session::new_with_thread_id(thread_id.clone()));
+    let handle = self.start_agent(thread_id, overrides, None).await?;
+    handle.update_state(|state| {
+      state.scheduled_generation = 1;
+      state.inflight_generation = 1;
+      state.lifecycle = CollabAgentLifecycle::Busy;
+      state.turn_outcome = CollabTurnOutcome::NoneYet;
+    });
+    let thread_id_string = handle.thread_id().to_string();
+    self.emit_agent_state_changed(&thread_id_string).await;
+    handle
+      .tx_cmd
+      .send(ChildCommand::UserTurn {
+        message: initial_message,
+      })
+      .await
+      .map_err(|_| anyhow::anyhow!("spawned agent loop terminated before initial task"))?;
+
+    Ok(())
+  }
+
+  /// Run a one-shot read-only explorer in `cwd` and return its condensed
+  /// answer. The explorer is not a teammate and leaves no trace in the team.
+  pub(crate) async fn explore(
+    &self,
+    cwd: &Path,
+    question: String,
+    overrides: AgentModelOverrides,
+  ) -> anyhow::Result<ExploreReport> {
+    let mut turn_config = self.agent_control.turn_config().await;
+    turn_config.system_prompt = Some(explore::build_explorer_system_prompt(
+      turn_config.system_prompt.as_deref(),
+    ));
+    turn_config.cwd = cwd.to_path_buf();
+    // Nobody is listening for approval prompts; the read-only tools need none.
+    turn_config.approval_policy = AskForApproval::Never;
+    self
+      .apply_model_overrides(&mut turn_config, explore::EXPLORER_ROLE, overrides)
+      .await;
+    let model = turn_config.model.clone();
+    let (tool_registry, tool_router) =
+      build_restricted_tooling(self.config.as_ref(), explore::EXPLORER_TOOLS);
+    let session = Arc::new(
+      Session::new_with_thread_id(ThreadId::new())
+        .with_cost_ledger(self.agent_control.session().cost_ledger()),
+    );
+    let (tx_event, mut rx_event) = mpsc::channel(CHILD_EVENT_CHANNEL_CAPACITY);
+    tokio::spawn(async move { while rx_event.recv().await.is_some() {} });
+    let result = TurnExecutor::new(
+      self.model_client.clone(),
+      tool_registry.clone(),
+      tool_router,
+      session,
+      tx_event,
+      turn_config,
+    )
+    .run_turn(crate::turn::UserInput {
+      content: question,
+      attachments: Vec::new(),
+    })
+    .await
+    .map_err(|err| anyhow::anyhow!("explorer failed: {err}"))?;
+    Ok(ExploreReport {
+      model,
+      answer: explore::condense_answer(&result.content),
+      tool_calls: tool_registry.dispatched_calls(),
+    })
+  }
+
+  /// Build a teammate's `AgentControl` and command loop. `restored` carries the
+  /// conversation and checkout of a teammate from a previous process.
+  async fn start_agent(
+    &self,
+    thread_id: ThreadId,
+    overrides: AgentModelOverrides,
+    restored: Option<&PersistedTeammate>,
+  ) -> anyhow::Result<Arc<ManagedAgentHandle>> {
+    let session = Arc::new(
+      Session::new_with_thread_id(thread_id.clone())
+        .with_cost_ledger(self.agent_control.session().cost_ledger()),
+    );
+    if let Some(restored) = restored {
+      session.replace_history(restored.history.clone()).await;
+    }
     let thread_info = self.find_thread_info(&thread_id.to_string());
     let mut turn_config = self.agent_control.turn_config().await;
+    let role_name = thread_info
+      .as_ref()
+      .map(|info| info.role.clone())
+      .unwrap_or_else(|| "default".to_string());
+    let role = load_agent_role(&self.config, &self.config.cwd, &role_name).await;
     if let Some(base) = turn_config.system_prompt.as_deref() {
       // Tradeoff: we append a small sub-agent contract to the base prompt instead of
       // replacing it wholesale. This keeps tool-use and safety guidance consistent
@@ -1715,28 +3247,134 @@ impl TeamRuntime {
         thread_info
           .as_ref()
           .and_then(|info| info.nickname.as_deref()),
-        thread_info
-          .as_ref()
-          .map(|info| info.role.as_str())
-          .unwrap_or("default"),
+        &role_name,
       ));
     }
-    let tooling = build_default_tooling_with_cwd(self.config.as_ref(), &self.config.cwd).await?;
+    if let Some(role) = role.as_ref() {
+      if !role.system_prompt.is_empty() {
+        turn_config.system_prompt = Some(match turn_config.system_prompt.take() {
+          Some(prompt) => format!("{prompt}\n\n# Role: {}\n{}", role.name, role.system_prompt),
+          None => role.system_prompt.clone(),
+        });
+      }
+      if role.temperature.is_some() {
+        turn_config.temperature = role.temperature;
+      }
+      if role.max_tokens.is_some() {
+        turn_config.max_tokens = role.max_tokens;
+      }
+    }
+    self
+      .apply_model_overrides(&mut turn_config, &role_name, overrides)
+      .await;
+    let restored_worktree = restored
+      .and_then(|restored| restored.worktree.clone())
+      .filter(|worktree| worktree.path.exists());
+    let cwd = match (restored_worktree, &self.config.agents.isolation) {
+      (Some(worktree), _) => {
+        let cwd = worktree.cwd.clone();
+        self
+          .worktrees
+          .lock()
+          .unwrap_or_else(std::sync::PoisonError::into_inner)
+          .insert(thread_id.to_string(), worktree);
+        cwd
+      }
+      (None, AgentIsolation::Shared) => self.config.cwd.clone(),
+      (None, AgentIsolation::Worktree) => {
+        self
+          .create_worktree(&thread_id, thread_info.as_ref())
+          .await?
+      }
+    };
+    turn_config.cwd = cwd.clone();
+    let model = turn_config.model.clone();
+    let reasoning_effort = turn_config.reasoning.map(|reasoning| reasoning.effort);
+    let temperature = turn_config.temperature;
+    let tool_policy = role
+      .as_ref()
+      .map(|role| role.tool_policy())
+      .unwrap_or_default();
+    let agent_budget = budget::agent_budget(&self.config.agents, &role_name);
+    let prior_usage = restored
+      .map(|restored| restored.usage.clone())
+      .unwrap_or_default();
+    // The tool-call limit is enforced per registry, which starts from zero.
+    let remaining_tool_calls = agent_budget
+      .max_tool_calls
+      .map(|max| max.saturating_sub(prior_usage.tool_calls));
+    let out_of_process = self.config.agents.execution == AgentExecution::Process;
+    let tooling = if out_of_process {
+      // The child builds the real toolset (and its MCP servers); the leader
+      // only keeps the collaboration tools it answers on the child's behalf.
+      build_collaboration_tooling(self.config.as_ref()).await?
+    } else {
+      let tooling = build_default_tooling_with_cwd(self.config.as_ref(), &cwd).await?;
+      // Enforced at the registry, not just hidden from the prompt: a role that
+      // denies `apply_patch` cannot dispatch it even if the model guesses the name.
+      tooling.registry.set_access_policy(tool_policy.clone());
+      // Tradeoff: the other limits are checked after each model request and
+      // tool call, which lets a parallel batch overshoot, so the registry
+      // enforces this one itself.
+      tooling.registry.set_call_limit(remaining_tool_calls);
+      tooling
+    };
     let tool_registry = tooling.registry;
     let tool_router = tooling.router;
     let tool_runtime = tooling.runtime;
     let (tx_raw_event, mut rx_raw_event) = mpsc::channel(CHILD_EVENT_CHANNEL_CAPACITY);
     let root_tx_event = self.root_tx_event.clone();
+    let clock = self.liveness_clock;
+    let liveness = Arc::new(Mutex::new(AgentLiveness::new(clock.now())));
+    let liveness_for_events = liveness.clone();
+    let interrupt = Arc::new(Notify::new());
+    let interrupt_for_events = interrupt.clone();
+    let thread_id_for_events = thread_id.to_string();
     tokio::spawn(async move {
       while let Some(event) = rx_raw_event.recv().await {
+        liveness_for_events
+          .lock()
+          .unwrap_or_else(std::sync::PoisonError::into_inner)
+          .record_event(&event, clock.now());
+        let spends_budget = spends_budget(&event);
         let _ = root_tx_event.send(event).await;
+        // The command loop sees the spent budget once the turn is dropped.
+        if spends_budget && budget_exhaustion(&thread_id_for_events).is_some() {
+          interrupt_for_events.notify_waiters();
+        }
       }
     });
 
+    let process = out_of_process.then(|| {
+      let init = TeammateInit {
+        thread_id: thread_id.clone(),
+        root_thread_id: self.root_thread_id.clone(),
+        config: self.config.as_ref().clone(),
+        turn_config: turn_config.clone(),
+        tool_policy,
+        max_tool_calls: remaining_tool_calls,
+        history: Vec::new(),
+      };
+      let proxy = RouterProxy {
+        thread_id: thread_id.to_string(),
+        router: tool_router.clone(),
+        session: session.clone(),
+        turn_config: turn_config.clone(),
+        tx_event: tx_raw_event.clone(),
+      };
+      Arc::new(TeammateProcess::new(
+        init,
+        session.clone(),
+        process_launcher(self.config.agents.process.program.clone(), cwd.clone()),
+        Arc::new(proxy),
+        tx_raw_event.clone(),
+        self.config.agents.process.max_restarts,
+      ))
+    });
     let agent_control = Arc::new(AgentControl::new(
       Uuid::new_v4().to_string(),
       self.model_client.clone(),
-      tool_registry,
+      tool_registry.clone(),
       tool_router,
       tool_runtime,
       session.clone(),
@@ -1759,11 +3397,19 @@ impl TeamRuntime {
     let (state_tx, state_rx) = watch::channel(initial_state);
     let handle = Arc::new(ManagedAgentHandle {
       thread_id: thread_id.clone(),
+      model,
       session: session.clone(),
       tx_cmd: tx_cmd.clone(),
       state_tx,
       state_rx,
       wake_queue: Arc::new(Mutex::new(VecDeque::new())),
+      tool_registry,
+      started_at: Instant::now(),
+      budget: agent_budget,
+      liveness,
+      interrupt,
+      process,
+      prior_usage,
     });
 
     self
@@ -1771,8 +3417,33 @@ impl TeamRuntime {
       .lock()
       .unwrap_or_else(std::sync::PoisonError::into_inner)
       .insert(thread_id.to_string(), handle.clone());
+    if let Some(info) = thread_info.as_ref() {
+      self
+        .roster
+        .upsert(PersistedTeammate {
+          thread_id: thread_id.to_string(),
+          parent_thread_id: info.parent_thread_id.as_ref().map(ToString::to_string),
+          depth: info.depth,
+          nickname: info.nickname.clone(),
+          role: info.role.clone(),
+          task: info.task.clone(),
+          created_at: info.created_at,
+          model: handle.model.clone(),
+          reasoning_effort,
+          temperature,
+          worktree: self.worktree_for(&thread_id.to_string()),
+          history: session.clone_history().await,
+          last_turn_summary: restored.and_then(|restored| restored.last_turn_summary.clone()),
+          inflight_message: None,
+          pending_wakes: Vec::new(),
+          usage: handle.prior_usage.clone(),
+          blocked_reason: restored.and_then(|restored| restored.blocked_reason.clone()),
+        })
+        .await;
+    }
 
     let root_tx_event_for_state = self.root_tx_event.clone();
+    let roster = self.roster.clone();
     let team_state_for_events = self.team_state.clone();
     let handle_for_loop = handle.clone();
     let thread_info_for_loop = thread_info.clone();
@@ -1783,10 +3454,24 @@ impl TeamRuntime {
       while let Some(command) = rx_cmd.recv().await {
         match command {
           ChildCommand::UserTurn { message } => {
+            if let Some((runtime, reason)) = budget_exhaustion(&thread_id_for_loop) {
+              runtime.block_for_budget(&thread_id_for_loop, &reason).await;
+              continue;
+            }
+            // Registered before the teammate shows as busy, so an interrupt
+            // sent while the turn is still being set up is not lost.
+            let interrupted = handle_for_loop.interrupt.notified();
+            tokio::pin!(interrupted);
+            interrupted.as_mut().enable();
+            let restarted = handle_for_loop.liveness().begin_turn(clock.now());
             handle_for_loop.update_state(|state| {
               state.lifecycle = CollabAgentLifecycle::Busy;
-              state.attention_reason = None;
+              // A restarted turn keeps the stall note until it finishes.
+              if !restarted {
+                state.attention_reason = None;
+              }
               state.turn_outcome = CollabTurnOutcome::NoneYet;
+              state.escalated = false;
             });
             let _ = root_tx_event_for_state
               .send(agent_state_changed_event(
@@ -1796,11 +3481,46 @@ impl TeamRuntime {
                 &handle_for_loop.state(),
               ))
               .await;
+            roster.begin_turn(&handle_for_loop, &message).await;
+            let turn_id = Uuid::new_v4().to_string();
             let turn = Turn {
-              turn_id: Uuid::new_v4().to_string(),
+              turn_id: turn_id.clone(),
               user_message: message,
             };
-            match agent_control.process_turn(turn).await {
+            let outcome = tokio::select! {
+              result = run_agent_turn(&agent_control, handle_for_loop.process.as_deref(), turn) => Some(result),
+              () = &mut interrupted => None,
+            };
+            let Some(outcome) = outcome else {
+              if let Some(process) = &handle_for_loop.process {
+                process.interrupt().await;
+              }
+              let session = &handle_for_loop.session;
+              session.clear_pending_approvals_for_turn(&turn_id).await;
+              session.clear_pending_user_inputs_for_turn(&turn_id).await;
+              session.end_turn(&turn_id).await;
+              agent_control.abort_turn().await;
+              if let Some((runtime, reason)) = budget_exhaustion(&thread_id_for_loop) {
+                handle_for_loop.update_state(|state| {
+                  state.turn_outcome = CollabTurnOutcome::Interrupted;
+                  state.turns += 1;
+                });
+                roster.end_turn(&handle_for_loop).await;
+                runtime.block_for_budget(&thread_id_for_loop, &reason).await;
+                continue;
+              }
+              if tx_cmd_loop
+                .send(ChildCommand::UserTurn {
+                  message: STALLED_TURN_RESTART_MESSAGE.to_string(),
+                })
+                .await
+                .is_err()
+              {
+                break;
+              }
+              continue;
+            };
+            match outcome {
               Ok(result) => {
                 let has_output = !result.content.trim().is_empty();
                 let final_message = has_output.then_some(result.content);
@@ -1809,8 +3529,10 @@ impl TeamRuntime {
                   state.turn_outcome = CollabTurnOutcome::Succeeded;
                   state.last_turn_summary = final_message.clone();
                   state.attention_reason = None;
+                  state.turns += 1;
                   state.settled_generation = state.inflight_generation;
                 });
+                roster.end_turn(&handle_for_loop).await;
                 let _ = root_tx_event_for_state
                   .send(agent_state_changed_event(
                     &team_state_for_events,
@@ -1819,7 +3541,9 @@ impl TeamRuntime {
                     &handle_for_loop.state(),
                   ))
                   .await;
-                if let Some(next_wake) = handle_for_loop.dequeue_wake() {
+                if let Some((runtime, reason)) = budget_exhaustion(&thread_id_for_loop) {
+                  runtime.block_for_budget(&thread_id_for_loop, &reason).await;
+                } else if let Some(next_wake) = handle_for_loop.dequeue_wake() {
                   handle_for_loop.update_state(|state| {
                     state.lifecycle = CollabAgentLifecycle::Busy;
                     state.inflight_generation = state.settled_generation.saturating_add(1);
@@ -1869,8 +3593,10 @@ impl TeamRuntime {
                   state.turn_outcome = CollabTurnOutcome::Errored;
                   state.last_turn_summary = Some(err.clone());
                   state.attention_reason = Some(err.clone());
+                  state.turns += 1;
                   state.settled_generation = state.scheduled_generation;
                 });
+                roster.end_turn(&handle_for_loop).await;
                 let _ = root_tx_event_for_state
                   .send(agent_state_changed_event(
                     &team_state_for_events,
@@ -1883,6 +3609,9 @@ impl TeamRuntime {
             }
           }
           ChildCommand::Shutdown => {
+            if let Some(process) = &handle_for_loop.process {
+              process.shutdown().await;
+            }
             let _ = agent_control.stop().await;
             handle_for_loop.update_state(|state| {
               state.lifecycle = CollabAgentLifecycle::Shutdown;
@@ -1906,25 +3635,42 @@ impl TeamRuntime {
       let _ = session.shutdown().await;
     });
 
-    handle.update_state(|state| {
-      state.scheduled_generation = 1;
-      state.inflight_generation = 1;
-      state.lifecycle = CollabAgentLifecycle::Busy;
-      state.turn_outcome = CollabTurnOutcome::NoneYet;
-    });
-    let thread_id_string = thread_id.to_string();
-    self.emit_agent_state_changed(&thread_id_string).await;
-    tx_cmd
-      .send(ChildCommand::UserTurn {
-        message: initial_message,
-      })
-      .await
-      .map_err(|_| anyhow::anyhow!("spawned agent loop terminated before initial task"))?;
+    Ok(handle)
+  }
+}
 
-    Ok(())
+/// Run `turn` in the teammate's own process, or in this one.
+async fn run_agent_turn(
+  agent_control: &AgentControl,
+  process: Option<&TeammateProcess>,
+  turn: Turn,
+) -> anyhow::Result<crate::turn::TurnResult> {
+  match process {
+    Some(process) => process.run_turn(turn.user_message).await,
+    None => agent_control.process_turn(turn).await,
   }
 }
 
+/// Model requests and finished tool calls, after which the budget is checked
+/// without waiting for the turn to end.
+fn spends_budget(event: &EventMsg) -> bool {
+  matches!(
+    event,
+    EventMsg::TokenCount(_)
+      | EventMsg::ExecCommandEnd(_)
+      | EventMsg::McpToolCallEnd(_)
+      | EventMsg::PatchApplyEnd(_)
+      | EventMsg::WebSearchEnd(_)
+  )
+}
+
+/// The runtime owning `thread_id` and the reason its budget is spent, if any.
+fn budget_exhaustion(thread_id: &str) -> Option<(Arc<TeamRuntime>, String)> {
+  let runtime = runtime_for_thread(thread_id)?;
+  let reason = runtime.budget_exhaustion(thread_id)?;
+  Some((runtime, reason))
+}
+
 fn wake_message(reason: &WakeReason) -> String {
   match reason {
     WakeReason::UserInput => {
@@ -2015,6 +3761,8 @@ mod team_runs {
   use cokra_protocol::TeamPlan;
   use cokra_protocol::TeamPlanStatus;
   use cokra_protocol::TeamTask;
+  use cokra_protocol::TeamTaskReadyState;
+  use cokra_protocol::TeamTaskStatus;
   use cokra_protocol::WorkflowApprovalState;
   use cokra_protocol::WorkflowApprovalStatus;
   use cokra_protocol::WorkflowArtifact;
@@ -2024,6 +3772,8 @@ mod team_runs {
   use cokra_protocol::WorkflowStepState;
   use cokra_protocol::WorkflowStepStatus;
 
+  use crate::agent::workflow::ACCEPTANCE_STEP_ID;
+
   const AD_HOC_PLAN_WORKFLOW: &str = "ad_hoc_plan";
   const TEAM_PLAN_WORKFLOW: &str = "team_plan";
 
@@ -2031,6 +3781,17 @@ mod team_runs {
   pub(crate) struct TeamRunState {
     #[serde(default)]
     runs: HashMap<String, WorkflowRun>,
+    /// Steps of template runs, keyed by the id of the task that drives them.
+    #[serde(default)]
+    task_steps: HashMap<String, TaskStep>,
+  }
+
+  #[derive(Debug, Clone, Serialize, Deserialize)]
+  struct TaskStep {
+    run_id: String,
+    step_id: String,
+    #[serde(default)]
+    review_gated: bool,
   }
 
   impl TeamRunState {
@@ -2291,11 +4052,126 @@ mod team_runs {
       Some(run.clone())
     }
 
+    /// Start a run for a workflow template with one pending step per
+    /// `(step_id, title)`; tasks are linked to steps with [`Self::link_task`].
+    pub(crate) fn create_run_from_template(
+      &mut self,
+      owner_thread_id: String,
+      workflow_name: String,
+      title: String,
+      steps: Vec<(String, String)>,
+    ) -> WorkflowRun {
+      let now = Utc::now().timestamp();
+      let run_id = Uuid::new_v4().to_string();
+      let steps = steps
+        .into_iter()
+        .map(|(id, title)| WorkflowStepState {
+          id,
+          title,
+          details: None,
+          status: WorkflowStepStatus::Pending,
+          assigned_thread_id: None,
+          updated_at: now,
+        })
+        .collect::<Vec<_>>();
+      let run = WorkflowRun {
+        id: run_id.clone(),
+        workflow_name,
+        title,
+        owner_thread_id: owner_thread_id.clone(),
+        status: WorkflowRunStatus::Active,
+        resume_token: Some(format!("workflow://{owner_thread_id}/{run_id}")),
+        current_step_id: steps.first().map(|step| step.id.clone()),
+        steps,
+        artifacts: Vec::new(),
+        approval: WorkflowApprovalState {
+          status: WorkflowApprovalStatus::Approved,
+          updated_at: now,
+          ..Default::default()
+        },
+        created_at: now,
+        updated_at: now,
+      };
+      self.runs.insert(run_id, run.clone());
+      run
+    }
+
+    pub(crate) fn link_task(
+      &mut self,
+      task_id: &str,
+      run_id: &str,
+      step_id: &str,
+      review_gated: bool,
+    ) {
+      self.task_steps.insert(
+        task_id.to_string(),
+        TaskStep {
+          run_id: run_id.to_string(),
+          step_id: step_id.to_string(),
+          review_gated,
+        },
+      );
+    }
+
+    pub(crate) fn run(&self, run_id: &str) -> Option<WorkflowRun> {
+      self.runs.get(run_id).cloned()
+    }
+
+    /// Whether `task_id` must be approved by its reviewer before it completes.
+    pub(crate) fn is_review_gated(&self, task_id: &str) -> bool {
+      self
+        .task_steps
+        .get(task_id)
+        .is_some_and(|link| link.review_gated)
+    }
+
+    /// Mirror a template task onto its step. The run completes once its
+    /// acceptance step (or, without one, every step) is done.
+    pub(crate) fn sync_task(&mut self, task: &TeamTask) -> Option<WorkflowRun> {
+      let link = self.task_steps.get(&task.id)?;
+      let run = self.runs.get_mut(&link.run_id)?;
+      let now = Utc::now().timestamp();
+      let status = match task.status {
+        TeamTaskStatus::Pending if task.ready_state == TeamTaskReadyState::Blocked => {
+          WorkflowStepStatus::Blocked
+        }
+        TeamTaskStatus::Pending => WorkflowStepStatus::Pending,
+        TeamTaskStatus::InProgress | TeamTaskStatus::Review => WorkflowStepStatus::InProgress,
+        TeamTaskStatus::Completed => WorkflowStepStatus::Completed,
+        TeamTaskStatus::Failed => WorkflowStepStatus::Failed,
+        TeamTaskStatus::Canceled => WorkflowStepStatus::Skipped,
+      };
+      let step = run.steps.iter_mut().find(|step| step.id == link.step_id)?;
+      step.status = status.clone();
+      step.assigned_thread_id = task.assignee_thread_id.clone();
+      step.updated_at = now;
+      if status == WorkflowStepStatus::InProgress {
+        run.current_step_id = Some(step.id.clone());
+      }
+
+      let accepted = link.step_id == ACCEPTANCE_STEP_ID && status == WorkflowStepStatus::Completed;
+      let all_done = run.steps.iter().all(|step| {
+        matches!(
+          step.status,
+          WorkflowStepStatus::Completed | WorkflowStepStatus::Skipped
+        )
+      });
+      if accepted || all_done {
+        run.status = WorkflowRunStatus::Completed;
+      }
+      run.updated_at = now;
+      Some(run.clone())
+    }
+
     pub(crate) fn note_task_claim(
       &mut self,
       task: &TeamTask,
       claimer_thread_id: &str,
     ) -> Option<WorkflowRun> {
+      // Template runs keep their leader as owner; only the task's step moves.
+      if self.task_steps.contains_key(&task.id) {
+        return self.sync_task(task);
+      }
       let workflow_run_id = task.workflow_run_id.as_deref()?;
       let run = self.runs.get_mut(workflow_run_id)?;
       let now = Utc::now().timestamp();
@@ -2320,6 +4196,7 @@ mod team_runs {
 
     pub(crate) fn clear(&mut self) {
       self.runs.clear();
+      self.task_steps.clear();
     }
 
     fn find_latest_open_run(&self, owner_thread_id: &str, workflow_name: &str) -> Option<String> {
@@ -2366,6 +4243,58 @@ mod team_runs {
   mod tests {
     use super::*;
 
+    #[test]
+    fn template_run_follows_its_tasks_until_accepted() {
+      let mut state = TeamRunState::default();
+      let run = state.create_run_from_template(
+        "root".to_string(),
+        "ship".to_string(),
+        "ship: login".to_string(),
+        vec![
+          ("implement".to_string(), "Implement login".to_string()),
+          (ACCEPTANCE_STEP_ID.to_string(), "Accept login".to_string()),
+        ],
+      );
+      state.link_task("task-1", &run.id, "implement", true);
+      state.link_task("task-2", &run.id, ACCEPTANCE_STEP_ID, false);
+      assert!(state.is_review_gated("task-1"));
+      assert!(!state.is_review_gated("task-2"));
+
+      let mut task = TeamTask {
+        id: "task-1".to_string(),
+        title: "Implement login".to_string(),
+        details: None,
+        status: TeamTaskStatus::InProgress,
+        ready_state: TeamTaskReadyState::Claimed,
+        review_state: Default::default(),
+        owner_thread_id: Some("coder".to_string()),
+        blocked_by_task_ids: Vec::new(),
+        blocks_task_ids: vec!["task-2".to_string()],
+        blocking_reason: None,
+        blockers: Vec::new(),
+        requested_scopes: Vec::new(),
+        granted_scopes: Vec::new(),
+        scope_policy_override: false,
+        assignee_thread_id: Some("coder".to_string()),
+        reviewer_thread_id: None,
+        workflow_run_id: Some(run.id.clone()),
+        created_at: 0,
+        updated_at: 0,
+        notes: Vec::new(),
+      };
+      let claimed = state.note_task_claim(&task, "coder").expect("claimed");
+      assert_eq!(claimed.owner_thread_id, "root");
+      assert_eq!(claimed.steps[0].status, WorkflowStepStatus::InProgress);
+
+      task.status = TeamTaskStatus::Completed;
+      let run = state.sync_task(&task).expect("synced");
+      assert_eq!(run.status, WorkflowRunStatus::Active);
+
+      task.id = "task-2".to_string();
+      let run = state.sync_task(&task).expect("accepted");
+      assert_eq!(run.status, WorkflowRunStatus::Completed);
+    }
+
     #[test]
     fn team_plan_workflow_requires_approval_until_reviewed() {
       let mut state = TeamRunState::default();
@@ -2412,3 +4341,105 @@ mod team_runs {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn teammate(thread_id: &str, history: Vec<Message>) -> PersistedTeammate {
+    PersistedTeammate {
+      thread_id: thread_id.to_string(),
+      parent_thread_id: None,
+      depth: 1,
+      nickname: Some("robin".to_string()),
+      role: "default".to_string(),
+      task: "Refactor the parser".to_string(),
+      created_at: 0,
+      model: "mock/default".to_string(),
+      reasoning_effort: None,
+      temperature: None,
+      worktree: None,
+      history,
+      last_turn_summary: None,
+      inflight_message: None,
+      pending_wakes: Vec::new(),
+      usage: TeamMemberUsage::default(),
+      blocked_reason: None,
+    }
+  }
+
+  fn set_summary(roster: &TeamRoster, thread_id: &str, summary: &str) {
+    roster
+      .teammates
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .get_mut(thread_id)
+      .expect("teammate")
+      .last_turn_summary = Some(summary.to_string());
+  }
+
+  #[tokio::test]
+  async fn roster_saves_write_only_changed_histories() {
+    let tmpdir = tempfile::tempdir().expect("tempdir");
+    let state_db = Arc::new(
+      StateDb::new(StateDb::default_path_for(tmpdir.path()))
+        .await
+        .expect("state db"),
+    );
+    let roster = TeamRoster::load(state_db.clone(), "teammates::test".to_string())
+      .await
+      .expect("load roster");
+    roster
+      .upsert(teammate("agent-1", vec![Message::User("hi".to_string())]))
+      .await;
+    roster.upsert(teammate("agent-2", Vec::new())).await;
+
+    let history_key = history_store_key("teammates::test", "agent-1");
+    let stored = state_db
+      .load_json::<Vec<Message>>(&history_key)
+      .await
+      .expect("load history");
+    assert_eq!(stored.map(|history| history.len()), Some(1));
+    let document = state_db
+      .load_json::<serde_json::Value>("teammates::test")
+      .await
+      .expect("load roster")
+      .expect("roster document");
+    assert!(document["agent-1"].get("history").is_none());
+
+    // A checkpoint that touches no history leaves every history alone.
+    state_db.delete(&history_key).await.expect("delete history");
+    set_summary(&roster, "agent-1", "parsed");
+    roster.save(None).await;
+    assert!(
+      state_db
+        .load_json::<Vec<Message>>(&history_key)
+        .await
+        .expect("load history")
+        .is_none()
+    );
+    let document = state_db
+      .load_json::<serde_json::Value>("teammates::test")
+      .await
+      .expect("load roster")
+      .expect("roster document");
+    assert_eq!(document["agent-1"]["last_turn_summary"], "parsed");
+
+    // A turn boundary rewrites only that teammate's history.
+    roster
+      .teammates
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .get_mut("agent-1")
+      .expect("teammate")
+      .history
+      .push(Message::User("again".to_string()));
+    roster.save(Some("agent-1")).await;
+
+    let reloaded = TeamRoster::load(state_db, "teammates::test".to_string())
+      .await
+      .expect("reload roster");
+    assert_eq!(reloaded.get("agent-1").expect("agent-1").history.len(), 2);
+    assert!(reloaded.get("agent-2").expect("agent-2").history.is_empty());
+  }
+}
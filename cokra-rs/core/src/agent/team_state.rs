@@ -1,3 +1,4 @@
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::HashSet;
 
@@ -16,6 +17,8 @@ use cokra_protocol::TaskBlocker;
 use cokra_protocol::TaskBlockerKind;
 use cokra_protocol::TaskEdge;
 use cokra_protocol::TaskEdgeKind;
+use cokra_protocol::TeamDoc;
+use cokra_protocol::TeamDocSummary;
 use cokra_protocol::TeamMember;
 use cokra_protocol::TeamMessage;
 use cokra_protocol::TeamMessageAckState;
@@ -119,6 +122,86 @@ impl StoredMessage {
   }
 }
 
+/// Versions kept per team document; older ones are dropped from the history.
+const MAX_DOC_VERSIONS: usize = 50;
+const MAX_DOC_NAME_CHARS: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredDocVersion {
+  version: u64,
+  content: String,
+  author_thread_id: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  summary: Option<String>,
+  created_at: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct StoredDoc {
+  #[serde(default)]
+  versions: Vec<StoredDocVersion>,
+  #[serde(default)]
+  subscribers: BTreeSet<String>,
+}
+
+impl StoredDoc {
+  fn latest_version(&self) -> u64 {
+    self.versions.last().map_or(0, |entry| entry.version)
+  }
+
+  fn to_team_doc(&self, name: &str, version: Option<u64>) -> Option<TeamDoc> {
+    let entry = match version {
+      Some(version) => self
+        .versions
+        .iter()
+        .find(|entry| entry.version == version)?,
+      None => self.versions.last()?,
+    };
+    Some(TeamDoc {
+      name: name.to_string(),
+      version: entry.version,
+      latest_version: self.latest_version(),
+      content: entry.content.clone(),
+      author_thread_id: entry.author_thread_id.clone(),
+      summary: entry.summary.clone(),
+      created_at: entry.created_at,
+      subscribers: self.subscribers.iter().cloned().collect(),
+    })
+  }
+
+  fn to_summary(&self, name: &str) -> Option<TeamDocSummary> {
+    let latest = self.versions.last()?;
+    Some(TeamDocSummary {
+      name: name.to_string(),
+      version: latest.version,
+      author_thread_id: latest.author_thread_id.clone(),
+      updated_at: latest.created_at,
+      subscribers: self.subscribers.iter().cloned().collect(),
+    })
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum DocWriteFailure {
+  InvalidName,
+  /// The edit was made against `base_version`, but the document has moved on.
+  VersionConflict {
+    latest_version: u64,
+  },
+}
+
+/// Unified diff between two versions of a team document.
+pub(crate) fn render_doc_diff(from: &TeamDoc, to: &TeamDoc) -> String {
+  similar::TextDiff::from_lines(&from.content, &to.content)
+    .unified_diff()
+    .context_radius(3)
+    .header(
+      &format!("{}@v{}", from.name, from.version),
+      &format!("{}@v{}", to.name, to.version),
+    )
+    .to_string()
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub(crate) struct TeamState {
   #[serde(default)]
@@ -135,6 +218,8 @@ pub(crate) struct TeamState {
   messages: Vec<StoredMessage>,
   #[serde(default)]
   mailbox_version: u64,
+  #[serde(default)]
+  docs: HashMap<String, StoredDoc>,
 }
 
 #[derive(Debug, Clone)]
@@ -174,10 +259,13 @@ impl TeamState {
         role: thread.role,
         task: thread.task,
         depth: thread.depth,
+        model: None,
         state: statuses
           .get(&thread.thread_id.to_string())
           .cloned()
           .unwrap_or_default(),
+        usage: Default::default(),
+        liveness: None,
       })
       .collect::<Vec<_>>();
 
@@ -206,6 +294,9 @@ impl TeamState {
       recent_messages,
       ownership_leases,
       workflow: run_state,
+      team_usage: Default::default(),
+      team_budget: None,
+      docs: self.sorted_docs(),
     }
   }
 
@@ -1052,6 +1143,82 @@ impl TeamState {
     self.plans.clear();
     self.messages.clear();
     self.mailbox_version = 0;
+    self.docs.clear();
+  }
+
+  /// Save `content` as the next version of `name`. `base_version` is the
+  /// version the edit was made against (0 creates the document); a stale base
+  /// is rejected so concurrent editors cannot silently overwrite each other.
+  pub(crate) fn write_doc(
+    &mut self,
+    name: &str,
+    author_thread_id: String,
+    content: String,
+    base_version: u64,
+    summary: Option<String>,
+  ) -> Result<TeamDoc, DocWriteFailure> {
+    let name = name.trim();
+    if name.is_empty()
+      || name.chars().count() > MAX_DOC_NAME_CHARS
+      || name.chars().any(char::is_control)
+    {
+      return Err(DocWriteFailure::InvalidName);
+    }
+    let latest_version = self.docs.get(name).map_or(0, StoredDoc::latest_version);
+    if base_version != latest_version {
+      return Err(DocWriteFailure::VersionConflict { latest_version });
+    }
+
+    let doc = self.docs.entry(name.to_string()).or_default();
+    doc.versions.push(StoredDocVersion {
+      version: latest_version + 1,
+      content,
+      author_thread_id,
+      summary: summary
+        .map(|summary| summary.trim().to_string())
+        .filter(|summary| !summary.is_empty()),
+      created_at: Utc::now().timestamp(),
+    });
+    let excess = doc.versions.len().saturating_sub(MAX_DOC_VERSIONS);
+    doc.versions.drain(..excess);
+    doc
+      .to_team_doc(name, None)
+      .ok_or(DocWriteFailure::InvalidName)
+  }
+
+  /// A version of `name`, or its latest when `version` is `None`.
+  pub(crate) fn doc(&self, name: &str, version: Option<u64>) -> Option<TeamDoc> {
+    self
+      .docs
+      .get(name.trim())?
+      .to_team_doc(name.trim(), version)
+  }
+
+  /// Add or remove `thread_id` from the watchers of an existing document.
+  pub(crate) fn set_doc_subscription(
+    &mut self,
+    name: &str,
+    thread_id: &str,
+    subscribed: bool,
+  ) -> Option<TeamDoc> {
+    let name = name.trim();
+    let doc = self.docs.get_mut(name)?;
+    if subscribed {
+      doc.subscribers.insert(thread_id.to_string());
+    } else {
+      doc.subscribers.remove(thread_id);
+    }
+    doc.to_team_doc(name, None)
+  }
+
+  pub(crate) fn sorted_docs(&self) -> Vec<TeamDocSummary> {
+    let mut docs = self
+      .docs
+      .iter()
+      .filter_map(|(name, doc)| doc.to_summary(name))
+      .collect::<Vec<_>>();
+    docs.sort_by(|left, right| left.name.cmp(&right.name));
+    docs
   }
 
   pub(crate) fn open_task_count_for_thread(&self, thread_id: &str) -> usize {
@@ -1815,4 +1982,59 @@ mod tests {
       "conflicting writers should still be blocked on the claimed file"
     );
   }
+
+  #[test]
+  fn team_docs_keep_history_and_reject_stale_edits() {
+    let mut state = TeamState::default();
+    let first = state
+      .write_doc(
+        " api.md ",
+        "alpha".to_string(),
+        "GET /a\n".to_string(),
+        0,
+        Some("draft".to_string()),
+      )
+      .expect("doc should be created");
+    assert_eq!((first.name.as_str(), first.version), ("api.md", 1));
+    assert_eq!(
+      state.write_doc("", "alpha".to_string(), String::new(), 0, None),
+      Err(DocWriteFailure::InvalidName)
+    );
+
+    state
+      .set_doc_subscription("api.md", "beta", true)
+      .expect("doc should exist");
+    assert!(
+      state
+        .set_doc_subscription("missing.md", "beta", true)
+        .is_none()
+    );
+
+    let second = state
+      .write_doc(
+        "api.md",
+        "beta".to_string(),
+        "GET /a\nPOST /b\n".to_string(),
+        1,
+        None,
+      )
+      .expect("edit against the latest version should apply");
+    assert_eq!(second.version, 2);
+    assert_eq!(second.subscribers, vec!["beta".to_string()]);
+    assert_eq!(
+      state.write_doc("api.md", "alpha".to_string(), "stale".to_string(), 1, None),
+      Err(DocWriteFailure::VersionConflict { latest_version: 2 })
+    );
+
+    let old = state.doc("api.md", Some(1)).expect("v1 should be kept");
+    assert_eq!((old.version, old.latest_version), (1, 2));
+    let diff = render_doc_diff(&old, &second);
+    assert!(diff.contains("+POST /b"), "{diff}");
+    assert_eq!(state.sorted_docs()[0].version, 2);
+
+    let restored: TeamState =
+      serde_json::from_str(&serde_json::to_string(&state).expect("serialize"))
+        .expect("deserialize");
+    assert_eq!(restored.doc("api.md", None), Some(second));
+  }
 }
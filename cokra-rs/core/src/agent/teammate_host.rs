@@ -0,0 +1,397 @@
+//! Child side of an out-of-process teammate.
+//!
+//! `cokra teammate-host --socket <path>` connects back to the leader, rebuilds
+//! the teammate from the [`TeammateInit`] it is sent, and runs its turns. The
+//! team itself (mailbox, task board, leases, plans) stays in the leader, so
+//! collaboration tools and team gates are forwarded over the socket.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::OnceLock;
+use std::sync::Weak;
+use std::time::Duration;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use cokra_protocol::Event;
+use cokra_protocol::Op;
+use cokra_protocol::UserInput;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::io::BufReader;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::agent::AgentControl;
+use crate::agent::Guards;
+use crate::agent::Turn;
+use crate::model::ModelClient;
+use crate::model::init_model_layer;
+use crate::session::Session;
+use crate::tools::build_tooling_with_overrides;
+use crate::tools::context::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+use crate::tools::spec::ToolSourceKind;
+
+use super::teammate_wire::HostFrame;
+use super::teammate_wire::ProxyReply;
+use super::teammate_wire::ProxyRequest;
+use super::teammate_wire::TeammateFrame;
+use super::teammate_wire::TeammateInit;
+use super::teammate_wire::read_frame;
+use super::teammate_wire::write_frame;
+
+const FRAME_CHANNEL_CAPACITY: usize = 512;
+const WRITER_FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
+
+static TEAMMATE_LINKS: OnceLock<Mutex<HashMap<String, Arc<TeammateLink>>>> = OnceLock::new();
+
+fn link_registry() -> &'static Mutex<HashMap<String, Arc<TeammateLink>>> {
+  TEAMMATE_LINKS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// The connection back to the leader, when `thread_id` is hosted by this
+/// process on the leader's behalf.
+pub(crate) fn teammate_link(thread_id: &str) -> Option<Arc<TeammateLink>> {
+  link_registry()
+    .lock()
+    .unwrap_or_else(std::sync::PoisonError::into_inner)
+    .get(thread_id)
+    .cloned()
+}
+
+/// Sends requests to the leader and matches up its replies.
+pub(crate) struct TeammateLink {
+  tx_frame: mpsc::Sender<TeammateFrame>,
+  pending: Mutex<HashMap<String, oneshot::Sender<ProxyReply>>>,
+}
+
+impl TeammateLink {
+  async fn request(&self, request: ProxyRequest) -> anyhow::Result<ProxyReply> {
+    let request_id = Uuid::new_v4().to_string();
+    let (tx, rx) = oneshot::channel();
+    self
+      .pending
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .insert(request_id.clone(), tx);
+    self
+      .tx_frame
+      .send(TeammateFrame::Request {
+        request_id,
+        request,
+      })
+      .await
+      .map_err(|_| anyhow::anyhow!("connection to the team leader is closed"))?;
+    rx.await
+      .map_err(|_| anyhow::anyhow!("team leader went away before replying"))
+  }
+
+  fn resolve(&self, request_id: &str, reply: ProxyReply) {
+    let pending = self
+      .pending
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .remove(request_id);
+    if let Some(tx) = pending {
+      let _ = tx.send(reply);
+    }
+  }
+
+  pub(crate) async fn ensure_mutation_paths_owned(&self, paths: &[String]) -> Result<(), String> {
+    match self
+      .request(ProxyRequest::EnsureMutationPathsOwned {
+        paths: paths.to_vec(),
+      })
+      .await
+    {
+      Ok(ProxyReply::Denied { reason }) => Err(reason),
+      Ok(_) => Ok(()),
+      Err(err) => Err(err.to_string()),
+    }
+  }
+
+  /// Tradeoff: an unreachable leader reads as "approval required" so a child
+  /// that lost its team cannot start mutating the workspace on its own.
+  pub(crate) async fn requires_plan_approval(&self) -> bool {
+    !matches!(
+      self.request(ProxyRequest::RequiresPlanApproval).await,
+      Ok(ProxyReply::Flag { value: false })
+    )
+  }
+}
+
+/// Runs a collaboration tool in the leader, where the team lives.
+struct ProxyToolHandler;
+
+#[async_trait]
+impl ToolHandler for ProxyToolHandler {
+  fn kind(&self) -> ToolKind {
+    ToolKind::Function
+  }
+
+  async fn handle_async(
+    &self,
+    invocation: ToolInvocation,
+  ) -> Result<ToolOutput, FunctionCallError> {
+    let arguments = invocation.raw_arguments()?.to_string();
+    let runtime = invocation.runtime.ok_or_else(|| {
+      FunctionCallError::Fatal(format!("{} missing runtime context", invocation.name))
+    })?;
+    let link = teammate_link(&runtime.thread_id).ok_or_else(|| {
+      FunctionCallError::Execution(format!("{} team leader is not connected", invocation.name))
+    })?;
+    let reply = link
+      .request(ProxyRequest::CallTool {
+        name: invocation.name.clone(),
+        arguments,
+        turn_id: runtime.turn_id.clone(),
+        cwd: invocation.cwd.clone(),
+      })
+      .await
+      .map_err(|err| FunctionCallError::Execution(format!("{err:#}")))?;
+    match reply {
+      ProxyReply::Tool { text, is_error } => Ok(
+        ToolOutput::success(text)
+          .with_success(!is_error)
+          .with_id(invocation.id),
+      ),
+      ProxyReply::ToolError { message } => Err(FunctionCallError::RespondToModel(message)),
+      other => Err(FunctionCallError::Fatal(format!(
+        "unexpected reply to {}: {other:?}",
+        invocation.name
+      ))),
+    }
+  }
+}
+
+/// Entry point for `cokra teammate-host`: serve one teammate for the leader
+/// listening on `socket`.
+#[cfg(unix)]
+pub async fn serve_teammate_process(socket: &Path) -> anyhow::Result<()> {
+  let stream = tokio::net::UnixStream::connect(socket)
+    .await
+    .with_context(|| format!("failed to connect to {}", socket.display()))?;
+  serve_teammate(stream, None).await
+}
+
+#[cfg(not(unix))]
+pub async fn serve_teammate_process(_socket: &Path) -> anyhow::Result<()> {
+  anyhow::bail!("out-of-process teammates need Unix domain sockets")
+}
+
+/// Serve one teammate over `stream` until the leader shuts it down or hangs
+/// up. `model_client` overrides the one built from the sent config.
+pub(crate) async fn serve_teammate<S>(
+  stream: S,
+  model_client: Option<Arc<ModelClient>>,
+) -> anyhow::Result<()>
+where
+  S: AsyncRead + AsyncWrite + Send + 'static,
+{
+  let (reader, mut writer) = tokio::io::split(stream);
+  let mut reader = BufReader::new(reader);
+  let init = match read_frame::<_, HostFrame>(&mut reader).await? {
+    Some(HostFrame::Init(init)) => *init,
+    Some(_) => anyhow::bail!("expected an init frame from the team leader"),
+    None => return Ok(()),
+  };
+  let TeammateInit {
+    thread_id,
+    root_thread_id,
+    config,
+    turn_config,
+    tool_policy,
+    max_tool_calls,
+    history,
+  } = init;
+
+  let (tx_frame, mut rx_frame) = mpsc::channel::<TeammateFrame>(FRAME_CHANNEL_CAPACITY);
+  let writer_task = tokio::spawn(async move {
+    while let Some(frame) = rx_frame.recv().await {
+      if write_frame(&mut writer, &frame).await.is_err() {
+        break;
+      }
+    }
+  });
+
+  let model_client = match model_client {
+    Some(model_client) => model_client,
+    None => init_model_layer(&config)
+      .await
+      .context("failed to initialize model layer")?,
+  };
+  let session = Arc::new(Session::new_with_thread_id(thread_id.clone()));
+  // Tradeoff: this ledger only sees the child's own spend, so the session
+  // limits apply per child here; the leader still checks team budgets
+  // against the shared ledger as the child's events arrive.
+  session
+    .cost_ledger()
+    .set_limits(config.budget.soft_limit_usd, config.budget.hard_limit_usd);
+  session.replace_history(history).await;
+  let tooling = build_tooling_with_overrides(&config, &turn_config.cwd, |registry| {
+    let proxied = registry
+      .list_specs()
+      .into_iter()
+      .filter(|spec| matches!(spec.source_kind, ToolSourceKind::BuiltinCollaboration))
+      .map(|spec| spec.name)
+      .collect::<Vec<_>>();
+    for name in proxied {
+      registry.register_handler(name, Arc::new(ProxyToolHandler));
+    }
+  })
+  .await?;
+  tooling.registry.set_access_policy(tool_policy);
+  tooling.registry.set_call_limit(max_tool_calls);
+  let tool_registry = tooling.registry.clone();
+
+  let (tx_event, mut rx_event) = mpsc::channel(FRAME_CHANNEL_CAPACITY);
+  let tx_frame_for_events = tx_frame.clone();
+  let event_id = thread_id.to_string();
+  tokio::spawn(async move {
+    while let Some(msg) = rx_event.recv().await {
+      let event = Event {
+        id: event_id.clone(),
+        msg,
+      };
+      if tx_frame_for_events
+        .send(TeammateFrame::Event(Box::new(event)))
+        .await
+        .is_err()
+      {
+        break;
+      }
+    }
+  });
+
+  let agent_control = Arc::new(AgentControl::new(
+    Uuid::new_v4().to_string(),
+    model_client,
+    tooling.registry,
+    tooling.router,
+    tooling.runtime,
+    session.clone(),
+    turn_config,
+    tx_event,
+    Weak::new(),
+    Arc::new(Guards::default()),
+    root_thread_id,
+  ));
+  agent_control.start().await?;
+
+  let link = Arc::new(TeammateLink {
+    tx_frame: tx_frame.clone(),
+    pending: Mutex::new(HashMap::new()),
+  });
+  link_registry()
+    .lock()
+    .unwrap_or_else(std::sync::PoisonError::into_inner)
+    .insert(thread_id.to_string(), link.clone());
+  let _ = tx_frame.send(TeammateFrame::Ready).await;
+
+  let mut active_turn: Option<(String, JoinHandle<()>)> = None;
+  let result = loop {
+    let frame = match read_frame::<_, HostFrame>(&mut reader).await {
+      Ok(Some(frame)) => frame,
+      Ok(None) => break Ok(()),
+      Err(err) => break Err(err),
+    };
+    let submission = match frame {
+      HostFrame::Submission(submission) => *submission,
+      HostFrame::Reply { request_id, result } => {
+        link.resolve(&request_id, result);
+        continue;
+      }
+      HostFrame::Init(_) => {
+        tracing::warn!("teammate {thread_id} ignored a second init frame");
+        continue;
+      }
+    };
+    match submission.op {
+      Op::UserInput { items, .. } => {
+        let message = items
+          .into_iter()
+          .filter_map(|item| match item {
+            UserInput::Text { text, .. } => Some(text),
+            _ => None,
+          })
+          .collect::<Vec<_>>()
+          .join("\n");
+        let submission_id = submission.id;
+        let turn_id = submission_id.clone();
+        let agent_control = agent_control.clone();
+        let session = session.clone();
+        let tool_registry = tool_registry.clone();
+        let tx_frame = tx_frame.clone();
+        let task = tokio::spawn(async move {
+          let result = agent_control
+            .process_turn(Turn {
+              turn_id: submission_id.clone(),
+              user_message: message,
+            })
+            .await
+            .map_err(|err| err.to_string());
+          let _ = tx_frame
+            .send(TeammateFrame::TurnFinished {
+              submission_id,
+              result,
+              history: session.clone_history().await,
+              tool_calls: tool_registry.dispatched_calls(),
+            })
+            .await;
+        });
+        active_turn = Some((turn_id, task));
+      }
+      Op::Interrupt => {
+        if let Some((turn_id, task)) = active_turn.take() {
+          task.abort();
+          session.clear_pending_approvals_for_turn(&turn_id).await;
+          session.clear_pending_user_inputs_for_turn(&turn_id).await;
+          session.end_turn(&turn_id).await;
+          agent_control.abort_turn().await;
+        }
+      }
+      Op::ExecApproval { id, decision, .. } => {
+        session.notify_exec_approval(&id, decision).await;
+      }
+      Op::UserInputAnswer { id, response } => {
+        session.notify_user_input(&id, response).await;
+      }
+      Op::SteerInput {
+        expected_turn_id,
+        items,
+      } => {
+        let _ = session
+          .steer_input(expected_turn_id.as_deref(), items)
+          .await;
+      }
+      Op::Shutdown => break Ok(()),
+      other => {
+        tracing::debug!("teammate {thread_id} ignored unsupported op {other:?}");
+      }
+    }
+  };
+
+  if let Some((_, task)) = active_turn.take() {
+    task.abort();
+  }
+  let _ = agent_control.stop().await;
+  link_registry()
+    .lock()
+    .unwrap_or_else(std::sync::PoisonError::into_inner)
+    .remove(&thread_id.to_string());
+  // Give queued frames a moment to flush; the forwarders may still hold
+  // senders, so the writer is not waited on indefinitely.
+  drop(tx_frame);
+  drop(link);
+  drop(agent_control);
+  let _ = tokio::time::timeout(WRITER_FLUSH_TIMEOUT, writer_task).await;
+  result
+}
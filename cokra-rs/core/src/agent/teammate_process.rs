@@ -0,0 +1,951 @@
+//! Leader side of an out-of-process teammate.
+//!
+//! With `[agents] execution = "process"` each teammate runs in a child
+//! `cokra teammate-host` process, so a panic or runaway tool only takes down
+//! that child. [`TeammateProcess`] launches the child, relays its events and
+//! approvals, answers its team requests, and relaunches it from the last
+//! known history when it dies mid-turn.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use cokra_protocol::EventMsg;
+use cokra_protocol::Op;
+use cokra_protocol::ResponseTokenUsage;
+use cokra_protocol::ReviewDecision;
+use cokra_protocol::Submission;
+use cokra_protocol::UserInput;
+use cokra_protocol::WarningEvent;
+use cokra_protocol::user_input::RequestUserInputResponse;
+use futures::future::BoxFuture;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::io::BufReader;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+use tokio::time::timeout;
+use uuid::Uuid;
+
+use crate::session::Session;
+use crate::tools::router::ToolRouter;
+use crate::tools::router::ToolRunContext;
+use crate::turn::TurnConfig;
+use crate::turn::TurnResult;
+
+use super::team_runtime::runtime_for_thread;
+use super::teammate_wire::HostFrame;
+use super::teammate_wire::ProxyReply;
+use super::teammate_wire::ProxyRequest;
+use super::teammate_wire::TeammateFrame;
+use super::teammate_wire::TeammateInit;
+use super::teammate_wire::read_frame;
+use super::teammate_wire::write_frame;
+
+const FRAME_CHANNEL_CAPACITY: usize = 512;
+const READY_TIMEOUT: Duration = Duration::from_secs(30);
+const CRASH_RESTART_NOTE: &str = "Your previous process crashed during this turn and was restarted. Check the current state of your files and task before redoing any work.";
+
+/// Byte stream to a teammate plus the process behind it, if any.
+pub(crate) struct LaunchedTeammate {
+  pub(crate) stream: Box<dyn TeammateStream>,
+  /// Killed when the connection is dropped.
+  pub(crate) child: Option<tokio::process::Child>,
+}
+
+pub(crate) trait TeammateStream: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> TeammateStream for T {}
+
+pub(crate) type TeammateLauncher =
+  Arc<dyn Fn() -> BoxFuture<'static, anyhow::Result<LaunchedTeammate>> + Send + Sync>;
+
+/// Launch `program teammate-host --socket <path>` and wait for it to connect.
+/// `program` defaults to the running `cokra` binary.
+#[cfg(unix)]
+pub(crate) fn process_launcher(program: Option<PathBuf>, cwd: PathBuf) -> TeammateLauncher {
+  Arc::new(move || {
+    let program = program.clone();
+    let cwd = cwd.clone();
+    Box::pin(async move {
+      let program = match program {
+        Some(program) => program,
+        None => std::env::current_exe()?,
+      };
+      let socket =
+        std::env::temp_dir().join(format!("cokra-teammate-{}.sock", Uuid::new_v4().simple()));
+      let listener = tokio::net::UnixListener::bind(&socket)?;
+      let spawned = tokio::process::Command::new(&program)
+        .arg("teammate-host")
+        .arg("--socket")
+        .arg(&socket)
+        .current_dir(&cwd)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn();
+      let mut child = match spawned {
+        Ok(child) => child,
+        Err(err) => {
+          let _ = std::fs::remove_file(&socket);
+          anyhow::bail!("failed to launch {}: {err}", program.display());
+        }
+      };
+      let pid = child.id();
+      if let Some(stdout) = child.stdout.take() {
+        forward_child_output(stdout, pid, "stdout");
+      }
+      if let Some(stderr) = child.stderr.take() {
+        forward_child_output(stderr, pid, "stderr");
+      }
+      let accepted = tokio::select! {
+        accepted = timeout(READY_TIMEOUT, listener.accept()) => accepted,
+        status = child.wait() => {
+          let _ = std::fs::remove_file(&socket);
+          anyhow::bail!("teammate process exited before connecting: {}", status?);
+        }
+      };
+      let _ = std::fs::remove_file(&socket);
+      let (stream, _) =
+        accepted.map_err(|_| anyhow::anyhow!("teammate process did not connect in time"))??;
+      Ok(LaunchedTeammate {
+        stream: Box::new(stream),
+        child: Some(child),
+      })
+    })
+  })
+}
+
+/// Log each line a teammate process writes to `stream` until it closes.
+#[cfg(unix)]
+fn forward_child_output<R>(stream: R, pid: Option<u32>, stream_name: &'static str)
+where
+  R: AsyncRead + Send + Unpin + 'static,
+{
+  use tokio::io::AsyncBufReadExt;
+
+  tokio::spawn(async move {
+    let mut lines = BufReader::new(stream).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+      tracing::info!(target: "cokra::teammate", pid, stream = stream_name, "{line}");
+    }
+  });
+}
+
+#[cfg(not(unix))]
+pub(crate) fn process_launcher(_program: Option<PathBuf>, _cwd: PathBuf) -> TeammateLauncher {
+  Arc::new(|| {
+    Box::pin(async { anyhow::bail!("out-of-process teammates need Unix domain sockets") })
+  })
+}
+
+/// Answers the requests a child cannot serve itself.
+#[async_trait]
+pub(crate) trait TeammateProxy: Send + Sync {
+  async fn handle(&self, request: ProxyRequest) -> ProxyReply;
+}
+
+/// Serves a child's team requests from the leader's own router and runtime.
+pub(crate) struct RouterProxy {
+  pub(crate) thread_id: String,
+  pub(crate) router: Arc<ToolRouter>,
+  pub(crate) session: Arc<Session>,
+  pub(crate) turn_config: TurnConfig,
+  pub(crate) tx_event: mpsc::Sender<EventMsg>,
+}
+
+#[async_trait]
+impl TeammateProxy for RouterProxy {
+  async fn handle(&self, request: ProxyRequest) -> ProxyReply {
+    match request {
+      ProxyRequest::CallTool {
+        name,
+        arguments,
+        turn_id,
+        cwd,
+      } => {
+        let arguments = match serde_json::from_str(&arguments) {
+          Ok(arguments) => arguments,
+          Err(err) => {
+            return ProxyReply::ToolError {
+              message: format!("invalid arguments for {name}: {err}"),
+            };
+          }
+        };
+        let mut ctx = ToolRunContext::new(
+          self.session.clone(),
+          self.thread_id.clone(),
+          turn_id,
+          cwd,
+          self.turn_config.approval_policy.clone(),
+          self.turn_config.sandbox_policy.clone(),
+        );
+        ctx.tx_event = Some(self.tx_event.clone());
+        match self.router.route_tool_call(&name, arguments, ctx).await {
+          Ok(output) => ProxyReply::Tool {
+            text: output.text_content(),
+            is_error: output.is_error(),
+          },
+          Err(err) => ProxyReply::ToolError {
+            message: err.to_string(),
+          },
+        }
+      }
+      ProxyRequest::EnsureMutationPathsOwned { paths } => {
+        let Some(runtime) = runtime_for_thread(&self.thread_id) else {
+          return ProxyReply::Allowed;
+        };
+        match runtime
+          .ensure_mutation_paths_owned(&self.thread_id, &paths)
+          .await
+        {
+          Ok(()) => ProxyReply::Allowed,
+          Err(err) => ProxyReply::Denied {
+            reason: err.to_string(),
+          },
+        }
+      }
+      ProxyRequest::RequiresPlanApproval => ProxyReply::Flag {
+        value: runtime_for_thread(&self.thread_id)
+          .is_some_and(|runtime| runtime.requires_plan_approval(&self.thread_id)),
+      },
+    }
+  }
+}
+
+/// One live connection to a child.
+struct Connection {
+  tx_frame: mpsc::Sender<HostFrame>,
+  turns: TurnTable,
+  _child: Option<tokio::process::Child>,
+}
+
+impl Connection {
+  fn is_alive(&self) -> bool {
+    !self.tx_frame.is_closed()
+  }
+}
+
+/// Turns waiting on a child, by submission id.
+type TurnTable = Arc<Mutex<HashMap<String, oneshot::Sender<FinishedTurn>>>>;
+
+struct FinishedTurn {
+  result: Result<TurnResult, String>,
+}
+
+/// A teammate hosted in a supervised child process.
+pub(crate) struct TeammateProcess {
+  init: TeammateInit,
+  session: Arc<Session>,
+  launcher: TeammateLauncher,
+  proxy: Arc<dyn TeammateProxy>,
+  tx_event: mpsc::Sender<EventMsg>,
+  max_restarts: u32,
+  connection: tokio::sync::Mutex<Option<Connection>>,
+  /// Approval and question ids the child is waiting on.
+  pending_prompts: Arc<Mutex<HashSet<String>>>,
+  tool_calls: Arc<AtomicU64>,
+  crashes: AtomicU32,
+}
+
+impl TeammateProcess {
+  /// `init.history` is ignored; the child is always started from `session`'s
+  /// history, which is kept in sync after every turn.
+  pub(crate) fn new(
+    init: TeammateInit,
+    session: Arc<Session>,
+    launcher: TeammateLauncher,
+    proxy: Arc<dyn TeammateProxy>,
+    tx_event: mpsc::Sender<EventMsg>,
+    max_restarts: u32,
+  ) -> Self {
+    Self {
+      init,
+      session,
+      launcher,
+      proxy,
+      tx_event,
+      max_restarts,
+      connection: tokio::sync::Mutex::new(None),
+      pending_prompts: Arc::new(Mutex::new(HashSet::new())),
+      tool_calls: Arc::new(AtomicU64::new(0)),
+      crashes: AtomicU32::new(0),
+    }
+  }
+
+  /// Tool calls the child has dispatched, as of its last finished turn.
+  pub(crate) fn tool_calls(&self) -> u64 {
+    self.tool_calls.load(Ordering::Relaxed)
+  }
+
+  /// Run one turn in the child, relaunching it if it dies on the way.
+  pub(crate) async fn run_turn(&self, message: String) -> anyhow::Result<TurnResult> {
+    let mut message = message;
+    loop {
+      let submission_id = Uuid::new_v4().to_string();
+      let (tx_done, rx_done) = oneshot::channel();
+      match self.connect().await {
+        Ok((tx_frame, turns)) => {
+          // Registered before sending so a fast child cannot finish first.
+          {
+            let mut turns = turns
+              .lock()
+              .unwrap_or_else(std::sync::PoisonError::into_inner);
+            turns.retain(|_, tx| !tx.is_closed());
+            turns.insert(submission_id.clone(), tx_done);
+          }
+          let _ = tx_frame
+            .send(HostFrame::Submission(Box::new(Submission {
+              id: submission_id,
+              op: Op::UserInput {
+                items: vec![UserInput::Text {
+                  text: message.clone(),
+                  text_elements: Vec::new(),
+                }],
+                final_output_json_schema: None,
+              },
+            })))
+            .await;
+        }
+        Err(err) => {
+          tracing::warn!("failed to launch teammate {}: {err:#}", self.init.thread_id);
+          drop(tx_done);
+        }
+      }
+      if let Ok(finished) = rx_done.await {
+        self.crashes.store(0, Ordering::Relaxed);
+        return finished.result.map_err(anyhow::Error::msg);
+      }
+
+      let crashes = self.crashes.fetch_add(1, Ordering::Relaxed) + 1;
+      if crashes > self.max_restarts {
+        anyhow::bail!("teammate process crashed too often ({crashes} in a row); giving up");
+      }
+      let _ = self
+        .tx_event
+        .send(EventMsg::Warning(WarningEvent {
+          thread_id: self.init.thread_id.to_string(),
+          turn_id: String::new(),
+          message: format!(
+            "Teammate process exited mid-turn; restarting it ({crashes}/{}).",
+            self.max_restarts
+          ),
+        }))
+        .await;
+      if !message.starts_with(CRASH_RESTART_NOTE) {
+        message = format!("{CRASH_RESTART_NOTE}\n\n{message}");
+      }
+    }
+  }
+
+  /// Interrupt the child's running turn, if it is still up.
+  pub(crate) async fn interrupt(&self) {
+    self.submit(Op::Interrupt).await;
+  }
+
+  pub(crate) async fn steer(&self, items: Vec<UserInput>) {
+    self
+      .submit(Op::SteerInput {
+        expected_turn_id: None,
+        items,
+      })
+      .await;
+  }
+
+  pub(crate) async fn shutdown(&self) {
+    self.submit(Op::Shutdown).await;
+    self.connection.lock().await.take();
+  }
+
+  /// Forward an approval decision if the child asked for `approval_id`.
+  pub(crate) async fn notify_exec_approval(
+    &self,
+    approval_id: &str,
+    decision: ReviewDecision,
+  ) -> bool {
+    if !self.take_prompt(approval_id) {
+      return false;
+    }
+    self
+      .submit(Op::ExecApproval {
+        id: approval_id.to_string(),
+        turn_id: None,
+        decision,
+      })
+      .await;
+    true
+  }
+
+  pub(crate) async fn notify_user_input(
+    &self,
+    request_id: &str,
+    response: RequestUserInputResponse,
+  ) -> bool {
+    if !self.take_prompt(request_id) {
+      return false;
+    }
+    self
+      .submit(Op::UserInputAnswer {
+        id: request_id.to_string(),
+        response,
+      })
+      .await;
+    true
+  }
+
+  fn take_prompt(&self, id: &str) -> bool {
+    self
+      .pending_prompts
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .remove(id)
+  }
+
+  async fn submit(&self, op: Op) {
+    let connection = self.connection.lock().await;
+    if let Some(connection) = connection
+      .as_ref()
+      .filter(|connection| connection.is_alive())
+    {
+      let _ = connection
+        .tx_frame
+        .send(HostFrame::Submission(Box::new(Submission {
+          id: Uuid::new_v4().to_string(),
+          op,
+        })))
+        .await;
+    }
+  }
+
+  /// The live connection's frame sender and turn table, launching a child
+  /// first if needed.
+  async fn connect(&self) -> anyhow::Result<(mpsc::Sender<HostFrame>, TurnTable)> {
+    let mut connection = self.connection.lock().await;
+    if let Some(live) = connection
+      .as_ref()
+      .filter(|connection| connection.is_alive())
+    {
+      return Ok((live.tx_frame.clone(), live.turns.clone()));
+    }
+    connection.take();
+    let launched = (self.launcher)().await?;
+    let mut init = self.init.clone();
+    init.history = self.session.clone_history().await;
+    let (reader, mut writer) = tokio::io::split(launched.stream);
+    let mut reader = BufReader::new(reader);
+    write_frame(&mut writer, &HostFrame::Init(Box::new(init))).await?;
+    match timeout(READY_TIMEOUT, read_frame::<_, TeammateFrame>(&mut reader)).await {
+      Ok(Ok(Some(TeammateFrame::Ready))) => {}
+      Ok(Ok(Some(_))) => anyhow::bail!("teammate process sent a frame before it was ready"),
+      Ok(Ok(None)) => anyhow::bail!("teammate process exited during startup"),
+      Ok(Err(err)) => return Err(err),
+      Err(_) => anyhow::bail!("teammate process did not become ready in time"),
+    }
+
+    let (tx_frame, mut rx_frame) = mpsc::channel::<HostFrame>(FRAME_CHANNEL_CAPACITY);
+    let turns: TurnTable = Arc::new(Mutex::new(HashMap::new()));
+    let writer_task = tokio::spawn(async move {
+      while let Some(frame) = rx_frame.recv().await {
+        if write_frame(&mut writer, &frame).await.is_err() {
+          break;
+        }
+      }
+    });
+    let reader_ctx = ReaderContext {
+      thread_id: self.init.thread_id.to_string(),
+      session: self.session.clone(),
+      proxy: self.proxy.clone(),
+      tx_event: self.tx_event.clone(),
+      tx_frame: tx_frame.clone(),
+      turns: turns.clone(),
+      pending_prompts: self.pending_prompts.clone(),
+      tool_calls: self.tool_calls.clone(),
+    };
+    tokio::spawn(async move {
+      reader_ctx.run(reader).await;
+      // Closing the writer marks the connection dead for the next caller.
+      writer_task.abort();
+    });
+
+    let handles = (tx_frame.clone(), turns.clone());
+    *connection = Some(Connection {
+      tx_frame,
+      turns,
+      _child: launched.child,
+    });
+    Ok(handles)
+  }
+}
+
+/// State the per-connection reader task needs.
+struct ReaderContext {
+  thread_id: String,
+  session: Arc<Session>,
+  proxy: Arc<dyn TeammateProxy>,
+  tx_event: mpsc::Sender<EventMsg>,
+  tx_frame: mpsc::Sender<HostFrame>,
+  turns: TurnTable,
+  pending_prompts: Arc<Mutex<HashSet<String>>>,
+  tool_calls: Arc<AtomicU64>,
+}
+
+impl ReaderContext {
+  async fn run<R>(self, mut reader: BufReader<R>)
+  where
+    R: AsyncRead + Unpin,
+  {
+    loop {
+      let frame = match read_frame::<_, TeammateFrame>(&mut reader).await {
+        Ok(Some(frame)) => frame,
+        Ok(None) => break,
+        Err(err) => {
+          tracing::warn!(
+            "dropping connection to teammate {}: {err:#}",
+            self.thread_id
+          );
+          break;
+        }
+      };
+      match frame {
+        TeammateFrame::Ready => {}
+        TeammateFrame::Event(event) => {
+          let msg = self.observe_event(event.msg);
+          let _ = self.tx_event.send(msg).await;
+        }
+        TeammateFrame::TurnFinished {
+          submission_id,
+          result,
+          history,
+          tool_calls,
+        } => {
+          // The leader's copy backs roster persistence and relaunches.
+          self.session.replace_history(history).await;
+          self.tool_calls.store(tool_calls, Ordering::Relaxed);
+          let tx_done = self
+            .turns
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .remove(&submission_id);
+          if let Some(tx_done) = tx_done {
+            let _ = tx_done.send(FinishedTurn { result });
+          }
+        }
+        TeammateFrame::Request {
+          request_id,
+          request,
+        } => {
+          let proxy = self.proxy.clone();
+          let tx_frame = self.tx_frame.clone();
+          tokio::spawn(async move {
+            let result = proxy.handle(request).await;
+            let _ = tx_frame.send(HostFrame::Reply { request_id, result }).await;
+          });
+        }
+      }
+    }
+    // Dropping the senders fails every turn still waiting on this child.
+    self
+      .turns
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .clear();
+    self
+      .pending_prompts
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .clear();
+  }
+
+  /// Track prompts the child is waiting on and fold its spend into the
+  /// leader's ledger, so team budgets and `/cost` see it.
+  fn observe_event(&self, mut msg: EventMsg) -> EventMsg {
+    let prompt_id = match &msg {
+      EventMsg::ExecApprovalRequest(event) => Some(event.id.clone()),
+      EventMsg::ApplyPatchApprovalRequest(event) => Some(event.call_id.clone()),
+      EventMsg::RequestUserInput(event) => Some(event.call_id.clone()),
+      _ => None,
+    };
+    if let Some(prompt_id) = prompt_id {
+      self
+        .pending_prompts
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .insert(prompt_id);
+    }
+    if let EventMsg::TokenCount(event) = &mut msg {
+      let ledger = self.session.cost_ledger();
+      let thread = ledger.record(
+        &self.thread_id,
+        &ResponseTokenUsage {
+          input_tokens: event.input_tokens,
+          cached_input_tokens: event.cached_input_tokens,
+          output_tokens: event.output_tokens,
+          reasoning_output_tokens: event.reasoning_output_tokens,
+          total_tokens: event.total_tokens,
+          cache_write_input_tokens: event.cache_write_input_tokens,
+        },
+        event.cost_usd,
+      );
+      event.thread_cost_usd = thread.cost_usd;
+      event.session_cost_usd = ledger.session_cost().cost_usd;
+    }
+    msg
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::pin::Pin;
+  use std::sync::Arc;
+  use std::sync::Mutex;
+  use std::sync::atomic::AtomicU32;
+  use std::sync::atomic::Ordering;
+
+  use async_trait::async_trait;
+  use cokra_config::Config;
+  use cokra_protocol::EventMsg;
+  use cokra_protocol::ThreadId;
+  use futures::Stream;
+  use pretty_assertions::assert_eq;
+  use reqwest::Client;
+  use tokio::sync::mpsc;
+
+  use super::LaunchedTeammate;
+  use super::TeammateLauncher;
+  use super::TeammateProcess;
+  use super::TeammateProxy;
+  use crate::agent::teammate_host::serve_teammate;
+  use crate::agent::teammate_wire::ProxyReply;
+  use crate::agent::teammate_wire::ProxyRequest;
+  use crate::agent::teammate_wire::TeammateInit;
+  use crate::model::ChatRequest;
+  use crate::model::ChatResponse;
+  use crate::model::Chunk;
+  use crate::model::ContentDelta;
+  use crate::model::ListModelsResponse;
+  use crate::model::Message;
+  use crate::model::ModelClient;
+  use crate::model::ProviderConfig;
+  use crate::model::ProviderRegistry;
+  use crate::model::ToolCallDelta;
+  use crate::model::provider::ModelProvider;
+  use crate::session::Session;
+  use crate::tools::registry::ToolAccessPolicy;
+  use crate::turn::TurnConfig;
+
+  /// Calls `team_status` once, then reports what the tool returned.
+  #[derive(Debug)]
+  struct TeamToolProvider {
+    client: Client,
+    config: ProviderConfig,
+  }
+
+  #[async_trait]
+  impl ModelProvider for TeamToolProvider {
+    fn provider_id(&self) -> &'static str {
+      "mock-team"
+    }
+
+    fn provider_name(&self) -> &'static str {
+      "Mock Team Provider"
+    }
+
+    async fn chat_completion(&self, _request: ChatRequest) -> crate::model::Result<ChatResponse> {
+      Err(crate::model::ModelError::InvalidRequest(
+        "chat_completion is unused in this test provider".to_string(),
+      ))
+    }
+
+    async fn chat_completion_stream(
+      &self,
+      request: ChatRequest,
+    ) -> crate::model::Result<Pin<Box<dyn Stream<Item = crate::model::Result<Chunk>> + Send>>> {
+      let tool_output = request.messages.iter().find_map(|message| match message {
+        Message::Tool { content, .. } => Some(content.clone()),
+        _ => None,
+      });
+      let chunk = match tool_output {
+        Some(content) => Chunk::Content {
+          delta: ContentDelta {
+            text: format!("team said: {content}"),
+          },
+        },
+        None => Chunk::ToolCall {
+          delta: ToolCallDelta {
+            id: Some("call_team_1".to_string()),
+            name: Some("team_status".to_string()),
+            arguments: Some("{}".to_string()),
+            thought_signature: None,
+          },
+        },
+      };
+      Ok(Box::pin(futures::stream::iter(vec![
+        Ok(chunk),
+        Ok(Chunk::MessageStop),
+      ])))
+    }
+
+    async fn list_models(&self) -> crate::model::Result<ListModelsResponse> {
+      Ok(ListModelsResponse {
+        object_type: "list".to_string(),
+        data: Vec::new(),
+      })
+    }
+
+    async fn validate_auth(&self) -> crate::model::Result<()> {
+      Ok(())
+    }
+
+    fn client(&self) -> &Client {
+      &self.client
+    }
+
+    fn config(&self) -> &ProviderConfig {
+      &self.config
+    }
+  }
+
+  async fn build_client() -> Arc<ModelClient> {
+    let registry = Arc::new(ProviderRegistry::new());
+    registry
+      .register(TeamToolProvider {
+        client: Client::new(),
+        config: ProviderConfig {
+          provider_id: "mock-team".to_string(),
+          ..Default::default()
+        },
+      })
+      .await;
+    registry
+      .set_default("mock-team")
+      .await
+      .expect("set default provider");
+    Arc::new(
+      ModelClient::new(registry)
+        .await
+        .expect("create model client"),
+    )
+  }
+
+  #[derive(Default)]
+  struct RecordingProxy {
+    tools: Mutex<Vec<String>>,
+  }
+
+  #[async_trait]
+  impl TeammateProxy for RecordingProxy {
+    async fn handle(&self, request: ProxyRequest) -> ProxyReply {
+      match request {
+        ProxyRequest::CallTool { name, .. } => {
+          self
+            .tools
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .push(name);
+          ProxyReply::Tool {
+            text: "2 teammates idle".to_string(),
+            is_error: false,
+          }
+        }
+        ProxyRequest::EnsureMutationPathsOwned { .. } => ProxyReply::Allowed,
+        ProxyRequest::RequiresPlanApproval => ProxyReply::Flag { value: false },
+      }
+    }
+  }
+
+  /// Hosts the child in-process over an in-memory pipe. The first
+  /// `dead_launches` launches hand back a pipe whose child end is gone.
+  fn in_memory_launcher(
+    model_client: Arc<ModelClient>,
+    dead_launches: u32,
+    launches: Arc<AtomicU32>,
+  ) -> TeammateLauncher {
+    Arc::new(move || {
+      let model_client = model_client.clone();
+      let launch = launches.fetch_add(1, Ordering::SeqCst);
+      Box::pin(async move {
+        let (leader, child) = tokio::io::duplex(1 << 16);
+        if launch < dead_launches {
+          drop(child);
+        } else {
+          tokio::spawn(serve_teammate(child, Some(model_client)));
+        }
+        Ok(LaunchedTeammate {
+          stream: Box::new(leader),
+          child: None,
+        })
+      })
+    })
+  }
+
+  fn teammate(
+    cwd: &std::path::Path,
+    launcher: TeammateLauncher,
+    proxy: Arc<RecordingProxy>,
+    max_restarts: u32,
+  ) -> (TeammateProcess, Arc<Session>, mpsc::Receiver<EventMsg>) {
+    let thread_id = ThreadId::new();
+    let config = Config {
+      cwd: cwd.to_path_buf(),
+      ..Config::default()
+    };
+    let turn_config = TurnConfig {
+      model: "mock-team/model".to_string(),
+      cwd: cwd.to_path_buf(),
+      ..TurnConfig::default()
+    };
+    let init = TeammateInit {
+      thread_id: thread_id.clone(),
+      root_thread_id: ThreadId::new(),
+      config,
+      turn_config,
+      tool_policy: ToolAccessPolicy::default(),
+      max_tool_calls: None,
+      history: Vec::new(),
+    };
+    let session = Arc::new(Session::new_with_thread_id(thread_id));
+    let (tx_event, rx_event) = mpsc::channel(512);
+    let process = TeammateProcess::new(
+      init,
+      session.clone(),
+      launcher,
+      proxy,
+      tx_event,
+      max_restarts,
+    );
+    (process, session, rx_event)
+  }
+
+  #[tokio::test]
+  async fn child_turn_proxies_team_tools_and_mirrors_history() {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let launches = Arc::new(AtomicU32::new(0));
+    let proxy = Arc::new(RecordingProxy::default());
+    let launcher = in_memory_launcher(build_client().await, 0, launches.clone());
+    let (process, session, _rx_event) = teammate(temp.path(), launcher, proxy.clone(), 3);
+
+    let result = process
+      .run_turn("check on the team".to_string())
+      .await
+      .expect("turn succeeds");
+
+    assert_eq!(result.content, "team said: 2 teammates idle");
+    assert!(result.success);
+    assert_eq!(
+      *proxy
+        .tools
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner),
+      vec!["team_status".to_string()]
+    );
+    assert_eq!(process.tool_calls(), 1);
+    assert!(!session.clone_history().await.is_empty());
+    assert_eq!(launches.load(Ordering::SeqCst), 1);
+    process.shutdown().await;
+  }
+
+  #[tokio::test]
+  async fn crashed_child_is_relaunched_until_restart_limit() {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let launches = Arc::new(AtomicU32::new(0));
+    let launcher = in_memory_launcher(build_client().await, 1, launches.clone());
+    let (process, _session, mut rx_event) = teammate(
+      temp.path(),
+      launcher,
+      Arc::new(RecordingProxy::default()),
+      3,
+    );
+
+    let result = process
+      .run_turn("check on the team".to_string())
+      .await
+      .expect("relaunched turn succeeds");
+
+    assert_eq!(result.content, "team said: 2 teammates idle");
+    assert_eq!(launches.load(Ordering::SeqCst), 2);
+    let mut warned = false;
+    while let Ok(event) = rx_event.try_recv() {
+      warned |=
+        matches!(event, EventMsg::Warning(ref warning) if warning.message.contains("restarting"));
+    }
+    assert!(warned, "expected a restart warning");
+
+    let launches = Arc::new(AtomicU32::new(0));
+    let launcher = in_memory_launcher(build_client().await, u32::MAX, launches.clone());
+    let (process, _session, _rx_event) = teammate(
+      temp.path(),
+      launcher,
+      Arc::new(RecordingProxy::default()),
+      2,
+    );
+    let err = process
+      .run_turn("check on the team".to_string())
+      .await
+      .expect_err("gives up after the restart limit");
+    assert!(err.to_string().contains("giving up"), "{err}");
+    assert_eq!(launches.load(Ordering::SeqCst), 3);
+  }
+
+  const HOST_SOCKET_ENV: &str = "COKRA_TEST_TEAMMATE_SOCKET";
+
+  /// What `cokra teammate-host` runs; the child of
+  /// `teammate_host_process_reports_failed_turns` re-enters the test binary here.
+  #[tokio::test]
+  #[ignore = "entry point for a spawned teammate-host process"]
+  async fn teammate_host_entry() {
+    if let Some(socket) = std::env::var_os(HOST_SOCKET_ENV) {
+      crate::serve_teammate_process(std::path::Path::new(&socket))
+        .await
+        .expect("serve teammate");
+    }
+  }
+
+  #[cfg(unix)]
+  #[tokio::test]
+  async fn teammate_host_process_reports_failed_turns() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = tempfile::tempdir().expect("tempdir");
+    let host = temp.path().join("cokra");
+    let test_binary = std::env::current_exe().expect("test binary");
+    std::fs::write(
+      &host,
+      format!(
+        "#!/bin/sh\n{HOST_SOCKET_ENV}=\"$3\" exec \"{}\" --exact agent::teammate_process::tests::teammate_host_entry --ignored --nocapture\n",
+        test_binary.display()
+      ),
+    )
+    .expect("write host script");
+    std::fs::set_permissions(&host, std::fs::Permissions::from_mode(0o755))
+      .expect("make host script executable");
+    let launcher = super::process_launcher(Some(host), temp.path().to_path_buf());
+    let (process, _session, _rx_event) = teammate(
+      temp.path(),
+      launcher,
+      Arc::new(RecordingProxy::default()),
+      0,
+    );
+
+    // The child builds its own model layer, which has no `mock-team`
+    // provider, so the turn fails there and the failure must come back.
+    let err = process
+      .run_turn("check on the team".to_string())
+      .await
+      .expect_err("the child's turn fails");
+
+    assert!(err.to_string().contains("mock-team"), "{err}");
+    assert!(
+      !err.to_string().contains("giving up"),
+      "the child should not crash: {err}"
+    );
+    process.shutdown().await;
+  }
+}
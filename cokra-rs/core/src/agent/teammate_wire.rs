@@ -0,0 +1,121 @@
+//! Frames exchanged between the leader and an out-of-process teammate.
+//!
+//! Each frame is one JSON object per line. The leader drives the child with
+//! ordinary [`Submission`]s and the child answers with [`Event`]s, plus a few
+//! frames for the team state that only the leader holds.
+
+use cokra_config::Config;
+use cokra_protocol::Event;
+use cokra_protocol::Submission;
+use cokra_protocol::ThreadId;
+use serde::Deserialize;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tokio::io::AsyncBufRead;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+
+use crate::model::Message;
+use crate::tools::registry::ToolAccessPolicy;
+use crate::turn::TurnConfig;
+use crate::turn::TurnResult;
+
+/// Everything a child needs to rebuild the teammate the leader configured.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct TeammateInit {
+  pub(crate) thread_id: ThreadId,
+  pub(crate) root_thread_id: ThreadId,
+  pub(crate) config: Config,
+  pub(crate) turn_config: TurnConfig,
+  pub(crate) tool_policy: ToolAccessPolicy,
+  pub(crate) max_tool_calls: Option<u64>,
+  pub(crate) history: Vec<Message>,
+}
+
+/// Leader → child.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum HostFrame {
+  Init(Box<TeammateInit>),
+  Submission(Box<Submission>),
+  Reply {
+    request_id: String,
+    result: ProxyReply,
+  },
+}
+
+/// Child → leader.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum TeammateFrame {
+  Ready,
+  Event(Box<Event>),
+  /// Sent once per `UserInput` submission; interrupted turns send nothing.
+  TurnFinished {
+    submission_id: String,
+    result: Result<TurnResult, String>,
+    history: Vec<Message>,
+    tool_calls: u64,
+  },
+  Request {
+    request_id: String,
+    request: ProxyRequest,
+  },
+}
+
+/// Work the child cannot do itself because the team lives in the leader.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum ProxyRequest {
+  CallTool {
+    name: String,
+    arguments: String,
+    turn_id: String,
+    cwd: std::path::PathBuf,
+  },
+  EnsureMutationPathsOwned {
+    paths: Vec<String>,
+  },
+  RequiresPlanApproval,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum ProxyReply {
+  Tool { text: String, is_error: bool },
+  ToolError { message: String },
+  Allowed,
+  Denied { reason: String },
+  Flag { value: bool },
+}
+
+pub(crate) async fn write_frame<W, T>(writer: &mut W, frame: &T) -> anyhow::Result<()>
+where
+  W: AsyncWrite + Unpin,
+  T: Serialize,
+{
+  let mut line = serde_json::to_vec(frame)?;
+  line.push(b'\n');
+  writer.write_all(&line).await?;
+  writer.flush().await?;
+  Ok(())
+}
+
+/// Read the next frame, or `None` once the peer has closed the stream.
+pub(crate) async fn read_frame<R, T>(reader: &mut R) -> anyhow::Result<Option<T>>
+where
+  R: AsyncBufRead + Unpin,
+  T: DeserializeOwned,
+{
+  let mut line = String::new();
+  loop {
+    line.clear();
+    if reader.read_line(&mut line).await? == 0 {
+      return Ok(None);
+    }
+    if !line.trim().is_empty() {
+      return Ok(Some(serde_json::from_str(&line)?));
+    }
+  }
+}
@@ -0,0 +1,508 @@
+//! Declarative team workflow templates.
+//!
+//! Templates live in `.cokra/workflows/*.toml` (the user directory first, then
+//! project directories from the outermost ancestor down, later files winning
+//! by name). Running one spawns its roles, creates its tasks with their
+//! dependencies, scopes and review gates, and tracks everything as a single
+//! workflow run:
+//!
+//! ```toml
+//! description = "Plan, implement, test and review a change"
+//!
+//! [[roles]]
+//! name = "coder"
+//! role = "coding"
+//!
+//! [[roles]]
+//! name = "reviewer"
+//! role = "review"
+//!
+//! [[tasks]]
+//! id = "implement"
+//! title = "Implement {{goal}}"
+//! assignee = "coder"
+//! scopes = [{ kind = "Directory", path = "src" }]
+//! review_by = "reviewer"
+//!
+//! [[tasks]]
+//! id = "test"
+//! title = "Test {{goal}}"
+//! assignee = "coder"
+//! depends_on = ["implement"]
+//!
+//! [acceptance]
+//! title = "Accept {{goal}}"
+//! ```
+//!
+//! `assignee` and `review_by` name a role or `leader`. `{{goal}}` is replaced by
+//! the goal given when the workflow is run. The acceptance task is assigned to
+//! the leader and waits for every task nothing else depends on.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+
+use cokra_protocol::ScopeRequest;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::model::ReasoningEffort;
+use crate::skills::loader::collect_files_with_extension;
+use crate::skills::loader::ordered_cokra_roots;
+
+pub const WORKFLOWS_DIR: &str = "workflows";
+
+/// Name that refers to the team leader in `assignee` and `review_by`.
+pub const LEADER: &str = "leader";
+
+/// Step id of the final acceptance task.
+pub const ACCEPTANCE_STEP_ID: &str = "acceptance";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkflowTemplate {
+  /// Defaults to the file stem.
+  #[serde(default)]
+  pub name: String,
+  #[serde(default)]
+  pub description: String,
+  #[serde(default)]
+  pub roles: Vec<WorkflowRoleTemplate>,
+  #[serde(default)]
+  pub tasks: Vec<WorkflowTaskTemplate>,
+  #[serde(default)]
+  pub acceptance: Option<WorkflowAcceptanceTemplate>,
+  /// Where the template was loaded from.
+  #[serde(skip)]
+  pub path: PathBuf,
+}
+
+/// A teammate to spawn when the workflow starts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkflowRoleTemplate {
+  /// Nickname of the teammate, referenced by tasks.
+  pub name: String,
+  /// Agent role to spawn it with; defaults to `default`.
+  #[serde(default)]
+  pub role: Option<String>,
+  #[serde(default)]
+  pub model: Option<String>,
+  #[serde(default)]
+  pub provider: Option<String>,
+  #[serde(default)]
+  pub reasoning_effort: Option<ReasoningEffort>,
+  /// Extra briefing appended to the teammate's first message.
+  #[serde(default)]
+  pub prompt: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkflowTaskTemplate {
+  pub id: String,
+  pub title: String,
+  #[serde(default)]
+  pub details: Option<String>,
+  /// A role name or `leader`; unassigned tasks can be claimed by anyone.
+  #[serde(default)]
+  pub assignee: Option<String>,
+  #[serde(default)]
+  pub depends_on: Vec<String>,
+  #[serde(default)]
+  pub scopes: Vec<ScopeRequest>,
+  /// A role name or `leader` that must approve the task before it completes.
+  #[serde(default)]
+  pub review_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkflowAcceptanceTemplate {
+  pub title: String,
+  #[serde(default)]
+  pub details: Option<String>,
+}
+
+impl WorkflowTemplate {
+  pub(crate) fn validate(&self) -> Result<(), String> {
+    if self.tasks.is_empty() {
+      return Err("workflow defines no tasks".to_string());
+    }
+
+    let mut members = HashSet::from([LEADER]);
+    for role in &self.roles {
+      if role.name.trim().is_empty() {
+        return Err("role with an empty name".to_string());
+      }
+      if !members.insert(role.name.as_str()) {
+        return Err(format!("duplicate role `{}`", role.name));
+      }
+    }
+
+    let mut task_ids = HashSet::new();
+    for task in &self.tasks {
+      if task.id.trim().is_empty() {
+        return Err(format!("task `{}` has an empty id", task.title));
+      }
+      if task.id == ACCEPTANCE_STEP_ID {
+        return Err(format!("task id `{ACCEPTANCE_STEP_ID}` is reserved"));
+      }
+      if !task_ids.insert(task.id.as_str()) {
+        return Err(format!("duplicate task id `{}`", task.id));
+      }
+    }
+
+    for task in &self.tasks {
+      for member in task.assignee.iter().chain(task.review_by.iter()) {
+        if !members.contains(member.as_str()) {
+          return Err(format!(
+            "task `{}` refers to unknown role `{member}`",
+            task.id
+          ));
+        }
+      }
+      if task.review_by.is_some() && task.review_by == task.assignee {
+        return Err(format!(
+          "task `{}` cannot be reviewed by its assignee",
+          task.id
+        ));
+      }
+      for dependency in &task.depends_on {
+        if !task_ids.contains(dependency.as_str()) {
+          return Err(format!(
+            "task `{}` depends on unknown task `{dependency}`",
+            task.id
+          ));
+        }
+      }
+    }
+
+    if let Some(task_id) = self.find_cycle() {
+      return Err(format!("task `{task_id}` is part of a dependency cycle"));
+    }
+    Ok(())
+  }
+
+  /// A task that (transitively) depends on itself, if any.
+  fn find_cycle(&self) -> Option<&str> {
+    let edges = self
+      .tasks
+      .iter()
+      .map(|task| (task.id.as_str(), &task.depends_on))
+      .collect::<HashMap<_, _>>();
+    let mut done = HashSet::new();
+    for task in &self.tasks {
+      let mut visiting = HashSet::new();
+      if visit(task.id.as_str(), &edges, &mut visiting, &mut done) {
+        return Some(task.id.as_str());
+      }
+    }
+    None
+  }
+
+  /// Tasks no other task depends on; the acceptance task waits for them.
+  pub(crate) fn sink_task_ids(&self) -> Vec<&str> {
+    let depended_on = self
+      .tasks
+      .iter()
+      .flat_map(|task| task.depends_on.iter().map(String::as_str))
+      .collect::<HashSet<_>>();
+    self
+      .tasks
+      .iter()
+      .map(|task| task.id.as_str())
+      .filter(|id| !depended_on.contains(id))
+      .collect()
+  }
+
+  /// Title of the run, which includes the goal when there is one.
+  pub(crate) fn run_title(&self, goal: Option<&str>) -> String {
+    match goal {
+      Some(goal) => format!("{}: {goal}", self.name),
+      None => self.name.clone(),
+    }
+  }
+
+  /// The first message a teammate spawned for `role` receives.
+  pub(crate) fn teammate_briefing(
+    &self,
+    role: &WorkflowRoleTemplate,
+    goal: Option<&str>,
+  ) -> String {
+    let mut briefing = format!(
+      "You are `{}` in the `{}` workflow run by the team leader.",
+      role.name, self.name
+    );
+    if let Some(goal) = goal {
+      briefing.push_str(&format!(" Goal: {goal}."));
+    }
+    briefing.push_str(
+      " Your tasks are on the team board and you will be woken when each one is ready; \
+       work only on tasks assigned to you and report through the task status.",
+    );
+    if let Some(prompt) = role.prompt.as_deref() {
+      briefing.push_str("\n\n");
+      briefing.push_str(&render(prompt, goal));
+    }
+    briefing
+  }
+
+  /// Task details with the review gate spelled out for the assignee.
+  pub(crate) fn task_details(&self, task: &WorkflowTaskTemplate, goal: Option<&str>) -> String {
+    let mut details = task
+      .details
+      .as_deref()
+      .map(|details| render(details, goal))
+      .unwrap_or_default();
+    if let Some(reviewer) = task.review_by.as_deref() {
+      if !details.is_empty() {
+        details.push_str("\n\n");
+      }
+      details.push_str(&format!(
+        "Review gate: when the work is done set the status to Review. `{reviewer}` approves \
+         with review_state Approved and status Completed, or sends it back with review_state \
+         ChangesRequested and status InProgress."
+      ));
+    }
+    details
+  }
+}
+
+fn visit<'a>(
+  id: &'a str,
+  edges: &HashMap<&'a str, &'a Vec<String>>,
+  visiting: &mut HashSet<&'a str>,
+  done: &mut HashSet<&'a str>,
+) -> bool {
+  if done.contains(id) {
+    return false;
+  }
+  if !visiting.insert(id) {
+    return true;
+  }
+  for dependency in edges.get(id).into_iter().flat_map(|deps| deps.iter()) {
+    if visit(dependency.as_str(), edges, visiting, done) {
+      return true;
+    }
+  }
+  visiting.remove(id);
+  done.insert(id);
+  false
+}
+
+/// Replaces `{{goal}}` in template text.
+pub(crate) fn render(text: &str, goal: Option<&str>) -> String {
+  text.replace("{{goal}}", goal.unwrap_or("the goal"))
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct WorkflowCatalog {
+  pub templates: Vec<WorkflowTemplate>,
+  pub warnings: Vec<String>,
+}
+
+pub async fn discover_workflow_templates(cwd: &Path) -> WorkflowCatalog {
+  let mut by_name: HashMap<String, WorkflowTemplate> = HashMap::new();
+  let mut warnings = Vec::new();
+
+  for root in ordered_cokra_roots(cwd) {
+    let dir = root.config_dir.join(WORKFLOWS_DIR);
+    for path in collect_files_with_extension(&dir, "toml").await {
+      match read_template_file(&path).await {
+        Ok(template) => {
+          by_name.insert(template.name.clone(), template);
+        }
+        Err(err) => warnings.push(format!("failed to load workflow {}: {err}", path.display())),
+      }
+    }
+  }
+
+  for warning in &warnings {
+    tracing::warn!("{warning}");
+  }
+  let mut templates = by_name.into_values().collect::<Vec<_>>();
+  templates.sort_by(|left, right| left.name.cmp(&right.name));
+  WorkflowCatalog {
+    templates,
+    warnings,
+  }
+}
+
+pub async fn load_workflow_template(cwd: &Path, name: &str) -> anyhow::Result<WorkflowTemplate> {
+  let catalog = discover_workflow_templates(cwd).await;
+  let names = catalog
+    .templates
+    .iter()
+    .map(|template| template.name.clone())
+    .collect::<Vec<_>>();
+  if let Some(template) = catalog
+    .templates
+    .into_iter()
+    .find(|template| template.name == name)
+  {
+    return Ok(template);
+  }
+
+  let mut message = if names.is_empty() {
+    format!("unknown workflow `{name}`: no templates found in .cokra/{WORKFLOWS_DIR}/")
+  } else {
+    format!(
+      "unknown workflow `{name}` (available: {})",
+      names.join(", ")
+    )
+  };
+  for warning in catalog.warnings {
+    message.push_str(&format!("\n{warning}"));
+  }
+  anyhow::bail!(message)
+}
+
+pub(crate) fn build_run_workflow_description(templates: &[WorkflowTemplate]) -> String {
+  let base = "Start a workflow template: spawns its teammates and creates its tasks, \
+              dependencies, review gates and acceptance step as one workflow run.";
+  if templates.is_empty() {
+    return base.to_string();
+  }
+
+  let lines = templates
+    .iter()
+    .map(|template| {
+      let mut line = format!("- {}", template.name);
+      if !template.description.is_empty() {
+        line.push_str(&format!(": {}", template.description));
+      }
+      line
+    })
+    .collect::<Vec<_>>()
+    .join("\n");
+  format!("{base}\n\nAvailable workflows (pass one as `name`):\n{lines}")
+}
+
+async fn read_template_file(path: &Path) -> Result<WorkflowTemplate, String> {
+  let raw = tokio::fs::read_to_string(path)
+    .await
+    .map_err(|err| format!("read error: {err}"))?;
+  let fallback_name = path
+    .file_stem()
+    .and_then(|stem| stem.to_str())
+    .unwrap_or("unnamed");
+  let mut template = parse_template(&raw, fallback_name)?;
+  template.path = path.to_path_buf();
+  Ok(template)
+}
+
+fn parse_template(raw: &str, fallback_name: &str) -> Result<WorkflowTemplate, String> {
+  let mut template = toml::from_str::<WorkflowTemplate>(raw).map_err(|err| err.to_string())?;
+  if template.name.trim().is_empty() {
+    template.name = fallback_name.to_string();
+  }
+  template.validate()?;
+  Ok(template)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cokra_protocol::OwnershipScopeKind;
+
+  const PIPELINE: &str = r#"
+description = "Plan, implement and review"
+
+[[roles]]
+name = "coder"
+role = "coding"
+
+[[roles]]
+name = "reviewer"
+
+[[tasks]]
+id = "plan"
+title = "Plan {{goal}}"
+assignee = "leader"
+
+[[tasks]]
+id = "implement"
+title = "Implement {{goal}}"
+assignee = "coder"
+depends_on = ["plan"]
+scopes = [{ kind = "Directory", path = "src" }]
+review_by = "reviewer"
+
+[acceptance]
+title = "Accept {{goal}}"
+"#;
+
+  #[test]
+  fn parses_roles_tasks_scopes_and_acceptance() {
+    let template = parse_template(PIPELINE, "pipeline").expect("parse");
+    assert_eq!(template.name, "pipeline");
+    assert_eq!(template.roles.len(), 2);
+    assert_eq!(template.tasks[1].depends_on, vec!["plan".to_string()]);
+    assert_eq!(
+      template.tasks[1].scopes[0].kind,
+      OwnershipScopeKind::Directory
+    );
+    assert_eq!(template.sink_task_ids(), vec!["implement"]);
+    assert_eq!(
+      render(&template.tasks[0].title, Some("login")),
+      "Plan login"
+    );
+    assert!(
+      template
+        .task_details(&template.tasks[1], None)
+        .starts_with("Review gate:")
+    );
+  }
+
+  #[test]
+  fn rejects_unknown_references_and_cycles() {
+    let unknown_role = PIPELINE.replace("review_by = \"reviewer\"", "review_by = \"qa\"");
+    assert_eq!(
+      parse_template(&unknown_role, "pipeline").unwrap_err(),
+      "task `implement` refers to unknown role `qa`"
+    );
+
+    let cycle = PIPELINE.replace(
+      "assignee = \"leader\"",
+      "assignee = \"leader\"\ndepends_on = [\"implement\"]",
+    );
+    assert_eq!(
+      parse_template(&cycle, "pipeline").unwrap_err(),
+      "task `plan` is part of a dependency cycle"
+    );
+  }
+
+  #[tokio::test]
+  async fn later_roots_override_templates_by_name() {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let outer = temp.path().join(".cokra").join(WORKFLOWS_DIR);
+    let inner_root = temp.path().join("app");
+    let inner = inner_root.join(".cokra").join(WORKFLOWS_DIR);
+    std::fs::create_dir_all(&outer).expect("outer");
+    std::fs::create_dir_all(&inner).expect("inner");
+    std::fs::write(outer.join("pipeline.toml"), PIPELINE).expect("write outer");
+    std::fs::write(
+      inner.join("pipeline.toml"),
+      PIPELINE.replace("Plan, implement and review", "Project pipeline"),
+    )
+    .expect("write inner");
+    std::fs::write(inner.join("broken.toml"), "tasks = 3").expect("write broken");
+
+    let catalog = discover_workflow_templates(&inner_root).await;
+    let pipeline = catalog
+      .templates
+      .iter()
+      .find(|template| template.name == "pipeline")
+      .expect("pipeline");
+    assert_eq!(pipeline.description, "Project pipeline");
+    assert_eq!(catalog.warnings.len(), 1);
+
+    let err = load_workflow_template(&inner_root, "ship")
+      .await
+      .unwrap_err()
+      .to_string();
+    assert!(err.starts_with("unknown workflow `ship`"), "{err}");
+  }
+}
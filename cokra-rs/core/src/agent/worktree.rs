@@ -0,0 +1,468 @@
+//! Per-teammate `git worktree` isolation.
+//!
+//! With `agents.isolation = "worktree"` every spawned teammate works in its own
+//! checkout on a `cokra/<nickname>` branch instead of the leader's cwd, so
+//! concurrent edits (and concurrent `cargo build`s) never share files. When a
+//! teammate's task completes its branch is rebased onto the leader's `HEAD` and
+//! fast-forwarded in; conflicts are left for the teammate to resolve.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::process::Command;
+
+/// Directory (inside the repository's common git dir) holding teammate checkouts.
+const WORKTREES_DIR: &str = "cokra-worktrees";
+const BRANCH_PREFIX: &str = "cokra/";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct TeammateWorktree {
+  /// Top level of the leader's checkout; merges land here.
+  pub(crate) repo_root: PathBuf,
+  /// Top level of the teammate's checkout.
+  pub(crate) path: PathBuf,
+  /// Leader cwd mapped into the teammate's checkout.
+  pub(crate) cwd: PathBuf,
+  pub(crate) branch: String,
+  /// Process that created the checkout; cleanup leaves it alone while that
+  /// process is still running.
+  #[serde(default)]
+  pub(crate) owner_pid: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum MergeBackOutcome {
+  /// The teammate branch has no commits the leader lacks.
+  UpToDate,
+  /// The teammate branch was rebased and fast-forwarded into the leader.
+  Merged { commits: usize },
+  /// Rebasing onto the leader's `HEAD` (`onto`) conflicted; the rebase was aborted.
+  Conflict { onto: String, files: Vec<String> },
+  /// The leader's checkout refused the fast-forward (usually local edits).
+  Rejected { reason: String },
+}
+
+/// Add a worktree for one teammate, branching from the leader's `HEAD`.
+///
+/// `label` is normally the nickname; `fallback_suffix` disambiguates when a
+/// branch of the same name survives from an earlier team.
+pub(crate) async fn create_teammate_worktree(
+  leader_cwd: &Path,
+  label: &str,
+  fallback_suffix: &str,
+) -> anyhow::Result<TeammateWorktree> {
+  let repo_root = PathBuf::from(
+    git(leader_cwd, &["rev-parse", "--show-toplevel"])
+      .await
+      .context("worktree isolation requires the session cwd to be inside a git repository")?,
+  );
+  let common_dir = PathBuf::from(git(&repo_root, &["rev-parse", "--git-common-dir"]).await?);
+  let worktrees_dir = repo_root.join(common_dir).join(WORKTREES_DIR);
+
+  let mut slug = branch_slug(label);
+  if branch_exists(&repo_root, &slug).await || worktrees_dir.join(&slug).exists() {
+    slug = format!("{slug}-{}", branch_slug(fallback_suffix));
+  }
+  let branch = format!("{BRANCH_PREFIX}{slug}");
+  let path = worktrees_dir.join(&slug);
+  let path_arg = path.display().to_string();
+  git(
+    &repo_root,
+    &["worktree", "add", "-b", &branch, &path_arg, "HEAD"],
+  )
+  .await
+  .with_context(|| format!("failed to add worktree for {branch}"))?;
+
+  // `--show-toplevel` resolves symlinks, so compare against the canonical cwd.
+  let leader_cwd = leader_cwd
+    .canonicalize()
+    .unwrap_or_else(|_| leader_cwd.to_path_buf());
+  let cwd = leader_cwd
+    .strip_prefix(&repo_root)
+    .map(|relative| path.join(relative))
+    .unwrap_or_else(|_| path.clone());
+
+  Ok(TeammateWorktree {
+    repo_root,
+    path,
+    cwd,
+    branch,
+    owner_pid: Some(std::process::id()),
+  })
+}
+
+/// Commit the teammate's pending changes, then bring its branch into the
+/// leader's checkout. Untracked files are committed unless `.gitignore` or one
+/// of the `excluded` pathspecs covers them.
+///
+/// Tradeoff: we rebase in the teammate's checkout and only fast-forward the
+/// leader, so a conflict never leaves the leader mid-merge; the teammate owns
+/// the resolution and the leader's working tree is untouched.
+pub(crate) async fn merge_back(
+  worktree: &TeammateWorktree,
+  commit_message: &str,
+  excluded: &[String],
+) -> anyhow::Result<MergeBackOutcome> {
+  commit_pending_changes(worktree, commit_message, excluded).await?;
+  let leader_head = git(&worktree.repo_root, &["rev-parse", "HEAD"]).await?;
+  let range = format!("{leader_head}..HEAD");
+  let commits = git(&worktree.path, &["rev-list", "--count", &range])
+    .await?
+    .parse::<usize>()
+    .unwrap_or_default();
+  if commits == 0 {
+    return Ok(MergeBackOutcome::UpToDate);
+  }
+
+  if git(&worktree.path, &["rebase", &leader_head])
+    .await
+    .is_err()
+  {
+    let files = git(&worktree.path, &["diff", "--name-only", "--diff-filter=U"])
+      .await
+      .map(|output| output.lines().map(ToString::to_string).collect())
+      .unwrap_or_default();
+    let _ = git(&worktree.path, &["rebase", "--abort"]).await;
+    return Ok(MergeBackOutcome::Conflict {
+      onto: leader_head,
+      files,
+    });
+  }
+
+  match git(
+    &worktree.repo_root,
+    &["merge", "--ff-only", "--quiet", &worktree.branch],
+  )
+  .await
+  {
+    Ok(_) => Ok(MergeBackOutcome::Merged { commits }),
+    Err(err) => Ok(MergeBackOutcome::Rejected {
+      reason: err.to_string(),
+    }),
+  }
+}
+
+/// Remove a teammate checkout. Pending changes are committed first (see
+/// [`merge_back`] for which) and the branch is only deleted when it is fully
+/// merged. A checkout that still has untracked files after that is kept and
+/// the error lists them, since removing it would delete them.
+pub(crate) async fn remove_teammate_worktree(
+  worktree: &TeammateWorktree,
+  excluded: &[String],
+) -> anyhow::Result<()> {
+  if worktree.path.exists() {
+    let _ = commit_pending_changes(
+      worktree,
+      "cokra: snapshot before worktree cleanup",
+      excluded,
+    )
+    .await;
+    let left = untracked_files(&worktree.path, &[]).await?;
+    if !left.is_empty() {
+      anyhow::bail!(
+        "kept worktree {} because these untracked files would be lost: {}",
+        worktree.path.display(),
+        left.join(", ")
+      );
+    }
+    let path_arg = worktree.path.display().to_string();
+    git(
+      &worktree.repo_root,
+      &["worktree", "remove", "--force", &path_arg],
+    )
+    .await?;
+  }
+  let _ = git(&worktree.repo_root, &["worktree", "prune"]).await;
+  let _ = git(&worktree.repo_root, &["branch", "-d", &worktree.branch]).await;
+  Ok(())
+}
+
+/// Recorded teammate checkouts that cleanup may remove: on a `cokra/` branch,
+/// inside the repository's teammate directory, and not owned by another
+/// process that is still running. Checkouts without a recorded owner are kept.
+pub(crate) fn removable_teammate_worktrees(
+  recorded: impl IntoIterator<Item = TeammateWorktree>,
+) -> Vec<TeammateWorktree> {
+  let own_pid = std::process::id();
+  recorded
+    .into_iter()
+    .filter(|worktree| worktree.branch.starts_with(BRANCH_PREFIX))
+    .filter(|worktree| {
+      worktree
+        .path
+        .parent()
+        .and_then(Path::file_name)
+        .is_some_and(|dir| dir == WORKTREES_DIR)
+    })
+    .filter(|worktree| match worktree.owner_pid {
+      Some(pid) => pid == own_pid || !process_alive(pid),
+      None => false,
+    })
+    .collect()
+}
+
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+  let Ok(pid) = libc::pid_t::try_from(pid) else {
+    return false;
+  };
+  // Signal 0 only probes; EPERM means the process exists under another user.
+  let probed = unsafe { libc::kill(pid, 0) };
+  probed == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+fn process_alive(_pid: u32) -> bool {
+  true
+}
+
+async fn commit_pending_changes(
+  worktree: &TeammateWorktree,
+  message: &str,
+  excluded: &[String],
+) -> anyhow::Result<()> {
+  git(&worktree.path, &["add", "-u"]).await?;
+  let files = untracked_files(&worktree.path, excluded).await?;
+  if !files.is_empty() {
+    let mut args = vec!["add", "--"];
+    args.extend(files.iter().map(String::as_str));
+    git(&worktree.path, &args).await?;
+  }
+  if git(&worktree.path, &["diff", "--cached", "--quiet"])
+    .await
+    .is_ok()
+  {
+    return Ok(());
+  }
+  git(&worktree.path, &["commit", "--quiet", "-m", message]).await?;
+  Ok(())
+}
+
+/// Untracked, non-ignored files under `checkout` outside the `excluded`
+/// pathspecs.
+async fn untracked_files(checkout: &Path, excluded: &[String]) -> anyhow::Result<Vec<String>> {
+  let excluded = excluded
+    .iter()
+    .map(|pathspec| format!(":(exclude){pathspec}"))
+    .collect::<Vec<_>>();
+  let mut args = vec![
+    "ls-files",
+    "--others",
+    "--exclude-standard",
+    "-z",
+    "--",
+    ".",
+  ];
+  args.extend(excluded.iter().map(String::as_str));
+  let files = git(checkout, &args).await?;
+  Ok(
+    files
+      .split('\0')
+      .filter(|file| !file.is_empty())
+      .map(ToString::to_string)
+      .collect(),
+  )
+}
+
+async fn branch_exists(repo_root: &Path, slug: &str) -> bool {
+  let reference = format!("refs/heads/{BRANCH_PREFIX}{slug}");
+  git(repo_root, &["rev-parse", "--verify", "--quiet", &reference])
+    .await
+    .is_ok()
+}
+
+fn branch_slug(label: &str) -> String {
+  let slug = label
+    .chars()
+    .map(|ch| {
+      if ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_') {
+        ch.to_ascii_lowercase()
+      } else {
+        '-'
+      }
+    })
+    .collect::<String>();
+  let slug = slug.trim_matches('-');
+  if slug.is_empty() {
+    "teammate".to_string()
+  } else {
+    slug.to_string()
+  }
+}
+
+async fn git(cwd: &Path, args: &[&str]) -> anyhow::Result<String> {
+  let output = Command::new("git")
+    .args(args)
+    .current_dir(cwd)
+    .output()
+    .await
+    .context("failed to run git")?;
+  if !output.status.success() {
+    anyhow::bail!(
+      "git {} failed: {}",
+      args.first().copied().unwrap_or_default(),
+      String::from_utf8_lossy(&output.stderr).trim()
+    );
+  }
+  Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  async fn init_repo(dir: &Path) {
+    for args in [
+      &["init", "--quiet", "--initial-branch=main"][..],
+      &["config", "user.name", "Test"],
+      &["config", "user.email", "test@example.com"],
+    ] {
+      git(dir, args).await.expect("git setup");
+    }
+    std::fs::write(dir.join("lib.rs"), "fn a() {}\n").expect("write");
+    git(dir, &["add", "-A"]).await.expect("add");
+    git(dir, &["commit", "--quiet", "-m", "init"])
+      .await
+      .expect("commit");
+  }
+
+  #[tokio::test]
+  async fn teammate_changes_merge_back_into_leader() {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let repo = temp.path().canonicalize().expect("canonical");
+    init_repo(&repo).await;
+
+    let worktree = create_teammate_worktree(&repo, "Ada Lovelace", "t1")
+      .await
+      .expect("worktree");
+    assert_eq!(worktree.branch, "cokra/ada-lovelace");
+    assert_eq!(worktree.cwd, worktree.path);
+
+    let excluded = vec!["*.log".to_string()];
+    std::fs::write(worktree.cwd.join("new.rs"), "fn b() {}\n").expect("write");
+    std::fs::create_dir(worktree.cwd.join("docs")).expect("mkdir");
+    std::fs::write(worktree.cwd.join("docs/notes.md"), "notes\n").expect("write");
+    std::fs::write(worktree.cwd.join("scratch.log"), "debug\n").expect("write");
+    let outcome = merge_back(&worktree, "task done", &excluded)
+      .await
+      .expect("merge");
+    assert_eq!(outcome, MergeBackOutcome::Merged { commits: 1 });
+    // New files are committed by default; excluded pathspecs stay behind.
+    assert!(repo.join("new.rs").exists());
+    assert!(repo.join("docs/notes.md").exists());
+    assert!(!repo.join("scratch.log").exists());
+    assert_eq!(
+      merge_back(&worktree, "again", &excluded)
+        .await
+        .expect("merge"),
+      MergeBackOutcome::UpToDate
+    );
+
+    // A second teammate with the same nickname gets a distinct branch.
+    let other = create_teammate_worktree(&repo, "ada-lovelace", "t2")
+      .await
+      .expect("worktree");
+    assert_eq!(other.branch, "cokra/ada-lovelace-t2");
+
+    // Removing the checkout would delete the excluded file, so it is kept.
+    let err = remove_teammate_worktree(&worktree, &excluded)
+      .await
+      .expect_err("untracked files block removal");
+    assert!(err.to_string().contains("scratch.log"), "{err}");
+    assert!(worktree.cwd.join("scratch.log").exists());
+
+    std::fs::remove_file(worktree.cwd.join("scratch.log")).expect("remove");
+    remove_teammate_worktree(&worktree, &excluded)
+      .await
+      .expect("remove");
+    assert!(!worktree.path.exists());
+    assert!(!branch_exists(&repo, "ada-lovelace").await);
+  }
+
+  #[tokio::test]
+  async fn cleanup_only_takes_teammate_checkouts_whose_owner_is_gone() {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let repo = temp.path().canonicalize().expect("canonical");
+    init_repo(&repo).await;
+    let own = create_teammate_worktree(&repo, "ada", "t1")
+      .await
+      .expect("worktree");
+
+    let mut exited = Command::new("true").spawn().expect("spawn");
+    let exited_pid = exited.id();
+    exited.wait().await.expect("wait");
+    let orphaned = TeammateWorktree {
+      owner_pid: exited_pid,
+      ..own.clone()
+    };
+    let live_owner = TeammateWorktree {
+      owner_pid: Some(1),
+      ..own.clone()
+    };
+    let unknown_owner = TeammateWorktree {
+      owner_pid: None,
+      ..own.clone()
+    };
+    let foreign_branch = TeammateWorktree {
+      branch: "feature/ada".to_string(),
+      ..own.clone()
+    };
+    let outside = TeammateWorktree {
+      path: repo.clone(),
+      ..own.clone()
+    };
+
+    assert_eq!(
+      removable_teammate_worktrees([
+        own.clone(),
+        orphaned.clone(),
+        live_owner,
+        unknown_owner,
+        foreign_branch,
+        outside,
+      ]),
+      vec![own, orphaned]
+    );
+  }
+
+  #[tokio::test]
+  async fn conflicting_changes_are_reported_and_kept_on_the_branch() {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let repo = temp.path().canonicalize().expect("canonical");
+    init_repo(&repo).await;
+    let worktree = create_teammate_worktree(&repo, "bob", "t1")
+      .await
+      .expect("worktree");
+
+    std::fs::write(worktree.cwd.join("lib.rs"), "fn teammate() {}\n").expect("write");
+    std::fs::write(repo.join("lib.rs"), "fn leader() {}\n").expect("write");
+    git(&repo, &["commit", "--quiet", "-am", "leader edit"])
+      .await
+      .expect("commit");
+
+    let outcome = merge_back(&worktree, "task done", &[])
+      .await
+      .expect("merge");
+    let leader_head = git(&repo, &["rev-parse", "HEAD"]).await.expect("head");
+    assert_eq!(
+      outcome,
+      MergeBackOutcome::Conflict {
+        onto: leader_head,
+        files: vec!["lib.rs".to_string()]
+      }
+    );
+    assert_eq!(
+      std::fs::read_to_string(repo.join("lib.rs")).expect("read"),
+      "fn leader() {}\n"
+    );
+
+    // Unmerged work survives cleanup on its branch.
+    remove_teammate_worktree(&worktree, &[])
+      .await
+      .expect("remove");
+    assert!(branch_exists(&repo, "bob").await);
+  }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::collections::VecDeque;
 use std::path::PathBuf;
 use std::pin::Pin;
@@ -19,10 +20,14 @@ use cokra_protocol::AskForApproval;
 use cokra_protocol::CompletionStatus;
 use cokra_protocol::ContextCompactedEvent;
 use cokra_protocol::ContextCompactionReason;
+use cokra_protocol::CustomPrompt;
 use cokra_protocol::Event;
 use cokra_protocol::EventMsg;
+use cokra_protocol::ListCustomPromptsResponseEvent;
 use cokra_protocol::Op;
 use cokra_protocol::ReadOnlyAccess;
+use cokra_protocol::ReasoningEffortConfig;
+use cokra_protocol::ReasoningSummaryConfig;
 use cokra_protocol::SandboxPolicy;
 use cokra_protocol::SessionConfiguredEvent;
 use cokra_protocol::Submission;
@@ -39,12 +44,17 @@ use crate::agent::team_runtime::clear_team_runtime;
 use crate::agent::team_runtime::register_team_runtime;
 use crate::agent::team_runtime::runtime_for_thread;
 use crate::compaction::compact_history_with_summary;
-use crate::compaction::estimate_messages_tokens;
+use crate::mcp::McpConnectionManager;
+use crate::mcp::McpServerSnapshot;
 use crate::model::ChatResponse;
 use crate::model::ModelClient;
+use crate::model::ReasoningEffort;
+use crate::model::ReasoningRequest;
+use crate::model::ReasoningSummary;
 use crate::model::ToolCall;
 use crate::model::Usage;
 use crate::model::init_model_layer;
+use crate::session::ElicitationResponse;
 use crate::session::Session;
 use crate::session::SteerInputError;
 use crate::thread_manager::ThreadManager;
@@ -110,6 +120,7 @@ pub struct Cokra {
   pub(crate) tool_runtime: Arc<UnifiedToolRuntime>,
   pub(crate) agent_control: Arc<AgentControl>,
   pub(crate) thread_manager: Arc<ThreadManager>,
+  pub(crate) mcp_manager: Arc<McpConnectionManager>,
 }
 
 /// Result of spawning a Cokra runtime.
@@ -153,6 +164,9 @@ impl Cokra {
     let root_thread_id = resolve_or_persist_root_thread_id(&config).await?;
     let thread_id = root_thread_id.clone();
     let session = Arc::new(Session::new_with_thread_id(root_thread_id.clone()));
+    session
+      .cost_ledger()
+      .set_limits(config.budget.soft_limit_usd, config.budget.hard_limit_usd);
     let thread_manager = Arc::new(ThreadManager::new(root_thread_id.clone()));
     let guards = Arc::new(crate::agent::Guards::default());
     let mut turn_config = build_turn_config(&config);
@@ -178,6 +192,19 @@ impl Cokra {
     let tool_registry = tooling.registry.clone();
     let tool_router = tooling.router.clone();
     let tool_runtime = tooling.runtime.clone();
+    let mcp_manager = tooling.mcp_manager.clone();
+    tooling
+      .mcp_manager
+      .attach_elicitation_target(session.clone(), tx_raw_event.clone());
+    tooling
+      .mcp_manager
+      .attach_event_target(tx_raw_event.clone());
+    tooling.mcp_manager.attach_sampling_target(
+      session.clone(),
+      tx_raw_event.clone(),
+      model_client.clone(),
+      config.cwd.clone(),
+    );
     let agent_control = Arc::new(AgentControl::new(
       Uuid::new_v4().to_string(),
       model_client.clone(),
@@ -223,6 +250,7 @@ impl Cokra {
       config.clone(),
       model_client.clone(),
       agent_control.clone(),
+      mcp_manager.clone(),
       rx_sub,
       tx_event.clone(),
       event_bus.clone(),
@@ -245,6 +273,7 @@ impl Cokra {
       tool_runtime,
       agent_control,
       thread_manager,
+      mcp_manager,
     };
 
     Ok(CokraSpawnOk { cokra, thread_id })
@@ -444,6 +473,24 @@ impl Cokra {
     self.thread_manager.list_thread_ids()
   }
 
+  /// Conversation of the root thread, for callers that persist it themselves.
+  pub async fn history(&self) -> Vec<crate::model::Message> {
+    self.session.clone_history().await
+  }
+
+  /// Replace the root conversation, e.g. when reopening a saved thread.
+  pub async fn restore_history(&self, messages: Vec<crate::model::Message>) {
+    self.session.replace_history(messages).await;
+  }
+
+  /// Route `read_file`/`write_file` of the root thread through the host editor.
+  pub async fn set_editor_file_system(
+    &self,
+    editor_fs: Option<Arc<dyn crate::session::EditorFileSystem>>,
+  ) {
+    self.session.set_editor_file_system(editor_fs).await;
+  }
+
   pub fn team_snapshot(&self) -> Option<cokra_protocol::TeamSnapshot> {
     let thread_id = self.thread_id()?.to_string();
     let runtime = runtime_for_thread(&thread_id)?;
@@ -518,6 +565,77 @@ impl Cokra {
     ))
   }
 
+  /// Teammates left running by a previous process that `resume_team` can restore.
+  pub fn resumable_teammates(&self) -> Vec<cokra_protocol::CollabAgentRef> {
+    let Some(thread_id) = self.thread_id().map(ToString::to_string) else {
+      return Vec::new();
+    };
+    runtime_for_thread(&thread_id)
+      .map(|runtime| runtime.resumable_teammates())
+      .unwrap_or_default()
+  }
+
+  /// Restore the teammates of a team that was running when the previous
+  /// process exited, emitting `CollabResumeBegin`/`CollabResumeEnd` for each.
+  pub async fn resume_team(&self) -> Vec<cokra_protocol::CollabAgentStatusEntry> {
+    let Some(thread_id) = self.thread_id().map(ToString::to_string) else {
+      return Vec::new();
+    };
+    let Some(runtime) = runtime_for_thread(&thread_id) else {
+      return Vec::new();
+    };
+    runtime.resume_team(&thread_id).await
+  }
+
+  /// Prompts offered by connected MCP servers, as slash commands. Servers
+  /// not connected yet start connecting.
+  pub fn mcp_prompts(&self) -> Vec<CustomPrompt> {
+    self.mcp_manager.prompt_descriptors()
+  }
+
+  /// Render the MCP prompt behind slash command `command` into the text of a
+  /// user message.
+  pub async fn render_mcp_prompt(
+    &self,
+    command: &str,
+    arguments: HashMap<String, String>,
+  ) -> anyhow::Result<String> {
+    self.mcp_manager.render_prompt(command, arguments).await
+  }
+
+  /// Configured MCP servers with their connection status. Servers not
+  /// connected yet start connecting.
+  pub fn mcp_servers(&self) -> Vec<McpServerSnapshot> {
+    self.mcp_manager.server_snapshots()
+  }
+
+  /// Drop and reconnect MCP server `name`; progress arrives as
+  /// `McpStartupUpdate` events.
+  pub fn restart_mcp_server(&self, name: &str) -> anyhow::Result<()> {
+    self.mcp_manager.restart_server(name)
+  }
+
+  /// Workflow templates found in `.cokra/workflows/` for this session's cwd.
+  pub async fn workflow_templates(&self) -> crate::WorkflowCatalog {
+    crate::agent::workflow::discover_workflow_templates(&self.config.cwd).await
+  }
+
+  /// Instantiate the workflow template `name` with this session as leader.
+  pub async fn run_workflow(
+    &self,
+    name: &str,
+    goal: Option<&str>,
+  ) -> anyhow::Result<cokra_protocol::WorkflowRun> {
+    let thread_id = self
+      .thread_id()
+      .map(ToString::to_string)
+      .ok_or_else(|| anyhow::anyhow!("no active thread to run a workflow from"))?;
+    let runtime = runtime_for_thread(&thread_id)
+      .ok_or_else(|| anyhow::anyhow!("agent teams are not available in this session"))?;
+    let template = crate::agent::workflow::load_workflow_template(&self.config.cwd, name).await?;
+    runtime.run_workflow(&thread_id, &template, goal).await
+  }
+
   pub async fn cleanup_team_runtime(&self) -> anyhow::Result<()> {
     let Some(thread_id) = self.thread_id().map(ToString::to_string) else {
       return Ok(());
@@ -597,6 +715,61 @@ async fn sync_turn_context_window_limit(model_client: &ModelClient, turn_config:
     .and_then(|limit| usize::try_from(limit).ok());
 }
 
+/// Turn the selected effort/summary into request settings for the current
+/// model. Returns a warning when the catalog doesn't list the model as a
+/// reasoning model, in which case nothing is sent.
+async fn sync_turn_reasoning(
+  model_client: &ModelClient,
+  turn_config: &mut TurnConfig,
+  effort: Option<ReasoningEffortConfig>,
+  summary: Option<ReasoningSummaryConfig>,
+) -> Option<String> {
+  turn_config.reasoning = None;
+  let effort = match effort?.effort {
+    cokra_protocol::ReasoningEffort::Minimal => ReasoningEffort::Minimal,
+    cokra_protocol::ReasoningEffort::Low => ReasoningEffort::Low,
+    cokra_protocol::ReasoningEffort::Medium => ReasoningEffort::Medium,
+    cokra_protocol::ReasoningEffort::High => ReasoningEffort::High,
+  };
+  let supports_reasoning = model_client
+    .resolve_model_catalog(&turn_config.model)
+    .await
+    .is_some_and(|entry| entry.reasoning);
+  if !supports_reasoning {
+    return Some(format!(
+      "{} does not support reasoning; ignoring \"{}\" effort",
+      turn_config.model,
+      effort.as_str()
+    ));
+  }
+  let summary = match summary {
+    None | Some(ReasoningSummaryConfig::Auto) => Some(ReasoningSummary::Auto),
+    Some(ReasoningSummaryConfig::Always) => Some(ReasoningSummary::Detailed),
+    Some(ReasoningSummaryConfig::Never) => None,
+  };
+  turn_config.reasoning = Some(ReasoningRequest { effort, summary });
+  None
+}
+
+async fn emit_reasoning_warning(
+  tx_event: &mpsc::Sender<Event>,
+  event_bus: &broadcast::Sender<EventMsg>,
+  session: &Session,
+  turn_id: &str,
+  message: String,
+) {
+  emit_event(
+    tx_event,
+    event_bus,
+    EventMsg::Warning(WarningEvent {
+      thread_id: session.thread_id().cloned().unwrap_or_default().to_string(),
+      turn_id: turn_id.to_string(),
+      message,
+    }),
+  )
+  .await;
+}
+
 async fn emit_session_configured_event(
   tx_event: &mpsc::Sender<Event>,
   event_bus: &broadcast::Sender<EventMsg>,
@@ -644,12 +817,14 @@ async fn run_manual_compaction(
   }
 
   let history = session.clone_history().await;
-  let tokens_before_est = estimate_messages_tokens(&history);
+  let counter = session.token_counter_for(&turn_config.model).await;
+  let tokens_before_est = counter.count_messages(&history);
   let compacted = compact_history_with_summary(
     model_client,
     &turn_config.model,
     &history,
     &turn_config.compaction,
+    &counter,
   )
   .await;
 
@@ -657,6 +832,7 @@ async fn run_manual_compaction(
     Ok(Some(result)) => {
       session.update_token_usage(&result.usage).await;
       session.replace_history(result.compacted_history).await;
+      let invalidated_cached_tokens = session.take_cached_input_tokens().await;
       emit_event(
         tx_event,
         event_bus,
@@ -668,6 +844,7 @@ async fn run_manual_compaction(
           tokens_after_est: result.tokens_after_est,
           reserve_tokens: turn_config.compaction.reserve_tokens,
           keep_recent_tokens: turn_config.compaction.keep_recent_tokens,
+          invalidated_cached_tokens: invalidated_cached_tokens as i64,
         }),
       )
       .await;
@@ -729,7 +906,10 @@ async fn maybe_compact_before_model_switch(
   }
 
   let history = session.clone_history().await;
-  let tokens_before_est = estimate_messages_tokens(&history);
+  // Measure with the incoming model's tokenizer: that is the window that
+  // has to fit.
+  let counter = session.token_counter_for(&turn_config.model).await;
+  let tokens_before_est = counter.count_messages(&history);
   if tokens_before_est < threshold {
     return;
   }
@@ -739,6 +919,7 @@ async fn maybe_compact_before_model_switch(
     previous_model,
     &history,
     &turn_config.compaction,
+    &counter,
   )
   .await;
 
@@ -747,6 +928,7 @@ async fn maybe_compact_before_model_switch(
     Ok(Some(result)) => {
       session.update_token_usage(&result.usage).await;
       session.replace_history(result.compacted_history).await;
+      let invalidated_cached_tokens = session.take_cached_input_tokens().await;
       emit_event(
         tx_event,
         event_bus,
@@ -758,6 +940,7 @@ async fn maybe_compact_before_model_switch(
           tokens_after_est: result.tokens_after_est,
           reserve_tokens: turn_config.compaction.reserve_tokens,
           keep_recent_tokens: turn_config.compaction.keep_recent_tokens,
+          invalidated_cached_tokens: invalidated_cached_tokens as i64,
         }),
       )
       .await;
@@ -832,7 +1015,7 @@ fn map_approval_policy(config: &Config) -> AskForApproval {
   }
 }
 
-fn map_sandbox_policy(config: &Config) -> SandboxPolicy {
+pub(crate) fn map_sandbox_policy(config: &Config) -> SandboxPolicy {
   match config.sandbox.mode {
     SandboxMode::Strict => SandboxPolicy::ReadOnly {
       access: ReadOnlyAccess::FullAccess,
@@ -928,11 +1111,13 @@ async fn emit_steer_input_rejected_warning(
   .await;
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn submission_loop(
   session: Arc<Session>,
   config: Arc<Config>,
   model_client: Arc<ModelClient>,
   agent_control: Arc<AgentControl>,
+  mcp_manager: Arc<McpConnectionManager>,
   mut rx_sub: mpsc::Receiver<Submission>,
   tx_event: mpsc::Sender<Event>,
   event_bus: Arc<broadcast::Sender<EventMsg>>,
@@ -940,6 +1125,8 @@ async fn submission_loop(
   let mut queue: VecDeque<Submission> = VecDeque::new();
   let mut turn_config = build_turn_config(&config);
   sync_turn_context_window_limit(model_client.as_ref(), &mut turn_config).await;
+  let mut reasoning_effort: Option<ReasoningEffortConfig> = None;
+  let mut reasoning_summary: Option<ReasoningSummaryConfig> = None;
 
   loop {
     let sub = if let Some(next) = queue.pop_front() {
@@ -964,6 +1151,16 @@ async fn submission_loop(
         turn_config.sandbox_policy = sandbox_policy;
         turn_config.cwd = cwd;
         sync_turn_context_window_limit(model_client.as_ref(), &mut turn_config).await;
+        if let Some(warning) = sync_turn_reasoning(
+          model_client.as_ref(),
+          &mut turn_config,
+          reasoning_effort,
+          reasoning_summary,
+        )
+        .await
+        {
+          emit_reasoning_warning(&tx_event, &event_bus, &session, &sub.id, warning).await;
+        }
         maybe_compact_before_model_switch(
           &session,
           model_client.as_ref(),
@@ -984,6 +1181,8 @@ async fn submission_loop(
         approval_policy,
         sandbox_policy,
         model,
+        effort,
+        summary,
         collaboration_mode: _,
         personality: _,
       } => {
@@ -992,6 +1191,12 @@ async fn submission_loop(
         if let Some(model) = model {
           turn_config.model = model;
         }
+        if let Some(effort) = effort {
+          reasoning_effort = effort;
+        }
+        if let Some(summary) = summary {
+          reasoning_summary = Some(summary);
+        }
         if let Some(approval_policy) = approval_policy {
           turn_config.approval_policy = approval_policy;
         }
@@ -1001,8 +1206,19 @@ async fn submission_loop(
         if let Some(cwd) = cwd {
           turn_config.cwd = cwd;
         }
+        mcp_manager.update_roots(&turn_config.cwd, &turn_config.sandbox_policy);
 
         sync_turn_context_window_limit(model_client.as_ref(), &mut turn_config).await;
+        if let Some(warning) = sync_turn_reasoning(
+          model_client.as_ref(),
+          &mut turn_config,
+          reasoning_effort,
+          reasoning_summary,
+        )
+        .await
+        {
+          emit_reasoning_warning(&tx_event, &event_bus, &session, &sub.id, warning).await;
+        }
         maybe_compact_before_model_switch(
           &session,
           model_client.as_ref(),
@@ -1021,6 +1237,7 @@ async fn submission_loop(
         emit_session_configured_event(&tx_event, &event_bus, &session, &turn_config).await;
       }
       Op::UserInput { items, .. } => {
+        mcp_manager.wait_for_startup().await;
         let user_item = cokra_protocol::UserMessageItem::new(&items);
         emit_event(
           &tx_event,
@@ -1061,12 +1278,13 @@ async fn submission_loop(
         cwd: _,
         approval_policy: _,
         sandbox_policy: _,
-        effort: _,
-        summary: _,
+        effort,
+        summary,
         final_output_json_schema: _,
         collaboration_mode: _,
         personality: _,
       } => {
+        mcp_manager.wait_for_startup().await;
         let user_item = cokra_protocol::UserMessageItem::new(&items);
         emit_event(
           &tx_event,
@@ -1094,6 +1312,22 @@ async fn submission_loop(
         sync_turn_context_window_limit(model_client.as_ref(), &mut turn_config).await;
         let model_changed =
           previous_model != turn_config.model || previous_limit != turn_config.context_window_limit;
+        // Like the model, a turn's effort/summary become the session's selection.
+        let reasoning_changed = effort != reasoning_effort || summary != reasoning_summary;
+        reasoning_effort = effort;
+        reasoning_summary = summary;
+        let reasoning_warning = sync_turn_reasoning(
+          model_client.as_ref(),
+          &mut turn_config,
+          reasoning_effort,
+          reasoning_summary,
+        )
+        .await;
+        if let Some(warning) = reasoning_warning
+          && (model_changed || reasoning_changed)
+        {
+          emit_reasoning_warning(&tx_event, &event_bus, &session, &sub.id, warning).await;
+        }
         maybe_compact_before_model_switch(
           &session,
           model_client.as_ref(),
@@ -1190,6 +1424,43 @@ async fn submission_loop(
           .await;
         }
       }
+      Op::ResolveElicitation {
+        server_name,
+        request_id,
+        decision,
+        content,
+      } => {
+        let response = ElicitationResponse {
+          action: decision,
+          content,
+        };
+        if !session
+          .notify_mcp_elicitation(&server_name, &request_id, response)
+          .await
+        {
+          emit_event(
+            &tx_event,
+            &event_bus,
+            EventMsg::Warning(cokra_protocol::WarningEvent {
+              thread_id: session.thread_id().cloned().unwrap_or_default().to_string(),
+              turn_id: sub.id.clone(),
+              message: format!("no pending elicitation found for {server_name}/{request_id}"),
+            }),
+          )
+          .await;
+        }
+      }
+      Op::ListCustomPrompts => {
+        mcp_manager.wait_for_startup().await;
+        emit_event(
+          &tx_event,
+          &event_bus,
+          EventMsg::ListCustomPromptsResponse(ListCustomPromptsResponseEvent {
+            custom_prompts: mcp_manager.prompt_descriptors(),
+          }),
+        )
+        .await;
+      }
       Op::Compact => {
         run_manual_compaction(
           &session,
@@ -1269,12 +1540,14 @@ async fn run_turn_with_interrupt(
         let _ = res;
         session.clear_pending_approvals_for_turn(turn_id).await;
         session.clear_pending_user_inputs_for_turn(turn_id).await;
+        session.clear_pending_elicitations_for_turn(turn_id).await;
         break;
       }
       maybe_sub = rx_sub.recv() => {
         let Some(next_sub) = maybe_sub else {
           session.clear_pending_approvals_for_turn(turn_id).await;
           session.clear_pending_user_inputs_for_turn(turn_id).await;
+          session.clear_pending_elicitations_for_turn(turn_id).await;
           break;
         };
         match next_sub.op {
@@ -1322,6 +1595,31 @@ async fn run_turn_with_interrupt(
               ).await;
             }
           }
+          Op::ResolveElicitation {
+            server_name,
+            request_id,
+            decision,
+            content,
+          } => {
+            let response = ElicitationResponse {
+              action: decision,
+              content,
+            };
+            if !session
+              .notify_mcp_elicitation(&server_name, &request_id, response)
+              .await
+            {
+              emit_event(
+                tx_event,
+                event_bus,
+                EventMsg::Warning(cokra_protocol::WarningEvent {
+                  thread_id: session.thread_id().cloned().unwrap_or_default().to_string(),
+                  turn_id: turn_id.to_string(),
+                  message: format!("no pending elicitation found for {server_name}/{request_id}"),
+                }),
+              ).await;
+            }
+          }
           Op::SteerInput {
             expected_turn_id,
             items,
@@ -1353,12 +1651,14 @@ async fn run_turn_with_interrupt(
             ).await;
             session.clear_pending_approvals_for_turn(turn_id).await;
             session.clear_pending_user_inputs_for_turn(turn_id).await;
+            session.clear_pending_elicitations_for_turn(turn_id).await;
             break;
           }
           Op::Shutdown => {
             emit_event(tx_event, event_bus, EventMsg::ShutdownComplete).await;
             session.clear_pending_approvals_for_turn(turn_id).await;
             session.clear_pending_user_inputs_for_turn(turn_id).await;
+            session.clear_pending_elicitations_for_turn(turn_id).await;
             break;
           }
           _ => queue.push_back(next_sub),
@@ -1470,6 +1770,7 @@ mod tests {
           input_tokens: 1,
           output_tokens: 1,
           total_tokens: 2,
+          ..Default::default()
         },
         extra: Default::default(),
       })
@@ -1586,6 +1887,7 @@ mod tests {
           input_tokens: 1,
           output_tokens: 1,
           total_tokens: 2,
+          ..Default::default()
         },
         extra: Default::default(),
       })
@@ -1720,13 +2022,14 @@ mod tests {
             input_tokens: 2,
             output_tokens: 4,
             total_tokens: 6,
+            ..Default::default()
           },
           extra: Default::default(),
         });
       }
 
       let saw_tool_output = request.messages.iter().any(|message| {
-        matches!(message, Message::Tool { tool_call_id, content }
+        matches!(message, Message::Tool { tool_call_id, content, .. }
           if tool_call_id == "call_read_1" && content.contains("hello from tool loop"))
       });
 
@@ -1754,6 +2057,7 @@ mod tests {
           input_tokens: 3,
           output_tokens: 2,
           total_tokens: 5,
+          ..Default::default()
         },
         extra: Default::default(),
       })
@@ -1785,7 +2089,7 @@ mod tests {
       }
 
       let saw_tool_output = request.messages.iter().any(|message| {
-        matches!(message, Message::Tool { tool_call_id, content }
+        matches!(message, Message::Tool { tool_call_id, content, .. }
           if tool_call_id == "call_read_1" && content.contains("hello from tool loop"))
       });
 
@@ -1916,13 +2220,14 @@ mod tests {
             input_tokens: 2,
             output_tokens: 4,
             total_tokens: 6,
+            ..Default::default()
           },
           extra: Default::default(),
         });
       }
 
       let saw_tool_output = request.messages.iter().any(|message| {
-        matches!(message, Message::Tool { tool_call_id, content }
+        matches!(message, Message::Tool { tool_call_id, content, .. }
           if tool_call_id == "call_write_1" && content.contains("wrote"))
       });
 
@@ -1950,6 +2255,7 @@ mod tests {
           input_tokens: 2,
           output_tokens: 2,
           total_tokens: 4,
+          ..Default::default()
         },
         extra: Default::default(),
       })
@@ -1985,7 +2291,7 @@ mod tests {
       }
 
       let saw_tool_output = request.messages.iter().any(|message| {
-        matches!(message, Message::Tool { tool_call_id, content }
+        matches!(message, Message::Tool { tool_call_id, content, .. }
           if tool_call_id == "call_write_1" && content.contains("wrote"))
       });
 
@@ -2124,6 +2430,7 @@ mod tests {
             input_tokens: 2,
             output_tokens: 4,
             total_tokens: 6,
+            ..Default::default()
           },
           extra: Default::default(),
         });
@@ -2133,6 +2440,7 @@ mod tests {
         Message::Tool {
           tool_call_id,
           content,
+          ..
         } if tool_call_id == "call_input_1" => serde_json::from_str::<serde_json::Value>(content)
           .ok()
           .is_some_and(|value| {
@@ -2170,6 +2478,7 @@ mod tests {
           input_tokens: 2,
           output_tokens: 2,
           total_tokens: 4,
+          ..Default::default()
         },
         extra: Default::default(),
       })
@@ -2218,6 +2527,7 @@ mod tests {
         Message::Tool {
           tool_call_id,
           content,
+          ..
         } if tool_call_id == "call_input_1" => serde_json::from_str::<serde_json::Value>(content)
           .ok()
           .is_some_and(|value| {
@@ -2644,6 +2954,135 @@ mod tests {
     assert_eq!(closed["lifecycle"], "Shutdown");
   }
 
+  #[tokio::test]
+  async fn test_spawned_agents_pin_their_own_model() {
+    let tmpdir = tempfile::tempdir().expect("tempdir");
+    let mut config = cokra_config::ConfigLoader::default()
+      .load_with_cli_overrides(vec![])
+      .expect("load config");
+    config.models.provider = "mock".to_string();
+    config.models.model = "mock/default".to_string();
+    config.approval.policy = ApprovalMode::Auto;
+    config.cwd = tmpdir.path().to_path_buf();
+    config.agents.roles.insert(
+      "tester".to_string(),
+      cokra_config::AgentRoleConfig {
+        description: None,
+        config_file: None,
+        model: Some("cheap".to_string()),
+        provider: None,
+        reasoning_effort: None,
+        temperature: Some(0.0),
+        budget: None,
+      },
+    );
+
+    let cokra = Cokra::new_with_model_client(config, build_mock_client().await)
+      .await
+      .expect("create cokra");
+
+    let spawn = |id: &str, arguments: serde_json::Value| crate::model::ToolCall {
+      id: id.to_string(),
+      call_type: "function".to_string(),
+      function: crate::model::ToolCallFunction {
+        name: "spawn_agent".to_string(),
+        arguments: arguments.to_string(),
+      },
+      provider_meta: None,
+    };
+    let explicit = cokra
+      .execute_tool(spawn(
+        "spawn-1",
+        serde_json::json!({
+          "task": "Plan the refactor",
+          "model": "planner",
+          "provider": "mock",
+          "reasoning_effort": "high"
+        }),
+      ))
+      .await
+      .expect("spawn with explicit model");
+    let explicit: serde_json::Value =
+      serde_json::from_str(&explicit.text_content()).expect("spawn json");
+    assert_eq!(explicit["model"], "mock/planner");
+
+    let from_role = cokra
+      .execute_tool(spawn(
+        "spawn-2",
+        serde_json::json!({ "task": "Write tests", "role": "tester" }),
+      ))
+      .await
+      .expect("spawn with role model");
+    let from_role: serde_json::Value =
+      serde_json::from_str(&from_role.text_content()).expect("spawn json");
+    assert_eq!(from_role["model"], "mock/cheap");
+
+    let snapshot = cokra.team_snapshot().expect("team snapshot");
+    let model_of = |thread_id: &str| {
+      snapshot
+        .members
+        .iter()
+        .find(|member| member.thread_id == thread_id)
+        .and_then(|member| member.model.clone())
+    };
+    assert_eq!(
+      model_of(explicit["agent_id"].as_str().expect("agent id")).as_deref(),
+      Some("mock/planner")
+    );
+    assert_eq!(
+      model_of(from_role["agent_id"].as_str().expect("agent id")).as_deref(),
+      Some("mock/cheap")
+    );
+    assert_eq!(
+      model_of(&snapshot.root_thread_id).as_deref(),
+      Some("mock/default")
+    );
+  }
+
+  #[tokio::test]
+  async fn test_explore_runs_a_read_only_subagent_outside_the_team() {
+    let tmpdir = tempfile::tempdir().expect("tempdir");
+    let fixture = tmpdir.path().join("auth.rs");
+    std::fs::write(&fixture, "hello from tool loop").expect("write fixture");
+    let mut config = cokra_config::ConfigLoader::default()
+      .load_with_cli_overrides(vec![])
+      .expect("load config");
+    config.models.provider = "mocktool".to_string();
+    config.models.model = "mocktool/default".to_string();
+    config.approval.policy = ApprovalMode::Auto;
+    config.cwd = tmpdir.path().to_path_buf();
+
+    let cokra = Cokra::new_with_model_client(
+      config,
+      build_tool_loop_client(fixture.display().to_string()).await,
+    )
+    .await
+    .expect("create cokra");
+
+    let report = cokra
+      .execute_tool(crate::model::ToolCall {
+        id: "explore-1".to_string(),
+        call_type: "function".to_string(),
+        function: crate::model::ToolCallFunction {
+          name: "explore".to_string(),
+          arguments: serde_json::json!({ "question": "How does auth work?" }).to_string(),
+        },
+        provider_meta: None,
+      })
+      .await
+      .expect("explore");
+    let report: serde_json::Value =
+      serde_json::from_str(&report.text_content()).expect("explore json");
+    assert_eq!(report["answer"], "tool loop complete");
+    assert_eq!(report["model"], "mocktool/default");
+    assert_eq!(report["tool_calls"], 1);
+
+    let snapshot = cokra.team_snapshot().expect("team snapshot");
+    assert_eq!(snapshot.members.len(), 1);
+    assert!(snapshot.tasks.is_empty());
+    assert!(cokra.session.clone_history().await.is_empty());
+  }
+
   #[tokio::test]
   async fn test_team_mailbox_and_task_board_round_trip() {
     let tmpdir = tempfile::tempdir().expect("tempdir");
@@ -2873,6 +3312,893 @@ mod tests {
     assert_eq!(snapshot.unread_counts.get(&current_root), Some(&0usize));
   }
 
+  #[tokio::test]
+  async fn test_team_docs_notify_subscribers_and_persist() {
+    let tmpdir = tempfile::tempdir().expect("tempdir");
+    let mut config = cokra_config::ConfigLoader::default()
+      .load_with_cli_overrides(vec![])
+      .expect("load config");
+    config.models.provider = "mock".to_string();
+    config.models.model = "mock/default".to_string();
+    config.approval.policy = ApprovalMode::Auto;
+    config.cwd = tmpdir.path().to_path_buf();
+
+    let cokra = Cokra::new_with_model_client(config.clone(), build_mock_client().await)
+      .await
+      .expect("create cokra");
+    let root_id = cokra.thread_id().expect("thread id").to_string();
+
+    let spawn = execute_tool_as_thread(
+      &cokra,
+      root_id.clone(),
+      "spawn_agent",
+      serde_json::json!({"task": "You are teammate alex. Wait for team instructions."}),
+    )
+    .await;
+    let spawned: serde_json::Value =
+      serde_json::from_str(&spawn.text_content()).expect("spawn json");
+    let agent_id = spawned["agent_id"].as_str().expect("agent id").to_string();
+    let _ = execute_tool_as_thread(
+      &cokra,
+      root_id.clone(),
+      "wait",
+      serde_json::json!({"agent_ids": [agent_id.clone()], "timeout_ms": 10000}),
+    )
+    .await;
+
+    let _ = execute_tool_as_thread(
+      &cokra,
+      root_id.clone(),
+      "write_team_doc",
+      serde_json::json!({
+        "name": "api.md",
+        "content": "GET /users\n",
+        "base_version": 0
+      }),
+    )
+    .await;
+    let _ = execute_tool_as_thread(
+      &cokra,
+      agent_id.clone(),
+      "subscribe_team_doc",
+      serde_json::json!({"name": "api.md"}),
+    )
+    .await;
+    let written = execute_tool_as_thread(
+      &cokra,
+      root_id.clone(),
+      "write_team_doc",
+      serde_json::json!({
+        "name": "api.md",
+        "content": "GET /users\nPOST /users\n",
+        "base_version": 1,
+        "summary": "add user creation"
+      }),
+    )
+    .await;
+    let written: serde_json::Value =
+      serde_json::from_str(&written.text_content()).expect("write json");
+    assert_eq!(written["version"], 2);
+
+    let stale = execute_tool_as_thread_result(
+      &cokra,
+      root_id.clone(),
+      "write_team_doc",
+      serde_json::json!({"name": "api.md", "content": "stale", "base_version": 1}),
+    )
+    .await
+    .expect_err("a write against an old version should be rejected");
+    assert!(stale.to_string().contains("is at v2"), "{stale}");
+
+    let read = execute_tool_as_thread(
+      &cokra,
+      agent_id.clone(),
+      "read_team_messages",
+      serde_json::json!({"unread_only": true}),
+    )
+    .await;
+    let messages: Vec<cokra_protocol::TeamMessage> =
+      serde_json::from_str(&read.text_content()).expect("messages json");
+    assert!(
+      messages.iter().any(|message| message
+        .message
+        .contains("'api.md' is now v2: add user creation")),
+      "{messages:?}"
+    );
+
+    let diff = execute_tool_as_thread(
+      &cokra,
+      agent_id.clone(),
+      "diff_team_doc",
+      serde_json::json!({"name": "api.md"}),
+    )
+    .await;
+    assert!(diff.text_content().contains("+POST /users"));
+
+    cokra.shutdown().await.expect("shutdown first runtime");
+
+    let restored = Cokra::new_with_model_client(config, build_mock_client().await)
+      .await
+      .expect("recreate cokra");
+    let restored_root = restored.thread_id().expect("thread id").to_string();
+    let doc = execute_tool_as_thread(
+      &restored,
+      restored_root,
+      "read_team_doc",
+      serde_json::json!({"name": "api.md", "version": 1}),
+    )
+    .await;
+    let doc: cokra_protocol::TeamDoc = serde_json::from_str(&doc.text_content()).expect("doc json");
+    assert_eq!((doc.version, doc.latest_version), (1, 2));
+    assert_eq!(doc.subscribers, vec![agent_id]);
+  }
+
+  #[tokio::test]
+  async fn test_team_resumes_teammates_after_restart() {
+    let tmpdir = tempfile::tempdir().expect("tempdir");
+    let mut config = cokra_config::ConfigLoader::default()
+      .load_with_cli_overrides(vec![])
+      .expect("load config");
+    config.models.provider = "mock".to_string();
+    config.models.model = "mock/default".to_string();
+    config.approval.policy = ApprovalMode::Auto;
+    config.cwd = tmpdir.path().to_path_buf();
+
+    let tool_call = |id: &str, name: &str, arguments: serde_json::Value| crate::model::ToolCall {
+      id: id.to_string(),
+      call_type: "function".to_string(),
+      function: crate::model::ToolCallFunction {
+        name: name.to_string(),
+        arguments: arguments.to_string(),
+      },
+      provider_meta: None,
+    };
+    let last_user_message = |request: &crate::model::ChatRequest| match request.messages.last() {
+      Some(Message::User(text)) => text.clone(),
+      _ => String::new(),
+    };
+
+    let parser = tmpdir.path().join("parser.rs");
+
+    // The first turn completes; the second is still running at shutdown.
+    let gate = Arc::new(tokio::sync::Semaphore::new(1));
+    let provider = crate::test_support::ScriptedProvider::echoing().gated(Arc::clone(&gate));
+    let requests = provider.requests();
+    let cokra = Cokra::new_with_model_client(config.clone(), provider.into_model_client().await)
+      .await
+      .expect("create cokra");
+    let spawned = cokra
+      .execute_tool(tool_call(
+        "resume-spawn-1",
+        "spawn_agent",
+        serde_json::json!({ "task": "Refactor the parser", "nickname": "robin" }),
+      ))
+      .await
+      .expect("spawn");
+    let spawned: serde_json::Value =
+      serde_json::from_str(&spawned.text_content()).expect("spawn json");
+    let agent_id = spawned["agent_id"].as_str().expect("agent id").to_string();
+    let _ = cokra
+      .execute_tool(tool_call(
+        "resume-wait-1",
+        "wait",
+        serde_json::json!({ "agent_ids": [agent_id.clone()], "timeout_ms": 10000 }),
+      ))
+      .await
+      .expect("wait");
+
+    let created = cokra
+      .execute_tool(tool_call(
+        "resume-task-1",
+        "create_team_task",
+        serde_json::json!({
+          "title": "Rewrite the tokenizer",
+          "requested_scopes": [file_scope(&parser)]
+        }),
+      ))
+      .await
+      .expect("create task");
+    let task: TeamTask = serde_json::from_str(&created.text_content()).expect("task json");
+    let _ = execute_tool_as_thread(
+      &cokra,
+      agent_id.clone(),
+      "claim_team_task",
+      serde_json::json!({ "task_id": task.id.clone() }),
+    )
+    .await;
+    let _ = execute_tool_as_thread(
+      &cokra,
+      agent_id.clone(),
+      "write_file",
+      serde_json::json!({ "file_path": parser.display().to_string(), "content": "fn parse() {}" }),
+    )
+    .await;
+    let leases = cokra
+      .team_snapshot()
+      .expect("team snapshot")
+      .ownership_leases;
+    assert_eq!(leases.len(), 1);
+
+    cokra
+      .execute_tool(tool_call(
+        "resume-input-1",
+        "send_input",
+        serde_json::json!({ "agent_id": agent_id.clone(), "message": "Port the lexer." }),
+      ))
+      .await
+      .expect("send input");
+    timeout(Duration::from_secs(10), async {
+      while requests
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .len()
+        < 2
+      {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+      }
+    })
+    .await
+    .expect("second turn should reach the model");
+    cokra
+      .execute_tool(tool_call(
+        "resume-input-2",
+        "send_input",
+        serde_json::json!({ "agent_id": agent_id.clone(), "message": "Then add tests." }),
+      ))
+      .await
+      .expect("queue input");
+    assert!(cokra.resumable_teammates().is_empty());
+    cokra.shutdown().await.expect("shutdown first runtime");
+
+    let provider = crate::test_support::ScriptedProvider::echoing();
+    let requests = provider.requests();
+    let restored = Cokra::new_with_model_client(config, provider.into_model_client().await)
+      .await
+      .expect("recreate cokra");
+    let resumable = restored.resumable_teammates();
+    assert_eq!(resumable.len(), 1);
+    assert_eq!(resumable[0].thread_id, agent_id);
+    assert_eq!(resumable[0].nickname.as_deref(), Some("robin"));
+
+    let mut events = restored.subscribe_events();
+    let resumed = restored.resume_team().await;
+    assert_eq!(
+      resumed
+        .iter()
+        .map(|entry| entry.thread_id.as_str())
+        .collect::<Vec<_>>(),
+      vec![agent_id.as_str()]
+    );
+    assert!(restored.resumable_teammates().is_empty());
+    let snapshot = restored.team_snapshot().expect("team snapshot");
+    assert!(
+      snapshot
+        .members
+        .iter()
+        .any(|member| member.thread_id == agent_id)
+    );
+
+    // Leases are reattached to the teammate that held them.
+    assert_eq!(snapshot.ownership_leases.len(), 1);
+    let lease = &snapshot.ownership_leases[0];
+    assert_eq!(lease.id, leases[0].id);
+    assert_eq!(lease.owner_thread_id, agent_id);
+    assert_eq!(lease.scope, leases[0].scope);
+    assert!(lease.scope.path.ends_with("parser.rs"));
+
+    let mut begun = false;
+    let end = timeout(Duration::from_secs(5), async {
+      loop {
+        match events.recv().await.expect("event") {
+          EventMsg::CollabResumeBegin(begin) if begin.receiver_thread_id == agent_id => {
+            begun = true;
+          }
+          EventMsg::CollabResumeEnd(end) if end.receiver_thread_id == agent_id => return end,
+          _ => {}
+        }
+      }
+    })
+    .await
+    .expect("resume events");
+    assert!(begun);
+    assert_eq!(end.error, None);
+    assert_eq!(end.receiver_nickname.as_deref(), Some("robin"));
+
+    // The interrupted turn is replayed on top of the restored history, then
+    // the queued wake runs.
+    let sent = timeout(Duration::from_secs(10), async {
+      loop {
+        let sent = requests
+          .lock()
+          .unwrap_or_else(std::sync::PoisonError::into_inner)
+          .clone();
+        if sent
+          .iter()
+          .any(|request| last_user_message(request).contains("Then add tests."))
+        {
+          return sent;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+      }
+    })
+    .await
+    .expect("queued wake should run after resume");
+    let replayed = &sent[0];
+    assert!(last_user_message(replayed).contains("The session restarted"));
+    assert!(last_user_message(replayed).contains("Port the lexer."));
+    assert!(replayed.messages.iter().any(|message| matches!(
+      message,
+      Message::User(text) if text.contains("Refactor the parser")
+    )));
+    assert!(replayed.messages.iter().any(|message| matches!(
+      message,
+      Message::Assistant { content: Some(text), .. } if text.contains("Refactor the parser")
+    )));
+
+    // The resumed teammate accepts new work under its original thread id.
+    restored
+      .execute_tool(tool_call(
+        "resume-input-3",
+        "send_input",
+        serde_json::json!({ "agent_id": agent_id.clone(), "message": "Continue." }),
+      ))
+      .await
+      .expect("send input to resumed teammate");
+  }
+
+  #[tokio::test]
+  async fn test_teammate_is_blocked_once_its_budget_is_spent() {
+    let tmpdir = tempfile::tempdir().expect("tempdir");
+    let mut config = cokra_config::ConfigLoader::default()
+      .load_with_cli_overrides(vec![])
+      .expect("load config");
+    config.models.provider = "mock".to_string();
+    config.models.model = "mock/default".to_string();
+    config.approval.policy = ApprovalMode::Auto;
+    config.cwd = tmpdir.path().to_path_buf();
+    config.agents.budget.max_turns = Some(1);
+
+    let tool_call = |id: &str, name: &str, arguments: serde_json::Value| crate::model::ToolCall {
+      id: id.to_string(),
+      call_type: "function".to_string(),
+      function: crate::model::ToolCallFunction {
+        name: name.to_string(),
+        arguments: arguments.to_string(),
+      },
+      provider_meta: None,
+    };
+
+    let cokra = Cokra::new_with_model_client(config, build_mock_client().await)
+      .await
+      .expect("create cokra");
+    let spawned = cokra
+      .execute_tool(tool_call(
+        "budget-spawn-1",
+        "spawn_agent",
+        serde_json::json!({ "task": "Summarize the repo", "nickname": "wren" }),
+      ))
+      .await
+      .expect("spawn");
+    let spawned: serde_json::Value =
+      serde_json::from_str(&spawned.text_content()).expect("spawn json");
+    let agent_id = spawned["agent_id"].as_str().expect("agent id").to_string();
+
+    let member = async {
+      loop {
+        let snapshot = cokra.team_snapshot().expect("team snapshot");
+        if let Some(member) = snapshot.members.iter().find(|member| {
+          member.thread_id == agent_id
+            && member.state.lifecycle == cokra_protocol::CollabAgentLifecycle::Blocked
+        }) {
+          return (snapshot.clone(), member.clone());
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+      }
+    };
+    let (snapshot, member) = timeout(Duration::from_secs(10), member)
+      .await
+      .expect("teammate should be blocked after one turn");
+    assert_eq!(member.usage.turns, 1);
+    assert_eq!(
+      member
+        .usage
+        .budget
+        .as_ref()
+        .and_then(|budget| budget.max_turns),
+      Some(1)
+    );
+    assert!(
+      member
+        .state
+        .attention_reason
+        .as_deref()
+        .is_some_and(|reason| reason.contains("turn budget of 1 reached"))
+    );
+    assert_eq!(snapshot.team_usage.turns, 1);
+    assert!(snapshot.recent_messages.iter().any(|message| {
+      message.sender_thread_id == agent_id
+        && message.recipient_thread_id.as_deref() == Some(snapshot.root_thread_id.as_str())
+        && message.message.contains("wren stopped")
+    }));
+
+    assert!(
+      cokra
+        .execute_tool(tool_call(
+          "budget-input-1",
+          "send_input",
+          serde_json::json!({ "agent_id": agent_id, "message": "Keep going." }),
+        ))
+        .await
+        .is_err()
+    );
+  }
+
+  #[tokio::test]
+  async fn test_budget_interrupts_the_turn_and_survives_a_restart() {
+    let tmpdir = tempfile::tempdir().expect("tempdir");
+    let notes = tmpdir.path().join("notes.txt");
+    std::fs::write(&notes, "notes").expect("write notes");
+    let mut config = cokra_config::ConfigLoader::default()
+      .load_with_cli_overrides(vec![])
+      .expect("load config");
+    config.models.provider = "mock".to_string();
+    config.models.model = "mock/default".to_string();
+    config.approval.policy = ApprovalMode::Auto;
+    config.cwd = tmpdir.path().to_path_buf();
+    config.agents.budget.max_tokens = Some(10);
+
+    // Every reply is another tool call, so only the budget ends the turn.
+    let endless_reader = || {
+      crate::test_support::ScriptedProvider::calling(
+        "read_file",
+        serde_json::json!({ "file_path": notes.display().to_string() }),
+      )
+      .with_usage(Usage {
+        input_tokens: 4,
+        output_tokens: 2,
+        total_tokens: 6,
+        ..Default::default()
+      })
+    };
+    let provider = endless_reader();
+    let requests = provider.requests();
+    let cokra = Cokra::new_with_model_client(config.clone(), provider.into_model_client().await)
+      .await
+      .expect("create cokra");
+    let spawned = cokra
+      .execute_tool(ToolCall {
+        id: "budget-spawn-1".to_string(),
+        call_type: "function".to_string(),
+        function: ToolCallFunction {
+          name: "spawn_agent".to_string(),
+          arguments: serde_json::json!({ "task": "Read the notes", "nickname": "wren" })
+            .to_string(),
+        },
+        provider_meta: None,
+      })
+      .await
+      .expect("spawn");
+    let spawned: serde_json::Value =
+      serde_json::from_str(&spawned.text_content()).expect("spawn json");
+    let agent_id = spawned["agent_id"].as_str().expect("agent id").to_string();
+
+    let blocked_member = |cokra: &Cokra| {
+      cokra
+        .team_snapshot()
+        .expect("team snapshot")
+        .members
+        .into_iter()
+        .find(|member| {
+          member.thread_id == agent_id
+            && member.state.lifecycle == cokra_protocol::CollabAgentLifecycle::Blocked
+        })
+    };
+    let member = timeout(Duration::from_secs(10), async {
+      loop {
+        if let Some(member) = blocked_member(&cokra) {
+          return member;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+      }
+    })
+    .await
+    .expect("teammate should be blocked in the middle of its turn");
+    assert_eq!(
+      member.state.turn_outcome,
+      cokra_protocol::CollabTurnOutcome::Interrupted
+    );
+    assert!(
+      member
+        .state
+        .attention_reason
+        .as_deref()
+        .is_some_and(|reason| reason.contains("token budget of 10 reached"))
+    );
+    assert!(member.usage.total_tokens >= 12);
+
+    // The interrupted turn makes no further requests.
+    let sent = requests
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .len();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(
+      requests
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .len(),
+      sent
+    );
+    cokra.shutdown().await.expect("shutdown first runtime");
+
+    let provider = endless_reader();
+    let requests = provider.requests();
+    let restored = Cokra::new_with_model_client(config, provider.into_model_client().await)
+      .await
+      .expect("recreate cokra");
+    restored.resume_team().await;
+    let member = blocked_member(&restored).expect("resumed teammate stays blocked");
+    assert!(member.usage.total_tokens >= 12);
+    assert!(
+      member
+        .state
+        .attention_reason
+        .as_deref()
+        .is_some_and(|reason| reason.contains("token budget of 10 reached"))
+    );
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(
+      requests
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .is_empty()
+    );
+  }
+
+  #[tokio::test]
+  async fn test_stalled_teammate_is_nudged_restarted_then_escalated() {
+    let tmpdir = tempfile::tempdir().expect("tempdir");
+    let mut config = cokra_config::ConfigLoader::default()
+      .load_with_cli_overrides(vec![])
+      .expect("load config");
+    config.models.provider = "mock".to_string();
+    config.models.model = "mock/default".to_string();
+    config.approval.policy = ApprovalMode::Auto;
+    config.cwd = tmpdir.path().to_path_buf();
+    config.agents.liveness = cokra_config::AgentLivenessConfig {
+      nudge_after_secs: 10,
+      restart_after_secs: 20,
+      escalate_after_secs: 30,
+      check_interval_secs: 1,
+    };
+
+    // No permits: every model request hangs, so the teammate goes quiet.
+    let provider = crate::test_support::ScriptedProvider::replying("done")
+      .gated(Arc::new(tokio::sync::Semaphore::new(0)));
+    let requests = provider.requests();
+    let cokra = Cokra::new_with_model_client(config, provider.into_model_client().await)
+      .await
+      .expect("create cokra");
+    // Paused only now: opening the state db waits on real time.
+    tokio::time::pause();
+    let spawned = cokra
+      .execute_tool(ToolCall {
+        id: "stall-spawn-1".to_string(),
+        call_type: "function".to_string(),
+        function: ToolCallFunction {
+          name: "spawn_agent".to_string(),
+          arguments: serde_json::json!({ "task": "Run the migration", "nickname": "finch" })
+            .to_string(),
+        },
+        provider_meta: None,
+      })
+      .await
+      .expect("spawn");
+    let spawned: serde_json::Value =
+      serde_json::from_str(&spawned.text_content()).expect("spawn json");
+    let agent_id = spawned["agent_id"].as_str().expect("agent id").to_string();
+
+    // Advance the paused clock one check at a time until the leader has been
+    // asked to step in. The paused clock also jumps while the state db does
+    // real I/O, so the deadline is on the wall clock.
+    let escalated_at = |snapshot: &cokra_protocol::TeamSnapshot| {
+      snapshot
+        .recent_messages
+        .iter()
+        .find(|message| {
+          message.sender_thread_id == agent_id
+            && message.recipient_thread_id.as_deref() == Some(snapshot.root_thread_id.as_str())
+            && message.message.contains("finch has made no progress")
+        })
+        .map(|message| message.created_at)
+    };
+    let restarted = || {
+      requests
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .iter()
+        .any(|request| {
+          request.messages.iter().any(|message| {
+            matches!(message, Message::User(text) if text.contains("stopped making progress"))
+          })
+        })
+    };
+    let deadline = std::time::Instant::now() + Duration::from_secs(30);
+    let snapshot = loop {
+      assert!(
+        std::time::Instant::now() < deadline,
+        "stalled teammate should be restarted and escalated"
+      );
+      tokio::time::sleep(Duration::from_secs(1)).await;
+      let snapshot = cokra.team_snapshot().expect("team snapshot");
+      // The restarted turn asks the model again once it gets going.
+      if escalated_at(&snapshot).is_some() && restarted() {
+        break snapshot;
+      }
+    };
+    let member = snapshot
+      .members
+      .iter()
+      .find(|member| member.thread_id == agent_id)
+      .expect("teammate in snapshot");
+    assert_eq!(
+      member.state.lifecycle,
+      cokra_protocol::CollabAgentLifecycle::Busy
+    );
+    assert!(
+      member
+        .state
+        .attention_reason
+        .as_deref()
+        .is_some_and(|reason| reason.ends_with("escalated to the leader"))
+    );
+
+    // The nudge reached the teammate before the leader heard about it.
+    let nudge = snapshot
+      .recent_messages
+      .iter()
+      .find(|message| {
+        message.recipient_thread_id.as_deref() == Some(agent_id.as_str())
+          && message.message.starts_with("No progress from you")
+      })
+      .expect("nudge in the mailbox");
+    assert!(escalated_at(&snapshot).is_some_and(|escalated_at| nudge.created_at <= escalated_at));
+  }
+
+  #[tokio::test]
+  async fn test_team_budget_counts_closed_teammates() {
+    let tmpdir = tempfile::tempdir().expect("tempdir");
+    let mut config = cokra_config::ConfigLoader::default()
+      .load_with_cli_overrides(vec![])
+      .expect("load config");
+    config.models.provider = "mock".to_string();
+    config.models.model = "mock/default".to_string();
+    config.approval.policy = ApprovalMode::Auto;
+    config.cwd = tmpdir.path().to_path_buf();
+    config.agents.team_budget.max_turns = Some(1);
+
+    let tool_call = |id: &str, name: &str, arguments: serde_json::Value| crate::model::ToolCall {
+      id: id.to_string(),
+      call_type: "function".to_string(),
+      function: crate::model::ToolCallFunction {
+        name: name.to_string(),
+        arguments: arguments.to_string(),
+      },
+      provider_meta: None,
+    };
+    let cokra = Cokra::new_with_model_client(config, build_mock_client().await)
+      .await
+      .expect("create cokra");
+    let spawn = |id: &'static str, nickname: &'static str| {
+      let cokra = &cokra;
+      async move {
+        let spawned = cokra
+          .execute_tool(tool_call(
+            id,
+            "spawn_agent",
+            serde_json::json!({ "task": "Summarize the repo", "nickname": nickname }),
+          ))
+          .await
+          .expect("spawn");
+        let spawned: serde_json::Value =
+          serde_json::from_str(&spawned.text_content()).expect("spawn json");
+        spawned["agent_id"].as_str().expect("agent id").to_string()
+      }
+    };
+    let blocked = |agent_id: String| {
+      let cokra = &cokra;
+      async move {
+        loop {
+          let snapshot = cokra.team_snapshot().expect("team snapshot");
+          if let Some(member) = snapshot.members.iter().find(|member| {
+            member.thread_id == agent_id
+              && member.state.lifecycle == cokra_protocol::CollabAgentLifecycle::Blocked
+          }) {
+            return member.clone();
+          }
+          tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+      }
+    };
+
+    let wren = spawn("team-budget-spawn-1", "wren").await;
+    let member = timeout(Duration::from_secs(10), blocked(wren.clone()))
+      .await
+      .expect("wren should spend the team budget");
+    assert_eq!(member.usage.turns, 1);
+    cokra
+      .execute_tool(tool_call(
+        "team-budget-close-1",
+        "close_agent",
+        serde_json::json!({ "agent_id": wren }),
+      ))
+      .await
+      .expect("close wren");
+
+    let finch = spawn("team-budget-spawn-2", "finch").await;
+    let member = timeout(Duration::from_secs(10), blocked(finch))
+      .await
+      .expect("the replacement should still be over the team budget");
+    assert_eq!(member.usage.turns, 0);
+    assert!(
+      member
+        .state
+        .attention_reason
+        .as_deref()
+        .is_some_and(|reason| reason.contains("team turn budget of 1 reached"))
+    );
+    let snapshot = cokra.team_snapshot().expect("team snapshot");
+    assert_eq!(snapshot.team_usage.turns, 1);
+  }
+
+  #[tokio::test]
+  async fn test_workflow_template_creates_gated_task_graph() {
+    let tmpdir = tempfile::tempdir().expect("tempdir");
+    let workflows_dir = tmpdir.path().join(".cokra").join("workflows");
+    std::fs::create_dir_all(&workflows_dir).expect("workflows dir");
+    std::fs::write(
+      workflows_dir.join("ship.toml"),
+      r#"
+description = "Implement, test and accept"
+
+[[roles]]
+name = "coder"
+
+[[roles]]
+name = "checker"
+
+[[tasks]]
+id = "implement"
+title = "Implement {{goal}}"
+assignee = "coder"
+review_by = "checker"
+
+[[tasks]]
+id = "test"
+title = "Test {{goal}}"
+assignee = "checker"
+depends_on = ["implement"]
+
+[acceptance]
+title = "Accept {{goal}}"
+"#,
+    )
+    .expect("write workflow");
+    let mut config = cokra_config::ConfigLoader::default()
+      .load_with_cli_overrides(vec![])
+      .expect("load config");
+    config.models.provider = "mock".to_string();
+    config.models.model = "mock/default".to_string();
+    config.approval.policy = ApprovalMode::Auto;
+    config.cwd = tmpdir.path().to_path_buf();
+
+    let cokra = Cokra::new_with_model_client(config, build_mock_client().await)
+      .await
+      .expect("create cokra");
+    assert_eq!(cokra.workflow_templates().await.templates.len(), 1);
+    let run = cokra
+      .run_workflow("ship", Some("login"))
+      .await
+      .expect("run workflow");
+    assert_eq!(run.workflow_name, "ship");
+    assert_eq!(run.title, "ship: login");
+    assert_eq!(run.steps.len(), 3);
+
+    let snapshot = cokra.team_snapshot().expect("team snapshot");
+    assert_eq!(snapshot.members.len(), 3);
+    let task = |title: &str| {
+      snapshot
+        .tasks
+        .iter()
+        .find(|task| task.title == title)
+        .cloned()
+        .expect("workflow task")
+    };
+    let implement = task("Implement login");
+    let test = task("Test login");
+    let accept = task("Accept login");
+    assert!(implement.reviewer_thread_id.is_some());
+    assert_eq!(test.blocked_by_task_ids, vec![implement.id.clone()]);
+    assert_eq!(accept.blocked_by_task_ids, vec![test.id.clone()]);
+    assert_eq!(
+      accept.assignee_thread_id.as_deref(),
+      Some(snapshot.root_thread_id.as_str())
+    );
+
+    let runtime = runtime_for_thread(&snapshot.root_thread_id).expect("team runtime");
+    let assignee = implement.assignee_thread_id.clone().expect("assignee");
+    let reviewer = implement.reviewer_thread_id.clone().expect("reviewer");
+    let complete = |actor: String, review_state| {
+      let runtime = runtime.clone();
+      let task_id = implement.id.clone();
+      async move {
+        runtime
+          .update_task(
+            &actor,
+            &task_id,
+            Some(cokra_protocol::TeamTaskStatus::Completed),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            review_state,
+            None,
+          )
+          .await
+      }
+    };
+    let err = complete(assignee.clone(), None)
+      .await
+      .expect_err("review gate");
+    assert!(err.to_string().contains("review gate"));
+    let err = complete(
+      assignee,
+      Some(cokra_protocol::TeamTaskReviewState::Approved),
+    )
+    .await
+    .expect_err("assignee cannot approve its own task");
+    assert!(err.to_string().contains("can approve"));
+    complete(
+      reviewer,
+      Some(cokra_protocol::TeamTaskReviewState::Approved),
+    )
+    .await
+    .expect("approved completion");
+
+    let snapshot = cokra.team_snapshot().expect("team snapshot");
+    let run = snapshot
+      .workflow
+      .as_ref()
+      .and_then(|workflow| {
+        workflow
+          .runs
+          .iter()
+          .find(|candidate| candidate.id == run.id)
+      })
+      .expect("workflow run");
+    let step = |id: &str| {
+      run
+        .steps
+        .iter()
+        .find(|step| step.id == id)
+        .map(|step| step.status.clone())
+    };
+    assert_eq!(
+      step("implement"),
+      Some(cokra_protocol::WorkflowStepStatus::Completed)
+    );
+    assert_ne!(
+      step("test"),
+      Some(cokra_protocol::WorkflowStepStatus::Blocked)
+    );
+    assert_eq!(
+      step("acceptance"),
+      Some(cokra_protocol::WorkflowStepStatus::Blocked)
+    );
+  }
+
   #[tokio::test]
   async fn test_cleanup_team_clears_persisted_state() {
     let tmpdir = tempfile::tempdir().expect("tempdir");
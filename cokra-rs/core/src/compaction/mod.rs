@@ -2,6 +2,8 @@ use std::collections::BTreeSet;
 use std::collections::HashSet;
 use std::fmt::Write as _;
 
+use serde::Deserialize;
+use serde::Serialize;
 use serde_json::Value;
 
 use crate::model::ChatRequest;
@@ -10,6 +12,7 @@ use crate::model::ModelClient;
 use crate::model::ModelError;
 use crate::model::ToolCall;
 use crate::model::Usage;
+use crate::tokenizer::TokenCounter;
 
 const SUMMARY_MARKER: &str = "[cokra-summary-v1]";
 const SUMMARIZATION_SYSTEM_PROMPT: &str = "You produce structured context checkpoint summaries for an agentic coding session. Do not continue the conversation. Preserve exact file paths, function names, requirements, and unresolved issues.";
@@ -80,7 +83,7 @@ Use this EXACT format:
 
 Keep the summary concise, factual, and continuation-ready."#;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompactionSettings {
   pub enabled: bool,
   pub reserve_tokens: usize,
@@ -134,19 +137,6 @@ pub(crate) struct AppliedCompactionResult {
   pub tokens_after_est: usize,
 }
 
-pub(crate) fn estimate_message_tokens(msg: &Message) -> usize {
-  let text_len = msg.text().map_or(0usize, |s| s.chars().count());
-  if text_len == 0 {
-    1
-  } else {
-    text_len.div_ceil(4)
-  }
-}
-
-pub(crate) fn estimate_messages_tokens(messages: &[Message]) -> usize {
-  messages.iter().map(estimate_message_tokens).sum()
-}
-
 pub(crate) fn is_summary_message(message: &Message) -> bool {
   match message {
     Message::User(text) => text.starts_with(SUMMARY_MARKER),
@@ -181,6 +171,7 @@ pub(crate) fn find_safe_tail_start_index(
   boundary_start: usize,
   keep_recent_tokens: usize,
   allow_boundary_start: bool,
+  counter: &TokenCounter,
 ) -> Option<usize> {
   if boundary_start > history.len() {
     return None;
@@ -201,7 +192,7 @@ pub(crate) fn find_safe_tail_start_index(
 
   let mut suffix_tokens = vec![0usize; history.len() + 1];
   for index in (boundary_start..history.len()).rev() {
-    suffix_tokens[index] = suffix_tokens[index + 1] + estimate_message_tokens(&history[index]);
+    suffix_tokens[index] = suffix_tokens[index + 1] + counter.count_message(&history[index]);
   }
 
   if allow_boundary_start && suffix_tokens[boundary_start] <= keep_recent_tokens {
@@ -234,6 +225,7 @@ pub(crate) fn find_safe_tail_start_index(
 pub(crate) fn prepare_compaction(
   history: &[Message],
   settings: &CompactionSettings,
+  counter: &TokenCounter,
 ) -> Option<CompactionPlan> {
   if !settings.enabled {
     return None;
@@ -254,8 +246,13 @@ pub(crate) fn prepare_compaction(
     return None;
   }
 
-  let first_kept_index =
-    find_safe_tail_start_index(history, boundary_start, settings.keep_recent_tokens, false)?;
+  let first_kept_index = find_safe_tail_start_index(
+    history,
+    boundary_start,
+    settings.keep_recent_tokens,
+    false,
+    counter,
+  )?;
   if first_kept_index <= boundary_start || first_kept_index > history.len() {
     return None;
   }
@@ -278,7 +275,7 @@ pub(crate) fn prepare_compaction(
   let system_tokens = history
     .iter()
     .filter(|message| matches!(message, Message::System(_)))
-    .map(estimate_message_tokens)
+    .map(|message| counter.count_message(message))
     .sum::<usize>();
 
   Some(CompactionPlan {
@@ -287,10 +284,10 @@ pub(crate) fn prepare_compaction(
     kept_messages: kept_messages.clone(),
     previous_summary,
     file_ops,
-    tokens_before_est: estimate_messages_tokens(history),
+    tokens_before_est: counter.count_messages(history),
     tokens_after_est: system_tokens
       .saturating_add(settings.max_summary_tokens)
-      .saturating_add(estimate_messages_tokens(&kept_messages)),
+      .saturating_add(counter.count_messages(&kept_messages)),
   })
 }
 
@@ -331,6 +328,9 @@ pub(crate) async fn run_compaction(
   Ok(CompactionRunResult { summary, usage })
 }
 
+/// System messages are carried over verbatim and stay first, so the cached
+/// system/tool prefix survives; everything after them is rewritten and loses
+/// its prompt-cache entry.
 pub(crate) fn apply_compaction(
   history: &[Message],
   summary: &str,
@@ -351,8 +351,9 @@ pub(crate) async fn compact_history_with_summary(
   model: &str,
   history: &[Message],
   settings: &CompactionSettings,
+  counter: &TokenCounter,
 ) -> Result<Option<AppliedCompactionResult>, ModelError> {
-  let Some(plan) = prepare_compaction(history, settings) else {
+  let Some(plan) = prepare_compaction(history, settings, counter) else {
     return Ok(None);
   };
 
@@ -477,6 +478,7 @@ fn serialize_messages(messages: &[Message]) -> String {
       Message::Tool {
         tool_call_id,
         content,
+        ..
       } => {
         let _ = write!(
           out,
@@ -641,6 +643,7 @@ mod tests {
   use super::prepare_compaction;
   use crate::model::ToolCall;
   use crate::model::ToolCallFunction;
+  use crate::tokenizer::TokenCounter;
 
   fn tool_call(id: &str, name: &str, arguments: &str) -> ToolCall {
     ToolCall {
@@ -669,6 +672,7 @@ mod tests {
       Message::Tool {
         tool_call_id: "call-1".to_string(),
         content: "file content".to_string(),
+        images: Vec::new(),
       },
       Message::Assistant {
         content: Some("done".to_string()),
@@ -676,7 +680,8 @@ mod tests {
       },
     ];
 
-    let first_kept = find_safe_tail_start_index(&history, 0, 2, false).unwrap();
+    let first_kept =
+      find_safe_tail_start_index(&history, 0, 2, false, &TokenCounter::default()).unwrap();
     assert_eq!(first_kept, 3);
   }
 
@@ -699,6 +704,7 @@ mod tests {
         keep_recent_tokens: 1,
         ..CompactionSettings::default()
       },
+      &TokenCounter::default(),
     )
     .expect("plan should exist");
 
@@ -719,6 +725,7 @@ mod tests {
         keep_recent_tokens: 0,
         ..CompactionSettings::default()
       },
+      &TokenCounter::default(),
     )
     .expect("plan should exist");
 
@@ -57,5 +57,8 @@ fn manifest_to_server_config(
     tool_timeout_sec: manifest.tool_timeout_sec,
     enabled_tools: manifest.enabled_tools.clone(),
     disabled_tools: manifest.disabled_tools.clone(),
+    // Sampling spends the user's model budget, so it is only granted from
+    // the user's own config, never by an integration manifest.
+    allow_sampling: false,
   })
 }
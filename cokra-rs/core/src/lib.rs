@@ -34,16 +34,23 @@ pub(crate) mod session;
 pub(crate) mod shell;
 pub mod skills;
 pub(crate) mod thread_manager;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
+pub(crate) mod tokenizer;
 pub mod tool_runtime;
 pub mod tools;
 pub(crate) mod truncate;
 #[allow(dead_code)]
 pub(crate) mod turn;
 
+pub use agent::teammate_host::serve_teammate_process;
+pub use agent::workflow::WorkflowCatalog;
+pub use agent::workflow::WorkflowTemplate;
 pub use cokra::Cokra;
 pub use cokra::CokraSpawnOk;
 pub use cokra::StreamEvent;
 pub use cokra::TurnResult;
+pub use session::EditorFileSystem;
 pub use session::Session;
 pub use turn::TurnConfig;
 pub use turn::TurnExecutor;
@@ -3,36 +3,88 @@
 //! MCP stays a dynamic tool source: connect configured servers, mirror their
 //! tool/resource surface, and expose that surface through the tool kernel.
 
+mod oauth;
+mod output_schema;
+mod prompts;
+mod sampling;
+mod supervisor;
+
+pub use oauth::McpTokenStore;
+pub use oauth::login as oauth_login;
+
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::ffi::OsString;
+use std::path::Path;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+use std::sync::Weak;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
 
 use anyhow::Result;
 use anyhow::anyhow;
+use cokra_protocol::CustomPrompt;
+use cokra_protocol::EventMsg;
+use cokra_protocol::McpStartupCompleteEvent;
+use cokra_protocol::McpStartupFailure;
+use cokra_protocol::McpStartupStatus;
+use cokra_protocol::McpStartupUpdateEvent;
+use cokra_protocol::SandboxPolicy;
+use cokra_rmcp_client::ClientCallbacks;
+use cokra_rmcp_client::ListChanged;
+use cokra_rmcp_client::OnProgress;
+use cokra_rmcp_client::SendElicitation;
+use futures::FutureExt;
 use rmcp::model::ClientCapabilities;
+use rmcp::model::Content;
+use rmcp::model::CreateElicitationRequestParams;
+use rmcp::model::CreateElicitationResult;
 use rmcp::model::Implementation;
 use rmcp::model::InitializeRequestParams;
+use rmcp::model::JsonObject;
 use rmcp::model::PaginatedRequestParams;
+use rmcp::model::Prompt;
 use rmcp::model::ProtocolVersion;
+use rmcp::model::RawContent;
 use rmcp::model::ReadResourceResult;
 use rmcp::model::Resource;
+use rmcp::model::ResourceContents;
 use rmcp::model::ResourceTemplate;
+use rmcp::model::Root;
 use rmcp::model::Tool;
 use serde::Serialize;
 use serde_json::Value;
+use tokio::sync::mpsc;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time;
 
 use cokra_config::McpConfig;
 use cokra_config::McpServerConfig;
 use cokra_config::McpServerTransportConfig;
 
+use crate::model::ModelClient;
+use crate::session::Session;
 use crate::tools::context::McpToolCallResult;
+use crate::tools::context::ToolContentPart;
+use crate::tools::registry::ToolRegistry;
 use crate::tools::spec::AdditionalProperties;
 use crate::tools::spec::JsonSchema;
 use crate::tools::spec::ToolHandlerType;
 use crate::tools::spec::ToolPermissions;
 use crate::tools::spec::ToolSpec;
+use sampling::McpSamplingRouter;
+
+/// How long a turn (or a resource or prompt request) waits for servers still
+/// making their first connection. Their tools join mid-session if they
+/// connect later.
+const STARTUP_WAIT_LIMIT: Duration = Duration::from_secs(30);
 
 #[derive(Clone)]
 struct ManagedServer {
@@ -40,11 +92,18 @@ struct ManagedServer {
   tool_timeout: Option<Duration>,
 }
 
+#[derive(Clone)]
+struct ManagedPrompt {
+  server: String,
+  prompt: Prompt,
+}
+
 #[derive(Clone)]
 struct ManagedTool {
   server: String,
   tool: String,
   spec: ToolSpec,
+  output_schema: Option<Arc<JsonObject>>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -84,143 +143,469 @@ pub struct McpResourceTemplateDescriptor {
   pub mime_type: Option<String>,
 }
 
-pub struct McpConnectionManager {
+/// Where elicitation requests from connected servers are shown.
+///
+/// Servers connect while tooling is built, before the session exists, so the
+/// target is attached afterwards. Until then every request is declined.
+#[derive(Default)]
+pub(crate) struct McpElicitationRouter {
+  target: RwLock<Option<ElicitationTarget>>,
+}
+
+#[derive(Clone)]
+struct ElicitationTarget {
+  session: Arc<Session>,
+  tx_event: mpsc::Sender<EventMsg>,
+}
+
+impl McpElicitationRouter {
+  pub(crate) fn attach(&self, session: Arc<Session>, tx_event: mpsc::Sender<EventMsg>) {
+    *self.target.write().unwrap_or_else(PoisonError::into_inner) =
+      Some(ElicitationTarget { session, tx_event });
+  }
+
+  pub(crate) async fn request(
+    &self,
+    server_name: String,
+    params: CreateElicitationRequestParams,
+  ) -> CreateElicitationResult {
+    let target = self
+      .target
+      .read()
+      .unwrap_or_else(PoisonError::into_inner)
+      .clone();
+    let (
+      Some(target),
+      CreateElicitationRequestParams::FormElicitationParams {
+        message,
+        requested_schema,
+        ..
+      },
+    ) = (target, params)
+    else {
+      // Tradeoff: kernels without a user in front of them (e.g. teammates)
+      // decline instead of stalling the server until its tool call times out.
+      return CreateElicitationResult {
+        action: rmcp::model::ElicitationAction::Decline,
+        content: None,
+      };
+    };
+
+    let response = target
+      .session
+      .request_mcp_elicitation(
+        server_name,
+        uuid::Uuid::new_v4().to_string(),
+        message,
+        serde_json::to_value(requested_schema).unwrap_or_default(),
+        Some(target.tx_event),
+      )
+      .await;
+    let action = match response.action {
+      cokra_protocol::ElicitationAction::Accept => rmcp::model::ElicitationAction::Accept,
+      cokra_protocol::ElicitationAction::Decline => rmcp::model::ElicitationAction::Decline,
+      cokra_protocol::ElicitationAction::Cancel => rmcp::model::ElicitationAction::Cancel,
+    };
+    let content = (action == rmcp::model::ElicitationAction::Accept).then(|| {
+      response
+        .content
+        .unwrap_or_else(|| Value::Object(Default::default()))
+    });
+    CreateElicitationResult { action, content }
+  }
+
+  fn sender_for(self: &Arc<Self>, server_name: &str) -> SendElicitation {
+    let router = Arc::clone(self);
+    let server_name = server_name.to_string();
+    Arc::new(move |params| {
+      let router = Arc::clone(&router);
+      let server_name = server_name.clone();
+      async move { router.request(server_name, params).await }.boxed()
+    })
+  }
+}
+
+/// What connected servers currently offer. Each server's share is replaced
+/// whenever it connects, drops or reports a changed list.
+#[derive(Default)]
+struct McpState {
   servers: HashMap<String, ManagedServer>,
   tools: HashMap<String, ManagedTool>,
   resources: HashMap<String, Vec<Resource>>,
   resource_templates: HashMap<String, Vec<ResourceTemplate>>,
+  /// Keyed by slash command name, so listing is already sorted.
+  prompts: BTreeMap<String, ManagedPrompt>,
 }
 
-impl McpConnectionManager {
-  pub async fn new(config: &McpConfig) -> Result<Self> {
-    let mut servers = HashMap::new();
-    let mut tools = HashMap::new();
-    let mut resources = HashMap::new();
-    let mut resource_templates = HashMap::new();
-
-    for (server_name, server_config) in config.servers.iter().filter(|(_, cfg)| cfg.enabled) {
-      let connect_result = connect_server(server_name, server_config)
-        .await
-        .and_then(|client| {
-          let tool_timeout = server_config.tool_timeout_sec.map(Duration::from_secs);
-          // Wrap in a future-friendly way — we call list_tools below once client is ready
-          Ok((Arc::new(client), tool_timeout))
-        });
+impl McpState {
+  fn remove_server(&mut self, server_name: &str) {
+    self.servers.remove(server_name);
+    self.tools.retain(|_, tool| tool.server != server_name);
+    self.resources.remove(server_name);
+    self.resource_templates.remove(server_name);
+    self
+      .prompts
+      .retain(|_, prompt| prompt.server != server_name);
+  }
 
-      let (client, tool_timeout) = match connect_result {
-        Ok(v) => v,
-        Err(err) => {
-          if server_config.required {
-            return Err(err.context(format!(
-              "required MCP server `{server_name}` failed to connect"
-            )));
-          }
-          tracing::warn!(
-            "MCP server `{server_name}` failed to connect (non-required, skipping): {err:#}"
-          );
-          continue;
-        }
-      };
+  fn replace_tools(
+    &mut self,
+    server_name: &str,
+    server_config: &McpServerConfig,
+    tools: Vec<Tool>,
+  ) {
+    self.tools.retain(|_, tool| tool.server != server_name);
+    for tool in filter_tools(server_config, tools) {
+      let exposed_name = qualify_tool_name(server_name, tool.name.as_ref(), &self.tools);
+      self.tools.insert(
+        exposed_name.clone(),
+        ManagedTool {
+          server: server_name.to_string(),
+          tool: tool.name.to_string(),
+          spec: ToolSpec::new(
+            exposed_name,
+            tool
+              .description
+              .as_deref()
+              .map(ToString::to_string)
+              .unwrap_or_else(|| format!("MCP tool {}", tool.name)),
+            json_schema_from_mcp_tool(&tool),
+            None,
+            ToolHandlerType::Mcp,
+            ToolPermissions::default(),
+          ),
+          output_schema: tool.output_schema.clone(),
+        },
+      );
+    }
+  }
 
-      let listed_tools = list_tools(&client, tool_timeout).await;
-      let listed_resources = list_resources(&client, tool_timeout).await;
-      let listed_resource_templates = list_resource_templates(&client, tool_timeout).await;
-
-      if listed_tools.is_err() && listed_resources.is_err() && listed_resource_templates.is_err() {
-        let tool_err = listed_tools
-          .as_ref()
-          .err()
-          .map(|err| err.to_string())
-          .unwrap_or_else(|| "n/a".to_string());
-        let resource_err = listed_resources
-          .as_ref()
-          .err()
-          .map(|err| err.to_string())
-          .unwrap_or_else(|| "n/a".to_string());
-        let template_err = listed_resource_templates
-          .as_ref()
-          .err()
-          .map(|err| err.to_string())
-          .unwrap_or_else(|| "n/a".to_string());
-        let message = format!(
-          "MCP server `{server_name}` failed capability discovery (tools: {tool_err}; resources: {resource_err}; templates: {template_err})"
-        );
-        if server_config.required {
-          return Err(anyhow!(message));
-        }
-        tracing::warn!("{message}");
+  fn replace_prompts(&mut self, server_name: &str, prompts: Vec<Prompt>) {
+    self
+      .prompts
+      .retain(|_, prompt| prompt.server != server_name);
+    for prompt in prompts {
+      let command = sanitize_tool_name(&format!("mcp__{server_name}__{}", prompt.name));
+      if self.prompts.contains_key(&command) {
+        tracing::warn!("MCP prompt `/{command}` is defined twice; keeping the first one");
         continue;
       }
+      self.prompts.insert(
+        command,
+        ManagedPrompt {
+          server: server_name.to_string(),
+          prompt,
+        },
+      );
+    }
+  }
 
-      if let Err(err) = &listed_tools {
-        tracing::warn!(
-          "MCP server `{server_name}` failed to list tools (continuing without tools): {err:#}"
-        );
-      }
-      if let Err(err) = &listed_resources {
-        tracing::warn!(
-          "MCP server `{server_name}` failed to list resources (continuing without resources): {err:#}"
-        );
-      }
-      if let Err(err) = &listed_resource_templates {
-        tracing::warn!(
-          "MCP server `{server_name}` failed to list resource templates (continuing without templates): {err:#}"
-        );
-      }
+  fn tool_specs(&self) -> Vec<ToolSpec> {
+    self.tools.values().map(|tool| tool.spec.clone()).collect()
+  }
+}
 
-      for tool in filter_tools(server_config, listed_tools.unwrap_or_default()) {
-        let exposed_name = qualify_tool_name(server_name, tool.name.as_ref(), &tools);
-        tools.insert(
-          exposed_name.clone(),
-          ManagedTool {
-            server: server_name.clone(),
-            tool: tool.name.to_string(),
-            spec: ToolSpec::new(
-              exposed_name,
-              tool
-                .description
-                .as_deref()
-                .map(ToString::to_string)
-                .unwrap_or_else(|| format!("MCP tool {}", tool.name)),
-              json_schema_from_mcp_tool(&tool),
-              None,
-              ToolHandlerType::Mcp,
-              ToolPermissions::default(),
-            ),
-          },
-        );
-      }
+/// Everything a server listed right after connecting.
+struct Discovery {
+  tools: Vec<Tool>,
+  resources: Option<Vec<Resource>>,
+  resource_templates: Option<Vec<ResourceTemplate>>,
+  prompts: Vec<Prompt>,
+}
+
+/// Live state of one configured server, for status panels.
+#[derive(Debug, Clone, Serialize)]
+pub struct McpServerSnapshot {
+  pub name: String,
+  pub status: McpStartupStatus,
+  pub required: bool,
+  pub tools: usize,
+  pub resources: usize,
+  pub prompts: usize,
+}
+
+/// Connects the configured MCP servers and keeps what they offer in sync.
+///
+/// Each server gets a supervisor task (see [`supervisor`]) that connects
+/// it, reconnects it with backoff after a failure and re-lists it on
+/// `list_changed` notifications. Required servers are connected before
+/// [`Self::new`] returns. The rest are connected on first use: the first
+/// turn, a resource or prompt request, or `/mcp` asking for statuses.
+pub struct McpConnectionManager {
+  /// Enabled servers, by name.
+  configs: BTreeMap<String, McpServerConfig>,
+  /// This manager, handed to the supervisors it spawns on first use.
+  this: Weak<Self>,
+  state: RwLock<McpState>,
+  /// Servers that have been asked to connect, by name.
+  statuses: watch::Sender<BTreeMap<String, McpStartupStatus>>,
+  /// Servers still making their first connection attempt.
+  starting: watch::Sender<BTreeSet<String>>,
+  startup_reported: AtomicBool,
+  supervisors: Mutex<HashMap<String, JoinHandle<()>>>,
+  /// Where status updates go once a session is attached.
+  tx_event: RwLock<Option<mpsc::Sender<EventMsg>>>,
+  /// The registry whose MCP specs follow `state.tools`.
+  registry: RwLock<Weak<ToolRegistry>>,
+  /// What servers get on `roots/list`; see [`workspace_roots`].
+  roots: Arc<RwLock<Vec<Root>>>,
+  elicitation_router: Arc<McpElicitationRouter>,
+  sampling_router: Arc<McpSamplingRouter>,
+}
 
-      if let Ok(server_resources) = listed_resources {
-        resources.insert(server_name.clone(), server_resources);
+impl McpConnectionManager {
+  pub async fn new(
+    config: &McpConfig,
+    cwd: &Path,
+    sandbox_policy: &SandboxPolicy,
+  ) -> Result<Arc<Self>> {
+    let configs = config
+      .servers
+      .iter()
+      .filter(|(_, cfg)| cfg.enabled)
+      .map(|(name, cfg)| (name.clone(), cfg.clone()))
+      .collect::<BTreeMap<_, _>>();
+    let manager = Arc::new_cyclic(|this| Self::with_servers(configs, this.clone()));
+    *manager
+      .roots
+      .write()
+      .unwrap_or_else(PoisonError::into_inner) = workspace_roots(cwd, sandbox_policy);
+
+    let required = manager
+      .configs
+      .iter()
+      .filter(|(_, cfg)| cfg.required)
+      .map(|(name, _)| name.clone())
+      .collect::<Vec<_>>();
+    for name in &required {
+      manager.connect(name);
+    }
+    if !required.is_empty() {
+      let mut statuses = manager.statuses.subscribe();
+      let statuses = statuses
+        .wait_for(|statuses| {
+          required
+            .iter()
+            .all(|name| !matches!(statuses.get(name), Some(McpStartupStatus::Starting)))
+        })
+        .await?;
+      for name in &required {
+        if let Some(McpStartupStatus::Failed { error }) = statuses.get(name) {
+          return Err(anyhow!(
+            "required MCP server `{name}` failed to connect: {error}"
+          ));
+        }
       }
-      if let Ok(server_templates) = listed_resource_templates {
-        resource_templates.insert(server_name.clone(), server_templates);
+    }
+    Ok(manager)
+  }
+
+  /// A manager with no servers, for kernels that never expose MCP tools.
+  pub fn empty() -> Self {
+    Self::default()
+  }
+
+  /// A manager for `configs` with no server asked to connect yet.
+  fn with_servers(configs: BTreeMap<String, McpServerConfig>, this: Weak<Self>) -> Self {
+    Self {
+      statuses: watch::Sender::new(BTreeMap::new()),
+      starting: watch::Sender::new(BTreeSet::new()),
+      configs,
+      this,
+      state: RwLock::new(McpState::default()),
+      startup_reported: AtomicBool::new(false),
+      supervisors: Mutex::new(HashMap::new()),
+      tx_event: RwLock::new(None),
+      registry: RwLock::new(Weak::new()),
+      roots: Arc::new(RwLock::new(Vec::new())),
+      elicitation_router: Arc::new(McpElicitationRouter::default()),
+      sampling_router: Arc::new(McpSamplingRouter::default()),
+    }
+  }
+
+  /// Follow a change of working directory or sandbox policy, telling every
+  /// connected server that its roots changed.
+  pub(crate) fn update_roots(&self, cwd: &Path, sandbox_policy: &SandboxPolicy) {
+    let roots = workspace_roots(cwd, sandbox_policy);
+    {
+      let mut current = self.roots.write().unwrap_or_else(PoisonError::into_inner);
+      if *current == roots {
+        return;
       }
+      *current = roots;
+    }
+    let clients = self
+      .state
+      .read()
+      .unwrap_or_else(PoisonError::into_inner)
+      .servers
+      .iter()
+      .map(|(name, server)| (name.clone(), Arc::clone(&server.client)))
+      .collect::<Vec<_>>();
+    for (server_name, client) in clients {
+      tokio::spawn(async move {
+        if let Err(err) = client.notify_roots_list_changed().await {
+          tracing::debug!("failed to notify MCP server `{server_name}` of new roots: {err:#}");
+        }
+      });
+    }
+  }
 
-      servers.insert(
-        server_name.clone(),
-        ManagedServer {
-          client,
-          tool_timeout,
-        },
-      );
+  /// Route elicitation requests from every connected server to `session`.
+  pub(crate) fn attach_elicitation_target(
+    &self,
+    session: Arc<Session>,
+    tx_event: mpsc::Sender<EventMsg>,
+  ) {
+    self.elicitation_router.attach(session, tx_event);
+  }
+
+  /// Serve sampling requests from servers with `allow_sampling` using
+  /// `session`'s current model.
+  pub(crate) fn attach_sampling_target(
+    &self,
+    session: Arc<Session>,
+    tx_event: mpsc::Sender<EventMsg>,
+    model_client: Arc<ModelClient>,
+    cwd: PathBuf,
+  ) {
+    self
+      .sampling_router
+      .attach(session, tx_event, model_client, cwd);
+  }
+
+  /// Report server status changes on `tx_event`, starting with the status
+  /// each server has reached so far.
+  pub(crate) fn attach_event_target(&self, tx_event: mpsc::Sender<EventMsg>) {
+    *self
+      .tx_event
+      .write()
+      .unwrap_or_else(PoisonError::into_inner) = Some(tx_event);
+    let statuses = self.statuses.borrow().clone();
+    for (server, status) in statuses {
+      self.emit(EventMsg::McpStartupUpdate(McpStartupUpdateEvent {
+        server,
+        status,
+      }));
     }
+    if self.starting.borrow().is_empty() {
+      self.report_startup_complete();
+    }
+  }
 
-    Ok(Self {
-      servers,
-      tools,
-      resources,
-      resource_templates,
-    })
+  /// Keep `registry`'s MCP tools in step with connected servers.
+  pub(crate) fn attach_registry(&self, registry: &Arc<ToolRegistry>) {
+    *self
+      .registry
+      .write()
+      .unwrap_or_else(PoisonError::into_inner) = Arc::downgrade(registry);
+    self.sync_registry();
+  }
+
+  /// Connect every server not connected yet and wait, at most
+  /// [`STARTUP_WAIT_LIMIT`], for those still making their first attempt, so
+  /// a turn sees their tools.
+  pub async fn wait_for_startup(&self) {
+    self.connect_all();
+    self
+      .wait_for_first_attempts(|starting| starting.is_empty())
+      .await;
+  }
+
+  async fn wait_for_first_attempts(&self, done: impl FnMut(&BTreeSet<String>) -> bool) {
+    let mut starting = self.starting.subscribe();
+    let _ = time::timeout(STARTUP_WAIT_LIMIT, starting.wait_for(done)).await;
+  }
+
+  /// Start connecting every server that has not been asked to yet.
+  pub fn connect_all(&self) {
+    for server_name in self.configs.keys() {
+      self.connect(server_name);
+    }
+  }
+
+  /// Start a supervisor for `server_name` unless it already has one.
+  fn connect(&self, server_name: &str) {
+    let mut supervisors = self
+      .supervisors
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner);
+    if supervisors.contains_key(server_name) || !self.configs.contains_key(server_name) {
+      return;
+    }
+    self.starting.send_modify(|starting| {
+      starting.insert(server_name.to_string());
+    });
+    self.set_status(server_name, McpStartupStatus::Starting);
+    supervisors.insert(
+      server_name.to_string(),
+      tokio::spawn(supervisor::supervise(
+        self.this.clone(),
+        server_name.to_string(),
+      )),
+    );
+  }
+
+  /// Drop `server_name`'s connection, if any, and connect it again.
+  pub fn restart_server(&self, server_name: &str) -> Result<()> {
+    if !self.configs.contains_key(server_name) {
+      return Err(anyhow!("unknown MCP server `{server_name}`"));
+    }
+    if let Some(supervisor) = self
+      .supervisors
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner)
+      .remove(server_name)
+    {
+      supervisor.abort();
+    }
+    self.uninstall(server_name);
+    self.connect(server_name);
+    Ok(())
+  }
+
+  /// Every configured server with its status and what it offers, by name.
+  /// Servers not connected yet start connecting.
+  pub fn server_snapshots(&self) -> Vec<McpServerSnapshot> {
+    self.connect_all();
+    let statuses = self.statuses.borrow().clone();
+    let state = self.state.read().unwrap_or_else(PoisonError::into_inner);
+    self
+      .configs
+      .iter()
+      .map(|(name, cfg)| McpServerSnapshot {
+        name: name.clone(),
+        status: statuses
+          .get(name)
+          .cloned()
+          .unwrap_or(McpStartupStatus::Starting),
+        required: cfg.required,
+        tools: state
+          .tools
+          .values()
+          .filter(|tool| &tool.server == name)
+          .count(),
+        resources: state.resources.get(name).map_or(0, Vec::len),
+        prompts: state
+          .prompts
+          .values()
+          .filter(|prompt| &prompt.server == name)
+          .count(),
+      })
+      .collect()
   }
 
   pub fn tool_specs(&self) -> Vec<ToolSpec> {
-    self.tools.values().map(|tool| tool.spec.clone()).collect()
+    self
+      .state
+      .read()
+      .unwrap_or_else(PoisonError::into_inner)
+      .tool_specs()
   }
 
   pub fn tool_descriptors(&self) -> Vec<McpToolDescriptor> {
-    let mut descriptors = self
+    let state = self.state.read().unwrap_or_else(PoisonError::into_inner);
+    let mut descriptors = state
       .tools
       .iter()
       .map(|(exposed_name, tool)| McpToolDescriptor {
@@ -236,21 +621,44 @@ impl McpConnectionManager {
   }
 
   pub fn tool_names(&self) -> Vec<String> {
-    self.tools.keys().cloned().collect()
+    self
+      .state
+      .read()
+      .unwrap_or_else(PoisonError::into_inner)
+      .tools
+      .keys()
+      .cloned()
+      .collect()
   }
 
   pub fn server_names(&self) -> Vec<String> {
-    let mut names = self.servers.keys().cloned().collect::<Vec<_>>();
+    let mut names = self
+      .state
+      .read()
+      .unwrap_or_else(PoisonError::into_inner)
+      .servers
+      .keys()
+      .cloned()
+      .collect::<Vec<_>>();
     names.sort();
     names
   }
 
   pub fn has_tools(&self) -> bool {
-    !self.tools.is_empty()
+    !self
+      .state
+      .read()
+      .unwrap_or_else(PoisonError::into_inner)
+      .tools
+      .is_empty()
   }
 
+  /// Resources of connected servers. Servers not connected yet start
+  /// connecting.
   pub fn resource_descriptors(&self) -> Vec<McpResourceDescriptor> {
-    let mut descriptors = self
+    self.connect_all();
+    let state = self.state.read().unwrap_or_else(PoisonError::into_inner);
+    let mut descriptors = state
       .resources
       .iter()
       .flat_map(|(server_name, resources)| {
@@ -274,8 +682,12 @@ impl McpConnectionManager {
     descriptors
   }
 
+  /// Resource templates of connected servers. Servers not connected yet
+  /// start connecting.
   pub fn resource_template_descriptors(&self) -> Vec<McpResourceTemplateDescriptor> {
-    let mut descriptors = self
+    self.connect_all();
+    let state = self.state.read().unwrap_or_else(PoisonError::into_inner);
+    let mut descriptors = state
       .resource_templates
       .iter()
       .flat_map(|(server_name, templates)| {
@@ -300,99 +712,499 @@ impl McpConnectionManager {
     descriptors
   }
 
-  pub fn resolve_tool_name(&self, exposed_name: &str) -> Option<(&str, &str)> {
+  /// Prompts from every connected server, as slash commands sorted by
+  /// name. Servers not connected yet start connecting.
+  pub fn prompt_descriptors(&self) -> Vec<CustomPrompt> {
+    self.connect_all();
+    self
+      .state
+      .read()
+      .unwrap_or_else(PoisonError::into_inner)
+      .prompts
+      .iter()
+      .map(|(command, managed)| {
+        prompts::custom_prompt(command.clone(), &managed.server, &managed.prompt)
+      })
+      .collect()
+  }
+
+  /// Fetch the prompt behind slash command `command` and flatten it into
+  /// the text of one user message.
+  pub async fn render_prompt(
+    &self,
+    command: &str,
+    arguments: HashMap<String, String>,
+  ) -> Result<String> {
+    self.wait_for_startup().await;
+    let (server, prompt_name) = {
+      let state = self.state.read().unwrap_or_else(PoisonError::into_inner);
+      let managed = state
+        .prompts
+        .get(command)
+        .ok_or_else(|| anyhow!("unknown MCP prompt `/{command}`"))?;
+      (
+        self.connected_server(&state, &managed.server)?,
+        managed.prompt.name.clone(),
+      )
+    };
+    let arguments = (!arguments.is_empty()).then(|| {
+      arguments
+        .into_iter()
+        .map(|(name, value)| (name, Value::String(value)))
+        .collect()
+    });
+    let result = server
+      .client
+      .get_prompt(prompt_name, arguments, server.tool_timeout)
+      .await?;
+    Ok(prompts::render_prompt_messages(&result))
+  }
+
+  pub fn resolve_tool_name(&self, exposed_name: &str) -> Option<(String, String)> {
     self
+      .state
+      .read()
+      .unwrap_or_else(PoisonError::into_inner)
       .tools
       .get(exposed_name)
-      .map(|tool| (tool.server.as_str(), tool.tool.as_str()))
+      .map(|tool| (tool.server.clone(), tool.tool.clone()))
   }
 
+  /// Call a tool by its exposed name. Dropping the future cancels the call
+  /// on the server.
   pub async fn call_tool(
     &self,
     exposed_name: &str,
     arguments: Option<Value>,
+    on_progress: Option<OnProgress>,
   ) -> Result<McpToolCallResult> {
-    let managed_tool = self
-      .tools
-      .get(exposed_name)
-      .ok_or_else(|| anyhow!("unknown MCP tool `{exposed_name}`"))?;
-    let server = self
-      .servers
-      .get(&managed_tool.server)
-      .ok_or_else(|| anyhow!("unknown MCP server `{}`", managed_tool.server))?;
+    let (server, tool_name, output_schema) = {
+      let state = self.state.read().unwrap_or_else(PoisonError::into_inner);
+      let managed_tool = state
+        .tools
+        .get(exposed_name)
+        .ok_or_else(|| anyhow!("unknown MCP tool `{exposed_name}`"))?;
+      (
+        self.connected_server(&state, &managed_tool.server)?,
+        managed_tool.tool.clone(),
+        managed_tool.output_schema.clone(),
+      )
+    };
     let result = server
       .client
-      .call_tool(managed_tool.tool.clone(), arguments, server.tool_timeout)
+      .call_tool(tool_name, arguments, server.tool_timeout, on_progress)
       .await?;
 
+    let is_error = result.is_error.unwrap_or(false);
+    // Error results are free-form, so only successful ones must match.
+    if let Some(schema) = output_schema.filter(|_| !is_error) {
+      let structured = result.structured_content.as_ref().ok_or_else(|| {
+        anyhow!(
+          "MCP tool `{exposed_name}` declares an outputSchema but returned no structured content"
+        )
+      })?;
+      output_schema::validate(&Value::Object((*schema).clone()), structured).map_err(|errors| {
+        anyhow!("structured content from MCP tool `{exposed_name}` does not match its outputSchema:\n{errors}")
+      })?;
+    }
+
     Ok(McpToolCallResult {
-      content: result
-        .content
-        .into_iter()
-        .map(|item| {
-          serde_json::to_value(item).unwrap_or_else(|_| Value::String("<content>".to_string()))
-        })
-        .collect(),
+      content: result.content.into_iter().map(content_part).collect(),
       structured_content: result.structured_content,
-      is_error: result.is_error.unwrap_or(false),
+      is_error,
     })
   }
 
   pub async fn read_resource(&self, server_name: &str, uri: &str) -> Result<ReadResourceResult> {
-    let server = self
-      .servers
-      .get(server_name)
-      .ok_or_else(|| anyhow!("unknown MCP server `{server_name}`"))?;
+    self.connect(server_name);
+    self
+      .wait_for_first_attempts(|starting| !starting.contains(server_name))
+      .await;
+    let server = {
+      let state = self.state.read().unwrap_or_else(PoisonError::into_inner);
+      self.connected_server(&state, server_name)?
+    };
     server
       .client
       .read_resource(uri.to_string(), server.tool_timeout)
       .await
   }
+
+  fn connected_server(&self, state: &McpState, server_name: &str) -> Result<ManagedServer> {
+    if let Some(server) = state.servers.get(server_name) {
+      return Ok(server.clone());
+    }
+    match self.statuses.borrow().get(server_name) {
+      Some(McpStartupStatus::Starting) => {
+        Err(anyhow!("MCP server `{server_name}` is still connecting"))
+      }
+      Some(_) => Err(anyhow!("MCP server `{server_name}` is not connected")),
+      None => Err(anyhow!("unknown MCP server `{server_name}`")),
+    }
+  }
+
+  fn callbacks_for(
+    &self,
+    manager: &Weak<Self>,
+    server_name: &str,
+    server_config: &McpServerConfig,
+  ) -> ClientCallbacks {
+    let manager = manager.clone();
+    let list_server = server_name.to_string();
+    let roots = Arc::clone(&self.roots);
+    ClientCallbacks {
+      send_elicitation: Some(self.elicitation_router.sender_for(server_name)),
+      send_sampling: server_config
+        .allow_sampling
+        .then(|| self.sampling_router.sender_for(server_name)),
+      on_list_changed: Some(Arc::new(move |list| {
+        let manager = manager.clone();
+        let server_name = list_server.clone();
+        tokio::spawn(async move {
+          if let Some(manager) = manager.upgrade() {
+            manager.refresh_list(&server_name, list).await;
+          }
+        });
+      })),
+      list_roots: Some(Arc::new(move || {
+        roots.read().unwrap_or_else(PoisonError::into_inner).clone()
+      })),
+    }
+  }
+
+  fn install(
+    &self,
+    server_name: &str,
+    server_config: &McpServerConfig,
+    server: ManagedServer,
+    discovery: Discovery,
+  ) {
+    {
+      let mut state = self.state.write().unwrap_or_else(PoisonError::into_inner);
+      state.remove_server(server_name);
+      state.replace_tools(server_name, server_config, discovery.tools);
+      if let Some(resources) = discovery.resources {
+        state.resources.insert(server_name.to_string(), resources);
+      }
+      if let Some(templates) = discovery.resource_templates {
+        state
+          .resource_templates
+          .insert(server_name.to_string(), templates);
+      }
+      state.replace_prompts(server_name, discovery.prompts);
+      state.servers.insert(server_name.to_string(), server);
+    }
+    self.sync_registry();
+  }
+
+  fn uninstall(&self, server_name: &str) {
+    self
+      .state
+      .write()
+      .unwrap_or_else(PoisonError::into_inner)
+      .remove_server(server_name);
+    self.sync_registry();
+  }
+
+  /// Re-list whatever `server_name` reported as changed.
+  async fn refresh_list(&self, server_name: &str, list: ListChanged) {
+    let Some(server_config) = self.configs.get(server_name) else {
+      return;
+    };
+    let Some(server) = self
+      .state
+      .read()
+      .unwrap_or_else(PoisonError::into_inner)
+      .servers
+      .get(server_name)
+      .cloned()
+    else {
+      return;
+    };
+    let client = &server.client;
+    let timeout = server.tool_timeout;
+    match list {
+      ListChanged::Tools => match list_tools(client, timeout).await {
+        Ok(tools) => {
+          self
+            .state
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .replace_tools(server_name, server_config, tools);
+          self.sync_registry();
+        }
+        Err(err) => tracing::warn!("MCP server `{server_name}` failed to re-list tools: {err:#}"),
+      },
+      ListChanged::Resources => {
+        let resources = list_resources(client, timeout).await;
+        let templates = list_resource_templates(client, timeout).await;
+        let mut state = self.state.write().unwrap_or_else(PoisonError::into_inner);
+        match resources {
+          Ok(resources) => {
+            state.resources.insert(server_name.to_string(), resources);
+          }
+          Err(err) => {
+            tracing::warn!("MCP server `{server_name}` failed to re-list resources: {err:#}")
+          }
+        }
+        if let Ok(templates) = templates {
+          state
+            .resource_templates
+            .insert(server_name.to_string(), templates);
+        }
+      }
+      ListChanged::Prompts => match list_prompts(client, timeout).await {
+        Ok(prompts) => self
+          .state
+          .write()
+          .unwrap_or_else(PoisonError::into_inner)
+          .replace_prompts(server_name, prompts),
+        Err(err) => {
+          tracing::warn!("MCP server `{server_name}` failed to re-list prompts: {err:#}")
+        }
+      },
+    }
+  }
+
+  fn sync_registry(&self) {
+    let registry = self
+      .registry
+      .read()
+      .unwrap_or_else(PoisonError::into_inner)
+      .upgrade();
+    if let Some(registry) = registry {
+      registry.set_mcp_specs(self.tool_specs());
+    }
+  }
+
+  fn set_status(&self, server_name: &str, status: McpStartupStatus) {
+    self.statuses.send_modify(|statuses| {
+      statuses.insert(server_name.to_string(), status.clone());
+    });
+    self.emit(EventMsg::McpStartupUpdate(McpStartupUpdateEvent {
+      server: server_name.to_string(),
+      status,
+    }));
+  }
+
+  /// Count `server_name`'s first connection attempt as finished.
+  fn finish_startup_attempt(&self, server_name: &str) {
+    let mut remaining = 0;
+    self.starting.send_modify(|starting| {
+      starting.remove(server_name);
+      remaining = starting.len();
+    });
+    if remaining == 0 {
+      self.report_startup_complete();
+    }
+  }
+
+  fn report_startup_complete(&self) {
+    if self.statuses.borrow().is_empty()
+      || self
+        .tx_event
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .is_none()
+      || self.startup_reported.swap(true, Ordering::SeqCst)
+    {
+      return;
+    }
+    let mut event = McpStartupCompleteEvent::default();
+    for (server, status) in self.statuses.borrow().iter() {
+      match status {
+        McpStartupStatus::Ready => event.ready.push(server.clone()),
+        McpStartupStatus::Cancelled => event.cancelled.push(server.clone()),
+        McpStartupStatus::Failed { error } => event.failed.push(McpStartupFailure {
+          server: server.clone(),
+          error: error.clone(),
+        }),
+        McpStartupStatus::Starting => {}
+      }
+    }
+    self.emit(EventMsg::McpStartupComplete(event));
+  }
+
+  fn emit(&self, event: EventMsg) {
+    let tx_event = self
+      .tx_event
+      .read()
+      .unwrap_or_else(PoisonError::into_inner)
+      .clone();
+    if let Some(tx_event) = tx_event
+      && let Err(err) = tx_event.try_send(event)
+    {
+      tracing::debug!("dropped MCP status event: {err}");
+    }
+  }
 }
 
 impl Default for McpConnectionManager {
   fn default() -> Self {
-    Self {
-      servers: HashMap::new(),
-      tools: HashMap::new(),
-      resources: HashMap::new(),
-      resource_templates: HashMap::new(),
+    Self::with_servers(BTreeMap::new(), Weak::new())
+  }
+}
+
+impl Drop for McpConnectionManager {
+  fn drop(&mut self) {
+    for (_, supervisor) in self
+      .supervisors
+      .get_mut()
+      .unwrap_or_else(PoisonError::into_inner)
+      .drain()
+    {
+      supervisor.abort();
+    }
+  }
+}
+
+/// Connect `server_name` and list everything it offers.
+///
+/// Fails if the handshake fails or if the server answers none of the list
+/// requests; a server missing only some lists is kept.
+async fn connect_and_discover(
+  server_name: &str,
+  server_config: &McpServerConfig,
+  callbacks: ClientCallbacks,
+) -> Result<(ManagedServer, Discovery)> {
+  let client = Arc::new(connect_server(server_name, server_config, callbacks).await?);
+  let tool_timeout = server_config.tool_timeout_sec.map(Duration::from_secs);
+
+  let listed_tools = list_tools(&client, tool_timeout).await;
+  let listed_resources = list_resources(&client, tool_timeout).await;
+  let listed_resource_templates = list_resource_templates(&client, tool_timeout).await;
+  let listed_prompts = list_prompts(&client, tool_timeout).await;
+
+  if listed_tools.is_err()
+    && listed_resources.is_err()
+    && listed_resource_templates.is_err()
+    && listed_prompts.is_err()
+  {
+    let tool_err = listed_tools
+      .as_ref()
+      .err()
+      .map(|err| err.to_string())
+      .unwrap_or_else(|| "n/a".to_string());
+    let resource_err = listed_resources
+      .as_ref()
+      .err()
+      .map(|err| err.to_string())
+      .unwrap_or_else(|| "n/a".to_string());
+    let template_err = listed_resource_templates
+      .as_ref()
+      .err()
+      .map(|err| err.to_string())
+      .unwrap_or_else(|| "n/a".to_string());
+    let prompt_err = listed_prompts
+      .as_ref()
+      .err()
+      .map(|err| err.to_string())
+      .unwrap_or_else(|| "n/a".to_string());
+    return Err(anyhow!(
+      "MCP server `{server_name}` failed capability discovery (tools: {tool_err}; resources: {resource_err}; templates: {template_err}; prompts: {prompt_err})"
+    ));
+  }
+
+  if let Err(err) = &listed_tools {
+    tracing::warn!(
+      "MCP server `{server_name}` failed to list tools (continuing without tools): {err:#}"
+    );
+  }
+  if let Err(err) = &listed_resources {
+    tracing::warn!(
+      "MCP server `{server_name}` failed to list resources (continuing without resources): {err:#}"
+    );
+  }
+  if let Err(err) = &listed_resource_templates {
+    tracing::warn!(
+      "MCP server `{server_name}` failed to list resource templates (continuing without templates): {err:#}"
+    );
+  }
+  if let Err(err) = &listed_prompts {
+    tracing::debug!("MCP server `{server_name}` has no prompts: {err:#}");
+  }
+
+  Ok((
+    ManagedServer {
+      client,
+      tool_timeout,
+    },
+    Discovery {
+      tools: listed_tools.unwrap_or_default(),
+      resources: listed_resources.ok(),
+      resource_templates: listed_resource_templates.ok(),
+      prompts: listed_prompts.unwrap_or_default(),
+    },
+  ))
+}
+
+/// Keep an MCP content block typed. Audio and resource links have no model
+/// input of their own, so they become text placeholders.
+fn content_part(content: Content) -> ToolContentPart {
+  match content.raw {
+    RawContent::Text(text) => ToolContentPart::Text { text: text.text },
+    RawContent::Image(image) => ToolContentPart::Image {
+      mime_type: image.mime_type,
+      data: image.data,
+    },
+    RawContent::Resource(embedded) => match embedded.resource {
+      ResourceContents::TextResourceContents {
+        uri,
+        mime_type,
+        text,
+        ..
+      } => ToolContentPart::Resource {
+        uri,
+        mime_type,
+        text: Some(text),
+      },
+      ResourceContents::BlobResourceContents { uri, mime_type, .. } => ToolContentPart::Resource {
+        uri,
+        mime_type,
+        text: None,
+      },
+    },
+    RawContent::Audio(audio) => ToolContentPart::Text {
+      text: format!("[audio: {}]", audio.mime_type),
+    },
+    RawContent::ResourceLink(link) => ToolContentPart::Text {
+      text: format!("[resource link: {} ({})]", link.uri, link.name),
+    },
+  }
+}
+
+/// The roots servers see: the working directory, then the sandbox's other
+/// writable roots. Relative writable roots are taken from `cwd`.
+fn workspace_roots(cwd: &Path, sandbox_policy: &SandboxPolicy) -> Vec<Root> {
+  let mut paths = vec![cwd.to_path_buf()];
+  if let SandboxPolicy::WorkspaceWrite { writable_roots, .. } = sandbox_policy {
+    for root in writable_roots {
+      let path = cwd.join(root);
+      if !paths.contains(&path) {
+        paths.push(path);
+      }
     }
   }
+  paths
+    .into_iter()
+    .filter_map(|path| {
+      let uri = reqwest::Url::from_file_path(&path).ok()?;
+      Some(Root {
+        uri: uri.to_string(),
+        name: path
+          .file_name()
+          .map(|name| name.to_string_lossy().into_owned()),
+      })
+    })
+    .collect()
 }
 
 async fn connect_server(
   server_name: &str,
   config: &McpServerConfig,
+  callbacks: ClientCallbacks,
 ) -> Result<cokra_rmcp_client::RmcpClient> {
-  let client = match &config.transport {
-    McpServerTransportConfig::Stdio {
-      command,
-      args,
-      env,
-      cwd,
-    } => {
-      cokra_rmcp_client::RmcpClient::new_stdio_client(
-        OsString::from(command),
-        args.iter().map(OsString::from).collect(),
-        env.clone(),
-        cwd.clone(),
-      )
-      .await?
-    }
-    McpServerTransportConfig::Http {
-      url,
-      bearer_token,
-      headers,
-    } => {
-      cokra_rmcp_client::RmcpClient::new_streamable_http_client(
-        url,
-        bearer_token.clone(),
-        headers.clone(),
-      )
-      .await?
-    }
-  };
+  let client = open_transport(server_name, config).await?;
 
   client
     .initialize(
@@ -417,6 +1229,7 @@ async fn connect_server(
         protocol_version: ProtocolVersion::V_2025_06_18,
       },
       config.startup_timeout_sec.map(Duration::from_secs),
+      callbacks,
     )
     .await
     .map_err(|err| anyhow!("failed to initialize MCP server `{server_name}`: {err:#}"))?;
@@ -424,6 +1237,82 @@ async fn connect_server(
   Ok(client)
 }
 
+/// Start `server_name`'s configured transport; the handshake comes after.
+async fn open_transport(
+  server_name: &str,
+  config: &McpServerConfig,
+) -> Result<cokra_rmcp_client::RmcpClient> {
+  #[cfg(test)]
+  if let Some(serve) = in_process_server(server_name) {
+    let (client_end, server_end) = tokio::io::duplex(64 * 1024);
+    serve(server_end);
+    return Ok(cokra_rmcp_client::RmcpClient::new_in_process_client(
+      client_end,
+    ));
+  }
+  Ok(match &config.transport {
+    McpServerTransportConfig::Stdio {
+      command,
+      args,
+      env,
+      cwd,
+    } => {
+      cokra_rmcp_client::RmcpClient::new_stdio_client(
+        OsString::from(command),
+        args.iter().map(OsString::from).collect(),
+        env.clone(),
+        cwd.clone(),
+      )
+      .await?
+    }
+    McpServerTransportConfig::Http {
+      url,
+      bearer_token,
+      headers,
+    } => {
+      // A configured bearer token wins over tokens from `cokra mcp login`.
+      let oauth = match bearer_token {
+        Some(_) => None,
+        None => oauth::stored_session(server_name).await,
+      };
+      cokra_rmcp_client::RmcpClient::new_streamable_http_client(
+        url,
+        bearer_token.clone(),
+        headers.clone(),
+        oauth,
+      )
+      .await?
+    }
+  })
+}
+
+/// Serves the other end of a pipe for a test server run in this process.
+#[cfg(test)]
+type ServeInProcess = Arc<dyn Fn(tokio::io::DuplexStream) + Send + Sync>;
+
+/// Test servers run in this process, by name. Connecting to one of these
+/// names uses a pipe to it instead of the configured transport.
+#[cfg(test)]
+static IN_PROCESS_SERVERS: std::sync::LazyLock<Mutex<HashMap<String, ServeInProcess>>> =
+  std::sync::LazyLock::new(Mutex::default);
+
+#[cfg(test)]
+fn serve_in_process(server_name: &str, serve: ServeInProcess) {
+  IN_PROCESS_SERVERS
+    .lock()
+    .unwrap_or_else(PoisonError::into_inner)
+    .insert(server_name.to_string(), serve);
+}
+
+#[cfg(test)]
+fn in_process_server(server_name: &str) -> Option<ServeInProcess> {
+  IN_PROCESS_SERVERS
+    .lock()
+    .unwrap_or_else(PoisonError::into_inner)
+    .get(server_name)
+    .cloned()
+}
+
 async fn list_tools(
   client: &cokra_rmcp_client::RmcpClient,
   timeout: Option<Duration>,
@@ -502,6 +1391,32 @@ async fn list_resource_templates(
   }
 }
 
+async fn list_prompts(
+  client: &cokra_rmcp_client::RmcpClient,
+  timeout: Option<Duration>,
+) -> Result<Vec<Prompt>> {
+  let mut prompts = Vec::new();
+  let mut cursor: Option<String> = None;
+
+  loop {
+    let result = client
+      .list_prompts(
+        cursor.as_ref().map(|next| PaginatedRequestParams {
+          meta: None,
+          cursor: Some(next.clone()),
+        }),
+        timeout,
+      )
+      .await?;
+    prompts.extend(result.prompts);
+
+    match result.next_cursor {
+      Some(next) => cursor = Some(next),
+      None => return Ok(prompts),
+    }
+  }
+}
+
 fn filter_tools(server_config: &McpServerConfig, tools: Vec<Tool>) -> Vec<Tool> {
   tools
     .into_iter()
@@ -676,6 +1591,7 @@ mod tests {
         server: server.to_string(),
         tool: tool.to_string(),
         spec,
+        output_schema: None,
       },
     )
   }
@@ -760,4 +1676,233 @@ mod tests {
   fn sanitize_tool_name_spaces_replaced() {
     assert_eq!(sanitize_tool_name("my tool name"), "my_tool_name");
   }
+
+  #[tokio::test]
+  async fn elicitation_without_target_is_declined() {
+    let router = McpElicitationRouter::default();
+    let result = router
+      .request(
+        "forms".to_string(),
+        CreateElicitationRequestParams::FormElicitationParams {
+          meta: None,
+          message: "Confirm?".to_string(),
+          requested_schema: rmcp::model::ElicitationSchema::builder().build().unwrap(),
+        },
+      )
+      .await;
+    assert_eq!(result.action, rmcp::model::ElicitationAction::Decline);
+    assert_eq!(result.content, None);
+  }
+
+  #[tokio::test]
+  async fn elicitation_round_trips_through_session() {
+    let router = McpElicitationRouter::default();
+    let session = Arc::new(Session::new());
+    let (tx_event, mut rx_event) = mpsc::channel(8);
+    router.attach(Arc::clone(&session), tx_event);
+
+    let pending = tokio::spawn(async move {
+      router
+        .request(
+          "forms".to_string(),
+          CreateElicitationRequestParams::FormElicitationParams {
+            meta: None,
+            message: "Who is deploying?".to_string(),
+            requested_schema: rmcp::model::ElicitationSchema::builder()
+              .required_string("name")
+              .build()
+              .unwrap(),
+          },
+        )
+        .await
+    });
+
+    let Some(EventMsg::ElicitationRequest(request)) = rx_event.recv().await else {
+      panic!("expected an elicitation request event");
+    };
+    assert_eq!(request.server_name, "forms");
+    assert_eq!(request.message, "Who is deploying?");
+    assert_eq!(
+      request.requested_schema["required"],
+      serde_json::json!(["name"])
+    );
+
+    let content = serde_json::json!({ "name": "ada" });
+    assert!(
+      session
+        .notify_mcp_elicitation(
+          "forms",
+          &request.id,
+          crate::session::ElicitationResponse {
+            action: cokra_protocol::ElicitationAction::Accept,
+            content: Some(content.clone()),
+          },
+        )
+        .await
+    );
+    let result = pending.await.unwrap();
+    assert_eq!(result.action, rmcp::model::ElicitationAction::Accept);
+    assert_eq!(result.content, Some(content));
+  }
+
+  #[test]
+  fn tool_results_keep_typed_parts_and_render_them_for_the_model() {
+    let result = McpToolCallResult {
+      content: vec![
+        content_part(Content::text("Rendered the chart.")),
+        content_part(Content::image("iVBORw0KGgoAAAA=", "image/png")),
+        content_part(Content::resource(ResourceContents::TextResourceContents {
+          uri: "file:///work/chart.md".to_string(),
+          mime_type: Some("text/markdown".to_string()),
+          text: "# Sales".to_string(),
+          meta: None,
+        })),
+      ],
+      structured_content: Some(serde_json::json!({ "points": 3 })),
+      is_error: false,
+    };
+
+    assert_eq!(
+      result.content[1],
+      ToolContentPart::Image {
+        mime_type: "image/png".to_string(),
+        data: "iVBORw0KGgoAAAA=".to_string(),
+      }
+    );
+    assert_eq!(
+      result.render(),
+      [
+        "Rendered the chart.",
+        "[image: image/png, 11 B]",
+        "[resource: file:///work/chart.md (text/markdown)]",
+        "# Sales",
+        "```json",
+        "{",
+        "  \"points\": 3",
+        "}",
+        "```",
+      ]
+      .join("\n")
+    );
+
+    let echoed = McpToolCallResult {
+      content: vec![content_part(Content::text(r#"{"points":3}"#))],
+      structured_content: Some(serde_json::json!({ "points": 3 })),
+      is_error: false,
+    };
+    assert_eq!(echoed.render(), r#"{"points":3}"#);
+  }
+
+  #[test]
+  fn workspace_roots_lead_with_cwd_and_dedupe_writable_roots() {
+    let cwd = Path::new("/work/app");
+    let policy = SandboxPolicy::WorkspaceWrite {
+      writable_roots: vec![".".to_string(), "/tmp/cache".to_string(), "out".to_string()],
+      read_only_access: cokra_protocol::ReadOnlyAccess::FullAccess,
+      network_access: false,
+      exclude_tmpdir_env_var: false,
+      exclude_slash_tmp: false,
+    };
+
+    let uris = workspace_roots(cwd, &policy)
+      .into_iter()
+      .map(|root| (root.uri, root.name))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      uris,
+      vec![
+        ("file:///work/app".to_string(), Some("app".to_string())),
+        ("file:///tmp/cache".to_string(), Some("cache".to_string())),
+        ("file:///work/app/out".to_string(), Some("out".to_string())),
+      ]
+    );
+    assert_eq!(
+      workspace_roots(cwd, &SandboxPolicy::DangerFullAccess).len(),
+      1
+    );
+  }
+
+  fn unreachable_server(required: bool) -> McpServerConfig {
+    McpServerConfig {
+      transport: McpServerTransportConfig::Stdio {
+        command: "cokra-test-no-such-mcp-server".to_string(),
+        args: Vec::new(),
+        env: None,
+        cwd: None,
+      },
+      enabled: true,
+      required,
+      startup_timeout_sec: None,
+      tool_timeout_sec: None,
+      enabled_tools: None,
+      disabled_tools: None,
+      allow_sampling: false,
+    }
+  }
+
+  #[tokio::test]
+  async fn optional_servers_connect_on_first_use_and_retry_a_failed_first_connect() {
+    let config = McpConfig {
+      servers: HashMap::from([("lazy".to_string(), unreachable_server(false))]),
+    };
+    let manager = McpConnectionManager::new(
+      &config,
+      Path::new("/work"),
+      &SandboxPolicy::DangerFullAccess,
+    )
+    .await
+    .expect("manager");
+    assert!(manager.statuses.borrow().is_empty());
+    assert!(
+      manager
+        .supervisors
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .is_empty()
+    );
+
+    let (tx_event, mut rx_event) = mpsc::channel(16);
+    manager.attach_event_target(tx_event);
+    manager.wait_for_startup().await;
+    assert!(matches!(
+      manager.statuses.borrow().get("lazy"),
+      Some(McpStartupStatus::Failed { .. })
+    ));
+
+    // A failed first connect is retried after the backoff.
+    let mut updates = Vec::new();
+    time::timeout(Duration::from_secs(10), async {
+      while updates.len() < 3 {
+        if let Some(EventMsg::McpStartupUpdate(update)) = rx_event.recv().await {
+          updates.push(update.status);
+        }
+      }
+    })
+    .await
+    .expect("first connect retried");
+    assert!(matches!(
+      updates.as_slice(),
+      [
+        McpStartupStatus::Starting,
+        McpStartupStatus::Failed { .. },
+        McpStartupStatus::Starting,
+      ]
+    ));
+  }
+
+  #[tokio::test]
+  async fn required_servers_connect_before_new_returns() {
+    let config = McpConfig {
+      servers: HashMap::from([("needed".to_string(), unreachable_server(true))]),
+    };
+    let err = McpConnectionManager::new(
+      &config,
+      Path::new("/work"),
+      &SandboxPolicy::DangerFullAccess,
+    )
+    .await
+    .err()
+    .expect("required server fails");
+    assert!(err.to_string().contains("required MCP server `needed`"));
+  }
 }
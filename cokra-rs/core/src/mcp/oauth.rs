@@ -0,0 +1,475 @@
+//! MCP authorization for streamable-HTTP servers (`cokra mcp login`).
+//!
+//! Follows the MCP authorization spec: protected-resource metadata
+//! (RFC 9728) names the authorization server, its metadata (RFC 8414) gives
+//! the endpoints, the client registers itself dynamically (RFC 7591) and the
+//! user authorizes with PKCE through a loopback redirect. Tokens live in the
+//! credential store under `mcp:<server>`; `rmcp-client` refreshes them.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context as _;
+use anyhow::Result;
+use anyhow::anyhow;
+use cokra_rmcp_client::OAuthSession;
+use cokra_rmcp_client::OAuthTokens;
+use reqwest::Url;
+use serde::Deserialize;
+use serde_json::json;
+use tokio_util::sync::CancellationToken;
+
+use crate::model::auth::CredentialStorage;
+use crate::model::auth::Credentials;
+use crate::model::auth::FileCredentialStorage;
+use crate::model::auth::StoredCredentials;
+use crate::model::oauth_connect::LoopbackCallback;
+use crate::model::oauth_connect::generate_verifier;
+use crate::model::oauth_connect::pkce_challenge;
+
+const CREDENTIAL_PREFIX: &str = "mcp:";
+const CALLBACK_PATH: &str = "/callback";
+const CLIENT_NAME: &str = "Cokra";
+/// How long `login` waits for the browser to come back to the loopback
+/// redirect before giving up.
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Default, Deserialize)]
+struct ProtectedResourceMetadata {
+  #[serde(default)]
+  authorization_servers: Vec<String>,
+  #[serde(default)]
+  scopes_supported: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AuthorizationServerMetadata {
+  authorization_endpoint: String,
+  token_endpoint: String,
+  #[serde(default)]
+  registration_endpoint: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClientRegistration {
+  client_id: String,
+  #[serde(default)]
+  client_secret: Option<String>,
+}
+
+/// Run the authorization flow for `server_name` and store its tokens.
+///
+/// `show_authorize_url` receives the URL the user has to open; the call
+/// returns once the browser is redirected back and the code is exchanged,
+/// or fails after [`LOGIN_TIMEOUT`] without a redirect.
+pub async fn login(
+  server_name: &str,
+  url: &str,
+  show_authorize_url: impl FnOnce(&str),
+) -> Result<()> {
+  let http = reqwest::Client::new();
+  let server_url = Url::parse(url).with_context(|| format!("invalid MCP server url `{url}`"))?;
+
+  let resource_metadata = discover_protected_resource(&http, &server_url).await;
+  let issuer = match resource_metadata.authorization_servers.first() {
+    Some(issuer) => {
+      Url::parse(issuer).with_context(|| format!("invalid authorization server `{issuer}`"))?
+    }
+    // Servers predating RFC 9728 act as their own authorization server.
+    None => origin(&server_url),
+  };
+  let metadata = discover_authorization_server(&http, &issuer).await?;
+  let registration_endpoint = metadata.registration_endpoint.as_deref().ok_or_else(|| {
+    anyhow!(
+      "the authorization server for `{server_name}` does not support dynamic client registration"
+    )
+  })?;
+
+  let callback = LoopbackCallback::bind(CALLBACK_PATH).await?;
+  let redirect_uri = callback.redirect_uri();
+  let client = register_client(&http, registration_endpoint, &redirect_uri).await?;
+
+  let verifier = generate_verifier();
+  let state = uuid::Uuid::new_v4().simple().to_string();
+  let mut authorize_url = Url::parse(&metadata.authorization_endpoint).with_context(|| {
+    format!(
+      "invalid authorization endpoint `{}`",
+      metadata.authorization_endpoint
+    )
+  })?;
+  {
+    let mut query = authorize_url.query_pairs_mut();
+    query
+      .append_pair("response_type", "code")
+      .append_pair("client_id", &client.client_id)
+      .append_pair("redirect_uri", &redirect_uri)
+      .append_pair("code_challenge", &pkce_challenge(&verifier))
+      .append_pair("code_challenge_method", "S256")
+      .append_pair("state", &state)
+      .append_pair("resource", url);
+    if !resource_metadata.scopes_supported.is_empty() {
+      query.append_pair("scope", &resource_metadata.scopes_supported.join(" "));
+    }
+  }
+  show_authorize_url(authorize_url.as_str());
+
+  let (code, returned_state) = tokio::time::timeout(
+    LOGIN_TIMEOUT,
+    callback.wait(CancellationToken::new()),
+  )
+  .await
+  .map_err(|_| {
+    anyhow!(
+      "timed out after {}s waiting for the authorization redirect; please retry `cokra mcp login`",
+      LOGIN_TIMEOUT.as_secs()
+    )
+  })??;
+  if returned_state.as_deref() != Some(state.as_str()) {
+    return Err(anyhow!(
+      "OAuth state mismatch; please retry `cokra mcp login`"
+    ));
+  }
+
+  let mut form = vec![
+    ("grant_type", "authorization_code"),
+    ("code", code.as_str()),
+    ("redirect_uri", redirect_uri.as_str()),
+    ("client_id", client.client_id.as_str()),
+    ("code_verifier", verifier.as_str()),
+    ("resource", url),
+  ];
+  if let Some(secret) = &client.client_secret {
+    form.push(("client_secret", secret));
+  }
+  let grant = cokra_rmcp_client::request_token(&http, &metadata.token_endpoint, &form).await?;
+
+  McpTokenStore::default_store()?
+    .save(
+      server_name,
+      &OAuthTokens {
+        expires_at: grant.expires_at(),
+        access_token: grant.access_token,
+        refresh_token: grant.refresh_token,
+        token_endpoint: metadata.token_endpoint,
+        client_id: client.client_id,
+        client_secret: client.client_secret,
+        resource: Some(url.to_string()),
+      },
+    )
+    .await
+}
+
+/// An OAuth session for `server_name` if `cokra mcp login` stored tokens for
+/// it. Refreshed tokens are written back to the credential store.
+pub(super) async fn stored_session(server_name: &str) -> Option<Arc<OAuthSession>> {
+  let store = McpTokenStore::default_store().ok()?;
+  let tokens = match store.load(server_name).await {
+    Ok(tokens) => tokens?,
+    Err(err) => {
+      tracing::warn!("failed to load OAuth tokens for MCP server `{server_name}`: {err:#}");
+      return None;
+    }
+  };
+  let server_name = server_name.to_string();
+  let on_refreshed: cokra_rmcp_client::OnTokensRefreshed = Arc::new(move |tokens: OAuthTokens| {
+    let store = store.clone();
+    let server_name = server_name.clone();
+    Box::pin(async move {
+      if let Err(err) = store.save(&server_name, &tokens).await {
+        tracing::warn!("failed to store refreshed OAuth tokens for `{server_name}`: {err:#}");
+      }
+    })
+  });
+  Some(Arc::new(OAuthSession::new(tokens, Some(on_refreshed))))
+}
+
+/// MCP OAuth tokens kept in the shared credential store.
+#[derive(Clone)]
+pub struct McpTokenStore {
+  storage: Arc<dyn CredentialStorage>,
+}
+
+impl McpTokenStore {
+  pub fn new(storage: Arc<dyn CredentialStorage>) -> Self {
+    Self { storage }
+  }
+
+  pub fn default_store() -> Result<Self> {
+    Ok(Self::new(Arc::new(
+      FileCredentialStorage::default_storage()?
+    )))
+  }
+
+  pub async fn load(&self, server_name: &str) -> Result<Option<OAuthTokens>> {
+    let stored = self.storage.load(&credential_key(server_name)).await?;
+    Ok(stored.and_then(|stored| tokens_from_stored(&stored)))
+  }
+
+  pub async fn save(&self, server_name: &str, tokens: &OAuthTokens) -> Result<()> {
+    self
+      .storage
+      .save(tokens_to_stored(server_name, tokens))
+      .await?;
+    Ok(())
+  }
+
+  /// Forget the tokens for `server_name`; returns whether any were stored.
+  pub async fn remove(&self, server_name: &str) -> Result<bool> {
+    let key = credential_key(server_name);
+    if self.storage.load(&key).await?.is_none() {
+      return Ok(false);
+    }
+    self.storage.delete(&key).await?;
+    Ok(true)
+  }
+}
+
+fn credential_key(server_name: &str) -> String {
+  format!("{CREDENTIAL_PREFIX}{server_name}")
+}
+
+/// `Credentials::OAuth` has no notion of a missing refresh token or expiry,
+/// so those are stored as an empty string and `u64::MAX`.
+fn tokens_to_stored(server_name: &str, tokens: &OAuthTokens) -> StoredCredentials {
+  StoredCredentials::new(
+    credential_key(server_name),
+    Credentials::OAuth {
+      access_token: tokens.access_token.clone(),
+      refresh_token: tokens.refresh_token.clone().unwrap_or_default(),
+      expires_at: tokens.expires_at.unwrap_or(u64::MAX),
+      account_id: None,
+      enterprise_url: None,
+    },
+  )
+  .with_metadata(json!({
+    "token_endpoint": tokens.token_endpoint,
+    "client_id": tokens.client_id,
+    "client_secret": tokens.client_secret,
+    "resource": tokens.resource,
+  }))
+}
+
+fn tokens_from_stored(stored: &StoredCredentials) -> Option<OAuthTokens> {
+  let Credentials::OAuth {
+    access_token,
+    refresh_token,
+    expires_at,
+    ..
+  } = &stored.credentials
+  else {
+    return None;
+  };
+  let metadata_str = |key: &str| {
+    stored
+      .metadata
+      .get(key)
+      .and_then(serde_json::Value::as_str)
+      .map(ToString::to_string)
+  };
+  Some(OAuthTokens {
+    access_token: access_token.clone(),
+    refresh_token: Some(refresh_token.clone()).filter(|token| !token.is_empty()),
+    expires_at: Some(*expires_at).filter(|expires_at| *expires_at != u64::MAX),
+    token_endpoint: metadata_str("token_endpoint")?,
+    client_id: metadata_str("client_id")?,
+    client_secret: metadata_str("client_secret"),
+    resource: metadata_str("resource"),
+  })
+}
+
+/// Protected-resource metadata, found through the `WWW-Authenticate` header
+/// of an unauthenticated request or at the well-known locations. Empty when
+/// the server publishes none.
+async fn discover_protected_resource(
+  http: &reqwest::Client,
+  server_url: &Url,
+) -> ProtectedResourceMetadata {
+  let mut candidates = Vec::new();
+  if let Ok(response) = http.get(server_url.clone()).send().await
+    && let Some(url) = response
+      .headers()
+      .get(reqwest::header::WWW_AUTHENTICATE)
+      .and_then(|value| value.to_str().ok())
+      .and_then(resource_metadata_url)
+      .and_then(|url| Url::parse(&url).ok())
+  {
+    candidates.push(url);
+  }
+  candidates.extend(well_known_urls(server_url, "oauth-protected-resource"));
+
+  for candidate in candidates {
+    if let Some(metadata) = fetch_json(http, &candidate).await {
+      return metadata;
+    }
+  }
+  ProtectedResourceMetadata::default()
+}
+
+async fn discover_authorization_server(
+  http: &reqwest::Client,
+  issuer: &Url,
+) -> Result<AuthorizationServerMetadata> {
+  let candidates = well_known_urls(issuer, "oauth-authorization-server")
+    .into_iter()
+    .chain(well_known_urls(issuer, "openid-configuration"));
+  for candidate in candidates {
+    if let Some(metadata) = fetch_json(http, &candidate).await {
+      return Ok(metadata);
+    }
+  }
+  // Servers without metadata use the default endpoint paths.
+  let base = origin(issuer);
+  let endpoint = |path: &str| base.join(path).map(|url| url.to_string());
+  Ok(AuthorizationServerMetadata {
+    authorization_endpoint: endpoint("authorize")?,
+    token_endpoint: endpoint("token")?,
+    registration_endpoint: Some(endpoint("register")?),
+  })
+}
+
+async fn register_client(
+  http: &reqwest::Client,
+  registration_endpoint: &str,
+  redirect_uri: &str,
+) -> Result<ClientRegistration> {
+  let response = http
+    .post(registration_endpoint)
+    .json(&json!({
+      "client_name": CLIENT_NAME,
+      "redirect_uris": [redirect_uri],
+      "grant_types": ["authorization_code", "refresh_token"],
+      "response_types": ["code"],
+      "token_endpoint_auth_method": "none",
+    }))
+    .send()
+    .await
+    .with_context(|| format!("client registration at {registration_endpoint} failed"))?;
+  let status = response.status();
+  if !status.is_success() {
+    let body = response.text().await.unwrap_or_default();
+    return Err(anyhow!(
+      "client registration at {registration_endpoint} returned {status}: {}",
+      body.trim()
+    ));
+  }
+  response
+    .json()
+    .await
+    .with_context(|| format!("invalid client registration from {registration_endpoint}"))
+}
+
+async fn fetch_json<T: serde::de::DeserializeOwned>(
+  http: &reqwest::Client,
+  url: &Url,
+) -> Option<T> {
+  let response = http.get(url.clone()).send().await.ok()?;
+  if !response.status().is_success() {
+    return None;
+  }
+  response.json().await.ok()
+}
+
+/// `/.well-known/<suffix>` locations for `url`: with the path appended after
+/// the suffix first (RFC 8414 §3.1), then at the root.
+fn well_known_urls(url: &Url, suffix: &str) -> Vec<Url> {
+  let base = origin(url);
+  let path = url.path().trim_end_matches('/');
+  let mut paths = Vec::new();
+  if !path.is_empty() {
+    paths.push(format!("/.well-known/{suffix}{path}"));
+  }
+  paths.push(format!("/.well-known/{suffix}"));
+  paths
+    .into_iter()
+    .filter_map(|path| base.join(&path).ok())
+    .collect()
+}
+
+fn origin(url: &Url) -> Url {
+  let mut origin = url.clone();
+  origin.set_path("/");
+  origin.set_query(None);
+  origin.set_fragment(None);
+  origin
+}
+
+/// The `resource_metadata` parameter of a `WWW-Authenticate: Bearer` header.
+fn resource_metadata_url(www_authenticate: &str) -> Option<String> {
+  let (_, rest) = www_authenticate.split_once("resource_metadata=")?;
+  let value = match rest.strip_prefix('"') {
+    Some(quoted) => quoted.split('"').next()?,
+    None => rest.split([',', ' ']).next()?,
+  };
+  (!value.is_empty()).then(|| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+  use crate::model::auth::MemoryCredentialStorage;
+
+  fn tokens() -> OAuthTokens {
+    OAuthTokens {
+      access_token: "access".to_string(),
+      refresh_token: None,
+      expires_at: Some(1_900_000_000),
+      token_endpoint: "https://auth.example.com/token".to_string(),
+      client_id: "client".to_string(),
+      client_secret: None,
+      resource: Some("https://mcp.example.com/mcp".to_string()),
+    }
+  }
+
+  #[test]
+  fn well_known_urls_try_the_path_before_the_root() {
+    let url = Url::parse("https://mcp.example.com/tenant/mcp/").unwrap();
+    let urls = well_known_urls(&url, "oauth-protected-resource")
+      .into_iter()
+      .map(String::from)
+      .collect::<Vec<_>>();
+    assert_eq!(
+      urls,
+      vec![
+        "https://mcp.example.com/.well-known/oauth-protected-resource/tenant/mcp",
+        "https://mcp.example.com/.well-known/oauth-protected-resource",
+      ]
+    );
+  }
+
+  #[test]
+  fn resource_metadata_is_read_from_www_authenticate() {
+    assert_eq!(
+      resource_metadata_url(
+        r#"Bearer error="invalid_token", resource_metadata="https://mcp.example.com/.well-known/oauth-protected-resource""#
+      ),
+      Some("https://mcp.example.com/.well-known/oauth-protected-resource".to_string())
+    );
+    assert_eq!(
+      resource_metadata_url("Bearer resource_metadata=https://a.example/rm, scope=x"),
+      Some("https://a.example/rm".to_string())
+    );
+    assert_eq!(resource_metadata_url(r#"Bearer realm="mcp""#), None);
+  }
+
+  #[tokio::test]
+  async fn token_store_round_trips_optional_fields() {
+    let store = McpTokenStore::new(Arc::new(MemoryCredentialStorage::new()));
+    store.save("docs", &tokens()).await.unwrap();
+    assert_eq!(store.load("docs").await.unwrap(), Some(tokens()));
+
+    let rotated = OAuthTokens {
+      refresh_token: Some("refresh".to_string()),
+      expires_at: None,
+      client_secret: Some("secret".to_string()),
+      ..tokens()
+    };
+    store.save("docs", &rotated).await.unwrap();
+    assert_eq!(store.load("docs").await.unwrap(), Some(rotated));
+
+    assert!(store.remove("docs").await.unwrap());
+    assert!(!store.remove("docs").await.unwrap());
+    assert_eq!(store.load("docs").await.unwrap(), None);
+  }
+}
@@ -0,0 +1,220 @@
+//! Checks a tool's `structuredContent` against its declared `outputSchema`.
+//!
+//! This covers the JSON Schema keywords MCP servers use in practice: `type`,
+//! `enum`, `const`, `properties`, `required`, `additionalProperties`,
+//! `items`, `anyOf`, `oneOf` and `allOf`. Unknown keywords are ignored rather
+//! than rejected, so a schema this does not understand never fails a call.
+
+use serde_json::Map;
+use serde_json::Value;
+
+/// Report at most this many mismatches; the first few say enough.
+const MAX_ERRORS: usize = 5;
+
+/// `Ok` when `value` satisfies `schema`, otherwise the mismatches, one per
+/// line, each prefixed with the JSON path where it was found.
+pub(super) fn validate(schema: &Value, value: &Value) -> Result<(), String> {
+  let mut errors = Vec::new();
+  check(schema, value, "$", &mut errors);
+  if errors.is_empty() {
+    return Ok(());
+  }
+  errors.truncate(MAX_ERRORS);
+  Err(errors.join("\n"))
+}
+
+fn check(schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+  let Some(schema) = schema.as_object() else {
+    // `true`/`false` schemas accept or reject everything.
+    if schema == &Value::Bool(false) {
+      errors.push(format!("{path}: no value is allowed here"));
+    }
+    return;
+  };
+
+  if let Some(expected) = schema.get("type")
+    && !type_matches(expected, value)
+  {
+    errors.push(format!(
+      "{path}: expected {}, got {}",
+      describe_type(expected),
+      json_type(value)
+    ));
+    return;
+  }
+  if let Some(allowed) = schema.get("enum").and_then(Value::as_array)
+    && !allowed.contains(value)
+  {
+    errors.push(format!(
+      "{path}: {value} is not one of {}",
+      Value::Array(allowed.clone())
+    ));
+  }
+  if let Some(expected) = schema.get("const")
+    && expected != value
+  {
+    errors.push(format!("{path}: expected {expected}, got {value}"));
+  }
+
+  match value {
+    Value::Object(object) => check_object(schema, object, path, errors),
+    Value::Array(items) => {
+      if let Some(item_schema) = schema.get("items") {
+        for (idx, item) in items.iter().enumerate() {
+          check(item_schema, item, &format!("{path}[{idx}]"), errors);
+        }
+      }
+    }
+    _ => {}
+  }
+
+  if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+    for sub in all {
+      check(sub, value, path, errors);
+    }
+  }
+  for keyword in ["anyOf", "oneOf"] {
+    if let Some(options) = schema.get(keyword).and_then(Value::as_array)
+      && !options.iter().any(|sub| validate(sub, value).is_ok())
+    {
+      errors.push(format!(
+        "{path}: matches none of the `{keyword}` alternatives"
+      ));
+    }
+  }
+}
+
+fn check_object(
+  schema: &Map<String, Value>,
+  object: &Map<String, Value>,
+  path: &str,
+  errors: &mut Vec<String>,
+) {
+  if let Some(required) = schema.get("required").and_then(Value::as_array) {
+    for name in required.iter().filter_map(Value::as_str) {
+      if !object.contains_key(name) {
+        errors.push(format!("{path}: missing required property `{name}`"));
+      }
+    }
+  }
+  let properties = schema.get("properties").and_then(Value::as_object);
+  let additional = schema.get("additionalProperties");
+  for (name, field) in object {
+    let field_path = format!("{path}.{name}");
+    match properties.and_then(|properties| properties.get(name)) {
+      Some(field_schema) => check(field_schema, field, &field_path, errors),
+      None => match additional {
+        Some(Value::Bool(false)) => {
+          errors.push(format!("{path}: unexpected property `{name}`"));
+        }
+        Some(additional) => check(additional, field, &field_path, errors),
+        None => {}
+      },
+    }
+  }
+}
+
+fn type_matches(expected: &Value, value: &Value) -> bool {
+  match expected {
+    Value::String(name) => single_type_matches(name, value),
+    Value::Array(names) => names
+      .iter()
+      .filter_map(Value::as_str)
+      .any(|name| single_type_matches(name, value)),
+    _ => true,
+  }
+}
+
+fn single_type_matches(name: &str, value: &Value) -> bool {
+  match name {
+    "null" => value.is_null(),
+    "boolean" => value.is_boolean(),
+    "object" => value.is_object(),
+    "array" => value.is_array(),
+    "string" => value.is_string(),
+    "number" => value.is_number(),
+    "integer" => {
+      value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+    }
+    _ => true,
+  }
+}
+
+fn describe_type(expected: &Value) -> String {
+  match expected {
+    Value::Array(names) => names
+      .iter()
+      .filter_map(Value::as_str)
+      .collect::<Vec<_>>()
+      .join(" or "),
+    other => other.as_str().unwrap_or("any").to_string(),
+  }
+}
+
+fn json_type(value: &Value) -> &'static str {
+  match value {
+    Value::Null => "null",
+    Value::Bool(_) => "boolean",
+    Value::Number(_) => "number",
+    Value::String(_) => "string",
+    Value::Array(_) => "array",
+    Value::Object(_) => "object",
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+  use serde_json::json;
+
+  use super::*;
+
+  fn weather_schema() -> Value {
+    json!({
+      "type": "object",
+      "properties": {
+        "temperature": { "type": "number" },
+        "unit": { "enum": ["celsius", "fahrenheit"] },
+        "readings": { "type": "array", "items": { "type": "integer" } },
+      },
+      "required": ["temperature", "unit"],
+      "additionalProperties": false,
+    })
+  }
+
+  #[test]
+  fn matching_content_passes() {
+    let value = json!({ "temperature": 21.5, "unit": "celsius", "readings": [20, 22] });
+    assert_eq!(validate(&weather_schema(), &value), Ok(()));
+  }
+
+  #[test]
+  fn mismatches_are_reported_with_their_path() {
+    let value = json!({ "temperature": "warm", "readings": [20, 1.5], "wind": 3 });
+    assert_eq!(
+      validate(&weather_schema(), &value),
+      Err(
+        [
+          "$: missing required property `unit`",
+          "$.readings[1]: expected integer, got number",
+          "$.temperature: expected number, got string",
+          "$: unexpected property `wind`",
+        ]
+        .join("\n")
+      )
+    );
+  }
+
+  #[test]
+  fn alternatives_and_unknown_keywords() {
+    let schema = json!({
+      "anyOf": [{ "type": "string" }, { "type": "null" }],
+      "x-vendor": { "ignored": true },
+    });
+    assert_eq!(validate(&schema, &json!(null)), Ok(()));
+    assert_eq!(
+      validate(&schema, &json!(3)),
+      Err("$: matches none of the `anyOf` alternatives".to_string())
+    );
+  }
+}
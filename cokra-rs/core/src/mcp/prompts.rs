@@ -0,0 +1,124 @@
+//! MCP prompts surfaced as `/mcp__<server>__<prompt>` slash commands.
+
+use cokra_protocol::CustomPrompt;
+use cokra_protocol::CustomPromptArgument;
+use rmcp::model::GetPromptResult;
+use rmcp::model::Prompt;
+use rmcp::model::PromptMessageContent;
+use rmcp::model::PromptMessageRole;
+use rmcp::model::ResourceContents;
+
+pub(super) fn custom_prompt(command: String, server_name: &str, prompt: &Prompt) -> CustomPrompt {
+  CustomPrompt {
+    id: command,
+    name: prompt.name.clone(),
+    description: prompt.description.clone().or_else(|| prompt.title.clone()),
+    server_name: Some(server_name.to_string()),
+    arguments: prompt
+      .arguments
+      .iter()
+      .flatten()
+      .map(|argument| CustomPromptArgument {
+        name: argument.name.clone(),
+        description: argument
+          .description
+          .clone()
+          .or_else(|| argument.title.clone()),
+        required: argument.required.unwrap_or(false),
+      })
+      .collect(),
+  }
+}
+
+/// Flatten the prompt's messages into one user message.
+///
+/// Assistant turns are kept as labelled text because a submission can only
+/// carry user input; binary content is replaced by a short placeholder.
+pub(super) fn render_prompt_messages(result: &GetPromptResult) -> String {
+  result
+    .messages
+    .iter()
+    .map(|message| {
+      let text = match &message.content {
+        PromptMessageContent::Text { text } => text.clone(),
+        PromptMessageContent::Image { image } => format!("[image: {}]", image.mime_type),
+        PromptMessageContent::Resource { resource } => match &resource.resource {
+          ResourceContents::TextResourceContents { uri, text, .. } => {
+            format!("<resource uri=\"{uri}\">\n{text}\n</resource>")
+          }
+          ResourceContents::BlobResourceContents { uri, .. } => format!("[resource: {uri}]"),
+        },
+        PromptMessageContent::ResourceLink { link } => format!("[resource: {}]", link.uri),
+      };
+      match message.role {
+        PromptMessageRole::User => text,
+        PromptMessageRole::Assistant => format!("Assistant: {text}"),
+      }
+    })
+    .filter(|text| !text.trim().is_empty())
+    .collect::<Vec<_>>()
+    .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+  use rmcp::model::AnnotateAble;
+  use rmcp::model::PromptArgument;
+  use rmcp::model::PromptMessage;
+
+  use super::*;
+
+  #[test]
+  fn prompt_arguments_carry_over() {
+    let prompt = Prompt::new(
+      "summarize",
+      Some("Summarize a document"),
+      Some(vec![PromptArgument {
+        name: "path".to_string(),
+        title: None,
+        description: Some("Document to read".to_string()),
+        required: Some(true),
+      }]),
+    );
+    assert_eq!(
+      custom_prompt("mcp__docs__summarize".to_string(), "docs", &prompt),
+      CustomPrompt {
+        id: "mcp__docs__summarize".to_string(),
+        name: "summarize".to_string(),
+        description: Some("Summarize a document".to_string()),
+        server_name: Some("docs".to_string()),
+        arguments: vec![CustomPromptArgument {
+          name: "path".to_string(),
+          description: Some("Document to read".to_string()),
+          required: true,
+        }],
+      }
+    );
+  }
+
+  #[test]
+  fn messages_render_as_one_user_message() {
+    let result = GetPromptResult {
+      description: None,
+      messages: vec![
+        PromptMessage::new_text(PromptMessageRole::User, "Review this diff."),
+        PromptMessage::new_text(PromptMessageRole::Assistant, "Which file first?"),
+        PromptMessage {
+          role: PromptMessageRole::User,
+          content: PromptMessageContent::Resource {
+            resource: rmcp::model::RawEmbeddedResource {
+              meta: None,
+              resource: ResourceContents::text("fn main() {}", "file:///main.rs"),
+            }
+            .no_annotation(),
+          },
+        },
+      ],
+    };
+    assert_eq!(
+      render_prompt_messages(&result),
+      "Review this diff.\n\nAssistant: Which file first?\n\n<resource uri=\"file:///main.rs\">\nfn main() {}\n</resource>"
+    );
+  }
+}
@@ -0,0 +1,472 @@
+//! Serve `sampling/createMessage` requests from opted-in MCP servers with the
+//! session's current model.
+//!
+//! Every request is approved by the user unless they chose "always" for that
+//! server earlier in the session, and its usage is charged to the session's
+//! cost ledger and reported as a `TokenCount` event like any other model
+//! request.
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+
+use cokra_protocol::EventMsg;
+use cokra_protocol::ReviewDecision;
+use cokra_protocol::TokenCountEvent;
+use cokra_rmcp_client::SendSampling;
+use futures::FutureExt;
+use rmcp::ErrorData as McpError;
+use rmcp::model::CreateMessageRequestParams;
+use rmcp::model::CreateMessageResult;
+use rmcp::model::Role;
+use rmcp::model::SamplingMessage;
+use rmcp::model::SamplingMessageContent;
+use tokio::sync::mpsc;
+
+use crate::model::ChatRequest;
+use crate::model::Message;
+use crate::model::ModelClient;
+use crate::model::cost::request_cost_usd;
+use crate::model::streaming::response_token_usage;
+use crate::session::BudgetStatus;
+use crate::session::Session;
+
+const SAMPLING_TOOL_NAME: &str = "mcp_sampling";
+const PROMPT_PREVIEW_CHARS: usize = 200;
+
+#[derive(Default)]
+pub(crate) struct McpSamplingRouter {
+  target: RwLock<Option<SamplingTarget>>,
+  always_allowed: Mutex<HashSet<String>>,
+}
+
+#[derive(Clone)]
+struct SamplingTarget {
+  session: Arc<Session>,
+  tx_event: mpsc::Sender<EventMsg>,
+  model_client: Arc<ModelClient>,
+  cwd: PathBuf,
+}
+
+impl McpSamplingRouter {
+  pub(crate) fn attach(
+    &self,
+    session: Arc<Session>,
+    tx_event: mpsc::Sender<EventMsg>,
+    model_client: Arc<ModelClient>,
+    cwd: PathBuf,
+  ) {
+    *self.target.write().unwrap_or_else(PoisonError::into_inner) = Some(SamplingTarget {
+      session,
+      tx_event,
+      model_client,
+      cwd,
+    });
+  }
+
+  pub(crate) async fn request(
+    &self,
+    server_name: String,
+    params: CreateMessageRequestParams,
+  ) -> Result<CreateMessageResult, McpError> {
+    let target = self
+      .target
+      .read()
+      .unwrap_or_else(PoisonError::into_inner)
+      .clone()
+      .ok_or_else(|| McpError::invalid_request("sampling is not available yet", None))?;
+    let messages = chat_messages(&params)?;
+
+    if let BudgetStatus::HardLimitReached { limit_usd, .. } =
+      target.session.cost_ledger().budget_status()
+    {
+      return Err(McpError::invalid_request(
+        format!("sampling refused: session budget of ${limit_usd:.2} is spent"),
+        None,
+      ));
+    }
+
+    let turn_id = target.session.active_turn_id().await;
+    let thread_id = target
+      .session
+      .thread_id()
+      .map(ToString::to_string)
+      .unwrap_or_default();
+    if !self.is_always_allowed(&server_name) {
+      let decision = target
+        .session
+        .request_exec_approval(
+          thread_id.clone(),
+          turn_id.clone().unwrap_or_default(),
+          format!("mcp_sampling#{server_name}#{}", uuid::Uuid::new_v4()),
+          SAMPLING_TOOL_NAME.to_string(),
+          approval_summary(&server_name, &params),
+          target.cwd.clone(),
+          Some(target.tx_event.clone()),
+        )
+        .await;
+      match decision {
+        ReviewDecision::Approved => {}
+        ReviewDecision::Always => {
+          self
+            .always_allowed
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(server_name.clone());
+        }
+        ReviewDecision::Denied => {
+          return Err(McpError::invalid_request(
+            "the user declined the sampling request",
+            None,
+          ));
+        }
+      }
+    }
+
+    let model = target
+      .session
+      .model_switch_state()
+      .await
+      .current_model
+      .ok_or_else(|| McpError::internal_error("no model is selected", None))?;
+    let response = target
+      .model_client
+      .chat(ChatRequest {
+        model: model.clone(),
+        messages,
+        temperature: params.temperature,
+        max_tokens: Some(params.max_tokens),
+        stop: params.stop_sequences.clone(),
+        stream: false,
+        ..Default::default()
+      })
+      .await
+      .map_err(|err| McpError::internal_error(format!("sampling failed: {err}"), None))?;
+
+    let usage = response_token_usage(&response.usage);
+    let cost_usd = target
+      .model_client
+      .resolve_model_cost(&model)
+      .await
+      .map(|pricing| request_cost_usd(&pricing, &usage));
+    let totals = target
+      .session
+      .record_request_cost(turn_id.as_deref(), &usage, cost_usd)
+      .await;
+    let _ = target
+      .tx_event
+      .send(EventMsg::TokenCount(TokenCountEvent {
+        thread_id,
+        turn_id: turn_id.unwrap_or_default(),
+        input_tokens: usage.input_tokens.max(0),
+        cached_input_tokens: usage.cached_input_tokens.max(0),
+        output_tokens: usage.output_tokens.max(0),
+        reasoning_output_tokens: usage.reasoning_output_tokens.max(0),
+        total_tokens: usage.total_tokens.max(0),
+        cache_write_input_tokens: usage.cache_write_input_tokens.max(0),
+        // Not measured: a one-off completion is not part of the thread's context.
+        context_tokens: 0,
+        cost_usd,
+        turn_cost_usd: totals.turn_cost_usd,
+        thread_cost_usd: totals.thread_cost_usd,
+        session_cost_usd: totals.session_cost_usd,
+      }))
+      .await;
+
+    let choice = response.choices.into_iter().next();
+    let stop_reason = choice
+      .as_ref()
+      .and_then(|choice| choice.finish_reason.as_deref())
+      .map(|reason| match reason {
+        "length" | "max_tokens" => CreateMessageResult::STOP_REASON_END_MAX_TOKEN.to_string(),
+        "stop" | "end_turn" => CreateMessageResult::STOP_REASON_END_TURN.to_string(),
+        "stop_sequence" => CreateMessageResult::STOP_REASON_END_SEQUENCE.to_string(),
+        other => other.to_string(),
+      });
+    let text = choice
+      .and_then(|choice| choice.message.content)
+      .unwrap_or_default();
+    Ok(CreateMessageResult {
+      model,
+      stop_reason,
+      message: SamplingMessage::assistant_text(text),
+    })
+  }
+
+  pub(super) fn sender_for(self: &Arc<Self>, server_name: &str) -> SendSampling {
+    let router = Arc::clone(self);
+    let server_name = server_name.to_string();
+    Arc::new(move |params| {
+      let router = Arc::clone(&router);
+      let server_name = server_name.clone();
+      async move { router.request(server_name, params).await }.boxed()
+    })
+  }
+
+  fn is_always_allowed(&self, server_name: &str) -> bool {
+    self
+      .always_allowed
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner)
+      .contains(server_name)
+  }
+}
+
+/// Only text content can be forwarded; Cokra's chat API has no slot for
+/// images, audio or server-defined tools in a one-off completion.
+fn chat_messages(params: &CreateMessageRequestParams) -> Result<Vec<Message>, McpError> {
+  let mut messages = Vec::with_capacity(params.messages.len() + 1);
+  if let Some(system_prompt) = params
+    .system_prompt
+    .as_deref()
+    .filter(|prompt| !prompt.trim().is_empty())
+  {
+    messages.push(Message::System(system_prompt.to_string()));
+  }
+  for message in &params.messages {
+    let text = message
+      .content
+      .clone()
+      .into_vec()
+      .into_iter()
+      .map(|content| match content {
+        SamplingMessageContent::Text(text) => Ok(text.text),
+        _ => Err(McpError::invalid_params(
+          "only text content is supported in sampling requests",
+          None,
+        )),
+      })
+      .collect::<Result<Vec<_>, _>>()?
+      .join("\n");
+    messages.push(match message.role {
+      Role::User => Message::User(text),
+      Role::Assistant => Message::Assistant {
+        content: Some(text),
+        tool_calls: None,
+      },
+    });
+  }
+  Ok(messages)
+}
+
+/// One line for the approval prompt: who is asking and what the model is
+/// asked, cut to a readable length.
+fn approval_summary(server_name: &str, params: &CreateMessageRequestParams) -> String {
+  let prompt = params
+    .messages
+    .iter()
+    .rev()
+    .find(|message| message.role == Role::User)
+    .and_then(|message| {
+      message
+        .content
+        .clone()
+        .into_vec()
+        .into_iter()
+        .find_map(|content| match content {
+          SamplingMessageContent::Text(text) => Some(text.text),
+          _ => None,
+        })
+    })
+    .unwrap_or_default();
+  let prompt = prompt.split_whitespace().collect::<Vec<_>>().join(" ");
+  let preview = if prompt.chars().count() > PROMPT_PREVIEW_CHARS {
+    let cut = prompt
+      .chars()
+      .take(PROMPT_PREVIEW_CHARS)
+      .collect::<String>();
+    format!("{cut}…")
+  } else {
+    prompt
+  };
+  format!(
+    "`{server_name}` asks the model (up to {} tokens): {preview}",
+    params.max_tokens
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+  use crate::model::Usage;
+  use crate::test_support::ScriptedProvider;
+
+  async fn attached_router() -> (
+    Arc<McpSamplingRouter>,
+    Arc<Session>,
+    mpsc::Receiver<EventMsg>,
+  ) {
+    let model_client = ScriptedProvider::echoing()
+      .with_finish_reason("length")
+      .with_usage(Usage {
+        input_tokens: 7,
+        output_tokens: 3,
+        total_tokens: 10,
+        ..Default::default()
+      })
+      .into_model_client()
+      .await;
+    let session = Arc::new(Session::new());
+    session.track_model_selection("mock/default").await;
+    let (tx_event, rx_event) = mpsc::channel(8);
+    let router = Arc::new(McpSamplingRouter::default());
+    router.attach(
+      Arc::clone(&session),
+      tx_event,
+      model_client,
+      std::env::temp_dir(),
+    );
+    (router, session, rx_event)
+  }
+
+  fn summarize_request(text: &str) -> CreateMessageRequestParams {
+    CreateMessageRequestParams {
+      meta: None,
+      task: None,
+      messages: vec![SamplingMessage::user_text(text)],
+      model_preferences: None,
+      system_prompt: Some("Summarize the document.".to_string()),
+      include_context: None,
+      temperature: None,
+      max_tokens: 64,
+      stop_sequences: None,
+      metadata: None,
+      tools: None,
+      tool_choice: None,
+    }
+  }
+
+  async fn answer_approval(
+    session: &Session,
+    rx_event: &mut mpsc::Receiver<EventMsg>,
+    decision: ReviewDecision,
+  ) -> String {
+    let Some(EventMsg::ExecApprovalRequest(request)) = rx_event.recv().await else {
+      panic!("expected an approval request");
+    };
+    assert!(session.notify_exec_approval(&request.id, decision).await);
+    request.command
+  }
+
+  fn token_count(rx_event: &mut mpsc::Receiver<EventMsg>) -> TokenCountEvent {
+    match rx_event.try_recv() {
+      Ok(EventMsg::TokenCount(event)) => event,
+      other => panic!("expected a token count, got {other:?}"),
+    }
+  }
+
+  #[test]
+  fn text_messages_map_onto_chat_messages() {
+    let mut params = summarize_request("first draft");
+    params
+      .messages
+      .push(SamplingMessage::assistant_text("a summary"));
+    let messages = chat_messages(&params).expect("text converts");
+    assert_eq!(
+      serde_json::to_value(messages).unwrap(),
+      serde_json::to_value(vec![
+        Message::System("Summarize the document.".to_string()),
+        Message::User("first draft".to_string()),
+        Message::Assistant {
+          content: Some("a summary".to_string()),
+          tool_calls: None,
+        },
+      ])
+      .unwrap()
+    );
+
+    params.messages.push(SamplingMessage::new(
+      Role::User,
+      SamplingMessageContent::Image(rmcp::model::RawImageContent {
+        data: String::new(),
+        mime_type: "image/png".to_string(),
+        meta: None,
+      }),
+    ));
+    assert!(chat_messages(&params).is_err());
+  }
+
+  #[tokio::test]
+  async fn denied_request_never_reaches_the_model() {
+    let (router, session, mut rx_event) = attached_router().await;
+    let pending = tokio::spawn({
+      let router = Arc::clone(&router);
+      async move {
+        router
+          .request("docs".to_string(), summarize_request("the doc"))
+          .await
+      }
+    });
+
+    let command = answer_approval(&session, &mut rx_event, ReviewDecision::Denied).await;
+    assert_eq!(command, "`docs` asks the model (up to 64 tokens): the doc");
+    assert!(pending.await.unwrap().is_err());
+    assert_eq!(session.cost_ledger().session_cost().total_tokens, 0);
+  }
+
+  #[tokio::test]
+  async fn always_allow_skips_later_prompts_and_records_usage() {
+    let (router, session, mut rx_event) = attached_router().await;
+    let pending = tokio::spawn({
+      let router = Arc::clone(&router);
+      async move {
+        router
+          .request("docs".to_string(), summarize_request("the doc"))
+          .await
+      }
+    });
+    answer_approval(&session, &mut rx_event, ReviewDecision::Always).await;
+    let result = pending.await.unwrap().expect("sampling succeeds");
+    assert_eq!(result.model, "mock/default");
+    assert_eq!(
+      result.stop_reason.as_deref(),
+      Some(CreateMessageResult::STOP_REASON_END_MAX_TOKEN)
+    );
+    assert_eq!(result.message, SamplingMessage::assistant_text("the doc"));
+    assert_eq!(token_count(&mut rx_event).total_tokens, 10);
+
+    let second = router
+      .request("docs".to_string(), summarize_request("again"))
+      .await
+      .expect("no prompt the second time");
+    assert_eq!(second.message, SamplingMessage::assistant_text("again"));
+    assert_eq!(token_count(&mut rx_event).total_tokens, 10);
+    assert!(rx_event.try_recv().is_err());
+    assert_eq!(session.cost_ledger().session_cost().total_tokens, 20);
+  }
+
+  #[tokio::test]
+  async fn sampling_between_turns_keeps_the_last_turn_total() {
+    let (router, session, mut rx_event) = attached_router().await;
+    let usage = cokra_protocol::ResponseTokenUsage::default();
+    session
+      .record_request_cost(Some("turn-1"), &usage, Some(0.5))
+      .await;
+
+    let pending = tokio::spawn({
+      let router = Arc::clone(&router);
+      async move {
+        router
+          .request("docs".to_string(), summarize_request("the doc"))
+          .await
+      }
+    });
+    answer_approval(&session, &mut rx_event, ReviewDecision::Approved).await;
+    pending.await.unwrap().expect("sampling succeeds");
+    let event = token_count(&mut rx_event);
+    assert_eq!(event.turn_id, "");
+    assert_eq!(event.turn_cost_usd, 0.0);
+    assert_eq!(event.input_tokens, 7);
+    assert_eq!(event.output_tokens, 3);
+
+    let totals = session
+      .record_request_cost(Some("turn-1"), &usage, Some(0.25))
+      .await;
+    assert_eq!(totals.turn_cost_usd, 0.75);
+  }
+}
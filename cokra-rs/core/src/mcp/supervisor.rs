@@ -0,0 +1,324 @@
+//! Keeps one MCP server connected for the lifetime of its manager.
+
+use std::sync::Weak;
+use std::time::Duration;
+
+use cokra_protocol::McpStartupStatus;
+
+use super::McpConnectionManager;
+use super::connect_and_discover;
+
+/// Delay before the first retry; doubles after every failed attempt.
+const RECONNECT_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const RECONNECT_MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// Failed attempts in a row before the server is left failed.
+const RECONNECT_MAX_ATTEMPTS: u32 = 5;
+
+/// Connect `server_name`, then reconnect it with backoff whenever it drops.
+///
+/// A failed connect, first or not, is retried with the same backoff, so a
+/// server that was briefly unavailable comes back by itself. After
+/// [`RECONNECT_MAX_ATTEMPTS`] failures in a row it is left failed until
+/// restarted by hand. Only a weak reference is held while waiting, so
+/// dropping the manager (which aborts this task) also closes the connection.
+pub(super) async fn supervise(manager: Weak<McpConnectionManager>, server_name: String) {
+  let mut first_attempt = true;
+  let mut reconnects = 0;
+  loop {
+    let (server_config, callbacks) = {
+      let Some(this) = manager.upgrade() else {
+        return;
+      };
+      let Some(server_config) = this.configs.get(&server_name).cloned() else {
+        return;
+      };
+      let callbacks = this.callbacks_for(&manager, &server_name, &server_config);
+      if !first_attempt {
+        this.set_status(&server_name, McpStartupStatus::Starting);
+      }
+      (server_config, callbacks)
+    };
+
+    let connected = connect_and_discover(&server_name, &server_config, callbacks).await;
+
+    let client = {
+      let Some(this) = manager.upgrade() else {
+        return;
+      };
+      let client = match connected {
+        Ok((server, discovery)) => {
+          let client = server.client.clone();
+          this.install(&server_name, &server_config, server, discovery);
+          this.set_status(&server_name, McpStartupStatus::Ready);
+          Some(client)
+        }
+        Err(err) => {
+          tracing::warn!("MCP server `{server_name}` failed to connect: {err:#}");
+          this.set_status(
+            &server_name,
+            McpStartupStatus::Failed {
+              error: format!("{err:#}"),
+            },
+          );
+          None
+        }
+      };
+      if first_attempt {
+        this.finish_startup_attempt(&server_name);
+      }
+      client
+    };
+
+    match client {
+      Some(client) => {
+        reconnects = 0;
+        client.wait_closed().await;
+        drop(client);
+        let Some(this) = manager.upgrade() else {
+          return;
+        };
+        tracing::warn!("MCP server `{server_name}` disconnected; reconnecting");
+        this.uninstall(&server_name);
+        this.set_status(
+          &server_name,
+          McpStartupStatus::Failed {
+            error: "connection closed; reconnecting".to_string(),
+          },
+        );
+      }
+      None if reconnects >= RECONNECT_MAX_ATTEMPTS => return,
+      None => {}
+    }
+    first_attempt = false;
+    tokio::time::sleep(reconnect_backoff(reconnects)).await;
+    reconnects += 1;
+  }
+}
+
+fn reconnect_backoff(previous_attempts: u32) -> Duration {
+  RECONNECT_INITIAL_BACKOFF
+    .saturating_mul(2u32.saturating_pow(previous_attempts))
+    .min(RECONNECT_MAX_BACKOFF)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+  use std::path::Path;
+  use std::sync::Arc;
+  use std::sync::Mutex;
+  use std::sync::PoisonError;
+
+  use cokra_config::McpConfig;
+  use cokra_config::McpServerConfig;
+  use cokra_config::McpServerTransportConfig;
+  use cokra_protocol::SandboxPolicy;
+  use pretty_assertions::assert_eq;
+  use rmcp::ErrorData as McpError;
+  use rmcp::RoleServer;
+  use rmcp::ServerHandler;
+  use rmcp::ServiceExt;
+  use rmcp::model::ListToolsResult;
+  use rmcp::model::PaginatedRequestParams;
+  use rmcp::model::ServerCapabilities;
+  use rmcp::model::ServerInfo;
+  use rmcp::model::Tool;
+  use rmcp::service::RequestContext;
+  use rmcp::service::RunningService;
+  use tokio::sync::mpsc;
+  use tokio::time;
+
+  use super::*;
+  use crate::tools::registry::ToolRegistry;
+
+  /// A server run in this process whose tool list a test can change.
+  #[derive(Clone, Default)]
+  struct ToolServer {
+    tools: Arc<Mutex<Vec<Tool>>>,
+  }
+
+  impl ServerHandler for ToolServer {
+    fn get_info(&self) -> ServerInfo {
+      ServerInfo {
+        capabilities: ServerCapabilities::builder()
+          .enable_tools()
+          .enable_tool_list_changed()
+          .build(),
+        ..ServerInfo::default()
+      }
+    }
+
+    async fn list_tools(
+      &self,
+      _request: Option<PaginatedRequestParams>,
+      _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+      Ok(ListToolsResult {
+        tools: self
+          .tools
+          .lock()
+          .unwrap_or_else(PoisonError::into_inner)
+          .clone(),
+        ..ListToolsResult::default()
+      })
+    }
+  }
+
+  type Connections = mpsc::UnboundedReceiver<RunningService<RoleServer, ToolServer>>;
+
+  fn tool(name: &'static str) -> Tool {
+    let schema = serde_json::json!({ "type": "object", "properties": {} });
+    let serde_json::Value::Object(schema) = schema else {
+      unreachable!("schema is an object");
+    };
+    Tool::new(name, "test tool", Arc::new(schema))
+  }
+
+  /// Serve `server` under a fresh name; every connection made to it comes
+  /// out of the returned receiver.
+  fn serve(server: ToolServer) -> (String, Connections) {
+    let server_name = format!("local-{}", uuid::Uuid::new_v4().simple());
+    let (connections_tx, connections) = mpsc::unbounded_channel();
+    super::super::serve_in_process(
+      &server_name,
+      Arc::new(move |transport| {
+        let server = server.clone();
+        let connections_tx = connections_tx.clone();
+        tokio::spawn(async move {
+          let running = server.serve(transport).await.expect("serve");
+          let _ = connections_tx.send(running);
+        });
+      }),
+    );
+    (server_name, connections)
+  }
+
+  async fn connected_manager(server_name: &str) -> (Arc<McpConnectionManager>, Arc<ToolRegistry>) {
+    let server_config = McpServerConfig {
+      // Replaced by the in-process server registered under this name.
+      transport: McpServerTransportConfig::Stdio {
+        command: "cokra-test-in-process".to_string(),
+        args: Vec::new(),
+        env: None,
+        cwd: None,
+      },
+      enabled: true,
+      required: false,
+      startup_timeout_sec: None,
+      tool_timeout_sec: None,
+      enabled_tools: None,
+      disabled_tools: None,
+      allow_sampling: false,
+    };
+    let config = McpConfig {
+      servers: HashMap::from([(server_name.to_string(), server_config)]),
+    };
+    let manager = McpConnectionManager::new(
+      &config,
+      Path::new("/work"),
+      &SandboxPolicy::DangerFullAccess,
+    )
+    .await
+    .expect("manager");
+    let registry = Arc::new(ToolRegistry::new());
+    manager.attach_registry(&registry);
+    manager.wait_for_startup().await;
+    (manager, registry)
+  }
+
+  async fn wait_for_status(
+    manager: &McpConnectionManager,
+    server_name: &str,
+    matches: impl Fn(&McpStartupStatus) -> bool,
+  ) -> McpStartupStatus {
+    let mut statuses = manager.statuses.subscribe();
+    let statuses = time::timeout(
+      Duration::from_secs(10),
+      statuses.wait_for(|statuses| statuses.get(server_name).is_some_and(&matches)),
+    )
+    .await
+    .expect("status in time")
+    .expect("manager alive");
+    statuses[server_name].clone()
+  }
+
+  #[tokio::test]
+  async fn closed_transport_uninstalls_tools_until_the_server_is_back() {
+    let server = ToolServer::default();
+    server
+      .tools
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner)
+      .push(tool("echo"));
+    let (server_name, mut connections) = serve(server);
+    let (manager, registry) = connected_manager(&server_name).await;
+    let echo = format!("mcp__{server_name}__echo");
+    assert_eq!(manager.tool_names(), vec![echo.clone()]);
+    assert!(registry.get_spec(&echo).is_some());
+
+    let first = connections.recv().await.expect("first connection");
+    first.cancel().await.expect("close the connection");
+    let status = wait_for_status(&manager, &server_name, |status| {
+      matches!(status, McpStartupStatus::Failed { .. })
+    })
+    .await;
+    assert!(
+      matches!(&status, McpStartupStatus::Failed { error } if error == "connection closed; reconnecting"),
+      "{status:?}"
+    );
+    assert!(manager.tool_names().is_empty());
+    assert!(registry.get_spec(&echo).is_none());
+
+    wait_for_status(&manager, &server_name, |status| {
+      matches!(status, McpStartupStatus::Ready)
+    })
+    .await;
+    assert!(connections.recv().await.is_some());
+    assert_eq!(manager.tool_names(), vec![echo.clone()]);
+    assert!(registry.get_spec(&echo).is_some());
+  }
+
+  #[tokio::test]
+  async fn tool_list_changed_relists_the_servers_tools() {
+    let server = ToolServer::default();
+    server
+      .tools
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner)
+      .push(tool("echo"));
+    let tools = Arc::clone(&server.tools);
+    let (server_name, mut connections) = serve(server);
+    let (manager, registry) = connected_manager(&server_name).await;
+    let connection = connections.recv().await.expect("connection");
+
+    *tools.lock().unwrap_or_else(PoisonError::into_inner) = vec![tool("grep")];
+    connection
+      .peer()
+      .notify_tool_list_changed()
+      .await
+      .expect("notify");
+    let grep = format!("mcp__{server_name}__grep");
+    time::timeout(Duration::from_secs(5), async {
+      while manager.tool_names() != vec![grep.clone()] {
+        time::sleep(Duration::from_millis(20)).await;
+      }
+    })
+    .await
+    .expect("tools re-listed");
+    assert!(registry.get_spec(&grep).is_some());
+    assert!(
+      registry
+        .get_spec(&format!("mcp__{server_name}__echo"))
+        .is_none()
+    );
+  }
+
+  #[test]
+  fn backoff_doubles_up_to_the_cap() {
+    let delays = (0..7).map(reconnect_backoff).collect::<Vec<_>>();
+    assert_eq!(
+      delays,
+      [1, 2, 4, 8, 16, 30, 30].map(Duration::from_secs).to_vec()
+    );
+  }
+}
@@ -0,0 +1,609 @@
+//! Record/replay transport for provider HTTP traffic.
+//!
+//! In record mode each provider request is sent as usual and the exchange is
+//! written to `<dir>/NNNN.json` (request and response head) plus
+//! `<dir>/NNNN.body` (the response bytes, e.g. an SSE stream). Credentials
+//! are redacted from both, including tokens in JSON and form bodies. Replay serves those files back in order without touching the
+//! network and fails on the first request that differs from the recording.
+//!
+//! Selection: `COKRA_CASSETTE=record|replay` with `COKRA_CASSETTE_DIR`, or the
+//! `[models.cassette]` config table. The environment wins.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::OnceLock;
+
+use reqwest::Client;
+use reqwest::Request;
+use reqwest::RequestBuilder;
+use reqwest::Response;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+
+pub use cokra_config::CassetteMode;
+
+use super::error::ModelError;
+use super::error::Result;
+
+const MODE_ENV: &str = "COKRA_CASSETTE";
+const DIR_ENV: &str = "COKRA_CASSETTE_DIR";
+const REDACTED: &str = "[REDACTED]";
+
+/// Headers that carry credentials or account identity.
+const SENSITIVE_HEADERS: &[&str] = &[
+  "authorization",
+  "proxy-authorization",
+  "x-api-key",
+  "api-key",
+  "x-goog-api-key",
+  "cookie",
+  "set-cookie",
+  "chatgpt-account-id",
+  "openai-organization",
+];
+
+/// Query parameters that carry credentials (Google passes `?key=`).
+const SENSITIVE_QUERY_PARAMS: &[&str] = &["key", "api_key", "access_token"];
+
+/// Body fields that carry credentials, e.g. in an OAuth token refresh.
+const SENSITIVE_BODY_KEYS: &[&str] = &[
+  "access_token",
+  "refresh_token",
+  "id_token",
+  "client_secret",
+  "api_key",
+];
+
+/// Top-level body keys that differ between otherwise identical runs.
+const VOLATILE_BODY_KEYS: &[&str] = &["prompt_cache_key", "user", "metadata"];
+
+/// Request half of a recorded interaction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+  pub method: String,
+  /// Path and query, without scheme or host, so a recording survives a
+  /// `base_url` change (e.g. a proxy or a local mock server).
+  pub path: String,
+  #[serde(default)]
+  pub headers: BTreeMap<String, String>,
+  #[serde(default)]
+  pub body: Option<Value>,
+}
+
+impl RecordedRequest {
+  fn from_request(request: &Request) -> Self {
+    let url = request.url();
+    let mut path = url.path().to_string();
+    let query: Vec<String> = url
+      .query_pairs()
+      .map(|(name, value)| {
+        if SENSITIVE_QUERY_PARAMS.contains(&name.as_ref()) {
+          format!("{name}={REDACTED}")
+        } else {
+          format!("{name}={value}")
+        }
+      })
+      .collect();
+    if !query.is_empty() {
+      path.push('?');
+      path.push_str(&query.join("&"));
+    }
+
+    let body = request
+      .body()
+      .and_then(|body| body.as_bytes())
+      .map(|bytes| match serde_json::from_slice(bytes) {
+        Ok(mut body) => {
+          redact_json(&mut body);
+          body
+        }
+        Err(_) => Value::String(redact_form(&String::from_utf8_lossy(bytes))),
+      });
+
+    Self {
+      method: request.method().to_string(),
+      path,
+      headers: redact_headers(request.headers()),
+      body,
+    }
+  }
+
+  /// Shape used for matching: headers are ignored and volatile body keys
+  /// are dropped.
+  fn normalized(&self) -> (String, String, Option<Value>) {
+    let body = self.body.clone().map(|mut body| {
+      if let Some(object) = body.as_object_mut() {
+        for key in VOLATILE_BODY_KEYS {
+          object.remove(*key);
+        }
+      }
+      body
+    });
+    (self.method.clone(), self.path.clone(), body)
+  }
+}
+
+/// Response head of a recorded interaction; the body lives next to it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedResponse {
+  pub status: u16,
+  #[serde(default)]
+  pub headers: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+  request: RecordedRequest,
+  response: RecordedResponse,
+}
+
+/// A directory of numbered provider HTTP exchanges.
+#[derive(Debug)]
+pub struct Cassette {
+  mode: CassetteMode,
+  dir: PathBuf,
+  next: Mutex<usize>,
+}
+
+impl Cassette {
+  pub fn new(mode: CassetteMode, dir: impl Into<PathBuf>) -> Self {
+    Self {
+      mode,
+      dir: dir.into(),
+      next: Mutex::new(0),
+    }
+  }
+
+  /// `COKRA_CASSETTE=record|replay`; the directory defaults to `cassettes`.
+  pub fn from_env() -> Option<Self> {
+    let mode = match std::env::var(MODE_ENV)
+      .ok()?
+      .trim()
+      .to_ascii_lowercase()
+      .as_str()
+    {
+      "record" => CassetteMode::Record,
+      "replay" => CassetteMode::Replay,
+      other => {
+        tracing::warn!("ignoring {MODE_ENV}={other}: expected `record` or `replay`");
+        return None;
+      }
+    };
+    let dir = std::env::var(DIR_ENV).unwrap_or_else(|_| "cassettes".to_string());
+    Some(Self::new(mode, dir))
+  }
+
+  pub fn from_config(config: &cokra_config::Config) -> Option<Self> {
+    let cassette = config.models.cassette.as_ref()?;
+    Some(Self::new(cassette.mode, cassette.dir.clone()))
+  }
+
+  pub fn mode(&self) -> CassetteMode {
+    self.mode
+  }
+
+  pub fn dir(&self) -> &Path {
+    &self.dir
+  }
+
+  /// Send `builder` through the cassette instead of straight to the network.
+  pub async fn send(&self, builder: RequestBuilder) -> Result<Response> {
+    let (client, request) = builder.build_split();
+    let request = request.map_err(ModelError::NetworkError)?;
+    let index = self.claim_index();
+    match self.mode {
+      CassetteMode::Record => self.record(index, client, request).await,
+      CassetteMode::Replay => self.replay(index, &request).await,
+    }
+  }
+
+  fn claim_index(&self) -> usize {
+    let mut next = self
+      .next
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let index = *next;
+    *next += 1;
+    index
+  }
+
+  fn head_path(&self, index: usize) -> PathBuf {
+    self.dir.join(format!("{index:04}.json"))
+  }
+
+  fn body_path(&self, index: usize) -> PathBuf {
+    self.dir.join(format!("{index:04}.body"))
+  }
+
+  // Tradeoff: the whole response is buffered before it is handed back, so a
+  // recorded stream arrives in one piece. Chunk timing is not something the
+  // parsers depend on, and buffering keeps the on-disk body byte-exact.
+  async fn record(&self, index: usize, client: Client, request: Request) -> Result<Response> {
+    let recorded_request = RecordedRequest::from_request(&request);
+    let response = client
+      .execute(request)
+      .await
+      .map_err(ModelError::NetworkError)?;
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = response.bytes().await.map_err(ModelError::NetworkError)?;
+
+    let interaction = Interaction {
+      request: recorded_request,
+      response: RecordedResponse {
+        status: status.as_u16(),
+        headers: redact_headers(&headers),
+      },
+    };
+    tokio::fs::create_dir_all(&self.dir).await.map_err(|err| {
+      ModelError::StreamError(format!("create cassette dir {}: {err}", self.dir.display()))
+    })?;
+    let head = serde_json::to_string_pretty(&interaction)?;
+    write_file(&self.head_path(index), head.as_bytes()).await?;
+    write_file(&self.body_path(index), &redact_response_body(&body)).await?;
+
+    let mut rebuilt = http::Response::new(body.to_vec());
+    *rebuilt.status_mut() = status;
+    *rebuilt.headers_mut() = headers;
+    Ok(Response::from(rebuilt))
+  }
+
+  async fn replay(&self, index: usize, request: &Request) -> Result<Response> {
+    let actual = RecordedRequest::from_request(request);
+    let head_path = self.head_path(index);
+    let head = tokio::fs::read_to_string(&head_path).await.map_err(|_| {
+      ModelError::CassetteMismatch(format!(
+        "no recorded interaction {} for {} {}",
+        head_path.display(),
+        actual.method,
+        actual.path
+      ))
+    })?;
+    let interaction: Interaction = serde_json::from_str(&head)?;
+
+    if interaction.request.normalized() != actual.normalized() {
+      return Err(ModelError::CassetteMismatch(format!(
+        "interaction {} differs from the live request\n--- recorded\n{}\n+++ actual\n{}",
+        head_path.display(),
+        describe(&interaction.request),
+        describe(&actual)
+      )));
+    }
+
+    let body_path = self.body_path(index);
+    let body = tokio::fs::read(&body_path).await.map_err(|err| {
+      ModelError::CassetteMismatch(format!("read {}: {err}", body_path.display()))
+    })?;
+
+    let mut builder = http::Response::builder().status(interaction.response.status);
+    for (name, value) in &interaction.response.headers {
+      builder = builder.header(name, value);
+    }
+    let response = builder
+      .body(body)
+      .map_err(|err| ModelError::CassetteMismatch(format!("{}: {err}", head_path.display())))?;
+    Ok(Response::from(response))
+  }
+}
+
+/// Process-wide cassette, resolved from the environment on first use unless
+/// [`install`] ran earlier.
+static ACTIVE: OnceLock<Option<Cassette>> = OnceLock::new();
+
+/// Pick the cassette for this process: `COKRA_CASSETTE` first, then
+/// `[models.cassette]`. See [`install_cassette`] for repeated calls.
+pub fn install(config: &cokra_config::Config) -> Result<()> {
+  install_cassette(Cassette::from_env().or_else(|| Cassette::from_config(config)))
+}
+
+/// Make `cassette` (or no cassette) the process-wide one.
+///
+/// The choice is fixed once made, by an earlier install or by the first
+/// provider request. Installing the same mode and directory again is a no-op;
+/// anything else fails instead of leaving the earlier choice silently in
+/// place.
+pub fn install_cassette(cassette: Option<Cassette>) -> Result<()> {
+  let wanted = cassette
+    .as_ref()
+    .map(|cassette| (cassette.mode, cassette.dir.clone()));
+  let mut fresh = false;
+  let active = ACTIVE.get_or_init(|| {
+    fresh = true;
+    cassette
+  });
+  let current = active
+    .as_ref()
+    .map(|cassette| (cassette.mode, cassette.dir.clone()));
+  if current != wanted {
+    return Err(ModelError::CassetteConflict(format!(
+      "{} requested, but {} is already in use",
+      describe_selection(wanted.as_ref()),
+      describe_selection(current.as_ref())
+    )));
+  }
+  if fresh && let Some(cassette) = active {
+    tracing::info!(
+      "provider HTTP cassette active: {:?} {}",
+      cassette.mode(),
+      cassette.dir().display()
+    );
+  }
+  Ok(())
+}
+
+fn describe_selection(selection: Option<&(CassetteMode, PathBuf)>) -> String {
+  match selection {
+    Some((mode, dir)) => format!("{mode:?} cassette {}", dir.display()),
+    None => "no cassette".to_string(),
+  }
+}
+
+pub fn active() -> Option<&'static Cassette> {
+  ACTIVE.get_or_init(Cassette::from_env).as_ref()
+}
+
+/// Send a provider request, going through the active cassette if there is one.
+pub async fn send(builder: RequestBuilder) -> Result<Response> {
+  match active() {
+    Some(cassette) => cassette.send(builder).await,
+    None => builder.send().await.map_err(ModelError::NetworkError),
+  }
+}
+
+/// `RequestBuilder::send` routed through [`send`].
+pub trait CassetteSend {
+  fn send_via_cassette(self) -> impl std::future::Future<Output = Result<Response>> + Send;
+}
+
+impl CassetteSend for RequestBuilder {
+  fn send_via_cassette(self) -> impl std::future::Future<Output = Result<Response>> + Send {
+    send(self)
+  }
+}
+
+fn redact_headers(headers: &reqwest::header::HeaderMap) -> BTreeMap<String, String> {
+  headers
+    .iter()
+    .map(|(name, value)| {
+      let name = name.as_str().to_ascii_lowercase();
+      let value = if SENSITIVE_HEADERS.contains(&name.as_str()) {
+        REDACTED.to_string()
+      } else {
+        String::from_utf8_lossy(value.as_bytes()).into_owned()
+      };
+      (name, value)
+    })
+    .collect()
+}
+
+/// Replace every credential-bearing field in `value`, at any depth.
+fn redact_json(value: &mut Value) {
+  match value {
+    Value::Object(object) => {
+      for (key, value) in object.iter_mut() {
+        if SENSITIVE_BODY_KEYS.contains(&key.as_str()) {
+          *value = Value::String(REDACTED.to_string());
+        } else {
+          redact_json(value);
+        }
+      }
+    }
+    Value::Array(items) => items.iter_mut().for_each(redact_json),
+    _ => {}
+  }
+}
+
+/// Redact credential fields of an `application/x-www-form-urlencoded` body;
+/// anything else passes through unchanged.
+fn redact_form(body: &str) -> String {
+  body
+    .split('&')
+    .map(|pair| match pair.split_once('=') {
+      Some((name, _)) if SENSITIVE_BODY_KEYS.contains(&name) => format!("{name}={REDACTED}"),
+      _ => pair.to_string(),
+    })
+    .collect::<Vec<_>>()
+    .join("&")
+}
+
+/// The response body as written to disk. JSON bodies (token responses) are
+/// redacted; anything else, such as an SSE stream, is kept byte-exact.
+fn redact_response_body(body: &[u8]) -> Vec<u8> {
+  let Ok(mut value) = serde_json::from_slice::<Value>(body) else {
+    return body.to_vec();
+  };
+  let original = value.clone();
+  redact_json(&mut value);
+  if value == original {
+    return body.to_vec();
+  }
+  serde_json::to_vec(&value).unwrap_or_else(|_| body.to_vec())
+}
+
+fn describe(request: &RecordedRequest) -> String {
+  let (method, path, body) = request.normalized();
+  let body = body
+    .map(|body| serde_json::to_string_pretty(&body).unwrap_or_default())
+    .unwrap_or_default();
+  format!("{method} {path}\n{body}")
+}
+
+async fn write_file(path: &Path, contents: &[u8]) -> Result<()> {
+  tokio::fs::write(path, contents)
+    .await
+    .map_err(|err| ModelError::StreamError(format!("write {}: {err}", path.display())))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+  use tokio::io::AsyncReadExt;
+  use tokio::io::AsyncWriteExt;
+  use tokio::net::TcpListener;
+
+  const SSE_BODY: &str =
+    "data: {\"choices\":[{\"delta\":{\"content\":\"hi\"}}]}\n\ndata: [DONE]\n\n";
+
+  /// Serve one canned SSE response on a loopback port.
+  async fn serve_once() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    tokio::spawn(async move {
+      let (mut socket, _) = listener.accept().await.expect("accept");
+      let mut buf = vec![0u8; 16 * 1024];
+      let _ = socket.read(&mut buf).await;
+      let response = format!(
+        "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\nset-cookie: session=abc\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{SSE_BODY}",
+        SSE_BODY.len()
+      );
+      socket.write_all(response.as_bytes()).await.expect("write");
+    });
+    format!("http://{addr}")
+  }
+
+  fn chat_request(client: &Client, base: &str, content: &str) -> RequestBuilder {
+    client
+      .post(format!("{base}/v1/chat/completions?key=secret"))
+      .header("Authorization", "Bearer sk-live")
+      .json(&json!({
+        "model": "gpt-test",
+        "stream": true,
+        "prompt_cache_key": uuid::Uuid::new_v4().to_string(),
+        "messages": [{ "role": "user", "content": content }],
+      }))
+  }
+
+  #[tokio::test]
+  async fn records_then_replays_without_network() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let client = Client::new();
+
+    let base = serve_once().await;
+    let recorder = Cassette::new(CassetteMode::Record, dir.path());
+    let live = recorder
+      .send(chat_request(&client, &base, "hello"))
+      .await
+      .expect("record");
+    assert_eq!(live.text().await.expect("body"), SSE_BODY);
+
+    let head = std::fs::read_to_string(dir.path().join("0000.json")).expect("head");
+    assert!(!head.contains("sk-live"));
+    assert!(!head.contains("secret"));
+    assert!(!head.contains("session=abc"));
+    assert_eq!(
+      std::fs::read_to_string(dir.path().join("0000.body")).expect("body"),
+      SSE_BODY
+    );
+
+    // Nothing listens on this port any more; replay must not need it.
+    let player = Cassette::new(CassetteMode::Replay, dir.path());
+    let replayed = player
+      .send(chat_request(&client, "http://127.0.0.1:9", "hello"))
+      .await
+      .expect("replay");
+    assert_eq!(replayed.status(), 200);
+    assert_eq!(
+      replayed
+        .headers()
+        .get("content-type")
+        .and_then(|value| value.to_str().ok()),
+      Some("text/event-stream")
+    );
+    assert_eq!(replayed.text().await.expect("body"), SSE_BODY);
+  }
+
+  #[tokio::test]
+  async fn replay_fails_loudly_on_mismatch_and_exhaustion() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let client = Client::new();
+    let base = serve_once().await;
+    Cassette::new(CassetteMode::Record, dir.path())
+      .send(chat_request(&client, &base, "hello"))
+      .await
+      .expect("record");
+
+    let player = Cassette::new(CassetteMode::Replay, dir.path());
+    let err = player
+      .send(chat_request(&client, &base, "goodbye"))
+      .await
+      .expect_err("mismatch");
+    let message = err.to_string();
+    assert!(matches!(err, ModelError::CassetteMismatch(_)));
+    assert!(message.contains("\"hello\""));
+    assert!(message.contains("\"goodbye\""));
+
+    let err = player
+      .send(chat_request(&client, &base, "hello"))
+      .await
+      .expect_err("exhausted");
+    assert!(err.to_string().contains("0001.json"));
+  }
+
+  #[tokio::test]
+  async fn recorded_token_refresh_keeps_no_tokens() {
+    const TOKEN_BODY: &str = r#"{"access_token":"at-live","refresh_token":"rt-next","id_token":"idt-live","expires_in":3600}"#;
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+    let addr = listener.local_addr().expect("addr");
+    tokio::spawn(async move {
+      let (mut socket, _) = listener.accept().await.expect("accept");
+      let mut buf = vec![0u8; 16 * 1024];
+      let _ = socket.read(&mut buf).await;
+      let response = format!(
+        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{TOKEN_BODY}",
+        TOKEN_BODY.len()
+      );
+      socket.write_all(response.as_bytes()).await.expect("write");
+    });
+    let dir = tempfile::tempdir().expect("tempdir");
+    let refresh = |base: String| {
+      Client::new().post(format!("{base}/oauth/token")).form(&[
+        ("grant_type", "refresh_token"),
+        ("refresh_token", "rt-live"),
+        ("client_id", "app_test"),
+        ("client_secret", "cs-live"),
+      ])
+    };
+
+    let live = Cassette::new(CassetteMode::Record, dir.path())
+      .send(refresh(format!("http://{addr}")))
+      .await
+      .expect("record");
+    assert_eq!(live.text().await.expect("body"), TOKEN_BODY);
+
+    for name in ["0000.json", "0000.body"] {
+      let recorded = std::fs::read_to_string(dir.path().join(name)).expect("recorded file");
+      for token in ["rt-live", "cs-live", "at-live", "rt-next", "idt-live"] {
+        assert!(
+          !recorded.contains(token),
+          "{name} leaks {token}: {recorded}"
+        );
+      }
+    }
+
+    // The redacted recording still matches the same refresh on replay.
+    let replayed = Cassette::new(CassetteMode::Replay, dir.path())
+      .send(refresh("http://127.0.0.1:9".to_string()))
+      .await
+      .expect("replay");
+    let token: Value = replayed.json().await.expect("json");
+    assert_eq!(token["access_token"], REDACTED);
+    assert_eq!(token["expires_in"], 3600);
+  }
+
+  #[test]
+  fn a_second_different_install_is_an_error() {
+    // Every other test in this binary runs without a cassette.
+    install_cassette(None).expect("first install");
+    install_cassette(None).expect("same selection again");
+
+    let err = install_cassette(Some(Cassette::new(CassetteMode::Replay, "elsewhere")))
+      .expect_err("conflicting install");
+    assert!(matches!(err, ModelError::CassetteConflict(_)));
+    assert!(err.to_string().contains("elsewhere"));
+    assert!(active().is_none());
+  }
+}
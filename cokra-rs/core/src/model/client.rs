@@ -13,6 +13,7 @@ use cokra_protocol::ResponseEvent;
 use super::auth::AuthManager;
 use super::error::ModelError;
 use super::error::Result;
+use super::models_dev::ModelsDevCost;
 use super::provider_catalog::find_provider_catalog_entry;
 use super::providers::register_provider_by_registration;
 use super::providers::registration_token_for_stored;
@@ -151,7 +152,7 @@ impl ModelClient {
       .ok_or(ModelError::ProviderNotFound(provider_id))
   }
 
-  async fn resolve_provider_id(&self, model: &str) -> Result<String> {
+  pub(crate) async fn resolve_provider_id(&self, model: &str) -> Result<String> {
     if let Some((provider_id, _)) = model.split_once('/') {
       return Ok(provider_id.to_string());
     }
@@ -218,11 +219,22 @@ impl ModelClient {
   }
 
   /// Enrich request with default values
+  ///
+  /// Reasoning settings are dropped unless the catalog lists the model as a
+  /// reasoning model: providers that don't know the fields reject the request.
   async fn enrich_request(
     &self,
-    request: ChatRequest,
+    mut request: ChatRequest,
     provider: &Arc<dyn super::ModelProvider>,
   ) -> Result<ChatRequest> {
+    if request.reasoning.is_some()
+      && !self
+        .resolve_model_catalog(&request.model)
+        .await
+        .is_some_and(|entry| entry.reasoning)
+    {
+      request.reasoning = None;
+    }
     let config = self.config.read().await;
     let transform = ProviderRuntimeTransform::from_config(provider.config());
     Ok(transform.apply_client_defaults(
@@ -251,6 +263,14 @@ impl ModelClient {
       .lookup_model_catalog(&provider_id, get_model_name(model))
       .await
   }
+
+  pub async fn resolve_model_cost(&self, model: &str) -> Option<ModelsDevCost> {
+    let provider_id = self.resolve_provider_id(model).await.ok()?;
+    self
+      .registry
+      .lookup_model_cost(&provider_id, get_model_name(model))
+      .await
+  }
 }
 
 impl Clone for ModelClient {
@@ -309,3 +329,65 @@ pub fn get_provider_id(model_id: &str) -> Option<&str> {
 pub fn get_model_name(model_id: &str) -> &str {
   parse_model_id(model_id).1
 }
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+  use crate::model::ReasoningEffort;
+  use crate::model::ReasoningRequest;
+  use crate::test_support::ScriptedProvider;
+
+  #[tokio::test]
+  async fn reasoning_settings_only_reach_reasoning_models() {
+    let provider = ScriptedProvider::replying("ok");
+    let requests = provider.requests();
+    let client = provider.into_model_client().await;
+    client
+      .registry()
+      .replace_models_dev_for_tests(
+        serde_json::from_value(serde_json::json!({
+          "mock": {
+            "id": "mock",
+            "name": "Mock",
+            "models": {
+              "default": { "id": "default", "name": "Default" },
+              "thinker": { "id": "thinker", "name": "Thinker", "reasoning": true }
+            }
+          }
+        }))
+        .expect("catalog"),
+      )
+      .await;
+
+    for model in ["mock/default", "mock/unlisted", "mock/thinker"] {
+      client
+        .chat(ChatRequest {
+          model: model.to_string(),
+          reasoning: Some(ReasoningRequest {
+            effort: ReasoningEffort::High,
+            summary: None,
+          }),
+          ..Default::default()
+        })
+        .await
+        .expect("chat");
+    }
+
+    let sent = requests
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .iter()
+      .map(|request| (request.model.clone(), request.reasoning.is_some()))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      sent,
+      vec![
+        ("default".to_string(), false),
+        ("unlisted".to_string(), false),
+        ("thinker".to_string(), true),
+      ]
+    );
+  }
+}
@@ -0,0 +1,145 @@
+//! Per-request cost accounting priced from the models.dev catalog.
+//!
+//! models.dev quotes every rate in USD per 1M tokens. Pricing is applied to the
+//! canonical `ResponseTokenUsage` produced by `streaming::parse_response_usage`,
+//! where `input_tokens` is already net of prompt-cache reads.
+
+use cokra_protocol::ResponseTokenUsage;
+
+use super::models_dev::ModelsDevCost;
+
+const TOKENS_PER_PRICE_UNIT: f64 = 1_000_000.0;
+
+/// Price one completed model request in USD.
+///
+/// Cache reads and writes bill at their catalog rates, falling back to the
+/// plain input rate when the catalog omits them. Reasoning tokens bill at the
+/// output rate, but only when the provider reports them on top of
+/// `output_tokens` (Gemini); OpenAI already folds them into `output_tokens`.
+pub fn request_cost_usd(cost: &ModelsDevCost, usage: &ResponseTokenUsage) -> f64 {
+  let input = usage.input_tokens.max(0) as f64;
+  let cache_read = usage.cached_input_tokens.max(0) as f64;
+  let cache_write = usage.cache_write_input_tokens.max(0) as f64;
+  let mut output = usage.output_tokens.max(0) as f64;
+  if reasoning_billed_separately(usage) {
+    output += usage.reasoning_output_tokens.max(0) as f64;
+  }
+
+  let priced = input * cost.input
+    + cache_read * cost.cache_read.unwrap_or(cost.input)
+    + cache_write * cost.cache_write.unwrap_or(cost.input)
+    + output * cost.output;
+  priced / TOKENS_PER_PRICE_UNIT
+}
+
+/// Tradeoff: providers disagree on whether reasoning tokens are part of
+/// `output_tokens`. We infer it from `total_tokens`: when the reported total
+/// only adds up with reasoning counted on its own, it was not included.
+fn reasoning_billed_separately(usage: &ResponseTokenUsage) -> bool {
+  let reasoning = usage.reasoning_output_tokens.max(0);
+  if reasoning == 0 {
+    return false;
+  }
+  let prompt = usage.input_tokens.max(0)
+    + usage.cached_input_tokens.max(0)
+    + usage.cache_write_input_tokens.max(0);
+  usage.total_tokens >= prompt + usage.output_tokens.max(0) + reasoning
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  fn pricing() -> ModelsDevCost {
+    ModelsDevCost {
+      input: 3.0,
+      output: 15.0,
+      cache_read: Some(0.3),
+      cache_write: Some(3.75),
+    }
+  }
+
+  fn assert_close(actual: f64, expected: f64) {
+    assert!(
+      (actual - expected).abs() < 1e-9,
+      "expected {expected}, got {actual}"
+    );
+  }
+
+  #[test]
+  fn prices_input_output_and_cache_tiers() {
+    let usage = ResponseTokenUsage {
+      input_tokens: 1_000_000,
+      cached_input_tokens: 2_000_000,
+      output_tokens: 100_000,
+      reasoning_output_tokens: 0,
+      total_tokens: 3_600_000,
+      cache_write_input_tokens: 500_000,
+    };
+
+    // 3.0 + 2 * 0.3 + 0.5 * 3.75 + 0.1 * 15.0
+    assert_close(request_cost_usd(&pricing(), &usage), 6.975);
+  }
+
+  #[test]
+  fn cache_rates_fall_back_to_input_rate() {
+    let cost = ModelsDevCost {
+      cache_read: None,
+      cache_write: None,
+      ..pricing()
+    };
+    let usage = ResponseTokenUsage {
+      input_tokens: 0,
+      cached_input_tokens: 1_000_000,
+      output_tokens: 0,
+      reasoning_output_tokens: 0,
+      total_tokens: 2_000_000,
+      cache_write_input_tokens: 1_000_000,
+    };
+
+    assert_close(request_cost_usd(&cost, &usage), 6.0);
+  }
+
+  #[test]
+  fn reasoning_included_in_output_is_not_billed_twice() {
+    // OpenAI shape: total = prompt + output, reasoning is a subset of output.
+    let usage = ResponseTokenUsage {
+      input_tokens: 1_000_000,
+      cached_input_tokens: 0,
+      output_tokens: 1_000_000,
+      reasoning_output_tokens: 400_000,
+      total_tokens: 2_000_000,
+      cache_write_input_tokens: 0,
+    };
+
+    assert_close(request_cost_usd(&pricing(), &usage), 18.0);
+  }
+
+  #[test]
+  fn reasoning_reported_separately_bills_at_output_rate() {
+    // Gemini shape: total = prompt + candidates + thoughts.
+    let usage = ResponseTokenUsage {
+      input_tokens: 1_000_000,
+      cached_input_tokens: 0,
+      output_tokens: 1_000_000,
+      reasoning_output_tokens: 1_000_000,
+      total_tokens: 3_000_000,
+      cache_write_input_tokens: 0,
+    };
+
+    assert_close(request_cost_usd(&pricing(), &usage), 33.0);
+  }
+
+  #[test]
+  fn negative_counts_are_ignored() {
+    let usage = ResponseTokenUsage {
+      input_tokens: -5,
+      output_tokens: -5,
+      ..Default::default()
+    };
+
+    assert_eq!(request_cost_usd(&pricing(), &usage), 0.0);
+  }
+}
@@ -68,6 +68,14 @@ pub enum ModelError {
   /// OAuth error
   #[error("OAuth error: {0}")]
   OAuthError(String),
+
+  /// Replayed request did not match the recorded cassette
+  #[error("Cassette mismatch: {0}")]
+  CassetteMismatch(String),
+
+  /// A different cassette was already chosen for this process
+  #[error("Cassette conflict: {0}")]
+  CassetteConflict(String),
 }
 
 /// Alias for Result<T, ModelError>
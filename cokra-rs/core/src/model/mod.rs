@@ -10,7 +10,9 @@
 //! - Provider implementations in [providers]
 
 pub mod auth_orchestrator;
+pub mod cassette;
 pub mod client;
+pub mod cost;
 pub mod error;
 pub mod metadata;
 pub mod model_catalog;
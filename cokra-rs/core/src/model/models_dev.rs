@@ -47,7 +47,8 @@ pub struct ModelsDevModel {
   pub provider: Option<ModelsDevModelProvider>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Catalog pricing, in USD per 1M tokens.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ModelsDevCost {
   #[serde(default)]
   pub input: f64,
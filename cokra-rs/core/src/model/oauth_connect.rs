@@ -962,6 +962,57 @@ pub async fn wait_for_local_callback(
   server.wait_for_callback(cancel).await
 }
 
+/// Localhost callback on a port picked by the OS, for authorization servers
+/// that accept any loopback redirect (RFC 8252 §7.3), such as MCP servers
+/// that register their clients dynamically.
+pub(crate) struct LoopbackCallback {
+  server: LocalCallbackServer,
+}
+
+impl LoopbackCallback {
+  pub(crate) async fn bind(path: &'static str) -> Result<Self, AuthError> {
+    let listener = TcpListener::bind(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0))
+      .await
+      .map_err(|e| {
+        AuthError::OAuthError(format!("failed to bind localhost callback server: {e}"))
+      })?;
+    let addr = listener.local_addr().map_err(|e| {
+      AuthError::OAuthError(format!("failed to read localhost callback address: {e}"))
+    })?;
+    Ok(Self {
+      server: LocalCallbackServer {
+        binding: CallbackBinding {
+          port: addr.port(),
+          path,
+        },
+        strategy: LocalCallbackBindStrategy::Loopback,
+        listeners: vec![LocalCallbackListener {
+          endpoint: LocalCallbackListenerEndpoint::new("ipv4-loopback", addr),
+          listener,
+        }],
+      },
+    })
+  }
+
+  pub(crate) fn redirect_uri(&self) -> String {
+    let binding = self.server.binding;
+    format!("http://127.0.0.1:{}{}", binding.port, binding.path)
+  }
+
+  /// Wait for the browser redirect and return its `code` and `state`.
+  pub(crate) async fn wait(
+    self,
+    cancel: CancellationToken,
+  ) -> Result<(String, Option<String>), AuthError> {
+    let callback_url = self.server.wait_for_callback(cancel).await?;
+    let parsed = parse_auth_input(&callback_url);
+    let code = parsed.code.ok_or_else(|| {
+      AuthError::OAuthError("OAuth callback missing authorization code".to_string())
+    })?;
+    Ok((code, parsed.state))
+  }
+}
+
 pub(super) fn start_anthropic_connect(
   provider_id: &str,
   provider_name: &str,
@@ -1567,7 +1618,7 @@ struct ParsedAuthInput {
   state: Option<String>,
 }
 
-fn generate_verifier() -> String {
+pub(crate) fn generate_verifier() -> String {
   format!(
     "{}{}",
     uuid::Uuid::new_v4().simple(),
@@ -1575,7 +1626,7 @@ fn generate_verifier() -> String {
   )
 }
 
-fn pkce_challenge(verifier: &str) -> String {
+pub(crate) fn pkce_challenge(verifier: &str) -> String {
   let digest = Sha256::digest(verifier.as_bytes());
   URL_SAFE_NO_PAD.encode(digest)
 }
@@ -18,6 +18,7 @@ use cokra_protocol::ResponseEvent;
 
 use super::error::ModelError;
 use super::error::Result;
+use super::streaming::response_token_usage;
 use super::types::ChatRequest;
 use super::types::ChatResponse;
 use super::types::Chunk;
@@ -95,9 +96,11 @@ pub fn chunk_stream_to_response_events(
     let mut emitted_item_started = false;
 
     let mut text_index: usize = 0;
+    let mut reasoning_index: usize = 0;
     let mut function_calls: BTreeMap<String, FunctionCallBuffer> = BTreeMap::new();
     let mut active_call_id: Option<String> = None;
     let mut emitted_end_turn = false;
+    let mut token_usage = None;
 
     while let Some(chunk) = chunk_stream.next().await {
       let chunk = match chunk {
@@ -128,6 +131,18 @@ pub fn chunk_stream_to_response_events(
           }));
           text_index += 1;
         }
+        Chunk::Reasoning { delta } => {
+          if !delta.text.is_empty() {
+            yield Ok(ResponseEvent::ReasoningContentDelta {
+              delta: delta.text,
+              content_index: reasoning_index,
+            });
+            reasoning_index += 1;
+          }
+          if let Some(signature) = delta.signature {
+            yield Ok(ResponseEvent::ReasoningSignature { signature });
+          }
+        }
         Chunk::ToolCall { delta } => {
           let call_id = delta
             .id
@@ -182,10 +197,13 @@ pub fn chunk_stream_to_response_events(
           }
           yield Ok(ResponseEvent::Completed {
             response_id: String::new(),
-            token_usage: None,
+            token_usage: token_usage.take(),
           });
           yield Ok(ResponseEvent::EndTurn);
         }
+        Chunk::Usage { usage } => {
+          token_usage = Some(response_token_usage(&usage));
+        }
         Chunk::MessageStart { .. } | Chunk::MessageDelta { .. } | Chunk::Unknown => {}
       }
     }
@@ -210,7 +228,7 @@ pub fn chunk_stream_to_response_events(
     if !emitted_end_turn {
       yield Ok(ResponseEvent::Completed {
         response_id: String::new(),
-        token_usage: None,
+        token_usage,
       });
       yield Ok(ResponseEvent::EndTurn);
     }
@@ -510,4 +528,43 @@ mod tests {
     assert!(matches!(&seen[2], ResponseEvent::Completed { .. }));
     assert_eq!(seen[3], ResponseEvent::EndTurn);
   }
+
+  #[tokio::test]
+  async fn chunk_stream_carries_cached_usage_into_completed() {
+    let source = futures::stream::iter(vec![
+      Ok(Chunk::Usage {
+        usage: super::super::types::Usage {
+          input_tokens: 1_000,
+          output_tokens: 20,
+          total_tokens: 1_020,
+          cached_input_tokens: 900,
+          cache_write_input_tokens: 60,
+        },
+      }),
+      Ok(Chunk::MessageStop),
+    ]);
+
+    let mut stream = chunk_stream_to_response_events(Box::pin(source));
+    let mut token_usage = None;
+    while let Some(event) = stream.next().await {
+      if let ResponseEvent::Completed {
+        token_usage: usage, ..
+      } = event.expect("response event")
+      {
+        token_usage = usage;
+      }
+    }
+
+    assert_eq!(
+      token_usage,
+      Some(cokra_protocol::ResponseTokenUsage {
+        input_tokens: 40,
+        cached_input_tokens: 900,
+        output_tokens: 20,
+        reasoning_output_tokens: 0,
+        total_tokens: 1_020,
+        cache_write_input_tokens: 60,
+      })
+    );
+  }
 }
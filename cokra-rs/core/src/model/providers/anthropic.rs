@@ -9,6 +9,7 @@ use serde::Deserialize;
 use serde::Serialize;
 use std::pin::Pin;
 
+use super::super::cassette::CassetteSend;
 use super::super::error::ModelError;
 use super::super::error::Result;
 use super::super::provider::ModelProvider;
@@ -16,6 +17,7 @@ use super::super::streaming::AnthropicUsageParser;
 use super::super::types::ChatRequest;
 use super::super::types::ChatResponse;
 use super::super::types::Chunk;
+use super::super::types::ImageData;
 use super::super::types::ListModelsResponse;
 use super::super::types::Message;
 use super::super::types::ProviderConfig;
@@ -60,14 +62,18 @@ impl AnthropicProvider {
     format!("{}/v1/{}", self.base_url.trim_end_matches('/'), path)
   }
 
-  /// Convert message to Anthropic format
-  fn convert_message(msg: &Message) -> AnthropicMessage {
+  /// Convert message to Anthropic format.
+  ///
+  /// With extended thinking on, an assistant turn that called tools must
+  /// start with the signed thinking block it was produced with.
+  fn convert_message(msg: &Message, thinking_enabled: bool) -> AnthropicMessage {
     match msg {
       Message::System(content) => AnthropicMessage {
         role: "user".to_string(),
         content: vec![AnthropicContent::Text {
           text: format!("<system_prompt>{}</system_prompt>", content),
           type_: "text".to_string(),
+          cache_control: None,
         }],
       },
       Message::User(content) => AnthropicMessage {
@@ -75,6 +81,7 @@ impl AnthropicProvider {
         content: vec![AnthropicContent::Text {
           text: content.clone(),
           type_: "text".to_string(),
+          cache_control: None,
         }],
       },
       Message::Assistant {
@@ -83,10 +90,25 @@ impl AnthropicProvider {
       } => {
         let mut parts = Vec::new();
 
+        if thinking_enabled
+          && let Some(block) = tool_calls
+            .as_ref()
+            .and_then(|calls| calls.first())
+            .and_then(|call| call.provider_meta.as_ref())
+            .and_then(|meta| meta.thinking.as_ref())
+        {
+          parts.push(AnthropicContent::Thinking {
+            thinking: block.text.clone(),
+            signature: block.signature.clone(),
+            type_: "thinking".to_string(),
+          });
+        }
+
         if let Some(text) = content {
           parts.push(AnthropicContent::Text {
             text: text.clone(),
             type_: "text".to_string(),
+            cache_control: None,
           });
         }
 
@@ -95,8 +117,10 @@ impl AnthropicProvider {
             parts.push(AnthropicContent::ToolUse {
               id: call.id.clone(),
               name: call.function.name.clone(),
-              input: call.function.arguments.clone(),
+              input: serde_json::from_str(&call.function.arguments)
+                .unwrap_or_else(|_| serde_json::json!({ "raw": call.function.arguments })),
               type_: "tool_use".to_string(),
+              cache_control: None,
             });
           }
         }
@@ -109,16 +133,101 @@ impl AnthropicProvider {
       Message::Tool {
         tool_call_id,
         content,
+        images,
       } => AnthropicMessage {
         role: "user".to_string(),
         content: vec![AnthropicContent::ToolResult {
           tool_use_id: tool_call_id.clone(),
-          content: content.clone(),
+          content: tool_result_content(content, images),
           type_: "tool_result".to_string(),
+          cache_control: None,
         }],
       },
     }
   }
+
+  /// Build the Messages API payload shared by the blocking and streaming paths.
+  fn build_request(request: &ChatRequest, stream: bool) -> AnthropicRequest {
+    let system = request
+      .messages
+      .iter()
+      .filter_map(|m| match m {
+        Message::System(s) if !s.is_empty() => Some(AnthropicSystemBlock {
+          text: s.clone(),
+          type_: "text".to_string(),
+          cache_control: None,
+        }),
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+    let thinking = request
+      .reasoning
+      .map(|reasoning| AnthropicThinking::enabled(reasoning.effort.thinking_budget_tokens()));
+    let messages = request
+      .messages
+      .iter()
+      .filter(|m| !matches!(m, Message::System(_)))
+      .map(|m| Self::convert_message(m, thinking.is_some()))
+      .collect();
+
+    let mut max_tokens = request.max_tokens.unwrap_or(4096);
+    if let Some(thinking) = &thinking {
+      // The budget counts against max_tokens; keep the usual room for the answer.
+      max_tokens += thinking.budget_tokens;
+    }
+    // Extended thinking rejects sampling overrides.
+    let (temperature, top_p) = if thinking.is_some() {
+      (None, None)
+    } else {
+      (request.temperature, request.top_p)
+    };
+
+    let mut anthropic_request = AnthropicRequest {
+      model: request.model.clone(),
+      messages,
+      max_tokens,
+      temperature,
+      top_p,
+      top_k: None,
+      system: (!system.is_empty()).then_some(system),
+      tools: request.tools.as_ref().map(|tools| {
+        tools
+          .iter()
+          .filter_map(|tool| tool.function.as_ref())
+          .map(|f| AnthropicTool {
+            name: f.name.clone(),
+            description: f.description.clone(),
+            input_schema: f.parameters.clone(),
+            cache_control: None,
+          })
+          .collect()
+      }),
+      thinking,
+      stream: Some(stream),
+    };
+    apply_cache_breakpoints(&mut anthropic_request);
+    anthropic_request
+  }
+}
+
+/// Place the four prompt-cache breakpoints Anthropic allows per request.
+///
+/// Tools render before the system prompt, so breakpoints on the last tool and
+/// the last system block cache the static prefix. The last two messages carry
+/// the rolling history breakpoints: the newest one writes this turn's prefix,
+/// the one before it still matches what the previous request wrote.
+fn apply_cache_breakpoints(request: &mut AnthropicRequest) {
+  if let Some(tool) = request.tools.as_mut().and_then(|tools| tools.last_mut()) {
+    tool.cache_control = Some(AnthropicCacheControl::ephemeral());
+  }
+  if let Some(block) = request.system.as_mut().and_then(|system| system.last_mut()) {
+    block.cache_control = Some(AnthropicCacheControl::ephemeral());
+  }
+  for message in request.messages.iter_mut().rev().take(2) {
+    if let Some(part) = message.content.last_mut() {
+      part.set_cache_control(AnthropicCacheControl::ephemeral());
+    }
+  }
 }
 
 /// Default models for Anthropic
@@ -149,50 +258,139 @@ struct AnthropicRequest {
   #[serde(skip_serializing_if = "Option::is_none")]
   top_k: Option<u32>,
   #[serde(skip_serializing_if = "Option::is_none")]
-  system: Option<String>,
+  system: Option<Vec<AnthropicSystemBlock>>,
   #[serde(skip_serializing_if = "Option::is_none")]
   tools: Option<Vec<AnthropicTool>>,
   #[serde(skip_serializing_if = "Option::is_none")]
+  thinking: Option<AnthropicThinking>,
+  #[serde(skip_serializing_if = "Option::is_none")]
   stream: Option<bool>,
 }
 
+#[derive(Debug, Serialize)]
+struct AnthropicThinking {
+  #[serde(rename = "type")]
+  type_: String,
+  budget_tokens: u32,
+}
+
+impl AnthropicThinking {
+  fn enabled(budget_tokens: u32) -> Self {
+    Self {
+      type_: "enabled".to_string(),
+      budget_tokens,
+    }
+  }
+}
+
+#[derive(Debug, Serialize, Clone, Deserialize, PartialEq)]
+struct AnthropicCacheControl {
+  #[serde(rename = "type")]
+  type_: String,
+}
+
+impl AnthropicCacheControl {
+  fn ephemeral() -> Self {
+    Self {
+      type_: "ephemeral".to_string(),
+    }
+  }
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct AnthropicSystemBlock {
+  text: String,
+  #[serde(rename = "type")]
+  type_: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  cache_control: Option<AnthropicCacheControl>,
+}
+
 #[derive(Debug, Serialize, Clone)]
 struct AnthropicMessage {
   role: String,
   content: Vec<AnthropicContent>,
 }
 
+/// `tool_result` content: plain text, or text followed by base64 image
+/// blocks.
+fn tool_result_content(content: &str, images: &[ImageData]) -> serde_json::Value {
+  if images.is_empty() {
+    return serde_json::Value::String(content.to_string());
+  }
+  let mut blocks = vec![serde_json::json!({ "type": "text", "text": content })];
+  blocks.extend(images.iter().map(|image| {
+    serde_json::json!({
+      "type": "image",
+      "source": {
+        "type": "base64",
+        "media_type": image.mime_type,
+        "data": image.data,
+      },
+    })
+  }));
+  serde_json::Value::Array(blocks)
+}
+
 #[derive(Debug, Serialize, Clone, Deserialize)]
 #[serde(untagged)]
 enum AnthropicContent {
+  /// Extended thinking output; cannot carry `cache_control`.
+  #[serde(rename = "thinking")]
+  Thinking {
+    thinking: String,
+    signature: String,
+    #[serde(rename = "type")]
+    type_: String,
+  },
   #[serde(rename = "text")]
   Text {
     text: String,
     #[serde(rename = "type")]
     type_: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cache_control: Option<AnthropicCacheControl>,
   },
   #[serde(rename = "tool_use")]
   ToolUse {
     id: String,
     name: String,
-    input: String,
+    input: serde_json::Value,
     #[serde(rename = "type")]
     type_: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cache_control: Option<AnthropicCacheControl>,
   },
   #[serde(rename = "tool_result")]
   ToolResult {
     tool_use_id: String,
-    content: String,
+    /// A string, or text and image blocks when the tool returned images.
+    content: serde_json::Value,
     #[serde(rename = "type")]
     type_: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cache_control: Option<AnthropicCacheControl>,
   },
 }
 
+impl AnthropicContent {
+  fn set_cache_control(&mut self, value: AnthropicCacheControl) {
+    match self {
+      AnthropicContent::Text { cache_control, .. }
+      | AnthropicContent::ToolUse { cache_control, .. }
+      | AnthropicContent::ToolResult { cache_control, .. } => *cache_control = Some(value),
+      AnthropicContent::Thinking { .. } => {}
+    }
+  }
+}
+
 #[derive(Debug, Serialize)]
 struct AnthropicTool {
   name: String,
   description: String,
   input_schema: serde_json::Value,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  cache_control: Option<AnthropicCacheControl>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -209,6 +407,10 @@ struct AnthropicResponse {
 struct AnthropicUsage {
   input_tokens: u32,
   output_tokens: u32,
+  #[serde(default)]
+  cache_read_input_tokens: u32,
+  #[serde(default)]
+  cache_creation_input_tokens: u32,
 }
 
 #[async_trait]
@@ -232,36 +434,7 @@ impl ModelProvider for AnthropicProvider {
   async fn chat_completion(&self, request: ChatRequest) -> Result<ChatResponse> {
     let url = self.endpoint("messages");
 
-    // Convert messages
-    let messages: Vec<AnthropicMessage> =
-      request.messages.iter().map(Self::convert_message).collect();
-
-    // Extract system message
-    let system = request.messages.iter().find_map(|m| match m {
-      Message::System(s) => Some(s.clone()),
-      _ => None,
-    });
-
-    let anthropic_request = AnthropicRequest {
-      model: request.model.clone(),
-      messages,
-      max_tokens: request.max_tokens.unwrap_or(4096),
-      temperature: request.temperature,
-      top_p: request.top_p,
-      top_k: None,
-      system,
-      tools: request.tools.map(|t| {
-        t.into_iter()
-          .filter_map(|tool| tool.function)
-          .map(|f| AnthropicTool {
-            name: f.name,
-            description: f.description,
-            input_schema: f.parameters,
-          })
-          .collect()
-      }),
-      stream: Some(false),
-    };
+    let anthropic_request = Self::build_request(&request, false);
 
     let mut req_builder = self
       .client
@@ -277,9 +450,8 @@ impl ModelProvider for AnthropicProvider {
 
     let response = req_builder
       .json(&anthropic_request)
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+      .send_via_cassette()
+      .await?;
 
     if !response.status().is_success() {
       let status = response.status();
@@ -302,19 +474,7 @@ impl ModelProvider for AnthropicProvider {
   ) -> Result<Pin<Box<dyn Stream<Item = Result<Chunk>> + Send>>> {
     let url = self.endpoint("messages");
 
-    let messages: Vec<AnthropicMessage> =
-      request.messages.iter().map(Self::convert_message).collect();
-
-    let anthropic_request = AnthropicRequest {
-      model: request.model.clone(),
-      messages,
-      max_tokens: request.max_tokens.unwrap_or(4096),
-      temperature: request.temperature,
-      top_p: request.top_p,
-      top_k: None,
-      stream: Some(true),
-      ..Default::default()
-    };
+    let anthropic_request = Self::build_request(&request, true);
 
     let mut req_builder = self
       .client
@@ -329,9 +489,8 @@ impl ModelProvider for AnthropicProvider {
 
     let response = req_builder
       .json(&anthropic_request)
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+      .send_via_cassette()
+      .await?;
 
     Ok(create_response_stream_with_usage_parser(
       response,
@@ -367,6 +526,7 @@ impl ModelProvider for AnthropicProvider {
         content: vec![AnthropicContent::Text {
           text: "Hi".to_string(),
           type_: "text".to_string(),
+          cache_control: None,
         }],
       }],
       max_tokens: 10,
@@ -379,9 +539,8 @@ impl ModelProvider for AnthropicProvider {
       .header("x-api-key", &self.api_key)
       .header("anthropic-version", &self.version)
       .json(&request)
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+      .send_via_cassette()
+      .await?;
 
     if response.status().is_success() {
       Ok(())
@@ -410,6 +569,7 @@ impl Default for AnthropicRequest {
       top_k: None,
       system: None,
       tools: None,
+      thinking: None,
       stream: None,
     }
   }
@@ -419,10 +579,16 @@ impl Default for AnthropicRequest {
 fn convert_anthropic_response(resp: AnthropicResponse, model: &str) -> ChatResponse {
   use crate::model::types::Choice;
   use crate::model::types::ChoiceMessage;
+  use crate::model::types::ThinkingBlock;
   use crate::model::types::ToolCall;
   use crate::model::types::ToolCallFunction;
+  use crate::model::types::ToolCallProviderMeta;
   use crate::model::types::Usage;
 
+  let prompt_tokens = resp.usage.input_tokens
+    + resp.usage.cache_read_input_tokens
+    + resp.usage.cache_creation_input_tokens;
+
   let content = resp
     .content
     .iter()
@@ -433,6 +599,17 @@ fn convert_anthropic_response(resp: AnthropicResponse, model: &str) -> ChatRespo
     .collect::<Vec<_>>()
     .join("");
 
+  let mut thinking = resp.content.iter().find_map(|c| match c {
+    AnthropicContent::Thinking {
+      thinking,
+      signature,
+      ..
+    } => Some(ThinkingBlock {
+      text: thinking.clone(),
+      signature: signature.clone(),
+    }),
+    _ => None,
+  });
   let tool_calls: Vec<ToolCall> = resp
     .content
     .iter()
@@ -444,9 +621,12 @@ fn convert_anthropic_response(resp: AnthropicResponse, model: &str) -> ChatRespo
         call_type: "function".to_string(),
         function: ToolCallFunction {
           name: name.clone(),
-          arguments: input.clone(),
+          arguments: input.to_string(),
         },
-        provider_meta: None,
+        provider_meta: thinking.take().map(|block| ToolCallProviderMeta {
+          thinking: Some(block),
+          ..Default::default()
+        }),
       }),
       _ => None,
     })
@@ -471,10 +651,191 @@ fn convert_anthropic_response(resp: AnthropicResponse, model: &str) -> ChatRespo
       finish_reason: resp.stop_reason,
     }],
     usage: Usage {
-      input_tokens: resp.usage.input_tokens,
+      input_tokens: prompt_tokens,
       output_tokens: resp.usage.output_tokens,
-      total_tokens: resp.usage.input_tokens + resp.usage.output_tokens,
+      total_tokens: prompt_tokens + resp.usage.output_tokens,
+      cached_input_tokens: resp.usage.cache_read_input_tokens,
+      cache_write_input_tokens: resp.usage.cache_creation_input_tokens,
     },
     extra: Default::default(),
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+  use serde_json::json;
+
+  use super::*;
+  use crate::model::types::FunctionDefinition;
+  use crate::model::types::ReasoningEffort;
+  use crate::model::types::ReasoningRequest;
+  use crate::model::types::ThinkingBlock;
+  use crate::model::types::Tool;
+  use crate::model::types::ToolCall;
+  use crate::model::types::ToolCallFunction;
+  use crate::model::types::ToolCallProviderMeta;
+
+  fn tool(name: &str) -> Tool {
+    Tool::function(FunctionDefinition {
+      name: name.to_string(),
+      description: String::new(),
+      parameters: json!({"type": "object"}),
+    })
+  }
+
+  #[test]
+  fn build_request_places_cache_breakpoints_on_static_prefix_and_recent_history() {
+    let request = ChatRequest {
+      model: "claude-sonnet-4-20250514".to_string(),
+      messages: vec![
+        Message::System("be terse".to_string()),
+        Message::User("first".to_string()),
+        Message::Assistant {
+          content: None,
+          tool_calls: Some(vec![ToolCall {
+            id: "toolu_1".to_string(),
+            call_type: "function".to_string(),
+            function: ToolCallFunction {
+              name: "read_file".to_string(),
+              arguments: r#"{"path":"a.txt"}"#.to_string(),
+            },
+            provider_meta: None,
+          }]),
+        },
+        Message::Tool {
+          tool_call_id: "toolu_1".to_string(),
+          content: "contents".to_string(),
+          images: Vec::new(),
+        },
+      ],
+      tools: Some(vec![tool("read_file"), tool("write_file")]),
+      ..Default::default()
+    };
+
+    let body = serde_json::to_value(AnthropicProvider::build_request(&request, true))
+      .expect("serialize request");
+    let ephemeral = json!({"type": "ephemeral"});
+
+    assert_eq!(
+      body["system"],
+      json!([{"text": "be terse", "type": "text", "cache_control": ephemeral}])
+    );
+    assert!(body["tools"][0].get("cache_control").is_none());
+    assert_eq!(body["tools"][1]["cache_control"], ephemeral);
+
+    let messages = body["messages"].as_array().expect("messages");
+    assert_eq!(
+      messages.len(),
+      3,
+      "system prompt is not repeated as a message"
+    );
+    assert!(messages[0]["content"][0].get("cache_control").is_none());
+    assert_eq!(messages[1]["content"][0]["cache_control"], ephemeral);
+    assert_eq!(messages[1]["content"][0]["input"], json!({"path": "a.txt"}));
+    assert_eq!(messages[2]["content"][0]["cache_control"], ephemeral);
+  }
+
+  #[test]
+  fn build_request_enables_thinking_and_replays_signed_block() {
+    let request = ChatRequest {
+      model: "claude-sonnet-4-20250514".to_string(),
+      messages: vec![
+        Message::User("first".to_string()),
+        Message::Assistant {
+          content: None,
+          tool_calls: Some(vec![ToolCall {
+            id: "toolu_1".to_string(),
+            call_type: "function".to_string(),
+            function: ToolCallFunction {
+              name: "read_file".to_string(),
+              arguments: "{}".to_string(),
+            },
+            provider_meta: Some(ToolCallProviderMeta {
+              thinking: Some(ThinkingBlock {
+                text: "look first".to_string(),
+                signature: "sig-1".to_string(),
+              }),
+              ..Default::default()
+            }),
+          }]),
+        },
+      ],
+      temperature: Some(0.2),
+      max_tokens: Some(4096),
+      reasoning: Some(ReasoningRequest {
+        effort: ReasoningEffort::High,
+        summary: None,
+      }),
+      ..Default::default()
+    };
+
+    let body = serde_json::to_value(AnthropicProvider::build_request(&request, true))
+      .expect("serialize request");
+
+    assert_eq!(
+      body["thinking"],
+      json!({"type": "enabled", "budget_tokens": 24_576})
+    );
+    assert_eq!(body["max_tokens"], json!(4096 + 24_576));
+    assert!(body.get("temperature").is_none());
+    assert_eq!(
+      body["messages"][1]["content"][0],
+      json!({"type": "thinking", "thinking": "look first", "signature": "sig-1"})
+    );
+    assert_eq!(body["messages"][1]["content"][1]["type"], json!("tool_use"));
+  }
+
+  #[test]
+  fn response_usage_counts_cached_prompt_tokens() {
+    let resp: AnthropicResponse = serde_json::from_value(json!({
+      "id": "msg_1",
+      "role": "assistant",
+      "content": [{"type": "text", "text": "hi"}],
+      "stop_reason": "end_turn",
+      "usage": {
+        "input_tokens": 10,
+        "output_tokens": 5,
+        "cache_read_input_tokens": 900,
+        "cache_creation_input_tokens": 90
+      }
+    }))
+    .expect("response");
+
+    let usage = convert_anthropic_response(resp, "claude").usage;
+    assert_eq!(usage.input_tokens, 1_000);
+    assert_eq!(usage.cached_input_tokens, 900);
+    assert_eq!(usage.cache_write_input_tokens, 90);
+    assert_eq!(usage.total_tokens, 1_005);
+  }
+
+  #[test]
+  fn tool_result_carries_images_as_base64_blocks() {
+    let request = ChatRequest {
+      model: "claude-sonnet-4-20250514".to_string(),
+      messages: vec![Message::Tool {
+        tool_call_id: "toolu_1".to_string(),
+        content: "screenshot taken".to_string(),
+        images: vec![ImageData {
+          mime_type: "image/png".to_string(),
+          data: "iVBORw0KGgo=".to_string(),
+        }],
+      }],
+      ..Default::default()
+    };
+
+    let body = serde_json::to_value(AnthropicProvider::build_request(&request, false))
+      .expect("serialize request");
+
+    assert_eq!(
+      body["messages"][0]["content"][0]["content"],
+      json!([
+        {"type": "text", "text": "screenshot taken"},
+        {
+          "type": "image",
+          "source": {"type": "base64", "media_type": "image/png", "data": "iVBORw0KGgo="},
+        },
+      ])
+    );
+  }
+}
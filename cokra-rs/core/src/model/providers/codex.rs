@@ -12,6 +12,7 @@ use tokio::sync::RwLock;
 use super::super::auth::AuthManager;
 use super::super::auth::Credentials;
 use super::super::auth::StoredCredentials;
+use super::super::cassette::CassetteSend;
 use super::super::error::ModelError;
 use super::super::error::Result;
 use super::super::provider::ModelProvider;
@@ -32,6 +33,7 @@ use super::super::types::Tool;
 use super::super::types::ToolCall;
 use super::super::types::Usage;
 use super::create_client;
+use super::flush_tool_images;
 
 const OPENAI_CLIENT_ID: &str = "app_EMoamEEZ73f0CkXaXp7hrann";
 const OPENAI_TOKEN_URL: &str = "https://auth.openai.com/oauth/token";
@@ -124,9 +126,8 @@ impl OpenAICodexProvider {
         ("refresh_token", refresh_token),
         ("client_id", OPENAI_CLIENT_ID),
       ])
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+      .send_via_cassette()
+      .await?;
 
     if !response.status().is_success() {
       let status = response.status();
@@ -209,8 +210,14 @@ impl OpenAICodexProvider {
     let request = ProviderRuntimeTransform::from_config(&self.config).normalize_request(request);
     let mut instructions = Vec::new();
     let mut input = Vec::<Value>::new();
+    // `function_call_output` only carries text; images follow as user input
+    // once the batch of outputs ends, so parallel call outputs stay adjacent.
+    let mut pending_images = Vec::<Value>::new();
 
     for message in request.messages {
+      if !matches!(message, super::super::types::Message::Tool { .. }) {
+        flush_tool_images(&mut input, &mut pending_images);
+      }
       match message {
         super::super::types::Message::System(content) => {
           if !content.is_empty() {
@@ -254,15 +261,23 @@ impl OpenAICodexProvider {
         super::super::types::Message::Tool {
           tool_call_id,
           content,
+          images,
         } => {
           input.push(serde_json::json!({
             "type": "function_call_output",
             "call_id": tool_call_id,
             "output": content,
           }));
+          pending_images.extend(images.iter().map(|image| {
+            serde_json::json!({
+              "type": "input_image",
+              "image_url": image.data_url(),
+            })
+          }));
         }
       }
     }
+    flush_tool_images(&mut input, &mut pending_images);
 
     let mut body = serde_json::Map::new();
     body.insert("model".to_string(), Value::String(request.model));
@@ -284,9 +299,22 @@ impl OpenAICodexProvider {
     if let Some(top_p) = request.top_p {
       body.insert("top_p".to_string(), serde_json::json!(top_p));
     }
+    if let Some(reasoning) = request.reasoning {
+      let mut config = serde_json::json!({ "effort": reasoning.effort.as_str() });
+      if let Some(summary) = reasoning.summary {
+        config["summary"] = Value::String(summary.as_str().to_string());
+      }
+      body.insert("reasoning".to_string(), config);
+    }
     if let Some(user) = request.user {
       body.insert("user".to_string(), Value::String(user));
     }
+    if let Some(prompt_cache_key) = request.prompt_cache_key {
+      body.insert(
+        "prompt_cache_key".to_string(),
+        Value::String(prompt_cache_key),
+      );
+    }
     if let Some(tools) = request.tools
       && !tools.is_empty()
     {
@@ -388,9 +416,8 @@ impl ModelProvider for OpenAICodexProvider {
       )
       .await?
       .json(&body)
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+      .send_via_cassette()
+      .await?;
 
     Ok(create_openai_responses_event_stream(response))
   }
@@ -503,6 +530,9 @@ fn tool_to_response_tool(tool: Tool) -> Value {
 mod tests {
   use super::*;
   use crate::model::types::Message;
+  use crate::model::types::ReasoningEffort;
+  use crate::model::types::ReasoningRequest;
+  use crate::model::types::ReasoningSummary;
 
   #[test]
   fn extracts_account_id_from_organization_claims() {
@@ -541,14 +571,70 @@ mod tests {
       messages: vec![Message::User("hello".to_string())],
       temperature: Some(0.2),
       max_tokens: Some(2048),
+      prompt_cache_key: Some("thread-1".to_string()),
+      reasoning: Some(ReasoningRequest {
+        effort: ReasoningEffort::High,
+        summary: Some(ReasoningSummary::Auto),
+      }),
       ..Default::default()
     });
 
     assert_eq!(body.get("store").and_then(Value::as_bool), Some(false));
+    assert_eq!(
+      body["reasoning"],
+      serde_json::json!({"effort": "high", "summary": "auto"})
+    );
+    assert_eq!(
+      body.get("prompt_cache_key").and_then(Value::as_str),
+      Some("thread-1")
+    );
     assert!(body.get("temperature").is_none());
     assert!(body.get("max_output_tokens").is_none());
   }
 
+  #[test]
+  fn codex_responses_body_sends_tool_images_after_the_output_batch() {
+    let stored = StoredCredentials::new(
+      "openai-codex",
+      Credentials::OAuth {
+        access_token: "access".to_string(),
+        refresh_token: "refresh".to_string(),
+        expires_at: u64::MAX,
+        account_id: None,
+        enterprise_url: None,
+      },
+    );
+    let provider = OpenAICodexProvider::new(&stored, ProviderConfig::default()).expect("provider");
+    let tool = |id: &str, data: &str| Message::Tool {
+      tool_call_id: id.to_string(),
+      content: "[image: image/png]".to_string(),
+      images: vec![crate::model::types::ImageData {
+        mime_type: "image/png".to_string(),
+        data: data.to_string(),
+      }],
+    };
+    let body = provider.build_responses_body(ChatRequest {
+      model: "gpt-5.3-codex".to_string(),
+      messages: vec![tool("call_1", "AAAA"), tool("call_2", "BBBB")],
+      ..Default::default()
+    });
+
+    let input = body["input"].as_array().expect("input");
+    assert_eq!(input.len(), 3);
+    assert_eq!(input[0]["call_id"], "call_1");
+    assert_eq!(input[1]["call_id"], "call_2");
+    assert_eq!(
+      input[2],
+      serde_json::json!({
+        "role": "user",
+        "content": [
+          {"type": "input_image", "image_url": "data:image/png;base64,AAAA"},
+          {"type": "input_image", "image_url": "data:image/png;base64,BBBB"},
+        ],
+      })
+    );
+  }
+
   #[tokio::test]
   async fn authorized_request_sets_opencode_originator_header() {
     let stored = StoredCredentials::new(
@@ -12,6 +12,7 @@ use std::pin::Pin;
 
 use super::super::auth::Credentials;
 use super::super::auth::StoredCredentials;
+use super::super::cassette::CassetteSend;
 use super::super::error::ModelError;
 use super::super::error::Result;
 use super::super::provider::ModelProvider;
@@ -231,6 +232,7 @@ impl ModelProvider for GitHubCopilotProvider {
           crate::model::types::Message::Tool {
             tool_call_id,
             content,
+            ..
           } => CopilotMessage {
             role: "user".to_string(),
             content: format!("[Tool Result for {}]: {}", tool_call_id, content),
@@ -257,9 +259,8 @@ impl ModelProvider for GitHubCopilotProvider {
       .apply_headers(self.client.post(&url), Some(&header_input))
       .header("Content-Type", "application/json")
       .json(&body)
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+      .send_via_cassette()
+      .await?;
 
     Ok(create_response_stream(response))
   }
@@ -272,7 +273,7 @@ impl ModelProvider for GitHubCopilotProvider {
       let response = match self
         .apply_headers(self.client.get(&url), None)
         .header("Accept", "application/json")
-        .send()
+        .send_via_cassette()
         .await
       {
         Ok(resp) => resp,
@@ -321,9 +322,8 @@ impl ModelProvider for GitHubCopilotProvider {
       .apply_headers(self.client.post(&url), Some(&request))
       .header("Content-Type", "application/json")
       .json(&body)
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+      .send_via_cassette()
+      .await?;
 
     if response.status().is_success() {
       Ok(())
@@ -355,9 +355,8 @@ impl GitHubCopilotProvider {
       .apply_headers(self.client.post(&url), Some(&header_input))
       .header("Content-Type", "application/json")
       .json(&body)
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+      .send_via_cassette()
+      .await?;
 
     if !response.status().is_success() {
       let status = response.status();
@@ -391,9 +390,8 @@ impl GitHubCopilotProvider {
       .apply_headers(self.client.post(&url), Some(&request))
       .header("Content-Type", "application/json")
       .json(&body)
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+      .send_via_cassette()
+      .await?;
 
     if !response.status().is_success() {
       let status = response.status();
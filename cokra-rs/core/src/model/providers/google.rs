@@ -10,6 +10,7 @@ use serde::Deserialize;
 use serde::Serialize;
 use std::pin::Pin;
 
+use super::super::cassette::CassetteSend;
 use super::super::error::ModelError;
 use super::super::error::Result;
 use super::super::provider::ModelProvider;
@@ -23,6 +24,9 @@ use super::super::types::ListModelsResponse;
 use super::super::types::Message;
 use super::super::types::ModelInfo;
 use super::super::types::ProviderConfig;
+use super::super::types::ReasoningDelta;
+use super::super::types::ReasoningEffort;
+use super::super::types::ReasoningRequest;
 use super::super::types::Usage;
 use super::create_client;
 
@@ -84,6 +88,7 @@ impl GoogleProvider {
       .content
       .parts
       .iter()
+      .filter(|part| part.thought != Some(true))
       .filter_map(|part| part.text.clone())
       .collect::<Vec<_>>()
       .join("");
@@ -94,6 +99,8 @@ impl GoogleProvider {
         input_tokens: usage.prompt_token_count.unwrap_or(0),
         output_tokens: usage.candidates_token_count.unwrap_or(0),
         total_tokens: usage.total_token_count.unwrap_or(0),
+        cached_input_tokens: usage.cached_content_token_count.unwrap_or(0),
+        ..Default::default()
       })
       .unwrap_or_default();
 
@@ -129,28 +136,46 @@ impl GoogleProvider {
           role: "user".to_string(),
           parts: vec![GeminiPart {
             text: Some(format!("<system_prompt>{text}</system_prompt>")),
+            thought: None,
+            inline_data: None,
           }],
         },
         Message::User(text) => GeminiContent {
           role: "user".to_string(),
           parts: vec![GeminiPart {
             text: Some(text.clone()),
+            thought: None,
+            inline_data: None,
           }],
         },
         Message::Assistant { content, .. } => GeminiContent {
           role: "model".to_string(),
           parts: vec![GeminiPart {
             text: Some(content.clone().unwrap_or_default()),
+            thought: None,
+            inline_data: None,
           }],
         },
         Message::Tool {
           tool_call_id,
           content,
+          images,
         } => GeminiContent {
           role: "user".to_string(),
-          parts: vec![GeminiPart {
+          parts: std::iter::once(GeminiPart {
             text: Some(format!("[Tool Result for {tool_call_id}]: {content}")),
-          }],
+            thought: None,
+            inline_data: None,
+          })
+          .chain(images.iter().map(|image| GeminiPart {
+            text: None,
+            thought: None,
+            inline_data: Some(GeminiInlineData {
+              mime_type: image.mime_type.clone(),
+              data: image.data.clone(),
+            }),
+          }))
+          .collect(),
         },
       };
       contents.push(content);
@@ -162,19 +187,31 @@ impl GoogleProvider {
         temperature: request.temperature,
         max_output_tokens: request.max_tokens,
         top_p: request.top_p,
+        thinking_config: request
+          .reasoning
+          .map(|reasoning| GeminiThinkingConfig::for_model(&request.model, reasoning)),
       }),
       safety_settings: None,
       system_instruction: None,
     }
   }
 
-  fn parse_stream_text(value: &serde_json::Value) -> Option<String> {
+  /// Concatenated text of the first candidate's parts, either the answer
+  /// (`thoughts == false`) or the thought summaries.
+  fn parse_stream_text(value: &serde_json::Value, thoughts: bool) -> Option<String> {
     let candidates = value.get("candidates")?.as_array()?;
     let first = candidates.first()?;
     let content = first.get("content")?;
     let parts = content.get("parts")?.as_array()?;
     let text = parts
       .iter()
+      .filter(|part| {
+        part
+          .get("thought")
+          .and_then(serde_json::Value::as_bool)
+          .unwrap_or(false)
+          == thoughts
+      })
       .filter_map(|part| part.get("text").and_then(serde_json::Value::as_str))
       .collect::<Vec<_>>()
       .join("");
@@ -230,9 +267,8 @@ impl ModelProvider for GoogleProvider {
       .header("Content-Type", "application/json")
       .header("x-goog-api-key", &self.api_key)
       .json(&body)
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+      .send_via_cassette()
+      .await?;
 
     if !response.status().is_success() {
       let status = response.status();
@@ -261,9 +297,8 @@ impl ModelProvider for GoogleProvider {
       .header("Content-Type", "application/json")
       .header("x-goog-api-key", &self.api_key)
       .json(&body)
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+      .send_via_cassette()
+      .await?;
 
     let mut byte_stream = response.bytes_stream();
 
@@ -299,7 +334,12 @@ impl ModelProvider for GoogleProvider {
                 }
                 match serde_json::from_str::<serde_json::Value>(payload) {
                   Ok(value) => {
-                    if let Some(text) = Self::parse_stream_text(&value) {
+                    if let Some(text) = Self::parse_stream_text(&value, true) {
+                      yield Ok(Chunk::Reasoning {
+                        delta: ReasoningDelta { text, signature: None }
+                      });
+                    }
+                    if let Some(text) = Self::parse_stream_text(&value, false) {
                       yield Ok(Chunk::Content {
                         delta: ContentDelta { text }
                       });
@@ -346,12 +386,7 @@ impl ModelProvider for GoogleProvider {
       self.base_url.trim_end_matches('/'),
       self.api_key
     );
-    let response = self
-      .client
-      .get(&url)
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+    let response = self.client.get(&url).send_via_cassette().await?;
     if response.status().is_success() {
       Ok(())
     } else {
@@ -390,6 +425,23 @@ struct GeminiContent {
 struct GeminiPart {
   #[serde(skip_serializing_if = "Option::is_none")]
   text: Option<String>,
+  /// Set on thought-summary parts when `includeThoughts` is on
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  thought: Option<bool>,
+  /// Base64 media, such as images a tool returned
+  #[serde(
+    default,
+    rename = "inlineData",
+    skip_serializing_if = "Option::is_none"
+  )]
+  inline_data: Option<GeminiInlineData>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiInlineData {
+  mime_type: String,
+  data: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -401,6 +453,41 @@ struct GeminiGenerationConfig {
   max_output_tokens: Option<u32>,
   #[serde(skip_serializing_if = "Option::is_none")]
   top_p: Option<f32>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  thinking_config: Option<GeminiThinkingConfig>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiThinkingConfig {
+  include_thoughts: bool,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  thinking_budget: Option<u32>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  thinking_level: Option<String>,
+}
+
+impl GeminiThinkingConfig {
+  /// Gemini 3 takes a thinking level; Gemini 2.5 takes a token budget.
+  fn for_model(model: &str, reasoning: ReasoningRequest) -> Self {
+    let include_thoughts = reasoning.summary.is_some();
+    if model.to_lowercase().contains("gemini-3") {
+      let level = match reasoning.effort {
+        ReasoningEffort::Minimal | ReasoningEffort::Low => "low",
+        ReasoningEffort::Medium | ReasoningEffort::High => "high",
+      };
+      return Self {
+        include_thoughts,
+        thinking_budget: None,
+        thinking_level: Some(level.to_string()),
+      };
+    }
+    Self {
+      include_thoughts,
+      thinking_budget: Some(reasoning.effort.thinking_budget_tokens()),
+      thinking_level: None,
+    }
+  }
 }
 
 #[derive(Debug, Deserialize)]
@@ -428,6 +515,8 @@ struct GeminiUsageMetadata {
   candidates_token_count: Option<u32>,
   #[serde(default)]
   total_token_count: Option<u32>,
+  #[serde(default)]
+  cached_content_token_count: Option<u32>,
 }
 
 #[cfg(test)]
@@ -474,4 +563,44 @@ mod tests {
     );
     assert_eq!(response.usage.total_tokens, 15);
   }
+
+  #[test]
+  fn reasoning_maps_to_thinking_config_and_thoughts_stay_out_of_text() {
+    let provider = GoogleProvider::new(
+      "test-key".to_string(),
+      ProviderConfig {
+        provider_id: "google".to_string(),
+        ..Default::default()
+      },
+    );
+    let body = serde_json::to_value(provider.to_gemini_request(&ChatRequest {
+      model: "gemini-2.5-pro".to_string(),
+      messages: vec![Message::User("hi".to_string())],
+      reasoning: Some(ReasoningRequest {
+        effort: ReasoningEffort::Low,
+        summary: Some(crate::model::types::ReasoningSummary::Auto),
+      }),
+      ..Default::default()
+    }))
+    .expect("serialize request");
+    assert_eq!(
+      body["generationConfig"]["thinkingConfig"],
+      serde_json::json!({"includeThoughts": true, "thinkingBudget": 4096})
+    );
+
+    let chunk = serde_json::json!({
+      "candidates": [{"content": {"parts": [
+        {"text": "pondering", "thought": true},
+        {"text": "answer"}
+      ]}}]
+    });
+    assert_eq!(
+      GoogleProvider::parse_stream_text(&chunk, true).as_deref(),
+      Some("pondering")
+    );
+    assert_eq!(
+      GoogleProvider::parse_stream_text(&chunk, false).as_deref(),
+      Some("answer")
+    );
+  }
 }
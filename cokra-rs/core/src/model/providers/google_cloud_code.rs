@@ -8,6 +8,7 @@ use reqwest::Client;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::model::cassette::CassetteSend;
 use crate::model::error::ModelError;
 use crate::model::error::Result;
 use crate::model::provider::ModelProvider;
@@ -21,6 +22,9 @@ use crate::model::types::ListModelsResponse;
 use crate::model::types::Message;
 use crate::model::types::ModelInfo;
 use crate::model::types::ProviderConfig;
+use crate::model::types::ReasoningDelta;
+use crate::model::types::ReasoningEffort;
+use crate::model::types::ReasoningRequest;
 use crate::model::types::Tool;
 use crate::model::types::ToolCall;
 use crate::model::types::ToolCallDelta;
@@ -235,7 +239,7 @@ impl GoogleCloudCodeProvider {
         .post(&request_url)
         .headers(request_headers.clone())
         .body(request_body.clone())
-        .send()
+        .send_via_cassette()
         .await
       {
         Ok(resp) if resp.status().is_success() => {
@@ -262,7 +266,7 @@ impl GoogleCloudCodeProvider {
             tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
             continue;
           }
-          last_err = Some(ModelError::NetworkError(err));
+          last_err = Some(err);
         }
       }
     }
@@ -319,9 +323,15 @@ impl GoogleCloudCodeProvider {
                           if let Some(text) = &part.text {
                             if !text.is_empty() {
                               saw_content = true;
-                              yield Ok(Chunk::Content {
-                                delta: ContentDelta { text: text.clone() },
-                              });
+                              if part.thought == Some(true) {
+                                yield Ok(Chunk::Reasoning {
+                                  delta: ReasoningDelta { text: text.clone(), signature: None },
+                                });
+                              } else {
+                                yield Ok(Chunk::Content {
+                                  delta: ContentDelta { text: text.clone() },
+                                });
+                              }
                             }
                           }
                           if let Some(function_call) = &part.function_call {
@@ -376,7 +386,7 @@ impl GoogleCloudCodeProvider {
           .post(&retry_url)
           .headers(request_headers.clone())
           .body(request_body.clone())
-          .send()
+          .send_via_cassette()
           .await
         {
           Ok(resp) if resp.status().is_success() => resp,
@@ -387,7 +397,7 @@ impl GoogleCloudCodeProvider {
             return;
           }
           Err(err) => {
-            yield Err(ModelError::NetworkError(err));
+            yield Err(err);
             return;
           }
         };
@@ -437,10 +447,13 @@ impl ModelProvider for GoogleCloudCodeProvider {
             // Preserve thought_signature for Gemini 3 multi-turn function calling
             provider_meta: delta.thought_signature.map(|sig| ToolCallProviderMeta {
               thought_signature: Some(sig),
+              ..Default::default()
             }),
           });
         }
         Chunk::MessageStop
+        | Chunk::Usage { .. }
+        | Chunk::Reasoning { .. }
         | Chunk::Unknown
         | Chunk::MessageStart { .. }
         | Chunk::MessageDelta { .. } => {}
@@ -584,12 +597,22 @@ struct CloudCodeAssistPart {
   function_call: Option<CloudCodeAssistFunctionCall>,
   #[serde(skip_serializing_if = "Option::is_none")]
   function_response: Option<CloudCodeAssistFunctionResponse>,
+  /// Base64 media, such as images a tool returned
+  #[serde(skip_serializing_if = "Option::is_none")]
+  inline_data: Option<CloudCodeAssistInlineData>,
   /// Google Gemini 3 thought signature - required for multi-turn function calling
   /// Must be passed back exactly as received from the model response
   #[serde(skip_serializing_if = "Option::is_none")]
   thought_signature: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CloudCodeAssistInlineData {
+  mime_type: String,
+  data: String,
+}
+
 #[derive(Debug, Clone, Serialize)]
 struct CloudCodeAssistFunctionCall {
   name: String,
@@ -724,6 +747,7 @@ fn build_cloud_code_assist_payload(
             thought: None,
             function_call: None,
             function_response: None,
+            inline_data: None,
             thought_signature: None,
           }],
         });
@@ -741,6 +765,7 @@ fn build_cloud_code_assist_payload(
             thought: None,
             function_call: None,
             function_response: None,
+            inline_data: None,
             thought_signature: None,
           });
         }
@@ -763,6 +788,7 @@ fn build_cloud_code_assist_payload(
                 id: Some(tool_call.id.clone()),
               }),
               function_response: None,
+              inline_data: None,
               thought_signature,
             });
           }
@@ -777,24 +803,38 @@ fn build_cloud_code_assist_payload(
       Message::Tool {
         tool_call_id,
         content,
+        images,
       } => {
         let tool_name = tool_names
           .get(tool_call_id)
           .cloned()
           .unwrap_or_else(|| "tool".to_string());
+        let mut parts = vec![CloudCodeAssistPart {
+          text: None,
+          thought: None,
+          function_call: None,
+          function_response: Some(CloudCodeAssistFunctionResponse {
+            name: tool_name,
+            response: serde_json::json!({ "output": content }),
+            id: Some(tool_call_id.clone()),
+          }),
+          inline_data: None,
+          thought_signature: None,
+        }];
+        parts.extend(images.iter().map(|image| CloudCodeAssistPart {
+          text: None,
+          thought: None,
+          function_call: None,
+          function_response: None,
+          inline_data: Some(CloudCodeAssistInlineData {
+            mime_type: image.mime_type.clone(),
+            data: image.data.clone(),
+          }),
+          thought_signature: None,
+        }));
         contents.push(CloudCodeAssistContent {
           role: "user".to_string(),
-          parts: vec![CloudCodeAssistPart {
-            text: None,
-            thought: None,
-            function_call: None,
-            function_response: Some(CloudCodeAssistFunctionResponse {
-              name: tool_name,
-              response: serde_json::json!({ "output": content }),
-              id: Some(tool_call_id.clone()),
-            }),
-            thought_signature: None,
-          }],
+          parts,
         });
       }
     }
@@ -823,14 +863,16 @@ fn build_cloud_code_assist_payload(
         max_output_tokens: request.max_tokens,
         temperature: request.temperature,
         top_p: request.top_p,
-        thinking_config: infer_thinking_config(&request.model),
+        thinking_config: infer_thinking_config(&request.model, request.reasoning),
       })
     } else {
-      infer_thinking_config(&request.model).map(|thinking_config| CloudCodeAssistGenerationConfig {
-        max_output_tokens: request.max_tokens,
-        temperature: request.temperature,
-        top_p: request.top_p,
-        thinking_config: Some(thinking_config),
+      infer_thinking_config(&request.model, request.reasoning).map(|thinking_config| {
+        CloudCodeAssistGenerationConfig {
+          max_output_tokens: request.max_tokens,
+          temperature: request.temperature,
+          top_p: request.top_p,
+          thinking_config: Some(thinking_config),
+        }
       })
     };
 
@@ -890,26 +932,42 @@ fn map_tool_choice(choice: &str) -> String {
   }
 }
 
-fn infer_thinking_config(model: &str) -> Option<CloudCodeAssistThinkingConfig> {
+/// Thinking settings for models that think; an explicit effort overrides the
+/// per-family default level or budget.
+fn infer_thinking_config(
+  model: &str,
+  reasoning: Option<ReasoningRequest>,
+) -> Option<CloudCodeAssistThinkingConfig> {
   let normalized = model.to_lowercase();
+  let include_thoughts = reasoning.is_none_or(|reasoning| reasoning.summary.is_some());
+  let effort = reasoning.map(|reasoning| reasoning.effort);
   if normalized.contains("gemini-3-pro") {
+    // Gemini 3 Pro only accepts LOW and HIGH.
+    let level = match effort {
+      Some(ReasoningEffort::Minimal | ReasoningEffort::Low) => "LOW",
+      _ => "HIGH",
+    };
     return Some(CloudCodeAssistThinkingConfig {
-      include_thoughts: true,
+      include_thoughts,
       thinking_budget: None,
-      thinking_level: Some("HIGH".to_string()),
+      thinking_level: Some(level.to_string()),
     });
   }
   if normalized.contains("gemini-3-flash") {
     return Some(CloudCodeAssistThinkingConfig {
-      include_thoughts: true,
+      include_thoughts,
       thinking_budget: None,
-      thinking_level: Some("MEDIUM".to_string()),
+      thinking_level: Some(
+        effort
+          .map_or("medium", ReasoningEffort::as_str)
+          .to_ascii_uppercase(),
+      ),
     });
   }
   if normalized.contains("gemini-2.5") || is_claude_thinking_model(model) {
     return Some(CloudCodeAssistThinkingConfig {
-      include_thoughts: true,
-      thinking_budget: Some(2048),
+      include_thoughts,
+      thinking_budget: Some(effort.map_or(2048, ReasoningEffort::thinking_budget_tokens)),
       thinking_level: None,
     });
   }
@@ -7,6 +7,7 @@ use futures::Stream;
 use reqwest::Client;
 use std::pin::Pin;
 
+use super::super::cassette::CassetteSend;
 use super::super::error::ModelError;
 use super::super::error::Result;
 use super::super::provider::ModelProvider;
@@ -53,12 +54,7 @@ impl LMStudioProvider {
   pub async fn list_available_models(&self) -> Result<Vec<LMStudioModel>> {
     let url = self.endpoint("models");
 
-    let response = self
-      .client
-      .get(&url)
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+    let response = self.client.get(&url).send_via_cassette().await?;
 
     if !response.status().is_success() {
       return Err(ModelError::ApiError("LM Studio not reachable".to_string()));
@@ -116,9 +112,8 @@ impl ModelProvider for LMStudioProvider {
       .post(&url)
       .header("Content-Type", "application/json")
       .json(&body)
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+      .send_via_cassette()
+      .await?;
 
     if !response.status().is_success() {
       let status = response.status();
@@ -145,9 +140,8 @@ impl ModelProvider for LMStudioProvider {
       .post(&url)
       .header("Content-Type", "application/json")
       .json(&body)
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+      .send_via_cassette()
+      .await?;
 
     Ok(super::super::providers::create_response_stream(response))
   }
@@ -174,12 +168,7 @@ impl ModelProvider for LMStudioProvider {
     // Just check if the server is reachable
     let url = self.endpoint("models");
 
-    let response = self
-      .client
-      .get(&url)
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+    let response = self.client.get(&url).send_via_cassette().await?;
 
     if response.status().is_success() {
       Ok(())
@@ -45,6 +45,8 @@ pub async fn register_all_providers(
   registry: &ProviderRegistry,
   config: &cokra_config::Config,
 ) -> Result<()> {
+  super::cassette::install(config)?;
+
   // OpenAI will be registered if credentials are found
   if let Ok(openai_key) = std::env::var("OPENAI_API_KEY") {
     let openai = OpenAIProvider::new(
@@ -577,11 +579,7 @@ pub fn create_client(timeout: Option<u64>) -> Client {
 /// providers (notably OpenRouter) reject requests that contain explicit
 /// `null` values for these fields.
 pub fn build_openai_request(request: ChatRequest, model: &str) -> serde_json::Value {
-  let messages: Vec<Value> = request
-    .messages
-    .iter()
-    .map(openai_compatible_message)
-    .collect();
+  let messages = openai_compatible_messages(&request.messages);
 
   let mut body = serde_json::Map::new();
   body.insert("model".to_string(), json!(model));
@@ -612,6 +610,12 @@ pub fn build_openai_request(request: ChatRequest, model: &str) -> serde_json::Va
   if let Some(top_p) = request.top_p {
     body.insert("top_p".to_string(), json!(top_p));
   }
+  if let Some(reasoning) = request.reasoning {
+    body.insert(
+      "reasoning_effort".to_string(),
+      json!(reasoning.effort.as_str()),
+    );
+  }
 
   Value::Object(body)
 }
@@ -649,37 +653,73 @@ fn log_request_summary(body: &Value) {
   }
 }
 
-fn openai_compatible_message(message: &Message) -> Value {
-  match message {
-    Message::System(content) => json!({
-      "role": "system",
-      "content": content,
-    }),
-    Message::User(content) => json!({
-      "role": "user",
-      "content": content,
-    }),
-    Message::Assistant {
-      content,
-      tool_calls,
-    } => {
-      let mut out = json!({
-        "role": "assistant",
+/// Chat Completions messages. Tool messages only take text, so images the
+/// tools returned follow in one user message after the last `tool` message of
+/// the batch; a user message between two `tool` messages would split the
+/// results of parallel calls.
+fn openai_compatible_messages(messages: &[Message]) -> Vec<Value> {
+  let mut out = Vec::with_capacity(messages.len());
+  let mut image_parts = Vec::new();
+  for message in messages {
+    if !matches!(message, Message::Tool { .. }) {
+      flush_tool_images(&mut out, &mut image_parts);
+    }
+    out.push(match message {
+      Message::System(content) => json!({
+        "role": "system",
         "content": content,
-      });
-      if let Some(calls) = tool_calls {
-        out["tool_calls"] = json!(calls);
+      }),
+      Message::User(content) => json!({
+        "role": "user",
+        "content": content,
+      }),
+      Message::Assistant {
+        content,
+        tool_calls,
+      } => {
+        let mut out = json!({
+          "role": "assistant",
+          "content": content,
+        });
+        if let Some(calls) = tool_calls {
+          out["tool_calls"] = json!(calls);
+        }
+        out
       }
-      out
-    }
-    Message::Tool {
-      tool_call_id,
-      content,
-    } => json!({
-      "role": "tool",
-      "tool_call_id": tool_call_id,
-      "content": content,
-    }),
+      Message::Tool {
+        tool_call_id,
+        content,
+        images,
+      } => {
+        if !images.is_empty() {
+          image_parts.push(json!({
+            "type": "text",
+            "text": format!("Images returned by tool call {tool_call_id}:"),
+          }));
+          image_parts.extend(images.iter().map(|image| {
+            json!({
+              "type": "image_url",
+              "image_url": { "url": image.data_url() },
+            })
+          }));
+        }
+        json!({
+          "role": "tool",
+          "tool_call_id": tool_call_id,
+          "content": content,
+        })
+      }
+    });
+  }
+  flush_tool_images(&mut out, &mut image_parts);
+  out
+}
+
+/// Appends pending tool images as one user message; tool messages cannot
+/// carry images in either the Chat Completions or the Responses API.
+pub(crate) fn flush_tool_images(out: &mut Vec<Value>, image_parts: &mut Vec<Value>) {
+  if !image_parts.is_empty() {
+    out.push(json!({ "role": "user", "content": std::mem::take(image_parts) }));
   }
 }
 
@@ -734,13 +774,23 @@ pub fn create_response_stream_with_usage_parser(
           return;
       }
 
+      // Tradeoff: hold the stop until the body ends. OpenAI sends the usage
+      // chunk after `finish_reason`, and Anthropic's final output count
+      // arrives in `message_delta`, so usage is only complete at the end.
+      let mut usage = None;
+      let mut stopped = false;
       while let Some(item) = stream.next().await {
           match item {
               Ok(bytes) => {
                   let text = String::from_utf8_lossy(&bytes).replace("\r\n\r\n", "\n\n");
                   for event in processor.push_text(&text) {
-                      if let Some(chunk) = event.chunk {
-                          yield Ok(chunk);
+                      if event.usage.is_some() {
+                          usage = event.usage;
+                      }
+                      match event.chunk {
+                          Some(Chunk::MessageStop) => stopped = true,
+                          Some(chunk) => yield Ok(chunk),
+                          None => {}
                       }
                   }
               }
@@ -751,10 +801,22 @@ pub fn create_response_stream_with_usage_parser(
       }
 
       for event in processor.finish() {
-          if let Some(chunk) = event.chunk {
-              yield Ok(chunk);
+          if event.usage.is_some() {
+              usage = event.usage;
+          }
+          match event.chunk {
+              Some(Chunk::MessageStop) => stopped = true,
+              Some(chunk) => yield Ok(chunk),
+              None => {}
           }
       }
+
+      if let Some(usage) = usage {
+          yield Ok(Chunk::Usage { usage });
+      }
+      if stopped {
+          yield Ok(Chunk::MessageStop);
+      }
   })
 }
 
@@ -766,6 +828,7 @@ mod tests {
   use crate::model::auth::StoredCredentials;
   use crate::model::provider_catalog::RuntimeRegistrationKind;
   use crate::model::types::ChatRequest;
+  use crate::model::types::ImageData;
   use crate::model::types::Message;
   use serde_json::json;
 
@@ -783,6 +846,7 @@ mod tests {
         Message::Tool {
           tool_call_id: "call_1".to_string(),
           content: "tool out".to_string(),
+          images: Vec::new(),
         },
       ],
       stream: false,
@@ -808,6 +872,7 @@ mod tests {
       messages: vec![Message::Tool {
         tool_call_id: "call_1".to_string(),
         content: "done".to_string(),
+        images: Vec::new(),
       }],
       stream: false,
       ..Default::default()
@@ -824,6 +889,81 @@ mod tests {
     );
   }
 
+  #[test]
+  fn build_openai_request_sends_tool_images_in_a_following_user_message() {
+    let request = ChatRequest {
+      model: "gpt-4o".to_string(),
+      messages: vec![Message::Tool {
+        tool_call_id: "call_1".to_string(),
+        content: "[image: image/png]".to_string(),
+        images: vec![ImageData {
+          mime_type: "image/png".to_string(),
+          data: "iVBORw0KGgo=".to_string(),
+        }],
+      }],
+      stream: false,
+      ..Default::default()
+    };
+
+    let payload = build_openai_request(request, "gpt-4o");
+    assert_eq!(payload["messages"][0]["role"], "tool");
+    assert_eq!(
+      payload["messages"][1],
+      json!({
+        "role": "user",
+        "content": [
+          {"type": "text", "text": "Images returned by tool call call_1:"},
+          {"type": "image_url", "image_url": {"url": "data:image/png;base64,iVBORw0KGgo="}},
+        ]
+      })
+    );
+  }
+
+  #[test]
+  fn build_openai_request_keeps_parallel_tool_results_together() {
+    let image = |data: &str| ImageData {
+      mime_type: "image/png".to_string(),
+      data: data.to_string(),
+    };
+    let request = ChatRequest {
+      model: "gpt-4o".to_string(),
+      messages: vec![
+        Message::Tool {
+          tool_call_id: "call_1".to_string(),
+          content: "[image: image/png]".to_string(),
+          images: vec![image("AAAA")],
+        },
+        Message::Tool {
+          tool_call_id: "call_2".to_string(),
+          content: "[image: image/png]".to_string(),
+          images: vec![image("BBBB")],
+        },
+        Message::User("next".to_string()),
+      ],
+      stream: false,
+      ..Default::default()
+    };
+
+    let payload = build_openai_request(request, "gpt-4o");
+    let roles = payload["messages"]
+      .as_array()
+      .expect("messages")
+      .iter()
+      .map(|message| message["role"].as_str().unwrap_or_default())
+      .collect::<Vec<_>>();
+    assert_eq!(roles, vec!["tool", "tool", "user", "user"]);
+    assert_eq!(
+      payload["messages"][2]["content"],
+      json!([
+        {"type": "text", "text": "Images returned by tool call call_1:"},
+        {"type": "image_url", "image_url": {"url": "data:image/png;base64,AAAA"}},
+        {"type": "text", "text": "Images returned by tool call call_2:"},
+        {"type": "image_url", "image_url": {"url": "data:image/png;base64,BBBB"}},
+      ])
+    );
+    assert_eq!(payload["messages"][3]["content"], "next");
+  }
+
   #[test]
   fn registration_token_for_openai_prefers_exchanged_api_key() {
     let mut stored = StoredCredentials::new(
@@ -8,6 +8,7 @@ use reqwest::Client;
 use serde::Deserialize;
 use std::pin::Pin;
 
+use super::super::cassette::CassetteSend;
 use super::super::error::ModelError;
 use super::super::error::Result;
 use super::super::provider::ModelProvider;
@@ -55,12 +56,7 @@ impl OllamaProvider {
   pub async fn list_available_models(&self) -> Result<Vec<OllamaModel>> {
     let url = self.endpoint("tags");
 
-    let response = self
-      .client
-      .get(&url)
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+    let response = self.client.get(&url).send_via_cassette().await?;
 
     #[derive(Deserialize)]
     struct TagsResponse {
@@ -89,9 +85,8 @@ impl OllamaProvider {
         name: model.to_string(),
         stream: Some(false),
       })
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+      .send_via_cassette()
+      .await?;
 
     Ok(())
   }
@@ -183,6 +178,7 @@ impl ModelProvider for OllamaProvider {
         crate::model::types::Message::Tool {
           tool_call_id,
           content,
+          ..
         } => OllamaMessage {
           role: "user".to_string(),
           content: format!("[Tool Result for {}]: {}", tool_call_id, content),
@@ -205,9 +201,8 @@ impl ModelProvider for OllamaProvider {
       .client
       .post(&url)
       .json(&ollama_request)
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+      .send_via_cassette()
+      .await?;
 
     if !response.status().is_success() {
       let status = response.status();
@@ -258,6 +253,7 @@ impl ModelProvider for OllamaProvider {
         input_tokens: ollama_response.prompt_eval_count,
         output_tokens: ollama_response.eval_count,
         total_tokens: ollama_response.prompt_eval_count + ollama_response.eval_count,
+        ..Default::default()
       },
       extra: Default::default(),
     })
@@ -302,9 +298,8 @@ impl ModelProvider for OllamaProvider {
       .client
       .post(&url)
       .json(&ollama_request)
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+      .send_via_cassette()
+      .await?;
 
     Ok(create_response_stream(response))
   }
@@ -335,12 +330,7 @@ impl ModelProvider for OllamaProvider {
     // Just check if the server is reachable
     let url = self.endpoint("tags");
 
-    let response = self
-      .client
-      .get(&url)
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+    let response = self.client.get(&url).send_via_cassette().await?;
 
     if response.status().is_success() {
       Ok(())
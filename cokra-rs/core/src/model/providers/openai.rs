@@ -9,6 +9,7 @@ use reqwest::RequestBuilder;
 use serde::Deserialize;
 use std::pin::Pin;
 
+use super::super::cassette::CassetteSend;
 use super::super::error::ModelError;
 use super::super::error::Result;
 use super::super::provider::ModelProvider;
@@ -58,6 +59,23 @@ impl OpenAIProvider {
     format!("Bearer {}", self.api_key)
   }
 
+  /// Chat Completions body with OpenAI-only extensions on top of the shared
+  /// OpenAI-compatible payload.
+  fn build_body(request: ChatRequest, stream: bool) -> serde_json::Value {
+    let model = request.model.clone();
+    let prompt_cache_key = request.prompt_cache_key.clone();
+    let mut body = build_openai_request(request, &model);
+    body["stream"] = serde_json::json!(stream);
+    if stream {
+      // Usage (including cached prompt tokens) is only streamed when asked for.
+      body["stream_options"] = serde_json::json!({ "include_usage": true });
+    }
+    if let Some(prompt_cache_key) = prompt_cache_key {
+      body["prompt_cache_key"] = serde_json::json!(prompt_cache_key);
+    }
+    body
+  }
+
   fn apply_headers(&self, request: RequestBuilder) -> RequestBuilder {
     let mut request = request.header("Authorization", self.auth_header());
 
@@ -121,16 +139,14 @@ impl ModelProvider for OpenAIProvider {
   async fn chat_completion(&self, request: ChatRequest) -> Result<ChatResponse> {
     let url = self.endpoint("chat/completions");
 
-    let model = request.model.clone();
-    let body = build_openai_request(request, &model);
+    let body = Self::build_body(request, false);
 
     let response = self
       .apply_headers(self.client.post(&url))
       .header("Content-Type", "application/json")
       .json(&body)
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+      .send_via_cassette()
+      .await?;
 
     if !response.status().is_success() {
       let status = response.status();
@@ -148,17 +164,14 @@ impl ModelProvider for OpenAIProvider {
   ) -> Result<Pin<Box<dyn Stream<Item = Result<Chunk>> + Send>>> {
     let url = self.endpoint("chat/completions");
 
-    let model = request.model.clone();
-    let mut body = build_openai_request(request, &model);
-    body["stream"] = serde_json::json!(true);
+    let body = Self::build_body(request, true);
 
     let response = self
       .apply_headers(self.client.post(&url))
       .header("Content-Type", "application/json")
       .json(&body)
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+      .send_via_cassette()
+      .await?;
 
     Ok(create_response_stream(response))
   }
@@ -168,9 +181,8 @@ impl ModelProvider for OpenAIProvider {
 
     let response = self
       .apply_headers(self.client.get(&url))
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+      .send_via_cassette()
+      .await?;
 
     if !response.status().is_success() {
       return Err(ModelError::AuthError("Failed to list models".to_string()));
@@ -212,9 +224,8 @@ impl ModelProvider for OpenAIProvider {
 
     let response = self
       .apply_headers(self.client.get(&url))
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+      .send_via_cassette()
+      .await?;
 
     if response.status().is_success() {
       Ok(())
@@ -243,6 +254,26 @@ mod tests {
     assert!(OPENAI_MODELS.contains(&"o1"));
   }
 
+  #[test]
+  fn test_openai_stream_body_requests_usage_and_cache_key() {
+    let body = OpenAIProvider::build_body(
+      ChatRequest {
+        model: "gpt-4o".to_string(),
+        prompt_cache_key: Some("thread-1".to_string()),
+        ..Default::default()
+      },
+      true,
+    );
+
+    assert_eq!(body["prompt_cache_key"], "thread-1");
+    assert_eq!(body["stream_options"]["include_usage"], true);
+    assert!(
+      OpenAIProvider::build_body(ChatRequest::default(), false)
+        .get("stream_options")
+        .is_none()
+    );
+  }
+
   #[test]
   fn test_openai_provider_applies_organization_header() {
     let provider = OpenAIProvider::new(
@@ -7,6 +7,7 @@ use futures::Stream;
 use reqwest::Client;
 use std::pin::Pin;
 
+use super::super::cassette::CassetteSend;
 use super::super::error::ModelError;
 use super::super::error::Result;
 use super::super::provider::ModelProvider;
@@ -16,6 +17,8 @@ use super::super::types::Chunk;
 use super::super::types::ListModelsResponse;
 use super::super::types::ModelInfo;
 use super::super::types::ProviderConfig;
+use super::super::types::ReasoningEffort;
+use super::super::types::ReasoningRequest;
 use super::build_openai_request;
 use super::create_client;
 use super::create_response_stream;
@@ -58,6 +61,25 @@ impl OpenRouterProvider {
   }
 }
 
+/// OpenRouter normalizes reasoning across upstream providers through its own
+/// `reasoning` object instead of OpenAI's `reasoning_effort`.
+fn apply_reasoning(body: &mut serde_json::Value, reasoning: Option<ReasoningRequest>) {
+  let Some(reasoning) = reasoning else {
+    return;
+  };
+  if let Some(body) = body.as_object_mut() {
+    body.remove("reasoning_effort");
+  }
+  let effort = match reasoning.effort {
+    ReasoningEffort::Minimal => ReasoningEffort::Low,
+    effort => effort,
+  };
+  body["reasoning"] = serde_json::json!({
+    "effort": effort.as_str(),
+    "exclude": reasoning.summary.is_none(),
+  });
+}
+
 /// Commonly used OpenRouter models.
 pub const OPENROUTER_MODELS: &[&str] = &[
   "openai/gpt-4o",
@@ -96,7 +118,9 @@ impl ModelProvider for OpenRouterProvider {
   async fn chat_completion(&self, request: ChatRequest) -> Result<ChatResponse> {
     let url = self.endpoint("chat/completions");
     let model = request.model.clone();
+    let reasoning = request.reasoning;
     let mut body = build_openai_request(request, &model);
+    apply_reasoning(&mut body, reasoning);
 
     if let Some(site_url) = &self.site_url {
       body["site_url"] = serde_json::json!(site_url);
@@ -126,9 +150,8 @@ impl ModelProvider for OpenRouterProvider {
       )
       .header("Content-Type", "application/json")
       .json(&body)
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+      .send_via_cassette()
+      .await?;
 
     if !response.status().is_success() {
       let status = response.status();
@@ -146,7 +169,9 @@ impl ModelProvider for OpenRouterProvider {
   ) -> Result<Pin<Box<dyn Stream<Item = Result<Chunk>> + Send>>> {
     let url = self.endpoint("chat/completions");
     let model = request.model.clone();
+    let reasoning = request.reasoning;
     let mut body = build_openai_request(request, &model);
+    apply_reasoning(&mut body, reasoning);
     body["stream"] = serde_json::json!(true);
     body["usage"] = serde_json::json!({ "include": true });
 
@@ -179,9 +204,8 @@ impl ModelProvider for OpenRouterProvider {
       )
       .header("Content-Type", "application/json")
       .json(&body)
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+      .send_via_cassette()
+      .await?;
 
     Ok(create_response_stream(response))
   }
@@ -192,9 +216,8 @@ impl ModelProvider for OpenRouterProvider {
       .client
       .get(&url)
       .header("Authorization", format!("Bearer {}", self.api_key))
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+      .send_via_cassette()
+      .await?;
 
     if response.status().is_success() {
       let body = response.text().await.map_err(ModelError::NetworkError)?;
@@ -261,9 +284,8 @@ impl ModelProvider for OpenRouterProvider {
       .client
       .get(&url)
       .header("Authorization", format!("Bearer {}", self.api_key))
-      .send()
-      .await
-      .map_err(ModelError::NetworkError)?;
+      .send_via_cassette()
+      .await?;
 
     if response.status().is_success() {
       Ok(())
@@ -293,4 +315,24 @@ mod tests {
     assert!(OPENROUTER_MODELS.contains(&"anthropic/claude-sonnet-4"));
     assert!(OPENROUTER_MODELS.contains(&"google/gemini-2.0-flash-exp"));
   }
+
+  #[test]
+  fn reasoning_uses_openrouter_reasoning_object() {
+    let request = ChatRequest {
+      model: "anthropic/claude-sonnet-4".to_string(),
+      reasoning: Some(ReasoningRequest {
+        effort: ReasoningEffort::Minimal,
+        summary: None,
+      }),
+      ..Default::default()
+    };
+    let mut body = build_openai_request(request.clone(), &request.model);
+    apply_reasoning(&mut body, request.reasoning);
+
+    assert!(body.get("reasoning_effort").is_none());
+    assert_eq!(
+      body["reasoning"],
+      serde_json::json!({"effort": "low", "exclude": true})
+    );
+  }
 }
@@ -13,6 +13,7 @@ use super::error::ModelError;
 use super::error::Result;
 use super::model_catalog;
 use super::models_dev::ModelsDevClient;
+use super::models_dev::ModelsDevCost;
 use super::provider::ProviderInfo;
 use super::provider_catalog::find_connect_provider_by_runtime_id;
 use super::provider_catalog::find_provider_catalog_entry;
@@ -25,6 +26,7 @@ pub struct ModelCatalogEntry {
   pub model_name: String,
   pub context_window: Option<u64>,
   pub reasoning: bool,
+  pub image_input: bool,
 }
 
 /// Provider Registry
@@ -376,6 +378,28 @@ impl ProviderRegistry {
     model_catalog::build_connected_models_catalog(&models_dev_db, connected)
   }
 
+  /// Catalog pricing for a model, read from the cached models.dev database.
+  ///
+  /// Never blocks on network I/O; returns `None` until the cache is warm or
+  /// when models.dev has no pricing for the model.
+  pub async fn lookup_model_cost(
+    &self,
+    provider_id: &str,
+    model_id: &str,
+  ) -> Option<ModelsDevCost> {
+    let models_dev_db = self
+      .models_dev
+      .get_cached_or_refresh()
+      .await
+      .unwrap_or_default();
+    models_dev_db
+      .get(provider_id)?
+      .models
+      .get(model_id)?
+      .cost
+      .clone()
+  }
+
   pub async fn lookup_model_catalog(
     &self,
     provider_id: &str,
@@ -420,6 +444,10 @@ impl ProviderRegistry {
           .map(|limit| limit.context)
           .filter(|limit| *limit > 0),
         reasoning: model.reasoning,
+        image_input: model
+          .modalities
+          .as_ref()
+          .is_some_and(|modalities| modalities.input.iter().any(|input| input == "image")),
       });
     }
 
@@ -434,6 +462,7 @@ impl ProviderRegistry {
         model_name: model_id.to_string(),
         context_window: None,
         reasoning: false,
+        image_input: false,
       })
   }
 
@@ -511,6 +540,14 @@ impl ProviderRegistry {
       .insert(provider_id.to_string(), config);
     Ok(())
   }
+
+  #[cfg(test)]
+  pub(crate) async fn replace_models_dev_for_tests(
+    &self,
+    db: super::models_dev::ModelsDevDatabase,
+  ) {
+    self.models_dev.replace_cached_database_for_tests(db).await;
+  }
 }
 
 /// Thread-safe reference to the registry
@@ -768,6 +805,64 @@ mod tests {
     assert!(!anthropic.authenticated, "no credentials for anthropic");
   }
 
+  #[tokio::test]
+  async fn lookup_model_cost_reads_models_dev_pricing() {
+    let registry = ProviderRegistry::new();
+    let mut models = HashMap::new();
+    models.insert(
+      "claude-sonnet-4-5".to_string(),
+      models_dev::ModelsDevModel {
+        id: "claude-sonnet-4-5".to_string(),
+        name: "Claude Sonnet 4.5".to_string(),
+        family: None,
+        release_date: "2025-09-29".to_string(),
+        attachment: true,
+        reasoning: true,
+        temperature: true,
+        tool_call: true,
+        cost: Some(models_dev::ModelsDevCost {
+          input: 3.0,
+          output: 15.0,
+          cache_read: Some(0.3),
+          cache_write: Some(3.75),
+        }),
+        limit: None,
+        modalities: None,
+        status: None,
+        options: None,
+        headers: None,
+        provider: None,
+      },
+    );
+    registry
+      .models_dev
+      .replace_cached_database_for_tests(HashMap::from([(
+        "anthropic".to_string(),
+        models_dev::ModelsDevProvider {
+          id: "anthropic".to_string(),
+          name: "Anthropic".to_string(),
+          api: None,
+          env: Vec::new(),
+          npm: None,
+          models,
+        },
+      )]))
+      .await;
+
+    let cost = registry
+      .lookup_model_cost("anthropic", "claude-sonnet-4-5")
+      .await
+      .expect("pricing");
+    assert_eq!(cost.input, 3.0);
+    assert_eq!(cost.cache_write, Some(3.75));
+    assert!(
+      registry
+        .lookup_model_cost("anthropic", "unknown-model")
+        .await
+        .is_none()
+    );
+  }
+
   #[tokio::test]
   async fn lookup_model_catalog_prefers_models_dev_limits() {
     let registry = ProviderRegistry::new();
@@ -796,7 +891,10 @@ mod tests {
           input: Some(272_000),
           output: 128_000,
         }),
-        modalities: None,
+        modalities: Some(models_dev::ModelsDevModalities {
+          input: vec!["text".to_string(), "image".to_string()],
+          output: vec!["text".to_string()],
+        }),
         status: None,
         options: None,
         headers: None,
@@ -833,6 +931,7 @@ mod tests {
         model_name: "GPT-5.3 Codex".to_string(),
         context_window: Some(272_000),
         reasoning: true,
+        image_input: true,
       }
     );
   }
@@ -912,6 +1011,7 @@ mod tests {
         model_name: "GPT-5.3 Codex".to_string(),
         context_window: Some(272_000),
         reasoning: true,
+        image_input: false,
       }
     );
   }
@@ -18,6 +18,7 @@ use cokra_protocol::ResponseTokenUsage;
 
 use super::types::Chunk;
 use super::types::ContentDelta;
+use super::types::ReasoningDelta;
 use super::types::ToolCallDelta;
 use super::types::Usage;
 use crate::model::error::ModelError;
@@ -84,19 +85,26 @@ impl UsageParser for AnthropicUsageParser {
       return;
     };
 
-    let top = value.get("usage").and_then(parse_usage);
-    if top.is_some() {
-      self.usage = top;
+    let usage = value.get("usage").and_then(parse_usage).or_else(|| {
+      value
+        .get("message")
+        .and_then(|msg| msg.get("usage"))
+        .and_then(parse_usage)
+    });
+    let Some(usage) = usage else {
       return;
-    }
+    };
 
-    let message = value
-      .get("message")
-      .and_then(|msg| msg.get("usage"))
-      .and_then(parse_usage);
-    if message.is_some() {
-      self.usage = message;
-    }
+    // `message_start` carries the prompt and cache counts; `message_delta`
+    // only the running output count. Keep the prompt side from the former.
+    self.usage = Some(match self.usage.take() {
+      Some(previous) if usage.input_tokens == 0 => Usage {
+        output_tokens: usage.output_tokens,
+        total_tokens: previous.input_tokens + usage.output_tokens,
+        ..previous
+      },
+      _ => usage,
+    });
   }
 
   fn retrieve(&self) -> Option<Usage> {
@@ -191,9 +199,70 @@ fn parse_chunk_value(value: &Value) -> Option<Chunk> {
   // Anthropic style
   if let Some(event_type) = value.get("type").and_then(Value::as_str) {
     match event_type {
+      "content_block_start" => {
+        let block = value.get("content_block")?;
+        if block.get("type").and_then(Value::as_str) != Some("tool_use") {
+          return None;
+        }
+        return Some(Chunk::ToolCall {
+          delta: ToolCallDelta {
+            id: block
+              .get("id")
+              .and_then(Value::as_str)
+              .map(ToString::to_string),
+            name: block
+              .get("name")
+              .and_then(Value::as_str)
+              .map(ToString::to_string),
+            arguments: None,
+            thought_signature: None,
+          },
+        });
+      }
       "content_block_delta" => {
-        let text = value
-          .get("delta")
+        let delta = value.get("delta");
+        match delta
+          .and_then(|delta| delta.get("type"))
+          .and_then(Value::as_str)
+        {
+          Some("thinking_delta") => {
+            let text = delta
+              .and_then(|delta| delta.get("thinking"))
+              .and_then(Value::as_str)
+              .unwrap_or_default()
+              .to_string();
+            return Some(Chunk::Reasoning {
+              delta: ReasoningDelta {
+                text,
+                signature: None,
+              },
+            });
+          }
+          Some("signature_delta") => {
+            return Some(Chunk::Reasoning {
+              delta: ReasoningDelta {
+                text: String::new(),
+                signature: delta
+                  .and_then(|delta| delta.get("signature"))
+                  .and_then(Value::as_str)
+                  .map(ToString::to_string),
+              },
+            });
+          }
+          _ => {}
+        }
+        if let Some(partial_json) = delta
+          .and_then(|delta| delta.get("partial_json"))
+          .and_then(Value::as_str)
+        {
+          return Some(Chunk::ToolCall {
+            delta: ToolCallDelta {
+              arguments: Some(partial_json.to_string()),
+              ..Default::default()
+            },
+          });
+        }
+        let text = delta
           .and_then(|delta| delta.get("text"))
           .and_then(Value::as_str)
           .unwrap_or_default()
@@ -248,6 +317,21 @@ fn parse_chunk_value(value: &Value) -> Option<Chunk> {
   }
 
   let delta = choice.get("delta").unwrap_or(&Value::Null);
+  // DeepSeek-style `reasoning_content`, or OpenRouter's `reasoning`. These
+  // arrive in deltas whose `content` is null or empty.
+  if let Some(text) = delta
+    .get("reasoning_content")
+    .or_else(|| delta.get("reasoning"))
+    .and_then(Value::as_str)
+    .filter(|text| !text.is_empty())
+  {
+    return Some(Chunk::Reasoning {
+      delta: ReasoningDelta {
+        text: text.to_string(),
+        signature: None,
+      },
+    });
+  }
   if let Some(text) = delta.get("content").and_then(Value::as_str) {
     return Some(Chunk::Content {
       delta: ContentDelta {
@@ -299,7 +383,7 @@ fn parse_data_line_json(chunk: &str) -> Option<Value> {
 }
 
 fn parse_usage(value: &Value) -> Option<Usage> {
-  let input_tokens = value
+  let mut input_tokens = value
     .get("prompt_tokens")
     .or_else(|| value.get("input_tokens"))
     .or_else(|| value.get("promptTokenCount"))
@@ -311,6 +395,27 @@ fn parse_usage(value: &Value) -> Option<Usage> {
     .or_else(|| value.get("candidatesTokenCount"))
     .and_then(Value::as_u64)
     .unwrap_or(0) as u32;
+  let cached_input_tokens = value
+    .get("cache_read_input_tokens")
+    .or_else(|| {
+      value
+        .get("prompt_tokens_details")
+        .and_then(|details| details.get("cached_tokens"))
+    })
+    .or_else(|| value.get("cachedContentTokenCount"))
+    .and_then(Value::as_u64)
+    .unwrap_or(0) as u32;
+  let cache_write_input_tokens = value
+    .get("cache_creation_input_tokens")
+    .and_then(Value::as_u64)
+    .unwrap_or(0) as u32;
+  // Anthropic reports `input_tokens` net of cache reads/writes; fold them back
+  // in so `Usage::input_tokens` is the full prompt for every provider.
+  if value.get("cache_read_input_tokens").is_some()
+    || value.get("cache_creation_input_tokens").is_some()
+  {
+    input_tokens += cached_input_tokens + cache_write_input_tokens;
+  }
   let total_tokens = value
     .get("total_tokens")
     .or_else(|| value.get("totalTokenCount"))
@@ -325,9 +430,27 @@ fn parse_usage(value: &Value) -> Option<Usage> {
     input_tokens,
     output_tokens,
     total_tokens,
+    cached_input_tokens,
+    cache_write_input_tokens,
   })
 }
 
+/// Convert chunk-stream [`Usage`] into the canonical response usage, where
+/// `input_tokens` excludes prompt-cache reads and writes.
+pub fn response_token_usage(usage: &Usage) -> ResponseTokenUsage {
+  let cached_input_tokens = i64::from(usage.cached_input_tokens);
+  let cache_write_input_tokens = i64::from(usage.cache_write_input_tokens);
+  ResponseTokenUsage {
+    input_tokens: (i64::from(usage.input_tokens) - cached_input_tokens - cache_write_input_tokens)
+      .max(0),
+    cached_input_tokens,
+    output_tokens: i64::from(usage.output_tokens),
+    reasoning_output_tokens: 0,
+    total_tokens: i64::from(usage.total_tokens),
+    cache_write_input_tokens,
+  }
+}
+
 #[derive(Debug, Clone, Default)]
 struct FunctionCallBuffer {
   call_id: String,
@@ -647,6 +770,23 @@ fn parse_openai_responses_event(
           });
         }
       }
+      "response.reasoning_text.delta" => {
+        let delta = value
+          .get("delta")
+          .and_then(Value::as_str)
+          .unwrap_or_default()
+          .to_string();
+        let content_index = value
+          .get("content_index")
+          .and_then(Value::as_u64)
+          .unwrap_or(0) as usize;
+        if !delta.is_empty() {
+          events.push(ResponseEvent::ReasoningContentDelta {
+            delta,
+            content_index,
+          });
+        }
+      }
       "response.function_call_arguments.delta" => {
         let item_id = value
           .get("item_id")
@@ -735,9 +875,23 @@ fn parse_response_usage(value: &Value) -> Option<ResponseTokenUsage> {
         .and_then(|details| details.get("cached_tokens"))
     })
     .or_else(|| value.get("cachedContentTokenCount"))
+    .or_else(|| value.get("cache_read_input_tokens"))
+    .and_then(Value::as_i64)
+    .unwrap_or(0);
+  let cache_write_input_tokens = value
+    .get("cache_write_input_tokens")
+    .or_else(|| value.get("cache_creation_input_tokens"))
     .and_then(Value::as_i64)
     .unwrap_or(0);
-  let input_tokens = prompt_tokens.saturating_sub(cached_input_tokens);
+  // Anthropic reports `input_tokens` already net of cache reads/writes; every
+  // other provider folds cached tokens into the prompt count.
+  let input_tokens = if value.get("cache_read_input_tokens").is_some()
+    || value.get("cache_creation_input_tokens").is_some()
+  {
+    prompt_tokens
+  } else {
+    prompt_tokens.saturating_sub(cached_input_tokens)
+  };
   let output_tokens = value
     .get("output_tokens")
     .or_else(|| value.get("completion_tokens"))
@@ -764,10 +918,17 @@ fn parse_response_usage(value: &Value) -> Option<ResponseTokenUsage> {
     .get("total_tokens")
     .or_else(|| value.get("totalTokenCount"))
     .and_then(Value::as_i64)
-    .unwrap_or(prompt_tokens + output_tokens + reasoning_output_tokens);
+    .unwrap_or(
+      input_tokens
+        + cached_input_tokens
+        + cache_write_input_tokens
+        + output_tokens
+        + reasoning_output_tokens,
+    );
 
   if input_tokens == 0
     && cached_input_tokens == 0
+    && cache_write_input_tokens == 0
     && output_tokens == 0
     && reasoning_output_tokens == 0
     && total_tokens == 0
@@ -781,6 +942,7 @@ fn parse_response_usage(value: &Value) -> Option<ResponseTokenUsage> {
     output_tokens,
     reasoning_output_tokens,
     total_tokens,
+    cache_write_input_tokens,
   })
 }
 
@@ -811,6 +973,125 @@ mod tests {
     assert_eq!(usage.output_tokens, 8);
   }
 
+  #[test]
+  fn test_anthropic_usage_parser_merges_cache_counts_with_final_output() {
+    let mut parser = AnthropicUsageParser::default();
+    parser.parse(
+      r#"data: {"type":"message_start","message":{"usage":{"input_tokens":10,"cache_read_input_tokens":900,"cache_creation_input_tokens":90,"output_tokens":1}}}"#,
+    );
+    parser.parse(r#"data: {"type":"message_delta","usage":{"output_tokens":42}}"#);
+
+    let usage = parser.retrieve().expect("usage");
+    assert_eq!(usage.input_tokens, 1_000);
+    assert_eq!(usage.cached_input_tokens, 900);
+    assert_eq!(usage.cache_write_input_tokens, 90);
+    assert_eq!(usage.output_tokens, 42);
+    assert_eq!(usage.total_tokens, 1_042);
+  }
+
+  #[test]
+  fn test_openai_usage_parser_reads_cached_prompt_tokens() {
+    let mut parser = OpenAIUsageParser::default();
+    parser.parse(
+      r#"data: {"usage":{"prompt_tokens":1000,"completion_tokens":5,"total_tokens":1005,"prompt_tokens_details":{"cached_tokens":768}}}"#,
+    );
+
+    let usage = parser.retrieve().expect("usage");
+    assert_eq!(usage.cached_input_tokens, 768);
+    assert_eq!(
+      response_token_usage(&usage),
+      ResponseTokenUsage {
+        input_tokens: 232,
+        cached_input_tokens: 768,
+        output_tokens: 5,
+        reasoning_output_tokens: 0,
+        total_tokens: 1005,
+        cache_write_input_tokens: 0,
+      }
+    );
+  }
+
+  #[test]
+  fn test_streaming_processor_anthropic_tool_use_blocks() {
+    let mut processor = StreamingProcessor::new(StreamingConfig {
+      separator: "\n\n",
+      usage_parser: Box::new(AnthropicUsageParser::default()),
+      binary_decoder: None,
+    });
+    let events = processor.push_text(concat!(
+      "data: {\"type\":\"content_block_start\",\"index\":1,\"content_block\":{\"type\":\"tool_use\",\"id\":\"toolu_1\",\"name\":\"read_file\",\"input\":{}}}\n\n",
+      "data: {\"type\":\"content_block_delta\",\"index\":1,\"delta\":{\"type\":\"input_json_delta\",\"partial_json\":\"{\\\"path\\\":\"}}\n\n",
+    ));
+
+    let deltas = events
+      .into_iter()
+      .filter_map(|event| match event.chunk {
+        Some(Chunk::ToolCall { delta }) => Some((delta.id, delta.name, delta.arguments)),
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(
+      deltas,
+      vec![
+        (
+          Some("toolu_1".to_string()),
+          Some("read_file".to_string()),
+          None
+        ),
+        (None, None, Some("{\"path\":".to_string())),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_streaming_processor_anthropic_thinking_blocks() {
+    let mut processor = StreamingProcessor::new(StreamingConfig {
+      separator: "\n\n",
+      usage_parser: Box::new(AnthropicUsageParser::default()),
+      binary_decoder: None,
+    });
+    let events = processor.push_text(concat!(
+      "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"thinking_delta\",\"thinking\":\"Check the file\"}}\n\n",
+      "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"signature_delta\",\"signature\":\"sig-1\"}}\n\n",
+      "data: {\"type\":\"content_block_delta\",\"index\":1,\"delta\":{\"type\":\"text_delta\",\"text\":\"Done\"}}\n\n",
+    ));
+
+    let chunks = events
+      .into_iter()
+      .filter_map(|event| match event.chunk {
+        Some(Chunk::Reasoning { delta }) => {
+          Some(format!("reasoning:{}:{:?}", delta.text, delta.signature))
+        }
+        Some(Chunk::Content { delta }) => Some(format!("content:{}", delta.text)),
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(
+      chunks,
+      vec![
+        "reasoning:Check the file:None".to_string(),
+        "reasoning::Some(\"sig-1\")".to_string(),
+        "content:Done".to_string(),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_streaming_processor_openai_reasoning_content() {
+    let mut processor = StreamingProcessor::new(StreamingConfig {
+      separator: "\n\n",
+      usage_parser: Box::new(OpenAIUsageParser::default()),
+      binary_decoder: None,
+    });
+    let events = processor.push_text(
+      "data: {\"choices\":[{\"delta\":{\"content\":null,\"reasoning_content\":\"hmm\"},\"finish_reason\":null}]}\n\n",
+    );
+    assert!(matches!(
+      &events[0].chunk,
+      Some(Chunk::Reasoning { delta }) if delta.text == "hmm"
+    ));
+  }
+
   #[test]
   fn test_streaming_processor_openai_event() {
     let config = StreamingConfig {
@@ -867,6 +1148,30 @@ mod tests {
         output_tokens: 5,
         reasoning_output_tokens: 7,
         total_tokens: 22,
+        cache_write_input_tokens: 0,
+      }
+    );
+  }
+
+  #[test]
+  fn test_parse_response_usage_anthropic_cache_fields() {
+    let usage = parse_response_usage(&serde_json::json!({
+      "input_tokens": 12,
+      "cache_read_input_tokens": 400,
+      "cache_creation_input_tokens": 30,
+      "output_tokens": 8
+    }))
+    .expect("usage");
+
+    assert_eq!(
+      usage,
+      ResponseTokenUsage {
+        input_tokens: 12,
+        cached_input_tokens: 400,
+        output_tokens: 8,
+        reasoning_output_tokens: 0,
+        total_tokens: 450,
+        cache_write_input_tokens: 30,
       }
     );
   }
@@ -345,6 +345,7 @@ impl AnthropicTransform {
       Message::Tool {
         tool_call_id,
         content,
+        ..
       } => Some(json!({
         "role": "user",
         "content": [{
@@ -676,6 +677,7 @@ fn parse_usage_from_value(value: &Value) -> Option<Usage> {
       input_tokens,
       output_tokens,
       total_tokens,
+      ..Default::default()
     });
   }
 
@@ -699,6 +701,7 @@ fn is_empty_message(message: &Message) -> bool {
     Message::Tool {
       tool_call_id,
       content,
+      ..
     } => tool_call_id.trim().is_empty() || content.trim().is_empty(),
   }
 }
@@ -726,6 +729,7 @@ fn replace_empty_message(message: &mut Message, replacement: &str) {
     Message::Tool {
       tool_call_id,
       content,
+      ..
     } => {
       if tool_call_id.trim().is_empty() {
         *tool_call_id = "tool_call_0".to_string();
@@ -54,6 +54,76 @@ pub struct ChatRequest {
   /// User identifier
   #[serde(default)]
   pub user: Option<String>,
+
+  /// Stable per-thread key that routes repeat prefixes to the same provider
+  /// prompt cache (OpenAI `prompt_cache_key`)
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub prompt_cache_key: Option<String>,
+
+  /// Reasoning effort and summary settings; providers translate these into
+  /// their own request fields
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub reasoning: Option<ReasoningRequest>,
+}
+
+/// How much a reasoning model should think before answering
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningEffort {
+  Minimal,
+  Low,
+  Medium,
+  High,
+}
+
+impl ReasoningEffort {
+  /// OpenAI-style effort string
+  pub fn as_str(self) -> &'static str {
+    match self {
+      ReasoningEffort::Minimal => "minimal",
+      ReasoningEffort::Low => "low",
+      ReasoningEffort::Medium => "medium",
+      ReasoningEffort::High => "high",
+    }
+  }
+
+  /// Thinking token budget for providers that take a budget instead of a
+  /// level (Anthropic extended thinking, Gemini 2.5 `thinkingBudget`)
+  pub fn thinking_budget_tokens(self) -> u32 {
+    match self {
+      // Anthropic rejects budgets below 1024.
+      ReasoningEffort::Minimal => 1_024,
+      ReasoningEffort::Low => 4_096,
+      ReasoningEffort::Medium => 10_000,
+      ReasoningEffort::High => 24_576,
+    }
+  }
+}
+
+/// Which reasoning summary the provider should stream back
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningSummary {
+  Auto,
+  Detailed,
+}
+
+impl ReasoningSummary {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      ReasoningSummary::Auto => "auto",
+      ReasoningSummary::Detailed => "detailed",
+    }
+  }
+}
+
+/// Reasoning settings for one request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReasoningRequest {
+  pub effort: ReasoningEffort,
+  /// `None` asks the provider not to return reasoning text at all
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub summary: Option<ReasoningSummary>,
 }
 
 /// Message in a conversation
@@ -83,9 +153,30 @@ pub enum Message {
 
     /// Content of the tool result
     content: String,
+
+    /// Images the tool returned, for providers that accept them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    images: Vec<ImageData>,
   },
 }
 
+/// Base64-encoded image attached to a message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageData {
+  /// MIME type, e.g. `image/png`
+  pub mime_type: String,
+
+  /// Base64 data without a `data:` prefix
+  pub data: String,
+}
+
+impl ImageData {
+  /// `data:` URL for providers that take images by URL
+  pub fn data_url(&self) -> String {
+    format!("data:{};base64,{}", self.mime_type, self.data)
+  }
+}
+
 impl Message {
   /// Create a system message
   pub fn system(content: impl Into<String>) -> Self {
@@ -110,6 +201,7 @@ impl Message {
     Message::Tool {
       tool_call_id: tool_call_id.into(),
       content: content.into(),
+      images: Vec::new(),
     }
   }
 
@@ -213,11 +305,24 @@ pub struct ToolCall {
 ///
 /// Different providers attach different metadata to tool calls that must
 /// be preserved across the conversation history for proper multi-turn behavior.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ToolCallProviderMeta {
   /// Google Gemini thought signature for preserving reasoning state
   #[serde(default, skip_serializing_if = "Option::is_none")]
   pub thought_signature: Option<String>,
+
+  /// Anthropic thinking block that preceded this call. Only set on the first
+  /// call of a message; it has to be replayed verbatim (with its signature)
+  /// ahead of the `tool_use` blocks while extended thinking is enabled.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub thinking: Option<ThinkingBlock>,
+}
+
+/// A signed reasoning block returned by the provider
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThinkingBlock {
+  pub text: String,
+  pub signature: String,
 }
 
 /// Function call in a tool call
@@ -310,6 +415,14 @@ pub struct Usage {
   #[serde(default)]
   #[serde(rename = "total_tokens")]
   pub total_tokens: u32,
+
+  /// Prompt tokens read from the provider prompt cache (part of `input_tokens`)
+  #[serde(default)]
+  pub cached_input_tokens: u32,
+
+  /// Prompt tokens written into the provider prompt cache (part of `input_tokens`)
+  #[serde(default)]
+  pub cache_write_input_tokens: u32,
 }
 
 /// Streaming chunk from the model
@@ -344,6 +457,20 @@ pub enum Chunk {
     delta: MessageDelta,
   },
 
+  /// Final token usage, emitted just before the message stop
+  #[serde(rename = "usage")]
+  Usage {
+    /// Usage reported by the provider
+    usage: Usage,
+  },
+
+  /// Reasoning (thinking) text, streamed separately from the answer
+  #[serde(rename = "reasoning_delta")]
+  Reasoning {
+    /// Delta
+    delta: ReasoningDelta,
+  },
+
   /// Message stop
   #[serde(rename = "message_stop")]
   MessageStop,
@@ -361,6 +488,18 @@ pub struct ContentDelta {
   pub text: String,
 }
 
+/// Reasoning delta in streaming
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct ReasoningDelta {
+  /// The reasoning text delta
+  #[serde(default)]
+  pub text: String,
+
+  /// Signature closing the current reasoning block (Anthropic)
+  #[serde(default)]
+  pub signature: Option<String>,
+}
+
 /// Tool call delta in streaming
 #[derive(Debug, Clone, Deserialize, Default)]
 pub struct ToolCallDelta {
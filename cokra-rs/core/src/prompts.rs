@@ -15,6 +15,7 @@
 //!                      (config.agents.max_threads > 1)
 //!   agent_spawned.md — injected as a suffix when a spawned teammate is
 //!                      created by build_spawned_agent_system_prompt()
+//!   agent_explorer.md — appended for one-shot read-only `explore` subagents
 //! ```
 
 /// Base system prompt shared by every agent role.
@@ -25,3 +26,6 @@ pub const AGENT_LEADER_SUFFIX: &str = include_str!("prompts/agent_leader.md");
 
 /// Suffix appended to spawned teammate agents' system prompts.
 pub const AGENT_SPAWNED_SUFFIX: &str = include_str!("prompts/agent_spawned.md");
+
+/// Suffix appended to one-shot `explore` subagents' system prompts.
+pub const AGENT_EXPLORER_SUFFIX: &str = include_str!("prompts/agent_explorer.md");
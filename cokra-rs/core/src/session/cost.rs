@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use cokra_protocol::ResponseTokenUsage;
+
+/// Accumulated model usage and spend for one thread.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ThreadCost {
+  pub input_tokens: u64,
+  pub cached_input_tokens: u64,
+  pub cache_write_input_tokens: u64,
+  pub output_tokens: u64,
+  pub reasoning_output_tokens: u64,
+  pub total_tokens: u64,
+  pub cost_usd: f64,
+  /// Requests whose model had no catalog pricing; their spend is unknown.
+  pub unpriced_requests: u64,
+}
+
+impl ThreadCost {
+  fn add_usage(&mut self, usage: &ResponseTokenUsage, cost_usd: Option<f64>) {
+    self.input_tokens += usage.input_tokens.max(0) as u64;
+    self.cached_input_tokens += usage.cached_input_tokens.max(0) as u64;
+    self.cache_write_input_tokens += usage.cache_write_input_tokens.max(0) as u64;
+    self.output_tokens += usage.output_tokens.max(0) as u64;
+    self.reasoning_output_tokens += usage.reasoning_output_tokens.max(0) as u64;
+    self.total_tokens += usage.total_tokens.max(0) as u64;
+    match cost_usd {
+      Some(cost_usd) => self.cost_usd += cost_usd,
+      None => self.unpriced_requests += 1,
+    }
+  }
+
+  fn merge(&mut self, other: &ThreadCost) {
+    self.input_tokens += other.input_tokens;
+    self.cached_input_tokens += other.cached_input_tokens;
+    self.cache_write_input_tokens += other.cache_write_input_tokens;
+    self.output_tokens += other.output_tokens;
+    self.reasoning_output_tokens += other.reasoning_output_tokens;
+    self.total_tokens += other.total_tokens;
+    self.cost_usd += other.cost_usd;
+    self.unpriced_requests += other.unpriced_requests;
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BudgetStatus {
+  WithinBudget,
+  /// Spend crossed the soft limit and nobody has confirmed continuing yet.
+  SoftLimitReached {
+    spent_usd: f64,
+    limit_usd: f64,
+  },
+  HardLimitReached {
+    spent_usd: f64,
+    limit_usd: f64,
+  },
+}
+
+/// Session-wide spend ledger.
+///
+/// One ledger is shared by the root thread and every teammate it spawns, so
+/// budget limits apply to the team as a whole while spend stays attributable
+/// per thread.
+#[derive(Debug, Default)]
+pub struct CostLedger {
+  state: Mutex<CostLedgerState>,
+}
+
+#[derive(Debug, Default)]
+struct CostLedgerState {
+  threads: HashMap<String, ThreadCost>,
+  soft_limit_usd: Option<f64>,
+  hard_limit_usd: Option<f64>,
+  soft_limit_acknowledged: bool,
+}
+
+impl CostLedger {
+  pub fn set_limits(&self, soft_limit_usd: Option<f64>, hard_limit_usd: Option<f64>) {
+    let mut state = self.lock();
+    state.soft_limit_usd = soft_limit_usd;
+    state.hard_limit_usd = hard_limit_usd;
+    state.soft_limit_acknowledged = false;
+  }
+
+  /// Record one completed request and return the thread's new totals.
+  pub fn record(
+    &self,
+    thread_id: &str,
+    usage: &ResponseTokenUsage,
+    cost_usd: Option<f64>,
+  ) -> ThreadCost {
+    let mut state = self.lock();
+    let thread = state.threads.entry(thread_id.to_string()).or_default();
+    thread.add_usage(usage, cost_usd);
+    thread.clone()
+  }
+
+  pub fn thread_cost(&self, thread_id: &str) -> ThreadCost {
+    self
+      .lock()
+      .threads
+      .get(thread_id)
+      .cloned()
+      .unwrap_or_default()
+  }
+
+  pub fn thread_costs(&self) -> HashMap<String, ThreadCost> {
+    self.lock().threads.clone()
+  }
+
+  pub fn session_cost(&self) -> ThreadCost {
+    let state = self.lock();
+    let mut total = ThreadCost::default();
+    for thread in state.threads.values() {
+      total.merge(thread);
+    }
+    total
+  }
+
+  pub fn budget_status(&self) -> BudgetStatus {
+    let spent_usd = self.session_cost().cost_usd;
+    let state = self.lock();
+    if let Some(limit_usd) = state.hard_limit_usd
+      && spent_usd >= limit_usd
+    {
+      return BudgetStatus::HardLimitReached {
+        spent_usd,
+        limit_usd,
+      };
+    }
+    if let Some(limit_usd) = state.soft_limit_usd
+      && spent_usd >= limit_usd
+      && !state.soft_limit_acknowledged
+    {
+      return BudgetStatus::SoftLimitReached {
+        spent_usd,
+        limit_usd,
+      };
+    }
+    BudgetStatus::WithinBudget
+  }
+
+  /// The user chose to keep going past the soft limit; don't ask again.
+  pub fn acknowledge_soft_limit(&self) {
+    self.lock().soft_limit_acknowledged = true;
+  }
+
+  fn lock(&self) -> std::sync::MutexGuard<'_, CostLedgerState> {
+    self
+      .state
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  fn usage(total_tokens: i64) -> ResponseTokenUsage {
+    ResponseTokenUsage {
+      input_tokens: total_tokens,
+      total_tokens,
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn record_aggregates_per_thread_and_session() {
+    let ledger = CostLedger::default();
+    ledger.record("root", &usage(100), Some(0.5));
+    ledger.record("root", &usage(50), None);
+    ledger.record("child", &usage(10), Some(0.25));
+
+    let root = ledger.thread_cost("root");
+    assert_eq!(root.total_tokens, 150);
+    assert_eq!(root.cost_usd, 0.5);
+    assert_eq!(root.unpriced_requests, 1);
+
+    let session = ledger.session_cost();
+    assert_eq!(session.total_tokens, 160);
+    assert_eq!(session.cost_usd, 0.75);
+    assert_eq!(ledger.thread_costs().len(), 2);
+  }
+
+  #[test]
+  fn soft_limit_asks_once_until_limits_change() {
+    let ledger = CostLedger::default();
+    ledger.set_limits(Some(1.0), None);
+    ledger.record("root", &usage(1), Some(1.5));
+
+    assert_eq!(
+      ledger.budget_status(),
+      BudgetStatus::SoftLimitReached {
+        spent_usd: 1.5,
+        limit_usd: 1.0,
+      }
+    );
+    ledger.acknowledge_soft_limit();
+    assert_eq!(ledger.budget_status(), BudgetStatus::WithinBudget);
+
+    ledger.set_limits(Some(1.0), None);
+    assert!(matches!(
+      ledger.budget_status(),
+      BudgetStatus::SoftLimitReached { .. }
+    ));
+  }
+
+  #[test]
+  fn hard_limit_covers_all_threads_and_wins_over_soft() {
+    let ledger = CostLedger::default();
+    ledger.set_limits(Some(1.0), Some(2.0));
+    ledger.acknowledge_soft_limit();
+    ledger.record("root", &usage(1), Some(1.0));
+    ledger.record("child", &usage(1), Some(1.0));
+
+    assert_eq!(
+      ledger.budget_status(),
+      BudgetStatus::HardLimitReached {
+        spent_usd: 2.0,
+        limit_usd: 2.0,
+      }
+    );
+  }
+}
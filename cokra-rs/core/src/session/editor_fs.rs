@@ -0,0 +1,62 @@
+use std::path::Path;
+
+use async_trait::async_trait;
+
+/// File access served by the editor hosting Cokra, e.g. an ACP client.
+///
+/// When installed on a session, `read_file`, `read_many_files`, `write_file`
+/// and `edit_file` go through it so the agent sees unsaved buffer contents and
+/// its edits land in open buffers. `apply_patch` still works on disk.
+#[async_trait]
+pub trait EditorFileSystem: Send + Sync {
+  async fn read_text_file(&self, path: &Path) -> anyhow::Result<String>;
+
+  async fn write_text_file(&self, path: &Path, content: &str) -> anyhow::Result<()>;
+}
+
+/// In-memory buffers standing in for an editor in handler tests.
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct BufferFileSystem {
+  buffers: std::sync::Mutex<std::collections::HashMap<std::path::PathBuf, String>>,
+}
+
+#[cfg(test)]
+impl BufferFileSystem {
+  pub(crate) fn with_buffer(self, path: &Path, content: &str) -> Self {
+    self.set(path, content);
+    self
+  }
+
+  pub(crate) fn buffer(&self, path: &Path) -> Option<String> {
+    self
+      .buffers
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .get(path)
+      .cloned()
+  }
+
+  fn set(&self, path: &Path, content: &str) {
+    self
+      .buffers
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .insert(path.to_path_buf(), content.to_string());
+  }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl EditorFileSystem for BufferFileSystem {
+  async fn read_text_file(&self, path: &Path) -> anyhow::Result<String> {
+    self
+      .buffer(path)
+      .ok_or_else(|| anyhow::anyhow!("no buffer for {}", path.display()))
+  }
+
+  async fn write_text_file(&self, path: &Path, content: &str) -> anyhow::Result<()> {
+    self.set(path, content);
+    Ok(())
+  }
+}
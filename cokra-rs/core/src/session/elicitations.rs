@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+use tokio::sync::Mutex;
+use tokio::sync::oneshot;
+
+use cokra_protocol::ElicitationAction;
+
+/// What the user answered to an MCP elicitation form.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElicitationResponse {
+  pub action: ElicitationAction,
+  pub content: Option<Value>,
+}
+
+impl ElicitationResponse {
+  pub fn cancelled() -> Self {
+    Self {
+      action: ElicitationAction::Cancel,
+      content: None,
+    }
+  }
+}
+
+/// Elicitation ids are only unique per server, so entries are keyed by
+/// `(server_name, request_id)`.
+type ElicitationKey = (String, String);
+
+#[derive(Default)]
+pub struct PendingElicitations {
+  state: Mutex<PendingElicitationsState>,
+}
+
+#[derive(Default)]
+struct PendingElicitationsState {
+  by_key: HashMap<ElicitationKey, oneshot::Sender<ElicitationResponse>>,
+  keys_by_turn: HashMap<String, Vec<ElicitationKey>>,
+}
+
+impl PendingElicitations {
+  pub async fn insert(
+    &self,
+    server_name: String,
+    request_id: String,
+    turn_id: String,
+    tx: oneshot::Sender<ElicitationResponse>,
+  ) -> Option<oneshot::Sender<ElicitationResponse>> {
+    let key = (server_name, request_id);
+    let mut state = self.state.lock().await;
+    let previous = state.by_key.insert(key.clone(), tx);
+
+    if previous.is_some() {
+      for keys in state.keys_by_turn.values_mut() {
+        keys.retain(|existing| existing != &key);
+      }
+    }
+
+    state.keys_by_turn.entry(turn_id).or_default().push(key);
+    previous
+  }
+
+  pub async fn remove(
+    &self,
+    server_name: &str,
+    request_id: &str,
+  ) -> Option<oneshot::Sender<ElicitationResponse>> {
+    let key = (server_name.to_string(), request_id.to_string());
+    let mut state = self.state.lock().await;
+    let removed = state.by_key.remove(&key);
+
+    removed.as_ref()?;
+
+    state.keys_by_turn.retain(|_, keys| {
+      keys.retain(|existing| existing != &key);
+      !keys.is_empty()
+    });
+
+    removed
+  }
+
+  pub async fn clear_turn(&self, turn_id: &str) -> Vec<oneshot::Sender<ElicitationResponse>> {
+    let mut state = self.state.lock().await;
+    let Some(keys) = state.keys_by_turn.remove(turn_id) else {
+      return Vec::new();
+    };
+
+    keys
+      .into_iter()
+      .filter_map(|key| state.by_key.remove(&key))
+      .collect()
+  }
+}
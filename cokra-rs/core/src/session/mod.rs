@@ -1,4 +1,7 @@
 mod approvals;
+mod cost;
+mod editor_fs;
+mod elicitations;
 mod user_input;
 
 use std::collections::VecDeque;
@@ -10,14 +13,16 @@ use tokio::sync::broadcast;
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
 
-use crate::compaction::estimate_message_tokens;
 use crate::compaction::find_safe_tail_start_index;
 use crate::compaction::first_non_system_index;
 use crate::model::Message;
 use crate::model::Usage;
 use crate::shell::Shell;
+use crate::tokenizer::TokenCounter;
+use crate::tokenizer::next_calibration;
 use crate::turn::response_items::ResponseItem;
 use approvals::PendingApprovals;
+use cokra_protocol::ElicitationRequestEvent;
 use cokra_protocol::EventMsg;
 use cokra_protocol::ExecApprovalRequestEvent;
 use cokra_protocol::RequestUserInputEvent;
@@ -25,8 +30,17 @@ use cokra_protocol::ReviewDecision;
 use cokra_protocol::TurnId;
 use cokra_protocol::UserInput;
 use cokra_protocol::user_input::RequestUserInputResponse;
+use elicitations::PendingElicitations;
 use user_input::PendingUserInputs;
 
+pub use cost::BudgetStatus;
+pub use cost::CostLedger;
+pub use cost::ThreadCost;
+#[cfg(test)]
+pub(crate) use editor_fs::BufferFileSystem;
+pub use editor_fs::EditorFileSystem;
+pub use elicitations::ElicitationResponse;
+
 /// Runtime session state for one conversation thread.
 ///
 /// Spec 3.2: Session caches the resolved user shell so that
@@ -39,11 +53,16 @@ pub struct Session {
   event_tx: broadcast::Sender<cokra_protocol::EventMsg>,
   pending_approvals: Arc<PendingApprovals>,
   pending_user_inputs: Arc<PendingUserInputs>,
+  pending_elicitations: Arc<PendingElicitations>,
   active_turn_state: Arc<RwLock<ActiveTurnState>>,
   /// Spec 3.2: cached user shell, resolved once at session creation.
   cached_shell: Arc<RwLock<Shell>>,
   token_usage: Arc<RwLock<TokenUsageState>>,
+  /// Shared with every teammate spawned from this session.
+  cost_ledger: Arc<CostLedger>,
   model_switch_state: Arc<RwLock<ModelSwitchState>>,
+  /// Set by hosts that own the file buffers, e.g. an ACP editor.
+  editor_fs: Arc<RwLock<Option<Arc<dyn EditorFileSystem>>>>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -51,6 +70,23 @@ pub struct TokenUsageState {
   pub input_tokens: u64,
   pub output_tokens: u64,
   pub total_tokens: u64,
+  /// Prompt tokens the last request read from the provider prompt cache.
+  pub cached_input_tokens: u64,
+  /// Model that `token_scale` was learned from.
+  pub calibrated_model: Option<String>,
+  /// Reported prompt tokens per estimated token for `calibrated_model`.
+  pub token_scale: Option<f64>,
+  /// Turn that `turn_cost_usd` is accumulating for.
+  pub turn_id: Option<String>,
+  pub turn_cost_usd: f64,
+}
+
+/// Running spend after one request has been recorded.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RequestCostTotals {
+  pub turn_cost_usd: f64,
+  pub thread_cost_usd: f64,
+  pub session_cost_usd: f64,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
@@ -91,13 +127,34 @@ impl Session {
       event_tx,
       pending_approvals: Arc::new(PendingApprovals::default()),
       pending_user_inputs: Arc::new(PendingUserInputs::default()),
+      pending_elicitations: Arc::new(PendingElicitations::default()),
       active_turn_state: Arc::new(RwLock::new(ActiveTurnState::default())),
       cached_shell: Arc::new(RwLock::new(shell)),
       token_usage: Arc::new(RwLock::new(TokenUsageState::default())),
+      cost_ledger: Arc::new(CostLedger::default()),
       model_switch_state: Arc::new(RwLock::new(ModelSwitchState::default())),
+      editor_fs: Arc::new(RwLock::new(None)),
     }
   }
 
+  /// Share an existing spend ledger, e.g. the root session's for a teammate.
+  pub fn with_cost_ledger(mut self, cost_ledger: Arc<CostLedger>) -> Self {
+    self.cost_ledger = cost_ledger;
+    self
+  }
+
+  pub fn cost_ledger(&self) -> Arc<CostLedger> {
+    Arc::clone(&self.cost_ledger)
+  }
+
+  pub async fn set_editor_file_system(&self, editor_fs: Option<Arc<dyn EditorFileSystem>>) {
+    *self.editor_fs.write().await = editor_fs;
+  }
+
+  pub async fn editor_file_system(&self) -> Option<Arc<dyn EditorFileSystem>> {
+    self.editor_fs.read().await.clone()
+  }
+
   /// Spec 3.2: get the session-cached user shell.
   pub async fn user_shell(&self) -> Shell {
     self.cached_shell.read().await.clone()
@@ -126,6 +183,7 @@ impl Session {
   /// Strategy: always keep all `System` messages, then walk non-system messages
   /// from newest to oldest until budget is exhausted.
   pub async fn get_history_for_prompt(&self, max_tokens: usize) -> Vec<Message> {
+    let counter = self.token_counter().await;
     let history = self.history.read().await;
     if max_tokens == 0 {
       return history
@@ -143,8 +201,9 @@ impl Session {
     let Some(boundary_start) = first_non_system_index(&history) else {
       return systems;
     };
-    let first_kept_index = find_safe_tail_start_index(&history, boundary_start, max_tokens, true)
-      .unwrap_or(boundary_start);
+    let first_kept_index =
+      find_safe_tail_start_index(&history, boundary_start, max_tokens, true, &counter)
+        .unwrap_or(boundary_start);
 
     systems.extend(
       history[first_kept_index..]
@@ -242,12 +301,88 @@ impl Session {
     token_usage.input_tokens = usage.input_tokens as u64;
     token_usage.output_tokens = usage.output_tokens as u64;
     token_usage.total_tokens = usage.total_tokens as u64;
+    token_usage.cached_input_tokens = usage.cached_input_tokens as u64;
+  }
+
+  /// Forget the cached prompt prefix after history was rewritten, returning
+  /// how many cached tokens it held.
+  pub async fn take_cached_input_tokens(&self) -> u64 {
+    std::mem::take(&mut self.token_usage.write().await.cached_input_tokens)
+  }
+
+  /// Token counter for the session's current model.
+  pub(crate) async fn token_counter(&self) -> TokenCounter {
+    let model = self.model_switch_state.read().await.current_model.clone();
+    self
+      .token_counter_for(model.as_deref().unwrap_or_default())
+      .await
+  }
+
+  /// Token counter for `model`, scaled by what its provider has reported so
+  /// far in this session.
+  pub(crate) async fn token_counter_for(&self, model: &str) -> TokenCounter {
+    let token_usage = self.token_usage.read().await;
+    let scale = token_usage
+      .token_scale
+      .filter(|_| token_usage.calibrated_model.as_deref() == Some(model));
+    TokenCounter::for_model(model).with_calibration(scale)
+  }
+
+  /// Learn from one request: `estimated_tokens` is the uncalibrated size of
+  /// what was sent, `reported_tokens` the full prompt the provider billed.
+  pub(crate) async fn calibrate_token_counter(
+    &self,
+    model: &str,
+    estimated_tokens: usize,
+    reported_tokens: u64,
+  ) {
+    let mut token_usage = self.token_usage.write().await;
+    if token_usage.calibrated_model.as_deref() != Some(model) {
+      token_usage.calibrated_model = Some(model.to_string());
+      token_usage.token_scale = None;
+    }
+    token_usage.token_scale =
+      next_calibration(token_usage.token_scale, estimated_tokens, reported_tokens);
   }
 
   pub async fn get_total_token_usage(&self) -> u64 {
     self.token_usage.read().await.total_tokens
   }
 
+  /// Attribute one completed request to this thread and `turn_id`.
+  ///
+  /// `cost_usd` is `None` when the model has no catalog pricing; the tokens are
+  /// still counted so per-thread usage stays complete. A request made outside
+  /// any turn (`turn_id` is `None`) leaves the running turn total untouched and
+  /// reports it as 0.
+  pub async fn record_request_cost(
+    &self,
+    turn_id: Option<&str>,
+    usage: &cokra_protocol::ResponseTokenUsage,
+    cost_usd: Option<f64>,
+  ) -> RequestCostTotals {
+    let turn_cost_usd = match turn_id {
+      Some(turn_id) => {
+        let mut token_usage = self.token_usage.write().await;
+        if token_usage.turn_id.as_deref() != Some(turn_id) {
+          token_usage.turn_id = Some(turn_id.to_string());
+          token_usage.turn_cost_usd = 0.0;
+        }
+        token_usage.turn_cost_usd += cost_usd.unwrap_or_default();
+        token_usage.turn_cost_usd
+      }
+      None => 0.0,
+    };
+    let thread = self
+      .cost_ledger
+      .record(&self.thread_id.to_string(), usage, cost_usd);
+    RequestCostTotals {
+      turn_cost_usd,
+      thread_cost_usd: thread.cost_usd,
+      session_cost_usd: self.cost_ledger.session_cost().cost_usd,
+    }
+  }
+
   pub async fn track_model_selection(&self, model: impl Into<String>) -> ModelSwitchState {
     let model = model.into();
     let mut state = self.model_switch_state.write().await;
@@ -273,11 +408,12 @@ impl Session {
 
   /// Drop oldest non-system messages until usage is below `target_total_tokens`.
   pub async fn compact_history_to_token_target(&self, target_total_tokens: usize) {
+    let counter = self.token_counter().await;
     let mut history = self.history.write().await;
     let system_tokens = history
       .iter()
       .filter(|msg| matches!(msg, Message::System(_)))
-      .map(estimate_message_tokens)
+      .map(|msg| counter.count_message(msg))
       .sum::<usize>();
     let Some(boundary_start) = first_non_system_index(&history) else {
       return;
@@ -289,9 +425,13 @@ impl Session {
       return;
     }
 
-    let Some(first_kept_index) =
-      find_safe_tail_start_index(&history, boundary_start, allowed_non_system_tokens, true)
-    else {
+    let Some(first_kept_index) = find_safe_tail_start_index(
+      &history,
+      boundary_start,
+      allowed_non_system_tokens,
+      true,
+      &counter,
+    ) else {
       return;
     };
     if first_kept_index <= boundary_start {
@@ -372,6 +512,17 @@ impl Session {
     self.pending_user_inputs.clear_turn(turn_id).await.len()
   }
 
+  /// Cancels every elicitation still open for `turn_id`, so the MCP server
+  /// waiting on it gets an answer instead of a dropped request.
+  pub async fn clear_pending_elicitations_for_turn(&self, turn_id: &str) -> usize {
+    let pending = self.pending_elicitations.clear_turn(turn_id).await;
+    let total = pending.len();
+    for tx in pending {
+      let _ = tx.send(ElicitationResponse::cancelled());
+    }
+    total
+  }
+
   pub async fn emit_exec_approval_request(
     &self,
     thread_id: String,
@@ -497,6 +648,63 @@ impl Session {
     true
   }
 
+  /// Show an MCP server's form to the user and wait for the answer.
+  ///
+  /// Elicitations arrive in the middle of an MCP tool call, so they are tied
+  /// to the active turn (or `""` between turns) and cancelled with it.
+  pub async fn request_mcp_elicitation(
+    &self,
+    server_name: String,
+    request_id: String,
+    message: String,
+    requested_schema: serde_json::Value,
+    tx_event: Option<mpsc::Sender<EventMsg>>,
+  ) -> ElicitationResponse {
+    let turn_id = self.active_turn_id().await.unwrap_or_default();
+    let (tx, rx) = oneshot::channel();
+    let previous = self
+      .pending_elicitations
+      .insert(server_name.clone(), request_id.clone(), turn_id.clone(), tx)
+      .await;
+    if let Some(previous) = previous {
+      tracing::warn!("overwriting existing pending elicitation {server_name}/{request_id}");
+      let _ = previous.send(ElicitationResponse::cancelled());
+    }
+
+    let event = EventMsg::ElicitationRequest(ElicitationRequestEvent {
+      thread_id: self.thread_id.to_string(),
+      turn_id,
+      server_name,
+      id: request_id,
+      message,
+      requested_schema,
+    });
+    self.emit_event(event.clone());
+    if let Some(tx_event) = tx_event {
+      let _ = tx_event.send(event).await;
+    }
+
+    rx.await
+      .unwrap_or_else(|_| ElicitationResponse::cancelled())
+  }
+
+  pub async fn notify_mcp_elicitation(
+    &self,
+    server_name: &str,
+    request_id: &str,
+    response: ElicitationResponse,
+  ) -> bool {
+    let Some(tx) = self
+      .pending_elicitations
+      .remove(server_name, request_id)
+      .await
+    else {
+      return false;
+    };
+    let _ = tx.send(response);
+    true
+  }
+
   pub fn thread_id(&self) -> Option<&cokra_protocol::ThreadId> {
     Some(&self.thread_id)
   }
@@ -517,13 +725,80 @@ impl Default for Session {
 mod tests {
   use tokio::sync::oneshot;
 
+  use super::RequestCostTotals;
   use super::Session;
   use super::SteerInputError;
   use crate::model::Message;
+  use crate::model::Usage;
   use cokra_protocol::ReviewDecision;
   use cokra_protocol::UserInput;
   use cokra_protocol::user_input::RequestUserInputResponse;
 
+  #[tokio::test]
+  async fn take_cached_input_tokens_reports_and_clears_cached_prefix() {
+    let session = Session::new();
+    session
+      .set_token_usage(&Usage {
+        input_tokens: 1_000,
+        cached_input_tokens: 900,
+        ..Default::default()
+      })
+      .await;
+
+    assert_eq!(session.take_cached_input_tokens().await, 900);
+    assert_eq!(session.take_cached_input_tokens().await, 0);
+  }
+
+  #[tokio::test]
+  async fn record_request_cost_tracks_turn_thread_and_shared_session_totals() {
+    let root = Session::new();
+    let child = Session::new().with_cost_ledger(root.cost_ledger());
+    let usage = cokra_protocol::ResponseTokenUsage {
+      input_tokens: 10,
+      total_tokens: 10,
+      ..Default::default()
+    };
+
+    root
+      .record_request_cost(Some("turn-1"), &usage, Some(0.25))
+      .await;
+    let totals = root
+      .record_request_cost(Some("turn-1"), &usage, Some(0.5))
+      .await;
+    assert_eq!(
+      totals,
+      RequestCostTotals {
+        turn_cost_usd: 0.75,
+        thread_cost_usd: 0.75,
+        session_cost_usd: 0.75,
+      }
+    );
+
+    let totals = root.record_request_cost(Some("turn-2"), &usage, None).await;
+    assert_eq!(totals.turn_cost_usd, 0.0);
+
+    // A request between turns counts for the thread, not for any turn.
+    let totals = root.record_request_cost(None, &usage, Some(0.5)).await;
+    assert_eq!(totals.turn_cost_usd, 0.0);
+    assert_eq!(totals.thread_cost_usd, 1.25);
+    let totals = root
+      .record_request_cost(Some("turn-2"), &usage, Some(0.25))
+      .await;
+    assert_eq!(totals.turn_cost_usd, 0.25);
+
+    let totals = child
+      .record_request_cost(Some("turn-a"), &usage, Some(1.0))
+      .await;
+    assert_eq!(
+      totals,
+      RequestCostTotals {
+        turn_cost_usd: 1.0,
+        thread_cost_usd: 1.0,
+        session_cost_usd: 2.5,
+      }
+    );
+  }
+
   #[tokio::test]
   async fn pending_approval_insert_notify_round_trip() {
     let session = Session::new();
@@ -579,6 +854,39 @@ mod tests {
     ));
   }
 
+  #[tokio::test]
+  async fn clear_turn_cancels_pending_elicitations() {
+    let session = std::sync::Arc::new(Session::new());
+    session.begin_turn("turn-a".to_string()).await;
+    let waiter = {
+      let session = std::sync::Arc::clone(&session);
+      tokio::spawn(async move {
+        session
+          .request_mcp_elicitation(
+            "forms".to_string(),
+            "e1".to_string(),
+            "Confirm?".to_string(),
+            serde_json::json!({ "type": "object", "properties": {} }),
+            None,
+          )
+          .await
+      })
+    };
+    while session.clear_pending_elicitations_for_turn("turn-a").await == 0 {
+      tokio::task::yield_now().await;
+    }
+
+    assert_eq!(
+      waiter.await.unwrap(),
+      super::ElicitationResponse::cancelled()
+    );
+    assert!(
+      !session
+        .notify_mcp_elicitation("forms", "e1", super::ElicitationResponse::cancelled())
+        .await
+    );
+  }
+
   #[tokio::test]
   async fn clear_turn_denies_pending_waiters() {
     let session = Session::new();
@@ -701,6 +1009,24 @@ mod tests {
     assert!(session.take_pending_inputs().await.is_empty());
   }
 
+  #[tokio::test]
+  async fn token_counter_learns_scale_per_model() {
+    let session = Session::new();
+    let text = "a".repeat(4_000);
+    let uncalibrated = session.token_counter_for("claude-sonnet-4-5").await;
+    assert_eq!(uncalibrated.count_text(&text), 1_000);
+
+    session
+      .calibrate_token_counter("claude-sonnet-4-5", 1_000, 1_200)
+      .await;
+    let calibrated = session.token_counter_for("claude-sonnet-4-5").await;
+    assert_eq!(calibrated.count_text(&text), 1_200);
+
+    // A different model starts from the raw estimate again.
+    let other = session.token_counter_for("gemini-2.5-pro").await;
+    assert_eq!(other.count_text(&text), 1_000);
+  }
+
   #[tokio::test]
   async fn get_history_for_prompt_keeps_system_and_recent_messages() {
     let session = Session::new();
@@ -771,6 +1097,7 @@ mod tests {
         Message::Tool {
           tool_call_id: "call-1".to_string(),
           content: "content".to_string(),
+          images: Vec::new(),
         },
         Message::Assistant {
           content: Some("done".to_string()),
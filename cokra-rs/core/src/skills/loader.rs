@@ -158,12 +158,16 @@ fn project_root_candidates(path: &Path) -> Vec<CokraRoot> {
 }
 
 pub(crate) async fn collect_markdown_files(dir: &Path) -> Vec<PathBuf> {
+  collect_files_with_extension(dir, "md").await
+}
+
+pub(crate) async fn collect_files_with_extension(dir: &Path, extension: &str) -> Vec<PathBuf> {
   let mut files = collect_files(dir).await;
   files.retain(|path| {
     path
       .extension()
-      .and_then(|extension| extension.to_str())
-      .map(|extension| extension.eq_ignore_ascii_case("md"))
+      .and_then(|candidate| candidate.to_str())
+      .map(|candidate| candidate.eq_ignore_ascii_case(extension))
       .unwrap_or(false)
   });
   files.sort();
@@ -0,0 +1,505 @@
+//! Canned model traffic for tests in this and dependent crates: a scripted
+//! provider, and a recorded tool-calling turn replayed through the real
+//! OpenAI provider.
+//!
+//! Enabled by the `test-support` feature; front ends pull it in through their
+//! dev-dependencies.
+
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use cokra_config::ApprovalMode;
+use cokra_config::ApprovalPolicy;
+use cokra_config::PatchApproval;
+use cokra_config::SandboxConfig;
+use cokra_config::SandboxMode;
+use cokra_config::ShellApproval;
+use cokra_protocol::EventMsg;
+use futures::Stream;
+use reqwest::Client;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::sync::Semaphore;
+use tokio::sync::mpsc;
+
+use crate::model::ChatRequest;
+use crate::model::ChatResponse;
+use crate::model::Choice;
+use crate::model::ChoiceMessage;
+use crate::model::Chunk;
+use crate::model::ContentDelta;
+use crate::model::ListModelsResponse;
+use crate::model::Message;
+use crate::model::ModelClient;
+use crate::model::ModelInfo;
+use crate::model::ModelProvider;
+use crate::model::ProviderConfig;
+use crate::model::ProviderRegistry;
+use crate::model::Result;
+use crate::model::ToolCall;
+use crate::model::ToolCallDelta;
+use crate::model::ToolCallFunction;
+use crate::model::Usage;
+use crate::model::cassette;
+use crate::model::cassette::Cassette;
+use crate::model::cassette::CassetteMode;
+use crate::model::providers::OpenAIProvider;
+use crate::session::Session;
+use crate::tools::FunctionCallError;
+use crate::tools::ToolInvocation;
+use crate::tools::ToolOutput;
+use crate::tools::ToolPayload;
+use crate::tools::ToolValidator;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+use crate::tools::registry::ToolRegistry;
+use crate::tools::router::ToolRouter;
+use crate::turn::TurnConfig;
+use crate::turn::TurnExecutor;
+use crate::turn::TurnResult;
+use crate::turn::UserInput;
+
+pub const SCRIPTED_PROVIDER_ID: &str = "mock";
+pub const SCRIPTED_MODEL: &str = "mock/default";
+
+#[derive(Debug, Clone)]
+enum Reply {
+  Text(String),
+  /// The text of the request's last user message.
+  Echo,
+  /// A call to `name`, so the turn never ends by itself.
+  ToolCall {
+    name: String,
+    arguments: String,
+  },
+}
+
+/// Answers every request, streamed or not, with the same reply.
+#[derive(Debug)]
+pub struct ScriptedProvider {
+  client: Client,
+  config: ProviderConfig,
+  reply: Reply,
+  usage: Option<Usage>,
+  finish_reason: String,
+  requests: Arc<Mutex<Vec<ChatRequest>>>,
+  gate: Option<Arc<Semaphore>>,
+}
+
+impl ScriptedProvider {
+  pub fn replying(text: impl Into<String>) -> Self {
+    Self::new(Reply::Text(text.into()))
+  }
+
+  pub fn echoing() -> Self {
+    Self::new(Reply::Echo)
+  }
+
+  /// Calls `name` with `arguments` on every request.
+  pub fn calling(name: impl Into<String>, arguments: serde_json::Value) -> Self {
+    Self::new(Reply::ToolCall {
+      name: name.into(),
+      arguments: arguments.to_string(),
+    })
+  }
+
+  fn new(reply: Reply) -> Self {
+    Self {
+      client: Client::new(),
+      config: ProviderConfig {
+        provider_id: SCRIPTED_PROVIDER_ID.to_string(),
+        ..Default::default()
+      },
+      reply,
+      usage: None,
+      finish_reason: "stop".to_string(),
+      requests: Arc::default(),
+      gate: None,
+    }
+  }
+
+  /// The requests this provider receives, in order.
+  pub fn requests(&self) -> Arc<Mutex<Vec<ChatRequest>>> {
+    Arc::clone(&self.requests)
+  }
+
+  /// Report `usage` for every reply, streamed ones included.
+  pub fn with_usage(mut self, usage: Usage) -> Self {
+    self.usage = Some(usage);
+    self
+  }
+
+  pub fn with_finish_reason(mut self, finish_reason: impl Into<String>) -> Self {
+    self.finish_reason = finish_reason.into();
+    self
+  }
+
+  /// Hold each streamed reply until `gate` hands out a permit, so a test can
+  /// act while a turn is still running.
+  pub fn gated(mut self, gate: Arc<Semaphore>) -> Self {
+    self.gate = Some(gate);
+    self
+  }
+
+  /// A model client whose only, default provider is this one.
+  pub async fn into_model_client(self) -> Arc<ModelClient> {
+    let registry = Arc::new(ProviderRegistry::new());
+    registry.register(self).await;
+    registry
+      .set_default(SCRIPTED_PROVIDER_ID)
+      .await
+      .expect("set default provider");
+    Arc::new(ModelClient::new(registry).await.expect("model client"))
+  }
+
+  /// The `-c` overrides that select this provider.
+  pub fn config_overrides() -> Vec<(String, String)> {
+    vec![
+      (
+        "models.provider".to_string(),
+        SCRIPTED_PROVIDER_ID.to_string(),
+      ),
+      ("models.model".to_string(), SCRIPTED_MODEL.to_string()),
+    ]
+  }
+
+  fn reply_to(&self, request: &ChatRequest) -> Reply {
+    self
+      .requests
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .push(request.clone());
+    match &self.reply {
+      Reply::Echo => Reply::Text(match request.messages.last() {
+        Some(Message::User(text)) => text.clone(),
+        _ => String::new(),
+      }),
+      reply => reply.clone(),
+    }
+  }
+
+  /// A distinct id per request, so repeated calls stay apart in history.
+  fn call_id(&self) -> String {
+    let requests = self
+      .requests
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .len();
+    format!("scripted-call-{requests}")
+  }
+}
+
+#[async_trait]
+impl ModelProvider for ScriptedProvider {
+  fn provider_id(&self) -> &'static str {
+    SCRIPTED_PROVIDER_ID
+  }
+
+  fn provider_name(&self) -> &'static str {
+    "Scripted Provider"
+  }
+
+  fn default_models(&self) -> Vec<&'static str> {
+    vec!["default"]
+  }
+
+  async fn chat_completion(&self, request: ChatRequest) -> Result<ChatResponse> {
+    let (content, tool_calls, finish_reason) = match self.reply_to(&request) {
+      Reply::ToolCall { name, arguments } => (
+        None,
+        Some(vec![ToolCall {
+          id: self.call_id(),
+          call_type: "function".to_string(),
+          function: ToolCallFunction { name, arguments },
+          provider_meta: None,
+        }]),
+        "tool_calls".to_string(),
+      ),
+      Reply::Text(text) => (Some(text), None, self.finish_reason.clone()),
+      Reply::Echo => unreachable!("echo replies are resolved per request"),
+    };
+    Ok(ChatResponse {
+      id: "scripted".to_string(),
+      object_type: "chat.completion".to_string(),
+      created: 0,
+      model: request.model.clone(),
+      choices: vec![Choice {
+        index: 0,
+        message: ChoiceMessage {
+          role: "assistant".to_string(),
+          content,
+          tool_calls,
+        },
+        finish_reason: Some(finish_reason),
+      }],
+      usage: self.usage.clone().unwrap_or_default(),
+      extra: Default::default(),
+    })
+  }
+
+  async fn chat_completion_stream(
+    &self,
+    request: ChatRequest,
+  ) -> Result<Pin<Box<dyn Stream<Item = Result<Chunk>> + Send>>> {
+    // Recorded before the gate, so a held request is already visible.
+    let reply = self.reply_to(&request);
+    if let Some(gate) = &self.gate
+      && let Ok(permit) = gate.acquire().await
+    {
+      permit.forget();
+    }
+    let mut chunks = vec![match reply {
+      Reply::ToolCall { name, arguments } => Chunk::ToolCall {
+        delta: ToolCallDelta {
+          id: Some(self.call_id()),
+          name: Some(name),
+          arguments: Some(arguments),
+          thought_signature: None,
+        },
+      },
+      Reply::Text(text) => Chunk::Content {
+        delta: ContentDelta { text },
+      },
+      Reply::Echo => unreachable!("echo replies are resolved per request"),
+    }];
+    if let Some(usage) = &self.usage {
+      chunks.push(Chunk::Usage {
+        usage: usage.clone(),
+      });
+    }
+    chunks.push(Chunk::MessageStop);
+    Ok(Box::pin(futures::stream::iter(chunks.into_iter().map(Ok))))
+  }
+
+  async fn list_models(&self) -> Result<ListModelsResponse> {
+    Ok(ListModelsResponse {
+      object_type: "list".to_string(),
+      data: vec![ModelInfo {
+        id: SCRIPTED_MODEL.to_string(),
+        object_type: "model".to_string(),
+        created: 0,
+        owned_by: Some(SCRIPTED_PROVIDER_ID.to_string()),
+      }],
+    })
+  }
+
+  async fn validate_auth(&self) -> Result<()> {
+    Ok(())
+  }
+
+  fn client(&self) -> &Client {
+    &self.client
+  }
+
+  fn config(&self) -> &ProviderConfig {
+    &self.config
+  }
+}
+
+/// The prompt of the recorded turn; short enough to skip auto context.
+pub const READ_FILE_TURN_PROMPT: &str = "read demo";
+/// What the recorded turn's `read_file` call returns.
+pub const READ_FILE_TURN_TOOL_OUTPUT: &str = "hello from tool";
+
+/// What the loopback upstream answers while recording, one body per request.
+const READ_FILE_TURN_UPSTREAM: &[&str] = &[
+  concat!(
+    "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":0,\"model\":\"gpt-4o-mini\",\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\",\"content\":\"I'll read the file. \"},\"finish_reason\":null}]}\n\n",
+    "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":0,\"model\":\"gpt-4o-mini\",\"choices\":[{\"index\":0,\"delta\":{\"tool_calls\":[{\"index\":0,\"id\":\"call_read_1\",\"type\":\"function\",\"function\":{\"name\":\"read_file\",\"arguments\":\"{\\\"file_path\\\":\\\"demo.txt\\\"}\"}}]},\"finish_reason\":null}]}\n\n",
+    "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":0,\"model\":\"gpt-4o-mini\",\"choices\":[{\"index\":0,\"delta\":{},\"finish_reason\":\"tool_calls\"}]}\n\n",
+    "data: {\"id\":\"chatcmpl-1\",\"object\":\"chat.completion.chunk\",\"created\":0,\"model\":\"gpt-4o-mini\",\"choices\":[],\"usage\":{\"prompt_tokens\":42,\"completion_tokens\":12,\"total_tokens\":54}}\n\n",
+    "data: [DONE]\n\n",
+  ),
+  concat!(
+    "data: {\"id\":\"chatcmpl-2\",\"object\":\"chat.completion.chunk\",\"created\":0,\"model\":\"gpt-4o-mini\",\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\",\"content\":\"File content: \"},\"finish_reason\":null}]}\n\n",
+    "data: {\"id\":\"chatcmpl-2\",\"object\":\"chat.completion.chunk\",\"created\":0,\"model\":\"gpt-4o-mini\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"hello from tool\"},\"finish_reason\":null}]}\n\n",
+    "data: {\"id\":\"chatcmpl-2\",\"object\":\"chat.completion.chunk\",\"created\":0,\"model\":\"gpt-4o-mini\",\"choices\":[{\"index\":0,\"delta\":{},\"finish_reason\":\"stop\"}]}\n\n",
+    "data: {\"id\":\"chatcmpl-2\",\"object\":\"chat.completion.chunk\",\"created\":0,\"model\":\"gpt-4o-mini\",\"choices\":[],\"usage\":{\"prompt_tokens\":61,\"completion_tokens\":6,\"total_tokens\":67}}\n\n",
+    "data: [DONE]\n\n",
+  ),
+];
+
+/// Where the recorded turn lives: `core/tests/cassettes/read_file_turn`.
+pub fn read_file_turn_cassette() -> PathBuf {
+  PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/cassettes/read_file_turn")
+}
+
+/// The outcome of [`run_read_file_turn`].
+#[derive(Debug)]
+pub struct RecordedTurn {
+  pub result: TurnResult,
+  /// Every event the turn emitted, in order.
+  pub events: Vec<EventMsg>,
+  /// The arguments of each `read_file` call the turn routed.
+  pub tool_arguments: Vec<String>,
+}
+
+/// Run one turn in which the model calls `read_file` and then answers with
+/// its output, through the OpenAI provider and the cassette.
+///
+/// `Replay` serves the committed cassette with nothing listening on the
+/// provider's port. `Record` rewrites it from a loopback upstream that
+/// speaks the Chat Completions stream format. Either way the cassette is
+/// installed for the whole process, so a test binary can run this once.
+pub async fn run_read_file_turn(mode: CassetteMode) -> RecordedTurn {
+  let dir = read_file_turn_cassette();
+  let base_url = match mode {
+    CassetteMode::Record => {
+      let _ = std::fs::remove_dir_all(&dir);
+      serve_upstream(READ_FILE_TURN_UPSTREAM).await
+    }
+    CassetteMode::Replay => "http://127.0.0.1:9/v1".to_string(),
+  };
+  cassette::install_cassette(Some(Cassette::new(mode, dir))).expect("install cassette");
+
+  let registry = Arc::new(ProviderRegistry::new());
+  registry
+    .register(OpenAIProvider::new(
+      "sk-test".to_string(),
+      ProviderConfig {
+        provider_id: "openai".to_string(),
+        api_key: Some("sk-test".to_string()),
+        base_url: Some(base_url),
+        ..Default::default()
+      },
+    ))
+    .await;
+  registry
+    .set_default("openai")
+    .await
+    .expect("set default provider");
+  let model_client = Arc::new(ModelClient::new(registry).await.expect("model client"));
+
+  let handler = Arc::new(ReadFileHandler::default());
+  let mut tools = ToolRegistry::new();
+  tools.register_handler("read_file", handler.clone());
+  let tools = Arc::new(tools);
+  let router = Arc::new(ToolRouter::new(
+    tools.clone(),
+    Arc::new(ToolValidator::new(
+      SandboxConfig {
+        mode: SandboxMode::Permissive,
+        network_access: false,
+      },
+      ApprovalPolicy {
+        policy: ApprovalMode::Auto,
+        shell: ShellApproval::OnFailure,
+        patch: PatchApproval::OnRequest,
+      },
+    )),
+  ));
+
+  let (tx_event, mut rx_event) = mpsc::channel(256);
+  let executor = TurnExecutor::new(
+    model_client,
+    tools,
+    router,
+    Arc::new(Session::new()),
+    tx_event,
+    TurnConfig {
+      model: "openai/gpt-4o-mini".to_string(),
+      enable_tools: true,
+      // The cwd is part of the prompt; a fixed one keeps the recording
+      // machine-independent.
+      cwd: PathBuf::from("/workspace"),
+      ..TurnConfig::default()
+    },
+  );
+  let result = executor
+    .run_turn(UserInput {
+      content: READ_FILE_TURN_PROMPT.to_string(),
+      attachments: Vec::new(),
+    })
+    .await
+    .expect("run turn");
+
+  let mut events = Vec::new();
+  while let Ok(event) = rx_event.try_recv() {
+    events.push(event);
+  }
+  let tool_arguments = std::mem::take(
+    &mut *handler
+      .calls
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner),
+  );
+  RecordedTurn {
+    result,
+    events,
+    tool_arguments,
+  }
+}
+
+/// Answer each request on a loopback port with the next canned body.
+async fn serve_upstream(bodies: &'static [&'static str]) -> String {
+  let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+  let addr = listener.local_addr().expect("addr");
+  tokio::spawn(async move {
+    for body in bodies {
+      let (mut socket, _) = listener.accept().await.expect("accept");
+      read_request(&mut socket).await;
+      let response = format!(
+        "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+        body.len()
+      );
+      socket.write_all(response.as_bytes()).await.expect("write");
+    }
+  });
+  format!("http://{addr}/v1")
+}
+
+async fn read_request(socket: &mut TcpStream) {
+  let mut buf = Vec::new();
+  let mut chunk = [0u8; 8192];
+  loop {
+    let n = socket.read(&mut chunk).await.expect("read");
+    if n == 0 {
+      return;
+    }
+    buf.extend_from_slice(&chunk[..n]);
+    let Some(head_end) = buf.windows(4).position(|window| window == b"\r\n\r\n") else {
+      continue;
+    };
+    let head = String::from_utf8_lossy(&buf[..head_end]).to_ascii_lowercase();
+    let content_length = head
+      .lines()
+      .find_map(|line| line.strip_prefix("content-length:"))
+      .and_then(|value| value.trim().parse::<usize>().ok())
+      .unwrap_or(0);
+    if buf.len() >= head_end + 4 + content_length {
+      return;
+    }
+  }
+}
+
+#[derive(Debug, Default)]
+struct ReadFileHandler {
+  calls: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl ToolHandler for ReadFileHandler {
+  fn kind(&self) -> ToolKind {
+    ToolKind::Function
+  }
+
+  fn handle(
+    &self,
+    invocation: ToolInvocation,
+  ) -> std::result::Result<ToolOutput, FunctionCallError> {
+    let ToolPayload::Function { arguments } = invocation.payload else {
+      return Err(FunctionCallError::ToolNotFound(invocation.name));
+    };
+    self
+      .calls
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .push(arguments);
+    Ok(ToolOutput::success(READ_FILE_TURN_TOOL_OUTPUT))
+  }
+}
@@ -77,6 +77,15 @@ impl ThreadManagerState {
     thread_id
   }
 
+  /// Insert a thread that was spawned by an earlier process, keeping its id.
+  pub fn restore_thread(&self, info: ThreadInfo) {
+    self
+      .threads
+      .lock()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .insert(info.thread_id.clone(), info);
+  }
+
   pub fn remove_thread(&self, thread_id: &ThreadId) -> bool {
     self
       .threads
@@ -0,0 +1,360 @@
+//! Token counting for context budgeting.
+//!
+//! OpenAI models are counted exactly with their BPE encoder (`o200k_base` for
+//! the GPT-4o/GPT-5/o-series families, `cl100k_base` for GPT-4 and
+//! GPT-3.5). Every other model gets a per-character estimate that is then
+//! scaled by what the provider actually reported for earlier requests (see
+//! [`next_calibration`]).
+
+use std::sync::OnceLock;
+
+use tiktoken_rs::CoreBPE;
+
+use crate::model::ChatRequest;
+use crate::model::Message;
+
+/// Clamp for the learned estimate scale; anything outside this range is a
+/// measurement problem (e.g. a provider that reports cumulative usage).
+const MIN_CALIBRATION: f64 = 0.5;
+const MAX_CALIBRATION: f64 = 3.0;
+
+/// Requests smaller than this say more about fixed framing overhead than
+/// about how the provider tokenizes text.
+const MIN_CALIBRATION_SAMPLE_TOKENS: usize = 512;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TokenEncoding {
+  O200kBase,
+  Cl100kBase,
+  /// Character-class estimate for providers without a public tokenizer.
+  Estimate,
+}
+
+impl TokenEncoding {
+  /// Pick the encoding for a model id, ignoring any `provider/` prefix.
+  pub(crate) fn for_model(model: &str) -> Self {
+    let name = model
+      .rsplit('/')
+      .next()
+      .unwrap_or(model)
+      .to_ascii_lowercase();
+    const O200K_PREFIXES: &[&str] = &[
+      "gpt-4o",
+      "chatgpt-4o",
+      "gpt-4.1",
+      "gpt-4.5",
+      "gpt-5",
+      "gpt-oss",
+      "o1",
+      "o3",
+      "o4",
+      "codex",
+    ];
+    const CL100K_PREFIXES: &[&str] =
+      &["gpt-4", "gpt-3.5", "text-embedding-3", "text-embedding-ada"];
+    if O200K_PREFIXES.iter().any(|prefix| name.starts_with(prefix)) {
+      TokenEncoding::O200kBase
+    } else if CL100K_PREFIXES
+      .iter()
+      .any(|prefix| name.starts_with(prefix))
+    {
+      TokenEncoding::Cl100kBase
+    } else {
+      TokenEncoding::Estimate
+    }
+  }
+
+  fn bpe(self) -> Option<&'static CoreBPE> {
+    static O200K: OnceLock<Option<CoreBPE>> = OnceLock::new();
+    static CL100K: OnceLock<Option<CoreBPE>> = OnceLock::new();
+    match self {
+      TokenEncoding::O200kBase => O200K
+        .get_or_init(|| tiktoken_rs::o200k_base().ok())
+        .as_ref(),
+      TokenEncoding::Cl100kBase => CL100K
+        .get_or_init(|| tiktoken_rs::cl100k_base().ok())
+        .as_ref(),
+      TokenEncoding::Estimate => None,
+    }
+  }
+}
+
+/// Counts tokens the way one model's provider will bill them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct TokenCounter {
+  encoding: TokenEncoding,
+  /// Reported prompt tokens per estimated token; only applies to
+  /// [`TokenEncoding::Estimate`].
+  scale: f64,
+}
+
+impl Default for TokenCounter {
+  fn default() -> Self {
+    Self {
+      encoding: TokenEncoding::Estimate,
+      scale: 1.0,
+    }
+  }
+}
+
+impl TokenCounter {
+  pub(crate) fn for_model(model: &str) -> Self {
+    Self {
+      encoding: TokenEncoding::for_model(model),
+      scale: 1.0,
+    }
+  }
+
+  pub(crate) fn with_calibration(mut self, scale: Option<f64>) -> Self {
+    if let Some(scale) = scale {
+      self.scale = scale.clamp(MIN_CALIBRATION, MAX_CALIBRATION);
+    }
+    self
+  }
+
+  pub(crate) fn encoding(&self) -> TokenEncoding {
+    self.encoding
+  }
+
+  pub(crate) fn count_text(&self, text: &str) -> usize {
+    match self.encoding.bpe() {
+      Some(bpe) => bpe.encode_ordinary(text).len(),
+      None if text.is_empty() => 0,
+      None => ((estimate_weight(text) * self.scale).ceil() as usize).max(1),
+    }
+  }
+
+  /// Apply the learned scale to a count from [`Self::count_request_uncalibrated`].
+  pub(crate) fn calibrate(&self, uncalibrated_tokens: usize) -> usize {
+    match self.encoding.bpe() {
+      Some(_) => uncalibrated_tokens,
+      None => (uncalibrated_tokens as f64 * self.scale).ceil() as usize,
+    }
+  }
+
+  /// Byte lengths of the longest prefix within `head_tokens` and the longest
+  /// disjoint suffix within `tail_tokens`, cut on character boundaries.
+  pub(crate) fn head_and_tail_within(
+    &self,
+    text: &str,
+    head_tokens: usize,
+    tail_tokens: usize,
+  ) -> (usize, usize) {
+    let (head, tail) = match self.encoding.bpe() {
+      Some(bpe) => {
+        let token_lens: Vec<usize> = bpe
+          ._decode_native_and_split(bpe.encode_ordinary(text))
+          .map(|bytes| bytes.len())
+          .collect();
+        let head_tokens = head_tokens.min(token_lens.len());
+        let tail_tokens = tail_tokens.min(token_lens.len() - head_tokens);
+        let head: usize = token_lens[..head_tokens].iter().sum();
+        let tail: usize = token_lens[token_lens.len() - tail_tokens..].iter().sum();
+        (head, tail)
+      }
+      None => {
+        let head = self.bytes_within(text.chars(), head_tokens as f64);
+        let tail = self.bytes_within(text[head..].chars().rev(), tail_tokens as f64);
+        (head, tail)
+      }
+    };
+    // A multi-byte character can straddle a token boundary; drop it rather
+    // than split it.
+    let mut head_end = head;
+    while !text.is_char_boundary(head_end) {
+      head_end -= 1;
+    }
+    let mut tail_start = text.len() - tail;
+    while !text.is_char_boundary(tail_start) {
+      tail_start += 1;
+    }
+    (head_end, text.len() - tail_start.max(head_end))
+  }
+
+  fn bytes_within(&self, chars: impl Iterator<Item = char>, budget: f64) -> usize {
+    let mut spent = 0.0;
+    chars
+      .take_while(|ch| {
+        spent += char_token_weight(*ch) * self.scale;
+        spent <= budget
+      })
+      .map(char::len_utf8)
+      .sum()
+  }
+
+  /// Content plus tool-call names and arguments; never zero so every message
+  /// costs something when walking a budget.
+  pub(crate) fn count_message(&self, message: &Message) -> usize {
+    let mut tokens = message.text().map_or(0, |text| self.count_text(text));
+    if let Message::Assistant {
+      tool_calls: Some(tool_calls),
+      ..
+    } = message
+    {
+      for call in tool_calls {
+        tokens += self.count_text(&call.function.name);
+        tokens += self.count_text(&call.function.arguments);
+      }
+    }
+    tokens.max(1)
+  }
+
+  pub(crate) fn count_messages(&self, messages: &[Message]) -> usize {
+    messages
+      .iter()
+      .map(|message| self.count_message(message))
+      .sum()
+  }
+
+  /// Uncalibrated size of everything a request sends as prompt: messages and
+  /// tool schemas. This is what [`next_calibration`] compares against the
+  /// provider's reported prompt tokens.
+  pub(crate) fn count_request_uncalibrated(&self, request: &ChatRequest) -> usize {
+    let raw = Self {
+      scale: 1.0,
+      ..*self
+    };
+    let tools = request
+      .tools
+      .as_ref()
+      .and_then(|tools| serde_json::to_string(tools).ok())
+      .map_or(0, |schema| raw.count_text(&schema));
+    raw.count_messages(&request.messages) + tools
+  }
+}
+
+/// Fold one observation (our uncalibrated estimate vs. the prompt tokens the
+/// provider reported for the same request) into the running scale.
+///
+/// Returns `previous` unchanged when the sample is too small to be useful.
+pub(crate) fn next_calibration(
+  previous: Option<f64>,
+  estimated_tokens: usize,
+  reported_tokens: u64,
+) -> Option<f64> {
+  if estimated_tokens < MIN_CALIBRATION_SAMPLE_TOKENS || reported_tokens == 0 {
+    return previous;
+  }
+  let observed =
+    (reported_tokens as f64 / estimated_tokens as f64).clamp(MIN_CALIBRATION, MAX_CALIBRATION);
+  Some(match previous {
+    // Smooth so one unusual request (a huge base64 blob, say) does not swing
+    // every later estimate.
+    Some(previous) => previous * 0.5 + observed * 0.5,
+    None => observed,
+  })
+}
+
+/// Estimated token share of one character.
+///
+/// Weights are fitted against `o200k_base` on prose, source code and CJK
+/// text: English words average ~4 characters per token, digits are grouped in
+/// threes, punctuation in code is usually its own token, and CJK ideographs
+/// are close to one token each.
+fn char_token_weight(ch: char) -> f64 {
+  match ch {
+    'a'..='z' | 'A'..='Z' => 0.25,
+    '0'..='9' => 0.34,
+    ' ' | '\t' => 0.1,
+    '\n' | '\r' => 0.5,
+    c if c.is_ascii_punctuation() => 0.6,
+    c if c.is_ascii() => 0.5,
+    c if is_cjk(c) => 1.0,
+    c if (c as u32) >= 0x1F000 => 2.0,
+    _ => 0.4,
+  }
+}
+
+fn estimate_weight(text: &str) -> f64 {
+  text.chars().map(char_token_weight).sum()
+}
+
+fn is_cjk(ch: char) -> bool {
+  matches!(
+    ch as u32,
+    0x2E80..=0x9FFF // radicals, kana, CJK unified ideographs
+      | 0xAC00..=0xD7AF // hangul syllables
+      | 0xF900..=0xFAFF // compatibility ideographs
+      | 0xFF00..=0xFFEF // fullwidth forms
+      | 0x20000..=0x2FA1F // extension B and beyond
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn selects_encoding_by_model_family() {
+    assert_eq!(
+      TokenEncoding::for_model("gpt-5.2-codex"),
+      TokenEncoding::O200kBase
+    );
+    assert_eq!(
+      TokenEncoding::for_model("openrouter/openai/gpt-4o-mini"),
+      TokenEncoding::O200kBase
+    );
+    assert_eq!(
+      TokenEncoding::for_model("gpt-4-turbo"),
+      TokenEncoding::Cl100kBase
+    );
+    assert_eq!(
+      TokenEncoding::for_model("claude-sonnet-4-5"),
+      TokenEncoding::Estimate
+    );
+  }
+
+  #[test]
+  fn bpe_counts_match_the_reference_encoder() {
+    let counter = TokenCounter::for_model("gpt-4o");
+    assert_eq!(counter.count_text("hello world"), 2);
+    assert_eq!(counter.count_text(""), 0);
+  }
+
+  #[test]
+  fn estimate_weighs_code_and_cjk_above_prose() {
+    let prose = "the quick brown fox jumps over the lazy dog";
+    let code = "fn f(a:&[u8])->Option<(u8,u8)>{a.get(0)?;}";
+    let cjk = "上下文压缩触发得太晚导致溢出问题";
+    let chars_over_four = |text: &str| text.chars().count().div_ceil(4);
+    let estimate_tokens = |text: &str| TokenCounter::default().count_text(text);
+
+    assert!(estimate_tokens(prose) <= chars_over_four(prose) + 1);
+    assert!(estimate_tokens(code) > chars_over_four(code));
+    assert!(estimate_tokens(cjk) >= cjk.chars().count());
+  }
+
+  #[test]
+  fn head_and_tail_split_on_token_boundaries() {
+    let text = "alpha beta gamma delta epsilon";
+    let exact = TokenCounter::for_model("gpt-4o");
+    let (head, tail) = exact.head_and_tail_within(text, 2, 1);
+    assert_eq!(&text[..head], "alpha beta");
+    assert_eq!(&text[text.len() - tail..], " epsilon");
+
+    let (head, tail) = TokenCounter::default().head_and_tail_within("字字字字", 1, 1);
+    assert_eq!((head, tail), (3, 3));
+    let (head, tail) = exact.head_and_tail_within(text, 100, 100);
+    assert_eq!((head, tail), (text.len(), 0));
+  }
+
+  #[test]
+  fn calibration_scales_estimates_toward_reported_usage() {
+    let scale = next_calibration(None, 1_000, 1_500);
+    assert_eq!(scale, Some(1.5));
+    assert_eq!(next_calibration(scale, 1_000, 1_000), Some(1.25));
+    // Too small to learn from.
+    assert_eq!(next_calibration(scale, 100, 1_000), scale);
+
+    let text = "a".repeat(400);
+    let counter = TokenCounter::default().with_calibration(Some(1.5));
+    assert_eq!(counter.count_text(&text), 150);
+    assert_eq!(counter.calibrate(100), 150);
+    // Exact encoders ignore the scale.
+    let exact = TokenCounter::for_model("gpt-4o").with_calibration(Some(1.5));
+    assert_eq!(
+      exact.count_text(&text),
+      TokenCounter::for_model("gpt-4o").count_text(&text)
+    );
+  }
+}
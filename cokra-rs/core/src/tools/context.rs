@@ -9,6 +9,7 @@ use tokio::sync::mpsc;
 
 use crate::exec::PermissionProfile;
 use crate::exec::SandboxPermissions;
+use crate::model::ImageData;
 use crate::session::Session;
 use crate::tools::registry::ToolRegistry;
 use cokra_protocol::AskForApproval;
@@ -158,15 +159,106 @@ impl ToolOutputBody {
   }
 }
 
+/// One typed piece of a tool result.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToolContentPart {
+  Text {
+    text: String,
+  },
+  /// Base64 image data, forwarded to the model as an image.
+  Image {
+    mime_type: String,
+    data: String,
+  },
+  /// An embedded resource; `text` is `None` for binary resources.
+  Resource {
+    uri: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mime_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+  },
+}
+
+impl ToolContentPart {
+  /// How the part reads in the text sent to the model and shown in the UI.
+  /// Images become a placeholder since the data travels separately.
+  pub fn render(&self) -> String {
+    match self {
+      Self::Text { text } => text.clone(),
+      Self::Image { mime_type, data } => {
+        format!("[image: {mime_type}, {}]", format_size(base64_len(data)))
+      }
+      Self::Resource {
+        uri,
+        mime_type,
+        text,
+      } => {
+        let mime_type = mime_type.as_deref().unwrap_or("unknown type");
+        match text {
+          Some(text) => format!("[resource: {uri} ({mime_type})]\n{text}"),
+          None => format!("[resource: {uri} ({mime_type}), binary]"),
+        }
+      }
+    }
+  }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct McpToolCallResult {
-  pub content: Vec<serde_json::Value>,
+  pub content: Vec<ToolContentPart>,
+  /// Already checked against the tool's `outputSchema`, when it has one.
   #[serde(default, skip_serializing_if = "Option::is_none")]
   pub structured_content: Option<serde_json::Value>,
   #[serde(default)]
   pub is_error: bool,
 }
 
+impl McpToolCallResult {
+  /// The content parts joined for the model. Structured content is appended
+  /// as pretty JSON unless a text part already carries the same value, as the
+  /// MCP spec recommends servers do.
+  pub fn render(&self) -> String {
+    let mut sections = self
+      .content
+      .iter()
+      .map(ToolContentPart::render)
+      .collect::<Vec<_>>();
+    if let Some(structured) = &self.structured_content {
+      let echoed = self.content.iter().any(|part| {
+        matches!(part, ToolContentPart::Text { text }
+          if serde_json::from_str::<serde_json::Value>(text).ok().as_ref() == Some(structured))
+      });
+      if !echoed {
+        let pretty = serde_json::to_string_pretty(structured).unwrap_or_default();
+        sections.push(if sections.is_empty() {
+          pretty
+        } else {
+          format!("```json\n{pretty}\n```")
+        });
+      }
+    }
+    sections.join("\n")
+  }
+}
+
+/// Decoded size of base64 `data`, without decoding it.
+fn base64_len(data: &str) -> usize {
+  let padding = data.bytes().rev().take_while(|b| *b == b'=').count();
+  (data.len() / 4 * 3).saturating_sub(padding)
+}
+
+fn format_size(bytes: usize) -> String {
+  if bytes < 1024 {
+    format!("{bytes} B")
+  } else if bytes < 1024 * 1024 {
+    format!("{:.1} KB", bytes as f64 / 1024.0)
+  } else {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+  }
+}
+
 /// Standard output from a tool.
 #[derive(Debug, Clone)]
 pub enum ToolOutput {
@@ -237,16 +329,39 @@ impl ToolOutput {
     match self {
       Self::Function { body, .. } => body.to_text(),
       Self::Mcp { result, .. } => match result {
-        Ok(result) => serde_json::to_string(result).unwrap_or_else(|_| "{}".to_string()),
+        Ok(result) => result.render(),
         Err(message) => message.clone(),
       },
     }
   }
 
+  /// Images to forward to the model alongside [`Self::text_content`].
+  pub fn images(&self) -> Vec<ImageData> {
+    match self {
+      Self::Function { .. } | Self::Mcp { result: Err(_), .. } => Vec::new(),
+      Self::Mcp {
+        result: Ok(result), ..
+      } => result
+        .content
+        .iter()
+        .filter_map(|part| match part {
+          ToolContentPart::Image { mime_type, data } => Some(ImageData {
+            mime_type: mime_type.clone(),
+            data: data.clone(),
+          }),
+          _ => None,
+        })
+        .collect(),
+    }
+  }
+
   pub fn is_error(&self) -> bool {
     match self {
       Self::Function { success, .. } => success == &Some(false),
-      Self::Mcp { result, .. } => result.is_err(),
+      Self::Mcp { result, .. } => match result {
+        Ok(result) => result.is_error,
+        Err(_) => true,
+      },
     }
   }
 }
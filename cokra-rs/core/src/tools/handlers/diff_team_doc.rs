@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::agent::team_runtime::runtime_for_thread;
+use crate::tools::context::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+pub struct DiffTeamDocHandler;
+
+#[derive(Debug, Deserialize)]
+struct DiffTeamDocArgs {
+  name: String,
+  from_version: Option<u64>,
+  to_version: Option<u64>,
+}
+
+#[async_trait]
+impl ToolHandler for DiffTeamDocHandler {
+  fn kind(&self) -> ToolKind {
+    ToolKind::Function
+  }
+
+  async fn handle_async(
+    &self,
+    invocation: ToolInvocation,
+  ) -> Result<ToolOutput, FunctionCallError> {
+    let args: DiffTeamDocArgs = invocation.parse_arguments()?;
+    let runtime = invocation.runtime.ok_or_else(|| {
+      FunctionCallError::Fatal("diff_team_doc missing runtime context".to_string())
+    })?;
+    let team_runtime = runtime_for_thread(&runtime.thread_id).ok_or_else(|| {
+      FunctionCallError::Execution("diff_team_doc runtime is not configured".to_string())
+    })?;
+    let diff = team_runtime
+      .diff_doc(&args.name, args.from_version, args.to_version)
+      .map_err(|err| FunctionCallError::RespondToModel(format!("{err:#}")))?;
+    let diff = if diff.is_empty() {
+      "No changes between these versions.".to_string()
+    } else {
+      diff
+    };
+    Ok(ToolOutput::success(diff).with_id(invocation.id))
+  }
+}
@@ -5,13 +5,19 @@
 //! - Create new file when old_string is empty
 //! - CRLF normalisation
 //! - Returns unified diff output + optional LSP diagnostics
+//!
+//! Reads and writes go through the session's editor file system when one is
+//! attached, so edits apply to unsaved buffers the same way `write_file` does.
 
 use std::fs;
+use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use serde::Deserialize;
 
+use crate::session::EditorFileSystem;
 use crate::tools::context::FunctionCallError;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
@@ -61,6 +67,11 @@ impl ToolHandler for EditFileHandler {
       ));
     }
 
+    let editor_fs = match &invocation.runtime {
+      Some(runtime) => runtime.session.editor_file_system().await,
+      None => None,
+    };
+
     // Create new file when old_string is empty
     if args.old_string.is_empty() {
       if let Some(parent) = path.parent()
@@ -70,9 +81,7 @@ impl ToolHandler for EditFileHandler {
           FunctionCallError::Execution(format!("failed to create {}: {e}", parent.display()))
         })?;
       }
-      fs::write(&path, args.new_string.as_bytes()).map_err(|e| {
-        FunctionCallError::Execution(format!("failed to write {}: {e}", path.display()))
-      })?;
+      write_text(editor_fs.as_ref(), &path, &args.new_string).await?;
       let diag_suffix = collect_file_diagnostics(&path).await;
       return Ok(
         ToolOutput::success(format!(
@@ -85,9 +94,14 @@ impl ToolHandler for EditFileHandler {
     }
 
     // Read existing file
-    let content = fs::read_to_string(&path).map_err(|e| {
-      FunctionCallError::RespondToModel(format!("failed to read {}: {e}", path.display()))
-    })?;
+    let content = match &editor_fs {
+      Some(editor_fs) => editor_fs.read_text_file(&path).await.map_err(|e| {
+        FunctionCallError::RespondToModel(format!("failed to read {}: {e}", path.display()))
+      })?,
+      None => fs::read_to_string(&path).map_err(|e| {
+        FunctionCallError::RespondToModel(format!("failed to read {}: {e}", path.display()))
+      })?,
+    };
 
     // Normalise CRLF → LF for matching
     let normalised_content = content.replace("\r\n", "\n");
@@ -133,9 +147,7 @@ impl ToolHandler for EditFileHandler {
       new_content
     };
 
-    fs::write(&path, final_content.as_bytes()).map_err(|e| {
-      FunctionCallError::Execution(format!("failed to write {}: {e}", path.display()))
-    })?;
+    write_text(editor_fs.as_ref(), &path, &final_content).await?;
 
     let replacements = if args.replace_all { count } else { 1 };
     let diff_summary = build_diff_summary(&normalised_old, &normalised_new, replacements);
@@ -153,6 +165,21 @@ impl ToolHandler for EditFileHandler {
   }
 }
 
+async fn write_text(
+  editor_fs: Option<&Arc<dyn EditorFileSystem>>,
+  path: &Path,
+  content: &str,
+) -> Result<(), FunctionCallError> {
+  match editor_fs {
+    Some(editor_fs) => editor_fs.write_text_file(path, content).await.map_err(|e| {
+      FunctionCallError::Execution(format!("failed to write {}: {e}", path.display()))
+    }),
+    None => fs::write(path, content.as_bytes()).map_err(|e| {
+      FunctionCallError::Execution(format!("failed to write {}: {e}", path.display()))
+    }),
+  }
+}
+
 /// Trim each line independently to enable fuzzy whitespace matching.
 fn trim_lines(s: &str) -> String {
   s.lines().map(|l| l.trim()).collect::<Vec<_>>().join("\n")
@@ -178,11 +205,17 @@ fn build_diff_summary(old: &str, new: &str, replacements: usize) -> String {
 #[cfg(test)]
 mod tests {
   use std::fs;
+  use std::sync::Arc;
 
   use super::EditFileHandler;
+  use crate::session::BufferFileSystem;
+  use crate::session::Session;
   use crate::tools::context::ToolInvocation;
   use crate::tools::context::ToolPayload;
+  use crate::tools::context::ToolRuntimeContext;
   use crate::tools::registry::ToolHandler;
+  use crate::tools::registry::ToolRegistry;
+  use cokra_protocol::AskForApproval;
 
   fn make_inv(id: &str, args: serde_json::Value) -> ToolInvocation {
     ToolInvocation {
@@ -196,6 +229,26 @@ mod tests {
     }
   }
 
+  async fn editor_runtime(editor_fs: Arc<BufferFileSystem>) -> Arc<ToolRuntimeContext> {
+    let session = Arc::new(Session::new());
+    session.set_editor_file_system(Some(editor_fs)).await;
+    Arc::new(ToolRuntimeContext {
+      session,
+      tool_registry: Arc::new(ToolRegistry::new()),
+      tx_event: None,
+      thread_id: "thread-1".to_string(),
+      turn_id: "turn-1".to_string(),
+      approval_policy: AskForApproval::OnRequest,
+      model_provider_id: None,
+      model_runtime_kind: None,
+      supports_native_web_search: false,
+      has_managed_network_requirements: false,
+      allowed_domains: Vec::new(),
+      denied_domains: Vec::new(),
+      network_attempt_id: None,
+    })
+  }
+
   fn temp_path(name: &str) -> std::path::PathBuf {
     std::env::temp_dir().join(format!("cokra-edit-{}-{}.txt", name, uuid::Uuid::new_v4()))
   }
@@ -375,4 +428,29 @@ mod tests {
     assert!(err.to_string().contains("whitespace"));
     let _ = fs::remove_file(path);
   }
+
+  #[tokio::test]
+  async fn edits_the_editor_buffer_instead_of_disk() {
+    let path = temp_path("buffer");
+    fs::write(&path, "saved on disk").unwrap();
+    let editor_fs = Arc::new(BufferFileSystem::default().with_buffer(&path, "unsaved buffer text"));
+    let mut inv = make_inv(
+      "11",
+      serde_json::json!({
+        "file_path": path.display().to_string(),
+        "old_string": "unsaved",
+        "new_string": "edited"
+      }),
+    );
+    inv.runtime = Some(editor_runtime(Arc::clone(&editor_fs)).await);
+
+    let out = EditFileHandler.handle_async(inv).await.unwrap();
+    assert!(!out.is_error());
+    assert_eq!(
+      editor_fs.buffer(&path).as_deref(),
+      Some("edited buffer text")
+    );
+    assert_eq!(fs::read_to_string(&path).unwrap(), "saved on disk");
+    let _ = fs::remove_file(path);
+  }
 }
@@ -0,0 +1,66 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::agent::team_runtime::AgentModelOverrides;
+use crate::agent::team_runtime::runtime_for_thread;
+use crate::model::ReasoningEffort;
+use crate::tools::context::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+use super::spawn_agent::non_empty;
+
+pub struct ExploreHandler;
+
+#[derive(Debug, Deserialize)]
+struct ExploreArgs {
+  #[serde(alias = "task", alias = "prompt")]
+  question: String,
+  model: Option<String>,
+  provider: Option<String>,
+  #[serde(alias = "effort")]
+  reasoning_effort: Option<ReasoningEffort>,
+}
+
+#[async_trait]
+impl ToolHandler for ExploreHandler {
+  fn kind(&self) -> ToolKind {
+    ToolKind::Function
+  }
+
+  async fn handle_async(
+    &self,
+    invocation: ToolInvocation,
+  ) -> Result<ToolOutput, FunctionCallError> {
+    let args: ExploreArgs = invocation.parse_arguments()?;
+    let question = args.question.trim().to_string();
+    if question.is_empty() {
+      return Err(FunctionCallError::RespondToModel(
+        "explore requires a non-empty question".to_string(),
+      ));
+    }
+    let runtime = invocation
+      .runtime
+      .ok_or_else(|| FunctionCallError::Fatal("explore missing runtime context".to_string()))?;
+    let team_runtime = runtime_for_thread(&runtime.thread_id).ok_or_else(|| {
+      FunctionCallError::Execution("explore runtime is not configured".to_string())
+    })?;
+    let overrides = AgentModelOverrides {
+      model: non_empty(args.model),
+      provider: non_empty(args.provider),
+      reasoning_effort: args.reasoning_effort,
+      temperature: None,
+    };
+    let report = team_runtime
+      .explore(&invocation.cwd, question, overrides)
+      .await
+      .map_err(|err| FunctionCallError::RespondToModel(format!("{err:#}")))?;
+
+    let out = ToolOutput::success(serde_json::to_string(&report).map_err(|err| {
+      FunctionCallError::Fatal(format!("failed to serialize explore report: {err}"))
+    })?);
+    Ok(out.with_id(invocation.id))
+  }
+}
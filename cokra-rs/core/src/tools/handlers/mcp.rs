@@ -1,10 +1,15 @@
 use async_trait::async_trait;
 use std::sync::Arc;
 
+use cokra_protocol::EventMsg;
+use cokra_protocol::McpToolCallProgressEvent;
+use cokra_rmcp_client::OnProgress;
+
 use crate::mcp::McpConnectionManager;
 use crate::tools::context::FunctionCallError;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolRuntimeContext;
 use crate::tools::registry::ToolHandler;
 use crate::tools::registry::ToolKind;
 
@@ -36,7 +41,14 @@ impl ToolHandler for McpHandler {
     };
 
     let arguments = invocation.parse_arguments_value().ok();
-    let result = self.manager.call_tool(&invocation.name, arguments).await;
+    let on_progress = invocation
+      .runtime
+      .as_ref()
+      .map(|runtime| progress_reporter(Arc::clone(runtime), invocation.id.clone()));
+    let result = self
+      .manager
+      .call_tool(&invocation.name, arguments, on_progress)
+      .await;
 
     Ok(ToolOutput::Mcp {
       id: invocation.id,
@@ -44,3 +56,23 @@ impl ToolHandler for McpHandler {
     })
   }
 }
+
+/// Forward the server's progress notifications for one call as
+/// `McpToolCallProgress` events on the running tool cell.
+fn progress_reporter(runtime: Arc<ToolRuntimeContext>, call_id: String) -> OnProgress {
+  Arc::new(move |param| {
+    let event = EventMsg::McpToolCallProgress(McpToolCallProgressEvent {
+      thread_id: runtime.thread_id.clone(),
+      turn_id: runtime.turn_id.clone(),
+      call_id: call_id.clone(),
+      progress: param.progress,
+      total: param.total,
+      message: param.message,
+    });
+    runtime.session.emit_event(event.clone());
+    if let Some(tx_event) = &runtime.tx_event {
+      // Progress is best-effort; a full channel just drops an update.
+      let _ = tx_event.try_send(event);
+    }
+  })
+}
@@ -17,8 +17,10 @@ pub mod connect_integration;
 pub mod create_team_task;
 pub mod deactivate_tools;
 pub mod diagnostics;
+pub mod diff_team_doc;
 pub mod dynamic;
 pub mod edit_file;
+pub mod explore;
 pub mod force_release_lease;
 pub mod glob;
 pub mod grep_files;
@@ -36,11 +38,13 @@ pub mod plan;
 pub mod read_file;
 pub mod read_many_files;
 pub mod read_mcp_resource;
+pub mod read_team_doc;
 pub mod read_team_messages;
 pub mod release_task_leases;
 pub mod remove_task_dependency;
 pub mod request_user_input;
 pub mod reset_active_tools;
+pub mod run_workflow;
 pub mod save_memory;
 pub mod send_input;
 pub mod send_team_message;
@@ -49,6 +53,7 @@ pub mod shell;
 pub mod skill;
 pub mod spawn_agent;
 pub mod submit_team_plan;
+pub mod subscribe_team_doc;
 pub mod team_selectors;
 pub mod team_status;
 pub mod todo;
@@ -62,6 +67,7 @@ pub mod web_fetch;
 pub mod web_page;
 pub mod web_search;
 pub mod write_file;
+pub mod write_team_doc;
 
 use std::sync::Arc;
 
@@ -131,13 +137,9 @@ pub fn register_builtin_handlers(
     "tool_audit_log",
     Arc::new(tool_audit_log::ToolAuditLogHandler),
   );
-  for tool_name in mcp_manager.tool_names() {
-    registry.register_handler(
-      tool_name,
-      Arc::new(mcp::McpHandler::new(Arc::clone(&mcp_manager))),
-    );
-  }
+  registry.register_mcp_handler(Arc::new(mcp::McpHandler::new(Arc::clone(&mcp_manager))));
   registry.register_handler("spawn_agent", Arc::new(spawn_agent::SpawnAgentHandler));
+  registry.register_handler("explore", Arc::new(explore::ExploreHandler));
   registry.register_handler("send_input", Arc::new(send_input::SendInputHandler));
   registry.register_handler("wait", Arc::new(wait::WaitHandler));
   registry.register_handler("close_agent", Arc::new(close_agent::CloseAgentHandler));
@@ -174,6 +176,7 @@ pub fn register_builtin_handlers(
     "approve_team_plan",
     Arc::new(approve_team_plan::ApproveTeamPlanHandler),
   );
+  registry.register_handler("run_workflow", Arc::new(run_workflow::RunWorkflowHandler));
   registry.register_handler("team_status", Arc::new(team_status::TeamStatusHandler));
   registry.register_handler(
     "ack_team_message",
@@ -195,6 +198,16 @@ pub fn register_builtin_handlers(
     "read_team_messages",
     Arc::new(read_team_messages::ReadTeamMessagesHandler),
   );
+  registry.register_handler("read_team_doc", Arc::new(read_team_doc::ReadTeamDocHandler));
+  registry.register_handler(
+    "write_team_doc",
+    Arc::new(write_team_doc::WriteTeamDocHandler),
+  );
+  registry.register_handler("diff_team_doc", Arc::new(diff_team_doc::DiffTeamDocHandler));
+  registry.register_handler(
+    "subscribe_team_doc",
+    Arc::new(subscribe_team_doc::SubscribeTeamDocHandler),
+  );
   registry.register_handler(
     "create_team_task",
     Arc::new(create_team_task::CreateTeamTaskHandler),
@@ -9,6 +9,7 @@ use std::path::PathBuf;
 use async_trait::async_trait;
 use serde::Deserialize;
 use tokio::fs::File;
+use tokio::io::AsyncBufRead;
 use tokio::io::AsyncBufReadExt;
 use tokio::io::BufReader;
 
@@ -77,7 +78,19 @@ impl ToolHandler for ReadFileHandler {
       ));
     }
 
-    let collected = read_slice(&path, offset, limit).await?;
+    let editor_fs = match &invocation.runtime {
+      Some(runtime) => runtime.session.editor_file_system().await,
+      None => None,
+    };
+    let collected = match editor_fs {
+      Some(editor_fs) => {
+        let text = editor_fs.read_text_file(&path).await.map_err(|err| {
+          FunctionCallError::RespondToModel(format!("failed to read file: {err}"))
+        })?;
+        read_lines(text.as_bytes(), offset, limit).await?
+      }
+      None => read_slice(&path, offset, limit).await?,
+    };
     Ok(ToolOutput::success(collected.join("\n")).with_id(id))
   }
 }
@@ -111,8 +124,16 @@ async fn read_slice(
   let file = File::open(path)
     .await
     .map_err(|err| FunctionCallError::RespondToModel(format!("failed to read file: {err}")))?;
+  read_lines(BufReader::new(file), offset, limit).await
+}
 
-  let mut reader = BufReader::new(file);
+/// Numbered lines `offset..offset + limit` of `reader`, shared by disk and
+/// editor-buffer reads.
+async fn read_lines(
+  mut reader: impl AsyncBufRead + Unpin,
+  offset: usize,
+  limit: usize,
+) -> Result<Vec<String>, FunctionCallError> {
   let mut collected = Vec::new();
   let mut seen = 0usize;
   let mut buffer = Vec::new();
@@ -241,6 +262,14 @@ mod tests {
     assert_eq!(lines, vec![format!("L1: {expected}")]);
   }
 
+  #[tokio::test]
+  async fn reads_editor_buffer_text() {
+    let lines = read_lines("one\r\ntwo\nthree".as_bytes(), 2, 5)
+      .await
+      .expect("read");
+    assert_eq!(lines, vec!["L2: two".to_string(), "L3: three".to_string()]);
+  }
+
   #[tokio::test]
   async fn rejects_relative_path() {
     let invocation = ToolInvocation {
@@ -8,12 +8,17 @@
 //! - 每行最多 500 字节（与 read_file 对齐）
 //! - 路径必须为绝对路径
 //! - 读取失败的文件单独报错，不中断整批
+//! - 会话挂载了编辑器文件系统时从编辑器读取（与 read_file 对齐，可看到未保存的缓冲区）
 
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use serde::Deserialize;
+use tokio::io::AsyncBufRead;
+use tokio::io::AsyncBufReadExt;
 
+use crate::session::EditorFileSystem;
 use crate::tools::context::FunctionCallError;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
@@ -85,6 +90,10 @@ impl ToolHandler for ReadManyFilesHandler {
     }
 
     let effective_limit = args.limit.min(MAX_LINES_PER_FILE);
+    let editor_fs = match &invocation.runtime {
+      Some(runtime) => runtime.session.editor_file_system().await,
+      None => None,
+    };
 
     // 并发读取所有文件
     let tasks: Vec<_> = args
@@ -95,7 +104,8 @@ impl ToolHandler for ReadManyFilesHandler {
         let path_str = path_str.clone();
         let offset = args.offset;
         let limit = effective_limit;
-        tokio::spawn(async move { read_one_file(path_str, path, offset, limit).await })
+        let editor_fs = editor_fs.clone();
+        tokio::spawn(async move { read_one_file(editor_fs, path_str, path, offset, limit).await })
       })
       .collect();
 
@@ -113,12 +123,26 @@ impl ToolHandler for ReadManyFilesHandler {
 }
 
 /// 读取单个文件，返回带文件头注释的内容块。
-async fn read_one_file(path_str: String, path: PathBuf, offset: usize, limit: usize) -> String {
+async fn read_one_file(
+  editor_fs: Option<Arc<dyn EditorFileSystem>>,
+  path_str: String,
+  path: PathBuf,
+  offset: usize,
+  limit: usize,
+) -> String {
   if !path.is_absolute() {
     return format!("=== {path_str} ===\nError: 路径必须为绝对路径，收到: {path_str}");
   }
 
-  match read_slice_async(&path, offset, limit).await {
+  let result = match editor_fs {
+    Some(editor_fs) => match editor_fs.read_text_file(&path).await {
+      Ok(text) => read_lines_async(text.as_bytes(), offset, limit).await,
+      Err(e) => Err(format!("读取文件失败: {e}")),
+    },
+    None => read_slice_async(&path, offset, limit).await,
+  };
+
+  match result {
     Ok(lines) => {
       if lines.is_empty() {
         format!("=== {path_str} ===\n(空文件或 offset 超出文件长度)")
@@ -136,13 +160,19 @@ async fn read_slice_async(
   offset: usize,
   limit: usize,
 ) -> Result<Vec<String>, String> {
-  use tokio::io::AsyncBufReadExt;
-
   let file = tokio::fs::File::open(path)
     .await
     .map_err(|e| format!("打开文件失败: {e}"))?;
 
-  let mut reader = tokio::io::BufReader::new(file);
+  read_lines_async(tokio::io::BufReader::new(file), offset, limit).await
+}
+
+/// 从任意行读取器中取出指定行范围。
+async fn read_lines_async(
+  mut reader: impl AsyncBufRead + Unpin,
+  offset: usize,
+  limit: usize,
+) -> Result<Vec<String>, String> {
   let mut collected = Vec::new();
   let mut seen: usize = 0;
   let mut buffer = Vec::new();
@@ -197,6 +227,31 @@ async fn read_slice_async(
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::session::BufferFileSystem;
+  use crate::session::Session;
+  use crate::tools::context::ToolRuntimeContext;
+  use crate::tools::registry::ToolRegistry;
+  use cokra_protocol::AskForApproval;
+
+  async fn editor_runtime(editor_fs: Arc<BufferFileSystem>) -> Arc<ToolRuntimeContext> {
+    let session = Arc::new(Session::new());
+    session.set_editor_file_system(Some(editor_fs)).await;
+    Arc::new(ToolRuntimeContext {
+      session,
+      tool_registry: Arc::new(ToolRegistry::new()),
+      tx_event: None,
+      thread_id: "thread-1".to_string(),
+      turn_id: "turn-1".to_string(),
+      approval_policy: AskForApproval::OnRequest,
+      model_provider_id: None,
+      model_runtime_kind: None,
+      supports_native_web_search: false,
+      has_managed_network_requirements: false,
+      allowed_domains: Vec::new(),
+      denied_domains: Vec::new(),
+      network_attempt_id: None,
+    })
+  }
 
   // ── read_slice_async 测试 ─────────────────────────────────────────────
 
@@ -262,7 +317,7 @@ mod tests {
     tokio::fs::write(&file, "fn main() {}\n").await.unwrap();
 
     let path_str = file.to_string_lossy().to_string();
-    let section = read_one_file(path_str.clone(), file, 1, 100).await;
+    let section = read_one_file(None, path_str.clone(), file, 1, 100).await;
     assert!(section.starts_with(&format!("=== {path_str} ===")));
     assert!(section.contains("fn main()"));
   }
@@ -270,6 +325,7 @@ mod tests {
   #[tokio::test]
   async fn read_one_file_relative_path_returns_error() {
     let section = read_one_file(
+      None,
       "relative/path.txt".to_string(),
       PathBuf::from("relative/path.txt"),
       1,
@@ -283,7 +339,7 @@ mod tests {
   #[tokio::test]
   async fn read_one_file_missing_file_returns_error_section() {
     let path = "/absolutely/missing/file.txt";
-    let section = read_one_file(path.to_string(), PathBuf::from(path), 1, 10).await;
+    let section = read_one_file(None, path.to_string(), PathBuf::from(path), 1, 10).await;
     assert!(section.contains("Error:"));
   }
 
@@ -392,4 +448,30 @@ mod tests {
     assert!(text.contains("hello"));
     assert!(text.contains("Error:"));
   }
+
+  #[tokio::test]
+  async fn handler_reads_editor_buffers() {
+    use serde_json::json;
+
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("open.rs");
+    tokio::fs::write(&file, "saved\n").await.unwrap();
+    let editor_fs = Arc::new(BufferFileSystem::default().with_buffer(&file, "unsaved\nedit\n"));
+
+    let invocation = crate::tools::context::ToolInvocation {
+      id: "test-5".to_string(),
+      name: "read_many_files".to_string(),
+      payload: crate::tools::context::ToolPayload::Function {
+        arguments: json!({ "paths": [file.to_string_lossy()] }).to_string(),
+      },
+      cwd: dir.path().to_path_buf(),
+      runtime: Some(editor_runtime(editor_fs).await),
+    };
+
+    let output = ReadManyFilesHandler.handle_async(invocation).await.unwrap();
+    let text = output.text_content();
+    assert!(text.contains("L1: unsaved"));
+    assert!(text.contains("L2: edit"));
+    assert!(!text.contains("L1: saved"));
+  }
 }
@@ -0,0 +1,53 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::agent::team_runtime::runtime_for_thread;
+use crate::tools::context::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+pub struct ReadTeamDocHandler;
+
+#[derive(Debug, Deserialize)]
+struct ReadTeamDocArgs {
+  name: Option<String>,
+  version: Option<u64>,
+}
+
+#[async_trait]
+impl ToolHandler for ReadTeamDocHandler {
+  fn kind(&self) -> ToolKind {
+    ToolKind::Function
+  }
+
+  async fn handle_async(
+    &self,
+    invocation: ToolInvocation,
+  ) -> Result<ToolOutput, FunctionCallError> {
+    let args: ReadTeamDocArgs = invocation.parse_arguments()?;
+    let runtime = invocation.runtime.ok_or_else(|| {
+      FunctionCallError::Fatal("read_team_doc missing runtime context".to_string())
+    })?;
+    let team_runtime = runtime_for_thread(&runtime.thread_id).ok_or_else(|| {
+      FunctionCallError::Execution("read_team_doc runtime is not configured".to_string())
+    })?;
+
+    // Without a name, list the team's documents instead.
+    let serialized = match args.name.filter(|name| !name.trim().is_empty()) {
+      Some(name) => {
+        let doc = team_runtime.read_doc(&name, args.version).ok_or_else(|| {
+          FunctionCallError::RespondToModel(match args.version {
+            Some(version) => format!("team doc '{}' has no v{version}", name.trim()),
+            None => format!("team doc '{}' does not exist", name.trim()),
+          })
+        })?;
+        serde_json::to_string(&doc)
+      }
+      None => serde_json::to_string(&team_runtime.list_docs()),
+    }
+    .map_err(|err| FunctionCallError::Fatal(format!("failed to serialize team doc: {err}")))?;
+    Ok(ToolOutput::success(serialized).with_id(invocation.id))
+  }
+}
@@ -0,0 +1,79 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use cokra_protocol::CollabTaskUpdatedEvent;
+use cokra_protocol::EventMsg;
+
+use crate::agent::team_runtime::runtime_for_thread;
+use crate::agent::workflow::load_workflow_template;
+use crate::tools::context::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+pub struct RunWorkflowHandler;
+
+#[derive(Debug, Deserialize)]
+struct RunWorkflowArgs {
+  name: String,
+  goal: Option<String>,
+}
+
+#[async_trait]
+impl ToolHandler for RunWorkflowHandler {
+  fn kind(&self) -> ToolKind {
+    ToolKind::Function
+  }
+
+  async fn handle_async(
+    &self,
+    invocation: ToolInvocation,
+  ) -> Result<ToolOutput, FunctionCallError> {
+    let args: RunWorkflowArgs = invocation.parse_arguments()?;
+    let runtime = invocation.runtime.ok_or_else(|| {
+      FunctionCallError::Fatal("run_workflow missing runtime context".to_string())
+    })?;
+    let team_runtime = runtime_for_thread(&runtime.thread_id).ok_or_else(|| {
+      FunctionCallError::Execution("run_workflow runtime is not configured".to_string())
+    })?;
+    if !team_runtime.is_root_thread(&runtime.thread_id) {
+      return Err(FunctionCallError::RespondToModel(
+        "run_workflow is only available to the team leader".to_string(),
+      ));
+    }
+    let template = load_workflow_template(&invocation.cwd, args.name.trim())
+      .await
+      .map_err(|err| FunctionCallError::RespondToModel(err.to_string()))?;
+    let goal = args
+      .goal
+      .as_deref()
+      .map(str::trim)
+      .filter(|goal| !goal.is_empty());
+    let run = team_runtime
+      .run_workflow(&runtime.thread_id, &template, goal)
+      .await
+      .map_err(|err| FunctionCallError::RespondToModel(format!("{err:#}")))?;
+
+    if let Some(tx_event) = &runtime.tx_event {
+      let tasks = team_runtime
+        .snapshot()
+        .tasks
+        .into_iter()
+        .filter(|task| task.workflow_run_id.as_deref() == Some(run.id.as_str()));
+      for task in tasks {
+        let _ = tx_event
+          .send(EventMsg::CollabTaskUpdated(CollabTaskUpdatedEvent {
+            actor_thread_id: runtime.thread_id.clone(),
+            task,
+          }))
+          .await;
+      }
+    }
+
+    let out = ToolOutput::success(serde_json::to_string(&run).map_err(|err| {
+      FunctionCallError::Fatal(format!("failed to serialize workflow run: {err}"))
+    })?);
+    Ok(out.with_id(invocation.id))
+  }
+}
@@ -8,7 +8,9 @@ use cokra_protocol::EventMsg;
 use cokra_protocol::TeamMessageDeliveryMode;
 use cokra_protocol::TeamMessageKind;
 
+use crate::agent::team_runtime::AgentModelOverrides;
 use crate::agent::team_runtime::runtime_for_thread;
+use crate::model::ReasoningEffort;
 use crate::tools::context::FunctionCallError;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
@@ -28,6 +30,11 @@ struct SpawnAgentArgs {
   role: Option<String>,
   #[serde(alias = "type")]
   agent_type: Option<String>,
+  model: Option<String>,
+  provider: Option<String>,
+  #[serde(alias = "effort")]
+  reasoning_effort: Option<ReasoningEffort>,
+  temperature: Option<f32>,
 }
 
 #[derive(Debug, Serialize)]
@@ -36,6 +43,7 @@ struct SpawnAgentResult {
   agent_id: String,
   nickname: Option<String>,
   role: String,
+  model: String,
   status: String,
 }
 
@@ -142,6 +150,13 @@ fn resolve_message(
   Ok((message, nickname, role))
 }
 
+/// Trims an optional argument, treating a blank value as absent.
+pub(crate) fn non_empty(value: Option<String>) -> Option<String> {
+  value
+    .map(|value| value.trim().to_string())
+    .filter(|value| !value.is_empty())
+}
+
 #[async_trait]
 impl ToolHandler for SpawnAgentHandler {
   fn kind(&self) -> ToolKind {
@@ -159,12 +174,27 @@ impl ToolHandler for SpawnAgentHandler {
     let team_runtime = runtime_for_thread(&runtime.thread_id).ok_or_else(|| {
       FunctionCallError::Execution("spawn_agent runtime is not configured".to_string())
     })?;
+    let overrides = AgentModelOverrides {
+      model: non_empty(args.model.clone()),
+      provider: non_empty(args.provider.clone()),
+      reasoning_effort: args.reasoning_effort,
+      temperature: args.temperature,
+    };
     let (message, nickname, role) = resolve_message(args)?;
     let initial_message = message.clone();
     let thread_id = team_runtime
-      .spawn_agent(&runtime.thread_id, message, nickname.clone(), role.clone())
+      .spawn_agent(
+        &runtime.thread_id,
+        message,
+        nickname.clone(),
+        role.clone(),
+        overrides,
+      )
       .await
       .map_err(|err| FunctionCallError::Execution(err.to_string()))?;
+    let model = team_runtime
+      .agent_model(&thread_id.to_string())
+      .unwrap_or_default();
 
     if let Some(tx_event) = &runtime.tx_event {
       let sender = team_runtime.collab_agent_ref(&runtime.thread_id);
@@ -195,6 +225,7 @@ impl ToolHandler for SpawnAgentHandler {
         agent_id: thread_id.to_string(),
         nickname,
         role,
+        model,
         status: "running".to_string(),
       })
       .map_err(|err| {
@@ -240,6 +271,10 @@ mod tests {
       nickname: Some("手动名称".to_string()),
       role: None,
       agent_type: Some("explorer".to_string()),
+      model: None,
+      provider: None,
+      reasoning_effort: None,
+      temperature: None,
     })
     .expect("spawn args should parse");
 
@@ -256,6 +291,10 @@ mod tests {
       nickname: None,
       role: None,
       agent_type: None,
+      model: None,
+      provider: None,
+      reasoning_effort: None,
+      temperature: None,
     })
     .expect("spawn args should parse");
 
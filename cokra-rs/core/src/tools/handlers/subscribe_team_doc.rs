@@ -0,0 +1,55 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::agent::team_runtime::runtime_for_thread;
+use crate::tools::context::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+pub struct SubscribeTeamDocHandler;
+
+#[derive(Debug, Deserialize)]
+struct SubscribeTeamDocArgs {
+  name: String,
+  subscribe: Option<bool>,
+}
+
+#[async_trait]
+impl ToolHandler for SubscribeTeamDocHandler {
+  fn kind(&self) -> ToolKind {
+    ToolKind::Function
+  }
+
+  async fn handle_async(
+    &self,
+    invocation: ToolInvocation,
+  ) -> Result<ToolOutput, FunctionCallError> {
+    let args: SubscribeTeamDocArgs = invocation.parse_arguments()?;
+    let runtime = invocation.runtime.ok_or_else(|| {
+      FunctionCallError::Fatal("subscribe_team_doc missing runtime context".to_string())
+    })?;
+    let team_runtime = runtime_for_thread(&runtime.thread_id).ok_or_else(|| {
+      FunctionCallError::Execution("subscribe_team_doc runtime is not configured".to_string())
+    })?;
+    let doc = team_runtime
+      .subscribe_doc(
+        &runtime.thread_id,
+        &args.name,
+        args.subscribe.unwrap_or(true),
+      )
+      .await
+      .map_err(|err| FunctionCallError::RespondToModel(format!("{err:#}")))?;
+
+    let out = ToolOutput::success(
+      serde_json::json!({
+        "name": doc.name,
+        "version": doc.version,
+        "subscribers": doc.subscribers,
+      })
+      .to_string(),
+    );
+    Ok(out.with_id(invocation.id))
+  }
+}
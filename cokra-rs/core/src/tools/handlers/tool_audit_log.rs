@@ -90,6 +90,7 @@ impl ToolHandler for ToolAuditLogHandler {
             continue;
           }
           let spec = runtime.tool_registry.get_spec(&name);
+          let spec = spec.as_ref();
           let (output, status) = outputs
             .remove(&id)
             .map(|(output, is_error)| {
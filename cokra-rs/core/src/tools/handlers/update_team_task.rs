@@ -71,6 +71,7 @@ impl ToolHandler for UpdateTeamTaskHandler {
     };
     let task = team_runtime
       .update_task(
+        &runtime.thread_id,
         &args.task_id,
         args.status,
         assignee_thread_id,
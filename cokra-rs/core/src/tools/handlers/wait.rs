@@ -40,11 +40,12 @@ fn is_wait_satisfied(
   state: &crate::agent::team_runtime::ManagedAgentState,
   target_generation: u64,
 ) -> bool {
-  state.settled_generation >= target_generation
-    && !matches!(
-      state.lifecycle,
-      CollabAgentLifecycle::PendingInit | CollabAgentLifecycle::Busy
-    )
+  state.escalated
+    || (state.settled_generation >= target_generation
+      && !matches!(
+        state.lifecycle,
+        CollabAgentLifecycle::PendingInit | CollabAgentLifecycle::Busy
+      ))
 }
 
 async fn wait_for_agent_status(
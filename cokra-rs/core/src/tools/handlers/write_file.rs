@@ -56,9 +56,21 @@ impl ToolHandler for WriteFileHandler {
       })?;
     }
 
-    fs::write(&path, args.content.as_bytes()).map_err(|e| {
-      FunctionCallError::Execution(format!("failed to write {}: {e}", path.display()))
-    })?;
+    let editor_fs = match &invocation.runtime {
+      Some(runtime) => runtime.session.editor_file_system().await,
+      None => None,
+    };
+    match editor_fs {
+      Some(editor_fs) => editor_fs
+        .write_text_file(&path, &args.content)
+        .await
+        .map_err(|e| {
+          FunctionCallError::Execution(format!("failed to write {}: {e}", path.display()))
+        })?,
+      None => fs::write(&path, args.content.as_bytes()).map_err(|e| {
+        FunctionCallError::Execution(format!("failed to write {}: {e}", path.display()))
+      })?,
+    }
 
     let diag_suffix = collect_file_diagnostics(&path).await;
     Ok(ToolOutput::success(format!("wrote {}{}", path.display(), diag_suffix)).with_id(id))
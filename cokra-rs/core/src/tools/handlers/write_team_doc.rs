@@ -0,0 +1,61 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::agent::team_runtime::runtime_for_thread;
+use crate::tools::context::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+pub struct WriteTeamDocHandler;
+
+#[derive(Debug, Deserialize)]
+struct WriteTeamDocArgs {
+  name: String,
+  content: String,
+  #[serde(default)]
+  base_version: u64,
+  summary: Option<String>,
+}
+
+#[async_trait]
+impl ToolHandler for WriteTeamDocHandler {
+  fn kind(&self) -> ToolKind {
+    ToolKind::Function
+  }
+
+  async fn handle_async(
+    &self,
+    invocation: ToolInvocation,
+  ) -> Result<ToolOutput, FunctionCallError> {
+    let args: WriteTeamDocArgs = invocation.parse_arguments()?;
+    let runtime = invocation.runtime.ok_or_else(|| {
+      FunctionCallError::Fatal("write_team_doc missing runtime context".to_string())
+    })?;
+    let team_runtime = runtime_for_thread(&runtime.thread_id).ok_or_else(|| {
+      FunctionCallError::Execution("write_team_doc runtime is not configured".to_string())
+    })?;
+    let doc = team_runtime
+      .write_doc(
+        &runtime.thread_id,
+        &args.name,
+        args.content,
+        args.base_version,
+        args.summary,
+      )
+      .await
+      .map_err(|err| FunctionCallError::RespondToModel(format!("{err:#}")))?;
+
+    // Echoing the full content back would only repeat what the caller sent.
+    let out = ToolOutput::success(
+      serde_json::json!({
+        "name": doc.name,
+        "version": doc.version,
+        "subscribers": doc.subscribers,
+      })
+      .to_string(),
+    );
+    Ok(out.with_id(invocation.id))
+  }
+}
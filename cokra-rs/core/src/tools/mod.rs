@@ -29,6 +29,10 @@ use cokra_config::Config;
 use cokra_config::ExecBackend;
 use cokra_config::ExecPublicSurface;
 
+use crate::agent::role::build_spawn_agent_description;
+use crate::agent::role::discover_agent_roles;
+use crate::agent::workflow::build_run_workflow_description;
+use crate::agent::workflow::discover_workflow_templates;
 use crate::integrations::discover_integrations;
 use crate::integrations::manifest::IntegrationKind;
 use crate::integrations::project_integrations;
@@ -43,11 +47,14 @@ use crate::tool_runtime::ToolProvider;
 use crate::tool_runtime::ToolRuntimeCatalog;
 use crate::tool_runtime::UnifiedToolRuntime;
 use crate::tools::spec::build_specs;
+use crate::tools::spec::run_workflow_tool_with_description;
 use crate::tools::spec::skill_tool_with_description;
+use crate::tools::spec::spawn_agent_tool_with_description;
 
 pub use context::FunctionCallError;
 pub use context::ToolInvocation;
 pub use context::ToolOutput;
+pub use context::ToolPayload;
 pub use registry::ToolHandler;
 pub use registry::ToolKind;
 pub use registry::ToolRegistry;
@@ -163,6 +170,16 @@ pub(crate) async fn build_default_tools_with_cwd(
 pub(crate) async fn build_default_tooling_with_cwd(
   config: &Config,
   cwd: &std::path::Path,
+) -> anyhow::Result<DefaultToolingBundle> {
+  build_tooling_with_overrides(config, cwd, |_| {}).await
+}
+
+/// Like [`build_default_tooling_with_cwd`], but lets the caller swap builtin
+/// handlers before the catalog is snapshotted.
+pub(crate) async fn build_tooling_with_overrides(
+  config: &Config,
+  cwd: &std::path::Path,
+  override_handlers: impl FnOnce(&mut ToolRegistry),
 ) -> anyhow::Result<DefaultToolingBundle> {
   let mut registry = ToolRegistry::new();
   let integration_catalog = discover_integrations(cwd).await;
@@ -170,26 +187,38 @@ pub(crate) async fn build_default_tooling_with_cwd(
     tracing::warn!("{warning}");
   }
   let projected_integrations = project_integrations(&config.mcp, &integration_catalog)?;
-  let mcp_manager =
-    Arc::new(McpConnectionManager::new(&projected_integrations.effective_mcp).await?);
+  let mcp_manager = McpConnectionManager::new(
+    &projected_integrations.effective_mcp,
+    cwd,
+    &crate::cokra::map_sandbox_policy(config),
+  )
+  .await?;
   let exec_config = resolve_exec_tool_config(config);
 
   // Mirrors OpenCode's skill-tool pattern: compute the cwd-aware skill
   // description before registering specs so the synthetic `skill` entry reflects
   // the prompt assets actually available in this workspace.
   let skill_description = build_skill_tool_description(cwd).await;
+  let spawn_agent_description =
+    build_spawn_agent_description(&discover_agent_roles(config, cwd).await.roles);
+  let run_workflow_description =
+    build_run_workflow_description(&discover_workflow_templates(cwd).await.templates);
 
   for spec in build_specs() {
     if spec.name == "skill" {
       // Override the static skill spec with the dynamically generated description.
       registry.register_spec(skill_tool_with_description(&skill_description));
+    } else if spec.name == "spawn_agent" {
+      registry.register_spec(spawn_agent_tool_with_description(&spawn_agent_description));
+    } else if spec.name == "run_workflow" {
+      registry.register_spec(run_workflow_tool_with_description(
+        &run_workflow_description,
+      ));
     } else {
       registry.register_spec(spec);
     }
   }
-  for spec in mcp_manager.tool_specs() {
-    registry.register_spec(spec);
-  }
+  registry.set_mcp_specs(mcp_manager.tool_specs());
   for tool in projected_integrations
     .cli_tools
     .iter()
@@ -204,6 +233,7 @@ pub(crate) async fn build_default_tooling_with_cwd(
   register_default_aliases(&mut registry);
 
   handlers::register_builtin_handlers(&mut registry, Arc::clone(&mcp_manager));
+  override_handlers(&mut registry);
 
   // Model-based tool selection: GPT-codex models prefer apply_patch,
   // all other models prefer edit_file + write_file.
@@ -242,6 +272,7 @@ pub(crate) async fn build_default_tooling_with_cwd(
   );
 
   let registry = Arc::new(registry);
+  mcp_manager.attach_registry(&registry);
   let validator = Arc::new(ToolValidator::new(
     config.sandbox.clone(),
     config.approval.clone(),
@@ -265,6 +296,67 @@ pub(crate) async fn build_default_tooling_with_cwd(
   })
 }
 
+/// Build a kernel that only exposes `tools`, without MCP servers or
+/// integrations, for one-shot subagents that must not touch the workspace.
+pub(crate) fn build_restricted_tooling(
+  config: &Config,
+  tools: &[&str],
+) -> (Arc<ToolRegistry>, Arc<ToolRouter>) {
+  let mut registry = ToolRegistry::new();
+  for spec in build_specs() {
+    if tools.contains(&spec.name.as_str()) {
+      registry.register_spec(spec);
+    }
+  }
+  handlers::register_builtin_handlers(&mut registry, Arc::new(McpConnectionManager::empty()));
+  // Tradeoff: builtin handlers are registered wholesale, so the allow-list is
+  // also enforced at dispatch in case the model guesses an unlisted name.
+  registry.set_access_policy(registry::ToolAccessPolicy {
+    allowed: Some(tools.iter().map(ToString::to_string).collect()),
+    denied: Vec::new(),
+  });
+
+  let registry = Arc::new(registry);
+  let validator = Arc::new(ToolValidator::new(
+    config.sandbox.clone(),
+    config.approval.clone(),
+  ));
+  let router = Arc::new(ToolRouter::new_with_exec_config(
+    registry.clone(),
+    validator,
+    resolve_exec_tool_config(config),
+  ));
+  (registry, router)
+}
+
+/// Build a kernel holding only the collaboration tools, for a leader that
+/// serves them to a teammate running in a child process.
+pub(crate) async fn build_collaboration_tooling(
+  config: &Config,
+) -> anyhow::Result<DefaultToolingBundle> {
+  let names = build_specs()
+    .into_iter()
+    .filter(|spec| matches!(spec.source_kind, ToolSourceKind::BuiltinCollaboration))
+    .map(|spec| spec.name)
+    .collect::<Vec<_>>();
+  let names = names.iter().map(String::as_str).collect::<Vec<_>>();
+  let (registry, router) = build_restricted_tooling(config, &names);
+  let providers: Vec<Arc<dyn ToolProvider>> =
+    vec![Arc::new(BuiltinToolProvider::from_registry(&registry))];
+  let tool_catalog = Arc::new(ToolRuntimeCatalog::from_providers(&providers).await?);
+  let runtime = Arc::new(UnifiedToolRuntime::new(
+    tool_catalog,
+    providers,
+    Arc::clone(&router),
+  ));
+  Ok(DefaultToolingBundle {
+    registry,
+    router,
+    mcp_manager: Arc::new(McpConnectionManager::empty()),
+    runtime,
+  })
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -352,4 +444,92 @@ mod tests {
     assert_eq!(resolved.public_surface, SHELL_TOOL_NAME);
     assert_eq!(resolved.backend, ResolvedExecBackend::UnifiedExec);
   }
+
+  #[tokio::test]
+  async fn reviewer_role_is_listed_and_cannot_apply_patches() {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let agents_dir = temp.path().join(".cokra").join("agents");
+    std::fs::create_dir_all(&agents_dir).expect("agents dir");
+    std::fs::write(
+      agents_dir.join("reviewer.md"),
+      "---\ndescription: Reviews changes\ndenied_tools: apply_patch, edit_file, write_file\n---\nReview only.\n",
+    )
+    .expect("write role");
+    let config = config_with_model("gpt-5");
+
+    let tooling = build_default_tooling_with_cwd(&config, temp.path())
+      .await
+      .expect("tooling");
+    let spawn_spec = tooling
+      .registry
+      .get_spec("spawn_agent")
+      .expect("spawn_agent spec");
+    assert!(
+      spawn_spec
+        .description
+        .contains("- reviewer: Reviews changes")
+    );
+    assert!(tooling.registry.is_permitted("apply_patch"));
+
+    let role = crate::agent::role::load_agent_role(&config, temp.path(), "reviewer")
+      .await
+      .expect("reviewer role");
+    tooling.registry.set_access_policy(role.tool_policy());
+    assert!(!tooling.registry.is_permitted("apply_patch"));
+    assert!(tooling.registry.is_permitted("read_file"));
+  }
+
+  #[tokio::test]
+  async fn explorer_tooling_refuses_writes() {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let existing = temp.path().join("auth.rs");
+    std::fs::write(&existing, "fn login() {}\n").expect("write fixture");
+    let (registry, _router) = build_restricted_tooling(
+      &config_with_model("gpt-5"),
+      crate::agent::explore::EXPLORER_TOOLS,
+    );
+    let invoke = |name: &str, arguments: serde_json::Value| context::ToolInvocation {
+      id: format!("{name}-1"),
+      name: name.to_string(),
+      payload: context::ToolPayload::Function {
+        arguments: arguments.to_string(),
+      },
+      cwd: temp.path().to_path_buf(),
+      runtime: None,
+    };
+
+    let write = registry
+      .dispatch_async(invoke(
+        "write_file",
+        serde_json::json!({ "file_path": "new.rs", "content": "pwned" }),
+      ))
+      .await;
+    assert!(matches!(
+      write,
+      Err(context::FunctionCallError::PermissionDenied(_))
+    ));
+    let patch = registry
+      .dispatch_async(invoke(
+        "apply_patch",
+        serde_json::json!({
+          "input": "*** Begin Patch\n*** Delete File: auth.rs\n*** End Patch\n"
+        }),
+      ))
+      .await;
+    assert!(matches!(
+      patch,
+      Err(context::FunctionCallError::PermissionDenied(_))
+    ));
+    assert!(!temp.path().join("new.rs").exists());
+    assert!(existing.exists());
+
+    let read = registry
+      .dispatch_async(invoke(
+        "read_file",
+        serde_json::json!({ "file_path": existing.display().to_string() }),
+      ))
+      .await
+      .expect("read_file stays available");
+    assert!(read.text_content().contains("fn login()"));
+  }
 }
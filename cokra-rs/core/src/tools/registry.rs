@@ -2,8 +2,12 @@ use std::collections::HashMap;
 use std::collections::HashSet;
 use std::sync::Arc;
 use std::sync::RwLock;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 
 use async_trait::async_trait;
+use serde::Deserialize;
+use serde::Serialize;
 
 use crate::model::transform::ProviderRuntimeKind;
 use crate::tools::context::FunctionCallError;
@@ -49,6 +53,10 @@ pub trait ToolHandler: Send + Sync {
 pub struct ToolRegistry {
   handlers: HashMap<String, Arc<dyn ToolHandler>>,
   specs: HashMap<String, ToolSpec>,
+  /// MCP tools, replaced as a whole whenever a server connects, drops or
+  /// reports a changed tool list. Every one dispatches to `mcp_handler`.
+  mcp_specs: RwLock<HashMap<String, ToolSpec>>,
+  mcp_handler: Option<Arc<dyn ToolHandler>>,
   /// Tool name aliases: legacy_name -> current_name.
   /// When dispatching or looking up a tool, aliases are resolved transparently.
   aliases: HashMap<String, String>,
@@ -59,6 +67,39 @@ pub struct ToolRegistry {
   /// underlying integration. Builtins stay always-on unless explicitly
   /// excluded through the existing config-driven exclusion path.
   inactive_external: RwLock<HashSet<String>>,
+  /// Role-level allow/deny lists. Unlike `excluded`, a tool outside the
+  /// policy has no reachable handler, so it cannot be dispatched at all.
+  access_policy: RwLock<ToolAccessPolicy>,
+  /// Permitted dispatches so far; feeds teammate tool-call budgets.
+  dispatched_calls: AtomicU64,
+  /// Dispatches allowed in total; `None` is unlimited.
+  call_limit: RwLock<Option<u64>>,
+}
+
+/// Which tools an agent may call, by name. A trailing `*` matches a prefix
+/// (e.g. `mcp__github__*`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolAccessPolicy {
+  /// When set, only these tools are reachable.
+  pub allowed: Option<Vec<String>>,
+  /// Never reachable, even if also allowed.
+  pub denied: Vec<String>,
+}
+
+impl ToolAccessPolicy {
+  pub fn permits(&self, name: &str) -> bool {
+    let matches = |pattern: &String| match pattern.strip_suffix('*') {
+      Some(prefix) => name.starts_with(prefix),
+      None => pattern == name,
+    };
+    if self.denied.iter().any(matches) {
+      return false;
+    }
+    self
+      .allowed
+      .as_ref()
+      .is_none_or(|allowed| allowed.iter().any(matches))
+  }
 }
 
 impl ToolRegistry {
@@ -80,6 +121,31 @@ impl ToolRegistry {
     self.register_handler(name, handler);
   }
 
+  /// The handler behind every spec passed to [`Self::set_mcp_specs`].
+  pub fn register_mcp_handler(&mut self, handler: Arc<dyn ToolHandler>) {
+    self.mcp_handler = Some(handler);
+  }
+
+  /// Replace the MCP tools offered to the model. Safe to call mid-session;
+  /// the next request picks up the new set.
+  pub fn set_mcp_specs(&self, specs: Vec<ToolSpec>) {
+    *self
+      .mcp_specs
+      .write()
+      .unwrap_or_else(std::sync::PoisonError::into_inner) = specs
+      .into_iter()
+      .map(|spec| (spec.name.clone(), spec))
+      .collect();
+  }
+
+  fn has_mcp_spec(&self, name: &str) -> bool {
+    self
+      .mcp_specs
+      .read()
+      .unwrap_or_else(std::sync::PoisonError::into_inner)
+      .contains_key(name)
+  }
+
   // ── Alias management ──────────────────────────────────────────────
 
   /// Register a tool name alias. When the model calls `alias`, it is
@@ -155,6 +221,62 @@ impl ToolRegistry {
     excluded.contains(resolved) || excluded.contains(name)
   }
 
+  /// Restrict this registry to the tools a role may use.
+  pub fn set_access_policy(&self, policy: ToolAccessPolicy) {
+    *self
+      .access_policy
+      .write()
+      .unwrap_or_else(std::sync::PoisonError::into_inner) = policy;
+  }
+
+  /// Returns true if the access policy lets agents call this tool.
+  pub fn is_permitted(&self, name: &str) -> bool {
+    let policy = self
+      .access_policy
+      .read()
+      .unwrap_or_else(std::sync::PoisonError::into_inner);
+    policy.permits(self.resolve_name(name)) && policy.permits(name)
+  }
+
+  /// Number of permitted tool dispatches since the registry was built.
+  pub fn dispatched_calls(&self) -> u64 {
+    self.dispatched_calls.load(Ordering::Relaxed)
+  }
+
+  /// Refuse dispatches once `limit` calls have been made.
+  pub fn set_call_limit(&self, limit: Option<u64>) {
+    *self
+      .call_limit
+      .write()
+      .unwrap_or_else(std::sync::PoisonError::into_inner) = limit;
+  }
+
+  fn count_dispatch(&self) -> Result<(), FunctionCallError> {
+    let limit = *self
+      .call_limit
+      .read()
+      .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let previous = self.dispatched_calls.fetch_add(1, Ordering::Relaxed);
+    match limit {
+      Some(limit) if previous >= limit => {
+        self.dispatched_calls.fetch_sub(1, Ordering::Relaxed);
+        Err(FunctionCallError::PermissionDenied(format!(
+          "tool call budget of {limit} reached; stop calling tools and report your progress"
+        )))
+      }
+      _ => Ok(()),
+    }
+  }
+
+  fn ensure_permitted(&self, name: &str) -> Result<(), FunctionCallError> {
+    if self.is_permitted(name) {
+      return Ok(());
+    }
+    Err(FunctionCallError::PermissionDenied(format!(
+      "tool `{name}` is not available to this agent's role"
+    )))
+  }
+
   fn is_gateable(spec: &ToolSpec) -> bool {
     matches!(
       spec.source_kind,
@@ -165,13 +287,13 @@ impl ToolRegistry {
   }
 
   pub fn is_active(&self, name: &str) -> bool {
-    if self.is_excluded(name) {
+    if self.is_excluded(name) || !self.is_permitted(name) {
       return false;
     }
     let Some(spec) = self.get_spec(name) else {
       return false;
     };
-    if !Self::is_gateable(spec) {
+    if !Self::is_gateable(&spec) {
       return true;
     }
     let resolved = self.resolve_name(name).to_string();
@@ -186,7 +308,7 @@ impl ToolRegistry {
     let Some(spec) = self.get_spec(name) else {
       return false;
     };
-    if !Self::is_gateable(spec) {
+    if !Self::is_gateable(&spec) {
       return false;
     }
     let resolved = self.resolve_name(name).to_string();
@@ -201,7 +323,7 @@ impl ToolRegistry {
     let Some(spec) = self.get_spec(name) else {
       return false;
     };
-    if !Self::is_gateable(spec) {
+    if !Self::is_gateable(&spec) {
       return false;
     }
     let resolved = self.resolve_name(name).to_string();
@@ -258,9 +380,9 @@ impl ToolRegistry {
 
   pub fn active_external_tool_names(&self) -> Vec<String> {
     let mut names = self
-      .specs
-      .values()
-      .filter(|spec| Self::is_gateable(spec))
+      .list_specs()
+      .into_iter()
+      .filter(Self::is_gateable)
       .filter(|spec| self.is_active(&spec.name))
       .map(|spec| spec.name.clone())
       .collect::<Vec<_>>();
@@ -270,9 +392,9 @@ impl ToolRegistry {
 
   pub fn inactive_external_tool_names(&self) -> Vec<String> {
     let mut names = self
-      .specs
-      .values()
-      .filter(|spec| Self::is_gateable(spec))
+      .list_specs()
+      .into_iter()
+      .filter(Self::is_gateable)
       .filter(|spec| !self.is_active(&spec.name))
       .map(|spec| spec.name.clone())
       .collect::<Vec<_>>();
@@ -282,31 +404,63 @@ impl ToolRegistry {
 
   // ── Lookup (alias-aware, exclude-aware) ───────────────────────────
 
-  pub fn get_handler(&self, name: &str) -> Option<&Arc<dyn ToolHandler>> {
+  pub fn get_handler(&self, name: &str) -> Option<Arc<dyn ToolHandler>> {
+    if !self.is_permitted(name) {
+      return None;
+    }
     let resolved = self.resolve_name(name);
-    self
+    if let Some(handler) = self
       .handlers
       .get(resolved)
       .or_else(|| self.handlers.get(name))
+    {
+      return Some(Arc::clone(handler));
+    }
+    if self.has_mcp_spec(resolved) || self.has_mcp_spec(name) {
+      return self.mcp_handler.clone();
+    }
+    None
   }
 
-  pub fn get_spec(&self, name: &str) -> Option<&ToolSpec> {
+  pub fn get_spec(&self, name: &str) -> Option<ToolSpec> {
     let resolved = self.resolve_name(name);
-    self.specs.get(resolved).or_else(|| self.specs.get(name))
+    if let Some(spec) = self.specs.get(resolved).or_else(|| self.specs.get(name)) {
+      return Some(spec.clone());
+    }
+    let mcp_specs = self
+      .mcp_specs
+      .read()
+      .unwrap_or_else(std::sync::PoisonError::into_inner);
+    mcp_specs
+      .get(resolved)
+      .or_else(|| mcp_specs.get(name))
+      .cloned()
   }
 
   /// All registered specs (unfiltered).
   pub fn list_specs(&self) -> Vec<ToolSpec> {
-    self.specs.values().cloned().collect()
+    let mcp_specs = self
+      .mcp_specs
+      .read()
+      .unwrap_or_else(std::sync::PoisonError::into_inner);
+    self
+      .specs
+      .values()
+      .chain(
+        mcp_specs
+          .values()
+          .filter(|spec| !self.specs.contains_key(&spec.name)),
+      )
+      .cloned()
+      .collect()
   }
 
   /// Only non-excluded specs, for sending to the model.
   pub fn active_specs(&self) -> Vec<ToolSpec> {
     self
-      .specs
-      .values()
+      .list_specs()
+      .into_iter()
       .filter(|s| self.is_active(&s.name))
-      .cloned()
       .collect()
   }
 
@@ -318,6 +472,8 @@ impl ToolRegistry {
   // ── Dispatch (alias-aware) ────────────────────────────────────────
 
   pub fn dispatch(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+    self.ensure_permitted(&invocation.name)?;
+    self.count_dispatch()?;
     let handler = self
       .get_handler(&invocation.name)
       .ok_or_else(|| FunctionCallError::ToolNotFound(invocation.name.clone()))?;
@@ -329,6 +485,8 @@ impl ToolRegistry {
     &self,
     invocation: ToolInvocation,
   ) -> Result<ToolOutput, FunctionCallError> {
+    self.ensure_permitted(&invocation.name)?;
+    self.count_dispatch()?;
     let handler = self
       .get_handler(&invocation.name)
       .ok_or_else(|| FunctionCallError::ToolNotFound(invocation.name.clone()))?;
@@ -336,6 +494,7 @@ impl ToolRegistry {
   }
 
   pub fn is_mutating(&self, invocation: &ToolInvocation) -> Result<bool, FunctionCallError> {
+    self.ensure_permitted(&invocation.name)?;
     let handler = self
       .get_handler(&invocation.name)
       .ok_or_else(|| FunctionCallError::ToolNotFound(invocation.name.clone()))?;
@@ -431,6 +590,69 @@ mod tests {
     assert!(reg.get_handler("apply_patch").is_some());
   }
 
+  #[test]
+  fn access_policy_blocks_dispatch_of_denied_tools() {
+    let mut reg = ToolRegistry::new();
+    reg.register_tool(dummy_spec("read_file"), Arc::new(DummyHandler));
+    reg.register_tool(dummy_spec("apply_patch"), Arc::new(DummyHandler));
+    reg.register_tool(dummy_spec("mcp__github__search"), Arc::new(DummyHandler));
+    reg.register_alias("patch", "apply_patch");
+
+    reg.set_access_policy(ToolAccessPolicy {
+      allowed: Some(vec!["read_file".to_string(), "mcp__github__*".to_string()]),
+      denied: Vec::new(),
+    });
+    let names: HashSet<String> = reg.active_specs().into_iter().map(|s| s.name).collect();
+    assert_eq!(
+      names,
+      HashSet::from(["read_file".to_string(), "mcp__github__search".to_string()])
+    );
+    assert!(reg.get_handler("patch").is_none());
+    let err = reg
+      .dispatch(ToolInvocation {
+        id: "call-1".to_string(),
+        name: "apply_patch".to_string(),
+        payload: crate::tools::context::ToolPayload::Function {
+          arguments: "{}".to_string(),
+        },
+        cwd: std::path::PathBuf::new(),
+        runtime: None,
+      })
+      .expect_err("denied tool must not dispatch");
+    assert!(matches!(err, FunctionCallError::PermissionDenied(_)));
+
+    reg.set_access_policy(ToolAccessPolicy {
+      allowed: None,
+      denied: vec!["apply_patch".to_string()],
+    });
+    assert!(reg.is_permitted("read_file"));
+    assert!(!reg.is_permitted("patch"));
+  }
+
+  #[test]
+  fn call_limit_refuses_dispatch_once_spent() {
+    let mut reg = ToolRegistry::new();
+    reg.register_tool(dummy_spec("read_file"), Arc::new(DummyHandler));
+    reg.set_call_limit(Some(2));
+    let invocation = |id: &str| ToolInvocation {
+      id: id.to_string(),
+      name: "read_file".to_string(),
+      payload: crate::tools::context::ToolPayload::Function {
+        arguments: "{}".to_string(),
+      },
+      cwd: std::path::PathBuf::new(),
+      runtime: None,
+    };
+
+    assert!(reg.dispatch(invocation("call-1")).is_ok());
+    assert!(reg.dispatch(invocation("call-2")).is_ok());
+    let err = reg
+      .dispatch(invocation("call-3"))
+      .expect_err("third call exceeds the limit");
+    assert!(matches!(err, FunctionCallError::PermissionDenied(_)));
+    assert_eq!(reg.dispatched_calls(), 2);
+  }
+
   #[test]
   fn include_tool_re_enables() {
     let mut reg = ToolRegistry::new();
@@ -520,4 +742,21 @@ mod tests {
       Some(1)
     );
   }
+
+  #[test]
+  fn mcp_specs_are_replaced_live_and_dispatch_to_the_mcp_handler() {
+    let mut reg = ToolRegistry::new();
+    reg.register_tool(dummy_spec("read_file"), Arc::new(DummyHandler));
+    reg.register_mcp_handler(Arc::new(DummyHandler));
+    reg.set_mcp_specs(vec![dummy_spec("mcp__github__search")]);
+
+    assert_eq!(reg.model_tools().len(), 2);
+    assert!(reg.get_handler("mcp__github__search").is_some());
+
+    reg.set_mcp_specs(vec![dummy_spec("mcp__github__issues")]);
+    assert!(reg.get_spec("mcp__github__search").is_none());
+    assert!(reg.get_handler("mcp__github__search").is_none());
+    assert!(reg.get_handler("mcp__github__issues").is_some());
+    assert!(reg.get_handler("read_file").is_some());
+  }
 }
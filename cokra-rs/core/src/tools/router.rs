@@ -44,6 +44,7 @@ use cokra_protocol::ReviewDecision;
 use cokra_protocol::SandboxPolicy;
 
 use crate::agent::team_runtime::runtime_for_thread;
+use crate::agent::teammate_host::teammate_link;
 use crate::tools::ResolvedExecBackend;
 use crate::tools::ResolvedExecToolConfig;
 use crate::tools::SHELL_TOOL_NAME;
@@ -176,7 +177,7 @@ impl ToolRouter {
     run_ctx: ToolRunContext,
   ) -> Result<ToolOutput, FunctionCallError> {
     self.validate_call(&call)?;
-    let spec = self.registry.get_spec(&call.tool_name).cloned();
+    let spec = self.registry.get_spec(&call.tool_name);
 
     let mut runtime = RegistryToolRuntime::new(
       Arc::clone(&self.registry),
@@ -356,6 +357,10 @@ pub(crate) fn should_emit_exec_events(tool_name: &str) -> bool {
       | "send_team_message"
       | "send_team_nudge"
       | "read_team_messages"
+      | "read_team_doc"
+      | "write_team_doc"
+      | "diff_team_doc"
+      | "subscribe_team_doc"
       | "create_team_task"
       | "update_team_task"
       | "assign_team_task"
@@ -373,6 +378,7 @@ pub(crate) fn should_emit_exec_events(tool_name: &str) -> bool {
       | "handoff_team_task"
       | "submit_team_plan"
       | "approve_team_plan"
+      | "run_workflow"
   )
 }
 
@@ -696,8 +702,13 @@ fn lock_gate_exempt(tool_name: &str) -> bool {
     tool_name,
     "approve_team_plan"
       | "submit_team_plan"
+      | "run_workflow"
       | "team_status"
       | "read_team_messages"
+      | "read_team_doc"
+      | "write_team_doc"
+      | "diff_team_doc"
+      | "subscribe_team_doc"
       | "watch_team_inbox"
       | "ack_team_message"
       | "send_team_message"
@@ -752,9 +763,15 @@ async fn enforce_ownership_lock_gate(
   if !is_mutating_call(registry, spec, req, cwd) || lock_gate_exempt(req.tool_name.as_str()) {
     return Ok(());
   }
-  let Some(team_runtime) = runtime_for_thread(thread_id) else {
-    return Ok(());
+  let team_runtime = runtime_for_thread(thread_id);
+  // A teammate hosted in a child process asks its leader instead.
+  let link = match team_runtime {
+    Some(_) => None,
+    None => teammate_link(thread_id),
   };
+  if team_runtime.is_none() && link.is_none() {
+    return Ok(());
+  }
   if exec_requires_explicit_lock_paths(req, cwd) {
     return Err(
       "ownership lock gate requires explicit mutation paths; use write_file/edit_file/apply_patch or a command with explicit file arguments"
@@ -765,10 +782,25 @@ async fn enforce_ownership_lock_gate(
   if mutation_paths.is_empty() {
     return Ok(());
   }
-  team_runtime
-    .ensure_mutation_paths_owned(thread_id, &mutation_paths)
-    .await
-    .map_err(|err| err.to_string())
+  match (team_runtime, link) {
+    (Some(team_runtime), _) => team_runtime
+      .ensure_mutation_paths_owned(thread_id, &mutation_paths)
+      .await
+      .map_err(|err| err.to_string()),
+    (None, Some(link)) => link.ensure_mutation_paths_owned(&mutation_paths).await,
+    (None, None) => Ok(()),
+  }
+}
+
+/// Whether `thread_id` must wait for an approved plan before mutating.
+async fn requires_plan_approval(thread_id: &str) -> bool {
+  if let Some(team_runtime) = runtime_for_thread(thread_id) {
+    return team_runtime.requires_plan_approval(thread_id);
+  }
+  match teammate_link(thread_id) {
+    Some(link) => link.requires_plan_approval().await,
+    None => false,
+  }
 }
 
 fn normalize_lock_path(path: &str, cwd: &Path) -> String {
@@ -997,14 +1029,16 @@ impl ToolRuntime<ToolCall, ToolOutput> for RegistryToolRuntime {
     );
     if is_mutating
       && let Some(runtime) = &invocation.runtime
-      && let Some(team_runtime) = runtime_for_thread(&runtime.thread_id)
-      && team_runtime.requires_plan_approval(&runtime.thread_id)
       && !matches!(
         invocation.name.as_str(),
         "approve_team_plan"
           | "submit_team_plan"
           | "team_status"
           | "read_team_messages"
+          | "read_team_doc"
+          | "write_team_doc"
+          | "diff_team_doc"
+          | "subscribe_team_doc"
           | "watch_team_inbox"
           | "ack_team_message"
           | "send_team_message"
@@ -1028,6 +1062,7 @@ impl ToolRuntime<ToolCall, ToolOutput> for RegistryToolRuntime {
           | "wait"
           | "close_agent"
       )
+      && requires_plan_approval(&runtime.thread_id).await
     {
       return Err(ToolError::Execution(
         "team plan approval required before mutating work; submit a plan and wait for approval"
@@ -1317,7 +1352,7 @@ mod tests {
     let registry = Arc::new(registry);
     let runtime = RegistryToolRuntime::new(
       registry.clone(),
-      registry.get_spec("shell").cloned(),
+      registry.get_spec("shell"),
       AskForApproval::OnRequest,
       SandboxPolicy::ReadOnly {
         access: ReadOnlyAccess::FullAccess,
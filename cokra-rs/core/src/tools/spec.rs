@@ -10,7 +10,9 @@ mod primitive_specs;
 #[path = "spec/workflow_specs.rs"]
 mod workflow_specs;
 
+pub use collaboration_specs::spawn_agent_tool_with_description;
 pub use primitive_specs::skill_tool_with_description;
+pub use workflow_specs::run_workflow_tool_with_description;
 
 /// Whether additional properties are allowed, and if so, any required schema.
 ///
@@ -209,6 +211,7 @@ pub fn build_specs() -> Vec<ToolSpec> {
     "list_mcp_resource_templates",
     "read_mcp_resource",
     "spawn_agent",
+    "explore",
     "send_input",
     "wait",
     "close_agent",
@@ -220,12 +223,17 @@ pub fn build_specs() -> Vec<ToolSpec> {
     "cleanup_team",
     "submit_team_plan",
     "approve_team_plan",
+    "run_workflow",
     "team_status",
     "send_team_message",
     "send_team_nudge",
     "ack_team_message",
     "watch_team_inbox",
     "read_team_messages",
+    "read_team_doc",
+    "write_team_doc",
+    "diff_team_doc",
+    "subscribe_team_doc",
     "create_team_task",
     "update_team_task",
     "add_task_dependency",
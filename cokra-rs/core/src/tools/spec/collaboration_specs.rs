@@ -13,6 +13,7 @@ use super::str_field;
 pub(crate) fn build_specs() -> Vec<ToolSpec> {
   vec![
     spawn_agent_tool(),
+    explore_tool(),
     send_input_tool(),
     wait_tool(),
     close_agent_tool(),
@@ -29,6 +30,10 @@ pub(crate) fn build_specs() -> Vec<ToolSpec> {
     ack_team_message_tool(),
     watch_team_inbox_tool(),
     read_team_messages_tool(),
+    read_team_doc_tool(),
+    write_team_doc_tool(),
+    diff_team_doc_tool(),
+    subscribe_team_doc_tool(),
     create_team_task_tool(),
     update_team_task_tool(),
     release_task_leases_tool(),
@@ -80,6 +85,12 @@ fn scope_request_schema(description: &str) -> JsonSchema {
 }
 
 fn spawn_agent_tool() -> ToolSpec {
+  spawn_agent_tool_with_description(
+    "Spawn a sub-agent and immediately start it on an initial task.",
+  )
+}
+
+pub fn spawn_agent_tool_with_description(description: impl Into<String>) -> ToolSpec {
   let mut props = BTreeMap::new();
   props.insert(
     "task".to_string(),
@@ -89,17 +100,68 @@ fn spawn_agent_tool() -> ToolSpec {
     "nickname".to_string(),
     str_field("Optional human-readable teammate name shown in team UI."),
   );
-  props.insert("role".to_string(), str_field("Agent role."));
+  props.insert(
+    "role".to_string(),
+    str_field("Agent role; one of the roles listed in this tool's description, if any."),
+  );
   props.insert(
     "agent_type".to_string(),
     str_field("Alias of role for Codex-style compatibility."),
   );
+  props.insert(
+    "model".to_string(),
+    str_field(
+      "Optional model for this teammate, as provider/model or a bare model id. Defaults to the role's model, then the leader's.",
+    ),
+  );
+  props.insert(
+    "provider".to_string(),
+    str_field("Optional provider id for the teammate's model."),
+  );
+  props.insert(
+    "reasoning_effort".to_string(),
+    str_field("Optional reasoning effort for the teammate: minimal, low, medium, or high."),
+  );
+  props.insert(
+    "temperature".to_string(),
+    int_field("Optional sampling temperature for the teammate."),
+  );
+  collaboration_tool("spawn_agent", description, obj(props, &[])).with_permission_key("agent")
+}
+
+fn explore_tool() -> ToolSpec {
+  let mut props = BTreeMap::new();
+  props.insert(
+    "question".to_string(),
+    str_field(
+      "What to find out, with enough context for a reader who has not seen this conversation.",
+    ),
+  );
+  props.insert(
+    "model".to_string(),
+    str_field(
+      "Optional model for the explorer, as provider/model or a bare model id. Defaults to [agents.roles.explorer], then the caller's model.",
+    ),
+  );
+  props.insert(
+    "provider".to_string(),
+    str_field("Optional provider id for the explorer's model."),
+  );
+  props.insert(
+    "reasoning_effort".to_string(),
+    str_field("Optional reasoning effort for the explorer: minimal, low, medium, or high."),
+  );
   collaboration_tool(
-    "spawn_agent",
-    "Spawn a sub-agent and immediately start it on an initial task.",
-    obj(props, &[]),
+    "explore",
+    "Answer a read-only question about the workspace with a one-shot subagent. It searches and \
+     reads files with read_file, grep_files, glob, lsp and code_search, then returns only its \
+     condensed answer. It is not a teammate: no mailbox, tasks or leases. Call it several times \
+     in one response to explore in parallel.",
+    obj(props, &["question"]),
   )
   .with_permission_key("agent")
+  .with_supports_parallel(true)
+  .with_mutates_state(false)
 }
 
 fn send_input_tool() -> ToolSpec {
@@ -416,6 +478,86 @@ fn read_team_messages_tool() -> ToolSpec {
   .with_supports_parallel(true)
 }
 
+fn read_team_doc_tool() -> ToolSpec {
+  let mut props = BTreeMap::new();
+  props.insert(
+    "name".to_string(),
+    str_field("Team doc to read. Omit to list every team doc with its latest version."),
+  );
+  props.insert(
+    "version".to_string(),
+    int_field("Optional older version to read. Defaults to the latest."),
+  );
+  collaboration_tool(
+    "read_team_doc",
+    "Read a shared team document (design notes, API contracts, decision logs), or list them all.",
+    obj(props, &[]),
+  )
+  .with_supports_parallel(true)
+  .with_mutates_state(false)
+}
+
+fn write_team_doc_tool() -> ToolSpec {
+  let mut props = BTreeMap::new();
+  props.insert(
+    "name".to_string(),
+    str_field("Team doc name, e.g. api-contract.md."),
+  );
+  props.insert(
+    "content".to_string(),
+    str_field("Full new markdown content of the document."),
+  );
+  props.insert(
+    "base_version".to_string(),
+    int_field(
+      "Version your edit is based on, from read_team_doc. Use 0 to create a new doc. The write is rejected if the doc has moved on.",
+    ),
+  );
+  props.insert(
+    "summary".to_string(),
+    str_field("Optional one-line description of the change, sent to subscribers."),
+  );
+  collaboration_tool(
+    "write_team_doc",
+    "Save a new version of a shared team document. Subscribers are notified through their mailbox.",
+    obj(props, &["name", "content", "base_version"]),
+  )
+}
+
+fn diff_team_doc_tool() -> ToolSpec {
+  let mut props = BTreeMap::new();
+  props.insert("name".to_string(), str_field("Team doc name."));
+  props.insert(
+    "from_version".to_string(),
+    int_field("Optional older version. Defaults to the version before to_version."),
+  );
+  props.insert(
+    "to_version".to_string(),
+    int_field("Optional newer version. Defaults to the latest."),
+  );
+  collaboration_tool(
+    "diff_team_doc",
+    "Show a unified diff between two versions of a shared team document.",
+    obj(props, &["name"]),
+  )
+  .with_supports_parallel(true)
+  .with_mutates_state(false)
+}
+
+fn subscribe_team_doc_tool() -> ToolSpec {
+  let mut props = BTreeMap::new();
+  props.insert("name".to_string(), str_field("Team doc name."));
+  props.insert(
+    "subscribe".to_string(),
+    bool_field("Set false to unsubscribe. Defaults to true."),
+  );
+  collaboration_tool(
+    "subscribe_team_doc",
+    "Watch a shared team document. Each new version by someone else lands in your mailbox and wakes you when idle.",
+    obj(props, &["name"]),
+  )
+}
+
 fn create_team_task_tool() -> ToolSpec {
   let mut props = BTreeMap::new();
   props.insert("title".to_string(), str_field("Short team task title."));
@@ -504,7 +646,9 @@ fn update_team_task_tool() -> ToolSpec {
   );
   props.insert(
     "review_state".to_string(),
-    str_field("Optional review state: NotRequested, Requested, Approved, or ChangesRequested."),
+    str_field(
+      "Optional review state: NotRequested, Requested, Approved, or ChangesRequested. Only the task's reviewer (or the leader when it has none) may approve.",
+    ),
   );
   props.insert(
     "scope_policy_override".to_string(),
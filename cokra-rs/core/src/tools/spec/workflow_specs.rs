@@ -19,6 +19,7 @@ pub(crate) fn build_specs() -> Vec<ToolSpec> {
     claim_ready_task_tool(),
     claim_next_team_task_tool(),
     plan_tool(),
+    run_workflow_tool(),
   ]
 }
 
@@ -151,3 +152,23 @@ fn plan_tool() -> ToolSpec {
   )
   .with_permission_key("plan")
 }
+
+fn run_workflow_tool() -> ToolSpec {
+  run_workflow_tool_with_description(
+    "Start a workflow template: spawns its teammates and creates its tasks, dependencies, \
+     review gates and acceptance step as one workflow run.",
+  )
+}
+
+pub fn run_workflow_tool_with_description(description: impl Into<String>) -> ToolSpec {
+  let mut props = BTreeMap::new();
+  props.insert(
+    "name".to_string(),
+    str_field("Workflow template name; one of the workflows listed in this tool's description."),
+  );
+  props.insert(
+    "goal".to_string(),
+    str_field("Optional goal substituted for {{goal}} in the template's tasks and briefings."),
+  );
+  workflow_tool("run_workflow", description, obj(props, &["name"]))
+}
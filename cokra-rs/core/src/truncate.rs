@@ -1,11 +1,17 @@
 use std::borrow::Cow;
 
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::tokenizer::TokenCounter;
+
 /// Truncation strategy for model-facing text payloads.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum TruncationPolicy {
   /// Keep up to N lines.
   Lines(usize),
-  /// Keep up to N estimated tokens.
+  /// Keep up to N tokens, counted with the model's tokenizer where the
+  /// caller knows the model (see [`crate::tokenizer`]).
   Tokens(usize),
   /// Do not truncate.
   #[default]
@@ -33,19 +39,37 @@ impl std::ops::Mul<f64> for TruncationPolicy {
 
 /// Truncate text and include omission marker suitable for model consumption.
 pub fn formatted_truncate_text(text: &str, policy: TruncationPolicy) -> String {
+  formatted_truncate_text_with(text, policy, &TokenCounter::default())
+}
+
+/// Truncate text without additional formatting guarantees.
+pub fn truncate_text(text: &str, policy: TruncationPolicy) -> String {
+  truncate_text_with(text, policy, &TokenCounter::default())
+}
+
+/// [`formatted_truncate_text`] with token budgets measured by `counter`.
+pub(crate) fn formatted_truncate_text_with(
+  text: &str,
+  policy: TruncationPolicy,
+  counter: &TokenCounter,
+) -> String {
   match policy {
     TruncationPolicy::None => text.to_string(),
     TruncationPolicy::Lines(max_lines) => truncate_lines(text, max_lines, true),
-    TruncationPolicy::Tokens(max_tokens) => truncate_tokens(text, max_tokens, true),
+    TruncationPolicy::Tokens(max_tokens) => truncate_tokens(text, max_tokens, true, counter),
   }
 }
 
-/// Truncate text without additional formatting guarantees.
-pub fn truncate_text(text: &str, policy: TruncationPolicy) -> String {
+/// [`truncate_text`] with token budgets measured by `counter`.
+pub(crate) fn truncate_text_with(
+  text: &str,
+  policy: TruncationPolicy,
+  counter: &TokenCounter,
+) -> String {
   match policy {
     TruncationPolicy::None => text.to_string(),
     TruncationPolicy::Lines(max_lines) => truncate_lines(text, max_lines, false),
-    TruncationPolicy::Tokens(max_tokens) => truncate_tokens(text, max_tokens, false),
+    TruncationPolicy::Tokens(max_tokens) => truncate_tokens(text, max_tokens, false, counter),
   }
 }
 
@@ -83,35 +107,36 @@ fn truncate_lines(text: &str, max_lines: usize, include_marker: bool) -> String
   out
 }
 
-fn truncate_tokens(text: &str, max_tokens: usize, include_marker: bool) -> String {
+fn truncate_tokens(
+  text: &str,
+  max_tokens: usize,
+  include_marker: bool,
+  counter: &TokenCounter,
+) -> String {
   if max_tokens == 0 {
     return String::new();
   }
 
-  let estimated = estimate_tokens(text);
-  if estimated <= max_tokens {
+  let total = counter.count_text(text);
+  if total <= max_tokens {
     return text.to_string();
   }
 
-  // Fast approx: 1 token ~= 4 chars.
-  let max_chars = max_tokens.saturating_mul(4);
-  if max_chars == 0 {
-    return String::new();
-  }
-
-  let chars: Vec<char> = text.chars().collect();
-  if chars.len() <= max_chars {
+  // Spend half the budget on each end, measured with the model's own
+  // tokenizer (or the calibrated estimate) so dense code and CJK are cut
+  // shorter than prose.
+  let head_budget = max_tokens / 2;
+  let (head_len, tail_len) =
+    counter.head_and_tail_within(text, head_budget, max_tokens - head_budget);
+  if head_len + tail_len >= text.len() {
     return text.to_string();
   }
 
-  let head_chars = max_chars / 2;
-  let tail_chars = max_chars.saturating_sub(head_chars);
-
-  let head = chars[..head_chars].iter().collect::<String>();
-  let tail = chars[chars.len() - tail_chars..].iter().collect::<String>();
+  let head = &text[..head_len];
+  let tail = &text[text.len() - tail_len..];
 
-  let omitted_chars = chars.len().saturating_sub(head_chars + tail_chars);
-  let omitted_tokens = estimated.saturating_sub(max_tokens);
+  let omitted_chars = text[head_len..text.len() - tail_len].chars().count();
+  let omitted_tokens = total.saturating_sub(counter.count_text(head) + counter.count_text(tail));
 
   if include_marker {
     format!("{head}\n... (~{omitted_tokens} tokens / {omitted_chars} chars omitted) ...\n{tail}")
@@ -120,17 +145,6 @@ fn truncate_tokens(text: &str, max_tokens: usize, include_marker: bool) -> Strin
   }
 }
 
-fn estimate_tokens(text: &str) -> usize {
-  // Keep deterministic and dependency-free for phase-0.
-  // Use ceil(chars / 4), minimum 1 for non-empty strings.
-  let chars = text.chars().count();
-  if chars == 0 {
-    return 0;
-  }
-  let est = chars.div_ceil(4);
-  est.max(1)
-}
-
 pub fn maybe_truncated<'a>(text: &'a str, policy: TruncationPolicy) -> Cow<'a, str> {
   let truncated = truncate_text(text, policy);
   if truncated == text {
@@ -153,6 +167,31 @@ mod tests {
     assert!(out.contains("lines omitted"));
   }
 
+  #[test]
+  fn token_policy_keeps_less_cjk_than_ascii() {
+    let ascii = "x".repeat(2000);
+    let cjk = "字".repeat(2000);
+    let ascii_out = truncate_text(&ascii, TruncationPolicy::Tokens(100));
+    let cjk_out = truncate_text(&cjk, TruncationPolicy::Tokens(100));
+    assert_eq!(ascii_out.chars().count(), 400);
+    assert_eq!(cjk_out.chars().count(), 100);
+  }
+
+  #[test]
+  fn token_policy_counts_with_the_model_tokenizer() {
+    let counter = TokenCounter::for_model("gpt-4o");
+    let input = "alpha beta gamma delta epsilon ".repeat(200);
+    let out = truncate_text_with(&input, TruncationPolicy::Tokens(100), &counter);
+    assert_eq!(counter.count_text(&out), 100);
+    assert!(out.starts_with("alpha beta"));
+    assert!(out.ends_with("epsilon "));
+
+    // The default estimate weighs every letter at a quarter token, which
+    // keeps far fewer characters than the real encoder.
+    let estimated = truncate_text(&input, TruncationPolicy::Tokens(100));
+    assert!(estimated.len() < out.len());
+  }
+
   #[test]
   fn token_policy_truncates() {
     let input = "x".repeat(2000);
@@ -6,6 +6,8 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
+use serde::Deserialize;
+use serde::Serialize;
 use tokio::sync::mpsc;
 use tokio::task::JoinError;
 use tokio_util::sync::CancellationToken;
@@ -15,6 +17,7 @@ use crate::agent::team_runtime::runtime_for_thread;
 use crate::compaction::CompactionSettings;
 use crate::model::Message as ModelMessage;
 use crate::model::ModelClient;
+use crate::model::ReasoningRequest;
 use crate::model::transform::ProviderRuntimeKind;
 use crate::session::Session;
 use crate::skills::injection::build_explicit_prompt_injections;
@@ -75,6 +78,9 @@ pub enum TurnError {
 
   #[error("Fatal error: {0}")]
   Fatal(String),
+
+  #[error("Budget exceeded: {0}")]
+  BudgetExceeded(String),
 }
 
 impl TurnError {
@@ -84,7 +90,7 @@ impl TurnError {
 }
 
 /// Turn execution result
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TurnResult {
   /// Final assistant text.
   pub content: String,
@@ -95,7 +101,7 @@ pub struct TurnResult {
 }
 
 /// Turn configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TurnConfig {
   pub model: String,
   pub temperature: Option<f32>,
@@ -111,6 +117,8 @@ pub struct TurnConfig {
   pub tool_output_truncation: TruncationPolicy,
   pub context_window_limit: Option<usize>,
   pub compaction: CompactionSettings,
+  /// Effort/summary sent with every request; `None` leaves the model default.
+  pub reasoning: Option<ReasoningRequest>,
 }
 
 impl Default for TurnConfig {
@@ -132,6 +140,7 @@ impl Default for TurnConfig {
       tool_output_truncation: TruncationPolicy::Tokens(DEFAULT_TOOL_OUTPUT_TOKENS),
       context_window_limit: None,
       compaction: CompactionSettings::default(),
+      reasoning: None,
     }
   }
 }
@@ -867,7 +876,8 @@ fn team_digest_prompt(snapshot: &cokra_protocol::TeamSnapshot) -> String {
     members.sort_by(|left, right| {
       fn lifecycle_rank(lifecycle: &cokra_protocol::CollabAgentLifecycle) -> u8 {
         match lifecycle {
-          cokra_protocol::CollabAgentLifecycle::Error => 0,
+          cokra_protocol::CollabAgentLifecycle::Error
+          | cokra_protocol::CollabAgentLifecycle::Blocked => 0,
           cokra_protocol::CollabAgentLifecycle::Busy => 1,
           cokra_protocol::CollabAgentLifecycle::PendingInit => 2,
           cokra_protocol::CollabAgentLifecycle::Ready => 3,
@@ -906,6 +916,7 @@ fn team_digest_prompt(snapshot: &cokra_protocol::TeamSnapshot) -> String {
         cokra_protocol::CollabAgentLifecycle::Ready => "Ready",
         cokra_protocol::CollabAgentLifecycle::Busy => "Busy",
         cokra_protocol::CollabAgentLifecycle::Error => "Error",
+        cokra_protocol::CollabAgentLifecycle::Blocked => "Blocked",
         cokra_protocol::CollabAgentLifecycle::Shutdown => "Shutdown",
         cokra_protocol::CollabAgentLifecycle::NotFound => "NotFound",
       };
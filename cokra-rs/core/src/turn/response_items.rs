@@ -38,6 +38,7 @@ impl ResponseItem {
       ModelMessage::Tool {
         tool_call_id,
         content,
+        ..
       } => Some(Self::FunctionCallOutput {
         call_id: tool_call_id.clone(),
         output: content.clone(),
@@ -8,34 +8,47 @@ use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
 use cokra_protocol::AgentMessageContentDeltaEvent;
+use cokra_protocol::AgentReasoningDeltaEvent;
+use cokra_protocol::AgentReasoningEvent;
+use cokra_protocol::AgentReasoningRawContentDeltaEvent;
+use cokra_protocol::AgentReasoningRawContentEvent;
+use cokra_protocol::AgentReasoningSectionBreakEvent;
 use cokra_protocol::ContextCompactedEvent;
 use cokra_protocol::ContextCompactionReason;
 use cokra_protocol::EventMsg;
 use cokra_protocol::FunctionCallEvent;
 use cokra_protocol::ItemCompletedEvent;
 use cokra_protocol::ItemStartedEvent;
+use cokra_protocol::RequestUserInputQuestion;
+use cokra_protocol::RequestUserInputQuestionOption;
 use cokra_protocol::ResponseEvent;
 use cokra_protocol::TokenCountEvent;
 
+use crate::agent::team_runtime::runtime_for_thread;
+use crate::agent::teammate_host::teammate_link;
 use crate::compaction::compact_history_with_summary;
 use crate::compaction::prepare_compaction;
 use crate::model::ChatRequest;
 use crate::model::Message as ModelMessage;
 use crate::model::ModelClient;
 use crate::model::ModelError;
+use crate::model::ThinkingBlock;
 use crate::model::ToolCall as ModelToolCall;
 use crate::model::ToolCallFunction;
 use crate::model::ToolCallProviderMeta;
 use crate::model::Usage;
+use crate::model::cost::request_cost_usd;
 use crate::model::transform::ProviderRuntimeKind;
+use crate::session::BudgetStatus;
 use crate::session::Session;
+use crate::tokenizer::TokenCounter;
 use crate::tools::context::ToolOutput;
 use crate::tools::parallel::ToolCallRuntime;
 use crate::tools::registry::ToolRegistry;
 use crate::tools::router::ToolCall;
 use crate::tools::router::ToolRouter;
 use crate::tools::router::ToolRunContext;
-use crate::truncate::truncate_text;
+use crate::truncate::truncate_text_with;
 
 use super::executor::TurnConfig;
 use super::executor::TurnError;
@@ -44,10 +57,16 @@ use super::response_items::ResponseItem;
 use super::text_function_calls::FunctionCallsTextFilter;
 use super::text_function_calls::parse_text_function_calls;
 
+const BUDGET_QUESTION_ID: &str = "budget";
+const BUDGET_CONTINUE_LABEL: &str = "Continue";
+const BUDGET_STOP_LABEL: &str = "Stop";
+
 #[derive(Debug)]
 struct SamplingRequestResult {
   assistant_delta: String,
   function_calls: Vec<FunctionCallEvent>,
+  /// Signed reasoning to replay ahead of the tool calls (Anthropic).
+  thinking: Option<ThinkingBlock>,
 }
 
 #[derive(Clone)]
@@ -116,6 +135,9 @@ impl SseTurnExecutor {
     item_id: &str,
     cancellation_token: CancellationToken,
   ) -> Result<SamplingRequestResult, TurnError> {
+    self
+      .enforce_budget(thread_id, turn_id, &cancellation_token)
+      .await?;
     let runtime_info = self
       .model_client
       .runtime_info_for_model(&self.config.model)
@@ -141,8 +163,12 @@ impl SseTurnExecutor {
         None
       },
       stream: true,
+      prompt_cache_key: Some(thread_id.to_string()),
+      reasoning: self.config.reasoning,
       ..Default::default()
     };
+    let estimated_prompt_tokens =
+      TokenCounter::for_model(&self.config.model).count_request_uncalibrated(&request);
 
     let mut stream = self
       .model_client
@@ -152,6 +178,10 @@ impl SseTurnExecutor {
     let mut assistant_delta = String::new();
     let mut function_calls: Vec<FunctionCallEvent> = Vec::new();
     let mut text_filter = FunctionCallsTextFilter::new();
+    let mut reasoning_summary = String::new();
+    let mut reasoning_summary_index = None;
+    let mut reasoning_content = String::new();
+    let mut reasoning_signature = None;
 
     loop {
       let event = tokio::select! {
@@ -187,6 +217,40 @@ impl SseTurnExecutor {
         ResponseEvent::FunctionCall(call) => {
           function_calls.push(call);
         }
+        ResponseEvent::ReasoningSummaryDelta {
+          delta,
+          summary_index,
+        } => {
+          if reasoning_summary_index.is_some_and(|index| index != summary_index) {
+            reasoning_summary.push_str("\n\n");
+            self
+              .send_event(EventMsg::AgentReasoningSectionBreak(
+                AgentReasoningSectionBreakEvent {
+                  item_id: item_id.to_string(),
+                  summary_index: summary_index as i64,
+                },
+              ))
+              .await?;
+          }
+          reasoning_summary_index = Some(summary_index);
+          reasoning_summary.push_str(&delta);
+          self
+            .send_event(EventMsg::AgentReasoningDelta(AgentReasoningDeltaEvent {
+              delta,
+            }))
+            .await?;
+        }
+        ResponseEvent::ReasoningContentDelta { delta, .. } => {
+          reasoning_content.push_str(&delta);
+          self
+            .send_event(EventMsg::AgentReasoningRawContentDelta(
+              AgentReasoningRawContentDeltaEvent { delta },
+            ))
+            .await?;
+        }
+        ResponseEvent::ReasoningSignature { signature } => {
+          reasoning_signature = Some(signature);
+        }
         ResponseEvent::Completed {
           token_usage: Some(usage),
           ..
@@ -194,11 +258,46 @@ impl SseTurnExecutor {
           self
             .session
             .set_token_usage(&Usage {
-              input_tokens: usage.input_tokens.max(0) as u32,
+              input_tokens: (usage.input_tokens
+                + usage.cached_input_tokens
+                + usage.cache_write_input_tokens)
+                .max(0) as u32,
               output_tokens: usage.output_tokens.max(0) as u32,
               total_tokens: usage.total_tokens.max(0) as u32,
+              cached_input_tokens: usage.cached_input_tokens.max(0) as u32,
+              cache_write_input_tokens: usage.cache_write_input_tokens.max(0) as u32,
             })
             .await;
+          self
+            .session
+            .calibrate_token_counter(
+              &self.config.model,
+              estimated_prompt_tokens,
+              (usage.input_tokens + usage.cached_input_tokens + usage.cache_write_input_tokens)
+                .max(0) as u64,
+            )
+            .await;
+          // What this request leaves in context: the prompt as the model's
+          // tokenizer counts it plus the reply so far.
+          let counter = self.session.token_counter_for(&self.config.model).await;
+          let context_tokens = counter.calibrate(estimated_prompt_tokens)
+            + counter.count_text(&assistant_delta)
+            + function_calls
+              .iter()
+              .map(|call| {
+                counter.count_text(&call.function.name)
+                  + counter.count_text(&call.function.arguments)
+              })
+              .sum::<usize>();
+          let cost_usd = self
+            .model_client
+            .resolve_model_cost(&self.config.model)
+            .await
+            .map(|pricing| request_cost_usd(&pricing, &usage));
+          let totals = self
+            .session
+            .record_request_cost(Some(turn_id), &usage, cost_usd)
+            .await;
           self
             .send_event(EventMsg::TokenCount(TokenCountEvent {
               thread_id: thread_id.to_string(),
@@ -208,6 +307,12 @@ impl SseTurnExecutor {
               output_tokens: usage.output_tokens.max(0),
               reasoning_output_tokens: usage.reasoning_output_tokens.max(0),
               total_tokens: usage.total_tokens.max(0),
+              cache_write_input_tokens: usage.cache_write_input_tokens.max(0),
+              context_tokens: context_tokens as i64,
+              cost_usd,
+              turn_cost_usd: totals.turn_cost_usd,
+              thread_cost_usd: totals.thread_cost_usd,
+              session_cost_usd: totals.session_cost_usd,
             }))
             .await?;
         }
@@ -222,12 +327,116 @@ impl SseTurnExecutor {
       }
     }
 
+    if !reasoning_summary.is_empty() {
+      self
+        .send_event(EventMsg::AgentReasoning(AgentReasoningEvent {
+          text: reasoning_summary,
+        }))
+        .await?;
+    }
+    if !reasoning_content.is_empty() {
+      self
+        .send_event(EventMsg::AgentReasoningRawContent(
+          AgentReasoningRawContentEvent {
+            text: reasoning_content.clone(),
+          },
+        ))
+        .await?;
+    }
+
     Ok(SamplingRequestResult {
       assistant_delta,
       function_calls,
+      // Tradeoff: interleaved thinking can produce several signed blocks per
+      // response; only the last signature is kept, over all of the text.
+      thinking: reasoning_signature.map(|signature| ThinkingBlock {
+        text: reasoning_content,
+        signature,
+      }),
     })
   }
 
+  /// Spend check before each model request. The hard limit stops the turn;
+  /// the first soft-limit crossing pauses the root thread for confirmation.
+  async fn enforce_budget(
+    &self,
+    thread_id: &str,
+    turn_id: &str,
+    cancellation_token: &CancellationToken,
+  ) -> Result<(), TurnError> {
+    let ledger = self.session.cost_ledger();
+    let (spent_usd, limit_usd) = match ledger.budget_status() {
+      BudgetStatus::WithinBudget => return Ok(()),
+      BudgetStatus::HardLimitReached {
+        spent_usd,
+        limit_usd,
+      } => {
+        return Err(TurnError::BudgetExceeded(format!(
+          "session spend ${spent_usd:.2} reached the hard limit of ${limit_usd:.2}"
+        )));
+      }
+      BudgetStatus::SoftLimitReached {
+        spent_usd,
+        limit_usd,
+      } => (spent_usd, limit_usd),
+    };
+
+    // Tradeoff: teammates cannot prompt the user, so they keep working past the
+    // soft limit; the hard limit is what stops a runaway team.
+    let is_teammate = match runtime_for_thread(thread_id) {
+      Some(team_runtime) => !team_runtime.is_root_thread(thread_id),
+      None => teammate_link(thread_id).is_some(),
+    };
+    if is_teammate {
+      return Ok(());
+    }
+
+    let question = RequestUserInputQuestion {
+      id: BUDGET_QUESTION_ID.to_string(),
+      header: "Budget".to_string(),
+      question: format!(
+        "Session spend is ${spent_usd:.2}, past the soft limit of ${limit_usd:.2}. Keep going?"
+      ),
+      is_other: false,
+      is_secret: false,
+      options: Some(vec![
+        RequestUserInputQuestionOption {
+          label: BUDGET_CONTINUE_LABEL.to_string(),
+          description: "Continue and don't ask again this session".to_string(),
+        },
+        RequestUserInputQuestionOption {
+          label: BUDGET_STOP_LABEL.to_string(),
+          description: "Stop this turn".to_string(),
+        },
+      ]),
+    };
+    let request_id = format!("budget-{}", Uuid::new_v4());
+    let response = tokio::select! {
+      _ = cancellation_token.cancelled() => return Err(TurnError::TurnAborted),
+      response = self.session.request_user_input(
+        thread_id.to_string(),
+        turn_id.to_string(),
+        request_id.clone(),
+        request_id,
+        vec![question],
+        Some(self.tx_event.clone()),
+      ) => response,
+    };
+    let approved = response.is_some_and(|response| {
+      response
+        .answers
+        .get(BUDGET_QUESTION_ID)
+        .is_some_and(|answer| answer.answers.iter().any(|a| a == BUDGET_CONTINUE_LABEL))
+    });
+    if !approved {
+      return Err(TurnError::BudgetExceeded(format!(
+        "stopped at the soft limit of ${limit_usd:.2} (spent ${spent_usd:.2})"
+      )));
+    }
+    ledger.acknowledge_soft_limit();
+    Ok(())
+  }
+
   async fn run_sampling_request(
     &self,
     messages: &mut Vec<ModelMessage>,
@@ -317,16 +526,18 @@ impl SseTurnExecutor {
     }
 
     let history = self.session.clone_history().await;
-    if prepare_compaction(&history, &self.config.compaction).is_none() {
+    let counter = self.session.token_counter_for(&self.config.model).await;
+    if prepare_compaction(&history, &self.config.compaction, &counter).is_none() {
       return Ok(false);
     }
 
-    let tokens_before_est = estimate_messages_tokens(messages);
+    let tokens_before_est = counter.count_messages(messages);
     let Some(compaction) = compact_history_with_summary(
       self.model_client.as_ref(),
       &self.config.model,
       &history,
       &self.config.compaction,
+      &counter,
     )
     .await
     .map_err(TurnError::ModelError)?
@@ -339,8 +550,9 @@ impl SseTurnExecutor {
       .session
       .replace_history(compaction.compacted_history.clone())
       .await;
+    let invalidated_cached_tokens = self.session.take_cached_input_tokens().await;
     let rebuilt = self.rebuild_messages_from_session().await;
-    let tokens_after_est = estimate_messages_tokens(&rebuilt);
+    let tokens_after_est = counter.count_messages(&rebuilt);
     *messages = rebuilt;
 
     self
@@ -352,6 +564,7 @@ impl SseTurnExecutor {
         tokens_after_est,
         reserve_tokens: self.config.compaction.reserve_tokens,
         keep_recent_tokens: self.config.compaction.keep_recent_tokens,
+        invalidated_cached_tokens: invalidated_cached_tokens as i64,
       }))
       .await?;
 
@@ -372,7 +585,8 @@ impl SseTurnExecutor {
       return Ok(());
     };
     let threshold = context_window_limit.saturating_sub(self.config.compaction.reserve_tokens);
-    if threshold == 0 || estimate_messages_tokens(messages) < threshold {
+    let counter = self.session.token_counter_for(&self.config.model).await;
+    if threshold == 0 || counter.count_messages(messages) < threshold {
       return Ok(());
     }
 
@@ -395,6 +609,13 @@ impl SseTurnExecutor {
   ) -> Result<TurnResult, TurnError> {
     let mut final_content = String::new();
     let turn_cancellation = self.cancellation_token.child_token();
+    // Images returned by tools only go to models that take image input;
+    // other models see the text placeholder in the tool output.
+    let accepts_images = self
+      .model_client
+      .resolve_model_catalog(&self.config.model)
+      .await
+      .is_some_and(|entry| entry.image_input);
 
     loop {
       if turn_cancellation.is_cancelled() {
@@ -451,6 +672,7 @@ impl SseTurnExecutor {
       let SamplingRequestResult {
         mut assistant_delta,
         mut function_calls,
+        thinking,
       } = self
         .run_sampling_request(
           &mut messages,
@@ -485,12 +707,17 @@ impl SseTurnExecutor {
         tool_calls: if function_calls.is_empty() {
           None
         } else {
-          Some(
-            function_calls
-              .iter()
-              .map(Self::to_model_tool_call)
-              .collect::<Vec<_>>(),
-          )
+          let mut calls = function_calls
+            .iter()
+            .map(Self::to_model_tool_call)
+            .collect::<Vec<_>>();
+          if let (Some(first), Some(thinking)) = (calls.first_mut(), thinking) {
+            first
+              .provider_meta
+              .get_or_insert_with(ToolCallProviderMeta::default)
+              .thinking = Some(thinking);
+          }
+          Some(calls)
         },
       };
       messages.push(assistant_message.clone());
@@ -587,6 +814,7 @@ impl SseTurnExecutor {
       // codex applies a 1.2x budget multiplier to account for JSON
       // serialization overhead.
       let truncation_policy = self.config.tool_output_truncation * 1.2;
+      let counter = self.session.token_counter_for(&self.config.model).await;
 
       while let Some(output_res) = in_flight.next().await {
         let (call_id, output) = output_res?;
@@ -596,12 +824,20 @@ impl SseTurnExecutor {
           output.id().to_string()
         };
 
-        let truncated_content = truncate_text(&output.text_content(), truncation_policy);
+        let truncated_content =
+          truncate_text_with(&output.text_content(), truncation_policy, &counter);
         let tool_msg = ModelMessage::Tool {
           tool_call_id: output_call_id,
           content: truncated_content,
+          images: Vec::new(),
         };
-        messages.push(tool_msg.clone());
+        // Images ride along for the rest of this turn only; history keeps the
+        // placeholder so later turns do not re-send the base64 data.
+        let mut turn_msg = tool_msg.clone();
+        if accepts_images && let ModelMessage::Tool { images, .. } = &mut turn_msg {
+          *images = output.images();
+        }
+        messages.push(turn_msg);
         self.session.append_message(tool_msg.clone()).await;
         self
           .session
@@ -647,6 +883,7 @@ impl SseTurnExecutor {
         .clone()
         .map(|sig| ToolCallProviderMeta {
           thought_signature: Some(sig),
+          ..Default::default()
         }),
     }
   }
@@ -741,23 +978,6 @@ impl SseTurnExecutor {
   }
 }
 
-fn estimate_message_tokens(msg: &ModelMessage) -> usize {
-  let text_len = match msg {
-    ModelMessage::System(text) | ModelMessage::User(text) => text.chars().count(),
-    ModelMessage::Assistant { content, .. } => content.as_deref().map_or(0, |s| s.chars().count()),
-    ModelMessage::Tool { content, .. } => content.chars().count(),
-  };
-  if text_len == 0 {
-    1
-  } else {
-    text_len.div_ceil(4)
-  }
-}
-
-fn estimate_messages_tokens(messages: &[ModelMessage]) -> usize {
-  messages.iter().map(estimate_message_tokens).sum()
-}
-
 fn map_stream_model_error(err: ModelError) -> TurnError {
   match err {
     ModelError::StreamError(msg) => TurnError::Stream(msg, None),
@@ -851,6 +1071,8 @@ mod tests {
   #[derive(Debug)]
   enum MockStep {
     Delta(&'static str),
+    Reasoning(&'static str),
+    Signature(&'static str),
     CompletedUsage(ResponseTokenUsage),
     Call {
       id: &'static str,
@@ -954,6 +1176,13 @@ mod tests {
           text: text.to_string(),
           index: 0,
         })),
+        MockStep::Reasoning(text) => Ok(ResponseEvent::ReasoningContentDelta {
+          delta: text.to_string(),
+          content_index: 0,
+        }),
+        MockStep::Signature(signature) => Ok(ResponseEvent::ReasoningSignature {
+          signature: signature.to_string(),
+        }),
         MockStep::CompletedUsage(usage) => Ok(ResponseEvent::Completed {
           response_id: "resp_mock".to_string(),
           token_usage: Some(usage),
@@ -1022,6 +1251,30 @@ mod tests {
     }
   }
 
+  #[derive(Debug)]
+  struct ScreenshotHandler;
+
+  #[async_trait]
+  impl ToolHandler for ScreenshotHandler {
+    fn kind(&self) -> ToolKind {
+      ToolKind::Function
+    }
+
+    fn handle(&self, _invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+      Ok(ToolOutput::Mcp {
+        id: String::new(),
+        result: Ok(crate::tools::context::McpToolCallResult {
+          content: vec![crate::tools::context::ToolContentPart::Image {
+            mime_type: "image/png".to_string(),
+            data: "iVBORw0KGgo=".to_string(),
+          }],
+          structured_content: None,
+          is_error: false,
+        }),
+      })
+    }
+  }
+
   async fn build_client(provider: MockResponsesProvider) -> Arc<ModelClient> {
     let registry = Arc::new(ProviderRegistry::new());
     registry.register(provider).await;
@@ -1084,6 +1337,7 @@ mod tests {
         output_tokens: 12,
         reasoning_output_tokens: 4,
         total_tokens: 96,
+        cache_write_input_tokens: 0,
       }),
       MockStep::End,
     ]]);
@@ -1137,6 +1391,53 @@ mod tests {
     assert_eq!(token_count.output_tokens, 12);
     assert_eq!(token_count.reasoning_output_tokens, 4);
     assert_eq!(token_count.total_tokens, 96);
+    // Counted locally rather than copied from the provider's usage.
+    assert!(token_count.context_tokens > 0);
+    assert_ne!(token_count.context_tokens, token_count.total_tokens);
+    assert_eq!(token_count.cost_usd, None);
+    assert_eq!(token_count.thread_cost_usd, 0.0);
+  }
+
+  #[tokio::test]
+  async fn test_sse_hard_budget_limit_stops_before_request() {
+    let provider = MockResponsesProvider::new(vec![vec![MockStep::Delta("unused"), MockStep::End]]);
+
+    let model_client = build_client(provider).await;
+    let tool_registry = Arc::new(ToolRegistry::new());
+    let tool_router = build_router(tool_registry.clone());
+    let session = Arc::new(Session::new());
+    session.cost_ledger().set_limits(None, Some(1.0));
+    session
+      .record_request_cost(Some("turn-0"), &ResponseTokenUsage::default(), Some(1.5))
+      .await;
+    let (tx_event, rx_event) = mpsc::channel(64);
+
+    let executor = SseTurnExecutor::new(
+      model_client,
+      tool_registry,
+      tool_router,
+      session,
+      tx_event,
+      test_config(),
+    );
+
+    let err = executor
+      .run_sse_interaction(
+        vec![ModelMessage::User("hello".to_string())],
+        "thread-1".to_string(),
+        "turn-1".to_string(),
+      )
+      .await
+      .expect_err("hard limit should stop the turn");
+
+    assert!(matches!(err, TurnError::BudgetExceeded(_)), "{err:?}");
+    let events = collect_events(rx_event);
+    assert!(
+      !events
+        .iter()
+        .any(|event| matches!(event, EventMsg::AgentMessageContentDelta(_))),
+      "no model request should be made past the hard limit"
+    );
   }
 
   #[tokio::test]
@@ -1158,7 +1459,7 @@ mod tests {
     ])
     .with_second_call_check(|messages| {
       let saw_tool_output = messages.iter().any(|msg| {
-        matches!(msg, ModelMessage::Tool { tool_call_id, content } if tool_call_id == "read_1" && content.contains("hello from tool"))
+        matches!(msg, ModelMessage::Tool { tool_call_id, content, .. } if tool_call_id == "read_1" && content.contains("hello from tool"))
       });
       if saw_tool_output {
         Ok(())
@@ -1213,6 +1514,217 @@ mod tests {
     assert_eq!(item_completed, 2);
   }
 
+  #[tokio::test]
+  async fn test_sse_tool_images_need_image_input_and_stay_out_of_history() {
+    let provider = MockResponsesProvider::new(vec![
+      vec![
+        MockStep::Call {
+          id: "shot_1",
+          name: "screenshot",
+          arguments: "{}",
+        },
+        MockStep::End,
+      ],
+      vec![MockStep::Delta("done"), MockStep::End],
+    ])
+    .with_second_call_check(|messages| match messages.last() {
+      // The mock model is not in the catalog, so it gets the placeholder only.
+      Some(ModelMessage::Tool {
+        content, images, ..
+      }) if images.is_empty() && content.starts_with("[image: image/png") => Ok(()),
+      other => Err(format!("unexpected tool message: {other:?}")),
+    });
+    let calls = provider.calls.clone();
+
+    let model_client = build_client(provider).await;
+    let mut registry = ToolRegistry::new();
+    registry.register_handler("screenshot", Arc::new(ScreenshotHandler));
+    let tool_registry = Arc::new(registry);
+    let tool_router = build_router(tool_registry.clone());
+    let session = Arc::new(Session::new());
+    let (tx_event, _rx_event) = mpsc::channel(64);
+
+    let executor = SseTurnExecutor::new(
+      model_client,
+      tool_registry,
+      tool_router,
+      session.clone(),
+      tx_event,
+      test_config(),
+    );
+    executor
+      .run_sse_interaction(
+        vec![ModelMessage::User("take a screenshot".to_string())],
+        "thread-img".to_string(),
+        "turn-img".to_string(),
+      )
+      .await
+      .expect("sse run");
+
+    assert_eq!(*calls.lock().await, 2);
+    assert!(
+      session
+        .clone_history()
+        .await
+        .iter()
+        .all(|message| match message {
+          ModelMessage::Tool { images, .. } => images.is_empty(),
+          _ => true,
+        })
+    );
+  }
+
+  #[tokio::test]
+  async fn test_sse_tool_images_reach_image_input_models_for_this_turn_only() {
+    let provider = MockResponsesProvider::new(vec![
+      vec![
+        MockStep::Call {
+          id: "shot_1",
+          name: "screenshot",
+          arguments: "{}",
+        },
+        MockStep::End,
+      ],
+      vec![MockStep::Delta("done"), MockStep::End],
+    ])
+    .with_second_call_check(|messages| match messages.last() {
+      Some(ModelMessage::Tool {
+        content, images, ..
+      }) if images.len() == 1 && content.starts_with("[image: image/png") => Ok(()),
+      other => Err(format!("unexpected tool message: {other:?}")),
+    });
+    let calls = provider.calls.clone();
+
+    let model_client = build_client(provider).await;
+    model_client
+      .registry()
+      .replace_models_dev_for_tests(
+        serde_json::from_value(serde_json::json!({
+          "mock-sse": {
+            "id": "mock-sse",
+            "name": "Mock",
+            "models": {
+              "model": {
+                "id": "model",
+                "name": "Model",
+                "modalities": { "input": ["text", "image"], "output": ["text"] }
+              }
+            }
+          }
+        }))
+        .expect("catalog"),
+      )
+      .await;
+    let mut registry = ToolRegistry::new();
+    registry.register_handler("screenshot", Arc::new(ScreenshotHandler));
+    let tool_registry = Arc::new(registry);
+    let tool_router = build_router(tool_registry.clone());
+    let session = Arc::new(Session::new());
+    let (tx_event, _rx_event) = mpsc::channel(64);
+
+    let executor = SseTurnExecutor::new(
+      model_client,
+      tool_registry,
+      tool_router,
+      session.clone(),
+      tx_event,
+      test_config(),
+    );
+    executor
+      .run_sse_interaction(
+        vec![ModelMessage::User("take a screenshot".to_string())],
+        "thread-img".to_string(),
+        "turn-img".to_string(),
+      )
+      .await
+      .expect("sse run");
+
+    assert_eq!(*calls.lock().await, 2);
+    let history = session.clone_history().await;
+    assert!(
+      history
+        .iter()
+        .any(|message| matches!(message, ModelMessage::Tool { .. }))
+    );
+    assert!(history.iter().all(|message| match message {
+      ModelMessage::Tool { images, .. } => images.is_empty(),
+      _ => true,
+    }));
+  }
+
+  #[tokio::test]
+  async fn test_sse_reasoning_streams_and_replays_signed_thinking() {
+    let provider = MockResponsesProvider::new(vec![
+      vec![
+        MockStep::Reasoning("Need the file. "),
+        MockStep::Reasoning("Read it."),
+        MockStep::Signature("sig-1"),
+        MockStep::Call {
+          id: "read_1",
+          name: "read_file",
+          arguments: r#"{"file_path":"demo.txt"}"#,
+        },
+        MockStep::End,
+      ],
+      vec![MockStep::Delta("done"), MockStep::End],
+    ])
+    .with_second_call_check(|messages| {
+      let thinking = messages.iter().find_map(|msg| match msg {
+        ModelMessage::Assistant {
+          tool_calls: Some(calls),
+          ..
+        } => calls
+          .first()
+          .and_then(|call| call.provider_meta.as_ref())
+          .and_then(|meta| meta.thinking.clone()),
+        _ => None,
+      });
+      match thinking {
+        Some(block) if block.text == "Need the file. Read it." && block.signature == "sig-1" => {
+          Ok(())
+        }
+        other => Err(format!("thinking block not replayed: {other:?}")),
+      }
+    });
+
+    let model_client = build_client(provider).await;
+    let mut registry = ToolRegistry::new();
+    registry.register_handler("read_file", Arc::new(ReadFileLikeHandler));
+    let tool_registry = Arc::new(registry);
+    let tool_router = build_router(tool_registry.clone());
+    let (tx_event, rx_event) = mpsc::channel(64);
+
+    let executor = SseTurnExecutor::new(
+      model_client,
+      tool_registry,
+      tool_router,
+      Arc::new(Session::new()),
+      tx_event,
+      test_config(),
+    );
+
+    let result = executor
+      .run_sse_interaction(
+        vec![ModelMessage::User("read demo".to_string())],
+        "thread-r".to_string(),
+        "turn-r".to_string(),
+      )
+      .await
+      .expect("sse run");
+    assert_eq!(result.content, "done");
+
+    let events = collect_events(rx_event);
+    let deltas = events
+      .iter()
+      .filter(|event| matches!(event, EventMsg::AgentReasoningRawContentDelta(_)))
+      .count();
+    assert_eq!(deltas, 2);
+    assert!(events.iter().any(|event| matches!(
+      event,
+      EventMsg::AgentReasoningRawContent(raw) if raw.text == "Need the file. Read it."
+    )));
+  }
+
   #[tokio::test]
   async fn test_sse_event_ordering() {
     let provider = MockResponsesProvider::new(vec![vec![
@@ -0,0 +1,51 @@
+//! Replays a recorded OpenAI tool-calling turn through the real provider,
+//! `TurnExecutor` and tool routing.
+//!
+//! The cassette under `tests/cassettes/read_file_turn` is committed. Run with
+//! `COKRA_CASSETTE=record` to re-record it.
+
+use cokra_core::model::cassette::Cassette;
+use cokra_core::model::cassette::CassetteMode;
+use cokra_core::test_support::READ_FILE_TURN_TOOL_OUTPUT;
+use cokra_core::test_support::read_file_turn_cassette;
+use cokra_core::test_support::run_read_file_turn;
+use cokra_protocol::EventMsg;
+use pretty_assertions::assert_eq;
+
+#[tokio::test]
+async fn replays_recorded_tool_calling_turn() {
+  let mode = Cassette::from_env().map_or(CassetteMode::Replay, |cassette| cassette.mode());
+  let turn = run_read_file_turn(mode).await;
+
+  assert!(turn.result.success);
+  assert_eq!(
+    turn.result.content,
+    "I'll read the file. File content: hello from tool"
+  );
+  assert_eq!(
+    turn.tool_arguments,
+    vec![r#"{"file_path":"demo.txt"}"#.to_string()]
+  );
+
+  let routed: Vec<&str> = turn
+    .events
+    .iter()
+    .filter_map(|event| match event {
+      EventMsg::ExecCommandBegin(begin) => Some(begin.tool_name.as_str()),
+      _ => None,
+    })
+    .collect();
+  assert_eq!(routed, vec!["read_file"]);
+  assert!(
+    turn
+      .events
+      .iter()
+      .any(|event| matches!(event, EventMsg::TurnComplete(_)))
+  );
+
+  // The follow-up request carried the tool result back to the model.
+  let second = std::fs::read_to_string(read_file_turn_cassette().join("0001.json"))
+    .expect("second interaction");
+  assert!(second.contains("\"tool_call_id\": \"call_read_1\""));
+  assert!(second.contains(READ_FILE_TURN_TOOL_OUTPUT));
+}
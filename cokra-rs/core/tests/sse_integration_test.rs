@@ -130,7 +130,7 @@ impl ModelProvider for MockResponsesProvider {
       let expected = self.expected_tool_output.clone();
       let saw_tool_output = request.messages.iter().any(|msg| {
         if let Some((expected_id, expected_content)) = expected.as_ref() {
-          matches!(msg, Message::Tool { tool_call_id, content } if tool_call_id == expected_id && content.contains(expected_content))
+          matches!(msg, Message::Tool { tool_call_id, content, .. } if tool_call_id == expected_id && content.contains(expected_content))
         } else {
           false
         }
@@ -1,6 +1,31 @@
-// mcp-server - Cokra module
-// Auto-generated placeholder
+// Cokra MCP Server
+// Exposes Cokra to MCP clients over stdio
 
-pub fn placeholder() -> bool {
-  true
+mod server;
+mod task_runner;
+mod tool_config;
+
+pub use server::CokraMcpServer;
+
+use std::path::PathBuf;
+
+use rmcp::ServiceExt;
+
+/// Serve MCP on stdin/stdout until the client disconnects.
+///
+/// `cwd` is the workspace used when a tool call omits one; `overrides` are the
+/// `-c key=value` pairs applied on top of every loaded config. Unless
+/// `allow_unsafe_overrides` is set, `run_task` may only tighten the sandbox and
+/// approval policy.
+pub async fn run_main(
+  cwd: PathBuf,
+  overrides: Vec<(String, String)>,
+  allow_unsafe_overrides: bool,
+) -> anyhow::Result<()> {
+  let service = CokraMcpServer::new(cwd, overrides)
+    .with_unsafe_overrides(allow_unsafe_overrides)
+    .serve(rmcp::transport::stdio())
+    .await?;
+  service.waiting().await?;
+  Ok(())
 }
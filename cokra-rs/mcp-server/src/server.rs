@@ -0,0 +1,394 @@
+//! The MCP server handler.
+//!
+//! Each tool call spawns a [`cokra_core::Cokra`] runtime for the workspace, runs one turn
+//! and saves the conversation in the same thread store the app server uses,
+//! so threads are shared between the two front ends. Calls for the same
+//! workspace run one at a time (see [`cokra_app_server::thread_runner`]).
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use cokra_app_server::thread_runner::ThreadRunner;
+use cokra_app_server::thread_runner::model_overrides;
+use cokra_app_server::thread_runner::resolve_cwd;
+use cokra_app_server::thread_store::ThreadStore;
+use cokra_app_server::thread_store::preview_of;
+use cokra_app_server_protocol::JsonRpcError;
+use cokra_app_server_protocol::ThreadSummary;
+use cokra_config::Config;
+use cokra_core::model::ModelClient;
+use rmcp::ErrorData as McpError;
+use rmcp::RoleServer;
+use rmcp::ServerHandler;
+use rmcp::model::CallToolRequestParams;
+use rmcp::model::CallToolResult;
+use rmcp::model::Content;
+use rmcp::model::ErrorCode;
+use rmcp::model::Implementation;
+use rmcp::model::JsonObject;
+use rmcp::model::ListToolsResult;
+use rmcp::model::PaginatedRequestParams;
+use rmcp::model::ServerCapabilities;
+use rmcp::model::ServerInfo;
+use rmcp::service::RequestContext;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+
+use crate::task_runner::ProgressReporter;
+use crate::task_runner::TurnOutcome;
+use crate::task_runner::run_turn;
+use crate::tool_config::CONTINUE_THREAD_TOOL;
+use crate::tool_config::ContinueThreadArgs;
+use crate::tool_config::LIST_THREADS_TOOL;
+use crate::tool_config::ListThreadsArgs;
+use crate::tool_config::RUN_TASK_TOOL;
+use crate::tool_config::RunTaskArgs;
+use crate::tool_config::loosened_setting;
+use crate::tool_config::tools;
+
+pub struct CokraMcpServer {
+  default_cwd: PathBuf,
+  runner: ThreadRunner,
+  /// Lets `run_task` loosen the sandbox or approval policy.
+  allow_unsafe_overrides: bool,
+}
+
+impl CokraMcpServer {
+  pub fn new(default_cwd: PathBuf, overrides: Vec<(String, String)>) -> Self {
+    Self {
+      default_cwd,
+      runner: ThreadRunner::new(overrides),
+      allow_unsafe_overrides: false,
+    }
+  }
+
+  pub fn with_unsafe_overrides(mut self, allow: bool) -> Self {
+    self.allow_unsafe_overrides = allow;
+    self
+  }
+
+  pub fn with_model_client(mut self, model_client: Arc<ModelClient>) -> Self {
+    self.runner = self.runner.with_model_client(model_client);
+    self
+  }
+
+  async fn run_task(
+    &self,
+    args: RunTaskArgs,
+    context: RequestContext<RoleServer>,
+  ) -> Result<CallToolResult, McpError> {
+    let overrides = args.overrides().map_err(invalid_params)?;
+    let cwd = self.resolve_cwd(args.cwd.clone())?;
+    let _workspace = self.runner.lock_workspace(&cwd).await;
+    let store = ThreadStore::open(&cwd).await.map_err(internal)?;
+    let config = self.runner.load_config(&cwd, overrides).map_err(rpc)?;
+    if !self.allow_unsafe_overrides {
+      let base = self.runner.load_config(&cwd, Vec::new()).map_err(rpc)?;
+      if let Some(loosened) = loosened_setting(&base, &config) {
+        return Err(invalid_params(format!(
+          "{loosened}; start the server with `cokra mcp serve --allow-unsafe-overrides` to allow it"
+        )));
+      }
+    }
+    let now = chrono::Utc::now().timestamp();
+    let thread = ThreadSummary {
+      thread_id: uuid::Uuid::new_v4().to_string(),
+      cwd: cwd.clone(),
+      model: config.models.model.clone(),
+      preview: preview_of(&args.prompt),
+      created_at: now,
+      updated_at: now,
+      archived: false,
+    };
+    store.insert(&thread).await.map_err(internal)?;
+    self
+      .run_on_thread(&store, thread, config, &args.prompt, context)
+      .await
+  }
+
+  async fn continue_thread(
+    &self,
+    args: ContinueThreadArgs,
+    context: RequestContext<RoleServer>,
+  ) -> Result<CallToolResult, McpError> {
+    let cwd = self.resolve_cwd(args.cwd)?;
+    let _workspace = self.runner.lock_workspace(&cwd).await;
+    let store = ThreadStore::open(&cwd).await.map_err(internal)?;
+    let thread = store
+      .get(&args.thread_id)
+      .await
+      .map_err(internal)?
+      .ok_or_else(|| invalid_params(format!("unknown thread: {}", args.thread_id)))?;
+    let config = self
+      .runner
+      .load_config(&cwd, model_overrides(&thread.model))
+      .map_err(rpc)?;
+    self
+      .run_on_thread(&store, thread, config, &args.prompt, context)
+      .await
+  }
+
+  async fn list_threads(&self, args: ListThreadsArgs) -> Result<CallToolResult, McpError> {
+    let cwd = self.resolve_cwd(args.cwd)?;
+    let store = ThreadStore::open(&cwd).await.map_err(internal)?;
+    let threads = store
+      .list()
+      .await
+      .map_err(internal)?
+      .into_iter()
+      .filter(|thread| args.include_archived || !thread.archived)
+      .collect::<Vec<_>>();
+    let text = if threads.is_empty() {
+      "No threads.".to_string()
+    } else {
+      threads
+        .iter()
+        .map(|thread| {
+          format!(
+            "{}  {}  {}",
+            thread.thread_id,
+            thread.model,
+            thread.preview.as_deref().unwrap_or("(no prompt)")
+          )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+    };
+    let mut result = CallToolResult::success(vec![Content::text(text)]);
+    result.structured_content = Some(json!({ "threads": threads }));
+    Ok(result)
+  }
+
+  async fn run_on_thread(
+    &self,
+    store: &ThreadStore,
+    thread: ThreadSummary,
+    config: Config,
+    prompt: &str,
+    context: RequestContext<RoleServer>,
+  ) -> Result<CallToolResult, McpError> {
+    let mut reporter =
+      ProgressReporter::new(context.peer.clone(), context.meta.get_progress_token());
+    let outcome = self
+      .runner
+      .run_on_thread(store, &thread, config, prompt, async |cokra| {
+        run_turn(cokra, prompt, &mut reporter, &context.ct).await
+      })
+      .await
+      .map_err(rpc)?;
+
+    let structured = json!({ "thread_id": thread.thread_id });
+    let mut result = match outcome {
+      TurnOutcome::Completed { message } => CallToolResult::success(vec![Content::text(message)]),
+      TurnOutcome::Failed { error } => CallToolResult::error(vec![Content::text(error)]),
+    };
+    result.structured_content = Some(structured);
+    Ok(result)
+  }
+
+  fn resolve_cwd(&self, cwd: Option<PathBuf>) -> Result<PathBuf, McpError> {
+    resolve_cwd(&self.default_cwd, cwd).map_err(rpc)
+  }
+}
+
+impl ServerHandler for CokraMcpServer {
+  fn get_info(&self) -> ServerInfo {
+    ServerInfo {
+      capabilities: ServerCapabilities::builder().enable_tools().build(),
+      server_info: Implementation {
+        name: "cokra".to_string(),
+        title: Some("Cokra".to_string()),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        ..Implementation::default()
+      },
+      instructions: Some(
+        "Delegate coding tasks to Cokra with run_task, follow up with continue_thread.".to_string(),
+      ),
+      ..ServerInfo::default()
+    }
+  }
+
+  async fn list_tools(
+    &self,
+    _request: Option<PaginatedRequestParams>,
+    _context: RequestContext<RoleServer>,
+  ) -> Result<ListToolsResult, McpError> {
+    Ok(ListToolsResult {
+      tools: tools(),
+      ..ListToolsResult::default()
+    })
+  }
+
+  async fn call_tool(
+    &self,
+    request: CallToolRequestParams,
+    context: RequestContext<RoleServer>,
+  ) -> Result<CallToolResult, McpError> {
+    let arguments = request.arguments.unwrap_or_default();
+    match request.name.as_ref() {
+      RUN_TASK_TOOL => self.run_task(parse_args(arguments)?, context).await,
+      CONTINUE_THREAD_TOOL => self.continue_thread(parse_args(arguments)?, context).await,
+      LIST_THREADS_TOOL => self.list_threads(parse_args(arguments)?).await,
+      other => Err(invalid_params(format!("unknown tool: {other}"))),
+    }
+  }
+}
+
+fn parse_args<T: DeserializeOwned>(arguments: JsonObject) -> Result<T, McpError> {
+  serde_json::from_value(serde_json::Value::Object(arguments)).map_err(invalid_params)
+}
+
+fn invalid_params(err: impl std::fmt::Display) -> McpError {
+  McpError::invalid_params(err.to_string(), None)
+}
+
+fn internal(err: impl std::fmt::Display) -> McpError {
+  McpError::internal_error(format!("{err:#}"), None)
+}
+
+/// Carry a shared thread-runner error over as an MCP error.
+fn rpc(err: JsonRpcError) -> McpError {
+  McpError::new(ErrorCode(err.code as i32), err.message, err.data)
+}
+
+#[cfg(test)]
+mod tests {
+  use cokra_core::test_support::ScriptedProvider;
+  use pretty_assertions::assert_eq;
+  use rmcp::ServiceExt;
+  use rmcp::model::CallToolRequestParams;
+
+  use super::*;
+
+  async fn build_server(cwd: PathBuf) -> CokraMcpServer {
+    let model_client = ScriptedProvider::replying("mock reply")
+      .into_model_client()
+      .await;
+    CokraMcpServer::new(cwd, ScriptedProvider::config_overrides()).with_model_client(model_client)
+  }
+
+  fn call(name: &'static str, arguments: serde_json::Value) -> CallToolRequestParams {
+    let serde_json::Value::Object(arguments) = arguments else {
+      panic!("arguments must be an object");
+    };
+    CallToolRequestParams {
+      meta: None,
+      name: name.into(),
+      arguments: Some(arguments),
+      task: None,
+    }
+  }
+
+  fn text_of(result: &CallToolResult) -> String {
+    result
+      .content
+      .iter()
+      .filter_map(|content| content.as_text().map(|text| text.text.clone()))
+      .collect::<Vec<_>>()
+      .join("\n")
+  }
+
+  #[tokio::test]
+  async fn run_continue_and_list_threads_over_mcp() {
+    let workspace = tempfile::tempdir().expect("tempdir");
+    let cwd = std::fs::canonicalize(workspace.path()).expect("canonical cwd");
+    let (server_io, client_io) = tokio::io::duplex(64 * 1024);
+    let server = build_server(cwd).await;
+    let server_task = tokio::spawn(async move {
+      let running = server.serve(server_io).await.expect("serve");
+      let _ = running.waiting().await;
+    });
+    let client = ().serve(client_io).await.expect("client");
+
+    let tools = client.list_all_tools().await.expect("list tools");
+    let mut names = tools
+      .iter()
+      .map(|tool| tool.name.to_string())
+      .collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, vec!["continue_thread", "list_threads", "run_task"]);
+
+    let ran = client
+      .call_tool(call(RUN_TASK_TOOL, json!({ "prompt": "say hi" })))
+      .await
+      .expect("run_task");
+    assert_eq!(ran.is_error, Some(false));
+    assert_eq!(text_of(&ran), "mock reply");
+    let thread_id = ran
+      .structured_content
+      .as_ref()
+      .and_then(|value| value.get("thread_id"))
+      .and_then(serde_json::Value::as_str)
+      .expect("thread id")
+      .to_string();
+
+    let continued = client
+      .call_tool(call(
+        CONTINUE_THREAD_TOOL,
+        json!({ "thread_id": thread_id, "prompt": "again" }),
+      ))
+      .await
+      .expect("continue_thread");
+    assert_eq!(text_of(&continued), "mock reply");
+
+    let listed = client
+      .call_tool(call(LIST_THREADS_TOOL, json!({})))
+      .await
+      .expect("list_threads");
+    let threads: Vec<ThreadSummary> = serde_json::from_value(
+      listed
+        .structured_content
+        .and_then(|mut value| value.get_mut("threads").map(serde_json::Value::take))
+        .expect("threads"),
+    )
+    .expect("thread summaries");
+    assert_eq!(threads.len(), 1);
+    assert_eq!(threads[0].thread_id, thread_id);
+    assert_eq!(threads[0].preview.as_deref(), Some("say hi"));
+
+    let unknown = client
+      .call_tool(call(
+        CONTINUE_THREAD_TOOL,
+        json!({ "thread_id": "missing", "prompt": "x" }),
+      ))
+      .await;
+    assert!(unknown.is_err());
+
+    client.cancel().await.expect("close client");
+    let _ = server_task.await;
+  }
+
+  #[tokio::test]
+  async fn run_task_cannot_loosen_the_server_config() {
+    let workspace = tempfile::tempdir().expect("tempdir");
+    let cwd = std::fs::canonicalize(workspace.path()).expect("canonical cwd");
+    let (server_io, client_io) = tokio::io::duplex(64 * 1024);
+    let server = build_server(cwd).await;
+    let server_task = tokio::spawn(async move {
+      let running = server.serve(server_io).await.expect("serve");
+      let _ = running.waiting().await;
+    });
+    let client = ().serve(client_io).await.expect("client");
+
+    let err = client
+      .call_tool(call(
+        RUN_TASK_TOOL,
+        json!({ "prompt": "x", "sandbox": "dangerfullaccess", "approval_policy": "never" }),
+      ))
+      .await
+      .expect_err("looser sandbox is refused");
+    assert!(err.to_string().contains("--allow-unsafe-overrides"));
+
+    let ran = client
+      .call_tool(call(
+        RUN_TASK_TOOL,
+        json!({ "prompt": "x", "sandbox": "strict" }),
+      ))
+      .await
+      .expect("stricter sandbox is allowed");
+    assert_eq!(text_of(&ran), "mock reply");
+
+    client.cancel().await.expect("close client");
+    let _ = server_task.await;
+  }
+}
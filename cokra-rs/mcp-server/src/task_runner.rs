@@ -0,0 +1,449 @@
+//! Drive one Cokra turn on behalf of an MCP tool call.
+//!
+//! Events become progress notifications, approval and `request_user_input`
+//! prompts become elicitations (as do elicitations from Cokra's own MCP
+//! servers), and the last agent message is the answer.
+
+use std::collections::HashMap;
+
+use cokra_core::Cokra;
+use cokra_protocol::AgentMessageContent;
+use cokra_protocol::CompletionStatus;
+use cokra_protocol::ElicitationRequestEvent;
+use cokra_protocol::EventMsg;
+use cokra_protocol::ExecApprovalRequestEvent;
+use cokra_protocol::Op;
+use cokra_protocol::RequestUserInputAnswer;
+use cokra_protocol::RequestUserInputEvent;
+use cokra_protocol::RequestUserInputResponse;
+use cokra_protocol::ReviewDecision;
+use cokra_protocol::UserInput;
+use rmcp::Peer;
+use rmcp::RoleServer;
+use rmcp::model::CreateElicitationRequestParams;
+use rmcp::model::CreateElicitationResult;
+use rmcp::model::ElicitationAction;
+use rmcp::model::ElicitationSchema;
+use rmcp::model::EnumSchema;
+use rmcp::model::ProgressNotificationParam;
+use rmcp::model::ProgressToken;
+use tokio_util::sync::CancellationToken;
+
+const DECISION_FIELD: &str = "decision";
+
+/// How a turn ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TurnOutcome {
+  Completed { message: String },
+  Failed { error: String },
+}
+
+pub(crate) struct ProgressReporter {
+  peer: Peer<RoleServer>,
+  token: Option<ProgressToken>,
+  sent: u32,
+}
+
+impl ProgressReporter {
+  pub(crate) fn new(peer: Peer<RoleServer>, token: Option<ProgressToken>) -> Self {
+    Self {
+      peer,
+      token,
+      sent: 0,
+    }
+  }
+
+  async fn report(&mut self, message: String) {
+    let Some(token) = self.token.clone() else {
+      return;
+    };
+    self.sent += 1;
+    let result = self
+      .peer
+      .notify_progress(ProgressNotificationParam {
+        progress_token: token,
+        progress: f64::from(self.sent),
+        total: None,
+        message: Some(message),
+      })
+      .await;
+    if let Err(err) = result {
+      tracing::debug!("failed to send progress notification: {err}");
+    }
+  }
+}
+
+/// Submit `prompt` and wait for the turn to settle.
+pub(crate) async fn run_turn(
+  cokra: &Cokra,
+  prompt: &str,
+  reporter: &mut ProgressReporter,
+  cancel: &CancellationToken,
+) -> anyhow::Result<TurnOutcome> {
+  cokra
+    .submit(Op::UserInput {
+      items: vec![UserInput::Text {
+        text: prompt.to_string(),
+        text_elements: Vec::new(),
+      }],
+      final_output_json_schema: None,
+    })
+    .await?;
+
+  let mut last_message = String::new();
+  let mut last_error = None;
+  let mut interrupted = false;
+  loop {
+    let event = tokio::select! {
+      event = cokra.next_event() => event?,
+      _ = cancel.cancelled(), if !interrupted => {
+        interrupted = true;
+        cokra.submit(Op::Interrupt).await?;
+        continue;
+      }
+    };
+    if let Some(message) = progress_message(&event.msg) {
+      reporter.report(message).await;
+    }
+    match event.msg {
+      EventMsg::AgentMessage(message) => {
+        let text = agent_text(&message.content);
+        if !text.is_empty() {
+          last_message = text;
+        }
+      }
+      EventMsg::Error(error) => last_error = Some(error.user_facing_message),
+      EventMsg::ExecApprovalRequest(request) => {
+        let decision = ask_exec_approval(&reporter.peer, &request).await;
+        cokra
+          .submit(Op::ExecApproval {
+            id: request.id,
+            turn_id: Some(request.turn_id),
+            decision,
+          })
+          .await?;
+      }
+      EventMsg::RequestUserInput(request) => {
+        let response = ask_user_input(&reporter.peer, &request).await;
+        cokra
+          .submit(Op::UserInputAnswer {
+            id: request.call_id,
+            response,
+          })
+          .await?;
+      }
+      EventMsg::ElicitationRequest(request) => {
+        let result = forward_elicitation(&reporter.peer, &request).await;
+        cokra
+          .submit(Op::ResolveElicitation {
+            server_name: request.server_name,
+            request_id: request.id,
+            decision: match result.action {
+              ElicitationAction::Accept => cokra_protocol::ElicitationAction::Accept,
+              ElicitationAction::Decline => cokra_protocol::ElicitationAction::Decline,
+              ElicitationAction::Cancel => cokra_protocol::ElicitationAction::Cancel,
+            },
+            content: result.content,
+          })
+          .await?;
+      }
+      EventMsg::TurnComplete(complete) => {
+        return Ok(match complete.status {
+          CompletionStatus::Errored {
+            user_facing_message,
+            ..
+          } => TurnOutcome::Failed {
+            error: user_facing_message,
+          },
+          _ => match last_error {
+            Some(error) if last_message.is_empty() => TurnOutcome::Failed { error },
+            _ => TurnOutcome::Completed {
+              message: last_message,
+            },
+          },
+        });
+      }
+      EventMsg::TurnAborted(aborted) => {
+        return Ok(TurnOutcome::Failed {
+          error: format!("turn aborted: {}", aborted.reason),
+        });
+      }
+      EventMsg::ShutdownComplete => {
+        return Ok(TurnOutcome::Failed {
+          error: "Cokra shut down before the turn finished".to_string(),
+        });
+      }
+      _ => {}
+    }
+  }
+}
+
+fn agent_text(content: &[AgentMessageContent]) -> String {
+  content
+    .iter()
+    .map(|part| match part {
+      AgentMessageContent::Text { text } => text.as_str(),
+    })
+    .collect::<Vec<_>>()
+    .join("")
+}
+
+fn progress_message(msg: &EventMsg) -> Option<String> {
+  match msg {
+    EventMsg::TurnStarted(_) => Some("Turn started".to_string()),
+    EventMsg::ExecCommandBegin(begin) => Some(format!("Running {}", begin.command)),
+    EventMsg::PatchApplyBegin(begin) => Some(format!("Editing {} file(s)", begin.changes.len())),
+    EventMsg::McpToolCallBegin(begin) => Some(format!(
+      "Calling {}.{}",
+      begin.invocation.server, begin.invocation.tool
+    )),
+    EventMsg::AgentMessage(message) => {
+      let text = agent_text(&message.content);
+      (!text.is_empty()).then_some(text)
+    }
+    EventMsg::Warning(warning) => Some(format!("Warning: {}", warning.message)),
+    _ => None,
+  }
+}
+
+fn supports_elicitation(peer: &Peer<RoleServer>) -> bool {
+  peer
+    .peer_info()
+    .is_some_and(|info| info.capabilities.elicitation.is_some())
+}
+
+/// Tradeoff: without elicitation support there is nobody to ask, so commands
+/// that need approval are denied rather than run unattended.
+async fn ask_exec_approval(
+  peer: &Peer<RoleServer>,
+  request: &ExecApprovalRequestEvent,
+) -> ReviewDecision {
+  if !supports_elicitation(peer) {
+    return ReviewDecision::Denied;
+  }
+  let schema = ElicitationSchema::builder()
+    .required_enum_schema(
+      DECISION_FIELD,
+      EnumSchema::builder(vec![
+        "approved".to_string(),
+        "denied".to_string(),
+        "always".to_string(),
+      ])
+      .build(),
+    )
+    .build();
+  let Ok(requested_schema) = schema else {
+    return ReviewDecision::Denied;
+  };
+  let params = CreateElicitationRequestParams::FormElicitationParams {
+    meta: None,
+    message: format!(
+      "Cokra wants to run `{}` ({}) in {}.",
+      request.command,
+      request.tool_name,
+      request.cwd.display()
+    ),
+    requested_schema,
+  };
+  match peer.create_elicitation(params).await {
+    Ok(result) => decision_from_elicitation(&result),
+    Err(err) => {
+      tracing::warn!("approval elicitation failed: {err}");
+      ReviewDecision::Denied
+    }
+  }
+}
+
+/// Pass a form from one of Cokra's MCP servers through to our own client.
+async fn forward_elicitation(
+  peer: &Peer<RoleServer>,
+  request: &ElicitationRequestEvent,
+) -> CreateElicitationResult {
+  let declined = CreateElicitationResult {
+    action: ElicitationAction::Decline,
+    content: None,
+  };
+  if !supports_elicitation(peer) {
+    return declined;
+  }
+  let Ok(requested_schema) =
+    serde_json::from_value::<ElicitationSchema>(request.requested_schema.clone())
+  else {
+    return declined;
+  };
+  let params = CreateElicitationRequestParams::FormElicitationParams {
+    meta: None,
+    message: format!("[{}] {}", request.server_name, request.message),
+    requested_schema,
+  };
+  match peer.create_elicitation(params).await {
+    Ok(result) => result,
+    Err(err) => {
+      tracing::warn!("forwarded elicitation failed: {err}");
+      declined
+    }
+  }
+}
+
+pub(crate) fn decision_from_elicitation(result: &CreateElicitationResult) -> ReviewDecision {
+  if result.action != ElicitationAction::Accept {
+    return ReviewDecision::Denied;
+  }
+  let decision = result
+    .content
+    .as_ref()
+    .and_then(|content| content.get(DECISION_FIELD))
+    .and_then(serde_json::Value::as_str);
+  match decision {
+    Some("approved") => ReviewDecision::Approved,
+    Some("always") => ReviewDecision::Always,
+    _ => ReviewDecision::Denied,
+  }
+}
+
+async fn ask_user_input(
+  peer: &Peer<RoleServer>,
+  request: &RequestUserInputEvent,
+) -> RequestUserInputResponse {
+  if !supports_elicitation(peer) || request.questions.is_empty() {
+    return RequestUserInputResponse::default();
+  }
+  let mut builder = ElicitationSchema::builder();
+  for question in &request.questions {
+    let description = format!("{}: {}", question.header, question.question);
+    builder = match &question.options {
+      Some(options) if !question.is_other && !options.is_empty() => builder.required_enum_schema(
+        question.id.clone(),
+        EnumSchema::builder(options.iter().map(|option| option.label.clone()).collect())
+          .description(description)
+          .build(),
+      ),
+      _ => builder.required_string_with(question.id.clone(), |schema| {
+        schema.description(description)
+      }),
+    };
+  }
+  let Ok(requested_schema) = builder.build() else {
+    return RequestUserInputResponse::default();
+  };
+  let params = CreateElicitationRequestParams::FormElicitationParams {
+    meta: None,
+    message: "Cokra needs more information to continue.".to_string(),
+    requested_schema,
+  };
+  match peer.create_elicitation(params).await {
+    Ok(result) => answers_from_elicitation(&result),
+    Err(err) => {
+      tracing::warn!("user input elicitation failed: {err}");
+      RequestUserInputResponse::default()
+    }
+  }
+}
+
+pub(crate) fn answers_from_elicitation(
+  result: &CreateElicitationResult,
+) -> RequestUserInputResponse {
+  if result.action != ElicitationAction::Accept {
+    return RequestUserInputResponse::default();
+  }
+  let Some(serde_json::Value::Object(content)) = &result.content else {
+    return RequestUserInputResponse::default();
+  };
+  let answers: HashMap<String, RequestUserInputAnswer> = content
+    .iter()
+    .filter_map(|(id, value)| {
+      let answer = match value {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Null => return None,
+        other => other.to_string(),
+      };
+      Some((
+        id.clone(),
+        RequestUserInputAnswer {
+          answers: vec![answer],
+        },
+      ))
+    })
+    .collect();
+  RequestUserInputResponse { answers }
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+  use serde_json::json;
+
+  use super::*;
+
+  fn elicitation(
+    action: ElicitationAction,
+    content: Option<serde_json::Value>,
+  ) -> CreateElicitationResult {
+    CreateElicitationResult { action, content }
+  }
+
+  #[test]
+  fn approval_elicitation_maps_to_review_decision() {
+    assert!(matches!(
+      decision_from_elicitation(&elicitation(
+        ElicitationAction::Accept,
+        Some(json!({ "decision": "always" }))
+      )),
+      ReviewDecision::Always
+    ));
+    assert!(matches!(
+      decision_from_elicitation(&elicitation(
+        ElicitationAction::Accept,
+        Some(json!({ "decision": "approved" }))
+      )),
+      ReviewDecision::Approved
+    ));
+    assert!(matches!(
+      decision_from_elicitation(&elicitation(ElicitationAction::Accept, None)),
+      ReviewDecision::Denied
+    ));
+    assert!(matches!(
+      decision_from_elicitation(&elicitation(
+        ElicitationAction::Accept,
+        Some(json!({ "decision": "maybe" }))
+      )),
+      ReviewDecision::Denied
+    ));
+    assert!(matches!(
+      decision_from_elicitation(&elicitation(
+        ElicitationAction::Accept,
+        Some(json!({ "decision": "denied" }))
+      )),
+      ReviewDecision::Denied
+    ));
+    assert!(matches!(
+      decision_from_elicitation(&elicitation(ElicitationAction::Cancel, None)),
+      ReviewDecision::Denied
+    ));
+  }
+
+  #[test]
+  fn user_input_elicitation_maps_to_answers() {
+    let response = answers_from_elicitation(&elicitation(
+      ElicitationAction::Accept,
+      Some(json!({ "scope": "tests only", "count": 2, "skipped": null })),
+    ));
+    let mut answers = response
+      .answers
+      .into_iter()
+      .map(|(id, answer)| (id, answer.answers))
+      .collect::<Vec<_>>();
+    answers.sort();
+    assert_eq!(
+      answers,
+      vec![
+        ("count".to_string(), vec!["2".to_string()]),
+        ("scope".to_string(), vec!["tests only".to_string()]),
+      ]
+    );
+
+    let declined =
+      answers_from_elicitation(&elicitation(ElicitationAction::Decline, Some(json!({}))));
+    assert!(declined.answers.is_empty());
+  }
+}
@@ -0,0 +1,249 @@
+//! Tools Cokra exposes to MCP clients and their arguments.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use cokra_config::ApprovalMode;
+use cokra_config::Config;
+use cokra_config::SandboxMode;
+use rmcp::model::JsonObject;
+use rmcp::model::Tool;
+use serde::Deserialize;
+use serde_json::json;
+
+pub(crate) const RUN_TASK_TOOL: &str = "run_task";
+pub(crate) const CONTINUE_THREAD_TOOL: &str = "continue_thread";
+pub(crate) const LIST_THREADS_TOOL: &str = "list_threads";
+
+/// Ordered from most to least protective.
+const SANDBOX_MODES: &[&str] = &["strict", "permissive", "dangerfullaccess"];
+/// Ordered from most to least protective.
+const APPROVAL_POLICIES: &[&str] = &["ask", "auto", "never"];
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct RunTaskArgs {
+  pub(crate) prompt: String,
+  #[serde(default)]
+  pub(crate) cwd: Option<PathBuf>,
+  #[serde(default)]
+  pub(crate) model: Option<String>,
+  #[serde(default)]
+  pub(crate) sandbox: Option<String>,
+  #[serde(default)]
+  pub(crate) approval_policy: Option<String>,
+}
+
+impl RunTaskArgs {
+  /// Config overrides for the optional settings, rejecting unknown values.
+  pub(crate) fn overrides(&self) -> Result<Vec<(String, String)>, String> {
+    let mut overrides = Vec::new();
+    if let Some(model) = &self.model {
+      if let Some((provider, _)) = model.split_once('/') {
+        overrides.push(("models.provider".to_string(), provider.to_string()));
+      }
+      overrides.push(("models.model".to_string(), model.clone()));
+    }
+    if let Some(sandbox) = &self.sandbox {
+      if !SANDBOX_MODES.contains(&sandbox.as_str()) {
+        return Err(format!(
+          "unknown sandbox '{sandbox}', expected one of {}",
+          SANDBOX_MODES.join(", ")
+        ));
+      }
+      overrides.push(("sandbox.mode".to_string(), sandbox.clone()));
+    }
+    if let Some(policy) = &self.approval_policy {
+      if !APPROVAL_POLICIES.contains(&policy.as_str()) {
+        return Err(format!(
+          "unknown approval_policy '{policy}', expected one of {}",
+          APPROVAL_POLICIES.join(", ")
+        ));
+      }
+      overrides.push(("approval.policy".to_string(), policy.clone()));
+    }
+    Ok(overrides)
+  }
+}
+
+/// The first setting `requested` runs with less protection than the server's
+/// own `base` config, described for the client.
+pub(crate) fn loosened_setting(base: &Config, requested: &Config) -> Option<String> {
+  let (base_sandbox, sandbox) = (
+    sandbox_name(&base.sandbox.mode),
+    sandbox_name(&requested.sandbox.mode),
+  );
+  if rank(SANDBOX_MODES, sandbox) > rank(SANDBOX_MODES, base_sandbox) {
+    return Some(format!(
+      "sandbox '{sandbox}' is looser than the server's '{base_sandbox}'"
+    ));
+  }
+  let (base_policy, policy) = (
+    approval_name(&base.approval.policy),
+    approval_name(&requested.approval.policy),
+  );
+  if rank(APPROVAL_POLICIES, policy) > rank(APPROVAL_POLICIES, base_policy) {
+    return Some(format!(
+      "approval_policy '{policy}' is looser than the server's '{base_policy}'"
+    ));
+  }
+  None
+}
+
+fn rank(values: &[&str], value: &str) -> Option<usize> {
+  values.iter().position(|candidate| *candidate == value)
+}
+
+fn sandbox_name(mode: &SandboxMode) -> &'static str {
+  match mode {
+    SandboxMode::Strict => "strict",
+    SandboxMode::Permissive => "permissive",
+    SandboxMode::DangerFullAccess => "dangerfullaccess",
+  }
+}
+
+fn approval_name(policy: &ApprovalMode) -> &'static str {
+  match policy {
+    ApprovalMode::Ask => "ask",
+    ApprovalMode::Auto => "auto",
+    ApprovalMode::Never => "never",
+  }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct ContinueThreadArgs {
+  pub(crate) thread_id: String,
+  pub(crate) prompt: String,
+  #[serde(default)]
+  pub(crate) cwd: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub(crate) struct ListThreadsArgs {
+  #[serde(default)]
+  pub(crate) cwd: Option<PathBuf>,
+  #[serde(default)]
+  pub(crate) include_archived: bool,
+}
+
+pub(crate) fn tools() -> Vec<Tool> {
+  vec![
+    tool(
+      RUN_TASK_TOOL,
+      "Run a coding task with Cokra in a new thread and return its final answer. \
+       Progress is reported through MCP progress notifications; command approvals \
+       and clarifying questions are asked through elicitation.",
+      json!({
+        "type": "object",
+        "properties": {
+          "prompt": { "type": "string", "description": "The task to perform." },
+          "cwd": { "type": "string", "description": "Workspace directory; defaults to the server's." },
+          "model": { "type": "string", "description": "`provider/model` to use." },
+          "sandbox": {
+            "type": "string",
+            "enum": SANDBOX_MODES,
+            "description": "May only be stricter than the server's own setting."
+          },
+          "approval_policy": {
+            "type": "string",
+            "enum": APPROVAL_POLICIES,
+            "description": "May only be stricter than the server's own setting."
+          }
+        },
+        "required": ["prompt"],
+        "additionalProperties": false
+      }),
+    ),
+    tool(
+      CONTINUE_THREAD_TOOL,
+      "Send a follow-up prompt to an existing Cokra thread.",
+      json!({
+        "type": "object",
+        "properties": {
+          "thread_id": { "type": "string" },
+          "prompt": { "type": "string" },
+          "cwd": { "type": "string", "description": "Workspace the thread belongs to." }
+        },
+        "required": ["thread_id", "prompt"],
+        "additionalProperties": false
+      }),
+    ),
+    tool(
+      LIST_THREADS_TOOL,
+      "List Cokra threads of a workspace, most recently updated first.",
+      json!({
+        "type": "object",
+        "properties": {
+          "cwd": { "type": "string" },
+          "include_archived": { "type": "boolean" }
+        },
+        "additionalProperties": false
+      }),
+    ),
+  ]
+}
+
+fn tool(name: &'static str, description: &'static str, schema: serde_json::Value) -> Tool {
+  let schema: JsonObject = match schema {
+    serde_json::Value::Object(map) => map,
+    _ => JsonObject::new(),
+  };
+  Tool::new(name, description, Arc::new(schema))
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  #[test]
+  fn run_task_settings_become_config_overrides() {
+    let args: RunTaskArgs = serde_json::from_value(json!({
+      "prompt": "fix it",
+      "model": "openai/gpt-4o",
+      "sandbox": "strict",
+      "approval_policy": "never"
+    }))
+    .expect("args");
+    assert_eq!(
+      args.overrides().expect("overrides"),
+      vec![
+        ("models.provider".to_string(), "openai".to_string()),
+        ("models.model".to_string(), "openai/gpt-4o".to_string()),
+        ("sandbox.mode".to_string(), "strict".to_string()),
+        ("approval.policy".to_string(), "never".to_string()),
+      ]
+    );
+
+    let bad: RunTaskArgs =
+      serde_json::from_value(json!({ "prompt": "x", "sandbox": "open" })).expect("args");
+    assert!(bad.overrides().is_err());
+  }
+
+  #[test]
+  fn only_stricter_settings_pass_the_server_config() {
+    let base = Config::default();
+    let with = |sandbox: SandboxMode, policy: ApprovalMode| {
+      let mut config = Config::default();
+      config.sandbox.mode = sandbox;
+      config.approval.policy = policy;
+      config
+    };
+    assert_eq!(
+      loosened_setting(&base, &with(SandboxMode::Strict, ApprovalMode::Ask)),
+      None
+    );
+    assert_eq!(
+      loosened_setting(
+        &base,
+        &with(SandboxMode::DangerFullAccess, ApprovalMode::Ask)
+      )
+      .as_deref(),
+      Some("sandbox 'dangerfullaccess' is looser than the server's 'permissive'")
+    );
+    assert_eq!(
+      loosened_setting(&base, &with(SandboxMode::Permissive, ApprovalMode::Never)).as_deref(),
+      Some("approval_policy 'never' is looser than the server's 'ask'")
+    );
+  }
+}
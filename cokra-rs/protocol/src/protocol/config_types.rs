@@ -85,7 +85,7 @@ pub struct Settings {
 }
 
 /// Reasoning effort level
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ReasoningEffort {
   Minimal,
   Low,
@@ -94,13 +94,13 @@ pub enum ReasoningEffort {
 }
 
 /// Reasoning effort config
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ReasoningEffortConfig {
   pub effort: ReasoningEffort,
 }
 
 /// Reasoning summary config
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ReasoningSummaryConfig {
   Auto,
   Always,
@@ -41,6 +41,8 @@ pub enum CollabAgentLifecycle {
   Ready,
   Busy,
   Error,
+  /// Stopped by an exhausted budget; needs the leader before it runs again.
+  Blocked,
   Shutdown,
   NotFound,
 }
@@ -296,7 +298,56 @@ pub struct TeamMessage {
   pub unread: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+/// Consumption limits for a team member or the whole team; `None` is unlimited.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TeamBudget {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub max_tokens: Option<u64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub max_cost_usd: Option<f64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub max_wall_clock_secs: Option<u64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub max_turns: Option<u64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub max_tool_calls: Option<u64>,
+}
+
+impl TeamBudget {
+  pub fn is_unlimited(&self) -> bool {
+    self == &Self::default()
+  }
+}
+
+/// Accumulated model usage and spend for one team member.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TeamMemberUsage {
+  #[serde(default)]
+  pub total_tokens: i64,
+  #[serde(default)]
+  pub cost_usd: f64,
+  #[serde(default)]
+  pub turns: u64,
+  #[serde(default)]
+  pub tool_calls: u64,
+  /// Seconds since the member (re)started.
+  #[serde(default)]
+  pub elapsed_secs: u64,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub budget: Option<TeamBudget>,
+}
+
+/// When a member last showed signs of life, as unix timestamps.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct TeamMemberLiveness {
+  /// Last event of any kind from the member's turn.
+  pub last_event_at: i64,
+  /// Last tool call start, output or completion.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub last_tool_progress_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct TeamMember {
   pub thread_id: String,
   #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -304,11 +355,18 @@ pub struct TeamMember {
   pub role: String,
   pub task: String,
   pub depth: usize,
+  /// Model the member's turns run on (`provider/model` when known).
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub model: Option<String>,
   #[serde(flatten)]
   pub state: CollabAgentWaitState,
+  #[serde(default)]
+  pub usage: TeamMemberUsage,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub liveness: Option<TeamMemberLiveness>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct TeamSnapshot {
   pub root_thread_id: String,
   pub members: Vec<TeamMember>,
@@ -325,6 +383,13 @@ pub struct TeamSnapshot {
   pub ownership_leases: Vec<OwnershipLease>,
   #[serde(default, skip_serializing_if = "Option::is_none")]
   pub workflow: Option<WorkflowRuntimeSnapshot>,
+  /// Combined usage of every spawned teammate (the leader is excluded).
+  #[serde(default)]
+  pub team_usage: TeamMemberUsage,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub team_budget: Option<TeamBudget>,
+  #[serde(default)]
+  pub docs: Vec<TeamDocSummary>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -351,6 +416,32 @@ pub struct TeamPlan {
   pub updated_at: i64,
 }
 
+/// One version of a shared team document.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TeamDoc {
+  pub name: String,
+  pub version: u64,
+  /// Newest version; differs from `version` when an older one was read.
+  pub latest_version: u64,
+  pub content: String,
+  pub author_thread_id: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub summary: Option<String>,
+  pub created_at: i64,
+  #[serde(default)]
+  pub subscribers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TeamDocSummary {
+  pub name: String,
+  pub version: u64,
+  pub author_thread_id: String,
+  pub updated_at: i64,
+  #[serde(default)]
+  pub subscribers: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 pub enum WorkflowRunStatus {
   #[default]
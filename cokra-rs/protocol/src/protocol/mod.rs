@@ -57,6 +57,7 @@ pub enum EventMsg {
   McpStartupUpdate(McpStartupUpdateEvent),
   McpStartupComplete(McpStartupCompleteEvent),
   McpToolCallBegin(McpToolCallBeginEvent),
+  McpToolCallProgress(McpToolCallProgressEvent),
   McpToolCallEnd(McpToolCallEndEvent),
 
   // ========== WEB SEARCH EVENTS ==========
@@ -205,6 +206,11 @@ pub enum Op {
     approval_policy: Option<AskForApproval>,
     sandbox_policy: Option<SandboxPolicy>,
     model: Option<String>,
+    /// `Some(None)` clears the effort back to the model default.
+    #[serde(default)]
+    effort: Option<Option<ReasoningEffortConfig>>,
+    #[serde(default)]
+    summary: Option<ReasoningSummaryConfig>,
     collaboration_mode: Option<CollaborationMode>,
     personality: Option<Personality>,
   },
@@ -222,6 +228,16 @@ pub enum Op {
     response: RequestUserInputResponse,
   },
 
+  /// Answer to an MCP server's elicitation request
+  ResolveElicitation {
+    server_name: String,
+    request_id: String,
+    decision: ElicitationAction,
+    /// Form values keyed by schema property; only read on `Accept`.
+    #[serde(default)]
+    content: Option<serde_json::Value>,
+  },
+
   /// Set thread name
   SetThreadName { name: String },
 
@@ -233,6 +249,9 @@ pub enum Op {
 
   /// List available models
   ListModels,
+
+  /// List prompts that can run as slash commands
+  ListCustomPrompts,
 }
 
 /// A submitted operation with a caller-provided unique identifier.
@@ -378,6 +397,25 @@ pub struct TokenCountEvent {
   pub output_tokens: i64,
   pub reasoning_output_tokens: i64,
   pub total_tokens: i64,
+  /// Prompt-cache write tokens reported for this request.
+  #[serde(default)]
+  pub cache_write_input_tokens: i64,
+  /// Tokens the thread's context holds after this request, counted with the
+  /// model's tokenizer (or its calibrated estimate); 0 when not measured.
+  #[serde(default)]
+  pub context_tokens: i64,
+  /// Cost of this request in USD, when the model has catalog pricing.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub cost_usd: Option<f64>,
+  /// Running cost of the current turn in USD.
+  #[serde(default)]
+  pub turn_cost_usd: f64,
+  /// Running cost of this thread in USD.
+  #[serde(default)]
+  pub thread_cost_usd: f64,
+  /// Running cost of the whole session (root thread plus teammates) in USD.
+  #[serde(default)]
+  pub session_cost_usd: f64,
 }
 
 /// Agent message event
@@ -725,6 +763,10 @@ pub struct ContextCompactedEvent {
   pub tokens_after_est: usize,
   pub reserve_tokens: usize,
   pub keep_recent_tokens: usize,
+  /// Prompt tokens the provider had cached for the replaced history. Rewriting
+  /// history invalidates that prefix, so the next request pays full price.
+  #[serde(default)]
+  pub invalidated_cached_tokens: i64,
 }
 
 /// Thread rolled back event
@@ -808,6 +850,17 @@ pub struct McpToolCallBeginEvent {
   pub invocation: McpInvocation,
 }
 
+/// Progress an MCP server reported for a running tool call
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct McpToolCallProgressEvent {
+  pub thread_id: String,
+  pub turn_id: String,
+  pub call_id: String,
+  pub progress: f64,
+  pub total: Option<f64>,
+  pub message: Option<String>,
+}
+
 /// MCP tool call end event
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct McpToolCallEndEvent {
@@ -930,9 +983,16 @@ pub struct DynamicToolCallRequestEvent {
 /// Elicitation request event (MCP server prompting user)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ElicitationRequestEvent {
+  #[serde(default)]
+  pub thread_id: String,
+  #[serde(default)]
+  pub turn_id: String,
   pub server_name: String,
   pub id: String,
   pub message: String,
+  /// Flat JSON-schema object describing the form fields.
+  #[serde(default)]
+  pub requested_schema: serde_json::Value,
 }
 
 /// Apply patch approval request event
@@ -1048,11 +1108,27 @@ pub struct GetHistoryEntryResponseEvent {
 }
 
 /// Custom prompt metadata
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CustomPrompt {
+  /// Slash command name, without the leading `/`.
   pub id: String,
   pub name: String,
   pub description: Option<String>,
+  /// MCP server serving the prompt.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub server_name: Option<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub arguments: Vec<CustomPromptArgument>,
+}
+
+/// One argument a custom prompt accepts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomPromptArgument {
+  pub name: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub description: Option<String>,
+  #[serde(default)]
+  pub required: bool,
 }
 
 /// List custom prompts response event
@@ -1351,6 +1427,12 @@ pub struct CollabResumeEndEvent {
   pub call_id: String,
   pub sender_thread_id: String,
   pub receiver_thread_id: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub receiver_nickname: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub receiver_role: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub error: Option<String>,
   #[serde(default)]
   pub lifecycle: CollabAgentLifecycle,
 }
@@ -18,6 +18,9 @@ pub enum ResponseEvent {
   ReasoningSummaryDelta { delta: String, summary_index: usize },
   /// Raw reasoning content delta.
   ReasoningContentDelta { delta: String, content_index: usize },
+  /// Signature closing the current reasoning block (Anthropic extended
+  /// thinking); the block must be replayed with it on the next request.
+  ReasoningSignature { signature: String },
   /// Provider rate limit snapshot.
   RateLimits(ResponseRateLimitsSnapshot),
   /// Response completed with token usage.
@@ -51,6 +54,9 @@ pub struct ResponseTokenUsage {
   #[serde(default)]
   pub reasoning_output_tokens: i64,
   pub total_tokens: i64,
+  /// Tokens written into the provider prompt cache (billed at the cache-write rate).
+  #[serde(default)]
+  pub cache_write_input_tokens: i64,
 }
 
 /// Simplified rate limit snapshot.
@@ -136,6 +142,7 @@ mod tests {
         output_tokens: 5,
         reasoning_output_tokens: 2,
         total_tokens: 15,
+        cache_write_input_tokens: 1,
       }),
     };
 
@@ -3,28 +3,54 @@ use std::ffi::OsString;
 use std::path::PathBuf;
 use std::process::Stdio;
 use std::sync::Arc;
+use std::sync::PoisonError;
 use std::time::Duration;
 
 use anyhow::Result;
 use anyhow::anyhow;
+use futures::future::BoxFuture;
 use rmcp::ClientHandler;
+use rmcp::ErrorData as McpError;
+use rmcp::model::CallToolRequest;
 use rmcp::model::CallToolRequestParams;
 use rmcp::model::CallToolResult;
+use rmcp::model::CancelledNotificationParam;
 use rmcp::model::ClientInfo;
 use rmcp::model::ClientRequest;
+use rmcp::model::CreateElicitationRequestParams;
+use rmcp::model::CreateElicitationResult;
+use rmcp::model::CreateMessageRequestMethod;
+use rmcp::model::CreateMessageRequestParams;
+use rmcp::model::CreateMessageResult;
+use rmcp::model::ElicitationAction;
+use rmcp::model::ElicitationCapability;
 use rmcp::model::Extensions;
+use rmcp::model::GetPromptRequestParams;
+use rmcp::model::GetPromptResult;
 use rmcp::model::InitializeRequestParams;
 use rmcp::model::InitializeResult;
+use rmcp::model::ListPromptsResult;
 use rmcp::model::ListResourceTemplatesResult;
 use rmcp::model::ListResourcesResult;
+use rmcp::model::ListRootsResult;
 use rmcp::model::ListToolsResult;
 use rmcp::model::PaginatedRequestParams;
+use rmcp::model::ProgressNotificationParam;
+use rmcp::model::ProgressToken;
 use rmcp::model::ReadResourceRequestParams;
 use rmcp::model::ReadResourceResult;
+use rmcp::model::RequestId;
+use rmcp::model::Root;
+use rmcp::model::RootsCapabilities;
+use rmcp::model::SamplingCapability;
 use rmcp::model::ServerResult;
 use rmcp::service;
+use rmcp::service::NotificationContext;
+use rmcp::service::Peer;
+use rmcp::service::PeerRequestOptions;
+use rmcp::service::RequestContext;
 use rmcp::service::RoleClient;
-use rmcp::service::RunningService;
+use rmcp::service::RunningServiceCancellationToken;
 use rmcp::transport::child_process::TokioChildProcess;
 use rmcp::transport::streamable_http_client::StreamableHttpClientTransportConfig;
 use rmcp::transport::streamable_http_client::StreamableHttpClientWorker;
@@ -32,13 +58,32 @@ use rmcp::transport::worker::WorkerTransport;
 use serde_json::Value;
 use tokio::process::Command;
 use tokio::sync::Mutex;
+use tokio::sync::watch;
 use tokio::time;
 
-type StreamableHttpTransport = WorkerTransport<StreamableHttpClientWorker<reqwest::Client>>;
+mod oauth;
+
+pub use oauth::OAuthSession;
+pub use oauth::OAuthTokens;
+pub use oauth::OnTokensRefreshed;
+pub use oauth::TokenGrant;
+pub use oauth::request_token;
+
+use oauth::AuthorizedHttpClient;
+
+type StreamableHttpTransport = WorkerTransport<StreamableHttpClientWorker<AuthorizedHttpClient>>;
 
 enum PendingTransport {
-  ChildProcess { transport: TokioChildProcess },
-  StreamableHttp { transport: StreamableHttpTransport },
+  ChildProcess {
+    transport: TokioChildProcess,
+  },
+  StreamableHttp {
+    transport: StreamableHttpTransport,
+  },
+  #[cfg(any(test, feature = "test-support"))]
+  InProcess {
+    transport: tokio::io::DuplexStream,
+  },
 }
 
 enum ClientState {
@@ -46,23 +91,191 @@ enum ClientState {
     transport: Option<PendingTransport>,
   },
   Ready {
-    service: Arc<RunningService<RoleClient, NoopClientHandler>>,
+    peer: Peer<RoleClient>,
+    /// Stops the service task, and with it the transport, when the client
+    /// is dropped.
+    cancel: Option<RunningServiceCancellationToken>,
   },
 }
 
+/// Asks the user to answer a server's elicitation request.
+pub type SendElicitation = Arc<
+  dyn Fn(CreateElicitationRequestParams) -> BoxFuture<'static, CreateElicitationResult>
+    + Send
+    + Sync,
+>;
+
+/// Runs a server's `sampling/createMessage` request against the host's model.
+pub type SendSampling = Arc<
+  dyn Fn(CreateMessageRequestParams) -> BoxFuture<'static, Result<CreateMessageResult, McpError>>
+    + Send
+    + Sync,
+>;
+
+/// Which of the server's lists it reported as changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListChanged {
+  Tools,
+  Resources,
+  Prompts,
+}
+
+/// Told when the server sends a `notifications/*/list_changed`.
+pub type OnListChanged = Arc<dyn Fn(ListChanged) + Send + Sync>;
+
+/// Answers `roots/list` with the host's current roots.
+pub type ListRoots = Arc<dyn Fn() -> Vec<Root> + Send + Sync>;
+
+/// Told about each `notifications/progress` for one in-flight tool call.
+pub type OnProgress = Arc<dyn Fn(ProgressNotificationParam) + Send + Sync>;
+
+/// Host callbacks for server-initiated requests. Each capability is
+/// advertised only when its callback is set.
+#[derive(Clone, Default)]
+pub struct ClientCallbacks {
+  pub send_elicitation: Option<SendElicitation>,
+  pub send_sampling: Option<SendSampling>,
+  pub on_list_changed: Option<OnListChanged>,
+  pub list_roots: Option<ListRoots>,
+}
+
+/// Progress listeners of in-flight requests, by the token sent with each.
+type ProgressRoutes = Arc<std::sync::Mutex<HashMap<ProgressToken, OnProgress>>>;
+
 #[derive(Clone)]
-struct NoopClientHandler {
+struct CokraClientHandler {
   client_info: ClientInfo,
+  callbacks: ClientCallbacks,
+  progress: ProgressRoutes,
 }
 
-impl ClientHandler for NoopClientHandler {
+impl ClientHandler for CokraClientHandler {
   fn get_info(&self) -> ClientInfo {
     self.client_info.clone()
   }
+
+  async fn create_elicitation(
+    &self,
+    request: CreateElicitationRequestParams,
+    _context: RequestContext<RoleClient>,
+  ) -> Result<CreateElicitationResult, McpError> {
+    match (&self.callbacks.send_elicitation, &request) {
+      // URL mode is never advertised, so a server sending one gets a decline.
+      (Some(send), CreateElicitationRequestParams::FormElicitationParams { .. }) => {
+        Ok(send(request).await)
+      }
+      _ => Ok(CreateElicitationResult {
+        action: ElicitationAction::Decline,
+        content: None,
+      }),
+    }
+  }
+
+  async fn create_message(
+    &self,
+    params: CreateMessageRequestParams,
+    _context: RequestContext<RoleClient>,
+  ) -> Result<CreateMessageResult, McpError> {
+    match &self.callbacks.send_sampling {
+      Some(send) => send(params).await,
+      None => Err(McpError::method_not_found::<CreateMessageRequestMethod>()),
+    }
+  }
+
+  async fn list_roots(
+    &self,
+    _context: RequestContext<RoleClient>,
+  ) -> Result<ListRootsResult, McpError> {
+    let roots = self
+      .callbacks
+      .list_roots
+      .as_ref()
+      .map(|list_roots| list_roots())
+      .unwrap_or_default();
+    Ok(ListRootsResult { roots })
+  }
+
+  async fn on_progress(
+    &self,
+    params: ProgressNotificationParam,
+    _context: NotificationContext<RoleClient>,
+  ) {
+    let on_progress = self
+      .progress
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner)
+      .get(&params.progress_token)
+      .cloned();
+    if let Some(on_progress) = on_progress {
+      on_progress(params);
+    }
+  }
+
+  async fn on_tool_list_changed(&self, _context: NotificationContext<RoleClient>) {
+    self.notify_list_changed(ListChanged::Tools);
+  }
+
+  async fn on_resource_list_changed(&self, _context: NotificationContext<RoleClient>) {
+    self.notify_list_changed(ListChanged::Resources);
+  }
+
+  async fn on_prompt_list_changed(&self, _context: NotificationContext<RoleClient>) {
+    self.notify_list_changed(ListChanged::Prompts);
+  }
+}
+
+impl CokraClientHandler {
+  fn notify_list_changed(&self, list: ListChanged) {
+    if let Some(on_list_changed) = &self.callbacks.on_list_changed {
+      on_list_changed(list);
+    }
+  }
 }
 
 pub struct RmcpClient {
   state: Mutex<ClientState>,
+  /// Flips to `true` once the service loop ends, whether the server exited,
+  /// the transport broke or the client was dropped.
+  closed: Arc<watch::Sender<bool>>,
+  progress: ProgressRoutes,
+}
+
+/// A `tools/call` the server has not answered yet. Dropping it early, when
+/// the turn is interrupted or the call times out, sends
+/// `notifications/cancelled` so the server can stop working on it.
+struct InFlightCall {
+  peer: Peer<RoleClient>,
+  id: RequestId,
+  progress_token: ProgressToken,
+  progress: ProgressRoutes,
+  finished: bool,
+  cancel_reason: &'static str,
+}
+
+impl Drop for InFlightCall {
+  fn drop(&mut self) {
+    self
+      .progress
+      .lock()
+      .unwrap_or_else(PoisonError::into_inner)
+      .remove(&self.progress_token);
+    if self.finished {
+      return;
+    }
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+      return;
+    };
+    let peer = self.peer.clone();
+    let params = CancelledNotificationParam {
+      request_id: self.id.clone(),
+      reason: Some(self.cancel_reason.to_string()),
+    };
+    runtime.spawn(async move {
+      if let Err(err) = peer.notify_cancelled(params).await {
+        tracing::debug!("failed to send MCP cancellation: {err}");
+      }
+    });
+  }
 }
 
 impl RmcpClient {
@@ -94,18 +307,24 @@ impl RmcpClient {
       state: Mutex::new(ClientState::Connecting {
         transport: Some(PendingTransport::ChildProcess { transport }),
       }),
+      closed: Arc::new(watch::Sender::new(false)),
+      progress: ProgressRoutes::default(),
     })
   }
 
+  /// Connect over streamable HTTP. With an `oauth` session the static
+  /// `bearer_token` is ignored and the session's access token, refreshed as
+  /// needed, is sent instead.
   pub async fn new_streamable_http_client(
     url: &str,
     bearer_token: Option<String>,
     headers: Option<HashMap<String, String>>,
+    oauth: Option<Arc<OAuthSession>>,
   ) -> Result<Self> {
     let mut reqwest_builder = reqwest::Client::builder();
 
     let mut default_headers = reqwest::header::HeaderMap::new();
-    if let Some(token) = bearer_token {
+    if let Some(token) = bearer_token.filter(|_| oauth.is_none()) {
       let value = reqwest::header::HeaderValue::from_str(&format!("Bearer {token}"))
         .map_err(|e| anyhow!("invalid bearer token header value: {e}"))?;
       default_headers.insert(reqwest::header::AUTHORIZATION, value);
@@ -126,6 +345,10 @@ impl RmcpClient {
       .map_err(|e| anyhow!("failed to build HTTP client for {url}: {e}"))?;
 
     let config = StreamableHttpClientTransportConfig::with_uri(url);
+    let client = AuthorizedHttpClient {
+      http: client,
+      oauth,
+    };
     let worker = StreamableHttpClientWorker::new(client, config);
     let transport = WorkerTransport::spawn(worker);
 
@@ -133,21 +356,58 @@ impl RmcpClient {
       state: Mutex::new(ClientState::Connecting {
         transport: Some(PendingTransport::StreamableHttp { transport }),
       }),
+      closed: Arc::new(watch::Sender::new(false)),
+      progress: ProgressRoutes::default(),
     })
   }
 
+  /// Connect over an in-process pipe whose other end a test serves.
+  #[cfg(any(test, feature = "test-support"))]
+  pub fn new_in_process_client(transport: tokio::io::DuplexStream) -> Self {
+    Self {
+      state: Mutex::new(ClientState::Connecting {
+        transport: Some(PendingTransport::InProcess { transport }),
+      }),
+      closed: Arc::new(watch::Sender::new(false)),
+      progress: ProgressRoutes::default(),
+    }
+  }
+
+  /// Handshake with the server.
+  ///
+  /// Form elicitation, sampling and roots are advertised only for the
+  /// callbacks that are set; without them elicitations are declined, sampling
+  /// requests fail with `method not found` and the root list is empty.
   pub async fn initialize(
     &self,
     params: InitializeRequestParams,
     timeout: Option<Duration>,
+    callbacks: ClientCallbacks,
   ) -> Result<InitializeResult> {
-    let handler = NoopClientHandler {
+    let mut capabilities = params.capabilities.clone();
+    if callbacks.send_elicitation.is_some() {
+      capabilities.elicitation = Some(ElicitationCapability {
+        form: Some(Default::default()),
+        url: None,
+      });
+    }
+    if callbacks.send_sampling.is_some() {
+      capabilities.sampling = Some(SamplingCapability::default());
+    }
+    if callbacks.list_roots.is_some() {
+      capabilities.roots = Some(RootsCapabilities {
+        list_changed: Some(true),
+      });
+    }
+    let handler = CokraClientHandler {
       client_info: ClientInfo {
         meta: params.meta.clone(),
         protocol_version: params.protocol_version,
-        capabilities: params.capabilities.clone(),
+        capabilities,
         client_info: params.client_info.clone(),
       },
+      callbacks,
+      progress: Arc::clone(&self.progress),
     };
 
     let transport = {
@@ -177,6 +437,10 @@ impl RmcpClient {
         }
         None => service::serve_client(handler.clone(), transport).await?,
       },
+      #[cfg(any(test, feature = "test-support"))]
+      PendingTransport::InProcess { transport } => {
+        service::serve_client(handler.clone(), transport).await?
+      }
     };
 
     let initialize_result = service
@@ -185,14 +449,35 @@ impl RmcpClient {
       .ok_or_else(|| anyhow!("handshake succeeded but server info was missing"))?;
     let initialize_result = initialize_result.clone();
 
+    let peer = service.peer().clone();
+    let cancel = service.cancellation_token();
+    let closed = Arc::clone(&self.closed);
+    tokio::spawn(async move {
+      let reason = service.waiting().await;
+      tracing::debug!("MCP service loop ended: {reason:?}");
+      closed.send_replace(true);
+    });
+
     let mut guard = self.state.lock().await;
     *guard = ClientState::Ready {
-      service: Arc::new(service),
+      peer,
+      cancel: Some(cancel),
     };
 
     Ok(initialize_result)
   }
 
+  /// Resolves once the connection is gone. Never resolves before
+  /// [`Self::initialize`] succeeds.
+  pub async fn wait_closed(&self) {
+    let mut closed = self.closed.subscribe();
+    let _ = closed.wait_for(|closed| *closed).await;
+  }
+
+  pub fn is_closed(&self) -> bool {
+    *self.closed.borrow()
+  }
+
   pub async fn list_tools(
     &self,
     params: Option<PaginatedRequestParams>,
@@ -238,6 +523,41 @@ impl RmcpClient {
     })
   }
 
+  pub async fn list_prompts(
+    &self,
+    params: Option<PaginatedRequestParams>,
+    timeout: Option<Duration>,
+  ) -> Result<ListPromptsResult> {
+    let service = self.service().await?;
+    let fut = service.list_prompts(params);
+    Ok(match timeout {
+      Some(duration) => time::timeout(duration, fut)
+        .await
+        .map_err(|_| anyhow!("prompts/list timed out after {duration:?}"))??,
+      None => fut.await?,
+    })
+  }
+
+  pub async fn get_prompt(
+    &self,
+    name: String,
+    arguments: Option<serde_json::Map<String, Value>>,
+    timeout: Option<Duration>,
+  ) -> Result<GetPromptResult> {
+    let service = self.service().await?;
+    let fut = service.get_prompt(GetPromptRequestParams {
+      meta: None,
+      name,
+      arguments,
+    });
+    Ok(match timeout {
+      Some(duration) => time::timeout(duration, fut)
+        .await
+        .map_err(|_| anyhow!("prompts/get timed out after {duration:?}"))??,
+      None => fut.await?,
+    })
+  }
+
   pub async fn read_resource(
     &self,
     uri: impl Into<String>,
@@ -256,11 +576,15 @@ impl RmcpClient {
     })
   }
 
+  /// Call a tool. `on_progress` hears the server's progress notifications
+  /// for this call; dropping the returned future before it resolves cancels
+  /// the call on the server.
   pub async fn call_tool(
     &self,
     name: String,
     arguments: Option<Value>,
     timeout: Option<Duration>,
+    on_progress: Option<OnProgress>,
   ) -> Result<CallToolResult> {
     let service = self.service().await?;
     let arguments = match arguments {
@@ -272,18 +596,56 @@ impl RmcpClient {
       }
       None => None,
     };
-    let fut = service.call_tool(CallToolRequestParams {
-      meta: None,
-      name: name.into(),
-      arguments,
-      task: None,
+    let request = ClientRequest::CallToolRequest(CallToolRequest {
+      method: Default::default(),
+      params: CallToolRequestParams {
+        meta: None,
+        name: name.into(),
+        arguments,
+        task: None,
+      },
+      extensions: Default::default(),
     });
-    Ok(match timeout {
-      Some(duration) => time::timeout(duration, fut)
-        .await
-        .map_err(|_| anyhow!("tools/call timed out after {duration:?}"))??,
-      None => fut.await?,
-    })
+    let handle = service
+      .send_cancellable_request(request, PeerRequestOptions::no_options())
+      .await?;
+    if let Some(on_progress) = on_progress {
+      self
+        .progress
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(handle.progress_token.clone(), on_progress);
+    }
+    let mut in_flight = InFlightCall {
+      peer: handle.peer,
+      id: handle.id,
+      progress_token: handle.progress_token,
+      progress: Arc::clone(&self.progress),
+      finished: false,
+      cancel_reason: "interrupted by the user",
+    };
+
+    let response = match timeout {
+      Some(duration) => match time::timeout(duration, handle.rx).await {
+        Ok(response) => response,
+        Err(_) => {
+          in_flight.cancel_reason = "timed out";
+          return Err(anyhow!("tools/call timed out after {duration:?}"));
+        }
+      },
+      None => handle.rx.await,
+    };
+    in_flight.finished = true;
+    match response.map_err(|_| anyhow!("MCP server connection closed"))?? {
+      ServerResult::CallToolResult(result) => Ok(result),
+      other => Err(anyhow!("unexpected response to tools/call: {other:?}")),
+    }
+  }
+
+  /// Tell the server that the host's roots changed; it re-requests them.
+  pub async fn notify_roots_list_changed(&self) -> Result<()> {
+    let service = self.service().await?;
+    Ok(service.notify_roots_list_changed().await?)
   }
 
   pub async fn send_custom_request(
@@ -303,11 +665,215 @@ impl RmcpClient {
     )
   }
 
-  async fn service(&self) -> Result<Arc<RunningService<RoleClient, NoopClientHandler>>> {
+  async fn service(&self) -> Result<Peer<RoleClient>> {
     let guard = self.state.lock().await;
     match &*guard {
-      ClientState::Ready { service } => Ok(Arc::clone(service)),
+      ClientState::Ready { .. } if self.is_closed() => Err(anyhow!("MCP server connection closed")),
+      ClientState::Ready { peer, .. } => Ok(peer.clone()),
       ClientState::Connecting { .. } => Err(anyhow!("MCP client not initialized")),
     }
   }
 }
+
+impl Drop for RmcpClient {
+  fn drop(&mut self) {
+    if let ClientState::Ready { cancel, .. } = self.state.get_mut()
+      && let Some(cancel) = cancel.take()
+    {
+      cancel.cancel();
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+  use serde_json::json;
+  use tokio::io::AsyncBufReadExt;
+  use tokio::io::AsyncWriteExt;
+  use tokio::io::BufReader;
+  use tokio::io::DuplexStream;
+  use tokio::io::Lines;
+  use tokio::io::ReadHalf;
+  use tokio::io::WriteHalf;
+
+  use super::*;
+
+  /// The server end of an in-process connection, speaking raw JSON-RPC so
+  /// tests see exactly what the client sends.
+  struct FakeServer {
+    lines: Lines<BufReader<ReadHalf<DuplexStream>>>,
+    writer: WriteHalf<DuplexStream>,
+  }
+
+  impl FakeServer {
+    async fn recv(&mut self) -> Value {
+      let line = time::timeout(Duration::from_secs(5), self.lines.next_line())
+        .await
+        .expect("client message in time")
+        .expect("read")
+        .expect("connection open");
+      serde_json::from_str(&line).expect("json-rpc message")
+    }
+
+    async fn send(&mut self, message: Value) {
+      let mut line = message.to_string();
+      line.push('\n');
+      self.writer.write_all(line.as_bytes()).await.expect("write");
+    }
+
+    /// Read the next `tools/call` and return the whole request.
+    async fn recv_tool_call(&mut self) -> Value {
+      let request = self.recv().await;
+      assert_eq!(request["method"], "tools/call");
+      request
+    }
+  }
+
+  async fn connect() -> (Arc<RmcpClient>, FakeServer) {
+    let (client_end, server_end) = tokio::io::duplex(64 * 1024);
+    let (reader, writer) = tokio::io::split(server_end);
+    let mut server = FakeServer {
+      lines: BufReader::new(reader).lines(),
+      writer,
+    };
+    let client = Arc::new(RmcpClient::new_in_process_client(client_end));
+
+    let initialize = {
+      let client = Arc::clone(&client);
+      tokio::spawn(async move {
+        client
+          .initialize(
+            InitializeRequestParams::default(),
+            None,
+            ClientCallbacks::default(),
+          )
+          .await
+      })
+    };
+    let request = server.recv().await;
+    assert_eq!(request["method"], "initialize");
+    server
+      .send(json!({
+        "jsonrpc": "2.0",
+        "id": request["id"],
+        "result": {
+          "protocolVersion": "2025-06-18",
+          "capabilities": { "tools": {} },
+          "serverInfo": { "name": "fake", "version": "0.0.0" },
+        },
+      }))
+      .await;
+    initialize
+      .await
+      .expect("initialize task")
+      .expect("initialize");
+    assert_eq!(server.recv().await["method"], "notifications/initialized");
+    (client, server)
+  }
+
+  #[tokio::test]
+  async fn dropping_a_pending_call_cancels_it_on_the_server() {
+    let (client, mut server) = connect().await;
+    let call = {
+      let client = Arc::clone(&client);
+      tokio::spawn(async move { client.call_tool("slow".to_string(), None, None, None).await })
+    };
+    let request = server.recv_tool_call().await;
+
+    // Interrupting the turn drops the call future.
+    call.abort();
+
+    let cancelled = server.recv().await;
+    assert_eq!(cancelled["method"], "notifications/cancelled");
+    assert_eq!(cancelled["params"]["requestId"], request["id"]);
+    assert_eq!(cancelled["params"]["reason"], "interrupted by the user");
+  }
+
+  #[tokio::test]
+  async fn timed_out_call_is_cancelled_on_the_server() {
+    let (client, mut server) = connect().await;
+    let call = {
+      let client = Arc::clone(&client);
+      tokio::spawn(async move {
+        client
+          .call_tool(
+            "slow".to_string(),
+            None,
+            Some(Duration::from_millis(50)),
+            None,
+          )
+          .await
+      })
+    };
+    let request = server.recv_tool_call().await;
+
+    let err = call
+      .await
+      .expect("call task")
+      .expect_err("call should time out");
+    assert!(err.to_string().contains("timed out"));
+    let cancelled = server.recv().await;
+    assert_eq!(cancelled["method"], "notifications/cancelled");
+    assert_eq!(cancelled["params"]["requestId"], request["id"]);
+    assert_eq!(cancelled["params"]["reason"], "timed out");
+  }
+
+  #[tokio::test]
+  async fn progress_notifications_reach_the_calls_listener() {
+    let (client, mut server) = connect().await;
+    let (progress_tx, mut progress_rx) = tokio::sync::mpsc::unbounded_channel();
+    let on_progress: OnProgress = Arc::new(move |params: ProgressNotificationParam| {
+      let _ = progress_tx.send(params);
+    });
+    let call = {
+      let client = Arc::clone(&client);
+      tokio::spawn(async move {
+        client
+          .call_tool("slow".to_string(), None, None, Some(on_progress))
+          .await
+      })
+    };
+    let request = server.recv_tool_call().await;
+    let progress_token = request["params"]["_meta"]["progressToken"].clone();
+    assert!(!progress_token.is_null());
+
+    server
+      .send(json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/progress",
+        "params": {
+          "progressToken": progress_token,
+          "progress": 1,
+          "total": 2,
+          "message": "halfway",
+        },
+      }))
+      .await;
+    let progress = time::timeout(Duration::from_secs(5), progress_rx.recv())
+      .await
+      .expect("progress in time")
+      .expect("progress");
+    assert_eq!(progress.progress, 1.0);
+    assert_eq!(progress.total, Some(2.0));
+    assert_eq!(progress.message.as_deref(), Some("halfway"));
+
+    server
+      .send(json!({
+        "jsonrpc": "2.0",
+        "id": request["id"],
+        "result": { "content": [{ "type": "text", "text": "done" }] },
+      }))
+      .await;
+    let result = call.await.expect("call task").expect("call result");
+    assert_eq!(result.is_error, None);
+    // A finished call stops listening and is not cancelled.
+    assert!(
+      client
+        .progress
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .is_empty()
+    );
+  }
+}
@@ -0,0 +1,385 @@
+//! OAuth access tokens for streamable-HTTP servers.
+//!
+//! The authorization flow itself runs in the host (`cokra mcp login`); this
+//! module only carries the resulting tokens and refreshes them shortly before
+//! they expire, so a long session never sends a stale bearer token.
+
+use std::borrow::Cow;
+use std::sync::Arc;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use anyhow::Result;
+use anyhow::anyhow;
+use futures::future::BoxFuture;
+use futures::stream::BoxStream;
+use rmcp::model::ClientJsonRpcMessage;
+use rmcp::transport::streamable_http_client::SseError;
+use rmcp::transport::streamable_http_client::StreamableHttpClient;
+use rmcp::transport::streamable_http_client::StreamableHttpError;
+use rmcp::transport::streamable_http_client::StreamableHttpPostResponse;
+use serde::Deserialize;
+use serde::Serialize;
+use sse_stream::Sse;
+use tokio::sync::Mutex;
+
+/// Refresh this long before the recorded expiry, to cover clock skew and
+/// requests that are already in flight.
+const REFRESH_SKEW_SECS: u64 = 60;
+
+/// Tokens obtained from a server's authorization server, plus what is needed
+/// to refresh them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OAuthTokens {
+  pub access_token: String,
+  pub refresh_token: Option<String>,
+  /// Unix seconds; `None` when the authorization server gave no lifetime.
+  pub expires_at: Option<u64>,
+  pub token_endpoint: String,
+  pub client_id: String,
+  pub client_secret: Option<String>,
+  /// RFC 8707 resource indicator sent with every token request.
+  pub resource: Option<String>,
+}
+
+impl OAuthTokens {
+  fn needs_refresh(&self) -> bool {
+    self
+      .expires_at
+      .is_some_and(|expires_at| unix_now().saturating_add(REFRESH_SKEW_SECS) >= expires_at)
+  }
+}
+
+/// The fields of a successful token endpoint response that Cokra uses.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenGrant {
+  pub access_token: String,
+  #[serde(default)]
+  pub refresh_token: Option<String>,
+  #[serde(default)]
+  pub expires_in: Option<u64>,
+}
+
+impl TokenGrant {
+  /// Absolute expiry of the access token, if the server gave a lifetime.
+  pub fn expires_at(&self) -> Option<u64> {
+    self
+      .expires_in
+      .map(|expires_in| unix_now().saturating_add(expires_in))
+  }
+}
+
+/// POST a form-encoded token request (`authorization_code` or
+/// `refresh_token` grant) and read the grant from the response.
+pub async fn request_token(
+  http: &reqwest::Client,
+  token_endpoint: &str,
+  form: &[(&str, &str)],
+) -> Result<TokenGrant> {
+  let response = http
+    .post(token_endpoint)
+    .header(reqwest::header::ACCEPT, "application/json")
+    .form(form)
+    .send()
+    .await
+    .map_err(|e| anyhow!("token request to {token_endpoint} failed: {e}"))?;
+  let status = response.status();
+  if !status.is_success() {
+    let body = response.text().await.unwrap_or_default();
+    return Err(anyhow!(
+      "token endpoint {token_endpoint} returned {status}: {}",
+      body.trim()
+    ));
+  }
+  response
+    .json::<TokenGrant>()
+    .await
+    .map_err(|e| anyhow!("invalid token response from {token_endpoint}: {e}"))
+}
+
+/// Called with the new tokens after every successful refresh, so the host
+/// can persist them. The returned future is awaited before the refreshed
+/// token is handed out, so saves happen in refresh order.
+pub type OnTokensRefreshed = Arc<dyn Fn(OAuthTokens) -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// The live tokens of one connection.
+pub struct OAuthSession {
+  tokens: Mutex<OAuthTokens>,
+  http: reqwest::Client,
+  on_refreshed: Option<OnTokensRefreshed>,
+}
+
+impl OAuthSession {
+  pub fn new(tokens: OAuthTokens, on_refreshed: Option<OnTokensRefreshed>) -> Self {
+    Self {
+      tokens: Mutex::new(tokens),
+      http: reqwest::Client::new(),
+      on_refreshed,
+    }
+  }
+
+  /// A usable access token, refreshing first when the current one is about
+  /// to expire. Holding the lock across the refresh and the save keeps
+  /// concurrent requests from spending the same refresh token twice and
+  /// keeps an older rotated refresh token from being stored last.
+  pub async fn access_token(&self) -> Result<String> {
+    let mut tokens = self.tokens.lock().await;
+    if tokens.needs_refresh() {
+      let refresh_token = tokens.refresh_token.clone().ok_or_else(|| {
+        anyhow!("MCP access token expired and no refresh token is stored; run `cokra mcp login`")
+      })?;
+      let mut form = vec![
+        ("grant_type", "refresh_token"),
+        ("refresh_token", refresh_token.as_str()),
+        ("client_id", tokens.client_id.as_str()),
+      ];
+      if let Some(secret) = &tokens.client_secret {
+        form.push(("client_secret", secret));
+      }
+      if let Some(resource) = &tokens.resource {
+        form.push(("resource", resource));
+      }
+      let grant = request_token(&self.http, &tokens.token_endpoint, &form).await?;
+      tokens.expires_at = grant.expires_at();
+      tokens.access_token = grant.access_token;
+      // Servers that do not rotate refresh tokens omit them from the grant.
+      if grant.refresh_token.is_some() {
+        tokens.refresh_token = grant.refresh_token;
+      }
+      if let Some(on_refreshed) = &self.on_refreshed {
+        on_refreshed(tokens.clone()).await;
+      }
+    }
+    Ok(tokens.access_token.clone())
+  }
+}
+
+/// `reqwest` transport that swaps in the session's current access token for
+/// every request; without a session it behaves like a plain client.
+#[derive(Clone)]
+pub(crate) struct AuthorizedHttpClient {
+  pub(crate) http: reqwest::Client,
+  pub(crate) oauth: Option<Arc<OAuthSession>>,
+}
+
+impl AuthorizedHttpClient {
+  async fn auth_header(
+    &self,
+    static_header: Option<String>,
+  ) -> Result<Option<String>, StreamableHttpError<reqwest::Error>> {
+    match &self.oauth {
+      Some(oauth) => oauth
+        .access_token()
+        .await
+        .map(Some)
+        .map_err(|e| StreamableHttpError::UnexpectedServerResponse(Cow::Owned(format!("{e:#}")))),
+      None => Ok(static_header),
+    }
+  }
+}
+
+impl StreamableHttpClient for AuthorizedHttpClient {
+  type Error = reqwest::Error;
+
+  async fn post_message(
+    &self,
+    uri: Arc<str>,
+    message: ClientJsonRpcMessage,
+    session_id: Option<Arc<str>>,
+    auth_header: Option<String>,
+  ) -> Result<StreamableHttpPostResponse, StreamableHttpError<Self::Error>> {
+    let auth_header = self.auth_header(auth_header).await?;
+    self
+      .http
+      .post_message(uri, message, session_id, auth_header)
+      .await
+  }
+
+  async fn delete_session(
+    &self,
+    uri: Arc<str>,
+    session_id: Arc<str>,
+    auth_header: Option<String>,
+  ) -> Result<(), StreamableHttpError<Self::Error>> {
+    let auth_header = self.auth_header(auth_header).await?;
+    self.http.delete_session(uri, session_id, auth_header).await
+  }
+
+  async fn get_stream(
+    &self,
+    uri: Arc<str>,
+    session_id: Arc<str>,
+    last_event_id: Option<String>,
+    auth_header: Option<String>,
+  ) -> Result<BoxStream<'static, Result<Sse, SseError>>, StreamableHttpError<Self::Error>> {
+    let auth_header = self.auth_header(auth_header).await?;
+    self
+      .http
+      .get_stream(uri, session_id, last_event_id, auth_header)
+      .await
+  }
+}
+
+fn unix_now() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|elapsed| elapsed.as_secs())
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Mutex as StdMutex;
+
+  use tokio::io::AsyncReadExt;
+  use tokio::io::AsyncWriteExt;
+  use tokio::net::TcpListener;
+
+  use super::*;
+
+  /// Token endpoint that answers every request with `grant` and records the
+  /// form bodies it received.
+  async fn token_endpoint(grant: &'static str) -> (String, Arc<StdMutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+    let url = format!("http://{}/token", listener.local_addr().expect("addr"));
+    let bodies = Arc::new(StdMutex::new(Vec::new()));
+    let recorded = bodies.clone();
+    tokio::spawn(async move {
+      while let Ok((mut socket, _)) = listener.accept().await {
+        let mut request = Vec::new();
+        let mut buf = [0u8; 4096];
+        let body = loop {
+          let read = socket.read(&mut buf).await.unwrap_or(0);
+          if read == 0 {
+            break String::new();
+          }
+          request.extend_from_slice(&buf[..read]);
+          let text = String::from_utf8_lossy(&request).to_string();
+          let Some((head, body)) = text.split_once("\r\n\r\n") else {
+            continue;
+          };
+          let length = head
+            .lines()
+            .find_map(|line| {
+              let (name, value) = line.split_once(':')?;
+              name
+                .eq_ignore_ascii_case("content-length")
+                .then(|| value.trim().parse::<usize>().ok())?
+            })
+            .unwrap_or(0);
+          if body.len() >= length {
+            break body.to_string();
+          }
+        };
+        recorded.lock().expect("bodies").push(body);
+        let response = format!(
+          "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{grant}",
+          grant.len()
+        );
+        let _ = socket.write_all(response.as_bytes()).await;
+      }
+    });
+    (url, bodies)
+  }
+
+  fn tokens(
+    token_endpoint: &str,
+    refresh_token: Option<&str>,
+    expires_at: Option<u64>,
+  ) -> OAuthTokens {
+    OAuthTokens {
+      access_token: "access-1".to_string(),
+      refresh_token: refresh_token.map(ToString::to_string),
+      expires_at,
+      token_endpoint: token_endpoint.to_string(),
+      client_id: "client-1".to_string(),
+      client_secret: None,
+      resource: Some("https://mcp.example/mcp".to_string()),
+    }
+  }
+
+  fn recording_callback() -> (OnTokensRefreshed, Arc<StdMutex<Vec<OAuthTokens>>>) {
+    let saved = Arc::new(StdMutex::new(Vec::new()));
+    let recorded = saved.clone();
+    let on_refreshed: OnTokensRefreshed = Arc::new(move |tokens: OAuthTokens| {
+      let recorded = recorded.clone();
+      Box::pin(async move {
+        // A slow store must still finish before the token is handed out.
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        recorded.lock().expect("saved").push(tokens);
+      })
+    });
+    (on_refreshed, saved)
+  }
+
+  #[tokio::test]
+  async fn valid_token_is_used_without_refreshing() {
+    let (url, bodies) = token_endpoint("{}").await;
+    let session = OAuthSession::new(
+      tokens(&url, Some("refresh-1"), Some(unix_now() + 3_600)),
+      None,
+    );
+
+    assert_eq!(session.access_token().await.expect("token"), "access-1");
+    assert!(bodies.lock().expect("bodies").is_empty());
+  }
+
+  #[tokio::test]
+  async fn refreshes_inside_the_skew_window_and_saves_before_returning() {
+    let (url, bodies) = token_endpoint(
+      r#"{"access_token":"access-2","refresh_token":"refresh-2","expires_in":3600}"#,
+    )
+    .await;
+    let (on_refreshed, saved) = recording_callback();
+    // Still valid for 30s, which is inside the refresh skew.
+    let session = OAuthSession::new(
+      tokens(&url, Some("refresh-1"), Some(unix_now() + 30)),
+      Some(on_refreshed),
+    );
+
+    assert_eq!(session.access_token().await.expect("token"), "access-2");
+    let saved = saved.lock().expect("saved").clone();
+    assert_eq!(saved.len(), 1);
+    assert_eq!(saved[0].access_token, "access-2");
+    assert_eq!(saved[0].refresh_token.as_deref(), Some("refresh-2"));
+    assert!(saved[0].expires_at.expect("expiry") >= unix_now() + 3_500);
+
+    let body = bodies.lock().expect("bodies").join("&");
+    assert!(body.contains("grant_type=refresh_token"));
+    assert!(body.contains("refresh_token=refresh-1"));
+    assert!(body.contains("client_id=client-1"));
+
+    // The refreshed token is fresh, so the next call does not refresh again.
+    assert_eq!(session.access_token().await.expect("token"), "access-2");
+    assert_eq!(bodies.lock().expect("bodies").len(), 1);
+  }
+
+  #[tokio::test]
+  async fn concurrent_callers_spend_the_refresh_token_once() {
+    let (url, bodies) = token_endpoint(r#"{"access_token":"access-2","expires_in":3600}"#).await;
+    let (on_refreshed, saved) = recording_callback();
+    let session = Arc::new(OAuthSession::new(
+      tokens(&url, Some("refresh-1"), Some(unix_now())),
+      Some(on_refreshed),
+    ));
+
+    let (first, second) = tokio::join!(session.access_token(), session.access_token());
+    assert_eq!(first.expect("first"), "access-2");
+    assert_eq!(second.expect("second"), "access-2");
+    assert_eq!(bodies.lock().expect("bodies").len(), 1);
+    // Servers that do not rotate refresh tokens leave the stored one intact.
+    let saved = saved.lock().expect("saved").clone();
+    assert_eq!(saved.len(), 1);
+    assert_eq!(saved[0].refresh_token.as_deref(), Some("refresh-1"));
+  }
+
+  #[tokio::test]
+  async fn expired_token_without_refresh_token_asks_for_login() {
+    let (url, bodies) = token_endpoint("{}").await;
+    let session = OAuthSession::new(tokens(&url, None, Some(unix_now())), None);
+
+    let err = session.access_token().await.expect_err("no refresh token");
+    assert!(format!("{err:#}").contains("cokra mcp login"));
+    assert!(bodies.lock().expect("bodies").is_empty());
+  }
+}
@@ -31,9 +31,11 @@ use cokra_core::model::oauth_connect::OAuthConnectStart;
 use cokra_core::model::oauth_connect::PendingOAuthConnect;
 use cokra_core::model::provider::ProviderConnectMethod;
 use cokra_core::model::provider_catalog::find_provider_catalog_entry;
+use cokra_protocol::ElicitationRequestEvent;
 use cokra_protocol::Event;
 use cokra_protocol::EventMsg;
 use cokra_protocol::ExecApprovalRequestEvent;
+use cokra_protocol::McpStartupStatus;
 use cokra_protocol::Op;
 use cokra_protocol::RequestUserInputEvent;
 use cokra_protocol::ReviewDecision;
@@ -50,6 +52,9 @@ use crate::bottom_pane::BottomPaneAction;
 use crate::bottom_pane::approval_overlay::ApprovalRequest;
 use crate::bottom_pane::chat_composer::ComposerSubmission;
 use crate::bottom_pane::footer::InlineFooterStatus;
+use crate::bottom_pane::prompt_args::parse_prompt_args;
+use crate::bottom_pane::prompt_args::prompt_args_usage;
+use crate::bottom_pane::prompt_args::prompt_command;
 use crate::chatwidget::ActiveCellTranscriptKey;
 use crate::chatwidget::ChatWidget;
 use crate::chatwidget::ChatWidgetAction;
@@ -61,6 +66,7 @@ use crate::history_cell::PlainHistoryCell;
 use crate::path_utils::get_git_branch;
 use crate::render::renderable::Renderable;
 use crate::slash_command::SlashCommand;
+use crate::slash_command::parse_builtin;
 use crate::team_panel::TeamPanel;
 use crate::team_panel::TeamPanelMode;
 use crate::team_panel::TeamPanelTab;
@@ -90,6 +96,7 @@ pub struct App {
   pending_approval: Option<PendingApproval>,
   pending_approval_request: Option<ExecApprovalRequestEvent>,
   pending_user_input_request: Option<RequestUserInputEvent>,
+  pending_elicitation_request: Option<ElicitationRequestEvent>,
   prompt_queue: VecDeque<PromptRequest>,
   ui_mode: UiMode,
   transcript_cells: Vec<Box<dyn HistoryCell>>,
@@ -125,6 +132,10 @@ pub struct App {
   collab_done_candidate_since: Option<Instant>,
   collab_done_candidate_fingerprint: Option<u64>,
   latest_team_snapshot: Option<TeamSnapshot>,
+  /// Latest reported spend per thread, from `TokenCount` events.
+  thread_costs_usd: HashMap<String, f64>,
+  /// Session-wide spend; `None` until a priced request completes.
+  session_cost_usd: Option<f64>,
   team_panel_mode: TeamPanelMode,
   team_panel_tab: TeamPanelTab,
 }
@@ -139,6 +150,7 @@ struct PendingApproval {
 enum PromptRequest {
   ExecApproval(ExecApprovalRequestEvent),
   UserInput(RequestUserInputEvent),
+  Elicitation(ElicitationRequestEvent),
 }
 
 #[derive(Debug, Clone, Default)]
@@ -312,6 +324,7 @@ impl App {
       pending_approval: None,
       pending_approval_request: None,
       pending_user_input_request: None,
+      pending_elicitation_request: None,
       prompt_queue: VecDeque::new(),
       ui_mode,
       transcript_cells: Vec::new(),
@@ -347,6 +360,8 @@ impl App {
       collab_done_candidate_since: None,
       collab_done_candidate_fingerprint: None,
       latest_team_snapshot: None,
+      thread_costs_usd: HashMap::new(),
+      session_cost_usd: None,
       team_panel_mode: TeamPanelMode::Hidden,
       team_panel_tab: TeamPanelTab::Summary,
     };
@@ -363,6 +378,8 @@ impl App {
     app.refresh_status_line();
     app.chat_widget.set_collab_compact_mode(true);
     app.refresh_live_collab_summary();
+    let mcp_prompts = app.cokra.mcp_prompts();
+    app.chat_widget.bottom_pane.set_mcp_prompts(mcp_prompts);
 
     app
   }
@@ -371,6 +388,7 @@ impl App {
     let mut events = tui.event_stream();
 
     self.insert_startup_welcome(tui)?;
+    self.notify_resumable_team();
 
     // Trigger an initial draw so the UI is visible before any events arrive.
     self.draw(tui)?;
@@ -483,7 +501,7 @@ impl App {
   ) -> Result<()> {
     let provider_id = model_id.split('/').next().unwrap_or("").to_string();
     if provider_id.is_empty() {
-      self.apply_model_selection(model_id).await?;
+      self.apply_model_selection(model_id, effort).await?;
       if let Some(label) = footer_effort_label(effort.as_ref()) {
         self.footer_effort_label = label;
       }
@@ -498,7 +516,7 @@ impl App {
       .has_provider(&provider_id)
       .await;
     if has_provider {
-      self.apply_model_selection(model_id).await?;
+      self.apply_model_selection(model_id, effort).await?;
       if let Some(label) = footer_effort_label(effort.as_ref()) {
         self.footer_effort_label = label;
       }
@@ -516,7 +534,7 @@ impl App {
     .await
     {
       Ok(true) => {
-        self.apply_model_selection(model_id).await?;
+        self.apply_model_selection(model_id, effort).await?;
         if let Some(label) = footer_effort_label(effort.as_ref()) {
           self.footer_effort_label = label;
         }
@@ -946,6 +964,16 @@ impl App {
       AppEvent::OpenConnectProvidersPopup => {
         self.open_connect_providers_popup().await?;
       }
+      AppEvent::RestartMcpServer { name } => {
+        if let Err(err) = self.cokra.restart_mcp_server(&name) {
+          self
+            .chat_widget
+            .add_to_history(PlainHistoryCell::new(vec![Line::from(
+              format!("● {err:#}").red(),
+            )]));
+        }
+        self.refresh_mcp_state();
+      }
       AppEvent::OpenConnectProviderDetail { provider } => {
         self.open_connect_provider_detail(provider);
       }
@@ -1526,12 +1554,17 @@ impl App {
         self.exit_info = Some(self.build_exit_info(ExitReason::UserRequested));
       }
       BottomPaneAction::Submit(submission) => {
-        self.submit_user_input(submission).await?;
+        if let Some(args) = workflow_command_args(&submission.text) {
+          let args = args.to_string();
+          self.run_workflow_command(&args).await;
+        } else if let Some(submission) = self.expand_mcp_prompt(submission).await {
+          self.submit_user_input(submission).await?;
+        }
       }
       BottomPaneAction::Queue(submission) => {
         if self.active_thread_id != self.primary_thread_id {
           self.show_switch_to_main_warning();
-        } else {
+        } else if let Some(submission) = self.expand_mcp_prompt(submission).await {
           self.submit_steer_input(submission).await?;
         }
       }
@@ -1559,6 +1592,7 @@ impl App {
       }
       BottomPaneAction::UserInputDismissed => {
         self.pending_user_input_request = None;
+        self.pending_elicitation_request = None;
         self.maybe_open_next_prompt();
       }
       BottomPaneAction::SlashCommand(cmd) => {
@@ -1774,6 +1808,15 @@ impl App {
         );
         true
       }
+      EventMsg::CollabResumeEnd(ev) => {
+        self.remember_agent_thread(
+          ev.receiver_thread_id.clone(),
+          ev.receiver_nickname.clone(),
+          ev.receiver_role.clone(),
+          ev.error.is_some(),
+        );
+        true
+      }
       EventMsg::CollabWaitingEnd(ev) => {
         for agent in &ev.agent_statuses {
           self.remember_agent_thread(
@@ -2153,9 +2196,20 @@ impl App {
             .and_then(|limit| i64::try_from(limit).ok()),
         );
       }
+      EventMsg::TokenCount(e) => {
+        self
+          .thread_costs_usd
+          .insert(e.thread_id.clone(), e.thread_cost_usd);
+        if e.cost_usd.is_some() || self.session_cost_usd.is_some() {
+          self.session_cost_usd = Some(e.session_cost_usd);
+        }
+      }
       EventMsg::TurnStarted(e) if e.thread_id == self.primary_thread_id => {
         self.primary_active_turn_id = Some(e.turn_id.clone());
       }
+      EventMsg::McpStartupUpdate(_) | EventMsg::McpStartupComplete(_) => {
+        self.refresh_mcp_state();
+      }
       EventMsg::TurnComplete(e) if e.thread_id == self.primary_thread_id => {
         if self.primary_active_turn_id.as_deref() == Some(e.turn_id.as_str()) {
           self.primary_active_turn_id = None;
@@ -2197,6 +2251,9 @@ impl App {
             .push(req.clone());
           self.enqueue_prompt(PromptRequest::UserInput(req.clone()));
         }
+        EventMsg::ElicitationRequest(req) => {
+          self.enqueue_prompt(PromptRequest::Elicitation(req.clone()));
+        }
         _ => {}
       }
 
@@ -2231,6 +2288,9 @@ impl App {
         ChatWidgetAction::ShowRequestUserInput(req) => {
           self.enqueue_prompt(PromptRequest::UserInput(req));
         }
+        ChatWidgetAction::OpenElicitation(req) => {
+          self.enqueue_prompt(PromptRequest::Elicitation(req));
+        }
       }
     }
 
@@ -2264,6 +2324,13 @@ impl App {
       {
         self.pending_user_input_request = None;
       }
+      if self
+        .pending_elicitation_request
+        .as_ref()
+        .is_some_and(|req| req.thread_id == owner_thread_id)
+      {
+        self.pending_elicitation_request = None;
+      }
       self.remove_queued_prompts_for_thread(&owner_thread_id);
       self.maybe_open_next_prompt();
       self.sync_bottom_pane_context();
@@ -2294,7 +2361,11 @@ impl App {
         self.app_event_tx.send(AppEvent::NewSession);
       }
       SlashCommand::Resume => {
-        self.app_event_tx.send(AppEvent::OpenResumePicker);
+        if self.cokra.resumable_teammates().is_empty() {
+          self.app_event_tx.send(AppEvent::OpenResumePicker);
+        } else {
+          self.resume_team().await;
+        }
       }
       SlashCommand::Fork => {
         self.app_event_tx.send(AppEvent::ForkCurrentSession);
@@ -2326,6 +2397,34 @@ impl App {
             .count()
         });
 
+        let agent_costs = self
+          .latest_team_snapshot
+          .as_ref()
+          .map(|snapshot| {
+            snapshot
+              .members
+              .iter()
+              .map(|member| {
+                let label = if member.thread_id == snapshot.root_thread_id {
+                  "@main".to_string()
+                } else {
+                  member
+                    .nickname
+                    .as_ref()
+                    .map(|nickname| format!("@{nickname}"))
+                    .unwrap_or_else(|| member.thread_id.clone())
+                };
+                let cost_usd = self
+                  .thread_costs_usd
+                  .get(&member.thread_id)
+                  .copied()
+                  .unwrap_or(member.usage.cost_usd);
+                (label, cost_usd)
+              })
+              .collect()
+          })
+          .unwrap_or_default();
+
         let data = crate::status::StatusCardData {
           model_name: model,
           directory: cwd,
@@ -2334,6 +2433,8 @@ impl App {
           input_tokens: usage.input_tokens,
           output_tokens: usage.output_tokens,
           total_tokens: usage.total_tokens,
+          session_cost_usd: self.session_cost_usd,
+          agent_costs,
           collaboration_mode: collab_mode,
           agents_count,
         };
@@ -2406,6 +2507,12 @@ impl App {
       SlashCommand::Clean => {
         self.cleanup_team().await?;
       }
+      SlashCommand::Workflow => {
+        self.list_workflow_templates().await;
+      }
+      SlashCommand::Mcp => {
+        self.open_mcp_panel();
+      }
       // All other commands: show not-yet-implemented message.
       _ => {
         self
@@ -2803,7 +2910,7 @@ impl App {
         let actions: Vec<SelectionAction> = vec![Box::new(move |tx| {
           tx.send(AppEvent::ApplyModelSelection {
             model_id: model_for_action.clone(),
-            effort: effort.clone(),
+            effort,
           });
         })];
         items.push(SelectionItem {
@@ -2862,8 +2969,13 @@ impl App {
   //   2. Updates ModelClient default provider (opencode-style persistence).
   //   3. Updates the local model_name for immediate UI feedback.
   //   4. Shows a confirmation message in chat history.
-  async fn apply_model_selection(&mut self, model_id: String) -> Result<()> {
-    // 1) Send OverrideTurnContext to switch the active model.
+  async fn apply_model_selection(
+    &mut self,
+    model_id: String,
+    effort: Option<cokra_protocol::ReasoningEffortConfig>,
+  ) -> Result<()> {
+    // 1) Send OverrideTurnContext to switch the active model. The effort
+    //    picked alongside it ("Default" is None) replaces the previous one.
     let _ = self
       .cokra
       .submit(Op::OverrideTurnContext {
@@ -2871,6 +2983,8 @@ impl App {
         approval_policy: None,
         sandbox_policy: None,
         model: Some(model_id.clone()),
+        effort: Some(effort),
+        summary: None,
         collaboration_mode: None,
         personality: None,
       })
@@ -3774,6 +3888,78 @@ impl App {
     Ok(())
   }
 
+  fn open_mcp_panel(&mut self) {
+    if self.cokra.mcp_servers().is_empty() {
+      self
+        .chat_widget
+        .add_to_history(PlainHistoryCell::new(vec![Line::from(
+          "● No MCP servers are configured.".dim(),
+        )]));
+      return;
+    }
+    let params = self.mcp_panel_params();
+    self.chat_widget.bottom_pane.show_selection_view(params);
+  }
+
+  /// Pick up prompts from servers that (re)connected and redraw the `/mcp`
+  /// panel if it is open.
+  fn refresh_mcp_state(&mut self) {
+    let prompts = self.cokra.mcp_prompts();
+    self.chat_widget.bottom_pane.set_mcp_prompts(prompts);
+    let params = self.mcp_panel_params();
+    self
+      .chat_widget
+      .bottom_pane
+      .refresh_selection_view(MCP_PANEL_VIEW_ID, params);
+  }
+
+  fn mcp_panel_params(&self) -> crate::bottom_pane::list_selection_view::SelectionViewParams {
+    use crate::bottom_pane::list_selection_view::SelectionItem;
+    use crate::bottom_pane::list_selection_view::SelectionViewParams;
+    use crate::bottom_pane::popup_consts::standard_popup_hint_line;
+
+    let items = self
+      .cokra
+      .mcp_servers()
+      .into_iter()
+      .map(|server| {
+        let mut description = match &server.status {
+          McpStartupStatus::Starting => "connecting".to_string(),
+          McpStartupStatus::Ready => format!(
+            "ready · {}, {}, {}",
+            count_label(server.tools, "tool"),
+            count_label(server.resources, "resource"),
+            count_label(server.prompts, "prompt")
+          ),
+          McpStartupStatus::Failed { error } => format!("failed: {error}"),
+          McpStartupStatus::Cancelled => "cancelled".to_string(),
+        };
+        if server.required {
+          description.push_str(" · required");
+        }
+        let name = server.name.clone();
+        SelectionItem {
+          name: server.name,
+          description: Some(description),
+          actions: vec![Box::new(move |tx| {
+            tx.send(AppEvent::RestartMcpServer { name: name.clone() });
+          })],
+          dismiss_on_select: false,
+          ..Default::default()
+        }
+      })
+      .collect();
+
+    SelectionViewParams {
+      view_id: Some(MCP_PANEL_VIEW_ID),
+      title: Some("MCP servers".to_string()),
+      subtitle: Some("Press enter on a server to restart it.".to_string()),
+      footer_hint: Some(standard_popup_hint_line()),
+      items,
+      ..Default::default()
+    }
+  }
+
   fn open_agent_picker(&mut self) {
     use crate::bottom_pane::list_selection_view::SelectionAction;
     use crate::bottom_pane::list_selection_view::SelectionItem;
@@ -3890,6 +4076,130 @@ impl App {
       });
   }
 
+  fn notify_resumable_team(&mut self) {
+    let teammates = self.cokra.resumable_teammates();
+    if !teammates.is_empty() {
+      self
+        .chat_widget
+        .add_to_history(crate::multi_agents::resumable_team(&teammates));
+    }
+  }
+
+  async fn resume_team(&mut self) {
+    // Each teammate reports through CollabResumeBegin/CollabResumeEnd events.
+    let _ = self.cokra.resume_team().await;
+    self.refresh_team_snapshot_cache();
+    self.sync_bottom_pane_context();
+  }
+
+  /// Handles `/workflow [list | run <name> [goal]]` typed with arguments.
+  async fn run_workflow_command(&mut self, args: &str) {
+    if self.task_running {
+      self
+        .chat_widget
+        .add_to_history(PlainHistoryCell::new(vec![Line::from(
+          "'/workflow' is disabled while a task is in progress.",
+        )]));
+      return;
+    }
+    let (action, rest) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+    match action {
+      "" | "list" => self.list_workflow_templates().await,
+      "run" if !rest.trim().is_empty() => {
+        let rest = rest.trim();
+        let (name, goal) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let goal = Some(goal.trim()).filter(|goal| !goal.is_empty());
+        let line = match self.cokra.run_workflow(name, goal).await {
+          Ok(run) => Line::from(format!(
+            "● Started workflow {} ({} steps). Teammates pick up their tasks as they become ready.",
+            run.title,
+            run.steps.len()
+          )),
+          Err(err) => Line::from(format!("● {err:#}").red()),
+        };
+        self
+          .chat_widget
+          .add_to_history(PlainHistoryCell::new(vec![line]));
+        self.refresh_team_snapshot_cache();
+        self.sync_bottom_pane_context();
+      }
+      _ => {
+        self
+          .chat_widget
+          .add_to_history(PlainHistoryCell::new(vec![Line::from(
+            "● Usage: /workflow run <name> [goal]".dim(),
+          )]));
+      }
+    }
+  }
+
+  /// Replace a submitted `/mcp__<server>__<prompt> args` line with the
+  /// prompt's rendered messages. Returns `None` when the prompt could not be
+  /// rendered; the error is shown in history instead.
+  async fn expand_mcp_prompt(
+    &mut self,
+    submission: ComposerSubmission,
+  ) -> Option<ComposerSubmission> {
+    let prompts = self.cokra.mcp_prompts();
+    let Some((prompt, args)) = prompt_command(&submission.text, &prompts) else {
+      return Some(submission);
+    };
+    let rendered = match parse_prompt_args(args, &prompt.arguments) {
+      Ok(values) => self
+        .cokra
+        .render_mcp_prompt(&prompt.id, values)
+        .await
+        .map_err(|err| format!("{err:#}")),
+      Err(err) => Err(format!(
+        "{err}. Usage: /{} {}",
+        prompt.id,
+        prompt_args_usage(&prompt.arguments)
+      )),
+    };
+    match rendered {
+      Ok(text) => Some(ComposerSubmission {
+        text,
+        text_elements: Vec::new(),
+        ..submission
+      }),
+      Err(err) => {
+        self
+          .chat_widget
+          .add_to_history(PlainHistoryCell::new(vec![Line::from(
+            format!("● /{}: {err}", prompt.id).red(),
+          )]));
+        None
+      }
+    }
+  }
+
+  async fn list_workflow_templates(&mut self) {
+    let catalog = self.cokra.workflow_templates().await;
+    let mut lines = Vec::new();
+    if catalog.templates.is_empty() {
+      lines.push(Line::from(
+        "● No workflow templates found in .cokra/workflows/.".dim(),
+      ));
+    } else {
+      lines.push(Line::from(
+        "● Workflows (run with /workflow run <name> [goal]):",
+      ));
+      for template in &catalog.templates {
+        let mut line = format!("  - {}", template.name);
+        if !template.description.is_empty() {
+          line.push_str(&format!(": {}", template.description));
+        }
+        lines.push(Line::from(line));
+      }
+    }
+    for warning in &catalog.warnings {
+      lines.push(Line::from(format!("  {warning}")).dim());
+    }
+    self
+      .chat_widget
+      .add_to_history(PlainHistoryCell::new(lines));
+  }
+
   async fn cleanup_team(&mut self) -> Result<()> {
     let _ = self.cokra.cleanup_team_runtime().await;
     self.background_pending_threads.clear();
@@ -3899,13 +4209,16 @@ impl App {
     self.pending_approval = None;
     self.pending_approval_request = None;
     self.pending_user_input_request = None;
+    self.pending_elicitation_request = None;
     self.refresh_team_snapshot_cache();
     self.sync_bottom_pane_context();
     Ok(())
   }
 
   fn has_active_prompt(&self) -> bool {
-    self.pending_approval.is_some() || self.pending_user_input_request.is_some()
+    self.pending_approval.is_some()
+      || self.pending_user_input_request.is_some()
+      || self.pending_elicitation_request.is_some()
   }
 
   fn enqueue_prompt(&mut self, prompt: PromptRequest) {
@@ -3925,6 +4238,7 @@ impl App {
     match prompt {
       PromptRequest::ExecApproval(req) => self.open_background_approval(req),
       PromptRequest::UserInput(req) => self.open_background_user_input(req),
+      PromptRequest::Elicitation(req) => self.open_elicitation(req),
     }
   }
 
@@ -3950,6 +4264,11 @@ impl App {
       return;
     }
 
+    if let Some(req) = self.pending_elicitation_request.clone() {
+      self.chat_widget.bottom_pane.push_elicitation_request(req);
+      return;
+    }
+
     self.maybe_open_next_prompt();
   }
 
@@ -3981,13 +4300,13 @@ impl App {
   fn remove_queued_exec_approval(&mut self, id: &str) {
     self.prompt_queue.retain(|prompt| match prompt {
       PromptRequest::ExecApproval(req) => req.id != id,
-      PromptRequest::UserInput(_) => true,
+      PromptRequest::UserInput(_) | PromptRequest::Elicitation(_) => true,
     });
   }
 
   fn remove_queued_user_input(&mut self, turn_id: &str) {
     self.prompt_queue.retain(|prompt| match prompt {
-      PromptRequest::ExecApproval(_) => true,
+      PromptRequest::ExecApproval(_) | PromptRequest::Elicitation(_) => true,
       PromptRequest::UserInput(req) => req.turn_id != turn_id,
     });
   }
@@ -3996,6 +4315,7 @@ impl App {
     self.prompt_queue.retain(|prompt| match prompt {
       PromptRequest::ExecApproval(req) => req.thread_id != thread_id,
       PromptRequest::UserInput(req) => req.thread_id != thread_id,
+      PromptRequest::Elicitation(req) => req.thread_id != thread_id,
     });
   }
 
@@ -4047,6 +4367,19 @@ impl App {
     self.sync_bottom_pane_context();
   }
 
+  fn open_elicitation(&mut self, req: ElicitationRequestEvent) {
+    if self.has_active_prompt() {
+      self
+        .prompt_queue
+        .push_front(PromptRequest::Elicitation(req));
+      return;
+    }
+
+    self.pending_elicitation_request = Some(req.clone());
+    self.chat_widget.bottom_pane.push_elicitation_request(req);
+    self.sync_bottom_pane_context();
+  }
+
   fn open_background_approvals_picker(&mut self) {
     use crate::bottom_pane::list_selection_view::SelectionAction;
     use crate::bottom_pane::list_selection_view::SelectionItem;
@@ -4115,6 +4448,24 @@ impl App {
   }
 }
 
+const MCP_PANEL_VIEW_ID: &str = "mcp-servers";
+
+/// `1 tool`, `3 tools`.
+fn count_label(count: usize, noun: &str) -> String {
+  if count == 1 {
+    format!("1 {noun}")
+  } else {
+    format!("{count} {noun}s")
+  }
+}
+
+/// The arguments of a submitted `/workflow ...` line, if it is one.
+fn workflow_command_args(text: &str) -> Option<&str> {
+  let rest = text.trim().strip_prefix('/')?;
+  let (name, args) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+  (parse_builtin(name) == Some(SlashCommand::Workflow)).then_some(args.trim())
+}
+
 fn event_thread_targets(event: &EventMsg, primary_thread_id: &str) -> Vec<String> {
   let mut targets = match event {
     EventMsg::Error(e) => vec![e.thread_id.clone()],
@@ -4128,9 +4479,11 @@ fn event_thread_targets(event: &EventMsg, primary_thread_id: &str) -> Vec<String
     EventMsg::ThreadNameUpdated(e) => vec![e.thread_id.clone()],
     EventMsg::ExecCommandBegin(e) => vec![e.thread_id.clone()],
     EventMsg::ExecCommandOutputDelta(e) => vec![e.thread_id.clone()],
+    EventMsg::McpToolCallProgress(e) => vec![e.thread_id.clone()],
     EventMsg::ExecCommandEnd(e) => vec![e.thread_id.clone()],
     EventMsg::ExecApprovalRequest(e) => vec![e.thread_id.clone()],
     EventMsg::RequestUserInput(e) => vec![e.thread_id.clone()],
+    EventMsg::ElicitationRequest(e) if !e.thread_id.is_empty() => vec![e.thread_id.clone()],
     EventMsg::StreamError(e) => vec![e.thread_id.clone()],
     EventMsg::TurnComplete(e) => vec![e.thread_id.clone()],
     EventMsg::TurnAborted(e) => vec![e.thread_id.clone()],
@@ -4184,6 +4537,7 @@ fn transcript_owns_event(event: &EventMsg) -> bool {
       | EventMsg::CollabWaitingBegin(_)
       | EventMsg::CollabWaitingEnd(_)
       | EventMsg::CollabCloseEnd(_)
+      | EventMsg::CollabResumeEnd(_)
       | EventMsg::CollabMailboxDelivered(_)
       | EventMsg::CollabMessagesRead(_)
       | EventMsg::CollabMessagePosted(_)
@@ -4376,6 +4730,7 @@ mod tests {
         tokens_after_est: 50,
         reserve_tokens: 10,
         keep_recent_tokens: 10,
+        invalidated_cached_tokens: 0,
       },
     )));
   }
@@ -4398,6 +4753,17 @@ mod tests {
     );
   }
 
+  #[test]
+  fn workflow_command_args_only_match_the_workflow_command() {
+    assert_eq!(
+      workflow_command_args("/workflow run ship add login"),
+      Some("run ship add login")
+    );
+    assert_eq!(workflow_command_args("/workflow"), Some(""));
+    assert_eq!(workflow_command_args("/workflows run ship"), None);
+    assert_eq!(workflow_command_args("run the workflow"), None);
+  }
+
   #[test]
   fn status_line_agent_tabs_mark_active_member_and_selector_focus() {
     let threads = vec![
@@ -98,6 +98,9 @@ pub(crate) enum AppEvent {
   DisconnectProvider {
     provider_id: String,
   },
+  RestartMcpServer {
+    name: String,
+  },
   OpenReasoningPopup {
     model_id: String,
   },
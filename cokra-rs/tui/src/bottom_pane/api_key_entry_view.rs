@@ -60,7 +60,7 @@ impl ApiKeyEntryView {
       provider_id: self.provider_id.clone(),
       api_key,
       model_id: self.model_id.clone(),
-      effort: self.effort.clone(),
+      effort: self.effort,
     });
     self.complete = true;
   }
@@ -15,6 +15,10 @@ use crate::tui::InlineViewportSizing;
 pub(crate) trait BottomPaneView: Renderable {
   /// Downcast support for extracting concrete view state after popping.
   fn as_any_mut(&mut self) -> &mut dyn Any;
+  /// Stable id for views the app may refresh in place.
+  fn view_id(&self) -> Option<&'static str> {
+    None
+  }
   /// Controls how inline-mode viewport sizing behaves while this view is active.
   ///
   /// Dialog-style views should preserve visible history so resize redraws do not
@@ -4,6 +4,7 @@ use std::path::PathBuf;
 use std::time::Duration;
 use std::time::Instant;
 
+use cokra_protocol::CustomPrompt;
 use cokra_protocol::user_input::TextElement;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
@@ -24,6 +25,7 @@ use ratatui::widgets::Widget;
 use super::MentionBinding;
 use super::chat_composer_history::ChatComposerHistory;
 use super::chat_composer_history::HistoryEntry;
+use super::command_popup::CommandKind;
 use super::command_popup::CommandPopup;
 use super::footer;
 use super::footer::CollaborationModeIndicator;
@@ -75,6 +77,7 @@ pub(crate) struct ChatComposer {
   footer_timer: Option<Instant>,
   paste_burst: PasteBurst,
   command_popup: Option<CommandPopup>,
+  mcp_prompts: Vec<CustomPrompt>,
   history: ChatComposerHistory,
   input_enabled: bool,
   steer_enabled: bool,
@@ -101,6 +104,7 @@ impl ChatComposer {
       footer_timer: None,
       paste_burst: PasteBurst::default(),
       command_popup: None,
+      mcp_prompts: Vec::new(),
       history: ChatComposerHistory::new(),
       input_enabled: true,
       steer_enabled: false,
@@ -122,6 +126,11 @@ impl ChatComposer {
     self.is_task_running = running;
   }
 
+  /// MCP prompts offered in the slash popup next to the built-ins.
+  pub(crate) fn set_mcp_prompts(&mut self, prompts: Vec<CustomPrompt>) {
+    self.mcp_prompts = prompts;
+  }
+
   pub(crate) fn set_steer_enabled(&mut self, enabled: bool) {
     self.steer_enabled = enabled;
   }
@@ -238,14 +247,14 @@ impl ChatComposer {
             .unwrap_or("")
             .to_string();
           popup.on_composer_text_change(first_line.clone());
-          if let Some(cmd) = popup.selected_command() {
+          if let Some(kind) = popup.selected_kind() {
             let starts_with_cmd = first_line
               .trim_start()
-              .starts_with(&format!("/{}", cmd.command()));
+              .starts_with(&format!("/{}", kind.name()));
             if !starts_with_cmd {
               self
                 .textarea
-                .set_text_clearing_elements(&format!("/{} ", cmd.command()));
+                .set_text_clearing_elements(&format!("/{} ", kind.name()));
             }
             if !self.textarea.text().is_empty() {
               self.textarea.set_cursor(self.textarea.text().len());
@@ -260,11 +269,37 @@ impl ChatComposer {
         ..
       } => {
         // Enter = execute the selected command.
-        if let Some(popup) = &self.command_popup
-          && let Some(cmd) = popup.selected_command()
+        match self
+          .command_popup
+          .as_ref()
+          .and_then(CommandPopup::selected_kind)
         {
-          self.command_popup = None;
-          return self.apply_slash_command(cmd);
+          Some(CommandKind::Builtin(cmd)) => {
+            self.command_popup = None;
+            return self.apply_slash_command(cmd);
+          }
+          Some(CommandKind::Prompt {
+            name,
+            takes_arguments,
+          }) => {
+            self.command_popup = None;
+            let typed = self.textarea.text().trim().to_string();
+            let has_arguments = typed
+              .strip_prefix(&format!("/{name}"))
+              .is_some_and(|rest| rest.starts_with(char::is_whitespace));
+            if !has_arguments {
+              self
+                .textarea
+                .set_text_clearing_elements(&format!("/{name} "));
+              self.textarea.set_cursor(self.textarea.text().len());
+              // Leave room to type the arguments; the app runs the prompt
+              // when the line is submitted.
+              if takes_arguments {
+                return ComposerAction::None;
+              }
+            }
+          }
+          None => {}
         }
         // Fallback to default submit handling if no command selected.
         self.command_popup = None;
@@ -657,7 +692,7 @@ impl ChatComposer {
       }
       None => {
         if is_editing_slash_command {
-          let mut popup = CommandPopup::new(self.collaboration_modes_enabled);
+          let mut popup = CommandPopup::new(self.collaboration_modes_enabled, &self.mcp_prompts);
           popup.on_composer_text_change(first_line.to_string());
           self.command_popup = Some(popup);
         }
@@ -690,6 +725,10 @@ impl ChatComposer {
     }
 
     slash_commands::has_builtin_prefix(name, self.collaboration_modes_enabled, true, true, false)
+      || self
+        .mcp_prompts
+        .iter()
+        .any(|prompt| prompt.id.starts_with(name))
   }
 
   fn apply_slash_command(&mut self, command: SlashCommand) -> ComposerAction {
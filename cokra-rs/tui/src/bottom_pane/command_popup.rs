@@ -1,7 +1,9 @@
+use cokra_protocol::CustomPrompt;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 
 use super::popup_consts::MAX_COMMAND_POPUP_ITEMS;
+use super::prompt_args::prompt_args_usage;
 use super::scroll_state::ScrollState;
 use super::selection_popup_common::GenericDisplayRow;
 use super::selection_popup_common::render_rows_single_line;
@@ -13,9 +15,29 @@ use crate::slash_command::SlashCommand;
 // Hide alias commands in the default popup list so each unique action appears once.
 const ALIAS_COMMANDS: &[SlashCommand] = &[SlashCommand::Quit, SlashCommand::Approvals];
 
+/// What a popup row runs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum CommandKind {
+  Builtin(SlashCommand),
+  /// An MCP prompt; `takes_arguments` decides whether Enter runs it at once.
+  Prompt {
+    name: String,
+    takes_arguments: bool,
+  },
+}
+
+impl CommandKind {
+  pub(crate) fn name(&self) -> &str {
+    match self {
+      Self::Builtin(command) => command.command(),
+      Self::Prompt { name, .. } => name,
+    }
+  }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) struct CommandItem {
-  pub command: SlashCommand,
+  pub kind: CommandKind,
   pub display_name: String,
   pub description: String,
 }
@@ -28,16 +50,36 @@ pub(crate) struct CommandPopup {
 }
 
 impl CommandPopup {
-  pub(crate) fn new(collaboration_modes_enabled: bool) -> Self {
-    let items = slash_commands::builtins_for_input(collaboration_modes_enabled, true, true, false)
-      .into_iter()
-      .filter(|(_, cmd)| !cmd.command().starts_with("debug"))
-      .map(|(_, command)| CommandItem {
-        command,
-        display_name: format!("/{}", command.command()),
-        description: command.description().to_string(),
-      })
-      .collect();
+  pub(crate) fn new(collaboration_modes_enabled: bool, prompts: &[CustomPrompt]) -> Self {
+    let builtins =
+      slash_commands::builtins_for_input(collaboration_modes_enabled, true, true, false)
+        .into_iter()
+        .filter(|(_, cmd)| !cmd.command().starts_with("debug"))
+        .map(|(_, command)| CommandItem {
+          kind: CommandKind::Builtin(command),
+          display_name: format!("/{}", command.command()),
+          description: command.description().to_string(),
+        });
+    let prompts = prompts.iter().map(|prompt| {
+      let usage = prompt_args_usage(&prompt.arguments);
+      let description = prompt
+        .description
+        .clone()
+        .unwrap_or_else(|| format!("MCP prompt {}", prompt.name));
+      CommandItem {
+        kind: CommandKind::Prompt {
+          name: prompt.id.clone(),
+          takes_arguments: !prompt.arguments.is_empty(),
+        },
+        display_name: format!("/{}", prompt.id),
+        description: if usage.is_empty() {
+          description
+        } else {
+          format!("{description}  {usage}")
+        },
+      }
+    });
+    let items = builtins.chain(prompts).collect();
 
     Self {
       command_filter: String::new(),
@@ -90,8 +132,8 @@ impl CommandPopup {
     self.visible_row_count()
   }
 
-  /// Return currently selected command, if any.
-  pub(crate) fn selected_command(&self) -> Option<SlashCommand> {
+  /// Return the currently selected row, if any.
+  pub(crate) fn selected_kind(&self) -> Option<CommandKind> {
     let filtered = self.filtered_commands();
     if filtered.is_empty() {
       return None;
@@ -101,7 +143,15 @@ impl CommandPopup {
       .selected_idx
       .unwrap_or(0)
       .min(filtered.len() - 1);
-    filtered.get(idx).copied()
+    filtered.get(idx).cloned()
+  }
+
+  /// Return the currently selected built-in command, if any.
+  pub(crate) fn selected_command(&self) -> Option<SlashCommand> {
+    match self.selected_kind()? {
+      CommandKind::Builtin(command) => Some(command),
+      CommandKind::Prompt { .. } => None,
+    }
   }
 
   fn filtered(&self) -> Vec<(usize, Option<Vec<usize>>)> {
@@ -111,7 +161,9 @@ impl CommandPopup {
         .items
         .iter()
         .enumerate()
-        .filter(|(_, item)| !ALIAS_COMMANDS.contains(&item.command))
+        .filter(|(_, item)| {
+          !matches!(item.kind, CommandKind::Builtin(command) if ALIAS_COMMANDS.contains(&command))
+        })
         .map(|(idx, _)| (idx, None))
         .collect();
     }
@@ -123,7 +175,7 @@ impl CommandPopup {
     let mut prefix = Vec::new();
 
     for (idx, item) in self.items.iter().enumerate() {
-      let name = item.command.command();
+      let name = item.kind.name();
       let lower = name.to_lowercase();
       if lower == filter_lower {
         exact.push((idx, Some((0..filter_chars).collect())));
@@ -136,12 +188,12 @@ impl CommandPopup {
     exact
   }
 
-  fn filtered_commands(&self) -> Vec<SlashCommand> {
+  fn filtered_commands(&self) -> Vec<CommandKind> {
     self
       .filtered()
       .into_iter()
       .filter_map(|(idx, _)| self.items.get(idx))
-      .map(|item| item.command)
+      .map(|item| item.kind.clone())
       .collect()
   }
 
@@ -187,22 +239,51 @@ mod tests {
 
   #[test]
   fn exact_match_ranks_first() {
-    let mut popup = CommandPopup::new(true);
+    let mut popup = CommandPopup::new(true, &[]);
     popup.on_composer_text_change("/model".to_string());
     assert_eq!(popup.selected_command(), Some(SlashCommand::Model));
   }
 
   #[test]
   fn alias_commands_hidden_when_filter_empty() {
-    let popup = CommandPopup::new(true);
+    let popup = CommandPopup::new(true, &[]);
     let filtered = popup.filtered_commands();
-    assert!(!filtered.contains(&SlashCommand::Quit));
-    assert!(!filtered.contains(&SlashCommand::Approvals));
+    assert!(!filtered.contains(&CommandKind::Builtin(SlashCommand::Quit)));
+    assert!(!filtered.contains(&CommandKind::Builtin(SlashCommand::Approvals)));
   }
 
   #[test]
   fn default_popup_reserves_eight_rows_for_commands() {
-    let popup = CommandPopup::new(true);
+    let popup = CommandPopup::new(true, &[]);
     assert_eq!(8, popup.calculate_required_height(120));
   }
+
+  #[test]
+  fn mcp_prompts_are_listed_after_builtins() {
+    let prompts = vec![CustomPrompt {
+      id: "mcp__docs__summarize".to_string(),
+      name: "summarize".to_string(),
+      description: Some("Summarize a document".to_string()),
+      server_name: Some("docs".to_string()),
+      arguments: vec![cokra_protocol::CustomPromptArgument {
+        name: "path".to_string(),
+        description: None,
+        required: true,
+      }],
+    }];
+    let mut popup = CommandPopup::new(true, &prompts);
+    popup.on_composer_text_change("/mcp__docs".to_string());
+    assert_eq!(
+      popup.selected_kind(),
+      Some(CommandKind::Prompt {
+        name: "mcp__docs__summarize".to_string(),
+        takes_arguments: true,
+      })
+    );
+    assert_eq!(popup.selected_command(), None);
+    assert_eq!(
+      popup.render_rows_data()[0].description.as_deref(),
+      Some("Summarize a document  path=<PATH>")
+    );
+  }
 }
@@ -0,0 +1,351 @@
+//! Map MCP elicitation forms onto the `request_user_input` question flow.
+//!
+//! Elicitation schemas are flat objects of primitive properties, so each
+//! property becomes one question: enums and booleans are option lists, the
+//! rest are free text that is converted back to the declared type on submit.
+
+use cokra_protocol::RequestUserInputQuestion;
+use cokra_protocol::RequestUserInputQuestionOption;
+use cokra_protocol::user_input::RequestUserInputResponse;
+use serde_json::Map;
+use serde_json::Value;
+
+const YES_LABEL: &str = "Yes";
+const NO_LABEL: &str = "No";
+const NOTE_PREFIX: &str = "user_note: ";
+
+#[derive(Debug, Clone, PartialEq)]
+enum FieldKind {
+  Text,
+  Number {
+    integer: bool,
+  },
+  Boolean,
+  /// `(value sent back, label shown)` per allowed value.
+  Choice(Vec<(Value, String)>),
+}
+
+#[derive(Debug, Clone)]
+struct FormField {
+  name: String,
+  label: String,
+  description: Option<String>,
+  required: bool,
+  kind: FieldKind,
+}
+
+/// Why a submitted form was not sent; `question` is the index to return to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FormError {
+  pub(crate) question: usize,
+  pub(crate) message: String,
+}
+
+fn form_fields(schema: &Value) -> Vec<FormField> {
+  let required = schema
+    .get("required")
+    .and_then(Value::as_array)
+    .map(|names| names.iter().filter_map(Value::as_str).collect::<Vec<_>>())
+    .unwrap_or_default();
+  let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+    return Vec::new();
+  };
+  properties
+    .iter()
+    .map(|(name, property)| {
+      let title = property.get("title").and_then(Value::as_str);
+      FormField {
+        name: name.clone(),
+        label: title.unwrap_or(name).to_string(),
+        description: property
+          .get("description")
+          .and_then(Value::as_str)
+          .map(ToString::to_string),
+        required: required.contains(&name.as_str()),
+        kind: field_kind(property),
+      }
+    })
+    .collect()
+}
+
+fn field_kind(property: &Value) -> FieldKind {
+  if let Some(values) = property.get("enum").and_then(Value::as_array) {
+    let names = property.get("enumNames").and_then(Value::as_array);
+    return FieldKind::Choice(
+      values
+        .iter()
+        .enumerate()
+        .map(|(idx, value)| {
+          let label = names
+            .and_then(|names| names.get(idx))
+            .and_then(Value::as_str)
+            .map(ToString::to_string)
+            .unwrap_or_else(|| display_value(value));
+          (value.clone(), label)
+        })
+        .collect(),
+    );
+  }
+  if let Some(variants) = property.get("oneOf").and_then(Value::as_array) {
+    return FieldKind::Choice(
+      variants
+        .iter()
+        .filter_map(|variant| {
+          let value = variant.get("const")?.clone();
+          let label = variant
+            .get("title")
+            .and_then(Value::as_str)
+            .map(ToString::to_string)
+            .unwrap_or_else(|| display_value(&value));
+          Some((value, label))
+        })
+        .collect(),
+    );
+  }
+  match property.get("type").and_then(Value::as_str) {
+    Some("boolean") => FieldKind::Boolean,
+    Some("integer") => FieldKind::Number { integer: true },
+    Some("number") => FieldKind::Number { integer: false },
+    _ => FieldKind::Text,
+  }
+}
+
+fn display_value(value: &Value) -> String {
+  match value {
+    Value::String(text) => text.clone(),
+    other => other.to_string(),
+  }
+}
+
+fn option(label: &str) -> RequestUserInputQuestionOption {
+  RequestUserInputQuestionOption {
+    label: label.to_string(),
+    description: String::new(),
+  }
+}
+
+/// One question per schema property, each prefixed with the server's message.
+///
+/// A schema without properties is a plain confirmation and becomes a single
+/// question whose answer is ignored.
+pub(crate) fn questions_from_schema(
+  message: &str,
+  schema: &Value,
+) -> Vec<RequestUserInputQuestion> {
+  let fields = form_fields(schema);
+  if fields.is_empty() {
+    return vec![RequestUserInputQuestion {
+      id: String::new(),
+      header: "Confirm".to_string(),
+      question: message.to_string(),
+      is_other: false,
+      is_secret: false,
+      options: None,
+    }];
+  }
+  fields
+    .into_iter()
+    .map(|field| {
+      let mut prompt = field
+        .description
+        .clone()
+        .unwrap_or_else(|| field.label.clone());
+      if field.required {
+        prompt.push_str(" (required)");
+      }
+      let options = match &field.kind {
+        FieldKind::Boolean => Some(vec![option(YES_LABEL), option(NO_LABEL)]),
+        FieldKind::Choice(choices) => {
+          Some(choices.iter().map(|(_, label)| option(label)).collect())
+        }
+        FieldKind::Text | FieldKind::Number { .. } => None,
+      };
+      RequestUserInputQuestion {
+        id: field.name,
+        header: field.label,
+        question: format!("{message}\n\n{prompt}"),
+        is_other: false,
+        is_secret: false,
+        options,
+      }
+    })
+    .collect()
+}
+
+/// Build the typed form content from the answers the view collected.
+pub(crate) fn content_from_answers(
+  schema: &Value,
+  response: &RequestUserInputResponse,
+) -> Result<Value, FormError> {
+  let mut content = Map::new();
+  for (question, field) in form_fields(schema).into_iter().enumerate() {
+    let entries = response
+      .answers
+      .get(&field.name)
+      .map(|answer| answer.answers.as_slice())
+      .unwrap_or_default();
+    let selected = entries
+      .iter()
+      .find(|entry| !entry.starts_with(NOTE_PREFIX))
+      .map(|entry| entry.trim())
+      .filter(|entry| !entry.is_empty());
+    let error = |message: String| FormError { question, message };
+
+    let value = match (&field.kind, selected) {
+      (_, None) if field.required => {
+        return Err(error(format!("{} is required", field.label)));
+      }
+      (_, None) => continue,
+      (FieldKind::Boolean, Some(label)) => Value::Bool(label == YES_LABEL),
+      (FieldKind::Choice(choices), Some(label)) => choices
+        .iter()
+        .find(|(_, choice)| choice == label)
+        .map(|(value, _)| value.clone())
+        .ok_or_else(|| error(format!("{} has no option `{label}`", field.label)))?,
+      (FieldKind::Number { integer: true }, Some(text)) => text
+        .parse::<i64>()
+        .map(Value::from)
+        .map_err(|_| error(format!("{} must be a whole number", field.label)))?,
+      (FieldKind::Number { integer: false }, Some(text)) => text
+        .parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+        .map(Value::Number)
+        .ok_or_else(|| error(format!("{} must be a number", field.label)))?,
+      (FieldKind::Text, Some(text)) => Value::String(text.to_string()),
+    };
+    content.insert(field.name, value);
+  }
+  Ok(Value::Object(content))
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+
+  use cokra_protocol::user_input::RequestUserInputAnswer;
+  use pretty_assertions::assert_eq;
+  use serde_json::json;
+
+  use super::*;
+
+  fn deploy_schema() -> Value {
+    json!({
+      "type": "object",
+      "properties": {
+        "confirm": { "type": "boolean", "title": "Confirm" },
+        "env": { "type": "string", "enum": ["stg", "prod"], "enumNames": ["Staging", "Production"] },
+        "reason": { "type": "string" },
+        "replicas": { "type": "integer", "description": "How many replicas" }
+      },
+      "required": ["confirm", "env"]
+    })
+  }
+
+  fn response(answers: &[(&str, &[&str])]) -> RequestUserInputResponse {
+    RequestUserInputResponse {
+      answers: answers
+        .iter()
+        .map(|(id, entries)| {
+          (
+            id.to_string(),
+            RequestUserInputAnswer {
+              answers: entries.iter().map(ToString::to_string).collect(),
+            },
+          )
+        })
+        .collect::<HashMap<_, _>>(),
+    }
+  }
+
+  #[test]
+  fn schema_properties_become_questions() {
+    let questions = questions_from_schema("Deploy?", &deploy_schema());
+    let summary = questions
+      .iter()
+      .map(|question| {
+        (
+          question.id.as_str(),
+          question.question.as_str(),
+          question.options.as_ref().map(|options| {
+            options
+              .iter()
+              .map(|option| option.label.clone())
+              .collect::<Vec<_>>()
+          }),
+        )
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(
+      summary,
+      vec![
+        (
+          "confirm",
+          "Deploy?\n\nConfirm (required)",
+          Some(vec!["Yes".to_string(), "No".to_string()])
+        ),
+        (
+          "env",
+          "Deploy?\n\nenv (required)",
+          Some(vec!["Staging".to_string(), "Production".to_string()])
+        ),
+        ("reason", "Deploy?\n\nreason", None),
+        ("replicas", "Deploy?\n\nHow many replicas", None),
+      ]
+    );
+  }
+
+  #[test]
+  fn answers_convert_to_typed_content() {
+    let content = content_from_answers(
+      &deploy_schema(),
+      &response(&[
+        ("confirm", &["Yes", "user_note: looks fine"]),
+        ("env", &["Production"]),
+        ("replicas", &["3"]),
+        ("reason", &[]),
+      ]),
+    );
+    assert_eq!(
+      content,
+      Ok(json!({ "confirm": true, "env": "prod", "replicas": 3 }))
+    );
+  }
+
+  #[test]
+  fn invalid_answers_point_back_at_the_question() {
+    assert_eq!(
+      content_from_answers(&deploy_schema(), &response(&[("confirm", &["No"])])),
+      Err(FormError {
+        question: 1,
+        message: "env is required".to_string(),
+      })
+    );
+    assert_eq!(
+      content_from_answers(
+        &deploy_schema(),
+        &response(&[
+          ("confirm", &["No"]),
+          ("env", &["Staging"]),
+          ("replicas", &["many"])
+        ]),
+      ),
+      Err(FormError {
+        question: 3,
+        message: "replicas must be a whole number".to_string(),
+      })
+    );
+  }
+
+  #[test]
+  fn empty_schema_is_a_confirmation() {
+    let schema = json!({ "type": "object", "properties": {} });
+    let questions = questions_from_schema("Proceed?", &schema);
+    assert_eq!(questions.len(), 1);
+    assert_eq!(questions[0].question, "Proceed?");
+    assert_eq!(
+      content_from_answers(&schema, &RequestUserInputResponse::default()),
+      Ok(json!({}))
+    );
+  }
+}
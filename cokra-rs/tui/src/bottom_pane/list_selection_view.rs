@@ -141,6 +141,14 @@ impl ListSelectionView {
     s
   }
 
+  /// Index into the original items of the highlighted row.
+  pub(crate) fn selected_item_idx(&self) -> Option<usize> {
+    self
+      .state
+      .selected_idx
+      .and_then(|idx| self.filtered_indices.get(idx).copied())
+  }
+
   fn visible_len(&self) -> usize {
     self.filtered_indices.len()
   }
@@ -340,6 +348,10 @@ impl BottomPaneView for ListSelectionView {
     self
   }
 
+  fn view_id(&self) -> Option<&'static str> {
+    self.view_id
+  }
+
   fn inline_viewport_sizing(&self) -> InlineViewportSizing {
     InlineViewportSizing::ExpandForOverlay
   }
@@ -21,6 +21,8 @@ use bottom_pane_view::BottomPaneView;
 use chat_composer::ChatComposer;
 use chat_composer::ComposerAction;
 use chat_composer::ComposerSubmission;
+use cokra_protocol::CustomPrompt;
+use cokra_protocol::ElicitationRequestEvent;
 use cokra_protocol::RequestUserInputEvent;
 use queued_user_messages::QueuedUserMessages;
 use request_user_input::RequestUserInputView;
@@ -31,6 +33,7 @@ pub(crate) mod bottom_pane_view;
 pub(crate) mod chat_composer;
 pub(crate) mod chat_composer_history;
 pub(crate) mod command_popup;
+pub(crate) mod elicitation_form;
 pub(crate) mod footer;
 pub(crate) mod list_selection_view;
 pub(crate) mod oauth_connect_view;
@@ -159,6 +162,10 @@ impl BottomPane {
     self.composer.set_steer_enabled(enabled);
   }
 
+  pub(crate) fn set_mcp_prompts(&mut self, prompts: Vec<CustomPrompt>) {
+    self.composer.set_mcp_prompts(prompts);
+  }
+
   pub(crate) fn status_widget(&self) -> Option<&StatusIndicatorWidget> {
     self.status.as_ref()
   }
@@ -232,6 +239,32 @@ impl BottomPane {
     self.push_view(Box::new(view));
   }
 
+  /// Rebuild the active selection view from `params` if it is `view_id`,
+  /// keeping the highlighted row. Returns false when another view is active.
+  pub(crate) fn refresh_selection_view(
+    &mut self,
+    view_id: &'static str,
+    mut params: list_selection_view::SelectionViewParams,
+  ) -> bool {
+    let Some(active) = self.view_stack.last_mut() else {
+      return false;
+    };
+    if active.view_id() != Some(view_id) {
+      return false;
+    }
+    if let Some(view) = active
+      .as_any_mut()
+      .downcast_mut::<list_selection_view::ListSelectionView>()
+    {
+      params.initial_selected_idx = view.selected_item_idx();
+    }
+    *active = Box::new(list_selection_view::ListSelectionView::new(
+      params,
+      self.app_event_tx.clone(),
+    ));
+    true
+  }
+
   pub(crate) fn push_user_input_request(&mut self, request: RequestUserInputEvent) {
     self.push_view(Box::new(RequestUserInputView::new(
       request,
@@ -239,6 +272,13 @@ impl BottomPane {
     )));
   }
 
+  pub(crate) fn push_elicitation_request(&mut self, request: ElicitationRequestEvent) {
+    self.push_view(Box::new(RequestUserInputView::new_elicitation(
+      request,
+      self.app_event_tx.clone(),
+    )));
+  }
+
   pub(crate) fn dismiss_active_view(&mut self) {
     self.view_stack.pop();
   }
@@ -1,8 +1,150 @@
-/// Minimal placeholder for prompt argument parsing.
-///
-/// cokra currently keeps slash prompt arguments in raw text form. This module
-/// exists so bottom-pane modules can share the same location as codex for the
-/// future full parser.
-pub(crate) fn split_prompt_args(input: &str) -> Vec<String> {
-  input.split_whitespace().map(ToString::to_string).collect()
+//! Argument parsing for prompt slash commands such as `/mcp__docs__summarize`.
+//!
+//! Arguments are shell-quoted words. `NAME=value` sets a named argument;
+//! bare words fill the remaining declared arguments in order.
+
+use std::collections::HashMap;
+
+use cokra_protocol::CustomPrompt;
+use cokra_protocol::CustomPromptArgument;
+
+/// The prompt a submitted line invokes and the raw text after its name.
+pub(crate) fn prompt_command<'a>(
+  text: &'a str,
+  prompts: &'a [CustomPrompt],
+) -> Option<(&'a CustomPrompt, &'a str)> {
+  let rest = text.trim().strip_prefix('/')?;
+  let (name, args) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+  prompts
+    .iter()
+    .find(|prompt| prompt.id == name)
+    .map(|prompt| (prompt, args.trim()))
+}
+
+pub(crate) fn parse_prompt_args(
+  input: &str,
+  arguments: &[CustomPromptArgument],
+) -> Result<HashMap<String, String>, String> {
+  let words = shlex::split(input).ok_or_else(|| "unbalanced quotes in arguments".to_string())?;
+  let mut values = HashMap::new();
+  let mut positional = Vec::new();
+  for word in words {
+    match word.split_once('=') {
+      Some((name, value)) if arguments.iter().any(|argument| argument.name == name) => {
+        values.insert(name.to_string(), value.to_string());
+      }
+      _ => positional.push(word),
+    }
+  }
+
+  let mut positional = positional.into_iter();
+  for argument in arguments {
+    if values.contains_key(&argument.name) {
+      continue;
+    }
+    match positional.next() {
+      Some(value) => {
+        values.insert(argument.name.clone(), value);
+      }
+      None => break,
+    }
+  }
+  let extra = positional.collect::<Vec<_>>();
+  if !extra.is_empty() {
+    return Err(format!("unexpected arguments: {}", extra.join(" ")));
+  }
+
+  let missing = arguments
+    .iter()
+    .filter(|argument| argument.required && !values.contains_key(&argument.name))
+    .map(|argument| argument.name.as_str())
+    .collect::<Vec<_>>();
+  if !missing.is_empty() {
+    return Err(format!(
+      "missing required arguments: {}",
+      missing.join(", ")
+    ));
+  }
+  Ok(values)
+}
+
+/// `NAME=<NAME>` hints for the prompt's arguments, optional ones bracketed.
+pub(crate) fn prompt_args_usage(arguments: &[CustomPromptArgument]) -> String {
+  arguments
+    .iter()
+    .map(|argument| {
+      let upper = argument.name.to_uppercase();
+      if argument.required {
+        format!("{}=<{upper}>", argument.name)
+      } else {
+        format!("[{}=<{upper}>]", argument.name)
+      }
+    })
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  fn arguments() -> Vec<CustomPromptArgument> {
+    vec![
+      CustomPromptArgument {
+        name: "path".to_string(),
+        description: None,
+        required: true,
+      },
+      CustomPromptArgument {
+        name: "style".to_string(),
+        description: None,
+        required: false,
+      },
+    ]
+  }
+
+  #[test]
+  fn named_and_positional_arguments_fill_in_order() {
+    let values = parse_prompt_args("style=brief 'docs/a b.md'", &arguments()).unwrap();
+    assert_eq!(
+      values,
+      HashMap::from([
+        ("path".to_string(), "docs/a b.md".to_string()),
+        ("style".to_string(), "brief".to_string()),
+      ])
+    );
+  }
+
+  #[test]
+  fn missing_and_extra_arguments_are_rejected() {
+    assert_eq!(
+      parse_prompt_args("style=brief", &arguments()),
+      Err("missing required arguments: path".to_string())
+    );
+    assert_eq!(
+      parse_prompt_args("a b c", &arguments()),
+      Err("unexpected arguments: c".to_string())
+    );
+  }
+
+  #[test]
+  fn submitted_line_resolves_to_prompt() {
+    let prompts = vec![CustomPrompt {
+      id: "mcp__docs__summarize".to_string(),
+      name: "summarize".to_string(),
+      description: None,
+      server_name: Some("docs".to_string()),
+      arguments: arguments(),
+    }];
+    let (prompt, args) = prompt_command("/mcp__docs__summarize  README.md ", &prompts).unwrap();
+    assert_eq!(prompt.name, "summarize");
+    assert_eq!(args, "README.md");
+    assert!(prompt_command("/mcp__docs__other", &prompts).is_none());
+    assert_eq!(
+      prompt_args_usage(&prompt.arguments),
+      "path=<PATH> [style=<STYLE>]"
+    );
+  }
 }
@@ -1,6 +1,8 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
 
+use cokra_protocol::ElicitationAction;
+use cokra_protocol::ElicitationRequestEvent;
 use cokra_protocol::Op;
 use cokra_protocol::RequestUserInputEvent;
 use cokra_protocol::RequestUserInputQuestion;
@@ -26,6 +28,8 @@ use ratatui::widgets::Widget;
 use textwrap::Options;
 
 use super::bottom_pane_view::BottomPaneView;
+use super::elicitation_form::content_from_answers;
+use super::elicitation_form::questions_from_schema;
 use super::selection_popup_common::menu_surface_inset;
 use super::selection_popup_common::menu_surface_padding_height;
 use super::selection_popup_common::render_menu_surface;
@@ -33,6 +37,7 @@ use super::textarea::TextArea;
 use super::textarea::TextAreaState;
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
+use crate::history_cell::PlainHistoryCell;
 use crate::history_cell::RequestUserInputResultCell;
 use crate::render::renderable::Renderable;
 
@@ -48,9 +53,19 @@ struct AnswerState {
   note: String,
 }
 
+/// The MCP form a view is answering instead of a `request_user_input` call.
+struct ElicitationForm {
+  server_name: String,
+  request_id: String,
+  requested_schema: serde_json::Value,
+}
+
 pub(crate) struct RequestUserInputView {
   app_event_tx: AppEventSender,
   request: RequestUserInputEvent,
+  elicitation: Option<ElicitationForm>,
+  /// Why the last elicitation submit was rejected.
+  error: Option<String>,
   textarea: TextArea,
   textarea_state: RefCell<TextAreaState>,
   answers: Vec<AnswerState>,
@@ -64,6 +79,8 @@ impl RequestUserInputView {
       app_event_tx,
       answers: vec![AnswerState::default(); request.questions.len()],
       request,
+      elicitation: None,
+      error: None,
       textarea: TextArea::new(),
       textarea_state: RefCell::new(TextAreaState::default()),
       current_idx: 0,
@@ -73,6 +90,35 @@ impl RequestUserInputView {
     view
   }
 
+  pub(crate) fn new_elicitation(
+    request: ElicitationRequestEvent,
+    app_event_tx: AppEventSender,
+  ) -> Self {
+    let questions = questions_from_schema(&request.message, &request.requested_schema);
+    let mut view = Self::new(
+      RequestUserInputEvent {
+        thread_id: request.thread_id,
+        turn_id: request.turn_id,
+        call_id: request.id.clone(),
+        questions,
+      },
+      app_event_tx,
+    );
+    view.elicitation = Some(ElicitationForm {
+      server_name: request.server_name,
+      request_id: request.id,
+      requested_schema: request.requested_schema,
+    });
+    view
+  }
+
+  fn title(&self) -> String {
+    match &self.elicitation {
+      Some(form) => format!("{} requests input", form.server_name),
+      None => TITLE.to_string(),
+    }
+  }
+
   fn content_width(&self, width: u16) -> u16 {
     menu_surface_inset(Rect::new(0, 0, width, 1)).width.max(1)
   }
@@ -235,12 +281,28 @@ impl RequestUserInputView {
     } else {
       "enter next"
     };
-    let footer =
-      format!("{enter_tip} | left/right navigate | up/down select option | esc submit now");
-    textwrap::wrap(&footer, self.content_width(width) as usize)
-      .into_iter()
-      .map(|line| Line::from(line.to_string()).dim())
-      .collect()
+    let footer = if self.elicitation.is_some() {
+      format!(
+        "{enter_tip} | left/right navigate | up/down select option | ctrl+d decline | esc cancel"
+      )
+    } else {
+      format!("{enter_tip} | left/right navigate | up/down select option | esc submit now")
+    };
+    let wrap_width = self.content_width(width) as usize;
+    let mut lines = Vec::new();
+    if let Some(error) = &self.error {
+      lines.extend(
+        textwrap::wrap(error, wrap_width)
+          .into_iter()
+          .map(|line| Line::from(line.to_string()).red()),
+      );
+    }
+    lines.extend(
+      textwrap::wrap(&footer, wrap_width)
+        .into_iter()
+        .map(|line| Line::from(line.to_string()).dim()),
+    );
+    lines
   }
 
   fn submit_current_and_continue(&mut self) {
@@ -255,6 +317,14 @@ impl RequestUserInputView {
 
   fn submit_all(&mut self, interrupted: bool) {
     self.save_current_note();
+    if self.elicitation.is_some() {
+      if interrupted {
+        self.resolve_elicitation(ElicitationAction::Cancel, None);
+      } else {
+        self.accept_elicitation();
+      }
+      return;
+    }
     let response = self.build_response();
     self
       .app_event_tx
@@ -272,6 +342,76 @@ impl RequestUserInputView {
     self.complete = true;
   }
 
+  fn accept_elicitation(&mut self) {
+    let Some(form) = &self.elicitation else {
+      return;
+    };
+    let response = self.build_response();
+    match content_from_answers(&form.requested_schema, &response) {
+      Ok(content) => {
+        // A property-less confirmation has nothing worth echoing back.
+        if self
+          .request
+          .questions
+          .iter()
+          .any(|question| !question.id.is_empty())
+        {
+          self
+            .app_event_tx
+            .insert_history_cell(RequestUserInputResultCell {
+              questions: self.request.questions.clone(),
+              answers: response.answers,
+              interrupted: false,
+            });
+        } else {
+          self.insert_elicitation_outcome("Accepted");
+        }
+        self.resolve_elicitation(ElicitationAction::Accept, Some(content));
+      }
+      Err(err) => {
+        self.error = Some(err.message);
+        self.current_idx = err.question.min(self.question_count().saturating_sub(1));
+        self.sync_textarea_from_current();
+      }
+    }
+  }
+
+  fn resolve_elicitation(
+    &mut self,
+    decision: ElicitationAction,
+    content: Option<serde_json::Value>,
+  ) {
+    match decision {
+      ElicitationAction::Accept => {}
+      ElicitationAction::Decline => self.insert_elicitation_outcome("Declined"),
+      ElicitationAction::Cancel => self.insert_elicitation_outcome("Cancelled"),
+    }
+    let Some(form) = &self.elicitation else {
+      return;
+    };
+    self
+      .app_event_tx
+      .send(AppEvent::CodexOp(Op::ResolveElicitation {
+        server_name: form.server_name.clone(),
+        request_id: form.request_id.clone(),
+        decision,
+        content,
+      }));
+    self.complete = true;
+  }
+
+  fn insert_elicitation_outcome(&self, verb: &str) {
+    let Some(form) = &self.elicitation else {
+      return;
+    };
+    self
+      .app_event_tx
+      .insert_history_cell(PlainHistoryCell::new(vec![Line::from(format!(
+        "● {verb} input request from {}",
+        form.server_name
+      ))]));
+  }
+
   fn build_response(&self) -> RequestUserInputResponse {
     let mut answers = HashMap::new();
 
@@ -351,6 +491,13 @@ impl BottomPaneView for RequestUserInputView {
         modifiers: KeyModifiers::NONE,
         ..
       } => self.submit_current_and_continue(),
+      KeyEvent {
+        code: KeyCode::Char('d'),
+        modifiers: KeyModifiers::CONTROL,
+        ..
+      } if self.elicitation.is_some() => {
+        self.resolve_elicitation(ElicitationAction::Decline, None);
+      }
       KeyEvent {
         code: KeyCode::Left,
         modifiers: KeyModifiers::NONE,
@@ -466,7 +613,7 @@ impl Renderable for RequestUserInputView {
     ])
     .split(outer);
 
-    Paragraph::new(Line::from(TITLE.bold())).render(chunks[0], buf);
+    Paragraph::new(Line::from(self.title().bold())).render(chunks[0], buf);
     Paragraph::new(self.progress_line()).render(chunks[1], buf);
     Paragraph::new(
       self
@@ -612,4 +759,86 @@ mod tests {
     ));
     assert!(view.is_complete());
   }
+
+  fn sample_elicitation() -> ElicitationRequestEvent {
+    ElicitationRequestEvent {
+      thread_id: "thread".to_string(),
+      turn_id: "turn-1".to_string(),
+      server_name: "deploy".to_string(),
+      id: "e1".to_string(),
+      message: "Confirm the rollout".to_string(),
+      requested_schema: serde_json::json!({
+        "type": "object",
+        "properties": {
+          "confirm": { "type": "boolean" },
+          "replicas": { "type": "integer" }
+        },
+        "required": ["confirm", "replicas"]
+      }),
+    }
+  }
+
+  #[test]
+  fn elicitation_form_resolves_with_typed_content() {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let sender = AppEventSender { app_event_tx: tx };
+    let mut view = RequestUserInputView::new_elicitation(sample_elicitation(), sender);
+
+    view.handle_key_event(KeyEvent::from(KeyCode::Char('1')));
+    view.handle_key_event(KeyEvent::from(KeyCode::Enter));
+    view.handle_paste("three".to_string());
+    view.handle_key_event(KeyEvent::from(KeyCode::Enter));
+    assert!(rx.try_recv().is_err());
+    assert_eq!(
+      view.error.as_deref(),
+      Some("replicas must be a whole number")
+    );
+    assert!(!view.is_complete());
+
+    view.textarea.set_text_clearing_elements("3");
+    view.handle_key_event(KeyEvent::from(KeyCode::Enter));
+    assert!(matches!(rx.try_recv(), Ok(AppEvent::InsertHistoryCell(_))));
+    assert!(matches!(
+      rx.try_recv(),
+      Ok(AppEvent::CodexOp(Op::ResolveElicitation {
+        server_name,
+        request_id,
+        decision: ElicitationAction::Accept,
+        content: Some(content),
+      })) if server_name == "deploy"
+        && request_id == "e1"
+        && content == serde_json::json!({ "confirm": true, "replicas": 3 })
+    ));
+    assert!(view.is_complete());
+  }
+
+  #[test]
+  fn elicitation_can_be_declined_or_cancelled() {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let sender = AppEventSender { app_event_tx: tx };
+    let mut view = RequestUserInputView::new_elicitation(sample_elicitation(), sender.clone());
+    view.handle_key_event(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL));
+    assert!(matches!(rx.try_recv(), Ok(AppEvent::InsertHistoryCell(_))));
+    assert!(matches!(
+      rx.try_recv(),
+      Ok(AppEvent::CodexOp(Op::ResolveElicitation {
+        decision: ElicitationAction::Decline,
+        content: None,
+        ..
+      }))
+    ));
+    assert!(view.is_complete());
+
+    let mut view = RequestUserInputView::new_elicitation(sample_elicitation(), sender);
+    assert!(view.on_cancel());
+    assert!(matches!(rx.try_recv(), Ok(AppEvent::InsertHistoryCell(_))));
+    assert!(matches!(
+      rx.try_recv(),
+      Ok(AppEvent::CodexOp(Op::ResolveElicitation {
+        decision: ElicitationAction::Cancel,
+        content: None,
+        ..
+      }))
+    ));
+  }
 }
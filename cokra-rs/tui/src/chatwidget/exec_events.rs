@@ -122,6 +122,21 @@ impl ChatWidget {
     }
   }
 
+  pub(super) fn on_mcp_tool_call_progress(
+    &mut self,
+    event: &cokra_protocol::McpToolCallProgressEvent,
+  ) {
+    if let Some(cell) = self
+      .transcript
+      .active_exec_cell
+      .as_mut()
+      .and_then(|cell| cell.as_any_mut().downcast_mut::<ExecCell>())
+      && cell.set_progress(&event.call_id, format_mcp_progress(event))
+    {
+      self.bump_active_cell_revision();
+    }
+  }
+
   pub(super) fn handle_exec_approval_now(
     &mut self,
     ev: ExecApprovalRequestEvent,
@@ -218,6 +233,19 @@ impl ChatWidget {
   }
 }
 
+/// `3/10 indexing files`, or `3 indexing files` when the total is unknown.
+fn format_mcp_progress(event: &cokra_protocol::McpToolCallProgressEvent) -> String {
+  let mut text = match event.total {
+    Some(total) => format!("{}/{}", event.progress, total),
+    None => event.progress.to_string(),
+  };
+  if let Some(message) = event.message.as_deref().filter(|m| !m.is_empty()) {
+    text.push(' ');
+    text.push_str(message);
+  }
+  text
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -273,6 +301,44 @@ mod tests {
     }
   }
 
+  #[test]
+  fn mcp_progress_is_shown_on_the_running_call_until_it_ends() {
+    let mut widget = make_widget();
+    widget.handle_exec_begin_now(&begin_event(
+      "call-1",
+      "mcp__docs__index",
+      "mcp__docs__index",
+    ));
+
+    let progress = |total, message: Option<&str>| cokra_protocol::McpToolCallProgressEvent {
+      thread_id: "thread-1".to_string(),
+      turn_id: "turn-1".to_string(),
+      call_id: "call-1".to_string(),
+      progress: 3.0,
+      total,
+      message: message.map(str::to_string),
+    };
+    let active_progress = |widget: &ChatWidget| {
+      widget
+        .transcript
+        .active_exec_cell
+        .as_ref()
+        .and_then(|c| c.as_any().downcast_ref::<ExecCell>())
+        .and_then(|cell| cell.progress("call-1").map(str::to_string))
+    };
+
+    widget.on_mcp_tool_call_progress(&progress(None, None));
+    assert_eq!(active_progress(&widget).as_deref(), Some("3"));
+    widget.on_mcp_tool_call_progress(&progress(Some(10.0), Some("indexing files")));
+    assert_eq!(
+      active_progress(&widget).as_deref(),
+      Some("3/10 indexing files")
+    );
+
+    widget.handle_exec_end_now(&end_event("call-1"));
+    assert_eq!(active_progress(&widget), None);
+  }
+
   #[test]
   fn exec_end_restores_working_status_when_no_active_exec_calls_remain() {
     let mut widget = make_widget();
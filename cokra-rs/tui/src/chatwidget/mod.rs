@@ -18,6 +18,7 @@ use ratatui::text::Text;
 use ratatui::widgets::Paragraph;
 use ratatui::widgets::Widget;
 
+use cokra_protocol::ElicitationRequestEvent;
 use cokra_protocol::EventMsg;
 use cokra_protocol::ExecApprovalRequestEvent;
 use cokra_protocol::RequestUserInputEvent;
@@ -51,6 +52,7 @@ use std::cell::Cell;
 pub(crate) enum ChatWidgetAction {
   ShowApproval(ExecApprovalRequestEvent),
   ShowRequestUserInput(RequestUserInputEvent),
+  OpenElicitation(ElicitationRequestEvent),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -688,6 +690,7 @@ impl ChatWidget {
         );
       }
       EventMsg::ExecCommandOutputDelta(e) => self.on_exec_command_output_delta(e),
+      EventMsg::McpToolCallProgress(e) => self.on_mcp_tool_call_progress(e),
       EventMsg::ExecCommandEnd(e) => {
         // 1:1 Codex: flush the answer stream before deferring so the exec
         // end is handled immediately.
@@ -713,6 +716,15 @@ impl ChatWidget {
         self.flush_stream_controllers();
         return Some(ChatWidgetAction::ShowRequestUserInput(e.clone()));
       }
+      EventMsg::ElicitationRequest(e) => {
+        // Same as RequestUserInput: the MCP tool call blocks on the answer.
+        self.flush_stream_controllers();
+        self.add_to_history_preserving_exec(PlainHistoryCell::new(vec![Line::from(format!(
+          "● {} requests input: {}",
+          e.server_name, e.message
+        ))]));
+        return Some(ChatWidgetAction::OpenElicitation(e.clone()));
+      }
       EventMsg::Warning(e) => {
         self.add_to_history(PlainHistoryCell::new(vec![Line::from(vec![
           Span::from("warning: ").yellow(),
@@ -901,6 +913,8 @@ mod tests {
   use crate::app_event_sender::AppEventSender;
   use crate::exec_cell::new_active_exec_command;
   use crate::history_cell::AgentMessageCell;
+  use cokra_core::model::cassette::CassetteMode;
+  use cokra_core::test_support::run_read_file_turn;
   use ratatui::Terminal;
   use ratatui::backend::TestBackend;
   use tokio::sync::mpsc::unbounded_channel;
@@ -984,4 +998,46 @@ mod tests {
     assert!(rendered.contains("implementing b.txt"));
     assert!(rendered.contains("reviewing task-a"));
   }
+
+  #[tokio::test]
+  async fn replayed_tool_calling_turn_renders_the_call_and_the_answer() {
+    // Core events from the recorded OpenAI turn in core/tests/cassettes.
+    let turn = run_read_file_turn(CassetteMode::Replay).await;
+
+    let (tx, mut rx) = unbounded_channel();
+    let mut widget = ChatWidget::new(
+      AppEventSender::new(tx),
+      FrameRequester::test_dummy(),
+      false,
+      StreamRenderMode::ScrollbackFirst,
+    );
+    for event in &turn.events {
+      widget.handle_event(event);
+    }
+
+    let mut transcript = Vec::new();
+    while let Ok(event) = rx.try_recv() {
+      if let AppEvent::InsertHistoryCell(cell) = event {
+        for line in cell.display_lines(80) {
+          transcript.push(
+            line
+              .spans
+              .iter()
+              .map(|span| span.content.as_ref())
+              .collect::<String>(),
+          );
+        }
+      }
+    }
+    let usage_rule = format!("─ 61 in / 6 out {}", "─".repeat(64));
+    assert_eq!(
+      transcript,
+      vec![
+        "● I'll read the file.",
+        "● File content: hello from tool",
+        "● Explored read 1 file",
+        usage_rule.as_str(),
+      ]
+    );
+  }
 }
@@ -57,11 +57,15 @@ impl ChatWidget {
       | EventMsg::ListSkillsResponse(_)
       | EventMsg::ListRemoteSkillsResponse(_) => {}
       EventMsg::ContextCompacted(event) => {
-        let _ = event;
         self.session.context_used_tokens = None;
-        self.add_to_history_preserving_exec(PlainHistoryCell::new(vec![Line::from(
-          "● Context compacted".dim(),
-        )]));
+        let mut message = "● Context compacted".to_string();
+        if event.invalidated_cached_tokens > 0 {
+          message.push_str(&format!(
+            " · prompt cache reset ({} cached tokens)",
+            crate::bottom_pane::footer::format_tokens_compact(event.invalidated_cached_tokens)
+          ));
+        }
+        self.add_to_history_preserving_exec(PlainHistoryCell::new(vec![Line::from(message.dim())]));
       }
       EventMsg::ThreadRolledBack(e) => {
         self.add_to_history_preserving_exec(PlainHistoryCell::new(vec![Line::from(format!(
@@ -71,11 +75,9 @@ impl ChatWidget {
         ))]));
       }
       EventMsg::AgentReasoningDelta(e) => {
+        // Deltas only drive the status header; the transcript gets the
+        // finished summary from `AgentReasoning`.
         self.on_reasoning_delta(&e.delta);
-        self.add_to_history_preserving_exec(PlainHistoryCell::new(vec![Line::from(vec![
-          Span::from("reasoning: ").dim(),
-          Span::from(e.delta.clone()).dim(),
-        ])]));
       }
       EventMsg::AgentReasoning(e) => {
         self.on_reasoning_final(&e.text);
@@ -94,33 +96,32 @@ impl ChatWidget {
         self.on_reasoning_section_break();
       }
       EventMsg::McpStartupUpdate(e) => {
-        match &e.status {
+        let line = match &e.status {
           cokra_protocol::McpStartupStatus::Starting => {
             self.session.mcp_starting_servers.insert(e.server.clone());
+            None
           }
-          cokra_protocol::McpStartupStatus::Ready
-          | cokra_protocol::McpStartupStatus::Cancelled
-          | cokra_protocol::McpStartupStatus::Failed { .. } => {
-            self.session.mcp_starting_servers.remove(&e.server);
+          cokra_protocol::McpStartupStatus::Ready => {
+            Some(Line::from(format!("● MCP server `{}` ready", e.server)).dim())
           }
+          cokra_protocol::McpStartupStatus::Cancelled => None,
+          cokra_protocol::McpStartupStatus::Failed { error } => Some(Line::from(vec![
+            Span::from(format!("● MCP server `{}` failed: ", e.server)).red(),
+            Span::from(error.clone()),
+          ])),
+        };
+        if !matches!(e.status, cokra_protocol::McpStartupStatus::Starting) {
+          self.session.mcp_starting_servers.remove(&e.server);
         }
         self.sync_status_indicator();
-        self.add_to_history_preserving_exec(PlainHistoryCell::new(vec![Line::from(format!(
-          "● MCP server {}: {:?}",
-          e.server, e.status
-        ))]));
+        if let Some(line) = line {
+          self.add_to_history_preserving_exec(PlainHistoryCell::new(vec![line]));
+        }
       }
-      EventMsg::McpStartupComplete(e) => {
+      EventMsg::McpStartupComplete(_) => {
+        // Failures were already reported by their startup updates.
         self.session.mcp_starting_servers.clear();
         self.sync_status_indicator();
-        if !e.failed.is_empty() {
-          for f in &e.failed {
-            self.add_to_history_preserving_exec(PlainHistoryCell::new(vec![Line::from(vec![
-              Span::from("MCP failed: ").red(),
-              Span::from(format!("{}: {}", f.server, f.error)),
-            ])]));
-          }
-        }
       }
       EventMsg::McpToolCallBegin(e) => {
         self.flush_answer_stream();
@@ -164,7 +165,9 @@ impl ChatWidget {
               .iter()
               .map(|block| match block {
                 cokra_protocol::McpContentBlock::Text { text } => text.clone(),
-                cokra_protocol::McpContentBlock::Image { .. } => "[image]".to_string(),
+                cokra_protocol::McpContentBlock::Image { mime_type, .. } => {
+                  format!("[image: {mime_type}]")
+                }
                 cokra_protocol::McpContentBlock::Resource { uri, text } => {
                   text.clone().unwrap_or_else(|| uri.clone())
                 }
@@ -233,12 +236,6 @@ impl ChatWidget {
           e.path.display()
         ))]));
       }
-      EventMsg::ElicitationRequest(e) => {
-        self.add_to_history_preserving_exec(PlainHistoryCell::new(vec![Line::from(format!(
-          "● {} requests input: {}",
-          e.server_name, e.message
-        ))]));
-      }
       EventMsg::ApplyPatchApprovalRequest(e) => {
         let file_count = e.changes.len();
         self.add_to_history_preserving_exec(PlainHistoryCell::new(vec![Line::from(format!(
@@ -431,7 +428,10 @@ impl ChatWidget {
         }
         self.add_to_history_preserving_exec(multi_agents::plan_decision(e.clone()));
       }
-      EventMsg::CollabResumeBegin(_) | EventMsg::CollabResumeEnd(_) => {}
+      EventMsg::CollabResumeBegin(_) => {}
+      EventMsg::CollabResumeEnd(e) => {
+        self.add_to_history_preserving_exec(multi_agents::resume_end(e.clone()));
+      }
       _ => return false,
     }
 
@@ -608,6 +608,7 @@ mod tests {
         tokens_after_est: 12_345,
         reserve_tokens: 16_384,
         keep_recent_tokens: 24_576,
+        invalidated_cached_tokens: 0,
       },
     ));
 
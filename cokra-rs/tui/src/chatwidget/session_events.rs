@@ -31,7 +31,9 @@ impl ChatWidget {
     self.session.token_usage.output_tokens = event.output_tokens;
     self.session.token_usage.reasoning_output_tokens = event.reasoning_output_tokens;
     self.session.token_usage.total_tokens = event.total_tokens;
-    let used_tokens = if event.total_tokens > 0 {
+    let used_tokens = if event.context_tokens > 0 {
+      Some(event.context_tokens)
+    } else if event.total_tokens > 0 {
       Some(event.total_tokens)
     } else {
       let sum = event
@@ -125,6 +127,12 @@ mod tests {
       output_tokens: 45,
       reasoning_output_tokens: 12,
       total_tokens: 1557,
+      cache_write_input_tokens: 0,
+      context_tokens: 0,
+      cost_usd: Some(0.012),
+      turn_cost_usd: 0.012,
+      thread_cost_usd: 0.05,
+      session_cost_usd: 0.08,
     }
   }
 
@@ -207,4 +215,24 @@ mod tests {
     );
     assert_eq!(widget.context_used_tokens(), Some(1557));
   }
+
+  #[test]
+  fn context_usage_prefers_the_tokenizer_count() {
+    let (tx, _rx) = unbounded_channel();
+    let sender = AppEventSender::new(tx);
+    let mut widget = ChatWidget::new(
+      sender,
+      FrameRequester::test_dummy(),
+      false,
+      StreamRenderMode::AnimatedPreview,
+    );
+
+    widget.on_token_count(&cokra_protocol::TokenCountEvent {
+      context_tokens: 1490,
+      ..token_count_event()
+    });
+
+    assert_eq!(widget.token_usage().total_tokens, 1557);
+    assert_eq!(widget.context_used_tokens(), Some(1490));
+  }
 }
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::time::Duration;
 use std::time::Instant;
@@ -45,6 +46,9 @@ pub(crate) struct ExecCell {
   /// the "⠋ Exploring" state for at least a brief moment before being
   /// flushed to scrollback.
   pub(crate) exploring_visible_since: Option<Instant>,
+  /// Latest progress text of running calls, keyed by `command_id`, shown
+  /// after "Running" until the call completes.
+  progress: HashMap<String, String>,
 }
 
 impl ExecCell {
@@ -56,6 +60,7 @@ impl ExecCell {
       is_continuation: false,
       exploring_since,
       exploring_visible_since: exploring_since,
+      progress: HashMap::new(),
     }
   }
 
@@ -71,6 +76,7 @@ impl ExecCell {
       is_continuation: true,
       exploring_since: None,
       exploring_visible_since: None,
+      progress: HashMap::new(),
     }
   }
 
@@ -98,6 +104,7 @@ impl ExecCell {
         } else {
           self.exploring_visible_since
         },
+        progress: self.progress.clone(),
       })
     } else if self.is_active() {
       let mut calls = self.calls.clone();
@@ -108,6 +115,7 @@ impl ExecCell {
         is_continuation: self.is_continuation,
         exploring_since: None,
         exploring_visible_since: None,
+        progress: self.progress.clone(),
       })
     } else {
       None
@@ -130,6 +138,25 @@ impl ExecCell {
       call.duration = Some(duration);
       call.start_time = None;
     }
+    self.progress.remove(command_id);
+  }
+
+  /// Record progress reported for a running call. Returns false when no
+  /// running call has that id.
+  pub(crate) fn set_progress(&mut self, command_id: &str, text: String) -> bool {
+    if !self
+      .calls
+      .iter()
+      .any(|c| c.command_id == command_id && c.output.is_none())
+    {
+      return false;
+    }
+    self.progress.insert(command_id.to_string(), text);
+    true
+  }
+
+  pub(crate) fn progress(&self, command_id: &str) -> Option<&str> {
+    self.progress.get(command_id).map(String::as_str)
   }
 
   pub(crate) fn append_output(&mut self, command_id: &str, chunk: &str) -> bool {
@@ -152,6 +179,7 @@ impl ExecCell {
   }
 
   pub(crate) fn mark_failed_incomplete(&mut self) {
+    self.progress.clear();
     for call in &mut self.calls {
       if call.output.is_some() {
         continue;
@@ -22,6 +22,8 @@ use crate::wrapping::word_wrap_lines;
 
 const EXPLORING_SUMMARY_MAX_ITEMS: usize = 3;
 const EXPLORING_LIVE_MAX_HEIGHT: u16 = 6;
+/// Result lines shown for an MCP tool call before the rest is summarized.
+const MCP_OUTPUT_MAX_LINES: usize = 12;
 
 #[derive(Debug, Clone, Copy)]
 pub(crate) struct OutputLinesParams {
@@ -135,6 +137,64 @@ pub(crate) fn output_lines(
   }
 }
 
+/// An MCP tool result: `[image: …]` placeholders stand out, and JSON, either
+/// the whole result or a fenced `json` block, is pretty-printed as a block.
+fn mcp_output_lines(output: Option<&CommandOutput>) -> Vec<Line<'static>> {
+  let Some(output) = output else {
+    return Vec::new();
+  };
+
+  let mut body = Vec::new();
+  if let Some(pretty) = pretty_json(output.output.trim()) {
+    body.extend(json_block_lines(&pretty));
+  } else {
+    let mut fence: Option<Vec<&str>> = None;
+    for row in output.output.lines() {
+      match fence.as_mut() {
+        Some(block) if row.trim() == "```" => {
+          let text = block.join("\n");
+          body.extend(json_block_lines(&pretty_json(&text).unwrap_or(text)));
+          fence = None;
+        }
+        Some(block) => block.push(row),
+        None if row.trim() == "```json" => fence = Some(Vec::new()),
+        None if row.starts_with("[image: ") && row.ends_with(']') => {
+          body.push(Line::from(row.to_string().magenta().italic()));
+        }
+        None => body.push(Line::from(row.to_string().dim())),
+      }
+    }
+    // An unterminated fence is shown as it arrived.
+    body.extend(
+      fence
+        .into_iter()
+        .flatten()
+        .map(|row| Line::from(row.to_string().dim())),
+    );
+  }
+
+  let total = body.len();
+  if total > MCP_OUTPUT_MAX_LINES {
+    body.truncate(MCP_OUTPUT_MAX_LINES);
+    body.push(Line::from(
+      format!("… +{} lines", total - MCP_OUTPUT_MAX_LINES).dim(),
+    ));
+  }
+  prefix_lines(body, "  └ ".dim(), "    ".into())
+}
+
+fn pretty_json(text: &str) -> Option<String> {
+  if !text.starts_with('{') && !text.starts_with('[') {
+    return None;
+  }
+  let value = serde_json::from_str::<serde_json::Value>(text).ok()?;
+  serde_json::to_string_pretty(&value).ok()
+}
+
+fn json_block_lines(pretty: &str) -> impl Iterator<Item = Line<'static>> + '_ {
+  pretty.lines().map(|row| Line::from(row.to_string().cyan()))
+}
+
 fn format_duration_human(duration: Duration) -> String {
   let secs = duration.as_secs();
   if secs < 60 {
@@ -169,6 +229,10 @@ fn is_collapsed_output_call(tool_name: &str) -> bool {
   tool_name != "shell"
 }
 
+fn is_mcp_tool_call(tool_name: &str) -> bool {
+  tool_name.starts_with("mcp__")
+}
+
 impl HistoryCell for ExecCell {
   fn is_stream_continuation(&self) -> bool {
     self.is_continuation
@@ -209,6 +273,9 @@ impl HistoryCell for ExecCell {
       if !status_text.is_empty() {
         header.push_span(format!(" {status_text}").dim());
       }
+      if let Some(progress) = self.progress(&call.command_id) {
+        header.push_span(format!(" · {progress}").dim());
+      }
       if let Some(ms) = duration_ms {
         header.push_span(format!(" ({ms}ms)").dim());
       }
@@ -241,8 +308,11 @@ impl HistoryCell for ExecCell {
       }
 
       // Collapsed output: non-shell tools only show header + command, no
-      // output body. Shell keeps full output for user visibility.
-      if is_collapsed_output_call(&call.tool_name) {
+      // output body. Shell keeps full output for user visibility, and MCP
+      // tools show their result since it is all the user sees of them.
+      if is_mcp_tool_call(&call.tool_name) {
+        lines.extend(mcp_output_lines(call.output.as_ref()));
+      } else if is_collapsed_output_call(&call.tool_name) {
         // Show only stderr/error output for failed non-shell calls so users
         // can still see why a tool failed.
         if call
@@ -930,6 +1000,39 @@ mod tests {
     );
   }
 
+  #[test]
+  fn mcp_tool_output_shows_image_placeholders_and_pretty_json() {
+    let call = ExecCall {
+      command_id: "call-chart".to_string(),
+      tool_name: "mcp__charts__render".to_string(),
+      command: "mcp__charts__render".to_string(),
+      cwd: PathBuf::from("/home/user"),
+      output: Some(CommandOutput {
+        exit_code: 0,
+        output: "Rendered.\n[image: image/png, 2.0 KB]\n```json\n{\"points\":3}\n```".to_string(),
+      }),
+      start_time: None,
+      duration: Some(Duration::from_millis(40)),
+    };
+    let cell = ExecCell::new(call, false);
+    let rendered = cell
+      .display_lines(80)
+      .iter()
+      .map(Line::to_string)
+      .collect::<Vec<_>>();
+
+    assert_eq!(
+      rendered[rendered.len() - 5..],
+      [
+        "  └ Rendered.",
+        "    [image: image/png, 2.0 KB]",
+        "    {",
+        "      \"points\": 3",
+        "    }",
+      ]
+    );
+  }
+
   #[test]
   fn non_shell_transcript_output_is_collapsed() {
     let call = ExecCall {
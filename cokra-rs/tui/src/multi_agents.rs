@@ -5,6 +5,8 @@ use std::collections::HashMap;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::Hash;
 use std::hash::Hasher;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
 use cokra_protocol::CollabAgentInteractionEndEvent;
 use cokra_protocol::CollabAgentLifecycle;
@@ -18,6 +20,7 @@ use cokra_protocol::CollabMessagePostedEvent;
 use cokra_protocol::CollabMessagesReadEvent;
 use cokra_protocol::CollabPlanDecisionEvent;
 use cokra_protocol::CollabPlanSubmittedEvent;
+use cokra_protocol::CollabResumeEndEvent;
 use cokra_protocol::CollabSummaryCheckpointEvent;
 use cokra_protocol::CollabTaskUpdatedEvent;
 use cokra_protocol::CollabTeamSnapshotEvent;
@@ -27,13 +30,16 @@ use cokra_protocol::CollabWaitingEndEvent;
 use cokra_protocol::OwnershipAccessMode;
 use cokra_protocol::OwnershipLease;
 use cokra_protocol::ScopeRequest;
+use cokra_protocol::TeamBudget;
 use cokra_protocol::TeamMember;
+use cokra_protocol::TeamMemberUsage;
 use cokra_protocol::TeamMessage;
 use cokra_protocol::TeamSnapshot;
 use cokra_protocol::TeamTaskReadyState;
 use cokra_protocol::TeamTaskReviewState;
 use cokra_protocol::TeamTaskStatus;
 
+use crate::bottom_pane::footer::format_tokens_compact;
 use crate::history_cell::CollabWaitStatusTreeCell;
 use crate::history_cell::CollabWaitStatusTreeEntry;
 use crate::history_cell::PlainHistoryCell;
@@ -42,6 +48,8 @@ use crate::terminal_palette::light_blue;
 const PROMPT_PREVIEW_CHARS: usize = 120;
 const STATUS_PREVIEW_CHARS: usize = 160;
 const TASK_PREVIEW_CHARS: usize = 96;
+/// Busy teammates silent for longer than this get a "quiet" marker.
+const QUIET_AFTER_SECS: i64 = 60;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct WaitingPreview {
@@ -138,9 +146,12 @@ pub(crate) fn working_summary_snapshot(snapshot: &TeamSnapshot) -> Option<Collab
       )
     })
     .collect::<Vec<_>>();
-  let has_errored_members = members
-    .iter()
-    .any(|member| matches!(member.state.lifecycle.clone(), CollabAgentLifecycle::Error));
+  let has_errored_members = members.iter().any(|member| {
+    matches!(
+      member.state.lifecycle.clone(),
+      CollabAgentLifecycle::Error | CollabAgentLifecycle::Blocked
+    )
+  });
 
   let phase = if has_errored_members {
     CollabSummaryPhase::Attention
@@ -529,6 +540,41 @@ pub(crate) fn close_end(ev: CollabCloseEndEvent) -> PlainHistoryCell {
   )
 }
 
+pub(crate) fn resume_end(ev: CollabResumeEndEvent) -> PlainHistoryCell {
+  let agent = AgentLabel {
+    thread_id: &ev.receiver_thread_id,
+    nickname: ev.receiver_nickname.as_deref(),
+    role: ev.receiver_role.as_deref(),
+  };
+  match ev.error {
+    Some(error) => collab_event(
+      title_with_agent("Could not resume", agent),
+      vec![Line::from(error).red()],
+    ),
+    None => collab_event(
+      title_with_agent("Resumed", agent),
+      vec![status_summary_line(&CollabAgentWaitState {
+        lifecycle: ev.lifecycle,
+        ..Default::default()
+      })],
+    ),
+  }
+}
+
+/// Startup notice for teammates a previous session left running.
+pub(crate) fn resumable_team(agents: &[CollabAgentRef]) -> PlainHistoryCell {
+  collab_event(
+    title_text(format!(
+      "{} teammate(s) from the previous session can be resumed with /resume",
+      agents.len()
+    )),
+    agents
+      .iter()
+      .map(|agent| agent_label_line(agent_label_from_ref(agent)))
+      .collect(),
+  )
+}
+
 pub(crate) fn mailbox_delivered(
   ev: CollabMailboxDeliveredEvent,
 ) -> crate::history_cell::PeerMailboxHistoryCell {
@@ -716,6 +762,8 @@ pub(crate) fn team_dashboard_sections(snapshot: &TeamSnapshot) -> TeamDashboardS
 
   if teammate_count == 0 {
     summary.push(Line::from("No teammates yet".dim()));
+  } else if let Some(usage) = usage_summary(&snapshot.team_usage, snapshot.team_budget.as_ref()) {
+    summary.push(Line::from(format!("Team usage: {usage}")));
   }
 
   if !unread_members.is_empty() {
@@ -988,6 +1036,7 @@ fn member_activity_summary(snapshot: &TeamSnapshot, member: &TeamMember) -> Stri
     CollabAgentLifecycle::Ready => "idle".to_string(),
     CollabAgentLifecycle::Busy => "working".to_string(),
     CollabAgentLifecycle::Error => "attention needed".to_string(),
+    CollabAgentLifecycle::Blocked => "budget exhausted".to_string(),
     CollabAgentLifecycle::Shutdown => "closed".to_string(),
     CollabAgentLifecycle::NotFound => "unavailable".to_string(),
   }
@@ -1310,6 +1359,7 @@ fn status_summary_spans(state: &CollabAgentWaitState) -> Vec<Span<'static>> {
     CollabAgentLifecycle::Ready => vec![Span::from("Idle").green()],
     CollabAgentLifecycle::Busy => vec![Span::from("Busy").yellow().bold()],
     CollabAgentLifecycle::Error => vec![Span::from("Errored").red()],
+    CollabAgentLifecycle::Blocked => vec![Span::from("Blocked").magenta()],
     CollabAgentLifecycle::Shutdown => vec![Span::from("Closed").dim()],
     CollabAgentLifecycle::NotFound => vec![Span::from("Not found").red()],
   };
@@ -1348,9 +1398,25 @@ fn member_dashboard_line(snapshot: &TeamSnapshot, member: &TeamMember) -> Line<'
     CollabAgentLifecycle::Ready => Span::from("Idle").green(),
     CollabAgentLifecycle::Busy => Span::from("Busy").yellow().bold(),
     CollabAgentLifecycle::Error => Span::from("Errored").red(),
+    CollabAgentLifecycle::Blocked => Span::from("Blocked").magenta(),
     CollabAgentLifecycle::Shutdown => Span::from("Closed").dim(),
     CollabAgentLifecycle::NotFound => Span::from("Not found").red(),
   });
+  if let Some(model) = member.model.as_deref() {
+    spans.push(Span::from(" · ").dim());
+    spans.push(Span::from(model.to_string()).dim());
+  }
+  if let Some(usage) = usage_summary(&member.usage, member.usage.budget.as_ref()) {
+    spans.push(Span::from(" · ").dim());
+    spans.push(Span::from(usage).dim());
+  }
+  let now = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map_or(0, |elapsed| elapsed.as_secs() as i64);
+  if let Some(quiet) = quiet_summary(member, now) {
+    spans.push(Span::from(" · ").dim());
+    spans.push(Span::from(quiet).yellow());
+  }
 
   let activity = member_activity_summary(snapshot, member);
   let fallback = match member.state.lifecycle.clone() {
@@ -1358,6 +1424,7 @@ fn member_dashboard_line(snapshot: &TeamSnapshot, member: &TeamMember) -> Line<'
     CollabAgentLifecycle::Ready => "idle",
     CollabAgentLifecycle::Busy => "working",
     CollabAgentLifecycle::Error => "attention needed",
+    CollabAgentLifecycle::Blocked => "budget exhausted",
     CollabAgentLifecycle::Shutdown => "closed",
     CollabAgentLifecycle::NotFound => "unavailable",
   };
@@ -1369,6 +1436,43 @@ fn member_dashboard_line(snapshot: &TeamSnapshot, member: &TeamMember) -> Line<'
   Line::from(spans)
 }
 
+/// "quiet 4m" for a busy member that has emitted nothing for a while.
+fn quiet_summary(member: &TeamMember, now: i64) -> Option<String> {
+  if !matches!(member.state.lifecycle, CollabAgentLifecycle::Busy) {
+    return None;
+  }
+  let quiet_secs = now - member.liveness.as_ref()?.last_event_at;
+  (quiet_secs >= QUIET_AFTER_SECS).then(|| format!("quiet {}m", quiet_secs / 60))
+}
+
+/// "12.3k tok · $0.42 · 3/5 turns"; limits are shown as "used/max" when set.
+fn usage_summary(usage: &TeamMemberUsage, budget: Option<&TeamBudget>) -> Option<String> {
+  let budget = budget.cloned().unwrap_or_default();
+  if usage.total_tokens == 0 && usage.cost_usd == 0.0 && budget.is_unlimited() {
+    return None;
+  }
+  let mut tokens = format_tokens_compact(usage.total_tokens);
+  if let Some(max) = budget.max_tokens {
+    tokens.push_str(&format!("/{}", format_tokens_compact(max as i64)));
+  }
+  let mut parts = vec![format!("{tokens} tok")];
+  match budget.max_cost_usd {
+    Some(max) => parts.push(format!("${:.2}/${max:.2}", usage.cost_usd)),
+    None if usage.cost_usd > 0.0 => parts.push(format!("${:.2}", usage.cost_usd)),
+    None => {}
+  }
+  if let Some(max) = budget.max_turns {
+    parts.push(format!("{}/{max} turns", usage.turns));
+  }
+  if let Some(max) = budget.max_tool_calls {
+    parts.push(format!("{}/{max} tool calls", usage.tool_calls));
+  }
+  if let Some(max) = budget.max_wall_clock_secs {
+    parts.push(format!("{}/{max}s", usage.elapsed_secs));
+  }
+  Some(parts.join(" · "))
+}
+
 fn team_member_label_spans(member: &TeamMember, root_thread_id: &str) -> Vec<Span<'static>> {
   if member.thread_id == root_thread_id {
     return vec![Span::from("@main").style(light_blue()).bold()];
@@ -1557,6 +1661,7 @@ mod tests {
   use cokra_protocol::CollabTeamSnapshotEvent;
   use cokra_protocol::CollabTurnOutcome;
   use cokra_protocol::CollabWaitingEndEvent;
+  use cokra_protocol::TeamMemberLiveness;
   use cokra_protocol::TeamPlan;
   use cokra_protocol::TeamPlanStatus;
   use cokra_protocol::TeamSnapshot;
@@ -1611,7 +1716,10 @@ mod tests {
           role: "root".to_string(),
           task: "root session".to_string(),
           depth: 0,
+          model: None,
           state: member_state(CollabAgentLifecycle::Busy, CollabTurnOutcome::NoneYet, None),
+          usage: Default::default(),
+          liveness: None,
         },
         TeamMember {
           thread_id: "alpha-thread".to_string(),
@@ -1619,11 +1727,14 @@ mod tests {
           role: "codex".to_string(),
           task: "implement a".to_string(),
           depth: 1,
+          model: None,
           state: member_state(
             CollabAgentLifecycle::Ready,
             CollabTurnOutcome::Succeeded,
             None,
           ),
+          usage: Default::default(),
+          liveness: None,
         },
         TeamMember {
           thread_id: "beta-thread".to_string(),
@@ -1631,11 +1742,14 @@ mod tests {
           role: "codex".to_string(),
           task: "implement b".to_string(),
           depth: 1,
+          model: None,
           state: member_state(
             CollabAgentLifecycle::Ready,
             CollabTurnOutcome::Succeeded,
             None,
           ),
+          usage: Default::default(),
+          liveness: None,
         },
       ],
       tasks: vec![
@@ -1701,6 +1815,9 @@ mod tests {
       recent_messages: Vec::new(),
       ownership_leases: Vec::new(),
       workflow: None,
+      team_usage: Default::default(),
+      team_budget: None,
+      docs: Vec::new(),
     };
 
     let lines = working_summary_lines(&snapshot).expect("summary");
@@ -1732,7 +1849,10 @@ mod tests {
           role: "root".to_string(),
           task: "root session".to_string(),
           depth: 0,
+          model: None,
           state: member_state(CollabAgentLifecycle::Busy, CollabTurnOutcome::NoneYet, None),
+          usage: Default::default(),
+          liveness: None,
         },
         TeamMember {
           thread_id: "alpha-thread".to_string(),
@@ -1740,11 +1860,14 @@ mod tests {
           role: "codex".to_string(),
           task: "waiting".to_string(),
           depth: 1,
+          model: None,
           state: member_state(
             CollabAgentLifecycle::Ready,
             CollabTurnOutcome::Succeeded,
             None,
           ),
+          usage: Default::default(),
+          liveness: None,
         },
       ],
       tasks: vec![TeamTask {
@@ -1781,6 +1904,9 @@ mod tests {
       recent_messages: Vec::new(),
       ownership_leases: Vec::new(),
       workflow: None,
+      team_usage: Default::default(),
+      team_budget: None,
+      docs: Vec::new(),
     };
 
     let lines = working_summary_lines(&snapshot).expect("summary");
@@ -1811,7 +1937,10 @@ mod tests {
           role: "root".to_string(),
           task: "root session".to_string(),
           depth: 0,
+          model: None,
           state: member_state(CollabAgentLifecycle::Busy, CollabTurnOutcome::NoneYet, None),
+          usage: Default::default(),
+          liveness: None,
         },
         TeamMember {
           thread_id: "alpha-thread".to_string(),
@@ -1819,11 +1948,14 @@ mod tests {
           role: "codex".to_string(),
           task: "waiting".to_string(),
           depth: 1,
+          model: None,
           state: member_state(
             CollabAgentLifecycle::Ready,
             CollabTurnOutcome::Succeeded,
             None,
           ),
+          usage: Default::default(),
+          liveness: None,
         },
       ],
       tasks: vec![TeamTask {
@@ -1855,6 +1987,9 @@ mod tests {
       recent_messages: Vec::new(),
       ownership_leases: Vec::new(),
       workflow: None,
+      team_usage: Default::default(),
+      team_budget: None,
+      docs: Vec::new(),
     };
 
     let lines = working_summary_lines(&snapshot).expect("summary");
@@ -1886,7 +2021,10 @@ mod tests {
           role: "root".to_string(),
           task: "root session".to_string(),
           depth: 0,
+          model: None,
           state: member_state(CollabAgentLifecycle::Busy, CollabTurnOutcome::NoneYet, None),
+          usage: Default::default(),
+          liveness: None,
         },
         TeamMember {
           thread_id: "alpha-thread".to_string(),
@@ -1894,6 +2032,7 @@ mod tests {
           role: "codex".to_string(),
           task: "follow up".to_string(),
           depth: 1,
+          model: None,
           state: CollabAgentWaitState {
             pending_wake_count: 1,
             ..member_state(
@@ -1902,6 +2041,8 @@ mod tests {
               Some("finished first pass"),
             )
           },
+          usage: Default::default(),
+          liveness: None,
         },
       ],
       tasks: Vec::new(),
@@ -1912,6 +2053,9 @@ mod tests {
       recent_messages: Vec::new(),
       ownership_leases: Vec::new(),
       workflow: None,
+      team_usage: Default::default(),
+      team_budget: None,
+      docs: Vec::new(),
     };
     snapshot.members[0].state = member_state(
       CollabAgentLifecycle::Ready,
@@ -1936,6 +2080,123 @@ mod tests {
     assert!(!rendered.contains("Agent Teams Done"));
   }
 
+  #[test]
+  fn dashboard_shows_budget_usage_for_blocked_teammates() {
+    let budget = TeamBudget {
+      max_tokens: Some(10_000),
+      max_turns: Some(3),
+      ..Default::default()
+    };
+    let usage = TeamMemberUsage {
+      total_tokens: 1_500,
+      cost_usd: 0.2,
+      turns: 3,
+      budget: Some(budget),
+      ..Default::default()
+    };
+    let snapshot = TeamSnapshot {
+      root_thread_id: "root-thread".to_string(),
+      members: vec![
+        TeamMember {
+          thread_id: "root-thread".to_string(),
+          nickname: None,
+          role: "root".to_string(),
+          task: "root session".to_string(),
+          depth: 0,
+          model: None,
+          state: member_state(
+            CollabAgentLifecycle::Ready,
+            CollabTurnOutcome::NoneYet,
+            None,
+          ),
+          usage: Default::default(),
+          liveness: None,
+        },
+        TeamMember {
+          thread_id: "alpha-thread".to_string(),
+          nickname: Some("alpha".to_string()),
+          role: "codex".to_string(),
+          task: "explore".to_string(),
+          depth: 1,
+          model: None,
+          state: CollabAgentWaitState {
+            attention_reason: Some("budget exhausted: turn budget of 3 reached".to_string()),
+            ..member_state(
+              CollabAgentLifecycle::Blocked,
+              CollabTurnOutcome::Succeeded,
+              None,
+            )
+          },
+          usage: usage.clone(),
+          liveness: None,
+        },
+      ],
+      tasks: Vec::new(),
+      task_edges: Vec::new(),
+      plans: Vec::new(),
+      unread_counts: HashMap::new(),
+      mailbox_version: 0,
+      recent_messages: Vec::new(),
+      ownership_leases: Vec::new(),
+      workflow: None,
+      team_usage: TeamMemberUsage {
+        budget: None,
+        ..usage
+      },
+      team_budget: Some(TeamBudget {
+        max_cost_usd: Some(5.0),
+        ..Default::default()
+      }),
+      docs: Vec::new(),
+    };
+
+    let rendered = team_dashboard_sections(&snapshot)
+      .summary
+      .iter()
+      .map(|line| {
+        line
+          .spans
+          .iter()
+          .map(|span| span.content.as_ref())
+          .collect::<String>()
+      })
+      .collect::<Vec<_>>();
+
+    assert!(rendered.iter().any(|line| {
+      line.contains("@alpha")
+        && line
+          .contains("Blocked · 1.5k/10.0k tok · $0.20 · 3/3 turns · attention: budget exhausted")
+    }));
+    assert!(
+      rendered
+        .iter()
+        .any(|line| line == "Team usage: 1.5k tok · $0.20/$5.00")
+    );
+  }
+
+  #[test]
+  fn quiet_summary_marks_only_long_silent_busy_members() {
+    let mut member = TeamMember {
+      thread_id: "alpha-thread".to_string(),
+      nickname: Some("alpha".to_string()),
+      role: "codex".to_string(),
+      task: "explore".to_string(),
+      depth: 1,
+      model: None,
+      state: member_state(CollabAgentLifecycle::Busy, CollabTurnOutcome::NoneYet, None),
+      usage: Default::default(),
+      liveness: Some(TeamMemberLiveness {
+        last_event_at: 1_000,
+        last_tool_progress_at: None,
+      }),
+    };
+
+    assert_eq!(quiet_summary(&member, 1_030), None);
+    assert_eq!(quiet_summary(&member, 1_250), Some("quiet 4m".to_string()));
+    member.state.lifecycle = CollabAgentLifecycle::Ready;
+    assert_eq!(quiet_summary(&member, 1_250), None);
+  }
+
   #[test]
   fn team_snapshot_renders_compact_summary_card() {
     let cell = team_snapshot(CollabTeamSnapshotEvent {
@@ -1949,7 +2210,10 @@ mod tests {
             role: "root".to_string(),
             task: "root session".to_string(),
             depth: 0,
+            model: None,
             state: member_state(CollabAgentLifecycle::Busy, CollabTurnOutcome::NoneYet, None),
+            usage: Default::default(),
+            liveness: None,
           },
           TeamMember {
             thread_id: "ash-thread".to_string(),
@@ -1957,7 +2221,10 @@ mod tests {
             role: "default".to_string(),
             task: "你是团队成员“艾许”。请从架构角度继续深入分析。".to_string(),
             depth: 1,
+            model: None,
             state: member_state(CollabAgentLifecycle::Busy, CollabTurnOutcome::NoneYet, None),
+            usage: Default::default(),
+            liveness: None,
           },
           TeamMember {
             thread_id: "sparrow-thread".to_string(),
@@ -1965,11 +2232,14 @@ mod tests {
             role: "default".to_string(),
             task: "你是团队成员“六雀”。请从测试工具链角度继续深入分析。".to_string(),
             depth: 1,
+            model: None,
             state: member_state(
               CollabAgentLifecycle::Ready,
               CollabTurnOutcome::Succeeded,
               Some("Research summary completed"),
             ),
+            usage: Default::default(),
+            liveness: None,
           },
         ],
         tasks: vec![
@@ -2071,6 +2341,9 @@ mod tests {
           expires_at: None,
         }],
         workflow: None,
+        team_usage: Default::default(),
+        team_budget: None,
+        docs: Vec::new(),
       },
     });
 
@@ -2158,6 +2431,39 @@ mod tests {
     assert!(!rendered.contains("sparrow-thread"));
   }
 
+  #[test]
+  fn resume_end_reports_agent_and_failure() {
+    let render = |cell: PlainHistoryCell| {
+      cell
+        .lines
+        .iter()
+        .map(|line| {
+          line
+            .spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+    };
+    let event = |error: Option<&str>| CollabResumeEndEvent {
+      call_id: "call-1".to_string(),
+      sender_thread_id: "root-thread".to_string(),
+      receiver_thread_id: "sparrow-thread".to_string(),
+      receiver_nickname: Some("六雀".to_string()),
+      receiver_role: Some("default".to_string()),
+      error: error.map(ToString::to_string),
+      lifecycle: CollabAgentLifecycle::Busy,
+    };
+
+    let resumed = render(resume_end(event(None)));
+    assert!(resumed.contains("Resumed @六雀"));
+    let failed = render(resume_end(event(Some("thread manager dropped"))));
+    assert!(failed.contains("Could not resume @六雀"));
+    assert!(failed.contains("thread manager dropped"));
+  }
+
   #[test]
   fn mailbox_delivered_uses_simple_sender_prompt_label() {
     let cell = mailbox_delivered(CollabMailboxDeliveredEvent {
@@ -2205,11 +2511,14 @@ mod tests {
           role: "root".to_string(),
           task: "root session".to_string(),
           depth: 0,
+          model: None,
           state: member_state(
             CollabAgentLifecycle::Ready,
             CollabTurnOutcome::Succeeded,
             None,
           ),
+          usage: Default::default(),
+          liveness: None,
         }],
         tasks: vec![TeamTask {
           id: "task-1".to_string(),
@@ -2248,6 +2557,9 @@ mod tests {
         recent_messages: Vec::new(),
         ownership_leases: Vec::new(),
         workflow: None,
+        team_usage: Default::default(),
+        team_budget: None,
+        docs: Vec::new(),
       },
     });
 
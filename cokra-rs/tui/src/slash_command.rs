@@ -19,6 +19,7 @@ pub(crate) enum SlashCommand {
   Plan,
   Collab,
   Agent,
+  Workflow,
   Diff,
   Mention,
   Status,
@@ -59,6 +60,7 @@ impl SlashCommand {
       SlashCommand::Plan => "plan",
       SlashCommand::Collab => "collab",
       SlashCommand::Agent => "agent",
+      SlashCommand::Workflow => "workflow",
       SlashCommand::Diff => "diff",
       SlashCommand::Mention => "mention",
       SlashCommand::Status => "status",
@@ -89,7 +91,7 @@ impl SlashCommand {
       SlashCommand::Compact => "summarize conversation to prevent hitting the context limit",
       SlashCommand::Review => "review my current changes and find issues",
       SlashCommand::Rename => "rename the current thread",
-      SlashCommand::Resume => "resume a saved chat",
+      SlashCommand::Resume => "resume a saved chat or an interrupted agent team",
       SlashCommand::Fork => "fork the current chat",
       SlashCommand::Quit | SlashCommand::Exit => "exit cokra",
       SlashCommand::Diff => "show git diff (including untracked files)",
@@ -107,6 +109,7 @@ impl SlashCommand {
       SlashCommand::Plan => "switch to Plan mode",
       SlashCommand::Collab => "change collaboration mode",
       SlashCommand::Agent => "switch the active agent thread",
+      SlashCommand::Workflow => "run a team workflow template: /workflow run <name> [goal]",
       SlashCommand::Approvals => "choose what cokra is allowed to do",
       SlashCommand::Permissions => "choose what cokra is allowed to do",
       SlashCommand::ElevateSandbox => "set up elevated agent sandbox",
@@ -114,7 +117,7 @@ impl SlashCommand {
         "let sandbox read a directory: /sandbox-add-read-dir <absolute_path>"
       }
       SlashCommand::Experimental => "toggle experimental features",
-      SlashCommand::Mcp => "list configured MCP tools",
+      SlashCommand::Mcp => "show MCP server status and restart servers",
       SlashCommand::Apps => "manage apps",
       SlashCommand::Logout => "log out of cokra",
       SlashCommand::Rollout => "print the rollout file path",
@@ -130,6 +133,7 @@ impl SlashCommand {
         | SlashCommand::Rename
         | SlashCommand::Plan
         | SlashCommand::SandboxReadRoot
+        | SlashCommand::Workflow
     )
   }
 
@@ -150,6 +154,7 @@ impl SlashCommand {
       | SlashCommand::Experimental
       | SlashCommand::Review
       | SlashCommand::Plan
+      | SlashCommand::Workflow
       | SlashCommand::Logout
       | SlashCommand::MemoryDrop
       | SlashCommand::MemoryUpdate
@@ -201,6 +206,7 @@ const ALL_SLASH_COMMANDS: &[SlashCommand] = &[
   SlashCommand::Plan,
   SlashCommand::Collab,
   SlashCommand::Agent,
+  SlashCommand::Workflow,
   SlashCommand::Diff,
   SlashCommand::Mention,
   SlashCommand::Status,
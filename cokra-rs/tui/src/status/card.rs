@@ -24,6 +24,10 @@ pub(crate) struct StatusCardData {
   pub(crate) input_tokens: i64,
   pub(crate) output_tokens: i64,
   pub(crate) total_tokens: i64,
+  /// Session-wide spend in USD; `None` when no request had catalog pricing.
+  pub(crate) session_cost_usd: Option<f64>,
+  /// Spend per team member as `(label, usd)`; empty outside agent teams.
+  pub(crate) agent_costs: Vec<(String, f64)>,
   pub(crate) collaboration_mode: Option<String>,
   pub(crate) agents_count: Option<usize>,
 }
@@ -55,6 +59,14 @@ fn format_tokens_compact(tokens: i64) -> String {
   }
 }
 
+fn format_cost_usd(cost_usd: f64) -> String {
+  if cost_usd > 0.0 && cost_usd < 0.01 {
+    "<$0.01".to_string()
+  } else {
+    format!("${cost_usd:.2}")
+  }
+}
+
 fn format_directory_display(path: &PathBuf) -> String {
   // Try to show ~ for home directory
   if let Some(home) = dirs::home_dir() {
@@ -94,6 +106,12 @@ impl HistoryCell for StatusHistoryCell {
     }
     labels.push("Task running");
     labels.push("Token usage");
+    if d.session_cost_usd.is_some() {
+      labels.push("Cost");
+    }
+    if !d.agent_costs.is_empty() {
+      labels.push("Spend by agent");
+    }
 
     let formatter = FieldFormatter::from_labels(labels.iter().copied());
 
@@ -148,6 +166,25 @@ impl HistoryCell for StatusHistoryCell {
       ],
     ));
 
+    if let Some(cost_usd) = d.session_cost_usd {
+      lines.push(formatter.line(
+        "Cost",
+        vec![Span::from(format!("{} total", format_cost_usd(cost_usd)))],
+      ));
+    }
+
+    if !d.agent_costs.is_empty() {
+      let mut spans = Vec::new();
+      for (index, (label, cost_usd)) in d.agent_costs.iter().enumerate() {
+        if index > 0 {
+          spans.push(Span::from(" · ").dim());
+        }
+        spans.push(Span::from(format!("{label} ")));
+        spans.push(Span::from(format_cost_usd(*cost_usd)).dim());
+      }
+      lines.push(formatter.line("Spend by agent", spans));
+    }
+
     // Truncate and border
     let content_width = lines.iter().map(line_display_width).max().unwrap_or(0);
     let inner_width = content_width.min(available_inner_width);
@@ -173,6 +210,8 @@ mod tests {
       input_tokens: 299_000,
       output_tokens: 17_400,
       total_tokens: 317_000,
+      session_cost_usd: None,
+      agent_costs: Vec::new(),
       collaboration_mode: None,
       agents_count: None,
     }
@@ -237,6 +276,37 @@ mod tests {
     );
   }
 
+  #[test]
+  fn status_card_includes_cost_and_agent_spend_when_present() {
+    let mut data = sample_data();
+    data.session_cost_usd = Some(1.234);
+    data.agent_costs = vec![("@main".to_string(), 0.4), ("@alice".to_string(), 0.004)];
+    let cell = StatusHistoryCell::new(data);
+    let lines = cell.display_lines(100);
+    let rendered: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+    assert!(
+      rendered.iter().any(|l| l.contains("$1.23 total")),
+      "should contain session cost: {rendered:?}"
+    );
+    assert!(
+      rendered
+        .iter()
+        .any(|l| l.contains("@main $0.40") && l.contains("@alice <$0.01")),
+      "should contain per-agent spend: {rendered:?}"
+    );
+  }
+
+  #[test]
+  fn status_card_omits_cost_without_pricing() {
+    let cell = StatusHistoryCell::new(sample_data());
+    let lines = cell.display_lines(80);
+    let rendered: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+    assert!(
+      !rendered.iter().any(|l| l.contains("Cost")),
+      "cost row should be hidden: {rendered:?}"
+    );
+  }
+
   #[test]
   fn format_tokens_compact_covers_ranges() {
     assert_eq!(format_tokens_compact(500), "500");
@@ -156,6 +156,7 @@ mod tests {
       role: "general".to_string(),
       task: "work".to_string(),
       depth: 1,
+      model: None,
       state: CollabAgentWaitState {
         lifecycle: CollabAgentLifecycle::Ready,
         turn_outcome: CollabTurnOutcome::Succeeded,
@@ -163,6 +164,8 @@ mod tests {
         attention_reason: None,
         pending_wake_count: 0,
       },
+      usage: Default::default(),
+      liveness: None,
     }
   }
 
@@ -206,6 +209,7 @@ mod tests {
           role: "root".to_string(),
           task: "lead".to_string(),
           depth: 0,
+          model: None,
           state: CollabAgentWaitState {
             lifecycle: CollabAgentLifecycle::Ready,
             turn_outcome: CollabTurnOutcome::Succeeded,
@@ -213,6 +217,8 @@ mod tests {
             attention_reason: None,
             pending_wake_count: 0,
           },
+          usage: Default::default(),
+          liveness: None,
         },
         member("alpha-thread", Some("alpha")),
       ],
@@ -240,6 +246,9 @@ mod tests {
       recent_messages: Vec::new(),
       ownership_leases: Vec::new(),
       workflow: None,
+      team_usage: Default::default(),
+      team_budget: None,
+      docs: Vec::new(),
     }
   }
 
@@ -228,15 +228,18 @@ impl ThreadTranscriptStore {
       EventMsg::CollabWaitingBegin(event) => {
         self.push_plain_history_cell(multi_agents::waiting_begin(event.clone()))
       }
-      EventMsg::CollabWaitingEnd(event) => self.push_history_cell(multi_agents::waiting_end(
-        event.clone(),
-      )),
+      EventMsg::CollabWaitingEnd(event) => {
+        self.push_history_cell(multi_agents::waiting_end(event.clone()))
+      }
       EventMsg::CollabCloseEnd(event) => {
         self.push_plain_history_cell(multi_agents::close_end(event.clone()))
       }
-      EventMsg::CollabMailboxDelivered(event) => self.push_history_cell(
-        multi_agents::mailbox_delivered(event.clone()),
-      ),
+      EventMsg::CollabResumeEnd(event) => {
+        self.push_plain_history_cell(multi_agents::resume_end(event.clone()))
+      }
+      EventMsg::CollabMailboxDelivered(event) => {
+        self.push_history_cell(multi_agents::mailbox_delivered(event.clone()))
+      }
       EventMsg::CollabMessagesRead(event) => {
         self.push_plain_history_cell(multi_agents::messages_read(event.clone()))
       }